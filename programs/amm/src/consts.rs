@@ -14,8 +14,44 @@ pub const MAX_SWAP_FEE: Permillion = Permillion {
     permillion: 1_0000,
 };
 
+/// Increases of the swap fee done with
+/// [`crate::endpoints::set_pool_swap_fee`] only take effect after the pool's
+/// [`crate::models::Pool::swap_fee_timelock_slots`]. New pools start with
+/// this many slots, which is roughly a day.
+pub const DEFAULT_SWAP_FEE_TIMELOCK_SLOTS: u64 = 216_000;
+
+/// The admin can configure the pool's swap fee timelock with
+/// [`crate::endpoints::set_pool_swap_fee_timelock`] to at least this many
+/// slots, roughly an hour, so that the liquidity providers and traders always
+/// have some time to react to a fee increase.
+pub const MIN_SWAP_FEE_TIMELOCK_SLOTS: u64 = 9_000;
+
+/// And at most this many slots, roughly a week.
+pub const MAX_SWAP_FEE_TIMELOCK_SLOTS: u64 = 1_512_000;
+
 /// The program owner gets a share of the swap fee defined by this value.
 pub const PROGRAM_TOLL_SWAP_FEE_SHARE: Permillion = Permillion {
     // 1/3
     permillion: 33_3333,
 };
+
+/// A pool's [`crate::models::ReferralConfig::share`] of the program toll
+/// cannot be larger than this, so that the program toll authority always
+/// keeps a part of the toll.
+pub const MAX_REFERRAL_SHARE: Permillion = Permillion {
+    // 50%
+    permillion: 50_0000,
+};
+
+/// The slippage for [`crate::endpoints::swap::handle_with_slippage_bps`] is
+/// given in basis points, ie. 10_000 bps equals 100%.
+pub const MAX_SLIPPAGE_BPS: u64 = 10_000;
+
+/// The [`crate::endpoints::sweep_dust`] endpoint only sweeps wallets which
+/// hold less than one whole token divided by this number, ie. less than
+/// `10^decimals / MAX_DUST_DENOMINATOR` of the mint's smallest units. For a
+/// mint with 6 decimals that's 10_000.
+pub const MAX_DUST_DENOMINATOR: u64 = 100;
+
+/// The [`crate::models::MintBlocklist`] account has space for this many mints.
+pub const MAX_BLOCKED_MINTS: usize = 32;
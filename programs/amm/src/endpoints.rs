@@ -1,17 +1,81 @@
+pub mod batch_swap;
+pub mod burn_lp_to_reserves;
+pub mod cancel_pending_swap_fee;
 pub mod create_discount_settings;
 pub mod create_pool;
+pub mod create_pool_lp_mint_and_toll_wallet;
+pub mod create_pool_mirror;
+pub mod create_pool_referral_config;
+pub mod create_pool_with_nonce;
 pub mod create_program_toll;
 pub mod deposit_liquidity;
+pub mod deposit_liquidity_and_start_farming;
+pub mod exit_farm_and_redeem_liquidity;
+pub mod get_curve_invariant;
+pub mod get_fee_schedule;
+pub mod get_lp_value;
+pub mod get_pool_layout;
+pub mod get_pool_signer;
+pub mod migrate_pool;
+pub mod put_curve_constraints;
 pub mod put_discount;
+pub mod put_mint_blocklist;
+pub mod put_pool_fee_rebate;
+pub mod quote_deposit_liquidity;
+pub mod quote_redeem_liquidity;
+pub mod quote_single_deposit;
+pub mod quote_swap;
 pub mod redeem_liquidity;
+pub mod redeem_program_toll_into_reserve;
+pub mod refresh_pool_mirror;
+pub mod reinvest_program_toll;
+pub mod set_pool_decimal_scales;
+pub mod set_pool_max_lp_supply;
+pub mod set_pool_program_toll_wallet;
+pub mod set_pool_referral_default_recipient;
 pub mod set_pool_swap_fee;
+pub mod set_pool_swap_fee_timelock;
+pub mod set_pool_weights;
 pub mod swap;
+pub mod sweep_dust;
 
+pub use batch_swap::*;
+pub use burn_lp_to_reserves::*;
+pub use cancel_pending_swap_fee::*;
 pub use create_discount_settings::*;
 pub use create_pool::*;
+pub use create_pool_lp_mint_and_toll_wallet::*;
+pub use create_pool_mirror::*;
+pub use create_pool_referral_config::*;
+pub use create_pool_with_nonce::*;
 pub use create_program_toll::*;
 pub use deposit_liquidity::*;
+pub use deposit_liquidity_and_start_farming::*;
+pub use exit_farm_and_redeem_liquidity::*;
+pub use get_curve_invariant::*;
+pub use get_fee_schedule::*;
+pub use get_lp_value::*;
+pub use get_pool_layout::*;
+pub use get_pool_signer::*;
+pub use migrate_pool::*;
+pub use put_curve_constraints::*;
 pub use put_discount::*;
+pub use put_mint_blocklist::*;
+pub use put_pool_fee_rebate::*;
+pub use quote_deposit_liquidity::*;
+pub use quote_redeem_liquidity::*;
+pub use quote_single_deposit::*;
+pub use quote_swap::*;
 pub use redeem_liquidity::*;
+pub use redeem_program_toll_into_reserve::*;
+pub use refresh_pool_mirror::*;
+pub use reinvest_program_toll::*;
+pub use set_pool_decimal_scales::*;
+pub use set_pool_max_lp_supply::*;
+pub use set_pool_program_toll_wallet::*;
+pub use set_pool_referral_default_recipient::*;
 pub use set_pool_swap_fee::*;
+pub use set_pool_swap_fee_timelock::*;
+pub use set_pool_weights::*;
 pub use swap::*;
+pub use sweep_dust::*;
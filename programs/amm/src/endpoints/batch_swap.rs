@@ -0,0 +1,141 @@
+//! Performs several independent swaps in a single instruction, e.g. for an
+//! arbitrage bot which executes a cycle across several pools. Unlike a
+//! multi-hop route, the output of one swap is not the input of the next, each
+//! swap has its own sell amount and minimum buy amount.
+//!
+//! If any of the swaps fails, e.g. because its slippage is exceeded, the whole
+//! instruction fails and none of the swaps take place.
+//!
+//! # Remaining accounts
+//! One group per swap, in the same order as the swap arguments. Each group
+//! consists of the accounts of [`crate::endpoints::swap`] in the same order as
+//! in [`Swap`], followed by the pool's fee rebate PDA, which doesn't have to be
+//! initialized.
+//!
+//! ```text
+//! [
+//!   user,
+//!   discount1,
+//!   pool1,
+//!   pool_signer1,
+//!   sell_wallet1,
+//!   buy_wallet1,
+//!   sell_vault1,
+//!   buy_vault1,
+//!   lp_mint1,
+//!   program_toll_wallet1,
+//!   token_program,
+//!   fee_rebate1,
+//!   user,
+//!   discount2,
+//!   ...
+//! ]
+//! ```
+//!
+//! Each swap must be on a different pool.
+
+use crate::*;
+use std::collections::{BTreeMap, BTreeSet};
+use std::slice;
+
+/// How many remaining accounts form a single group.
+const GROUP_LEN: usize = 12;
+
+#[derive(Accounts)]
+pub struct BatchSwap<'info> {
+    /// Must be the user of all the swaps.
+    pub user: Signer<'info>,
+}
+
+/// Arguments of a single swap in [`handle`].
+#[derive(
+    AnchorDeserialize,
+    AnchorSerialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+)]
+pub struct BatchSwapLeg {
+    pub sell: TokenAmount,
+    pub min_buy: TokenAmount,
+}
+
+pub fn handle<'info>(
+    ctx: Context<'_, '_, '_, 'info, BatchSwap<'info>>,
+    legs: Vec<BatchSwapLeg>,
+) -> Result<()> {
+    if legs.is_empty() {
+        return Err(error!(err::arg("Provide at least one swap")));
+    }
+
+    if ctx.remaining_accounts.len() != legs.len() * GROUP_LEN {
+        return Err(error!(err::acc(format!(
+            "Expected {} remaining accounts for {} swaps, but got {}",
+            legs.len() * GROUP_LEN,
+            legs.len(),
+            ctx.remaining_accounts.len()
+        ))));
+    }
+
+    // all groups are validated before any swap is executed
+    let mut pools = BTreeSet::new();
+    let mut swaps = Vec::with_capacity(legs.len());
+    let mut remaining_accounts = ctx.remaining_accounts;
+    for index in 0..legs.len() {
+        let mut bumps = BTreeMap::new();
+        let swap = Swap::try_accounts(
+            ctx.program_id,
+            &mut remaining_accounts,
+            &[],
+            &mut bumps,
+        )?;
+        // the length of remaining accounts was checked above
+        let (fee_rebate, rest) = remaining_accounts
+            .split_first()
+            .ok_or(AmmError::InvariantViolation)?;
+        remaining_accounts = rest;
+
+        if swap.user.key() != ctx.accounts.user.key() {
+            return Err(error!(err::acc(format!(
+                "User of swap #{} must be the signer",
+                index
+            ))));
+        }
+
+        // the pool is written only once all swaps are done, therefore two
+        // swaps on the same pool would overwrite each other
+        if !pools.insert(swap.pool.key()) {
+            return Err(error!(err::acc(format!(
+                "Pool of swap #{} is used by another swap",
+                index
+            ))));
+        }
+
+        swaps.push((swap, bumps, fee_rebate));
+    }
+
+    for (index, ((swap, bumps, fee_rebate), leg)) in
+        swaps.iter_mut().zip(&legs).enumerate()
+    {
+        msg!("Swap #{}", index);
+        endpoints::swap::handle(
+            Context::new(
+                ctx.program_id,
+                swap,
+                slice::from_ref(*fee_rebate),
+                bumps.clone(),
+            ),
+            leg.sell,
+            leg.min_buy,
+        )?;
+    }
+
+    for (swap, _, _) in &swaps {
+        swap.exit(ctx.program_id)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,77 @@
+//! An LP provider, eg. a treasury, can donate their position to the remaining
+//! liquidity providers of the pool. This is the inverse of
+//! [`crate::endpoints::redeem_liquidity`]: given amount of LP tokens is burned
+//! from the user's wallet, but no reserve tokens are transferred out of the
+//! pool. Therefore each remaining LP token is worth proportionally more of
+//! each reserve.
+//!
+//! See [`crate::endpoints::reinvest_program_toll`] for the same operation
+//! with the program toll.
+
+use crate::misc::print_lp_supply;
+use crate::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct BurnLpToReserves<'info> {
+    /// Authority over the LP token wallet.
+    pub user: Signer<'info>,
+    pub pool: Account<'info, Pool>,
+    #[account(
+        mut,
+        constraint = lp_mint.key() == pool.mint
+            @ err::acc("LP mint must match pool's mint"),
+    )]
+    pub lp_mint: Account<'info, Mint>,
+    /// LP tokens are burned FROM this wallet.
+    #[account(
+        mut,
+        constraint = lp_token_wallet.mint == pool.mint
+            @ err::acc("LP wallet must be of the same mint as pool's mint"),
+    )]
+    pub lp_token_wallet: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handle(
+    ctx: Context<BurnLpToReserves>,
+    lp_tokens_to_burn: TokenAmount,
+) -> Result<()> {
+    let accs = ctx.accounts;
+
+    if lp_tokens_to_burn.amount == 0 {
+        return Err(error!(err::arg("LP tokens to burn mustn't be zero")));
+    }
+
+    if lp_tokens_to_burn.amount > accs.lp_token_wallet.amount {
+        return Err(error!(AmmError::InvalidLpTokenAmount));
+    }
+
+    // if all LP tokens were burned, the reserves would be stuck in the pool
+    // forever
+    if lp_tokens_to_burn.amount >= accs.lp_mint.supply {
+        return Err(error!(err::arg(
+            "Cannot burn the whole LP supply of the pool"
+        )));
+    }
+
+    token::burn(accs.as_burn_lp_ctx(), lp_tokens_to_burn.amount)?;
+
+    print_lp_supply(&mut accs.lp_mint)?;
+
+    Ok(())
+}
+
+impl<'info> BurnLpToReserves<'info> {
+    fn as_burn_lp_ctx(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, token::Burn<'info>> {
+        let cpi_accounts = token::Burn {
+            mint: self.lp_mint.to_account_info(),
+            from: self.lp_token_wallet.to_account_info(),
+            authority: self.user.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
@@ -0,0 +1,41 @@
+//! Admin of a pool can cancel a swap fee increase which is still timelocked,
+//! see [`crate::endpoints::set_pool_swap_fee`]. The pool keeps its current
+//! swap fee.
+//!
+//! Once the timelock has elapsed, the pending fee is already in effect and
+//! cannot be cancelled anymore. The admin can decrease the fee with
+//! [`crate::endpoints::set_pool_swap_fee`] instead.
+
+use crate::prelude::*;
+
+#[derive(Accounts)]
+pub struct CancelPendingSwapFee<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        constraint = pool.admin.key() == admin.key()
+            @ err::acc("The signer must match pool's admin"),
+    )]
+    pub pool: Account<'info, Pool>,
+}
+
+pub fn handle(ctx: Context<CancelPendingSwapFee>) -> Result<()> {
+    let accs = ctx.accounts;
+
+    // a pending fee which is already effective is not cancelled below
+    accs.pool.apply_pending_swap_fee(Slot::current()?);
+
+    if !accs.pool.has_pending_swap_fee() {
+        return Err(error!(err::arg("There's no pending swap fee to cancel")));
+    }
+
+    msg!(
+        "Cancelled pending swap fee of {} permillion, swap fee stays {} \
+        permillion",
+        accs.pool.pending_swap_fee.permillion,
+        accs.pool.swap_fee.permillion
+    );
+    accs.pool.cancel_pending_swap_fee();
+
+    Ok(())
+}
@@ -7,6 +7,9 @@
 //!
 //! The remaining accounts must be vaults, ie. token accounts owned by the pool
 //! signers. The order of the accounts does not matter.
+//!
+//! If the [`CurveConstraints`] exist, the amplifier of a stable curve must be
+//! within their bounds.
 
 use crate::prelude::*;
 use anchor_lang::solana_program::program_option::COption;
@@ -34,6 +37,22 @@ pub struct CreatePool<'info> {
         bump,
     )]
     pub program_toll: Account<'info, ProgramToll>,
+    /// CHECK: The mint blocklist might not be initialized, and that's fine,
+    /// we are conditionally parsing this account and only if it's valid
+    /// will we consider the blocked mints.
+    #[account(
+        seeds = [MintBlocklist::PDA_SEED],
+        bump,
+    )]
+    pub mint_blocklist: AccountInfo<'info>,
+    /// CHECK: The curve constraints might not be initialized, and that's fine,
+    /// we are conditionally parsing this account and only if it's valid
+    /// will we bound the curve parameters.
+    #[account(
+        seeds = [CurveConstraints::PDA_SEED],
+        bump,
+    )]
+    pub curve_constraints: AccountInfo<'info>,
     #[account(
         constraint = program_toll_wallet.mint == lp_mint.key()
             @ err::acc("Toll wallet must be of LP mint"),
@@ -58,9 +77,22 @@ pub struct CreatePool<'info> {
 pub fn handle(ctx: Context<CreatePool>, amplifier: u64) -> Result<()> {
     let accs = ctx.accounts;
 
+    // we check this up front, before any account is deserialized, so that the
+    // admin gets a clear error message
+    let vaults_count = ctx.remaining_accounts.len();
+    if !(2..=consts::MAX_RESERVES).contains(&vaults_count) {
+        return Err(error!(err::acc(format!(
+            "create_pool expects between 2 and {} remaining accounts, \
+            ie. a vault for each reserve, but got {}",
+            consts::MAX_RESERVES,
+            vaults_count
+        ))));
+    }
+
     accs.pool.mint = accs.lp_mint.key();
     accs.pool.admin = accs.admin.key();
     accs.pool.signer = accs.pool_signer.key();
+    accs.pool.swap_fee_timelock_slots = consts::DEFAULT_SWAP_FEE_TIMELOCK_SLOTS;
     accs.pool.curve = if amplifier == 0 {
         Curve::ConstProd
     } else {
@@ -70,10 +102,22 @@ pub fn handle(ctx: Context<CreatePool>, amplifier: u64) -> Result<()> {
         }
     };
 
-    if ctx.remaining_accounts.len() > consts::MAX_RESERVES {
-        return Err(error!(err::acc("Too many reserves")));
+    let is_curve_constraints_created =
+        accs.curve_constraints.owner == &crate::ID;
+    if is_curve_constraints_created {
+        // we've already verified it's the correct constraints bcs of the pda
+        Account::<CurveConstraints>::try_from(&accs.curve_constraints)?
+            .validate_curve(&accs.pool.curve)?;
     }
 
+    let is_mint_blocklist_created = accs.mint_blocklist.owner == &crate::ID;
+    let mint_blocklist = if is_mint_blocklist_created {
+        // we've already verified it's the correct blocklist bcs of the pda
+        Account::<MintBlocklist>::try_from(&accs.mint_blocklist)?.into_inner()
+    } else {
+        MintBlocklist::default()
+    };
+
     let is_lp_mint_without_supply = accs.lp_mint.supply == 0;
     let mut mints = BTreeSet::new();
     for (index, vault_info) in ctx.remaining_accounts.iter().enumerate() {
@@ -93,6 +137,17 @@ pub fn handle(ctx: Context<CreatePool>, amplifier: u64) -> Result<()> {
             )));
         }
 
+        // the pool's own LP tokens cannot be its reserve, which also rules
+        // out the program toll wallet as it's of the LP mint
+        if vault.mint == accs.lp_mint.key() {
+            return Err(error!(err::acc("Vault mustn't be of the LP mint")));
+        }
+        if mint_blocklist.is_blocked(&vault.mint) {
+            return Err(error!(err::acc(format!(
+                "Mint '{}' is blocked",
+                vault.mint
+            ))));
+        }
         if mints.contains(&vault.mint) {
             return Err(error!(err::acc("Duplicate reserve mint")));
         }
@@ -119,10 +174,6 @@ pub fn handle(ctx: Context<CreatePool>, amplifier: u64) -> Result<()> {
         };
     }
 
-    if mints.len() < 2 {
-        return Err(error!(err::acc("At least 2 vaults must be provided")));
-    }
-
     accs.pool.dimension = mints.len() as u64;
     accs.pool.program_toll_wallet = accs.program_toll_wallet.key();
 
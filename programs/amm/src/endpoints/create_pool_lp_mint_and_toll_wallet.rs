@@ -0,0 +1,80 @@
+//! Convenience endpoint which is called before
+//! [`crate::endpoints::create_pool`] to create the LP mint and the program toll
+//! wallet of the pool with the correct authorities. Both accounts are PDAs of
+//! the future pool's key, so the client doesn't have to create and sign for
+//! them:
+//!
+//! - LP mint with seeds `["lp_mint", pool]`, the mint authority is the pool
+//!   signer and there's no freeze authority;
+//! - program toll wallet with seeds `["program_toll_wallet", pool]`, which is
+//!   a token account of the LP mint owned by the program toll authority.
+//!
+//! Creating the accounts up front is optional, any accounts which pass the
+//! constraints of [`crate::endpoints::create_pool`] can be used.
+
+use crate::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+#[derive(Accounts)]
+#[instruction(lp_mint_decimals: u8)]
+pub struct CreatePoolLpMintAndTollWallet<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    /// CHECK: The pool doesn't exist yet, its key only seeds the PDAs.
+    pub pool: AccountInfo<'info>,
+    /// CHECK: UNSAFE_CODES.md#signer
+    #[account(
+        seeds = [Pool::SIGNER_PDA_PREFIX, pool.key().as_ref()],
+        bump
+    )]
+    pub pool_signer: AccountInfo<'info>,
+    #[account(
+        seeds = [ProgramToll::PDA_SEED],
+        bump,
+    )]
+    pub program_toll: Account<'info, ProgramToll>,
+    /// CHECK: only used as the owner of the program toll wallet
+    #[account(
+        constraint = program_toll_authority.key() == program_toll.authority
+            @ err::acc("Must be program toll authority"),
+    )]
+    pub program_toll_authority: AccountInfo<'info>,
+    #[account(
+        init,
+        payer = admin,
+        seeds = [Pool::LP_MINT_PDA_PREFIX, pool.key().as_ref()],
+        bump,
+        mint::decimals = lp_mint_decimals,
+        mint::authority = pool_signer,
+    )]
+    pub lp_mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = admin,
+        seeds = [Pool::PROGRAM_TOLL_WALLET_PDA_PREFIX, pool.key().as_ref()],
+        bump,
+        token::mint = lp_mint,
+        token::authority = program_toll_authority,
+    )]
+    pub program_toll_wallet: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// The accounts are initialized by anchor, see the account constraints.
+pub fn handle(
+    ctx: Context<CreatePoolLpMintAndTollWallet>,
+    _lp_mint_decimals: u8,
+) -> Result<()> {
+    let accs = ctx.accounts;
+
+    msg!(
+        "Created LP mint '{}' and program toll wallet '{}' for pool '{}'",
+        accs.lp_mint.key(),
+        accs.program_toll_wallet.key(),
+        accs.pool.key()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+//! Creates a [`PoolMirror`] for a pool and fills it with the pool's current
+//! state. Anyone can create the mirror, the payer bears the rent.
+//!
+//! Afterwards, the mirror is kept up to date by a crank calling
+//! [`crate::endpoints::refresh_pool_mirror`].
+
+use crate::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+pub struct CreatePoolMirror<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub pool: Account<'info, Pool>,
+    #[account(
+        constraint = lp_mint.key() == pool.mint
+            @ err::acc("LP mint must match pool's mint"),
+    )]
+    pub lp_mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = payer,
+        space = PoolMirror::space(),
+        seeds = [PoolMirror::PDA_PREFIX, pool.key().as_ref()],
+        bump,
+    )]
+    pub pool_mirror: Account<'info, PoolMirror>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle(ctx: Context<CreatePoolMirror>) -> Result<()> {
+    let accs = ctx.accounts;
+
+    accs.pool_mirror.pool = accs.pool.key();
+    accs.pool_mirror.refresh(
+        &accs.pool,
+        TokenAmount::new(accs.lp_mint.supply),
+        Slot::current()?,
+    );
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+//! Creates a [`ReferralConfig`] for a pool, which is then consumed by
+//! [`crate::endpoints::swap::handle_with_referral`].
+//!
+//! The config is meant to be baked into the pool at creation, ie. the pool's
+//! admin calls this endpoint in the same transaction as
+//! [`crate::endpoints::create_pool`]. Therefore, the config can only be created
+//! while there's no liquidity in the pool. Once created, the share cannot be
+//! changed. The default recipient starts as a wallet of the program toll
+//! authority, who can change it with
+//! [`crate::endpoints::set_pool_referral_default_recipient`].
+
+use crate::prelude::*;
+use anchor_spl::token::{Mint, TokenAccount};
+
+#[derive(Accounts)]
+pub struct CreatePoolReferralConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        constraint = pool.admin.key() == admin.key()
+            @ err::acc("The signer must match pool's admin"),
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(
+        constraint = lp_mint.key() == pool.mint
+            @ err::acc("LP mint must match pool's mint"),
+        constraint = lp_mint.supply == 0
+            @ err::acc("Referral config must be created before any deposit"),
+    )]
+    pub lp_mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = admin,
+        space = ReferralConfig::space(),
+        seeds = [ReferralConfig::PDA_PREFIX, pool.key().as_ref()],
+        bump,
+    )]
+    pub referral_config: Account<'info, ReferralConfig>,
+    #[account(
+        seeds = [ProgramToll::PDA_SEED],
+        bump,
+    )]
+    pub program_toll: Account<'info, ProgramToll>,
+    #[account(
+        constraint = default_referral_wallet.mint == pool.mint
+            @ err::acc("Default referral wallet must be of LP mint"),
+        constraint = default_referral_wallet.owner == program_toll.authority
+            @ err::acc(
+                "Default referral wallet must be owned by \
+                program toll authority"
+            ),
+    )]
+    pub default_referral_wallet: Account<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle(
+    ctx: Context<CreatePoolReferralConfig>,
+    share: Permillion,
+) -> Result<()> {
+    let accs = ctx.accounts;
+
+    if share > consts::MAX_REFERRAL_SHARE {
+        return Err(error!(err::arg(format!(
+            "Maximum referral share is {} permillion",
+            consts::MAX_REFERRAL_SHARE.permillion
+        ))));
+    }
+
+    accs.referral_config.share = share;
+    accs.referral_config.default_recipient = accs.default_referral_wallet.key();
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+//! Same as [`crate::endpoints::create_pool`], but the pool account is a PDA
+//! derived from the admin's key and a nonce chosen by the admin, see
+//! [`Pool::CREATOR_PDA_PREFIX`].
+//!
+//! The address of a pool created with [`crate::endpoints::create_pool`] is
+//! known before the pool is created, e.g. because the LP mint and vaults must
+//! be created for it first. Since only the admin whose key is in the seeds can
+//! create a pool at this address, nobody watching pending transactions can
+//! initialize it first with their own settings.
+//!
+//! The nonce lets the same admin create many pools, e.g. it can be the number
+//! of pools the admin has created so far.
+
+use crate::endpoints::CreatePool;
+use crate::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+#[derive(Accounts)]
+#[instruction(amplifier: u64, nonce: u64)]
+pub struct CreatePoolWithNonce<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = Pool::space(),
+        seeds = [
+            Pool::CREATOR_PDA_PREFIX,
+            admin.key().as_ref(),
+            &nonce.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub pool: Account<'info, Pool>,
+    /// CHECK: UNSAFE_CODES.md#signer
+    #[account(
+        seeds = [Pool::SIGNER_PDA_PREFIX, pool.key().as_ref()],
+        bump
+    )]
+    pub pool_signer: AccountInfo<'info>,
+    #[account(
+        seeds = [ProgramToll::PDA_SEED],
+        bump,
+    )]
+    pub program_toll: Account<'info, ProgramToll>,
+    /// CHECK: See [`CreatePool`]
+    #[account(
+        seeds = [MintBlocklist::PDA_SEED],
+        bump,
+    )]
+    pub mint_blocklist: AccountInfo<'info>,
+    /// CHECK: See [`CreatePool`]
+    #[account(
+        seeds = [CurveConstraints::PDA_SEED],
+        bump,
+    )]
+    pub curve_constraints: AccountInfo<'info>,
+    #[account(
+        constraint = program_toll_wallet.mint == lp_mint.key()
+            @ err::acc("Toll wallet must be of LP mint"),
+        constraint = program_toll_wallet.owner == program_toll.authority
+            @ err::acc(
+                "Toll wallet authority must match \
+                program toll authority"
+            ),
+    )]
+    pub program_toll_wallet: Account<'info, TokenAccount>,
+    #[account(
+        constraint = lp_mint.mint_authority == COption::Some(pool_signer.key())
+            @ err::acc("LP mint authority must be the pool signer"),
+        constraint = lp_mint.freeze_authority == COption::None
+            @ err::acc("LP mint mustn't have a freeze authority"),
+    )]
+    pub lp_mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreatePoolWithNonce<'info>>,
+    amplifier: u64,
+    _nonce: u64,
+) -> Result<()> {
+    let accs = ctx.accounts;
+
+    let mut create_pool = accs.as_create_pool();
+    endpoints::create_pool::handle(
+        Context::new(
+            ctx.program_id,
+            &mut create_pool,
+            ctx.remaining_accounts,
+            ctx.bumps.clone(),
+        ),
+        amplifier,
+    )?;
+
+    // the pool is written on exit of this instruction's accounts
+    *accs.pool = Pool::clone(&create_pool.pool);
+
+    Ok(())
+}
+
+impl<'info> CreatePoolWithNonce<'info> {
+    fn as_create_pool(&self) -> CreatePool<'info> {
+        CreatePool {
+            admin: self.admin.clone(),
+            pool: self.pool.clone(),
+            pool_signer: self.pool_signer.clone(),
+            program_toll: self.program_toll.clone(),
+            mint_blocklist: self.mint_blocklist.clone(),
+            curve_constraints: self.curve_constraints.clone(),
+            program_toll_wallet: self.program_toll_wallet.clone(),
+            lp_mint: self.lp_mint.clone(),
+            token_program: self.token_program.clone(),
+            system_program: self.system_program.clone(),
+        }
+    }
+}
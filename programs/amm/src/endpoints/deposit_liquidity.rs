@@ -6,7 +6,7 @@
 //! When a [`Pool`] is created by an admin, the amount of LP tokens to be
 //! minted corresponds to the minimum value of tokens deposited.
 
-use crate::misc::print_lp_supply;
+use crate::misc::{print_lp_supply, set_return_data_of};
 use crate::prelude::*;
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Mint, Token, TokenAccount};
@@ -55,16 +55,49 @@ pub struct DepositLiquidity<'info> {
 /// pool_vault's mint. If this operation is unsuccessful, throw error.
 ///     iii. transfer correct amount of tokens from the user token wallet to
 /// the pool token vault
-/// 6. mint the correct amount of lp tokens to the
+/// 6. Check that the lp supply doesn't exceed the pool's
+/// [`Pool::max_lp_supply`]
+/// 7. mint the correct amount of lp tokens to the
 /// user lp token wallet
-/// 7. Update the pool curve invariant value
+/// 8. Update the pool curve invariant value
 /// (only in the case the curve is stable)
+/// 9. Write the deposited amounts as return data, see [`LiquidityResult`]
 pub fn handle<'info>(
     ctx: Context<'_, '_, '_, 'info, DepositLiquidity<'info>>,
     max_amount_tokens: Vec<TokenLimit>,
 ) -> Result<()> {
+    let result = deposit(ctx, max_amount_tokens)?;
+
+    set_return_data_of(&result)
+}
+
+/// Steps 1. to 8. of [`handle`], returns the deposited amounts instead of
+/// writing them as return data. Composite endpoints such as
+/// [`crate::endpoints::deposit_liquidity_and_start_farming`] make CPIs after
+/// the deposit, which would clear the return data.
+pub(crate) fn deposit<'info>(
+    ctx: Context<'_, '_, '_, 'info, DepositLiquidity<'info>>,
+    max_amount_tokens: Vec<TokenLimit>,
+) -> Result<LiquidityResult> {
     let accs = ctx.accounts;
 
+    // the length of remaining accounts should be twice the number of
+    // non-trivial reserve tokens in the pool this is due to the fact that
+    // we are passing both a token vault (in the pool) and a token wallet
+    // (of the user) for each non-trivial reserve in the pool
+    //
+    // we check this up front, before any account is deserialized, so that
+    // the user gets a clear error message
+    let expected_rem_accs_len = 2 * accs.pool.dimension as usize;
+    if ctx.remaining_accounts.len() != expected_rem_accs_len {
+        return Err(error!(err::acc(format!(
+            "deposit_liquidity expects {} remaining accounts, ie. a vault and \
+            a wallet for each reserve, but got {}",
+            expected_rem_accs_len,
+            ctx.remaining_accounts.len()
+        ))));
+    }
+
     let pool_signer_bump_seed = *ctx.bumps.get("pool_signer_pda").unwrap();
     let token_vaults_wallets: Vec<Account<'_, TokenAccount>> = ctx
         .remaining_accounts
@@ -84,18 +117,6 @@ pub fn handle<'info>(
         )));
     }
 
-    // the length of token_vaults_wallets should be twice the number of
-    // non-trivial reserve tokens in the pool this is due to the fact that
-    // we are passing both a token vault (in the pool) and a token wallet
-    // (of the user) for each non-trivial reserve in the pool
-    let expected_rem_accs_len = 2 * accs.pool.dimension as usize;
-    if token_vaults_wallets.len() != expected_rem_accs_len {
-        return Err(error!(err::acc(format!(
-            "The remaining accs must be of length {}",
-            expected_rem_accs_len
-        ))));
-    }
-
     // convert max_amount_tokens to BTreeMap (to facilitate logic)
     let max_amount_tokens = max_amount_tokens
         .into_iter()
@@ -119,6 +140,18 @@ pub fn handle<'info>(
         AmmError::InvalidArg
     })?;
 
+    // the LP supply is a u64, and minting beyond it would fail in the token
+    // program without telling the user why
+    let lp_supply_headroom = u64::MAX - accs.lp_mint.supply;
+    if lp_tokens_to_distribute.amount > lp_supply_headroom {
+        msg!(
+            "Minting {} LP tokens would overflow the LP supply {}",
+            lp_tokens_to_distribute.amount,
+            accs.lp_mint.supply
+        );
+        return Err(error!(AmmError::MathOverflow));
+    }
+
     // deposit tokens from pool reserves
     for vault_wallet in token_vaults_wallets.chunks(2) {
         let vault: &Account<'info, TokenAccount> = &vault_wallet[0];
@@ -169,6 +202,21 @@ pub fn handle<'info>(
         )?;
     }
 
+    let max_lp_supply = accs.pool.max_lp_supply;
+    if max_lp_supply.amount != 0
+        && lp_tokens_to_distribute.amount
+            > max_lp_supply.amount.saturating_sub(accs.lp_mint.supply)
+    {
+        msg!(
+            "Minting {} LP tokens would exceed the pool's maximum LP supply \
+            {} as {} LP tokens are already in circulation",
+            lp_tokens_to_distribute.amount,
+            max_lp_supply.amount,
+            accs.lp_mint.supply
+        );
+        return Err(error!(AmmError::MaxLpSupplyExceeded));
+    }
+
     // logic to mint lp tokens and transfer it to the user lp token wallet
     // get the pool signer bump and signer seeds
     let signer_seeds = &[
@@ -186,7 +234,10 @@ pub fn handle<'info>(
 
     print_lp_supply(&mut accs.lp_mint)?;
 
-    Ok(())
+    Ok(LiquidityResult::deposited(
+        lp_tokens_to_distribute,
+        &tokens_to_deposit,
+    ))
 }
 
 impl<'info> DepositLiquidity<'info> {
@@ -0,0 +1,80 @@
+//! Deposits liquidity like [`crate::endpoints::deposit_liquidity`] and stakes
+//! all the LP tokens minted by the deposit in a farm of the farming program,
+//! see `farming::endpoints::start_farming`. Both happen in one instruction,
+//! therefore the user is never left holding unstaked LP tokens.
+//!
+//! The farm must stake the pool's LP mint and the farmer must have been
+//! created beforehand with `farming::endpoints::create_farmer`.
+//!
+//! The max amounts of tokens to deposit bound the slippage of the deposit
+//! same as in [`crate::endpoints::deposit_liquidity`]. The remaining accounts
+//! are also the same, ie. a vault and a wallet for each reserve.
+//!
+//! The deposited amounts are written as the instruction's return data, see
+//! [`LiquidityResult`].
+
+use crate::misc::set_return_data_of;
+use crate::prelude::*;
+
+#[derive(Accounts)]
+pub struct DepositLiquidityAndStartFarming<'info> {
+    /// The user is also the authority which stakes the minted LP tokens from
+    /// the `lp_token_wallet`.
+    pub deposit: DepositLiquidity<'info>,
+    /// CHECK: The farming program checks that the farmer belongs to the farm.
+    #[account(mut)]
+    pub farmer: AccountInfo<'info>,
+    /// CHECK: The farming program checks the farm.
+    pub farm: AccountInfo<'info>,
+    /// CHECK: The farming program checks that this is the farm's stake vault.
+    #[account(mut)]
+    pub stake_vault: AccountInfo<'info>,
+    pub farming_program: Program<'info, farming::program::Farming>,
+}
+
+pub fn handle<'info>(
+    ctx: Context<'_, '_, '_, 'info, DepositLiquidityAndStartFarming<'info>>,
+    max_amount_tokens: Vec<TokenLimit>,
+) -> Result<()> {
+    let accs = ctx.accounts;
+
+    let result = endpoints::deposit_liquidity::deposit(
+        Context::new(
+            ctx.program_id,
+            &mut accs.deposit,
+            ctx.remaining_accounts,
+            ctx.bumps.clone(),
+        ),
+        max_amount_tokens,
+    )?;
+
+    farming::cpi::start_farming(
+        accs.as_start_farming_context(),
+        farming::models::TokenAmount::new(result.lp_tokens_minted.amount),
+    )?;
+
+    set_return_data_of(&result)
+}
+
+impl<'info> DepositLiquidityAndStartFarming<'info> {
+    fn as_start_farming_context(
+        &self,
+    ) -> CpiContext<
+        '_,
+        '_,
+        '_,
+        'info,
+        farming::cpi::accounts::StartFarming<'info>,
+    > {
+        let cpi_accounts = farming::cpi::accounts::StartFarming {
+            wallet_authority: self.deposit.user.to_account_info(),
+            farmer: self.farmer.to_account_info(),
+            stake_wallet: self.deposit.lp_token_wallet.to_account_info(),
+            farm: self.farm.to_account_info(),
+            stake_vault: self.stake_vault.to_account_info(),
+            token_program: self.deposit.token_program.to_account_info(),
+        };
+        let cpi_program = self.farming_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
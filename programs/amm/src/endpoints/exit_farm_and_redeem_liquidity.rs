@@ -0,0 +1,123 @@
+//! Exits a farm of the farming program, see `farming::endpoints::exit_farm`,
+//! and redeems all the unstaked LP tokens like
+//! [`crate::endpoints::redeem_liquidity`]. Both happen in one instruction,
+//! therefore the user is never left holding unstaked LP tokens.
+//!
+//! Exiting the farm claims the farmer's harvest, unstakes all LP tokens into
+//! the `lp_token_wallet` and closes the farmer, returning its rent to the
+//! user. Therefore, the user must be writable. LP tokens which were in the
+//! `lp_token_wallet` before are not redeemed.
+//!
+//! The min amounts of tokens to redeem bound the slippage of the redemption
+//! same as in [`crate::endpoints::redeem_liquidity`].
+//!
+//! The redeemed amounts are written as the instruction's return data, see
+//! [`LiquidityResult`].
+//!
+//! # Remaining accounts
+//! First a vault and a wallet for each reserve, same as in
+//! [`crate::endpoints::redeem_liquidity`]. Then a harvest vault and a harvest
+//! wallet for each harvest mint the farmer is eligible for, same as in
+//! `farming::endpoints::exit_farm`.
+//!
+//! ```text
+//! [
+//!   vault1,
+//!   wallet1,
+//!   ...
+//!   harvest_vault1,
+//!   harvest_wallet1,
+//!   ...
+//! ]
+//! ```
+
+use crate::prelude::*;
+
+#[derive(Accounts)]
+pub struct ExitFarmAndRedeemLiquidity<'info> {
+    /// The user is also the authority over the farmer and receives the
+    /// unstaked LP tokens into the `lp_token_wallet`.
+    pub redeem: RedeemLiquidity<'info>,
+    /// CHECK: The farming program checks that the farmer is the user's farmer
+    /// of the farm.
+    #[account(mut)]
+    pub farmer: AccountInfo<'info>,
+    /// CHECK: The farming program checks the farm.
+    #[account(mut)]
+    pub farm: AccountInfo<'info>,
+    /// CHECK: The farming program checks that this is the farm's signer.
+    pub farm_signer_pda: AccountInfo<'info>,
+    /// CHECK: The farming program checks that this is the farm's stake vault.
+    #[account(mut)]
+    pub stake_vault: AccountInfo<'info>,
+    pub farming_program: Program<'info, farming::program::Farming>,
+}
+
+pub fn handle<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExitFarmAndRedeemLiquidity<'info>>,
+    min_amount_tokens: Vec<TokenLimit>,
+) -> Result<()> {
+    let accs = ctx.accounts;
+
+    if !accs.redeem.user.is_writable {
+        return Err(error!(err::acc(
+            "User must be writable because the farmer's rent is returned to it"
+        )));
+    }
+
+    let redeem_accounts_len = 2 * accs.redeem.pool.dimension as usize;
+    if ctx.remaining_accounts.len() < redeem_accounts_len {
+        return Err(error!(err::acc(format!(
+            "exit_farm_and_redeem_liquidity expects at least {} remaining \
+            accounts, ie. a vault and a wallet for each reserve, but got {}",
+            redeem_accounts_len,
+            ctx.remaining_accounts.len()
+        ))));
+    }
+    let (vaults_wallets, harvest_accounts) =
+        ctx.remaining_accounts.split_at(redeem_accounts_len);
+
+    let lp_tokens_before = accs.redeem.lp_token_wallet.amount;
+    farming::cpi::exit_farm(
+        accs.as_exit_farm_context()
+            .with_remaining_accounts(harvest_accounts.to_vec()),
+    )?;
+    accs.redeem.lp_token_wallet.reload()?;
+    let unstaked = TokenAmount::new(
+        accs.redeem
+            .lp_token_wallet
+            .amount
+            .checked_sub(lp_tokens_before)
+            .ok_or(AmmError::MathOverflow)?,
+    );
+
+    endpoints::redeem_liquidity::handle(
+        Context::new(
+            ctx.program_id,
+            &mut accs.redeem,
+            vaults_wallets,
+            ctx.bumps.clone(),
+        ),
+        unstaked,
+        min_amount_tokens,
+    )
+}
+
+impl<'info> ExitFarmAndRedeemLiquidity<'info> {
+    fn as_exit_farm_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, farming::cpi::accounts::ExitFarm<'info>>
+    {
+        let cpi_accounts = farming::cpi::accounts::ExitFarm {
+            authority: self.redeem.user.to_account_info(),
+            farmer: self.farmer.to_account_info(),
+            stake_wallet: self.redeem.lp_token_wallet.to_account_info(),
+            farm: self.farm.to_account_info(),
+            farm_signer_pda: self.farm_signer_pda.to_account_info(),
+            stake_vault: self.stake_vault.to_account_info(),
+            token_program: self.redeem.token_program.to_account_info(),
+        };
+        let cpi_program = self.farming_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
@@ -0,0 +1,21 @@
+//! Read-only endpoint for monitoring. It writes the invariant of the pool's
+//! curve computed from the current reserves as the instruction's return data,
+//! see [`CurveInvariant`].
+//!
+//! Swaps must never decrease the invariant, fees only increase it. For stable
+//! curve the return data also contains the invariant stored on the last
+//! deposit or redemption, to which the current one can be compared.
+
+use crate::misc::set_return_data_of;
+use crate::prelude::*;
+
+#[derive(Accounts)]
+pub struct GetCurveInvariant<'info> {
+    pub pool: Account<'info, Pool>,
+}
+
+pub fn handle(ctx: Context<GetCurveInvariant>) -> Result<()> {
+    let invariant = ctx.accounts.pool.curve_invariant()?;
+
+    set_return_data_of(&invariant)
+}
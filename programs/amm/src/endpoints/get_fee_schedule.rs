@@ -0,0 +1,22 @@
+//! Read-only endpoint for clients which display or simulate swaps. It writes
+//! the swap fee which applies at the current slot and the pending swap fee
+//! with the slot at which it becomes effective as the instruction's return
+//! data, see [`FeeSchedule`].
+//!
+//! The pool stores a pending fee until the next swap, therefore reading the
+//! [`Pool::swap_fee`] field directly is not enough once the timelock of a
+//! pending fee has elapsed, see [`crate::endpoints::set_pool_swap_fee`].
+
+use crate::misc::set_return_data_of;
+use crate::prelude::*;
+
+#[derive(Accounts)]
+pub struct GetFeeSchedule<'info> {
+    pub pool: Account<'info, Pool>,
+}
+
+pub fn handle(ctx: Context<GetFeeSchedule>) -> Result<()> {
+    let fee_schedule = ctx.accounts.pool.fee_schedule(Slot::current()?);
+
+    set_return_data_of(&fee_schedule)
+}
@@ -0,0 +1,24 @@
+//! Read-only endpoint for LP portfolio trackers. It writes the pool's
+//! reserves, LP supply and how many tokens of each reserve is one LP token
+//! worth as the instruction's return data, see [`LpValue`].
+
+use crate::misc::set_return_data_of;
+use crate::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+pub struct GetLpValue<'info> {
+    pub pool: Account<'info, Pool>,
+    #[account(
+        constraint = pool.mint == lp_mint.key() @ err::acc("LP mint mismatch"),
+    )]
+    pub lp_mint: Account<'info, Mint>,
+}
+
+pub fn handle(ctx: Context<GetLpValue>) -> Result<()> {
+    let accs = ctx.accounts;
+
+    let lp_value = accs.pool.lp_value(accs.lp_mint.supply.into())?;
+
+    set_return_data_of(&lp_value)
+}
@@ -0,0 +1,31 @@
+//! Read-only endpoint for integrators. It writes the size of the pool account
+//! and the size of a pool account created by this version of the program as
+//! the instruction's return data, see [`PoolLayout`].
+//!
+//! The pool account has no version field. A pool which is smaller than the
+//! current layout was created by an older version of the program and cannot
+//! be used by any other endpoint until it's migrated with
+//! [`crate::endpoints::migrate_pool`].
+//!
+//! Unlike other read-only endpoints, this one doesn't deserialize the pool and
+//! therefore also works with accounts of an older layout.
+
+use crate::misc::set_return_data_of;
+use crate::prelude::*;
+
+#[derive(Accounts)]
+pub struct GetPoolLayout<'info> {
+    /// CHECK: We only read the size of the account. The discriminator is
+    /// checked by [`Pool::layout`].
+    #[account(
+        constraint = *pool.owner == crate::ID
+            @ err::acc("Pool must be owned by the program"),
+    )]
+    pub pool: AccountInfo<'info>,
+}
+
+pub fn handle(ctx: Context<GetPoolLayout>) -> Result<()> {
+    let layout = Pool::layout(&ctx.accounts.pool.try_borrow_data()?)?;
+
+    set_return_data_of(&layout)
+}
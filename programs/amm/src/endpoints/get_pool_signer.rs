@@ -0,0 +1,31 @@
+//! Read-only endpoint for integrators. It writes the pool's signer PDA and the
+//! bump seed with which it's derived as the instruction's return data, see
+//! [`PoolSigner`].
+//!
+//! Endpoints which move tokens out of the pool's vaults check the signer PDA
+//! with seeds `["signer", pool]`, and fail on a seeds constraint if a client
+//! passes a wrong account. Clients can use this endpoint to self-correct.
+
+use crate::misc::set_return_data_of;
+use crate::prelude::*;
+
+#[derive(Accounts)]
+pub struct GetPoolSigner<'info> {
+    pub pool: Account<'info, Pool>,
+}
+
+pub fn handle(ctx: Context<GetPoolSigner>) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    let pool_signer = Pool::find_signer_address(&pool.key());
+    if pool_signer.signer != pool.signer {
+        msg!(
+            "Pool stores signer '{}' but it derives to '{}'",
+            pool.signer,
+            pool_signer.signer
+        );
+        return Err(error!(AmmError::InvariantViolation));
+    }
+
+    set_return_data_of(&pool_signer)
+}
@@ -0,0 +1,76 @@
+//! Converts a [`Pool`] account created before the pool stored a pending swap
+//! fee, an LP supply cap, decimal scales and lifetime fees into the current
+//! layout, see [`LegacyPool`]. Such an account cannot be used by any other
+//! endpoint until it's migrated. Use [`crate::endpoints::get_pool_layout`]
+//! to tell whether a pool needs to be migrated.
+//!
+//! The account is reallocated in place, therefore its pubkey doesn't change.
+//! Anyone can migrate a pool, the payer tops up the rent of the larger
+//! account.
+
+use crate::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+
+#[derive(Accounts)]
+pub struct MigratePool<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: the account is in the legacy layout and therefore cannot be
+    /// deserialized as [`Pool`], the layout and the discriminator are checked
+    /// in the [`handle`] function
+    #[account(
+        mut,
+        constraint = *pool.owner == crate::ID
+            @ err::acc("Pool must be owned by the program"),
+    )]
+    pub pool: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle(ctx: Context<MigratePool>) -> Result<()> {
+    let accs = ctx.accounts;
+
+    let legacy = {
+        let data = accs.pool.try_borrow_data()?;
+        if data.len() < 8 || data[..8] != Pool::discriminator() {
+            return Err(error!(err::acc("Account is not a pool")));
+        }
+        if data.len() != LegacyPool::space() {
+            return Err(error!(err::acc(
+                "Pool account is not in the legacy layout"
+            )));
+        }
+
+        LegacyPool::deserialize(&mut &data[8..])?
+    };
+
+    let pool = legacy.migrate();
+
+    let rent = Rent::get()?.minimum_balance(Pool::space());
+    let top_up = rent.saturating_sub(accs.pool.lamports());
+    if top_up != 0 {
+        system_program::transfer(accs.as_top_up_rent_context(), top_up)?;
+    }
+
+    accs.pool.realloc(Pool::space(), false)?;
+    let mut data = accs.pool.try_borrow_mut_data()?;
+    pool.try_serialize(&mut data.as_mut())?;
+
+    msg!("Pool {} migrated", accs.pool.key());
+
+    Ok(())
+}
+
+impl<'info> MigratePool<'info> {
+    fn as_top_up_rent_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, system_program::Transfer<'info>> {
+        let cpi_accounts = system_program::Transfer {
+            from: self.payer.to_account_info(),
+            to: self.pool.to_account_info(),
+        };
+        let cpi_program = self.system_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
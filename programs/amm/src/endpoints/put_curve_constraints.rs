@@ -0,0 +1,106 @@
+//! Either creates the [`CurveConstraints`] singleton - if it doesn't exist
+//! yet - or overwrites the bounds on curve parameters. In the former scenario,
+//! the authority must be mutable so that we can transfer rent to the new
+//! account.
+//!
+//! The constraints are global for all pools, therefore they're managed by the
+//! program toll authority.
+//!
+//! See the [`crate::models::curve_constraints`] module for more info.
+
+use crate::prelude::*;
+use anchor_lang::system_program;
+
+#[derive(Accounts)]
+pub struct PutCurveConstraints<'info> {
+    #[account(
+        constraint = authority.key() == program_toll.authority
+            @ err::acc("The authority must be the program toll authority"),
+    )]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [ProgramToll::PDA_SEED],
+        bump,
+    )]
+    pub program_toll: Account<'info, ProgramToll>,
+    /// CHECK: we create the constraints account if it does not exist yet in
+    /// the [`handle`] fn
+    #[account(
+        mut,
+        seeds = [CurveConstraints::PDA_SEED],
+        bump,
+    )]
+    pub curve_constraints: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle(
+    ctx: Context<PutCurveConstraints>,
+    min_amplifier: u64,
+    max_amplifier: u64,
+    min_weight: u8,
+    max_weight: u8,
+) -> Result<()> {
+    let accs = ctx.accounts;
+
+    if min_amplifier > max_amplifier {
+        return Err(error!(err::arg(
+            "Min amplifier must not be greater than max amplifier"
+        )));
+    }
+    if min_weight > max_weight || max_weight > 100 {
+        return Err(error!(err::arg(
+            "Weight bounds must be ordered and at most 100"
+        )));
+    }
+
+    let should_be_created = accs.curve_constraints.owner == &system_program::ID;
+    if should_be_created {
+        // we must transfer rent from authority
+        if !accs.authority.is_writable {
+            return Err(error!(err::acc(
+                "Authority must be writable \
+                because curve constraints account doesn't exist yet"
+            )));
+        }
+
+        let pda_seeds = &[
+            CurveConstraints::PDA_SEED.as_ref(),
+            &[*ctx.bumps.get("curve_constraints").unwrap()],
+        ];
+        let rent = Rent::get()?.minimum_balance(CurveConstraints::space());
+        system_program::create_account(
+            accs.as_create_curve_constraints_account_context()
+                .with_signer(&[&pda_seeds[..]]),
+            rent,
+            CurveConstraints::space() as u64,
+            ctx.program_id,
+        )?;
+    }
+
+    // overwrites the data in the constraints account
+    let constraints = CurveConstraints {
+        min_amplifier,
+        max_amplifier,
+        min_weight,
+        max_weight,
+    };
+    let mut constraints_data = accs.curve_constraints.try_borrow_mut_data()?;
+    constraints.try_serialize(&mut constraints_data.as_mut())?;
+
+    Ok(())
+}
+
+impl<'info> PutCurveConstraints<'info> {
+    fn as_create_curve_constraints_account_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, system_program::CreateAccount<'info>>
+    {
+        let cpi_accounts = system_program::CreateAccount {
+            from: self.authority.to_account_info(),
+            to: self.curve_constraints.to_account_info(),
+        };
+        let cpi_program = self.system_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
@@ -0,0 +1,93 @@
+//! Either creates the [`MintBlocklist`] singleton - if it doesn't exist yet -
+//! or overwrites the list of blocked mints. In the former scenario, the
+//! authority must be mutable so that we can transfer rent to the new account.
+//!
+//! The blocklist is global for all pools, therefore it's managed by the
+//! program toll authority. Pools which already exist are not affected.
+//!
+//! See the [`crate::models::mint_blocklist`] module for more info.
+
+use crate::prelude::*;
+use anchor_lang::system_program;
+
+#[derive(Accounts)]
+pub struct PutMintBlocklist<'info> {
+    #[account(
+        constraint = authority.key() == program_toll.authority
+            @ err::acc("The authority must be the program toll authority"),
+    )]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [ProgramToll::PDA_SEED],
+        bump,
+    )]
+    pub program_toll: Account<'info, ProgramToll>,
+    /// CHECK: we create the blocklist account if it does not exist yet in the
+    /// [`handle`] fn
+    #[account(
+        mut,
+        seeds = [MintBlocklist::PDA_SEED],
+        bump,
+    )]
+    pub mint_blocklist: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle(
+    ctx: Context<PutMintBlocklist>,
+    mints: Vec<Pubkey>,
+) -> Result<()> {
+    let accs = ctx.accounts;
+
+    if mints.len() > consts::MAX_BLOCKED_MINTS {
+        return Err(error!(err::arg(format!(
+            "At most {} mints can be blocked",
+            consts::MAX_BLOCKED_MINTS
+        ))));
+    }
+
+    let should_be_created = accs.mint_blocklist.owner == &system_program::ID;
+    if should_be_created {
+        // we must transfer rent from authority
+        if !accs.authority.is_writable {
+            return Err(error!(err::acc(
+                "Authority must be writable \
+                because mint blocklist account doesn't exist yet"
+            )));
+        }
+
+        let pda_seeds = &[
+            MintBlocklist::PDA_SEED.as_ref(),
+            &[*ctx.bumps.get("mint_blocklist").unwrap()],
+        ];
+        let rent = Rent::get()?.minimum_balance(MintBlocklist::space());
+        system_program::create_account(
+            accs.as_create_mint_blocklist_account_context()
+                .with_signer(&[&pda_seeds[..]]),
+            rent,
+            MintBlocklist::space() as u64,
+            ctx.program_id,
+        )?;
+    }
+
+    // overwrites the data in the blocklist account
+    let blocklist = MintBlocklist { mints };
+    let mut blocklist_data = accs.mint_blocklist.try_borrow_mut_data()?;
+    blocklist.try_serialize(&mut blocklist_data.as_mut())?;
+
+    Ok(())
+}
+
+impl<'info> PutMintBlocklist<'info> {
+    fn as_create_mint_blocklist_account_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, system_program::CreateAccount<'info>>
+    {
+        let cpi_accounts = system_program::CreateAccount {
+            from: self.authority.to_account_info(),
+            to: self.mint_blocklist.to_account_info(),
+        };
+        let cpi_program = self.system_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
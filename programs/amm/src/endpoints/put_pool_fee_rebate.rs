@@ -0,0 +1,98 @@
+//! Either creates a [`FeeRebate`] model for a pool - if it doesn't exist yet -
+//! or updates an existing one. In the former scenario, the admin must be
+//! mutable so that we can transfer rent to the new account. The account is
+//! created even if someone has already transferred lamports to the PDA, see
+//! [`create_pda_account`].
+//!
+//! To end a rebate early, the admin can set the share to zero.
+//!
+//! See the [`crate::models::fee_rebate`] module for more info.
+
+use crate::misc::create_pda_account;
+use crate::prelude::*;
+use anchor_lang::system_program;
+
+#[derive(Accounts)]
+pub struct PutPoolFeeRebate<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        constraint = pool.admin.key() == admin.key()
+            @ err::acc("The signer must match pool's admin"),
+    )]
+    pub pool: Account<'info, Pool>,
+    /// CHECK: we create the fee rebate account if it does not exist yet in the
+    /// [`handle`] fn
+    #[account(
+        mut,
+        seeds = [FeeRebate::PDA_PREFIX, pool.key().as_ref()],
+        bump,
+    )]
+    pub fee_rebate: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle(
+    ctx: Context<PutPoolFeeRebate>,
+    share: Permillion,
+    starts_at: Slot,
+    ends_at: Slot,
+) -> Result<()> {
+    let accs = ctx.accounts;
+
+    if starts_at > ends_at {
+        return Err(error!(err::arg(
+            "The fee rebate must start before it ends"
+        )));
+    }
+
+    if ends_at <= Slot::current()? {
+        return Err(error!(err::arg(
+            "The slot until which the fee rebate applies must be in the future"
+        )));
+    }
+
+    if share >= Permillion::from_percent(100) {
+        return Err(error!(err::arg(
+            "Fee rebate must be less than 100%, ie. 1,000,000 permillion"
+        )));
+    }
+
+    let should_be_created = accs.fee_rebate.owner == &system_program::ID;
+    if should_be_created {
+        // The following logic is basically what `#[account(init)]` does,
+        // see also the put_discount endpoint.
+
+        // we must transfer rent from admin
+        if !accs.admin.is_writable {
+            return Err(error!(err::acc(
+                "Admin must be writable \
+                because fee rebate account doesn't exist yet"
+            )));
+        }
+
+        let pool_key = accs.pool.key();
+        let pda_seeds = &[
+            FeeRebate::PDA_PREFIX,
+            pool_key.as_ref(),
+            &[*ctx.bumps.get("fee_rebate").unwrap()],
+        ];
+        create_pda_account(
+            accs.admin.to_account_info(),
+            accs.fee_rebate.to_account_info(),
+            accs.system_program.to_account_info(),
+            FeeRebate::space(),
+            &pda_seeds[..],
+        )?;
+    }
+
+    // overwrites the data in the fee rebate account
+    let fee_rebate = FeeRebate {
+        share,
+        starts_at,
+        ends_at,
+    };
+    let mut fee_rebate_data = accs.fee_rebate.try_borrow_mut_data()?;
+    fee_rebate.try_serialize(&mut fee_rebate_data.as_mut())?;
+
+    Ok(())
+}
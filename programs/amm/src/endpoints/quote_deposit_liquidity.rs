@@ -0,0 +1,38 @@
+//! Read-only endpoint for LP UIs which preview a deposit. It calculates the
+//! same [`LiquidityResult`] as [`crate::endpoints::deposit_liquidity`] would,
+//! with the same rounding, but doesn't move any tokens nor change the pool.
+//! The quote is written as the instruction's return data.
+
+use crate::misc::set_return_data_of;
+use crate::prelude::*;
+use anchor_spl::token::Mint;
+use std::collections::BTreeMap;
+
+#[derive(Accounts)]
+pub struct QuoteDepositLiquidity<'info> {
+    pub pool: Account<'info, Pool>,
+    #[account(
+        constraint = pool.mint == lp_mint.key() @ err::acc("LP mint mismatch"),
+    )]
+    pub lp_mint: Account<'info, Mint>,
+}
+
+pub fn handle(
+    ctx: Context<QuoteDepositLiquidity>,
+    max_amount_tokens: Vec<TokenLimit>,
+) -> Result<()> {
+    let accs = ctx.accounts;
+
+    let max_amount_tokens = max_amount_tokens
+        .into_iter()
+        .map(|h| (h.mint, h.tokens))
+        .collect::<BTreeMap<Pubkey, TokenAmount>>();
+
+    accs.pool.check_amount_tokens_is_valid(&max_amount_tokens)?;
+
+    let quote = accs
+        .pool
+        .quote_deposit(max_amount_tokens, accs.lp_mint.supply.into())?;
+
+    set_return_data_of(&quote)
+}
@@ -0,0 +1,34 @@
+//! Read-only endpoint for LP UIs which preview a redemption. It calculates the
+//! same [`LiquidityResult`] as [`crate::endpoints::redeem_liquidity`] would,
+//! with the same rounding, but doesn't move any tokens nor change the pool.
+//! The quote is written as the instruction's return data.
+
+use crate::misc::set_return_data_of;
+use crate::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+pub struct QuoteRedeemLiquidity<'info> {
+    pub pool: Account<'info, Pool>,
+    #[account(
+        constraint = pool.mint == lp_mint.key() @ err::acc("LP mint mismatch"),
+    )]
+    pub lp_mint: Account<'info, Mint>,
+}
+
+pub fn handle(
+    ctx: Context<QuoteRedeemLiquidity>,
+    lp_tokens_to_burn: TokenAmount,
+) -> Result<()> {
+    let accs = ctx.accounts;
+
+    if lp_tokens_to_burn.amount == 0 {
+        return Err(error!(err::arg("LP tokens to burn mustn't be zero")));
+    }
+
+    let quote = accs
+        .pool
+        .quote_redeem(lp_tokens_to_burn, accs.lp_mint.supply.into())?;
+
+    set_return_data_of(&quote)
+}
@@ -0,0 +1,36 @@
+//! Read-only endpoint for LP UIs which let the user enter the amount of one
+//! token only. It calculates how many tokens of the other reserves go with the
+//! given amount and how many LP tokens [`crate::endpoints::deposit_liquidity`]
+//! would mint for them, with the same rounding, see
+//! [`Pool::quote_single_deposit`]. No tokens are moved and the pool doesn't
+//! change.
+//!
+//! The quote is written as the instruction's return data, see
+//! [`LiquidityResult`]. Its token amounts can be used as the max amounts of
+//! tokens of the deposit.
+
+use crate::misc::set_return_data_of;
+use crate::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+pub struct QuoteSingleDeposit<'info> {
+    pub pool: Account<'info, Pool>,
+    #[account(
+        constraint = pool.mint == lp_mint.key() @ err::acc("LP mint mismatch"),
+    )]
+    pub lp_mint: Account<'info, Mint>,
+}
+
+pub fn handle(
+    ctx: Context<QuoteSingleDeposit>,
+    tokens: TokenLimit,
+) -> Result<()> {
+    let accs = ctx.accounts;
+
+    let quote = accs
+        .pool
+        .quote_single_deposit(tokens, accs.lp_mint.supply.into())?;
+
+    set_return_data_of(&quote)
+}
@@ -0,0 +1,102 @@
+//! Read-only endpoint for on-chain routers which compare pools. It calculates
+//! the same [`SwapQuote`] as [`crate::endpoints::swap`] would, but doesn't
+//! move any tokens nor change the pool. The quote is written as the
+//! instruction's return data.
+//!
+//! The user's [`Discount`] and the pool's [`FeeRebate`] are considered. If
+//! `with_referral` is set, the quote splits the program toll by the pool's
+//! [`ReferralConfig`] as if the swap was routed through
+//! [`crate::endpoints::swap::handle_with_referral`] or
+//! [`crate::endpoints::swap::handle_with_default_referral`], otherwise as if
+//! it was routed through [`crate::endpoints::swap::handle`].
+
+use crate::endpoints::swap::calculate_swap_quote;
+use crate::misc::set_return_data_of;
+use crate::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+pub struct QuoteSwap<'info> {
+    /// CHECK: The user who'd swap, only used to find their discount.
+    pub user: AccountInfo<'info>,
+    /// CHECK: The user's discount might not be initialized, and that's fine,
+    /// we are conditionally parsing this account and only if it's valid
+    /// will we consider the discount.
+    #[account(
+        seeds = [Discount::PDA_PREFIX, user.key().as_ref()],
+        bump,
+    )]
+    pub discount: AccountInfo<'info>,
+    pub pool: Box<Account<'info, Pool>>,
+    /// CHECK: The pool's fee rebate might not be initialized, and that's fine,
+    /// we are conditionally parsing this account and only if it's valid
+    /// will we consider the rebate.
+    #[account(
+        seeds = [FeeRebate::PDA_PREFIX, pool.key().as_ref()],
+        bump,
+    )]
+    pub fee_rebate: AccountInfo<'info>,
+    /// CHECK: The pool's referral config might not be initialized, and that's
+    /// fine unless the quote is for a referred swap, we are conditionally
+    /// parsing this account.
+    #[account(
+        seeds = [ReferralConfig::PDA_PREFIX, pool.key().as_ref()],
+        bump,
+    )]
+    pub referral_config: AccountInfo<'info>,
+    #[account(
+        constraint = pool.mint == lp_mint.key() @ err::acc("LP mint mismatch"),
+        constraint = lp_mint.supply > 0 @ err::acc("No liquidity provided yet"),
+    )]
+    pub lp_mint: Account<'info, Mint>,
+}
+
+pub fn handle(
+    ctx: Context<QuoteSwap>,
+    sell_mint: Pubkey,
+    sell: TokenAmount,
+    buy_mint: Pubkey,
+    with_referral: bool,
+) -> Result<()> {
+    let accs = ctx.accounts;
+
+    if sell.amount == 0 {
+        return Err(error!(err::arg("Sell amount mustn't be zero")));
+    }
+    if sell_mint == buy_mint {
+        return Err(error!(err::arg(
+            "Mint to swap from mustn't equal the mint to swap to"
+        )));
+    }
+
+    // IMPORTANT: the quote mutates the pool, hence the clone
+    let mut pool = Pool::clone(&accs.pool);
+    let quote = calculate_swap_quote(
+        &mut pool,
+        sell_mint,
+        sell,
+        buy_mint,
+        &accs.discount,
+        Some(&accs.fee_rebate),
+        accs.lp_mint.supply.into(),
+    )?;
+
+    let quote = if with_referral {
+        let is_referral_config_created =
+            accs.referral_config.owner == &crate::ID;
+        if !is_referral_config_created {
+            return Err(error!(err::acc(
+                "Pool has no referral config, cannot quote a referred swap"
+            )));
+        }
+
+        // we've already verified it's the correct config bcs of the pda
+        let config =
+            Account::<ReferralConfig>::try_from(&accs.referral_config)?;
+        quote.with_referral(config.share)?
+    } else {
+        quote
+    };
+
+    set_return_data_of(&quote)
+}
@@ -3,8 +3,12 @@
 //! Moreover, this endpoint computes the necessary amount of tokens that
 //! need to be redeem, given the amount of LP tokens the user wants to burn,
 //! such that the redemption respects the current pool ratio.
+//!
+//! Redemptions are not charged any fee. This applies to the program toll
+//! authority redeeming the LP tokens of the program toll wallet too, and
+//! doesn't depend on the features the program is compiled with.
 
-use crate::misc::print_lp_supply;
+use crate::misc::{print_lp_supply, set_return_data_of};
 use crate::prelude::*;
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Mint, Token, TokenAccount};
@@ -47,14 +51,16 @@ pub struct RedeemLiquidity<'info> {
 /// correspond to mints in the pool reserves)
 /// 4. Compute how many tokens should be redeemed, given the current token ratio
 /// in the pool and the TokenAmount's in [`min_amount_tokens`]
-/// 5. Loop over each (pool_vault, user_wallet) and verify:
-///     i. pool_vault and user_wallet contain the same mint
-///     ii. obtain the corresponding reserve, whose mint coincides with
-/// pool_vault's mint. If this operation is unsuccessful, throw error.
-///     iii. transfer correct amount of tokens from the pool token vault to
-/// the user token wallet
-/// 6. burn the correct amount of lp tokens from the user lp token wallet
-/// 7. Update the pool curve invariant value if stable curve
+/// 5. Loop over each (pool_vault, user_wallet) and verify, before any
+/// tokens are moved:
+///     i. pool_vault is a vault of one of the pool's reserves
+///     ii. user_wallet is of the same mint as that reserve and is owned by
+/// the user
+/// 6. Transfer correct amount of tokens from each pool token vault to the
+/// user token wallet
+/// 7. burn the correct amount of lp tokens from the user lp token wallet
+/// 8. Update the pool curve invariant value if stable curve
+/// 9. Write the redeemed amounts as return data, see [`LiquidityResult`]
 pub fn handle<'info>(
     ctx: Context<'_, '_, '_, 'info, RedeemLiquidity<'info>>,
     lp_tokens_to_burn: TokenAmount,
@@ -62,6 +68,23 @@ pub fn handle<'info>(
 ) -> Result<()> {
     let accs = ctx.accounts;
 
+    // the length of remaining accounts should be twice the number of
+    // non-trivial reserve tokens in the pool this is due to the fact that
+    // we are passing both a token vault (in the pool) and a token wallet
+    // (of the user) for each non-trivial reserve in the pool
+    //
+    // we check this up front, before any account is deserialized, so that
+    // the user gets a clear error message
+    let expected_rem_accs_len = 2 * accs.pool.dimension as usize;
+    if ctx.remaining_accounts.len() != expected_rem_accs_len {
+        return Err(error!(err::acc(format!(
+            "redeem_liquidity expects {} remaining accounts, ie. a vault and \
+            a wallet for each reserve, but got {}",
+            expected_rem_accs_len,
+            ctx.remaining_accounts.len()
+        ))));
+    }
+
     let pool_signer_bump_seed = *ctx.bumps.get("pool_signer").unwrap();
 
     let token_vaults_wallets: Vec<Account<'_, TokenAccount>> = ctx
@@ -84,6 +107,43 @@ pub fn handle<'info>(
         ))));
     }
 
+    // validate all destination wallets before any tokens are moved, so that
+    // the user gets a descriptive error instead of the token program's mint
+    // mismatch
+    for vault_wallet in token_vaults_wallets.chunks(2) {
+        let vault = &vault_wallet[0];
+        let user_wallet = &vault_wallet[1];
+
+        // invalid if passed vault_wallet pubkey is not in the reserves
+        let reserve = accs
+            .pool
+            .reserves()
+            .iter()
+            .find(|r| r.vault == vault.key())
+            .ok_or_else(|| {
+                err::acc(
+                    "At least one of the vaults in remaining account \
+                    does not correspond to any vaul in the pool reserves",
+                )
+            })?;
+
+        if user_wallet.mint != reserve.mint {
+            return Err(error!(err::acc(format!(
+                "Destination wallet '{}' is of mint '{}', but the reserve \
+                of vault '{}' is of mint '{}'",
+                user_wallet.key(),
+                user_wallet.mint,
+                vault.key(),
+                reserve.mint
+            ))));
+        }
+        if user_wallet.owner != accs.user.key() {
+            return Err(error!(err::acc(
+                "User must be authority over all wallets"
+            )));
+        }
+    }
+
     if lp_tokens_to_burn.amount > accs.lp_mint.supply {
         return Err(error!(err::arg(
             "The amount of lp tokens to burn cannot \
@@ -96,18 +156,6 @@ pub fn handle<'info>(
         return Err(error!(AmmError::InvalidLpTokenAmount));
     }
 
-    // the length of token_vaults_wallets should be twice the number of
-    // non-trivial reserve tokens in the pool this is due to the fact that
-    // we are passing both a token vault (in the pool) and a token wallet
-    // (of the user) for each non-trivial reserve in the pool
-    let expected_rem_accs_len = 2 * accs.pool.dimension as usize;
-    if token_vaults_wallets.len() != expected_rem_accs_len {
-        return Err(error!(err::acc(format!(
-            "The remaining accs must be of length {}",
-            expected_rem_accs_len
-        ))));
-    }
-
     // convert min_amount_tokens to BTreeMap (to facilitate logic)
     let min_amount_tokens = min_amount_tokens
         .into_iter()
@@ -132,30 +180,6 @@ pub fn handle<'info>(
         let vault: &Account<'info, TokenAccount> = &vault_wallet[0];
         let user_wallet: &Account<'info, TokenAccount> = &vault_wallet[1];
 
-        if vault.mint != user_wallet.mint {
-            return Err(error!(err::acc(
-                "Each vault wallet pair must match in mint"
-            )));
-        }
-        if user_wallet.owner != accs.user.key() {
-            return Err(error!(err::acc(
-                "User must be authority over all wallets"
-            )));
-        }
-
-        // invalid if passed vault_wallet pubkey is not in the reserves
-        if !accs
-            .pool
-            .reserves()
-            .iter()
-            .any(|r| r.vault.eq(&vault.key()))
-        {
-            return Err(error!(err::acc(
-                "At least one of the vaults in remaining account \
-                    does not correspond to any vaul in the pool reserves",
-            )));
-        }
-
         // get tokens to remove to the reserve
         let tokens_redeemed =
             tokens_to_redeem.get(&vault.mint).ok_or_else(|| {
@@ -190,6 +214,11 @@ pub fn handle<'info>(
 
     print_lp_supply(&mut accs.lp_mint)?;
 
+    set_return_data_of(&LiquidityResult::redeemed(
+        lp_tokens_to_burn,
+        &tokens_to_redeem,
+    ))?;
+
     Ok(())
 }
 
@@ -0,0 +1,155 @@
+//! The program toll is paid in LP tokens, see [`crate::endpoints::swap`].
+//! Redeeming them with [`crate::endpoints::redeem_liquidity`] yields tokens of
+//! each reserve. This endpoint instead lets the program toll authority take
+//! the toll denominated in a single reserve's mint, eg. a stable coin.
+//!
+//! Only pools with two reserves are supported. Given amount of LP tokens is
+//! burned from the program toll wallet, and the share of the other reserve is
+//! swapped within the pool into the chosen reserve, see
+//! [`Pool::redeem_tokens_into_reserve`]. All redeemed tokens are transferred
+//! to the destination wallet. The authority bounds the slippage of the swap
+//! with the minimum amount of tokens to receive.
+
+use crate::misc::{print_lp_supply, set_return_data_of};
+use crate::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+use std::collections::BTreeMap;
+
+#[derive(Accounts)]
+pub struct RedeemProgramTollIntoReserve<'info> {
+    /// Authority over the program toll wallet.
+    pub program_toll_authority: Signer<'info>,
+    #[account(
+        seeds = [ProgramToll::PDA_SEED],
+        bump,
+        constraint = program_toll.authority == program_toll_authority.key()
+            @ err::acc("Signer must be program toll authority"),
+    )]
+    pub program_toll: Account<'info, ProgramToll>,
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    /// CHECK: UNSAFE_CODES.md#signer
+    #[account(
+        seeds = [Pool::SIGNER_PDA_PREFIX, pool.key().as_ref()],
+        bump,
+    )]
+    pub pool_signer: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = lp_mint.key() == pool.mint
+            @ err::acc("LP mint must match pool's mint"),
+    )]
+    pub lp_mint: Account<'info, Mint>,
+    /// LP tokens are burned FROM this wallet.
+    #[account(
+        mut,
+        constraint = program_toll_wallet.key() == pool.program_toll_wallet
+            @ err::acc("Program toll wallet mismatch"),
+        constraint = program_toll_wallet.mint == pool.mint
+            @ err::acc("Toll wallet must be of LP mint"),
+    )]
+    pub program_toll_wallet: Account<'info, TokenAccount>,
+    /// Redeemed tokens flow FROM this account.
+    #[account(
+        mut,
+        constraint = pool.reserve_vault(reserve_vault.mint)
+            == Some(reserve_vault.key())
+            @ err::acc("Vault is not reserve's vault"),
+    )]
+    pub reserve_vault: Account<'info, TokenAccount>,
+    /// Redeemed tokens flow INTO this account.
+    #[account(
+        mut,
+        constraint = destination_wallet.mint == reserve_vault.mint
+            @ err::acc("Destination wallet mint must match reserve vault mint"),
+    )]
+    pub destination_wallet: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handle(
+    ctx: Context<RedeemProgramTollIntoReserve>,
+    lp_tokens_to_burn: TokenAmount,
+    min_tokens: TokenAmount,
+) -> Result<()> {
+    let pool_signer_bump_seed = *ctx.bumps.get("pool_signer").unwrap();
+
+    let accs = ctx.accounts;
+
+    if lp_tokens_to_burn.amount == 0 {
+        return Err(error!(err::arg("LP tokens to burn mustn't be zero")));
+    }
+
+    if lp_tokens_to_burn.amount > accs.program_toll_wallet.amount {
+        return Err(error!(AmmError::InvalidLpTokenAmount));
+    }
+
+    // the swap within the pool uses the pending fee once its timelock has
+    // elapsed, same as any other swap
+    if accs.pool.has_pending_swap_fee() {
+        accs.pool.apply_pending_swap_fee(Slot::current()?);
+    }
+
+    let mint = accs.reserve_vault.mint;
+    // this mutates the state of the pool, removing the amount returned
+    let tokens = accs.pool.redeem_tokens_into_reserve(
+        lp_tokens_to_burn,
+        TokenAmount::new(accs.lp_mint.supply),
+        mint,
+    )?;
+
+    if min_tokens > tokens {
+        msg!(
+            "Would redeem only {} tokens, minimum is {}",
+            tokens.amount,
+            min_tokens.amount
+        );
+        return Err(error!(AmmError::SlippageExceeded));
+    }
+
+    token::burn(accs.as_burn_toll_ctx(), lp_tokens_to_burn.amount)?;
+
+    let signer_seeds = &[
+        Pool::SIGNER_PDA_PREFIX,
+        &accs.pool.key().to_bytes()[..],
+        &[pool_signer_bump_seed],
+    ];
+    token::transfer(
+        accs.as_transfer_tokens_ctx()
+            .with_signer(&[&signer_seeds[..]]),
+        tokens.amount,
+    )?;
+
+    print_lp_supply(&mut accs.lp_mint)?;
+
+    set_return_data_of(&LiquidityResult::redeemed(
+        lp_tokens_to_burn,
+        &BTreeMap::from([(mint, tokens)]),
+    ))
+}
+
+impl<'info> RedeemProgramTollIntoReserve<'info> {
+    fn as_burn_toll_ctx(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, token::Burn<'info>> {
+        let cpi_accounts = token::Burn {
+            mint: self.lp_mint.to_account_info(),
+            from: self.program_toll_wallet.to_account_info(),
+            authority: self.program_toll_authority.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+
+    fn as_transfer_tokens_ctx(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+        let cpi_accounts = token::Transfer {
+            from: self.reserve_vault.to_account_info(),
+            to: self.destination_wallet.to_account_info(),
+            authority: self.pool_signer.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
@@ -0,0 +1,33 @@
+//! Permissionless crank which copies the pool's current reserves and LP supply
+//! into its [`PoolMirror`].
+
+use crate::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+pub struct RefreshPoolMirror<'info> {
+    pub pool: Account<'info, Pool>,
+    #[account(
+        constraint = lp_mint.key() == pool.mint
+            @ err::acc("LP mint must match pool's mint"),
+    )]
+    pub lp_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [PoolMirror::PDA_PREFIX, pool.key().as_ref()],
+        bump,
+    )]
+    pub pool_mirror: Account<'info, PoolMirror>,
+}
+
+pub fn handle(ctx: Context<RefreshPoolMirror>) -> Result<()> {
+    let accs = ctx.accounts;
+
+    accs.pool_mirror.refresh(
+        &accs.pool,
+        TokenAmount::new(accs.lp_mint.supply),
+        Slot::current()?,
+    );
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+//! The program toll is paid in LP tokens minted into the pool's program toll
+//! wallet on each swap, see [`crate::endpoints::swap`]. Instead of redeeming
+//! those LP tokens for the reserve tokens, the program toll authority can
+//! decide to reinvest them back into the pool.
+//!
+//! This endpoint burns given amount of LP tokens from the program toll wallet.
+//! The pool's reserves stay untouched, therefore each remaining LP token is
+//! worth proportionally more of each reserve, ie. the toll is donated to the
+//! liquidity providers. No new LP tokens are minted.
+//!
+//! This is different from redeeming the toll via
+//! [`crate::endpoints::redeem_liquidity`], which withdraws tokens from the
+//! reserves.
+
+use crate::misc::print_lp_supply;
+use crate::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct ReinvestProgramToll<'info> {
+    /// Authority over the program toll wallet.
+    pub program_toll_authority: Signer<'info>,
+    #[account(
+        seeds = [ProgramToll::PDA_SEED],
+        bump,
+        constraint = program_toll.authority == program_toll_authority.key()
+            @ err::acc("Signer must be program toll authority"),
+    )]
+    pub program_toll: Account<'info, ProgramToll>,
+    pub pool: Account<'info, Pool>,
+    #[account(
+        mut,
+        constraint = lp_mint.key() == pool.mint
+            @ err::acc("LP mint must match pool's mint"),
+    )]
+    pub lp_mint: Account<'info, Mint>,
+    /// LP tokens are burned FROM this wallet.
+    #[account(
+        mut,
+        constraint = program_toll_wallet.key() == pool.program_toll_wallet
+            @ err::acc("Program toll wallet mismatch"),
+        constraint = program_toll_wallet.mint == pool.mint
+            @ err::acc("Toll wallet must be of LP mint"),
+    )]
+    pub program_toll_wallet: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handle(
+    ctx: Context<ReinvestProgramToll>,
+    lp_tokens_to_burn: TokenAmount,
+) -> Result<()> {
+    let accs = ctx.accounts;
+
+    if lp_tokens_to_burn.amount == 0 {
+        return Err(error!(err::arg("LP tokens to burn mustn't be zero")));
+    }
+
+    if lp_tokens_to_burn.amount > accs.program_toll_wallet.amount {
+        return Err(error!(AmmError::InvalidLpTokenAmount));
+    }
+
+    // if all LP tokens were burned, the reserves would be stuck in the pool
+    // forever
+    if lp_tokens_to_burn.amount >= accs.lp_mint.supply {
+        return Err(error!(err::arg(
+            "Cannot burn the whole LP supply of the pool"
+        )));
+    }
+
+    token::burn(accs.as_burn_toll_ctx(), lp_tokens_to_burn.amount)?;
+
+    print_lp_supply(&mut accs.lp_mint)?;
+
+    Ok(())
+}
+
+impl<'info> ReinvestProgramToll<'info> {
+    fn as_burn_toll_ctx(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, token::Burn<'info>> {
+        let cpi_accounts = token::Burn {
+            mint: self.lp_mint.to_account_info(),
+            from: self.program_toll_wallet.to_account_info(),
+            authority: self.program_toll_authority.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
@@ -0,0 +1,68 @@
+//! Configures how a stable pool normalizes the reserves of mints with
+//! different decimals, see [`Pool::decimal_scales`]. For example, a pool of
+//! USDC with 6 decimals and a token with 9 decimals sets the scales to
+//! `[3, 0, 0, 0]` so that both reserves are compared with 9 decimals.
+//!
+//! Like [`crate::endpoints::set_pool_weights`], the admin calls this endpoint
+//! in the same transaction as [`crate::endpoints::create_pool`]. Changing the
+//! scales of a pool with liquidity would change the price, therefore the
+//! scales can only be set while there's no liquidity in the pool.
+
+use crate::prelude::*;
+use anchor_spl::token::Mint;
+
+/// Scaling a reserve by more than this many decimals would leave too little
+/// room in [`Decimal`] for the invariant computation.
+pub const MAX_DECIMAL_SCALE: u8 = 18;
+
+#[derive(Accounts)]
+pub struct SetPoolDecimalScales<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        constraint = pool.admin.key() == admin.key()
+            @ err::acc("The signer must match pool's admin"),
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(
+        constraint = lp_mint.key() == pool.mint
+            @ err::acc("LP mint must match pool's mint"),
+        constraint = lp_mint.supply == 0
+            @ err::acc("Decimal scales must be set before any deposit"),
+    )]
+    pub lp_mint: Account<'info, Mint>,
+}
+
+pub fn handle(
+    ctx: Context<SetPoolDecimalScales>,
+    decimal_scales: [u8; 4],
+) -> Result<()> {
+    let accs = ctx.accounts;
+
+    if !matches!(accs.pool.curve, Curve::Stable { .. }) {
+        return Err(error!(err::acc(
+            "Decimal scales are only used by stable pools"
+        )));
+    }
+
+    let dimension = accs.pool.dimension as usize;
+    for (index, scale) in decimal_scales.iter().enumerate() {
+        if index >= dimension && *scale != 0 {
+            return Err(error!(err::arg(format!(
+                "Pool has {} reserves, scale #{} must be zero",
+                dimension, index
+            ))));
+        }
+
+        if *scale > MAX_DECIMAL_SCALE {
+            return Err(error!(err::arg(format!(
+                "Decimal scale can be at most {}",
+                MAX_DECIMAL_SCALE
+            ))));
+        }
+    }
+
+    accs.pool.decimal_scales = decimal_scales;
+
+    Ok(())
+}
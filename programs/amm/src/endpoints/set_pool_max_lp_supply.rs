@@ -0,0 +1,48 @@
+//! Admin of a pool can cap the LP supply, eg. to limit the TVL during a
+//! guarded launch, see [`Pool::max_lp_supply`].
+//!
+//! While there's no liquidity in the pool, any cap can be set. Afterwards the
+//! cap can only be raised or removed with zero, so that the admin cannot
+//! lower it under the liquidity providers who are waiting to deposit.
+
+use crate::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+pub struct SetPoolMaxLpSupply<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        constraint = pool.admin.key() == admin.key()
+            @ err::acc("The signer must match pool's admin"),
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(
+        constraint = lp_mint.key() == pool.mint
+            @ err::acc("LP mint must match pool's mint"),
+    )]
+    pub lp_mint: Account<'info, Mint>,
+}
+
+pub fn handle(
+    ctx: Context<SetPoolMaxLpSupply>,
+    max_lp_supply: TokenAmount,
+) -> Result<()> {
+    let accs = ctx.accounts;
+
+    let has_liquidity = accs.lp_mint.supply > 0;
+    let current_max = accs.pool.max_lp_supply;
+    let is_raised_or_removed = max_lp_supply.amount == 0
+        || (current_max.amount != 0 && max_lp_supply >= current_max);
+    if has_liquidity && !is_raised_or_removed {
+        return Err(error!(err::arg(format!(
+            "Once the pool has liquidity, the maximum LP supply of {} can \
+            only be raised or removed",
+            current_max.amount
+        ))));
+    }
+
+    accs.pool.max_lp_supply = max_lp_supply;
+
+    Ok(())
+}
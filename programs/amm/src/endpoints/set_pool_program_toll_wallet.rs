@@ -0,0 +1,43 @@
+//! The program toll authority can point a pool's program toll to a different
+//! wallet, e.g. if the original wallet has been compromised. All subsequent
+//! swaps mint the toll into the new wallet, see [`crate::endpoints::swap`].
+//!
+//! The new wallet is subject to the same constraints as the one provided on
+//! pool creation, see [`crate::endpoints::create_pool`].
+
+use crate::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+#[derive(Accounts)]
+pub struct SetPoolProgramTollWallet<'info> {
+    pub program_toll_authority: Signer<'info>,
+    #[account(
+        seeds = [ProgramToll::PDA_SEED],
+        bump,
+        constraint = program_toll.authority == program_toll_authority.key()
+            @ err::acc("Signer must be program toll authority"),
+    )]
+    pub program_toll: Account<'info, ProgramToll>,
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    #[account(
+        constraint = new_program_toll_wallet.mint == pool.mint
+            @ err::acc("Toll wallet must be of LP mint"),
+        constraint = new_program_toll_wallet.owner == program_toll.authority
+            @ err::acc(
+                "Toll wallet authority must match \
+                program toll authority"
+            ),
+        constraint = new_program_toll_wallet.key() != pool.program_toll_wallet
+            @ err::acc("Pool already uses this toll wallet"),
+    )]
+    pub new_program_toll_wallet: Account<'info, TokenAccount>,
+}
+
+pub fn handle(ctx: Context<SetPoolProgramTollWallet>) -> Result<()> {
+    let accs = ctx.accounts;
+
+    accs.pool.program_toll_wallet = accs.new_program_toll_wallet.key();
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+//! The program toll authority can point the pool's
+//! [`ReferralConfig::default_recipient`] to a different LP wallet, e.g. when
+//! the protocol delegates the referral program to a partner. The pool's admin
+//! cannot, because the default referral share is carved out of the program
+//! toll. Swaps routed through
+//! [`crate::endpoints::swap::handle_with_default_referral`] then mint the
+//! referral share into the new wallet.
+//!
+//! The referral share itself cannot be changed.
+
+use crate::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+#[derive(Accounts)]
+pub struct SetPoolReferralDefaultRecipient<'info> {
+    pub program_toll_authority: Signer<'info>,
+    #[account(
+        seeds = [ProgramToll::PDA_SEED],
+        bump,
+        constraint = program_toll.authority == program_toll_authority.key()
+            @ err::acc("Signer must be program toll authority"),
+    )]
+    pub program_toll: Account<'info, ProgramToll>,
+    pub pool: Account<'info, Pool>,
+    #[account(
+        mut,
+        seeds = [ReferralConfig::PDA_PREFIX, pool.key().as_ref()],
+        bump,
+    )]
+    pub referral_config: Account<'info, ReferralConfig>,
+    #[account(
+        constraint = new_default_referral_wallet.mint == pool.mint
+            @ err::acc("Default referral wallet must be of LP mint"),
+    )]
+    pub new_default_referral_wallet: Account<'info, TokenAccount>,
+}
+
+pub fn handle(ctx: Context<SetPoolReferralDefaultRecipient>) -> Result<()> {
+    let accs = ctx.accounts;
+
+    accs.referral_config.default_recipient =
+        accs.new_default_referral_wallet.key();
+
+    Ok(())
+}
@@ -1,5 +1,12 @@
 //! Admin of a pool can change the swap fee to a maximum of
 //! [`consts::MAX_SWAP_FEE`].
+//!
+//! A decrease of the fee takes effect immediately and cancels any pending
+//! increase. An increase only takes effect after the pool's
+//! [`Pool::swap_fee_timelock_slots`], so that liquidity providers and traders
+//! are not surprised by it. Until then, it's stored as the pool's pending swap
+//! fee. While there are no tokens in the pool's reserves, any change takes
+//! effect immediately.
 
 use crate::prelude::*;
 
@@ -24,7 +31,31 @@ pub fn handle(ctx: Context<SetPoolSwapFee>, fee: Permillion) -> Result<()> {
         ),)));
     }
 
-    accs.pool.swap_fee = fee;
+    let current_slot = Slot::current()?;
+    // a pending fee which is already effective is not overwritten below
+    accs.pool.apply_pending_swap_fee(current_slot);
+
+    let has_liquidity =
+        accs.pool.reserves().iter().any(|r| r.tokens.amount > 0);
+    if has_liquidity && fee > accs.pool.swap_fee {
+        let effective_at = Slot::new(
+            current_slot
+                .slot
+                .checked_add(accs.pool.swap_fee_timelock_slots)
+                .ok_or(AmmError::MathOverflow)?,
+        );
+        msg!(
+            "Swap fee of {} permillion takes effect at slot {}",
+            fee.permillion,
+            effective_at.slot
+        );
+
+        accs.pool.pending_swap_fee = fee;
+        accs.pool.pending_swap_fee_effective_at = effective_at;
+    } else {
+        accs.pool.swap_fee = fee;
+        accs.pool.cancel_pending_swap_fee();
+    }
 
     Ok(())
 }
@@ -0,0 +1,41 @@
+//! Admin of a pool can configure for how many slots a swap fee increase stays
+//! pending, see [`crate::endpoints::set_pool_swap_fee`], between
+//! [`consts::MIN_SWAP_FEE_TIMELOCK_SLOTS`] and
+//! [`consts::MAX_SWAP_FEE_TIMELOCK_SLOTS`].
+//!
+//! The new timelock only applies to the following increases. A pending
+//! increase keeps the slot at which it takes effect.
+
+use crate::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetPoolSwapFeeTimelock<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        constraint = pool.admin.key() == admin.key()
+            @ err::acc("The signer must match pool's admin"),
+    )]
+    pub pool: Account<'info, Pool>,
+}
+
+pub fn handle(
+    ctx: Context<SetPoolSwapFeeTimelock>,
+    timelock_slots: u64,
+) -> Result<()> {
+    let accs = ctx.accounts;
+
+    let bounds = consts::MIN_SWAP_FEE_TIMELOCK_SLOTS
+        ..=consts::MAX_SWAP_FEE_TIMELOCK_SLOTS;
+    if !bounds.contains(&timelock_slots) {
+        return Err(error!(err::arg(format!(
+            "Swap fee timelock must be between {} and {} slots",
+            consts::MIN_SWAP_FEE_TIMELOCK_SLOTS,
+            consts::MAX_SWAP_FEE_TIMELOCK_SLOTS
+        ))));
+    }
+
+    accs.pool.swap_fee_timelock_slots = timelock_slots;
+
+    Ok(())
+}
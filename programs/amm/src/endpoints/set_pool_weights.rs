@@ -0,0 +1,63 @@
+//! Turns a freshly created pool with two reserves into a weighted pool, see
+//! [`Curve::Weighted`]. For example, an index-style pool can hold 80% of its
+//! value in the first reserve and 20% in the second one.
+//!
+//! The admin calls this endpoint in the same transaction as
+//! [`crate::endpoints::create_pool`]. Changing the curve of a pool with
+//! liquidity would change the price, therefore the weights can only be set
+//! while there's no liquidity in the pool.
+//!
+//! If the [`CurveConstraints`] exist, both weights must be within their
+//! bounds.
+
+use crate::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+pub struct SetPoolWeights<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        constraint = pool.admin.key() == admin.key()
+            @ err::acc("The signer must match pool's admin"),
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(
+        constraint = lp_mint.key() == pool.mint
+            @ err::acc("LP mint must match pool's mint"),
+        constraint = lp_mint.supply == 0
+            @ err::acc("Weights must be set before any deposit"),
+    )]
+    pub lp_mint: Account<'info, Mint>,
+    /// CHECK: The curve constraints might not be initialized, and that's fine,
+    /// we are conditionally parsing this account and only if it's valid
+    /// will we bound the weights.
+    #[account(
+        seeds = [CurveConstraints::PDA_SEED],
+        bump,
+    )]
+    pub curve_constraints: AccountInfo<'info>,
+}
+
+pub fn handle(
+    ctx: Context<SetPoolWeights>,
+    weight_a: u8,
+    weight_b: u8,
+) -> Result<()> {
+    let accs = ctx.accounts;
+
+    let curve = Curve::Weighted { weight_a, weight_b };
+    curve.validate(accs.pool.dimension)?;
+
+    let is_curve_constraints_created =
+        accs.curve_constraints.owner == &crate::ID;
+    if is_curve_constraints_created {
+        // we've already verified it's the correct constraints bcs of the pda
+        Account::<CurveConstraints>::try_from(&accs.curve_constraints)?
+            .validate_curve(&curve)?;
+    }
+
+    accs.pool.curve = curve;
+
+    Ok(())
+}
@@ -5,8 +5,24 @@
 //! The user pays a fee for the swap, which is scaled down by the [`Discount`]
 //! associated with this user. A fraction of the swap fee is sent to program
 //! owner's wallet in LP tokens.
+//!
+//! If the pool has an active [`FeeRebate`], a share of the swap fee is kept by
+//! the user, ie. less sell tokens are transferred into the vault. The fee
+//! rebate account is optional and is provided as the first remaining account,
+//! so that clients which don't know about rebates keep working. Without it,
+//! no rebate is applied.
+//!
+//! If the pool was created with a [`ReferralConfig`], swaps can be routed
+//! through [`handle_with_referral`], in which case a share of the program toll
+//! is minted to the referrer's LP wallet instead. Swaps which weren't referred
+//! by anyone in particular can be routed through
+//! [`handle_with_default_referral`], which mints the share to the pool's
+//! default recipient.
+//!
+//! Each swap adds its fees to the pool's running totals and logs the new
+//! totals, see [`Pool::record_swap_fees`].
 
-use crate::misc::print_lp_supply;
+use crate::misc::{print_lifetime_fees, print_lp_supply};
 use crate::*;
 use anchor_spl::token::{self, Mint, Token, TokenAccount};
 use std::collections::BTreeMap;
@@ -34,6 +50,8 @@ pub struct Swap<'info> {
     /// Tokens to SELL flow FROM this account.
     #[account(
         mut,
+        constraint = sell_wallet.key() != buy_wallet.key()
+            @ err::acc("Sell wallet mustn't be the same account as buy wallet"),
         constraint = sell_wallet.mint != buy_wallet.mint
             @ err::acc("Mint to swap from mustn't equal the mint to swap to"),
         constraint = sell_wallet.mint == sell_vault.mint
@@ -80,10 +98,45 @@ pub struct Swap<'info> {
     pub token_program: Program<'info, Token>,
 }
 
-/// 1. Calculates swap fee and how many tokens should the user get in return for
-/// the sell tokens.
+#[derive(Accounts)]
+pub struct SwapWithReferral<'info> {
+    pub swap: Swap<'info>,
+    #[account(
+        seeds = [ReferralConfig::PDA_PREFIX, swap.pool.key().as_ref()],
+        bump,
+    )]
+    pub referral_config: Account<'info, ReferralConfig>,
+    /// Referrer's share of the program toll is minted INTO this wallet.
+    #[account(
+        mut,
+        constraint = referrer_wallet.mint == swap.pool.mint
+            @ err::acc("Referrer wallet must be of LP mint"),
+    )]
+    pub referrer_wallet: Box<Account<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct SwapWithDefaultReferral<'info> {
+    pub swap: Swap<'info>,
+    #[account(
+        seeds = [ReferralConfig::PDA_PREFIX, swap.pool.key().as_ref()],
+        bump,
+    )]
+    pub referral_config: Account<'info, ReferralConfig>,
+    /// Referrer's share of the program toll is minted INTO this wallet.
+    #[account(
+        mut,
+        constraint = default_referral_wallet.key()
+            == referral_config.default_recipient
+            @ err::acc("Wallet must be referral config's default recipient"),
+    )]
+    pub default_referral_wallet: Box<Account<'info, TokenAccount>>,
+}
+
+/// 1. Calculates swap fee, fee rebate and how many tokens should the user get
+/// in return for the sell tokens.
 ///
-/// 2. Transfer the sold tokens to the vault
+/// 2. Transfer the sold tokens minus the fee rebate to the vault
 ///
 /// 3. Transfers the bought tokens to the user
 ///
@@ -92,9 +145,140 @@ pub fn handle(
     ctx: Context<Swap>,
     sell: TokenAmount,
     min_buy: TokenAmount,
+) -> Result<()> {
+    swap_and_pay_toll(ctx, sell, min_buy, None, None)
+}
+
+/// Same as [`handle`], but additionally the bought tokens valued in sell
+/// tokens at the pool's spot price after the swap must be worth at least
+/// `min_round_trip_bps` basis points of the sold tokens, see
+/// [`check_round_trip_value`].
+///
+/// This guards against swaps in which most of the value is lost, e.g. because
+/// the reserves are so imbalanced that the bought amount is rounded down to a
+/// fraction of what was sold.
+pub fn handle_with_round_trip_guard(
+    ctx: Context<Swap>,
+    sell: TokenAmount,
+    min_buy: TokenAmount,
+    min_round_trip_bps: u64,
+) -> Result<()> {
+    swap_and_pay_toll(ctx, sell, min_buy, None, Some(min_round_trip_bps))
+}
+
+/// Same as [`handle`], but the referrer gets [`ReferralConfig::share`] of the
+/// program toll.
+pub fn handle_with_referral<'info>(
+    ctx: Context<'_, '_, '_, 'info, SwapWithReferral<'info>>,
+    sell: TokenAmount,
+    min_buy: TokenAmount,
+) -> Result<()> {
+    let Context {
+        program_id,
+        accounts: accs,
+        remaining_accounts,
+        bumps,
+    } = ctx;
+
+    let referral = Referral {
+        share: accs.referral_config.share,
+        wallet: accs.referrer_wallet.to_account_info(),
+    };
+
+    swap_and_pay_toll(
+        Context::new(program_id, &mut accs.swap, remaining_accounts, bumps),
+        sell,
+        min_buy,
+        Some(referral),
+        None,
+    )
+}
+
+/// Same as [`handle_with_referral`], but for swaps which weren't referred by
+/// anyone in particular. The referrer's share goes to the pool's
+/// [`ReferralConfig::default_recipient`].
+pub fn handle_with_default_referral<'info>(
+    ctx: Context<'_, '_, '_, 'info, SwapWithDefaultReferral<'info>>,
+    sell: TokenAmount,
+    min_buy: TokenAmount,
+) -> Result<()> {
+    let Context {
+        program_id,
+        accounts: accs,
+        remaining_accounts,
+        bumps,
+    } = ctx;
+
+    let referral = Referral {
+        share: accs.referral_config.share,
+        wallet: accs.default_referral_wallet.to_account_info(),
+    };
+
+    swap_and_pay_toll(
+        Context::new(program_id, &mut accs.swap, remaining_accounts, bumps),
+        sell,
+        min_buy,
+        Some(referral),
+        None,
+    )
+}
+
+/// Breakdown of a swap as calculated by [`calculate_swap_quote`]. It's written
+/// as return data by [`crate::endpoints::quote_swap`].
+#[derive(
+    AnchorDeserialize,
+    AnchorSerialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+)]
+pub struct SwapQuote {
+    /// How many buy tokens does the user receive.
+    pub bought: TokenAmount,
+    /// Swap fee in sell tokens after discount and rebate. It stays in the
+    /// pool's reserve and a share of it is paid as the program toll.
+    pub swap_fee: TokenAmount,
+    /// How many sell tokens are kept by the user thanks to the pool's
+    /// [`FeeRebate`].
+    pub fee_rebate: TokenAmount,
+    /// LP tokens minted to the pool's program toll wallet.
+    pub program_toll: TokenAmount,
+    /// LP tokens minted to the referrer's wallet, taken out of the program
+    /// toll. Zero unless the swap is routed through [`handle_with_referral`].
+    pub referral: TokenAmount,
+}
+
+impl SwapQuote {
+    /// Moves the referrer's share out of the program toll.
+    pub fn with_referral(self, share: Permillion) -> Result<Self> {
+        let toll =
+            TokenAmount::new(self.program_toll.amount + self.referral.amount);
+        let referral = calculate_referral_share(toll, share)?;
+
+        Ok(Self {
+            program_toll: TokenAmount::new(toll.amount - referral.amount),
+            referral,
+            ..self
+        })
+    }
+}
+
+struct Referral<'info> {
+    share: Permillion,
+    wallet: AccountInfo<'info>,
+}
+
+fn swap_and_pay_toll<'info>(
+    ctx: Context<'_, '_, '_, '_, Swap<'info>>,
+    sell: TokenAmount,
+    min_buy: TokenAmount,
+    referral: Option<Referral<'info>>,
+    min_round_trip_bps: Option<u64>,
 ) -> Result<()> {
     let accs = ctx.accounts;
-    let sell_mint = accs.sell_vault.mint;
 
     if sell.amount == 0 {
         return Err(error!(err::arg("Sell amount mustn't be zero")));
@@ -104,22 +288,29 @@ pub fn handle(
     // 1.
     //
 
-    let swap_fee =
-        calculate_swap_fee(sell, accs.pool.swap_fee, &accs.discount)?;
-
-    // swap fee is a fraction of the sell amount
-    let tokens_to_swap = TokenAmount::new(sell.amount - swap_fee.amount);
+    let fee_rebate =
+        fee_rebate_account(accs.pool.key(), ctx.remaining_accounts)?;
     // this also updates the reserves' balances
-    let bought =
-        accs.pool
-            .swap(sell_mint, tokens_to_swap, accs.buy_vault.mint)?;
-    // We must explicitly update the pool's state as swap fee was subtracted
-    // from the sell amount. However, the swap fee should still be considered
-    // when depositing or withdrawing.
-    accs.pool
-        .reserve_mut(sell_mint)
-        .unwrap() // the mint is part of the pool as per constraints and swap fn
-        .add_tokens(swap_fee)?;
+    let quote = calculate_swap_quote(
+        &mut accs.pool,
+        accs.sell_vault.mint,
+        sell,
+        accs.buy_vault.mint,
+        &accs.discount,
+        fee_rebate,
+        accs.lp_mint.supply.into(),
+    )?;
+    let quote = match &referral {
+        Some(Referral { share, .. }) => quote.with_referral(*share)?,
+        None => quote,
+    };
+    let SwapQuote {
+        bought,
+        swap_fee,
+        fee_rebate,
+        program_toll,
+        referral: referral_in_lp_tokens,
+    } = quote;
 
     if min_buy > bought {
         msg!(
@@ -131,6 +322,18 @@ pub fn handle(
         return Err(error!(AmmError::SlippageExceeded));
     }
 
+    if let Some(min_round_trip_bps) = min_round_trip_bps {
+        // the pool's reserves have already been updated by the quote
+        check_round_trip_value(
+            &accs.pool,
+            accs.sell_vault.mint,
+            sell,
+            accs.buy_vault.mint,
+            bought,
+            min_round_trip_bps,
+        )?;
+    }
+
     let pda_seeds = &[
         Pool::SIGNER_PDA_PREFIX,
         &accs.pool.key().to_bytes()[..],
@@ -140,7 +343,10 @@ pub fn handle(
     //
     // 2.
     //
-    token::transfer(accs.as_transfer_sold_tokens_to_vault_ctx(), sell.amount)?;
+    token::transfer(
+        accs.as_transfer_sold_tokens_to_vault_ctx(),
+        sell.amount - fee_rebate.amount,
+    )?;
 
     //
     // 3.
@@ -154,23 +360,35 @@ pub fn handle(
     //
     // 4.
     //
-    let toll_in_lp_tokens = calculate_toll_in_lp_tokens(
-        &accs.pool,
-        swap_fee,
-        sell_mint,
-        accs.lp_mint.supply.into(),
-    )?;
-    if let Some(toll_in_lp_tokens) = toll_in_lp_tokens {
+    // if the toll is too small to be represented in LP tokens, we skip it
+    if program_toll.amount + referral_in_lp_tokens.amount > 0 {
         // this will lower the value of the LP token mint by such an amount
         // which equals to the value of the toll
         token::mint_to(
-            accs.as_pay_toll_ctx().with_signer(&[&pda_seeds[..]]),
-            toll_in_lp_tokens.amount,
+            accs.as_pay_toll_ctx(accs.program_toll_wallet.to_account_info())
+                .with_signer(&[&pda_seeds[..]]),
+            program_toll.amount,
         )?;
+
+        if let Some(Referral { wallet, .. }) = referral {
+            if referral_in_lp_tokens.amount > 0 {
+                token::mint_to(
+                    accs.as_pay_toll_ctx(wallet).with_signer(&[&pda_seeds[..]]),
+                    referral_in_lp_tokens.amount,
+                )?;
+            }
+        }
     }
 
     print_lp_supply(&mut accs.lp_mint)?;
 
+    accs.pool.record_swap_fees(
+        accs.sell_vault.mint,
+        swap_fee,
+        TokenAmount::new(program_toll.amount + referral_in_lp_tokens.amount),
+    )?;
+    print_lifetime_fees(&accs.pool, accs.sell_vault.mint);
+
     // we need to update the invariant because fees have been taken, therefore
     // even though the invariant applies to swaps, it is changed during each
     // trade because of the collected fees
@@ -181,14 +399,210 @@ pub fn handle(
     Ok(())
 }
 
+/// Shares which scale the swap fee of a single swap. They're read from the
+/// user's [`Discount`] and the pool's [`FeeRebate`] accounts by
+/// [`calculate_swap_quote`], and are zero if the account doesn't exist or
+/// doesn't apply at the time of the swap.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SwapFeeShares {
+    /// Scales the swap fee down, see [`Discount::amount`].
+    pub discount: Permillion,
+    /// Share of the swap fee which the user keeps, see [`FeeRebate::share`].
+    pub fee_rebate: Permillion,
+}
+
+/// Calculates the swap fee, fee rebate, how many tokens should the user get in
+/// return for the sell tokens and the program toll in LP tokens.
+///
+/// The pool's reserves and swap fee, if a pending one became effective, are
+/// updated as if the swap happened, therefore pass a clone of the pool if you
+/// only want to know the quote.
+pub fn calculate_swap_quote(
+    pool: &mut Pool,
+    sell_mint: Pubkey,
+    sell: TokenAmount,
+    buy_mint: Pubkey,
+    discount: &AccountInfo,
+    fee_rebate: Option<&AccountInfo>,
+    lp_supply: TokenAmount,
+) -> Result<SwapQuote> {
+    let shares = SwapFeeShares {
+        discount: active_discount(discount)?,
+        fee_rebate: fee_rebate
+            .map(active_fee_rebate_share)
+            .transpose()?
+            .unwrap_or_default(),
+    };
+
+    simulate_swap(
+        pool,
+        Slot::current()?,
+        sell_mint,
+        sell,
+        buy_mint,
+        shares,
+        lp_supply,
+    )
+}
+
+/// Same as [`calculate_swap_quote`], but doesn't read any accounts nor the
+/// clock, therefore it can be used off-chain, eg. in a simulator. The
+/// processor calls this function too, so the results always agree.
+///
+/// The pool is updated as if the swap happened at the slot `now`.
+pub fn simulate_swap(
+    pool: &mut Pool,
+    now: Slot,
+    sell_mint: Pubkey,
+    sell: TokenAmount,
+    buy_mint: Pubkey,
+    shares: SwapFeeShares,
+    lp_supply: TokenAmount,
+) -> Result<SwapQuote> {
+    // swaps use the pending fee once its timelock has elapsed
+    if pool.has_pending_swap_fee() {
+        pool.apply_pending_swap_fee(now);
+    }
+
+    let swap_fee =
+        calculate_discounted_swap_fee(sell, pool.swap_fee, shares.discount)?;
+    // the rebate is always less than the swap fee
+    let fee_rebate = calculate_rebate_of_swap_fee(swap_fee, shares.fee_rebate)?;
+
+    // swap fee is a fraction of the sell amount
+    let tokens_to_swap = TokenAmount::new(sell.amount - swap_fee.amount);
+    // the rebated part of the swap fee never leaves the user's wallet
+    let swap_fee = TokenAmount::new(swap_fee.amount - fee_rebate.amount);
+    // this also updates the reserves' balances
+    let bought = pool.swap(sell_mint, tokens_to_swap, buy_mint)?;
+    // We must explicitly update the pool's state as swap fee was subtracted
+    // from the sell amount. However, the swap fee should still be considered
+    // when depositing or withdrawing.
+    pool.reserve_mut(sell_mint)
+        .ok_or(AmmError::InvariantViolation)? // checked by the swap fn
+        .add_tokens(swap_fee)?;
+
+    let program_toll =
+        calculate_toll_in_lp_tokens(pool, swap_fee, sell_mint, lp_supply)?
+            .unwrap_or_else(|| TokenAmount::new(0));
+
+    Ok(SwapQuote {
+        bought,
+        swap_fee,
+        fee_rebate,
+        program_toll,
+        referral: TokenAmount::new(0),
+    })
+}
+
+/// Same as [`handle`], but instead of an absolute minimum amount of buy tokens
+/// the user provides the maximum slippage in basis points. The minimum is
+/// derived from the fee-free spot price of the pool before the swap.
+///
+/// Note that the swap fee counts towards the slippage.
+///
+/// Because the spot price is read when the swap executes, the minimum moves
+/// together with the pool. A transaction placed before this swap which moves
+/// the price also lowers the minimum, therefore this endpoint does NOT protect
+/// against front-running or sandwich attacks. It only bounds the price impact
+/// of this swap itself. Use [`handle`] with a `min_buy` calculated off-chain
+/// for such protection.
+pub fn handle_with_slippage_bps(
+    ctx: Context<Swap>,
+    sell: TokenAmount,
+    max_slippage_bps: u64,
+) -> Result<()> {
+    let accs = &ctx.accounts;
+    let min_buy = calculate_min_buy_from_slippage_bps(
+        &accs.pool,
+        accs.sell_vault.mint,
+        sell,
+        accs.buy_vault.mint,
+        max_slippage_bps,
+    )?;
+
+    handle(ctx, sell, min_buy)
+}
+
+// `min_buy = floor(spot_buy * (10_000 - max_slippage_bps) / 10_000)`
+pub fn calculate_min_buy_from_slippage_bps(
+    pool: &Pool,
+    sell_mint: Pubkey,
+    sell: TokenAmount,
+    buy_mint: Pubkey,
+    max_slippage_bps: u64,
+) -> Result<TokenAmount> {
+    if max_slippage_bps > consts::MAX_SLIPPAGE_BPS {
+        return Err(error!(err::arg(
+            "Max slippage cannot be more than 10_000 bps"
+        )));
+    }
+
+    let spot_buy = pool.calculate_spot_swap(sell_mint, sell, buy_mint)?;
+
+    let min_buy = TokenAmount::new(
+        Decimal::from(spot_buy.amount)
+            .try_mul(Decimal::from(
+                consts::MAX_SLIPPAGE_BPS - max_slippage_bps,
+            ))?
+            .try_div(Decimal::from(consts::MAX_SLIPPAGE_BPS))?
+            .try_floor()?,
+    );
+
+    Ok(min_buy)
+}
+
+// `spot_sell(bought) >= ceil(sell * min_round_trip_bps / 10_000)`, whereas the
+// spot price is read from the pool after the swap
+pub fn check_round_trip_value(
+    pool_after_swap: &Pool,
+    sell_mint: Pubkey,
+    sell: TokenAmount,
+    buy_mint: Pubkey,
+    bought: TokenAmount,
+    min_round_trip_bps: u64,
+) -> Result<()> {
+    if min_round_trip_bps > consts::MAX_SLIPPAGE_BPS {
+        return Err(error!(err::arg(
+            "Min round trip value cannot be more than 10_000 bps"
+        )));
+    }
+
+    let round_trip = if bought.amount == 0 {
+        TokenAmount::new(0)
+    } else {
+        pool_after_swap.calculate_spot_swap(buy_mint, bought, sell_mint)?
+    };
+
+    let min_round_trip: u64 = Decimal::from(sell.amount)
+        .try_mul(Decimal::from(min_round_trip_bps))?
+        .try_div(Decimal::from(consts::MAX_SLIPPAGE_BPS))?
+        .try_ceil()?;
+
+    if round_trip.amount < min_round_trip {
+        msg!(
+            "For {} would receive {} worth {} after the swap, \
+            but requested minimum worth of {}",
+            sell.amount,
+            bought.amount,
+            round_trip.amount,
+            min_round_trip
+        );
+        return Err(error!(AmmError::SlippageExceeded));
+    }
+
+    Ok(())
+}
+
 impl<'info> Swap<'info> {
     fn as_pay_toll_ctx(
         &self,
+        to: AccountInfo<'info>,
     ) -> CpiContext<'_, '_, '_, 'info, token::MintTo<'info>> {
         let cpi_accounts = token::MintTo {
             authority: self.pool_signer.to_account_info(),
             mint: self.lp_mint.to_account_info(),
-            to: self.program_toll_wallet.to_account_info(),
+            to,
         };
         let cpi_program = self.token_program.to_account_info();
         CpiContext::new(cpi_program, cpi_accounts)
@@ -225,20 +639,26 @@ pub fn calculate_swap_fee(
     swap_fee_share: Permillion,
     discount: &AccountInfo,
 ) -> Result<TokenAmount> {
-    let is_discount_created = discount.owner == &crate::ID;
+    calculate_discounted_swap_fee(
+        sell,
+        swap_fee_share,
+        active_discount(discount)?,
+    )
+}
 
+/// Same as [`calculate_swap_fee`], but with the discount already read from the
+/// user's [`Discount`] account.
+pub fn calculate_discounted_swap_fee(
+    sell: TokenAmount,
+    swap_fee_share: Permillion,
+    discount: Permillion,
+) -> Result<TokenAmount> {
     let swap_fee_share: Decimal = swap_fee_share.into();
-    let swap_fee_share = if is_discount_created {
-        // we've already verified it's the correct discount bcs of the pda
-        let discount = Account::<Discount>::try_from(discount)?;
-        if !discount.does_apply()? {
-            swap_fee_share
-        } else {
-            let discount: Decimal = discount.amount.into();
-            swap_fee_share.try_sub(swap_fee_share.try_mul(discount)?)?
-        }
-    } else {
+    let swap_fee_share = if discount.permillion == 0 {
         swap_fee_share
+    } else {
+        let discount: Decimal = discount.into();
+        swap_fee_share.try_sub(swap_fee_share.try_mul(discount)?)?
     };
 
     // total swap fee, ie. liquidity providers fee + toll fee
@@ -251,6 +671,100 @@ pub fn calculate_swap_fee(
     Ok(swap_fee)
 }
 
+// `rebate = floor(swap_fee * rebate_share)`, or zero if the pool has no
+// active fee rebate
+pub fn calculate_fee_rebate(
+    swap_fee: TokenAmount,
+    fee_rebate: &AccountInfo,
+) -> Result<TokenAmount> {
+    calculate_rebate_of_swap_fee(swap_fee, active_fee_rebate_share(fee_rebate)?)
+}
+
+/// Same as [`calculate_fee_rebate`], but with the share already read from the
+/// pool's [`FeeRebate`] account.
+pub fn calculate_rebate_of_swap_fee(
+    swap_fee: TokenAmount,
+    fee_rebate_share: Permillion,
+) -> Result<TokenAmount> {
+    if fee_rebate_share.permillion == 0 {
+        return Ok(TokenAmount::new(0));
+    }
+
+    Ok(TokenAmount::new(
+        Decimal::from(swap_fee)
+            .try_mul(Decimal::from(fee_rebate_share))?
+            .try_floor()?,
+    ))
+}
+
+// zero if the user has no discount or it doesn't apply anymore
+fn active_discount(discount: &AccountInfo) -> Result<Permillion> {
+    let is_discount_created = discount.owner == &crate::ID;
+    if !is_discount_created {
+        return Ok(Permillion::default());
+    }
+
+    // we've already verified it's the correct discount bcs of the pda
+    let discount = Account::<Discount>::try_from(discount)?;
+    if !discount.does_apply()? {
+        return Ok(Permillion::default());
+    }
+
+    Ok(discount.amount)
+}
+
+/// Returns the first remaining account if provided. It must be the pool's
+/// [`FeeRebate`] PDA, but it doesn't have to be initialized.
+fn fee_rebate_account<'a, 'info>(
+    pool: Pubkey,
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> Result<Option<&'a AccountInfo<'info>>> {
+    let fee_rebate = match remaining_accounts.first() {
+        Some(fee_rebate) => fee_rebate,
+        None => return Ok(None),
+    };
+
+    let (expected_fee_rebate, _) = Pubkey::find_program_address(
+        &[FeeRebate::PDA_PREFIX, pool.as_ref()],
+        &crate::ID,
+    );
+    if fee_rebate.key() != expected_fee_rebate {
+        return Err(error!(err::acc(
+            "The first remaining account must be the pool's fee rebate"
+        )));
+    }
+
+    Ok(Some(fee_rebate))
+}
+
+// zero if the pool has no fee rebate or it doesn't apply at this slot
+fn active_fee_rebate_share(fee_rebate: &AccountInfo) -> Result<Permillion> {
+    let is_fee_rebate_created = fee_rebate.owner == &crate::ID;
+    if !is_fee_rebate_created {
+        return Ok(Permillion::default());
+    }
+
+    // the caller has already verified it's the correct fee rebate pda
+    let fee_rebate = Account::<FeeRebate>::try_from(fee_rebate)?;
+    if !fee_rebate.does_apply()? {
+        return Ok(Permillion::default());
+    }
+
+    Ok(fee_rebate.share)
+}
+
+// `referral = floor(toll * referral_share)`
+pub fn calculate_referral_share(
+    toll_in_lp_tokens: TokenAmount,
+    referral_share: Permillion,
+) -> Result<TokenAmount> {
+    Ok(TokenAmount::new(
+        Decimal::from(toll_in_lp_tokens)
+            .try_mul(Decimal::from(referral_share))?
+            .try_floor()?,
+    ))
+}
+
 // To find out how many LPs should we mint, we pretend to deposit to the pool.
 // We deposit tokens which are in total worth the `swap_fee * toll_share`.
 // Returns the LP amount.
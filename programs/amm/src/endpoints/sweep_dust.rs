@@ -0,0 +1,108 @@
+//! Over time, the pool signer can accumulate small amounts of tokens in token
+//! accounts which are not the pool's reserve vaults, e.g. if someone sends
+//! tokens to an associated token account of the pool signer. Those tokens are
+//! not accounted for in the reserves and cannot be used by the pool.
+//!
+//! Admin of a pool can sweep such dust into a wallet owned by the program toll
+//! authority. Only balances less than a fraction of a whole token can be
+//! swept, see [`consts::MAX_DUST_DENOMINATOR`], and the pool's reserve vaults
+//! are protected.
+
+use crate::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct SweepDust<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        constraint = pool.admin.key() == admin.key()
+            @ err::acc("The signer must match pool's admin"),
+    )]
+    pub pool: Account<'info, Pool>,
+    /// CHECK: UNSAFE_CODES.md#signer
+    #[account(
+        seeds = [Pool::SIGNER_PDA_PREFIX, pool.key().as_ref()],
+        bump
+    )]
+    pub pool_signer: AccountInfo<'info>,
+    #[account(
+        seeds = [ProgramToll::PDA_SEED],
+        bump,
+    )]
+    pub program_toll: Account<'info, ProgramToll>,
+    /// Dust is transferred FROM this wallet.
+    #[account(
+        mut,
+        constraint = dust_wallet.owner == pool_signer.key()
+            @ err::acc("Dust wallet must be owned by the pool signer"),
+        constraint = pool
+            .reserves()
+            .iter()
+            .all(|r| r.vault != dust_wallet.key())
+            @ err::acc("Cannot sweep pool's reserve vault"),
+    )]
+    pub dust_wallet: Account<'info, TokenAccount>,
+    /// Its decimals determine what's considered dust.
+    #[account(
+        constraint = dust_mint.key() == dust_wallet.mint
+            @ err::acc("Dust mint must match dust wallet's mint"),
+    )]
+    pub dust_mint: Account<'info, Mint>,
+    /// Dust is transferred INTO this wallet.
+    #[account(
+        mut,
+        constraint = program_toll_wallet.owner == program_toll.authority
+            @ err::acc("Wallet must be owned by program toll authority"),
+        constraint = program_toll_wallet.mint == dust_wallet.mint
+            @ err::acc("Wallet must be of the same mint as dust wallet"),
+    )]
+    pub program_toll_wallet: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handle(ctx: Context<SweepDust>) -> Result<()> {
+    let accs = ctx.accounts;
+
+    let dust = accs.dust_wallet.amount;
+    if dust == 0 {
+        return Err(error!(err::acc("There's no dust to sweep")));
+    }
+    let max_dust = max_dust_tokens(accs.dust_mint.decimals);
+    if dust >= max_dust {
+        return Err(error!(err::acc(format!(
+            "Only less than {} tokens can be swept, wallet holds {}",
+            max_dust, dust
+        ))));
+    }
+
+    let signer_seeds = &[
+        Pool::SIGNER_PDA_PREFIX,
+        &accs.pool.key().to_bytes()[..],
+        &[*ctx.bumps.get("pool_signer").unwrap()],
+    ];
+    token::transfer(
+        accs.as_sweep_dust_ctx().with_signer(&[&signer_seeds[..]]),
+        dust,
+    )?;
+
+    Ok(())
+}
+
+/// See [`consts::MAX_DUST_DENOMINATOR`].
+fn max_dust_tokens(decimals: u8) -> u64 {
+    10u64.saturating_pow(decimals as u32) / consts::MAX_DUST_DENOMINATOR
+}
+
+impl<'info> SweepDust<'info> {
+    fn as_sweep_dust_ctx(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+        let cpi_accounts = token::Transfer {
+            from: self.dust_wallet.to_account_info(),
+            to: self.program_toll_wallet.to_account_info(),
+            authority: self.pool_signer.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
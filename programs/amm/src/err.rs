@@ -28,6 +28,8 @@ pub enum AmmError {
     InvalidTokenMints,
     #[msg("Invalid lp token amount to burn")]
     InvalidLpTokenAmount,
+    #[msg("Deposit would exceed the pool's maximum LP supply")]
+    MaxLpSupplyExceeded,
 }
 
 pub fn acc(msg: impl Display) -> AmmError {
@@ -38,6 +38,57 @@ pub mod amm {
         endpoints::create_pool::handle(ctx, amplifier)
     }
 
+    /// Creates a pool at a PDA derived from the admin's key and given nonce,
+    /// so that nobody else can initialize that address first.
+    pub fn create_pool_with_nonce(
+        ctx: Context<CreatePoolWithNonce>,
+        amplifier: u64,
+        nonce: u64,
+    ) -> Result<()> {
+        endpoints::create_pool_with_nonce::handle(ctx, amplifier, nonce)
+    }
+
+    pub fn create_pool_lp_mint_and_toll_wallet(
+        ctx: Context<CreatePoolLpMintAndTollWallet>,
+        lp_mint_decimals: u8,
+    ) -> Result<()> {
+        endpoints::create_pool_lp_mint_and_toll_wallet::handle(
+            ctx,
+            lp_mint_decimals,
+        )
+    }
+
+    pub fn create_pool_referral_config(
+        ctx: Context<CreatePoolReferralConfig>,
+        share: Permillion,
+    ) -> Result<()> {
+        endpoints::create_pool_referral_config::handle(ctx, share)
+    }
+
+    pub fn create_pool_mirror(ctx: Context<CreatePoolMirror>) -> Result<()> {
+        endpoints::create_pool_mirror::handle(ctx)
+    }
+
+    pub fn refresh_pool_mirror(ctx: Context<RefreshPoolMirror>) -> Result<()> {
+        endpoints::refresh_pool_mirror::handle(ctx)
+    }
+
+    pub fn put_curve_constraints(
+        ctx: Context<PutCurveConstraints>,
+        min_amplifier: u64,
+        max_amplifier: u64,
+        min_weight: u8,
+        max_weight: u8,
+    ) -> Result<()> {
+        endpoints::put_curve_constraints::handle(
+            ctx,
+            min_amplifier,
+            max_amplifier,
+            min_weight,
+            max_weight,
+        )
+    }
+
     pub fn put_discount(
         ctx: Context<PutDiscount>,
         user: Pubkey,
@@ -47,6 +98,34 @@ pub mod amm {
         endpoints::put_discount::handle(ctx, user, discount_amount, valid_until)
     }
 
+    pub fn put_mint_blocklist(
+        ctx: Context<PutMintBlocklist>,
+        mints: Vec<Pubkey>,
+    ) -> Result<()> {
+        endpoints::put_mint_blocklist::handle(ctx, mints)
+    }
+
+    pub fn put_pool_fee_rebate(
+        ctx: Context<PutPoolFeeRebate>,
+        share: Permillion,
+        starts_at: Slot,
+        ends_at: Slot,
+    ) -> Result<()> {
+        endpoints::put_pool_fee_rebate::handle(ctx, share, starts_at, ends_at)
+    }
+
+    pub fn set_pool_program_toll_wallet(
+        ctx: Context<SetPoolProgramTollWallet>,
+    ) -> Result<()> {
+        endpoints::set_pool_program_toll_wallet::handle(ctx)
+    }
+
+    pub fn set_pool_referral_default_recipient(
+        ctx: Context<SetPoolReferralDefaultRecipient>,
+    ) -> Result<()> {
+        endpoints::set_pool_referral_default_recipient::handle(ctx)
+    }
+
     pub fn set_pool_swap_fee(
         ctx: Context<SetPoolSwapFee>,
         fee: Permillion,
@@ -54,6 +133,41 @@ pub mod amm {
         endpoints::set_pool_swap_fee::handle(ctx, fee)
     }
 
+    pub fn set_pool_swap_fee_timelock(
+        ctx: Context<SetPoolSwapFeeTimelock>,
+        timelock_slots: u64,
+    ) -> Result<()> {
+        endpoints::set_pool_swap_fee_timelock::handle(ctx, timelock_slots)
+    }
+
+    pub fn cancel_pending_swap_fee(
+        ctx: Context<CancelPendingSwapFee>,
+    ) -> Result<()> {
+        endpoints::cancel_pending_swap_fee::handle(ctx)
+    }
+
+    pub fn set_pool_decimal_scales(
+        ctx: Context<SetPoolDecimalScales>,
+        decimal_scales: [u8; 4],
+    ) -> Result<()> {
+        endpoints::set_pool_decimal_scales::handle(ctx, decimal_scales)
+    }
+
+    pub fn set_pool_max_lp_supply(
+        ctx: Context<SetPoolMaxLpSupply>,
+        max_lp_supply: TokenAmount,
+    ) -> Result<()> {
+        endpoints::set_pool_max_lp_supply::handle(ctx, max_lp_supply)
+    }
+
+    pub fn set_pool_weights(
+        ctx: Context<SetPoolWeights>,
+        weight_a: u8,
+        weight_b: u8,
+    ) -> Result<()> {
+        endpoints::set_pool_weights::handle(ctx, weight_a, weight_b)
+    }
+
     pub fn deposit_liquidity<'info>(
         ctx: Context<'_, '_, '_, 'info, DepositLiquidity<'info>>,
         max_amount_tokens: Vec<TokenLimit>,
@@ -61,6 +175,16 @@ pub mod amm {
         endpoints::deposit_liquidity::handle(ctx, max_amount_tokens)
     }
 
+    pub fn deposit_liquidity_and_start_farming<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositLiquidityAndStartFarming<'info>>,
+        max_amount_tokens: Vec<TokenLimit>,
+    ) -> Result<()> {
+        endpoints::deposit_liquidity_and_start_farming::handle(
+            ctx,
+            max_amount_tokens,
+        )
+    }
+
     pub fn redeem_liquidity<'info>(
         ctx: Context<'_, '_, '_, 'info, RedeemLiquidity<'info>>,
         lp_tokens_to_burn: TokenAmount,
@@ -73,6 +197,69 @@ pub mod amm {
         )
     }
 
+    pub fn exit_farm_and_redeem_liquidity<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExitFarmAndRedeemLiquidity<'info>>,
+        min_amount_tokens: Vec<TokenLimit>,
+    ) -> Result<()> {
+        endpoints::exit_farm_and_redeem_liquidity::handle(
+            ctx,
+            min_amount_tokens,
+        )
+    }
+
+    /// Writes the [`CurveInvariant`] of the pool as return data.
+    pub fn get_curve_invariant(ctx: Context<GetCurveInvariant>) -> Result<()> {
+        endpoints::get_curve_invariant::handle(ctx)
+    }
+
+    /// Writes the [`LpValue`] of the pool as return data.
+    pub fn get_lp_value(ctx: Context<GetLpValue>) -> Result<()> {
+        endpoints::get_lp_value::handle(ctx)
+    }
+
+    /// Writes the [`FeeSchedule`] of the pool as return data.
+    pub fn get_fee_schedule(ctx: Context<GetFeeSchedule>) -> Result<()> {
+        endpoints::get_fee_schedule::handle(ctx)
+    }
+
+    pub fn get_pool_layout(ctx: Context<GetPoolLayout>) -> Result<()> {
+        endpoints::get_pool_layout::handle(ctx)
+    }
+
+    pub fn get_pool_signer(ctx: Context<GetPoolSigner>) -> Result<()> {
+        endpoints::get_pool_signer::handle(ctx)
+    }
+
+    pub fn migrate_pool(ctx: Context<MigratePool>) -> Result<()> {
+        endpoints::migrate_pool::handle(ctx)
+    }
+
+    pub fn reinvest_program_toll(
+        ctx: Context<ReinvestProgramToll>,
+        lp_tokens_to_burn: TokenAmount,
+    ) -> Result<()> {
+        endpoints::reinvest_program_toll::handle(ctx, lp_tokens_to_burn)
+    }
+
+    pub fn redeem_program_toll_into_reserve(
+        ctx: Context<RedeemProgramTollIntoReserve>,
+        lp_tokens_to_burn: TokenAmount,
+        min_tokens: TokenAmount,
+    ) -> Result<()> {
+        endpoints::redeem_program_toll_into_reserve::handle(
+            ctx,
+            lp_tokens_to_burn,
+            min_tokens,
+        )
+    }
+
+    pub fn burn_lp_to_reserves(
+        ctx: Context<BurnLpToReserves>,
+        lp_tokens_to_burn: TokenAmount,
+    ) -> Result<()> {
+        endpoints::burn_lp_to_reserves::handle(ctx, lp_tokens_to_burn)
+    }
+
     pub fn swap<'info>(
         ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
         sell: TokenAmount,
@@ -80,4 +267,113 @@ pub mod amm {
     ) -> Result<()> {
         endpoints::swap::handle(ctx, sell, min_buy)
     }
+
+    /// Like [`swap`], but the minimum amount of buy tokens is derived from the
+    /// pool's current spot price and the provided max slippage in basis
+    /// points. The spot price is read at execution, therefore this doesn't
+    /// protect against front-running or sandwich attacks, see
+    /// [`endpoints::swap::handle_with_slippage_bps`].
+    pub fn swap_with_slippage_bps<'info>(
+        ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
+        sell: TokenAmount,
+        max_slippage_bps: u64,
+    ) -> Result<()> {
+        endpoints::swap::handle_with_slippage_bps(ctx, sell, max_slippage_bps)
+    }
+
+    /// Like [`swap`], but additionally the bought tokens valued at the pool's
+    /// spot price after the swap must be worth at least given basis points of
+    /// the sold tokens.
+    pub fn swap_with_round_trip_guard<'info>(
+        ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
+        sell: TokenAmount,
+        min_buy: TokenAmount,
+        min_round_trip_bps: u64,
+    ) -> Result<()> {
+        endpoints::swap::handle_with_round_trip_guard(
+            ctx,
+            sell,
+            min_buy,
+            min_round_trip_bps,
+        )
+    }
+
+    /// Like [`swap`], but a share of the program toll is minted to the
+    /// referrer's LP wallet as per the pool's referral config.
+    pub fn swap_with_referral<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapWithReferral<'info>>,
+        sell: TokenAmount,
+        min_buy: TokenAmount,
+    ) -> Result<()> {
+        endpoints::swap::handle_with_referral(ctx, sell, min_buy)
+    }
+
+    /// Like [`swap_with_referral`], but the referrer's share is minted to the
+    /// default recipient of the pool's referral config.
+    pub fn swap_with_default_referral<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapWithDefaultReferral<'info>>,
+        sell: TokenAmount,
+        min_buy: TokenAmount,
+    ) -> Result<()> {
+        endpoints::swap::handle_with_default_referral(ctx, sell, min_buy)
+    }
+
+    /// Performs several independent swaps atomically, see
+    /// [`endpoints::batch_swap`].
+    pub fn batch_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, BatchSwap<'info>>,
+        legs: Vec<BatchSwapLeg>,
+    ) -> Result<()> {
+        endpoints::batch_swap::handle(ctx, legs)
+    }
+
+    /// Writes the [`LiquidityResult`] of depositing at most given tokens as
+    /// return data, without depositing them.
+    pub fn quote_deposit_liquidity(
+        ctx: Context<QuoteDepositLiquidity>,
+        max_amount_tokens: Vec<TokenLimit>,
+    ) -> Result<()> {
+        endpoints::quote_deposit_liquidity::handle(ctx, max_amount_tokens)
+    }
+
+    /// Writes the [`LiquidityResult`] of burning given LP tokens as return
+    /// data, without burning them.
+    pub fn quote_redeem_liquidity(
+        ctx: Context<QuoteRedeemLiquidity>,
+        lp_tokens_to_burn: TokenAmount,
+    ) -> Result<()> {
+        endpoints::quote_redeem_liquidity::handle(ctx, lp_tokens_to_burn)
+    }
+
+    /// Writes the [`LiquidityResult`] of depositing at most given amount of a
+    /// single reserve's token, together with the tokens of the other reserves
+    /// at the pool's ratio, as return data, without depositing them.
+    pub fn quote_single_deposit(
+        ctx: Context<QuoteSingleDeposit>,
+        tokens: TokenLimit,
+    ) -> Result<()> {
+        endpoints::quote_single_deposit::handle(ctx, tokens)
+    }
+
+    /// Writes the [`endpoints::swap::SwapQuote`] of a swap as return data
+    /// without executing it.
+    pub fn quote_swap(
+        ctx: Context<QuoteSwap>,
+        sell_mint: Pubkey,
+        sell: TokenAmount,
+        buy_mint: Pubkey,
+        with_referral: bool,
+    ) -> Result<()> {
+        endpoints::quote_swap::handle(
+            ctx,
+            sell_mint,
+            sell,
+            buy_mint,
+            with_referral,
+        )
+    }
+
+    pub fn sweep_dust(ctx: Context<SweepDust>) -> Result<()> {
+        endpoints::sweep_dust::handle(ctx)
+    }
 }
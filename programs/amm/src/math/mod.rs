@@ -2,6 +2,7 @@ pub mod helpers;
 mod sdecimal;
 pub mod stable_curve_invariant;
 pub mod swap_equation;
+pub mod weighted_swap;
 
 pub use decimal::{
     AlmostEq, Decimal, LargeDecimal, ScaledVal, TryAdd, TryDiv, TryMul, TryPow,
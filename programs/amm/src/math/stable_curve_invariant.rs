@@ -39,6 +39,21 @@ pub fn compute(
     StableCurveInvariant::new(amp, token_reserves_amount)?.compute()
 }
 
+/// Same as [`compute`], but the reserves don't have to be whole token amounts,
+/// e.g. because they've been normalized to a common precision, see
+/// [`crate::models::Pool::decimal_scales`].
+pub fn compute_from_decimals(
+    amp: u64,
+    token_reserves_amount: &[Decimal],
+) -> Result<Decimal> {
+    if amp == 0 {
+        msg!("Input value of amplifier is zero, reduces to constant product curve case");
+        return Err(error!(AmmError::InvalidArg));
+    }
+
+    StableCurveInvariant::from_decimals(amp, token_reserves_amount)?.compute()
+}
+
 struct StableCurveInvariant {
     // number of reserves
     exponent: u64,
@@ -54,13 +69,24 @@ struct StableCurveInvariant {
 
 impl StableCurveInvariant {
     fn new(amp: u64, token_reserves_amount: &[TokenAmount]) -> Result<Self> {
+        let token_reserves_amount: Vec<_> = token_reserves_amount
+            .iter()
+            .map(|tokens| Decimal::from(tokens.amount))
+            .collect();
+
+        Self::from_decimals(amp, &token_reserves_amount)
+    }
+
+    fn from_decimals(
+        amp: u64,
+        token_reserves_amount: &[Decimal],
+    ) -> Result<Self> {
         let amp = Decimal::from(amp);
 
+        // our initial guess for Newton's method
         let sum = token_reserves_amount
             .iter()
-            .try_fold(Decimal::zero(), |acc, el| {
-                acc.try_add(Decimal::from(el.amount))
-            })?; // our initial guess for Newton's method
+            .try_fold(Decimal::zero(), |acc, el| acc.try_add(*el))?;
 
         let scl_down_sum = scale_down_value(sum)?;
         let initial_guess = scl_down_sum.scale_down;
@@ -69,12 +95,11 @@ impl StableCurveInvariant {
         let scl_down_coef =
             Decimal::from(1000_u64).try_pow(scl_down_exp as u64)?;
 
-        let product = token_reserves_amount.iter().try_fold(
-            Decimal::one(),
-            |acc, el| {
-                acc.try_mul(Decimal::from(el.amount).try_div(scl_down_coef)?)
-            },
-        )?;
+        let product = token_reserves_amount
+            .iter()
+            .try_fold(Decimal::one(), |acc, el| {
+                acc.try_mul(el.try_div(scl_down_coef)?)
+            })?;
 
         // we don't allow trades in which the product is infinitesimally close
         // to zero, as this means extreme imbalance on a stable swap pool
@@ -0,0 +1,144 @@
+//! Weighted constant mean curve for pools with two reserves. The invariant
+//! is `x^w_x * y^w_y = k`, where the weights `w_x` and `w_y` sum to 100. With
+//! weights 50/50 the curve is equivalent to the constant product curve.
+//!
+//! Solving the invariant for the buy reserve after a swap gives
+//! `y' = y * (x / x')^(w_x / w_y)`, where `x` is the sell reserve before the
+//! swap and `x'` after it. The fractional exponent is computed as an integer
+//! power of an integer root, see [`try_pow_fraction`].
+
+use crate::prelude::*;
+
+/// Newton's method converges in a handful of iterations for the bases we work
+/// with, ie. in the interval `(0, 1]`. This is a safety net for the compute
+/// budget.
+const MAX_ROOT_ITERATIONS: usize = 256;
+
+/// Returns the buy reserve balance after a swap on the weighted curve, ie.
+/// `y * (x / x')^(w_x / w_y)`.
+pub fn get_buy_reserve_balance_after_weighted_swap(
+    sell_reserve: Decimal,
+    sell_reserve_after_swap: Decimal,
+    buy_reserve: Decimal,
+    sell_weight: u8,
+    buy_weight: u8,
+) -> Result<Decimal> {
+    let ratio = sell_reserve.try_div(sell_reserve_after_swap)?;
+    let factor =
+        try_pow_fraction(ratio, sell_weight as u64, buy_weight as u64)?;
+
+    let balance = buy_reserve.try_mul(factor)?;
+    if balance == Decimal::zero() {
+        // the precision of the decimal is not enough to represent the factor,
+        // we'd drain the reserve
+        msg!("Swap is too large for the weights of the pool");
+        return Err(error!(AmmError::MathOverflow));
+    }
+
+    Ok(balance)
+}
+
+/// Computes `base^(numerator / denominator)` for `base` in `(0, 1]`.
+///
+/// The fraction is reduced first, then the root is taken before the power,
+/// because that keeps the intermediate values as large as possible.
+pub fn try_pow_fraction(
+    base: Decimal,
+    numerator: u64,
+    denominator: u64,
+) -> Result<Decimal> {
+    if denominator == 0 || base > Decimal::one() {
+        msg!("Fractional power is only defined for base in (0, 1]");
+        return Err(error!(AmmError::InvalidArg));
+    }
+
+    let gcd = gcd(numerator, denominator);
+    let (numerator, denominator) = (numerator / gcd, denominator / gcd);
+
+    Ok(try_nth_root(base, denominator)?.try_pow(numerator)?)
+}
+
+/// Newton's method for `r^n = a`, ie. `r' = ((n - 1) r + a / r^(n - 1)) / n`.
+/// Starting at one, which is an upper bound for `a` in `(0, 1]`, the sequence
+/// decreases monotonically towards the root.
+fn try_nth_root(a: Decimal, n: u64) -> Result<Decimal> {
+    if n == 1 || a == Decimal::zero() || a == Decimal::one() {
+        return Ok(a);
+    }
+
+    let n_dec = Decimal::from(n);
+    let n_minus_one = Decimal::from(n - 1);
+
+    let mut root = Decimal::one();
+    for _ in 0..MAX_ROOT_ITERATIONS {
+        let next = n_minus_one
+            .try_mul(root)?
+            .try_add(a.try_div(root.try_pow(n - 1)?)?)?
+            .try_div(n_dec)?;
+
+        if next >= root {
+            return Ok(root);
+        }
+        root = next;
+    }
+
+    msg!("Root of weighted curve did not converge");
+    Err(error!(AmmError::MathOverflow))
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let remainder = a % b;
+        a = b;
+        b = remainder;
+    }
+
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_computes_fractional_power() -> Result<()> {
+        let half = Decimal::one().try_div(Decimal::from(2u64))?;
+
+        // 0.5^(1/2) ≈ 0.707106781186547524
+        assert!(try_pow_fraction(half, 1, 2)?
+            .almost_eq(&Decimal::from_scaled_val(707_106_781_186_547_524), 15));
+        // 0.5^(80/20) = 0.5^4
+        assert_eq!(try_pow_fraction(half, 80, 20)?, half.try_pow(4)?);
+        // 0.5^(20/80) = 0.5^(1/4) ≈ 0.840896415253714543
+        assert!(try_pow_fraction(half, 20, 80)?
+            .almost_eq(&Decimal::from_scaled_val(840_896_415_253_714_543), 15));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_errs_fractional_power_of_base_over_one() {
+        assert!(try_pow_fraction(Decimal::from(2u64), 1, 2).is_err());
+    }
+
+    #[test]
+    fn it_computes_buy_reserve_balance_after_equal_weights_swap() -> Result<()>
+    {
+        // with 50/50 weights the curve is constant product, ie.
+        // 100 * 10 / 150
+        let balance = get_buy_reserve_balance_after_weighted_swap(
+            Decimal::from(100u64),
+            Decimal::from(150u64),
+            Decimal::from(10u64),
+            50,
+            50,
+        )?;
+
+        assert!(balance.almost_eq(
+            &Decimal::from(1_000u64).try_div(Decimal::from(150u64))?,
+            15
+        ));
+
+        Ok(())
+    }
+}
@@ -1,4 +1,6 @@
 use crate::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
+use anchor_lang::system_program;
 use anchor_spl::token::Mint;
 
 /// Reloads the mint, gets supply and prints it in a predictable way which
@@ -11,3 +13,101 @@ pub fn print_lp_supply(lp_mint: &mut Account<Mint>) -> Result<()> {
 
     Ok(())
 }
+
+/// Prints the pool's running totals of fees after a swap in a predictable way
+/// which can then be parsed from tx logs, see [`Pool::record_swap_fees`].
+///
+/// Each swap logs the new totals, therefore auditors can check that each total
+/// only grows by the fee of the swap.
+pub fn print_lifetime_fees(pool: &Pool, sell_mint: Pubkey) {
+    let trade_fees = pool
+        .reserves()
+        .iter()
+        .zip(pool.lifetime_trade_fees)
+        .find(|(r, _)| r.mint == sell_mint)
+        .map(|(_, fees)| fees.amount)
+        .unwrap_or_default();
+
+    msg!(
+        "lifetime-trade-fees={} mint={} lifetime-owner-fees={}",
+        trade_fees,
+        sell_mint,
+        pool.lifetime_owner_fees.amount
+    );
+}
+
+/// Writes given value as the instruction's return data, eg. the amounts moved
+/// by a deposit or a redeem, see [`LiquidityResult`].
+///
+/// Clients and CPI callers read the data and deserialize it with borsh into
+/// the same type.
+pub fn set_return_data_of<T: AnchorSerialize>(value: &T) -> Result<()> {
+    set_return_data(&value.try_to_vec()?);
+
+    Ok(())
+}
+
+/// Creates an account of given size owned by this program at a PDA, which is
+/// what `#[account(init)]` does. Unlike a plain
+/// [`system_program::create_account`], this works even if someone has
+/// already transferred lamports to the PDA, which would otherwise block the
+/// creation forever.
+pub fn create_pda_account<'info>(
+    payer: AccountInfo<'info>,
+    account: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    space: usize,
+    pda_seeds: &[&[u8]],
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+
+    if account.lamports() == 0 {
+        return system_program::create_account(
+            CpiContext::new(
+                system_program,
+                system_program::CreateAccount {
+                    from: payer,
+                    to: account,
+                },
+            )
+            .with_signer(&[pda_seeds]),
+            rent,
+            space as u64,
+            &crate::ID,
+        );
+    }
+
+    let top_up = rent.saturating_sub(account.lamports());
+    if top_up != 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer,
+                    to: account.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new(
+            system_program.clone(),
+            system_program::Allocate {
+                account_to_allocate: account.clone(),
+            },
+        )
+        .with_signer(&[pda_seeds]),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new(
+            system_program,
+            system_program::Assign {
+                account_to_assign: account,
+            },
+        )
+        .with_signer(&[pda_seeds]),
+        &crate::ID,
+    )
+}
@@ -1,10 +1,20 @@
+pub mod curve_constraints;
 pub mod discount;
+pub mod fee_rebate;
+pub mod mint_blocklist;
 pub mod pool;
+pub mod pool_mirror;
 pub mod program_toll;
+pub mod referral_config;
 
+pub use curve_constraints::*;
 pub use discount::*;
+pub use fee_rebate::*;
+pub use mint_blocklist::*;
 pub use pool::*;
+pub use pool_mirror::*;
 pub use program_toll::*;
+pub use referral_config::*;
 
 use crate::prelude::*;
 
@@ -0,0 +1,68 @@
+//! Constrained deployments can bound the parameters of the pools' curves, e.g.
+//! so that nobody creates a stable curve pool with an absurd amplifier. The
+//! program toll authority configures the [`CurveConstraints`] with
+//! [`crate::endpoints::put_curve_constraints`], and
+//! [`crate::endpoints::create_pool`] and
+//! [`crate::endpoints::set_pool_weights`] reject curves out of the bounds.
+//!
+//! The [`CurveConstraints`] is a singleton stored in an account with a PDA
+//! address. If it doesn't exist, any curve parameters which
+//! [`Curve::validate`] accepts are allowed. Pools which already exist are not
+//! affected.
+
+use crate::prelude::*;
+
+#[account]
+#[derive(Default, PartialEq, Eq, Debug)]
+pub struct CurveConstraints {
+    /// Inclusive bounds on the amplifier of [`Curve::Stable`].
+    pub min_amplifier: u64,
+    pub max_amplifier: u64,
+    /// Inclusive bounds on each weight of [`Curve::Weighted`], in percent.
+    pub min_weight: u8,
+    pub max_weight: u8,
+}
+
+impl CurveConstraints {
+    pub const PDA_SEED: &'static [u8; 17] = b"curve_constraints";
+
+    pub fn space() -> usize {
+        let discriminant = 8;
+        let min_amplifier = 8;
+        let max_amplifier = 8;
+        let min_weight = 1;
+        let max_weight = 1;
+
+        discriminant + min_amplifier + max_amplifier + min_weight + max_weight
+    }
+
+    /// Checks the parameters of given curve against the bounds of its type.
+    /// The constant product curve has no parameters.
+    pub fn validate_curve(&self, curve: &Curve) -> Result<()> {
+        match *curve {
+            Curve::ConstProd => (),
+            Curve::Stable { amplifier, .. } => {
+                if !(self.min_amplifier..=self.max_amplifier)
+                    .contains(&amplifier)
+                {
+                    return Err(error!(err::arg(format!(
+                        "Amplifier must be between {} and {}",
+                        self.min_amplifier, self.max_amplifier
+                    ))));
+                }
+            }
+            Curve::Weighted { weight_a, weight_b } => {
+                let weights = self.min_weight..=self.max_weight;
+                if !weights.contains(&weight_a) || !weights.contains(&weight_b)
+                {
+                    return Err(error!(err::arg(format!(
+                        "Weights must be between {} and {}",
+                        self.min_weight, self.max_weight
+                    ))));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
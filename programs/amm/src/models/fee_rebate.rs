@@ -0,0 +1,47 @@
+//! To bootstrap volume, pool admin can rebate a share of the swap fee to the
+//! swappers for a limited period of time. The [`FeeRebate`] is created or
+//! updated with [`crate::endpoints::put_pool_fee_rebate`] and is considered by
+//! each [`crate::endpoints::swap`].
+//!
+//! The [`FeeRebate`] is always stored in an account with a PDA address for
+//! which the pool's pubkey is a seed.
+
+use crate::prelude::*;
+
+/// A one-to-one account with relationship to a pool. Pools don't have to have
+/// any fee rebate.
+#[account]
+#[derive(Default, PartialEq, Eq, Debug)]
+pub struct FeeRebate {
+    /// What fraction of the swap fee the swapper keeps. The swap fee is
+    /// calculated first, ie. after the user's [`Discount`], and then the
+    /// rebate is subtracted from it.
+    ///
+    /// The share must be less than 100%, ie. the pool always collects some
+    /// fee.
+    pub share: Permillion,
+    /// The rebate applies from this slot (inclusive).
+    pub starts_at: Slot,
+    /// The rebate applies until this slot (inclusive).
+    pub ends_at: Slot,
+}
+
+impl FeeRebate {
+    pub const PDA_PREFIX: &'static [u8; 10] = b"fee_rebate";
+
+    pub fn space() -> usize {
+        let discriminant = 8;
+        let share = 8;
+        let starts_at = 8;
+        let ends_at = 8;
+
+        discriminant + share + starts_at + ends_at
+    }
+
+    pub fn does_apply(&self) -> Result<bool> {
+        let time = Slot::current()?;
+        Ok(self.share.permillion > 0
+            && self.starts_at <= time
+            && time <= self.ends_at)
+    }
+}
@@ -0,0 +1,33 @@
+//! Deployments can block pools for certain mints, e.g. known scam tokens. The
+//! program toll authority manages the [`MintBlocklist`] with
+//! [`crate::endpoints::put_mint_blocklist`] and
+//! [`crate::endpoints::create_pool`] rejects vaults of any blocked mint.
+//!
+//! The [`MintBlocklist`] is a singleton stored in an account with a PDA
+//! address. If it doesn't exist, no mints are blocked.
+
+use crate::prelude::*;
+
+#[account]
+#[derive(Default, PartialEq, Eq, Debug)]
+pub struct MintBlocklist {
+    /// At most [`consts::MAX_BLOCKED_MINTS`] mints.
+    pub mints: Vec<Pubkey>,
+}
+
+impl MintBlocklist {
+    pub const PDA_SEED: &'static [u8; 14] = b"mint_blocklist";
+
+    /// The account is always allocated for the maximum number of mints, so
+    /// that the blocklist can be changed without reallocating.
+    pub fn space() -> usize {
+        let discriminant = 8;
+        let mints = 4 + 32 * consts::MAX_BLOCKED_MINTS;
+
+        discriminant + mints
+    }
+
+    pub fn is_blocked(&self, mint: &Pubkey) -> bool {
+        self.mints.contains(mint)
+    }
+}
@@ -2,7 +2,9 @@
 
 use crate::math::helpers::*;
 use crate::math::swap_equation::*;
+use crate::math::weighted_swap::*;
 use crate::prelude::*;
+use anchor_lang::Discriminator;
 use std::collections::BTreeMap;
 use std::mem;
 
@@ -31,6 +33,57 @@ pub struct Pool {
     pub reserves: [Reserve; 4],
     pub curve: Curve,
     pub swap_fee: Permillion,
+    /// Swap fee increases are timelocked, see
+    /// [`crate::endpoints::set_pool_swap_fee`]. Until the slot
+    /// `pending_swap_fee_effective_at`, swaps keep using `swap_fee`.
+    pub pending_swap_fee: Permillion,
+    /// At this slot `pending_swap_fee` replaces `swap_fee`. Zero if there's
+    /// no pending change.
+    pub pending_swap_fee_effective_at: Slot,
+    /// How many slots a swap fee increase stays pending. The admin configures
+    /// this with [`crate::endpoints::set_pool_swap_fee_timelock`].
+    pub swap_fee_timelock_slots: u64,
+    /// Deposits which would mint LP tokens beyond this supply fail. Zero
+    /// means there's no cap. The admin configures this with
+    /// [`crate::endpoints::set_pool_max_lp_supply`].
+    pub max_lp_supply: TokenAmount,
+    /// Only used by [`Curve::Stable`]. The stable curve assumes that a token
+    /// of each reserve is worth the same, but reserve amounts are stored in
+    /// the smallest units of their mints, and mints can have different
+    /// decimals. Therefore, the amount of the reserve at the same index is
+    /// multiplied by `10^decimal_scales[i]` before the invariant and swap
+    /// computations, e.g. a pool of a mint with 6 decimals and a mint with 9
+    /// decimals has the scales `[3, 0, 0, 0]`.
+    ///
+    /// All zeros means no normalization. The admin configures this with
+    /// [`crate::endpoints::set_pool_decimal_scales`].
+    pub decimal_scales: [u8; 4],
+    /// Running total of swap fees in sell tokens which stayed in the reserve
+    /// at the same index, including the part which was paid out as the
+    /// program toll. Together with `lifetime_owner_fees` it's updated on each
+    /// swap, see [`Pool::record_swap_fees`].
+    ///
+    /// The counters saturate instead of overflowing.
+    pub lifetime_trade_fees: [TokenAmount; 4],
+    /// Running total of LP tokens minted by swaps as the program toll and as
+    /// referral shares.
+    pub lifetime_owner_fees: TokenAmount,
+}
+
+/// The [`Pool`] account as it was laid out before the pool stored a pending
+/// swap fee, an LP supply cap, decimal scales and lifetime fees. Such accounts
+/// are too small to be deserialized as [`Pool`] and must be converted with
+/// [`crate::endpoints::migrate_pool`] first.
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug, Eq, PartialEq)]
+pub struct LegacyPool {
+    pub admin: Pubkey,
+    pub signer: Pubkey,
+    pub mint: Pubkey,
+    pub program_toll_wallet: Pubkey,
+    pub dimension: u64,
+    pub reserves: [Reserve; 4],
+    pub curve: Curve,
+    pub swap_fee: Permillion,
 }
 
 #[derive(
@@ -38,7 +91,18 @@ pub struct Pool {
 )]
 pub enum Curve {
     ConstProd,
-    Stable { amplifier: u64, invariant: SDecimal },
+    Stable {
+        amplifier: u64,
+        invariant: SDecimal,
+    },
+    /// Weighted constant mean curve for pools with two reserves, see
+    /// [`crate::math::weighted_swap`]. The weight `a` belongs to the first
+    /// reserve and the weight `b` to the second one. Weights are in percent
+    /// and must sum to 100.
+    Weighted {
+        weight_a: u8,
+        weight_b: u8,
+    },
 }
 
 #[derive(
@@ -87,6 +151,195 @@ pub struct RedeemResult {
     pub tokens_to_redeem: BTreeMap<Pubkey, TokenAmount>,
 }
 
+/// Endpoints which move liquidity in or out of a pool write this struct as
+/// the instruction's return data, so that CPI callers don't need to re-derive
+/// how many tokens have been moved.
+#[derive(
+    AnchorDeserialize, AnchorSerialize, Clone, Debug, Default, Eq, PartialEq,
+)]
+pub struct LiquidityResult {
+    /// How many LP tokens were minted to the user on deposit. Zero on redeem.
+    pub lp_tokens_minted: TokenAmount,
+    /// How many LP tokens were burned from the user on redeem. Zero on
+    /// deposit.
+    pub lp_tokens_burned: TokenAmount,
+    /// How many tokens of each reserve were moved, ordered by mint pubkey.
+    pub tokens: Vec<TokenLimit>,
+    /// How the reserve token amounts were rounded to whole tokens. Always in
+    /// favour of the pool, ie. up on deposit and down on redeem. LP tokens
+    /// minted on deposit are always rounded down.
+    pub tokens_rounding: Rounding,
+}
+
+/// Direction in which a fractional token amount is rounded to a whole one,
+/// see [`LiquidityResult::tokens_rounding`].
+#[derive(
+    AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug, Eq, PartialEq,
+)]
+pub enum Rounding {
+    Floor,
+    Ceiling,
+}
+
+impl Default for Rounding {
+    fn default() -> Self {
+        Self::Floor
+    }
+}
+
+impl LiquidityResult {
+    pub fn deposited(
+        lp_tokens_minted: TokenAmount,
+        tokens_deposited: &BTreeMap<Pubkey, TokenAmount>,
+    ) -> Self {
+        Self {
+            lp_tokens_minted,
+            tokens: token_limits(tokens_deposited),
+            tokens_rounding: Rounding::Ceiling,
+            ..Default::default()
+        }
+    }
+
+    pub fn redeemed(
+        lp_tokens_burned: TokenAmount,
+        tokens_redeemed: &BTreeMap<Pubkey, TokenAmount>,
+    ) -> Self {
+        Self {
+            lp_tokens_burned,
+            tokens: token_limits(tokens_redeemed),
+            tokens_rounding: Rounding::Floor,
+            ..Default::default()
+        }
+    }
+}
+
+/// What is the pool's LP token worth in terms of the reserves, see
+/// [`Pool::lp_value`].
+#[derive(
+    AnchorDeserialize, AnchorSerialize, Clone, Debug, Default, Eq, PartialEq,
+)]
+pub struct LpValue {
+    pub lp_supply: TokenAmount,
+    /// How many tokens there are in each reserve, ordered by mint pubkey.
+    pub reserves: Vec<TokenLimit>,
+    /// How many tokens of each reserve would one LP token redeem, ordered by
+    /// mint pubkey. Rounded down, ie. small values can be zero.
+    pub tokens_per_lp_token: Vec<TokenLimit>,
+}
+
+/// A swap which brings the pool's reserves to a target ratio, see
+/// [`Pool::calculate_swap_to_reach_ratio`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct RatioSwap {
+    pub sell_mint: Pubkey,
+    pub buy_mint: Pubkey,
+    /// Zero if the reserves are already at the target ratio.
+    pub sell: TokenAmount,
+}
+
+/// The invariant of the pool's curve computed from the current reserves, see
+/// [`Pool::curve_invariant`]. Monitoring can use it to check that the
+/// invariant doesn't decrease with swaps.
+#[derive(
+    AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug, Eq, PartialEq,
+)]
+pub struct CurveInvariant {
+    /// For stable curve this includes the invariant which was stored on the
+    /// last deposit or redemption.
+    pub curve: Curve,
+    /// - constant product curve: product of all reserves;
+    /// - stable curve: `D` computed from the current reserves;
+    /// - weighted curve: `x^w_a * y^w_b` with weights as fractions of one.
+    ///
+    /// Zero if any reserve is empty.
+    pub invariant: SDecimal,
+}
+
+/// The PDA which signs for the pool's vaults and LP mint, see
+/// [`Pool::find_signer_address`].
+#[derive(
+    AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug, Eq, PartialEq,
+)]
+pub struct PoolSigner {
+    pub signer: Pubkey,
+    /// The bump seed with which the PDA is derived. Endpoints which require
+    /// the pool signer derive it with the same seeds.
+    pub bump: u8,
+}
+
+/// Size of a pool account, see [`crate::endpoints::get_pool_layout`].
+///
+/// An account which is smaller than [`PoolLayout::current_size`] was created
+/// by an older version of the program. Such an account cannot be deserialized
+/// as [`Pool`], therefore every endpoint which takes the pool fails until it's
+/// migrated with [`crate::endpoints::migrate_pool`].
+#[derive(
+    AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug, Eq, PartialEq,
+)]
+pub struct PoolLayout {
+    /// Size of the account data including the discriminator.
+    pub size: u64,
+    /// Size of a pool account created by this version of the program, see
+    /// [`Pool::space`].
+    pub current_size: u64,
+}
+
+/// Which swap fee applies right now and which one is pending, see
+/// [`Pool::fee_schedule`].
+#[derive(
+    AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug, Eq, PartialEq,
+)]
+pub struct FeeSchedule {
+    /// The fee which a swap would charge at the slot of the query. If the
+    /// timelock of a pending fee has already elapsed, this is the pending fee
+    /// even though the pool account hasn't been updated by a swap yet.
+    pub swap_fee: Permillion,
+    /// Zero if there's no pending change.
+    pub pending_swap_fee: Permillion,
+    /// Zero if there's no pending change.
+    pub pending_swap_fee_effective_at: Slot,
+}
+
+/// How many buy tokens a swap on a constant product curve receives. Only the
+/// sell and buy reserves change in a swap, therefore for any number of
+/// reserves `y - x * y / (x + dx)` simplifies to `y * dx / (x + dx)`, which is
+/// rounded down.
+///
+/// The result is computed in `u128`. If one token is worth many orders of
+/// magnitude more than the other, eg. BTC and SHIB, the product of the
+/// reserves is too large for the intermediate results of [`Decimal`], while
+/// the product of two `u64` is never too large for a `u128`. There is no
+/// rounding until the final division, therefore even the smallest swap of the
+/// high-value token receives the low-value tokens it's worth.
+///
+/// The caller checks that the sell reserve is positive.
+fn const_prod_tokens_to_receive(
+    sell_reserve: TokenAmount,
+    tokens_to_swap: TokenAmount,
+    buy_reserve: TokenAmount,
+) -> TokenAmount {
+    let numerator =
+        u128::from(buy_reserve.amount) * u128::from(tokens_to_swap.amount);
+    // positive because the sell reserve is positive
+    let sell_reserve_after_swap =
+        u128::from(sell_reserve.amount) + u128::from(tokens_to_swap.amount);
+    // less than the buy reserve because tokens_to_swap is less than
+    // sell_reserve_after_swap, therefore fits into u64
+    let tokens_to_receive = numerator / sell_reserve_after_swap;
+
+    TokenAmount::new(tokens_to_receive as u64)
+}
+
+fn token_limits(tokens: &BTreeMap<Pubkey, TokenAmount>) -> Vec<TokenLimit> {
+    tokens
+        .iter()
+        .map(|(mint, tokens)| TokenLimit {
+            mint: *mint,
+            tokens: *tokens,
+        })
+        .collect()
+}
+
 impl Default for Curve {
     fn default() -> Self {
         Curve::ConstProd
@@ -128,12 +381,96 @@ impl Curve {
         match self {
             Curve::ConstProd => None,
             Curve::Stable { invariant, .. } => Some(Decimal::from(*invariant)),
+            Curve::Weighted { .. } => None,
+        }
+    }
+
+    /// Checks that the curve's parameters are sensible for a pool with given
+    /// number of reserves.
+    pub fn validate(&self, dimension: u64) -> Result<()> {
+        if let Curve::Weighted { weight_a, weight_b } = *self {
+            if dimension != 2 {
+                return Err(error!(err::arg(
+                    "Weighted curve is only supported for pools with two \
+                    reserves"
+                )));
+            }
+            if weight_a == 0 || weight_b == 0 {
+                return Err(error!(err::arg("Weights must be positive")));
+            }
+            if weight_a as u16 + weight_b as u16 != 100 {
+                return Err(error!(err::arg("Weights must sum to 100")));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl LegacyPool {
+    /// Includes the discriminator, which is the same as [`Pool`]'s. Like
+    /// [`Pool::space`], this is more than the serialized pool takes.
+    pub fn space() -> usize {
+        let discriminant = 8;
+        let initializer = 32;
+        let signer = 32;
+        let lp_token_program_fee_wallet = 32;
+        let mint = 32;
+        let dimension = 8;
+        let reserves = mem::size_of::<Reserve>() * 4;
+        let curve = mem::size_of::<Curve>();
+        let fee = mem::size_of::<Permillion>();
+
+        discriminant
+            + initializer
+            + signer
+            + lp_token_program_fee_wallet
+            + mint
+            + dimension
+            + reserves
+            + curve
+            + fee
+    }
+
+    /// Converts the account into the current layout. The swap fee timelock is
+    /// [`consts::DEFAULT_SWAP_FEE_TIMELOCK_SLOTS`] as for new pools. The other
+    /// fields which the legacy layout didn't have start at zero, ie. there's
+    /// no pending swap fee, no LP supply cap and no decimal scales.
+    pub fn migrate(self) -> Pool {
+        Pool {
+            admin: self.admin,
+            signer: self.signer,
+            mint: self.mint,
+            program_toll_wallet: self.program_toll_wallet,
+            dimension: self.dimension,
+            reserves: self.reserves,
+            curve: self.curve,
+            swap_fee: self.swap_fee,
+            swap_fee_timelock_slots: consts::DEFAULT_SWAP_FEE_TIMELOCK_SLOTS,
+            ..Default::default()
         }
     }
 }
 
 impl Pool {
     pub const SIGNER_PDA_PREFIX: &'static [u8; 6] = b"signer";
+    pub const LP_MINT_PDA_PREFIX: &'static [u8; 7] = b"lp_mint";
+    pub const PROGRAM_TOLL_WALLET_PDA_PREFIX: &'static [u8; 19] =
+        b"program_toll_wallet";
+    /// Pools created with [`crate::endpoints::create_pool_with_nonce`] live
+    /// at a PDA with seeds `["pool", admin, nonce]`.
+    pub const CREATOR_PDA_PREFIX: &'static [u8; 4] = b"pool";
+
+    /// Derives the signer PDA of the pool with given key, ie. with seeds
+    /// `["signer", pool]`.
+    pub fn find_signer_address(pool: &Pubkey) -> PoolSigner {
+        let (signer, bump) = Pubkey::find_program_address(
+            &[Self::SIGNER_PDA_PREFIX, pool.as_ref()],
+            &crate::ID,
+        );
+
+        PoolSigner { signer, bump }
+    }
 
     pub fn space() -> usize {
         let discriminant = 8;
@@ -145,6 +482,13 @@ impl Pool {
         let reserves = mem::size_of::<Reserve>() * 4;
         let curve = mem::size_of::<Curve>();
         let fee = mem::size_of::<Permillion>();
+        let pending_fee = mem::size_of::<Permillion>();
+        let pending_fee_effective_at = mem::size_of::<Slot>();
+        let fee_timelock = 8;
+        let max_lp_supply = mem::size_of::<TokenAmount>();
+        let decimal_scales = 4;
+        let lifetime_trade_fees = mem::size_of::<TokenAmount>() * 4;
+        let lifetime_owner_fees = mem::size_of::<TokenAmount>();
 
         discriminant
             + initializer
@@ -155,6 +499,87 @@ impl Pool {
             + reserves
             + curve
             + fee
+            + pending_fee
+            + pending_fee_effective_at
+            + fee_timelock
+            + max_lp_supply
+            + decimal_scales
+            + lifetime_trade_fees
+            + lifetime_owner_fees
+    }
+
+    /// Given data of a pool account of any version of the program, returns its
+    /// size and the size of the current layout.
+    pub fn layout(data: &[u8]) -> Result<PoolLayout> {
+        if data.len() < 8 || data[..8] != Self::discriminator() {
+            return Err(error!(err::acc("Account is not a pool")));
+        }
+
+        Ok(PoolLayout {
+            size: data.len() as u64,
+            current_size: Self::space() as u64,
+        })
+    }
+
+    /// Adds the fees of a swap to the pool's running totals. The trade fee is
+    /// in the sell tokens, the owner fee in LP tokens.
+    pub fn record_swap_fees(
+        &mut self,
+        sell_mint: Pubkey,
+        trade_fee: TokenAmount,
+        owner_fee: TokenAmount,
+    ) -> Result<()> {
+        let index = self
+            .reserves()
+            .iter()
+            .position(|r| r.mint == sell_mint)
+            .ok_or_else(|| {
+                msg!("Provided short (sell) token mint is invalid");
+                error!(AmmError::InvalidArg)
+            })?;
+
+        let lifetime_trade_fee = &mut self.lifetime_trade_fees[index];
+        lifetime_trade_fee.amount =
+            lifetime_trade_fee.amount.saturating_add(trade_fee.amount);
+        self.lifetime_owner_fees.amount = self
+            .lifetime_owner_fees
+            .amount
+            .saturating_add(owner_fee.amount);
+
+        Ok(())
+    }
+
+    pub fn has_pending_swap_fee(&self) -> bool {
+        self.pending_swap_fee_effective_at != Slot::default()
+    }
+
+    /// If the timelock of the pending swap fee has elapsed, the pending fee
+    /// becomes the pool's swap fee.
+    pub fn apply_pending_swap_fee(&mut self, now: Slot) {
+        if self.has_pending_swap_fee()
+            && now >= self.pending_swap_fee_effective_at
+        {
+            self.swap_fee = self.pending_swap_fee;
+            self.cancel_pending_swap_fee();
+        }
+    }
+
+    pub fn cancel_pending_swap_fee(&mut self) {
+        self.pending_swap_fee = Permillion::default();
+        self.pending_swap_fee_effective_at = Slot::default();
+    }
+
+    /// Returns the swap fee which applies at the slot `now` and the pending
+    /// one, if any, without changing the pool.
+    pub fn fee_schedule(&self, now: Slot) -> FeeSchedule {
+        let mut pool = self.clone();
+        pool.apply_pending_swap_fee(now);
+
+        FeeSchedule {
+            swap_fee: pool.swap_fee,
+            pending_swap_fee: pool.pending_swap_fee,
+            pending_swap_fee_effective_at: pool.pending_swap_fee_effective_at,
+        }
     }
 
     /// Returns only reserves which are initialized, ie. this would return
@@ -487,10 +912,260 @@ impl Pool {
         })
     }
 
+    /// Calculates the underlying reserve tokens of a single LP token. The
+    /// values are the same as if one LP token was redeemed, see
+    /// [`Pool::redeem_tokens`].
+    pub fn lp_value(&self, lp_mint_supply: TokenAmount) -> Result<LpValue> {
+        let min_tokens = self
+            .reserves()
+            .iter()
+            .map(|r| (r.mint, TokenAmount::new(0)))
+            .collect();
+        // IMPORTANT: we don't actually want to redeem the LP token, hence
+        // the clone
+        let tokens_per_lp_token = self.clone().redeem_tokens(
+            min_tokens,
+            TokenAmount::new(1),
+            lp_mint_supply,
+        )?;
+
+        Ok(LpValue {
+            lp_supply: lp_mint_supply,
+            reserves: token_limits(&self.reserves_hashmap()),
+            tokens_per_lp_token: token_limits(&tokens_per_lp_token),
+        })
+    }
+
+    /// Calculates what a deposit of at most `max_tokens` would move, ie. the
+    /// same amounts as [`crate::endpoints::deposit_liquidity`] would, without
+    /// changing the pool.
+    pub fn quote_deposit(
+        &self,
+        max_tokens: BTreeMap<Pubkey, TokenAmount>,
+        lp_mint_supply: TokenAmount,
+    ) -> Result<LiquidityResult> {
+        // IMPORTANT: we don't actually want to deposit the tokens, hence the
+        // clone
+        let DepositResult {
+            lp_tokens_to_distribute,
+            tokens_to_deposit,
+        } = self.clone().deposit_tokens(max_tokens, lp_mint_supply)?;
+        let lp_tokens_to_distribute =
+            lp_tokens_to_distribute.ok_or_else(|| {
+                msg!("Provided liquidity is too small to be represented");
+                AmmError::InvalidArg
+            })?;
+
+        if lp_tokens_to_distribute.amount > u64::MAX - lp_mint_supply.amount {
+            msg!(
+                "Minting {} LP tokens would overflow the LP supply {}",
+                lp_tokens_to_distribute.amount,
+                lp_mint_supply.amount
+            );
+            return Err(error!(AmmError::MathOverflow));
+        }
+
+        Ok(LiquidityResult::deposited(
+            lp_tokens_to_distribute,
+            &tokens_to_deposit,
+        ))
+    }
+
+    /// Calculates the deposit of at most `tokens` of a single reserve, ie. how
+    /// many tokens of the other reserves the deposit needs at the pool's ratio
+    /// and how many LP tokens are minted for it, rounded down. The result is
+    /// the same as of [`Pool::quote_deposit`] with the other reserves
+    /// unbounded.
+    ///
+    /// The first deposit sets the ratio of the reserves, therefore it cannot be
+    /// quoted from a single reserve.
+    pub fn quote_single_deposit(
+        &self,
+        tokens: TokenLimit,
+        lp_mint_supply: TokenAmount,
+    ) -> Result<LiquidityResult> {
+        if lp_mint_supply.amount == 0 {
+            return Err(error!(err::arg(
+                "The first deposit must provide tokens of each reserve"
+            )));
+        }
+
+        if !self.reserves().iter().any(|r| r.mint == tokens.mint) {
+            return Err(error!(err::arg(format!(
+                "Mint '{}' is not part of this pool",
+                tokens.mint
+            ))));
+        }
+
+        let max_tokens = self
+            .reserves()
+            .iter()
+            .map(|r| {
+                let max = if r.mint == tokens.mint {
+                    tokens.tokens
+                } else {
+                    TokenAmount::new(u64::MAX)
+                };
+                (r.mint, max)
+            })
+            .collect();
+
+        self.quote_deposit(max_tokens, lp_mint_supply)
+    }
+
+    /// Calculates what burning `lp_tokens_to_burn` would redeem, ie. the same
+    /// amounts as [`crate::endpoints::redeem_liquidity`] would, without
+    /// changing the pool.
+    pub fn quote_redeem(
+        &self,
+        lp_tokens_to_burn: TokenAmount,
+        lp_mint_supply: TokenAmount,
+    ) -> Result<LiquidityResult> {
+        let min_tokens = self
+            .reserves()
+            .iter()
+            .map(|r| (r.mint, TokenAmount::new(0)))
+            .collect();
+        // IMPORTANT: we don't actually want to redeem the LP tokens, hence
+        // the clone
+        let tokens_to_redeem = self.clone().redeem_tokens(
+            min_tokens,
+            lp_tokens_to_burn,
+            lp_mint_supply,
+        )?;
+
+        Ok(LiquidityResult::redeemed(
+            lp_tokens_to_burn,
+            &tokens_to_redeem,
+        ))
+    }
+
+    /// Redeems given amount of LP tokens for a single reserve of a pool with
+    /// two reserves. The share of the other reserve is swapped within the pool
+    /// into the reserve of `mint`, ie. those tokens never leave the pool. The
+    /// swap pays the pool's swap fee to the liquidity providers, but no
+    /// program toll.
+    ///
+    /// This mutates the state of the pool, removing the amount returned.
+    pub fn redeem_tokens_into_reserve(
+        &mut self,
+        lp_tokens_to_burn: TokenAmount,
+        lp_mint_supply: TokenAmount,
+        mint: Pubkey,
+    ) -> Result<TokenAmount> {
+        if self.dimension != 2 {
+            return Err(error!(err::arg(
+                "Can only redeem into a single reserve if pool has two reserves"
+            )));
+        }
+        if self.reserve_vault(mint).is_none() {
+            return Err(error!(err::arg("Mint is not part of this pool")));
+        }
+        let other_mint = self
+            .reserves()
+            .iter()
+            .map(|r| r.mint)
+            .find(|m| *m != mint)
+            .ok_or(AmmError::InvariantViolation)?;
+
+        let min_tokens = self
+            .reserves()
+            .iter()
+            .map(|r| (r.mint, TokenAmount::new(0)))
+            .collect();
+        let redeemed =
+            self.redeem_tokens(min_tokens, lp_tokens_to_burn, lp_mint_supply)?;
+        // the swap must be priced with the invariant after the redemption
+        self.update_curve_invariant()?;
+
+        let tokens =
+            *redeemed.get(&mint).ok_or(AmmError::InvariantViolation)?;
+        let other_tokens = *redeemed
+            .get(&other_mint)
+            .ok_or(AmmError::InvariantViolation)?;
+        if other_tokens.amount == 0 {
+            return Ok(tokens);
+        }
+
+        let swap_fee_share: Decimal = self.swap_fee.into();
+        let swap_fee = TokenAmount::new(
+            Decimal::from(other_tokens.amount)
+                .try_mul(swap_fee_share)?
+                .try_ceil()?,
+        );
+        // swap fee is a fraction of the swapped amount
+        let tokens_to_swap =
+            TokenAmount::new(other_tokens.amount - swap_fee.amount);
+        let bought = self.swap(other_mint, tokens_to_swap, mint)?;
+        // the swap fee stays in the pool, see the swap endpoint
+        self.reserve_mut(other_mint)
+            .ok_or(AmmError::InvariantViolation)?
+            .add_tokens(swap_fee)?;
+
+        Ok(TokenAmount::new(
+            tokens
+                .amount
+                .checked_add(bought.amount)
+                .ok_or(AmmError::MathOverflow)?,
+        ))
+    }
+
+    /// Computes the invariant of the pool's curve from the current reserves,
+    /// see [`CurveInvariant`].
+    pub fn curve_invariant(&self) -> Result<CurveInvariant> {
+        let reserves: Vec<_> =
+            self.reserves().iter().map(|r| r.tokens).collect();
+
+        let invariant = if reserves.iter().any(|tokens| tokens.amount == 0) {
+            Decimal::zero()
+        } else {
+            match self.curve {
+                Curve::ConstProd => fold_product(
+                    &reserves
+                        .iter()
+                        .map(|tokens| Decimal::from(tokens.amount))
+                        .collect::<Vec<_>>(),
+                )?,
+                Curve::Stable { amplifier, .. } => {
+                    math::stable_curve_invariant::compute_from_decimals(
+                        amplifier,
+                        &self.normalized_reserves()?,
+                    )?
+                }
+                Curve::Weighted { weight_a, weight_b } => {
+                    let a = Decimal::from(reserves[0].amount);
+                    let b = Decimal::from(reserves[1].amount);
+
+                    // with weights summing to one, `a^w_a * b^w_b` equals
+                    // `b * (a / b)^w_a` and `a * (b / a)^w_b`, we pick the
+                    // form with base at most one
+                    if a <= b {
+                        b.try_mul(try_pow_fraction(
+                            a.try_div(b)?,
+                            weight_a as u64,
+                            100,
+                        )?)?
+                    } else {
+                        a.try_mul(try_pow_fraction(
+                            b.try_div(a)?,
+                            weight_b as u64,
+                            100,
+                        )?)?
+                    }
+                }
+            }
+        };
+
+        Ok(CurveInvariant {
+            curve: self.curve,
+            invariant: invariant.into(),
+        })
+    }
+
     /// This is called after a deposit or redemption.
     pub fn update_curve_invariant(&mut self) -> Result<()> {
         match self.curve {
-            Curve::ConstProd => (),
+            Curve::ConstProd | Curve::Weighted { .. } => (),
             Curve::Stable { amplifier, .. } => {
                 // need to recompute curve invariant, using Newton-Raphson
                 // approximation method
@@ -504,9 +1179,9 @@ impl Pool {
                     // this can happen on redeem, when all tokens are withdrawn
                     Decimal::zero().into()
                 } else {
-                    math::stable_curve_invariant::compute(
+                    math::stable_curve_invariant::compute_from_decimals(
                         amplifier,
-                        &token_reserves_amount,
+                        &self.normalized_reserves()?,
                     )?
                     .into()
                 };
@@ -574,9 +1249,25 @@ impl Pool {
         Ok(receive_tokens)
     }
 
-    /// Given the current state of the pool, how many buy tokens does the
-    /// trader receive out of the quote tokens he/she is selling in the swap.
-    fn calculate_swap(
+    /// Given the current state of the pool, calculates how many buy tokens
+    /// would the trader receive for `tokens_to_swap` sell tokens if the whole
+    /// swap was priced at the pool's marginal (spot) price, ie. as if the pool
+    /// had infinite depth and the trade had no price impact. Fees are not
+    /// considered.
+    ///
+    /// This is an upper bound on what [`Pool::swap`] returns for the same
+    /// input, and serves as a reference point for slippage.
+    ///
+    /// For constant product curve the marginal price is `x_buy / x_sell`.
+    ///
+    /// For stable curve we differentiate the invariant equation
+    /// `A n^n ∑x + D = A n^n D + D^(n+1) / (n^n ∏x)` and get
+    /// `(A n^n + C / x_sell) / (A n^n + C / x_buy)`, where
+    /// `C = D^(n+1) / (n^n ∏x)`.
+    ///
+    /// For weighted curve the marginal price is
+    /// `(x_buy / w_buy) / (x_sell / w_sell)`.
+    pub fn calculate_spot_swap(
         &self,
         sell_mint: Pubkey,
         tokens_to_swap: TokenAmount,
@@ -590,37 +1281,418 @@ impl Pool {
             return Err(error!(AmmError::InvalidArg));
         }
 
-        if !reserves.contains_key(&buy_mint) {
-            msg!("Provided long (buy) token mint is invalid");
-            return Err(error!(AmmError::InvalidArg));
-        }
-
-        if !reserves.contains_key(&sell_mint) {
-            msg!("Provided short (sell) token mint is invalid");
-            return Err(error!(AmmError::InvalidArg));
-        }
+        let buy_reserve = Decimal::from(
+            reserves
+                .get(&buy_mint)
+                .ok_or_else(|| {
+                    msg!("Provided long (buy) token mint is invalid");
+                    error!(AmmError::InvalidArg)
+                })?
+                .amount,
+        );
+        let sell_reserve = Decimal::from(
+            reserves
+                .get(&sell_mint)
+                .ok_or_else(|| {
+                    msg!("Provided short (sell) token mint is invalid");
+                    error!(AmmError::InvalidArg)
+                })?
+                .amount,
+        );
 
-        // checks if amount of short (sell) token to be swapped fits within
-        // current pool liquidity. It is important we don't allow
-        // user to swap the total number of tokens in the pool. The reason
-        // why we check the sell tokens instead of buy tokens is because the
-        // buy tokens amount is still unknown at this time, but it the sell
-        // tokens amount is bigger than the amount in the pool that would
-        // result in a buy tokens amount > to the amount of tokens in the pool
-        if tokens_to_swap >= *reserves.get(&sell_mint).unwrap() {
-            msg!(
-                "The user tries to swap the total amount of a single
-                 token reserve within the pool"
-            );
-            return Err(error!(AmmError::InvalidArg));
-        }
+        let tokens_to_receive = match self.curve {
+            Curve::ConstProd => try_mul_div(
+                Decimal::from(tokens_to_swap.amount),
+                buy_reserve,
+                sell_reserve,
+            )?,
+            Curve::Stable {
+                amplifier,
+                invariant,
+            } => {
+                let sell_scale = self.decimal_scale(sell_mint)?;
+                let buy_scale = self.decimal_scale(buy_mint)?;
+                let sell_reserve = sell_reserve.try_mul(sell_scale)?;
+                let buy_reserve = buy_reserve.try_mul(buy_scale)?;
 
-        let sold_or_neutral_token_balances_after_swap: Vec<Decimal> = reserves
-            .iter()
+                let d: Decimal = invariant.into();
+                let num_reserves = reserves.len() as u64;
+                let n = Decimal::from(num_reserves);
+                let amp_n_pow_n = Decimal::from(amplifier)
+                    .try_mul(n.try_pow(num_reserves)?)?;
+
+                // C = D * ∏(D / (n x_i)), which is the same as
+                // D^(n+1) / (n^n ∏x) but it doesn't overflow for large D
+                let c = self
+                    .normalized_reserves()?
+                    .into_iter()
+                    .try_fold(d, |acc, tokens| {
+                        acc.try_mul(d.try_div(n.try_mul(tokens)?)?)
+                    })?;
+
+                let sell_slope =
+                    amp_n_pow_n.try_add(c.try_div(sell_reserve)?)?;
+                let buy_slope = amp_n_pow_n.try_add(c.try_div(buy_reserve)?)?;
+
+                try_mul_div(
+                    Decimal::from(tokens_to_swap.amount).try_mul(sell_scale)?,
+                    sell_slope,
+                    buy_slope,
+                )?
+                .try_div(buy_scale)?
+            }
+            Curve::Weighted { .. } => {
+                let sell_weight = self.reserve_weight(sell_mint)?;
+                let buy_weight = self.reserve_weight(buy_mint)?;
+
+                try_mul_div(
+                    Decimal::from(tokens_to_swap.amount),
+                    buy_reserve.try_mul(Decimal::from(sell_weight as u64))?,
+                    sell_reserve.try_mul(Decimal::from(buy_weight as u64))?,
+                )?
+            }
+        };
+
+        Ok(tokens_to_receive.try_floor()?.into())
+    }
+
+    /// How many tokens to sell into the pool and in which direction so that
+    /// the ratio of the reserve of `mint_a` to the reserve of `mint_b` is
+    /// `target_ratio` after the swap, e.g. for a rebalancing bot. The other
+    /// reserves are not affected. Fees are not considered, ie. this is the
+    /// swap as priced by [`Pool::swap`].
+    ///
+    /// For constant product curve the product `x_a * x_b` doesn't change with
+    /// the swap, therefore the reserve which is sold into is
+    /// `x_a' = sqrt(x_a * x_b * r)` or `x_b' = sqrt(x_a * x_b / r)`.
+    ///
+    /// Other curves don't have such a closed form and we bisect the sell
+    /// amount instead, see [`Pool::bisect_swap_to_reach_ratio`].
+    ///
+    /// The ratio after the swap matches the target within rounding of the
+    /// token amounts.
+    pub fn calculate_swap_to_reach_ratio(
+        &self,
+        mint_a: Pubkey,
+        mint_b: Pubkey,
+        target_ratio: Decimal,
+    ) -> Result<RatioSwap> {
+        let reserve = |mint: Pubkey| {
+            self.reserves()
+                .iter()
+                .find(|r| r.mint == mint)
+                .map(|r| Decimal::from(r.tokens.amount))
+                .ok_or_else(|| {
+                    msg!("Mint '{}' is not a reserve of the pool", mint);
+                    error!(AmmError::InvalidArg)
+                })
+        };
+        let x_a = reserve(mint_a)?;
+        let x_b = reserve(mint_b)?;
+
+        if mint_a == mint_b || target_ratio == Decimal::zero() {
+            msg!("Provide two different mints and a positive target ratio");
+            return Err(error!(AmmError::InvalidArg));
+        }
+        if x_a == Decimal::zero() || x_b == Decimal::zero() {
+            msg!("Need to provide positive token reserves deposits");
+            return Err(error!(AmmError::InvalidArg));
+        }
+
+        // x_a / x_b < r
+        let sell_a = x_a < x_b.try_mul(target_ratio)?;
+        let (sell_mint, buy_mint) = if sell_a {
+            (mint_a, mint_b)
+        } else {
+            (mint_b, mint_a)
+        };
+
+        let sell = match self.curve {
+            Curve::ConstProd => {
+                let sell_reserve_after_swap = if sell_a {
+                    x_a.try_sqrt()?
+                        .try_mul(x_b.try_mul(target_ratio)?.try_sqrt()?)?
+                } else {
+                    x_b.try_sqrt()?
+                        .try_mul(x_a.try_div(target_ratio)?.try_sqrt()?)?
+                };
+                let sell_reserve = if sell_a { x_a } else { x_b };
+
+                if sell_reserve_after_swap <= sell_reserve {
+                    // the ratio is within rounding already
+                    TokenAmount::new(0)
+                } else {
+                    TokenAmount::new(
+                        sell_reserve_after_swap
+                            .try_sub(sell_reserve)?
+                            .try_round()?,
+                    )
+                }
+            }
+            Curve::Stable { .. } | Curve::Weighted { .. } => self
+                .bisect_swap_to_reach_ratio(
+                    mint_a,
+                    mint_b,
+                    sell_a,
+                    target_ratio,
+                )?,
+        };
+
+        Ok(RatioSwap {
+            sell_mint,
+            buy_mint,
+            sell,
+        })
+    }
+
+    /// Finds the smallest sell amount with which the ratio of the reserve of
+    /// `mint_a` to the reserve of `mint_b` reaches `target_ratio`. The ratio
+    /// after the swap is monotonic in the sell amount, therefore a bisection
+    /// over all sell amounts converges in at most 64 swap calculations.
+    fn bisect_swap_to_reach_ratio(
+        &self,
+        mint_a: Pubkey,
+        mint_b: Pubkey,
+        sell_a: bool,
+        target_ratio: Decimal,
+    ) -> Result<TokenAmount> {
+        let (sell_mint, buy_mint) = if sell_a {
+            (mint_a, mint_b)
+        } else {
+            (mint_b, mint_a)
+        };
+        let tokens = |mint: Pubkey| {
+            self.reserves()
+                .iter()
+                .find(|r| r.mint == mint)
+                .map(|r| r.tokens.amount)
+                .unwrap_or_default()
+        };
+        let sell_reserve = tokens(sell_mint);
+        let buy_reserve = tokens(buy_mint);
+
+        let reaches_ratio = |sell: u64| -> Result<bool> {
+            let bought = self
+                .calculate_swap(sell_mint, TokenAmount::new(sell), buy_mint)?
+                .amount;
+            let sell_reserve_after_swap =
+                Decimal::from(sell_reserve.saturating_add(sell));
+            let buy_reserve_after_swap =
+                Decimal::from(buy_reserve.saturating_sub(bought));
+
+            Ok(if sell_a {
+                sell_reserve_after_swap
+                    >= buy_reserve_after_swap.try_mul(target_ratio)?
+            } else {
+                buy_reserve_after_swap
+                    <= sell_reserve_after_swap.try_mul(target_ratio)?
+            })
+        };
+
+        if reaches_ratio(0)? {
+            return Ok(TokenAmount::new(0));
+        }
+
+        // the whole reserve cannot be swapped, see [`Pool::calculate_swap`]
+        let mut low = 0;
+        let mut high = sell_reserve.saturating_sub(1);
+        if !reaches_ratio(high)? {
+            msg!("Target ratio cannot be reached with a single swap");
+            return Err(error!(AmmError::InvalidArg));
+        }
+
+        // invariant: ratio is not reached with `low`, and is with `high`
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if reaches_ratio(mid)? {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+
+        Ok(TokenAmount::new(high))
+    }
+
+    /// The factor by which the stable curve multiplies the reserve of given
+    /// mint, see [`Pool::decimal_scales`]. One for other curves.
+    fn decimal_scale(&self, mint: Pubkey) -> Result<Decimal> {
+        let index = self.reserves().iter().position(|r| r.mint == mint);
+        match (self.curve, index) {
+            (Curve::Stable { .. }, Some(index)) => Ok(Decimal::from(10_u64)
+                .try_pow(self.decimal_scales[index] as u64)?),
+            _ => Ok(Decimal::one()),
+        }
+    }
+
+    /// Reserve amounts multiplied by [`Pool::decimal_scale`], in the order of
+    /// [`Pool::reserves`].
+    fn normalized_reserves(&self) -> Result<Vec<Decimal>> {
+        self.reserves()
+            .iter()
+            .map(|r| {
+                Ok(Decimal::from(r.tokens.amount)
+                    .try_mul(self.decimal_scale(r.mint)?)?)
+            })
+            .collect()
+    }
+
+    /// Weight of the reserve with given mint if the pool's curve is
+    /// [`Curve::Weighted`].
+    fn reserve_weight(&self, mint: Pubkey) -> Result<u8> {
+        match self.curve {
+            Curve::Weighted { weight_a, .. }
+                if self.reserves[0].mint == mint =>
+            {
+                Ok(weight_a)
+            }
+            Curve::Weighted { weight_b, .. }
+                if self.reserves[1].mint == mint =>
+            {
+                Ok(weight_b)
+            }
+            _ => {
+                msg!("Reserve has no weight");
+                Err(error!(AmmError::InvariantViolation))
+            }
+        }
+    }
+
+    /// Given the current state of the pool, how many buy tokens does the
+    /// trader receive out of the quote tokens he/she is selling in the swap.
+    fn calculate_swap(
+        &self,
+        sell_mint: Pubkey,
+        tokens_to_swap: TokenAmount,
+        buy_mint: Pubkey,
+    ) -> Result<TokenAmount> {
+        // the most common pool is a constant product one with two reserves,
+        // which we can price without the general machinery
+        if matches!(self.curve, Curve::ConstProd)
+            && self.dimension == 2
+            && sell_mint != buy_mint
+        {
+            self.calculate_const_prod_swap(sell_mint, tokens_to_swap, buy_mint)
+        } else {
+            self.calculate_swap_on_curve(sell_mint, tokens_to_swap, buy_mint)
+        }
+    }
+
+    /// Specialization of [`Pool::calculate_swap_on_curve`] for constant
+    /// product pools with two reserves. It avoids collecting the reserves into
+    /// intermediate collections, which saves compute units. Both price the
+    /// swap with [`const_prod_tokens_to_receive`], therefore the result is the
+    /// same.
+    fn calculate_const_prod_swap(
+        &self,
+        sell_mint: Pubkey,
+        tokens_to_swap: TokenAmount,
+        buy_mint: Pubkey,
+    ) -> Result<TokenAmount> {
+        debug_assert!(matches!(self.curve, Curve::ConstProd));
+        debug_assert_eq!(self.dimension, 2);
+
+        if self.reserves().iter().any(|r| r.tokens.amount == 0) {
+            msg!("Need to provide positive token reserves deposits");
+            return Err(error!(AmmError::InvalidArg));
+        }
+
+        let buy_reserve = self
+            .reserves()
+            .iter()
+            .find(|r| r.mint == buy_mint)
+            .ok_or_else(|| {
+                msg!("Provided long (buy) token mint is invalid");
+                error!(AmmError::InvalidArg)
+            })?
+            .tokens;
+        let sell_reserve = self
+            .reserves()
+            .iter()
+            .find(|r| r.mint == sell_mint)
+            .ok_or_else(|| {
+                msg!("Provided short (sell) token mint is invalid");
+                error!(AmmError::InvalidArg)
+            })?
+            .tokens;
+
+        // see the same check in [`Pool::calculate_swap_on_curve`]
+        if tokens_to_swap >= sell_reserve {
+            msg!(
+                "The user tries to swap the total amount of a single
+                 token reserve within the pool"
+            );
+            return Err(error!(AmmError::InvalidArg));
+        }
+
+        Ok(const_prod_tokens_to_receive(
+            sell_reserve,
+            tokens_to_swap,
+            buy_reserve,
+        ))
+    }
+
+    /// The general swap calculation which works for any curve and any number
+    /// of reserves. See [`Pool::calculate_swap`].
+    fn calculate_swap_on_curve(
+        &self,
+        sell_mint: Pubkey,
+        tokens_to_swap: TokenAmount,
+        buy_mint: Pubkey,
+    ) -> Result<TokenAmount> {
+        let reserves: BTreeMap<_, _> =
+            self.reserves().iter().map(|r| (r.mint, r.tokens)).collect();
+
+        if reserves.values().any(|v| v.amount == 0) {
+            msg!("Need to provide positive token reserves deposits");
+            return Err(error!(AmmError::InvalidArg));
+        }
+
+        if !reserves.contains_key(&buy_mint) {
+            msg!("Provided long (buy) token mint is invalid");
+            return Err(error!(AmmError::InvalidArg));
+        }
+
+        if !reserves.contains_key(&sell_mint) {
+            msg!("Provided short (sell) token mint is invalid");
+            return Err(error!(AmmError::InvalidArg));
+        }
+
+        // checks if amount of short (sell) token to be swapped fits within
+        // current pool liquidity. It is important we don't allow
+        // user to swap the total number of tokens in the pool. The reason
+        // why we check the sell tokens instead of buy tokens is because the
+        // buy tokens amount is still unknown at this time, but it the sell
+        // tokens amount is bigger than the amount in the pool that would
+        // result in a buy tokens amount > to the amount of tokens in the pool
+        if tokens_to_swap >= *reserves.get(&sell_mint).unwrap() {
+            msg!(
+                "The user tries to swap the total amount of a single
+                 token reserve within the pool"
+            );
+            return Err(error!(AmmError::InvalidArg));
+        }
+
+        if matches!(self.curve, Curve::ConstProd) {
+            // the product of the reserves doesn't change if a mint is sold for
+            // itself
+            if sell_mint == buy_mint {
+                return Ok(TokenAmount::new(0));
+            }
+
+            return Ok(const_prod_tokens_to_receive(
+                *reserves.get(&sell_mint).unwrap(),
+                tokens_to_swap,
+                *reserves.get(&buy_mint).unwrap(),
+            ));
+        }
+
+        let sold_or_neutral_token_balances_after_swap: Vec<Decimal> = reserves
+            .iter()
             // we filter out the buy mint value
             .filter(|(mint, _)| **mint != buy_mint)
             .map(|(mint, tokens)| {
-                Decimal::from(if *mint == sell_mint {
+                let balance = Decimal::from(if *mint == sell_mint {
                     // update the amount of sell token reserve
                     // notice that this calculation does not underflow
                     // because we checked above that
@@ -628,21 +1700,19 @@ impl Pool {
                     tokens.amount + tokens_to_swap.amount
                 } else {
                     tokens.amount
-                })
+                });
+
+                // scale is one unless the curve is stable
+                Ok(balance.try_mul(self.decimal_scale(*mint)?)?)
             })
-            .collect();
+            .collect::<Result<_>>()?;
 
         let product = fold_product(&sold_or_neutral_token_balances_after_swap)?;
 
         let bought_token_balance_after_swap = match self.curve {
             Curve::ConstProd => {
-                let tokens_deposits_before_swap: Vec<Decimal> = reserves
-                    .values()
-                    .map(|v| Decimal::from(v.amount))
-                    .collect();
-
-                let k = fold_product(&tokens_deposits_before_swap)?;
-                k.try_div(product)?
+                msg!("Constant product swaps are priced above");
+                return Err(error!(AmmError::InvariantViolation));
             }
             Curve::Stable {
                 amplifier,
@@ -664,8 +1734,25 @@ impl Pool {
                     product,
                 )?
             }
+            Curve::Weighted { .. } => {
+                let sell_reserve = reserves.get(&sell_mint).unwrap().amount;
+
+                get_buy_reserve_balance_after_weighted_swap(
+                    Decimal::from(sell_reserve),
+                    Decimal::from(sell_reserve + tokens_to_swap.amount),
+                    Decimal::from(reserves.get(&buy_mint).unwrap().amount),
+                    self.reserve_weight(sell_mint)?,
+                    self.reserve_weight(buy_mint)?,
+                )?
+            }
         };
 
+        // the stable curve works with normalized amounts, see
+        // [`Pool::decimal_scales`]
+        let bought_token_balance_after_swap =
+            bought_token_balance_after_swap
+                .try_div(self.decimal_scale(buy_mint)?)?;
+
         let tokens_to_receive = compute_delta_withdraw_token_amount(
             bought_token_balance_after_swap,
             reserves,
@@ -1084,124 +2171,350 @@ mod tests {
     }
 
     #[test]
-    fn it_calculates_tokens_to_redeem_when_min_tokens_match_tokens_to_redeem(
-    ) -> Result<()> {
+    fn it_decodes_liquidity_result_of_deposit() -> Result<()> {
         let mint1 = Pubkey::new_unique();
         let mint2 = Pubkey::new_unique();
-        let mint3 = Pubkey::new_unique();
 
         let mut pool = Pool {
             mint: Pubkey::new_unique(),
-            dimension: 3,
+            dimension: 2,
             reserves: [
                 Reserve {
-                    tokens: TokenAmount::new(10),
+                    tokens: TokenAmount::new(100),
                     mint: mint1,
                     vault: Pubkey::default(),
                 },
                 Reserve {
-                    tokens: TokenAmount::new(100),
+                    tokens: TokenAmount::new(200),
                     mint: mint2,
                     vault: Pubkey::default(),
                 },
-                Reserve {
-                    tokens: TokenAmount::new(250),
-                    mint: mint3,
-                    vault: Pubkey::default(),
-                },
+                Reserve::default(),
                 Reserve::default(),
             ],
             ..Default::default()
         };
 
-        let lp_mint_supply = TokenAmount::new(1_000);
-        let lp_tokens_to_burn = TokenAmount::new(100);
-        let mut min_tokens: BTreeMap<Pubkey, TokenAmount> = BTreeMap::new();
-
-        min_tokens.insert(mint1, TokenAmount::new(1));
-        min_tokens.insert(mint2, TokenAmount::new(10));
-        min_tokens.insert(mint3, TokenAmount::new(25));
-
-        let tokens_to_redeem =
-            pool.redeem_tokens(min_tokens, lp_tokens_to_burn, lp_mint_supply)?;
-
-        // Check the pool was currectly updated
-        assert_eq!(pool.reserves[0].mint, mint1);
-        assert_eq!(pool.reserves[0].tokens.amount, 10 - 1);
-
-        assert_eq!(pool.reserves[1].mint, mint2);
-        assert_eq!(pool.reserves[1].tokens.amount, 100 - 10);
-
-        assert_eq!(pool.reserves[2].mint, mint3);
-        assert_eq!(pool.reserves[2].tokens.amount, 250 - 25);
+        let mut max_tokens: BTreeMap<Pubkey, TokenAmount> = BTreeMap::new();
+        max_tokens.insert(mint1, TokenAmount::new(10));
+        max_tokens.insert(mint2, TokenAmount::new(20));
 
-        // check that calculated tokens to redeem is correct
-        assert_eq!(tokens_to_redeem.get(&mint1).unwrap().amount, 1);
-        assert_eq!(tokens_to_redeem.get(&mint2).unwrap().amount, 10);
-        assert_eq!(tokens_to_redeem.get(&mint3).unwrap().amount, 25);
+        let DepositResult {
+            lp_tokens_to_distribute,
+            tokens_to_deposit,
+        } = pool.deposit_tokens(max_tokens, TokenAmount::new(1_000))?;
+        let lp_tokens_minted = lp_tokens_to_distribute.unwrap();
+
+        let data =
+            LiquidityResult::deposited(lp_tokens_minted, &tokens_to_deposit)
+                .try_to_vec()?;
+        let result = LiquidityResult::try_from_slice(&data)?;
+
+        assert_eq!(result.lp_tokens_minted, TokenAmount::new(100));
+        assert_eq!(result.lp_tokens_burned, TokenAmount::new(0));
+        assert_eq!(result.tokens.len(), 2);
+        for TokenLimit { mint, tokens } in result.tokens {
+            assert_eq!(tokens_to_deposit[&mint], tokens);
+        }
+        assert_eq!(tokens_to_deposit[&mint1], TokenAmount::new(10));
+        assert_eq!(tokens_to_deposit[&mint2], TokenAmount::new(20));
 
         Ok(())
     }
 
     #[test]
-    fn it_calculates_tokens_to_redeem_after_token_deposit() -> Result<()> {
+    fn it_decodes_liquidity_result_of_redeem() -> Result<()> {
         let mint1 = Pubkey::new_unique();
         let mint2 = Pubkey::new_unique();
-        let mint3 = Pubkey::new_unique();
 
         let mut pool = Pool {
             mint: Pubkey::new_unique(),
-            dimension: 3,
+            dimension: 2,
             reserves: [
                 Reserve {
-                    tokens: TokenAmount::new(0),
+                    tokens: TokenAmount::new(100),
                     mint: mint1,
                     vault: Pubkey::default(),
                 },
                 Reserve {
-                    tokens: TokenAmount::new(0),
+                    tokens: TokenAmount::new(200),
                     mint: mint2,
                     vault: Pubkey::default(),
                 },
-                Reserve {
-                    tokens: TokenAmount::new(0),
-                    mint: mint3,
-                    vault: Pubkey::default(),
-                },
+                Reserve::default(),
                 Reserve::default(),
             ],
             ..Default::default()
         };
 
-        let mut lp_mint_supply = TokenAmount::new(0);
-        let mut max_tokens: BTreeMap<Pubkey, TokenAmount> = BTreeMap::new();
-
-        max_tokens.insert(mint1, TokenAmount::new(100));
-        max_tokens.insert(mint2, TokenAmount::new(100));
-        max_tokens.insert(mint3, TokenAmount::new(100));
-
-        pool.deposit_tokens(max_tokens, lp_mint_supply)?;
-
-        lp_mint_supply = TokenAmount::new(100);
-        let lp_tokens_to_burn = TokenAmount::new(50);
         let mut min_tokens: BTreeMap<Pubkey, TokenAmount> = BTreeMap::new();
+        min_tokens.insert(mint1, TokenAmount::new(0));
+        min_tokens.insert(mint2, TokenAmount::new(0));
 
-        min_tokens.insert(mint1, TokenAmount::new(50));
-        min_tokens.insert(mint2, TokenAmount::new(50));
-        min_tokens.insert(mint3, TokenAmount::new(50));
-
-        let tokens_to_redeem =
-            pool.redeem_tokens(min_tokens, lp_tokens_to_burn, lp_mint_supply)?;
+        let lp_tokens_burned = TokenAmount::new(100);
+        let tokens_to_redeem = pool.redeem_tokens(
+            min_tokens,
+            lp_tokens_burned,
+            TokenAmount::new(1_000),
+        )?;
 
-        // Check the pool was currectly updated
-        assert_eq!(pool.reserves[0].mint, mint1);
-        assert_eq!(pool.reserves[0].tokens.amount, 100 - 50);
+        let data =
+            LiquidityResult::redeemed(lp_tokens_burned, &tokens_to_redeem)
+                .try_to_vec()?;
+        let result = LiquidityResult::try_from_slice(&data)?;
+
+        assert_eq!(result.lp_tokens_minted, TokenAmount::new(0));
+        assert_eq!(result.lp_tokens_burned, TokenAmount::new(100));
+        let mut expected = vec![
+            TokenLimit {
+                mint: mint1,
+                tokens: TokenAmount::new(10),
+            },
+            TokenLimit {
+                mint: mint2,
+                tokens: TokenAmount::new(20),
+            },
+        ];
+        expected.sort_by_key(|t| t.mint);
+        assert_eq!(result.tokens, expected);
 
-        assert_eq!(pool.reserves[1].mint, mint2);
-        assert_eq!(pool.reserves[1].tokens.amount, 100 - 50);
+        Ok(())
+    }
 
-        assert_eq!(pool.reserves[2].mint, mint3);
-        assert_eq!(pool.reserves[2].tokens.amount, 100 - 50);
+    #[test]
+    fn it_reports_rounding_matching_amounts_moved() -> Result<()> {
+        let mint1 = Pubkey::new_unique();
+        let mint2 = Pubkey::new_unique();
+
+        let pool = Pool {
+            mint: Pubkey::new_unique(),
+            dimension: 2,
+            reserves: [
+                Reserve {
+                    tokens: TokenAmount::new(100),
+                    mint: mint1,
+                    vault: Pubkey::default(),
+                },
+                Reserve {
+                    tokens: TokenAmount::new(333),
+                    mint: mint2,
+                    vault: Pubkey::default(),
+                },
+                Reserve::default(),
+                Reserve::default(),
+            ],
+            ..Default::default()
+        };
+        let lp_supply = TokenAmount::new(1_000);
+        let tokens_of = |result: &LiquidityResult, mint: Pubkey| {
+            result
+                .tokens
+                .iter()
+                .find(|t| t.mint == mint)
+                .unwrap()
+                .tokens
+        };
+
+        // a tenth of the reserves, ie. 10 and 33.3
+        let max_tokens =
+            [(mint1, TokenAmount::new(10)), (mint2, TokenAmount::new(34))]
+                .into_iter()
+                .collect();
+        let deposited = pool.quote_deposit(max_tokens, lp_supply)?;
+        assert_eq!(deposited.tokens_rounding, Rounding::Ceiling);
+        assert_eq!(tokens_of(&deposited, mint1), TokenAmount::new(10));
+        assert_eq!(tokens_of(&deposited, mint2), TokenAmount::new(34));
+
+        let redeemed = pool.quote_redeem(TokenAmount::new(100), lp_supply)?;
+        assert_eq!(redeemed.tokens_rounding, Rounding::Floor);
+        assert_eq!(tokens_of(&redeemed, mint1), TokenAmount::new(10));
+        assert_eq!(tokens_of(&redeemed, mint2), TokenAmount::new(33));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_calculates_lp_value_same_as_redeem_of_one_lp_token() -> Result<()> {
+        let mint1 = Pubkey::new_unique();
+        let mint2 = Pubkey::new_unique();
+
+        let mut pool = Pool {
+            dimension: 2,
+            reserves: [
+                Reserve {
+                    tokens: TokenAmount::new(1_000),
+                    mint: mint1,
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve {
+                    tokens: TokenAmount::new(3_299),
+                    mint: mint2,
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve::default(),
+                Reserve::default(),
+            ],
+            ..Default::default()
+        };
+        let lp_supply = TokenAmount::new(300);
+
+        let data = pool.lp_value(lp_supply)?.try_to_vec()?;
+        let lp_value = LpValue::try_from_slice(&data)?;
+
+        assert_eq!(lp_value.lp_supply, lp_supply);
+        assert_eq!(lp_value.reserves.len(), 2);
+        for TokenLimit { mint, tokens } in &lp_value.reserves {
+            assert_eq!(pool.reserves_hashmap()[mint], *tokens);
+        }
+
+        let min_tokens =
+            [(mint1, TokenAmount::new(0)), (mint2, TokenAmount::new(0))]
+                .into_iter()
+                .collect();
+        let redeemed =
+            pool.redeem_tokens(min_tokens, TokenAmount::new(1), lp_supply)?;
+
+        assert_eq!(lp_value.tokens_per_lp_token.len(), 2);
+        for TokenLimit { mint, tokens } in lp_value.tokens_per_lp_token {
+            assert_eq!(redeemed[&mint], tokens);
+        }
+        // rounded down, 3.33 and 10.99
+        assert_eq!(redeemed[&mint1], TokenAmount::new(3));
+        assert_eq!(redeemed[&mint2], TokenAmount::new(10));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_errs_lp_value_when_zero_lp_mint_supply() {
+        let pool = Pool {
+            mint: Pubkey::new_unique(),
+            dimension: 2,
+            ..Default::default()
+        };
+
+        assert!(pool.lp_value(TokenAmount::new(0)).is_err());
+    }
+
+    #[test]
+    fn it_calculates_tokens_to_redeem_when_min_tokens_match_tokens_to_redeem(
+    ) -> Result<()> {
+        let mint1 = Pubkey::new_unique();
+        let mint2 = Pubkey::new_unique();
+        let mint3 = Pubkey::new_unique();
+
+        let mut pool = Pool {
+            mint: Pubkey::new_unique(),
+            dimension: 3,
+            reserves: [
+                Reserve {
+                    tokens: TokenAmount::new(10),
+                    mint: mint1,
+                    vault: Pubkey::default(),
+                },
+                Reserve {
+                    tokens: TokenAmount::new(100),
+                    mint: mint2,
+                    vault: Pubkey::default(),
+                },
+                Reserve {
+                    tokens: TokenAmount::new(250),
+                    mint: mint3,
+                    vault: Pubkey::default(),
+                },
+                Reserve::default(),
+            ],
+            ..Default::default()
+        };
+
+        let lp_mint_supply = TokenAmount::new(1_000);
+        let lp_tokens_to_burn = TokenAmount::new(100);
+        let mut min_tokens: BTreeMap<Pubkey, TokenAmount> = BTreeMap::new();
+
+        min_tokens.insert(mint1, TokenAmount::new(1));
+        min_tokens.insert(mint2, TokenAmount::new(10));
+        min_tokens.insert(mint3, TokenAmount::new(25));
+
+        let tokens_to_redeem =
+            pool.redeem_tokens(min_tokens, lp_tokens_to_burn, lp_mint_supply)?;
+
+        // Check the pool was currectly updated
+        assert_eq!(pool.reserves[0].mint, mint1);
+        assert_eq!(pool.reserves[0].tokens.amount, 10 - 1);
+
+        assert_eq!(pool.reserves[1].mint, mint2);
+        assert_eq!(pool.reserves[1].tokens.amount, 100 - 10);
+
+        assert_eq!(pool.reserves[2].mint, mint3);
+        assert_eq!(pool.reserves[2].tokens.amount, 250 - 25);
+
+        // check that calculated tokens to redeem is correct
+        assert_eq!(tokens_to_redeem.get(&mint1).unwrap().amount, 1);
+        assert_eq!(tokens_to_redeem.get(&mint2).unwrap().amount, 10);
+        assert_eq!(tokens_to_redeem.get(&mint3).unwrap().amount, 25);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_calculates_tokens_to_redeem_after_token_deposit() -> Result<()> {
+        let mint1 = Pubkey::new_unique();
+        let mint2 = Pubkey::new_unique();
+        let mint3 = Pubkey::new_unique();
+
+        let mut pool = Pool {
+            mint: Pubkey::new_unique(),
+            dimension: 3,
+            reserves: [
+                Reserve {
+                    tokens: TokenAmount::new(0),
+                    mint: mint1,
+                    vault: Pubkey::default(),
+                },
+                Reserve {
+                    tokens: TokenAmount::new(0),
+                    mint: mint2,
+                    vault: Pubkey::default(),
+                },
+                Reserve {
+                    tokens: TokenAmount::new(0),
+                    mint: mint3,
+                    vault: Pubkey::default(),
+                },
+                Reserve::default(),
+            ],
+            ..Default::default()
+        };
+
+        let mut lp_mint_supply = TokenAmount::new(0);
+        let mut max_tokens: BTreeMap<Pubkey, TokenAmount> = BTreeMap::new();
+
+        max_tokens.insert(mint1, TokenAmount::new(100));
+        max_tokens.insert(mint2, TokenAmount::new(100));
+        max_tokens.insert(mint3, TokenAmount::new(100));
+
+        pool.deposit_tokens(max_tokens, lp_mint_supply)?;
+
+        lp_mint_supply = TokenAmount::new(100);
+        let lp_tokens_to_burn = TokenAmount::new(50);
+        let mut min_tokens: BTreeMap<Pubkey, TokenAmount> = BTreeMap::new();
+
+        min_tokens.insert(mint1, TokenAmount::new(50));
+        min_tokens.insert(mint2, TokenAmount::new(50));
+        min_tokens.insert(mint3, TokenAmount::new(50));
+
+        let tokens_to_redeem =
+            pool.redeem_tokens(min_tokens, lp_tokens_to_burn, lp_mint_supply)?;
+
+        // Check the pool was currectly updated
+        assert_eq!(pool.reserves[0].mint, mint1);
+        assert_eq!(pool.reserves[0].tokens.amount, 100 - 50);
+
+        assert_eq!(pool.reserves[1].mint, mint2);
+        assert_eq!(pool.reserves[1].tokens.amount, 100 - 50);
+
+        assert_eq!(pool.reserves[2].mint, mint3);
+        assert_eq!(pool.reserves[2].tokens.amount, 100 - 50);
 
         // check that calculated tokens to redeem is correct
         assert_eq!(tokens_to_redeem.get(&mint1).unwrap().amount, 50);
@@ -1720,7 +3033,9 @@ mod tests {
         pool.update_curve_invariant().unwrap();
 
         let invariant = match pool.curve {
-            Curve::ConstProd => panic!("unexpected constant product curve"),
+            Curve::ConstProd | Curve::Weighted { .. } => {
+                panic!("unexpected curve")
+            }
             Curve::Stable { invariant, .. } => invariant,
         };
 
@@ -2041,73 +3356,522 @@ mod tests {
         );
     }
 
-    #[test]
-    fn works_if_stable_swap_curve() {
-        let deposit_mint = Pubkey::new_unique();
-        let withdraw_mint = Pubkey::new_unique();
-
-        let curve = Curve::Stable {
-            amplifier: 10,
-            invariant: 0_u64.into(),
-        };
-        let mut pool = Pool {
-            curve,
+    fn weighted_pool(
+        curve: Curve,
+        mint_a: Pubkey,
+        mint_b: Pubkey,
+        tokens: u64,
+    ) -> Pool {
+        Pool {
             dimension: 2,
             reserves: [
                 Reserve {
-                    tokens: TokenAmount { amount: 10 },
-                    mint: withdraw_mint,
+                    tokens: TokenAmount::new(tokens),
+                    mint: mint_a,
                     vault: Pubkey::new_unique(),
                 },
                 Reserve {
-                    tokens: TokenAmount { amount: 100 },
-                    mint: deposit_mint,
+                    tokens: TokenAmount::new(tokens),
+                    mint: mint_b,
                     vault: Pubkey::new_unique(),
                 },
                 Reserve::default(),
                 Reserve::default(),
             ],
+            curve,
             ..Default::default()
-        };
-        pool.update_curve_invariant().unwrap();
-        let tokens_to_swap = TokenAmount::new(50);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn const_prod_fast_path_matches_generic_swap(
+            reserve_a in 1..=u64::MAX,
+            reserve_b in 1..=u64::MAX,
+            tokens_to_swap in 1..=u64::MAX,
+        ) {
+            let mint_a = Pubkey::new_unique();
+            let mint_b = Pubkey::new_unique();
+            let mut pool =
+                weighted_pool(Curve::ConstProd, mint_a, mint_b, reserve_a);
+            pool.reserves[1].tokens = TokenAmount::new(reserve_b);
+            let tokens_to_swap = TokenAmount::new(tokens_to_swap);
+
+            for (sell_mint, buy_mint) in [(mint_a, mint_b), (mint_b, mint_a)] {
+                prop_assert_eq!(
+                    pool.calculate_swap(sell_mint, tokens_to_swap, buy_mint)
+                        .ok(),
+                    pool.calculate_swap_on_curve(
+                        sell_mint,
+                        tokens_to_swap,
+                        buy_mint
+                    )
+                    .ok()
+                );
+            }
+        }
+    }
 
+    #[test]
+    fn it_swaps_tiny_amount_of_high_value_token() {
+        let btc = Pubkey::new_unique();
+        let shib = Pubkey::new_unique();
+        let mut pool = weighted_pool(Curve::ConstProd, btc, shib, 1_000);
+        pool.reserves[1].tokens = TokenAmount::new(10_000_000_000_000_000_000);
+
+        // 10^19 * 1 / (1_000 + 1) rounded down
         assert_eq!(
-            pool.calculate_swap(deposit_mint, tokens_to_swap, withdraw_mint)
-                .unwrap(),
-            // The result for the root polynomial is x = 1.007841346549156426
-            // Where x is the token balance of the token bought by the trader
-            // after the swap. This means that floor(10 - 1.007841346549156426)
-            // = floor(8.99) = 8
-            8_u64.into()
+            pool.calculate_swap(btc, TokenAmount::new(1), shib).unwrap(),
+            TokenAmount::new(9_990_009_990_009_990)
+        );
+
+        // and back, 1_000 * 10^17 / (10^19 + 10^17) rounded down
+        assert_eq!(
+            pool.calculate_swap(
+                shib,
+                TokenAmount::new(100_000_000_000_000_000),
+                btc
+            )
+            .unwrap(),
+            TokenAmount::new(9)
         );
     }
 
     #[test]
-    fn stable_swap_curve_works_for_high_amounts() {
-        let sell_mint = Pubkey::new_unique();
-        let buy_mint = Pubkey::new_unique();
+    fn it_swaps_tiny_amount_of_high_value_token_with_three_reserves() {
+        let btc = Pubkey::new_unique();
+        let shib = Pubkey::new_unique();
+        let mut pool = weighted_pool(Curve::ConstProd, btc, shib, 1_000);
+        pool.reserves[1].tokens = TokenAmount::new(10_000_000_000_000_000_000);
+        pool.reserves[2] = Reserve {
+            tokens: TokenAmount::new(10_000_000_000_000_000_000),
+            mint: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+        };
+        pool.dimension = 3;
 
-        let tokens_to_swap = TokenAmount::new(50);
+        // the third reserve doesn't change the price, 10^19 * 1 / (1_000 + 1)
+        // rounded down
+        assert_eq!(
+            pool.calculate_swap(btc, TokenAmount::new(1), shib).unwrap(),
+            TokenAmount::new(9_990_009_990_009_990)
+        );
+    }
 
-        for tokens in [
-            TokenAmount::new(100),
-            TokenAmount::new(1000),
-            TokenAmount::new(100_000),
-            TokenAmount::new(1_000_000),
-            TokenAmount::new(100_000_000),
-            TokenAmount::new(1_000_000_000),
-            TokenAmount::new(10_000_000_000),
-            TokenAmount::new(100_000_000_000),
+    #[test]
+    fn it_errs_const_prod_fast_path_same_as_generic_swap() {
+        let mint_a = Pubkey::new_unique();
+        let mint_b = Pubkey::new_unique();
+        let pool = weighted_pool(Curve::ConstProd, mint_a, mint_b, 1_000);
+
+        for (sell_mint, tokens_to_swap, buy_mint) in [
+            // unknown sell mint
+            (Pubkey::new_unique(), 10, mint_b),
+            // unknown buy mint
+            (mint_a, 10, Pubkey::new_unique()),
+            // whole reserve
+            (mint_a, 1_000, mint_b),
         ] {
-            let curve: Curve = Curve::Stable {
-                amplifier: 10,
-                invariant: (2 * tokens.amount).into(),
-            };
-            let pool = Pool {
-                curve,
-                dimension: 2,
-                reserves: [
+            let tokens_to_swap = TokenAmount::new(tokens_to_swap);
+            assert!(pool
+                .calculate_const_prod_swap(sell_mint, tokens_to_swap, buy_mint)
+                .is_err());
+            assert!(pool
+                .calculate_swap_on_curve(sell_mint, tokens_to_swap, buy_mint)
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn weighted_curve_with_equal_weights_matches_constant_product() {
+        let mint_a = Pubkey::new_unique();
+        let mint_b = Pubkey::new_unique();
+
+        let const_prod =
+            weighted_pool(Curve::ConstProd, mint_a, mint_b, 1_000_000);
+        let weighted = weighted_pool(
+            Curve::Weighted {
+                weight_a: 50,
+                weight_b: 50,
+            },
+            mint_a,
+            mint_b,
+            1_000_000,
+        );
+
+        for tokens_to_swap in [1, 10_000, 500_000] {
+            let tokens_to_swap = TokenAmount::new(tokens_to_swap);
+            assert_eq!(
+                weighted
+                    .calculate_swap(mint_a, tokens_to_swap, mint_b)
+                    .unwrap(),
+                const_prod
+                    .calculate_swap(mint_a, tokens_to_swap, mint_b)
+                    .unwrap(),
+            );
+        }
+    }
+
+    #[test]
+    fn works_if_weighted_curve() {
+        let mint_a = Pubkey::new_unique();
+        let mint_b = Pubkey::new_unique();
+
+        let const_prod =
+            weighted_pool(Curve::ConstProd, mint_a, mint_b, 1_000_000);
+        let weighted = weighted_pool(
+            Curve::Weighted {
+                weight_a: 80,
+                weight_b: 20,
+            },
+            mint_a,
+            mint_b,
+            1_000_000,
+        );
+
+        let tokens_to_swap = TokenAmount::new(10_000);
+
+        // 1_000_000 - 1_000_000^2 / 1_010_000 = 9_900.99
+        assert_eq!(
+            const_prod
+                .calculate_swap(mint_a, tokens_to_swap, mint_b)
+                .unwrap(),
+            TokenAmount::new(9_900)
+        );
+
+        // with the same amount of tokens in each reserve, the reserve with
+        // higher weight is more valuable, hence selling A yields more B
+        //
+        // 1_000_000 * (1 - (1_000_000 / 1_010_000)^(80 / 20)) = 39_019.66
+        assert_eq!(
+            weighted
+                .calculate_swap(mint_a, tokens_to_swap, mint_b)
+                .unwrap(),
+            TokenAmount::new(39_019)
+        );
+
+        // and selling B yields less A
+        //
+        // 1_000_000 * (1 - (1_000_000 / 1_010_000)^(20 / 80)) = 2_484.49
+        assert_eq!(
+            weighted
+                .calculate_swap(mint_b, tokens_to_swap, mint_a)
+                .unwrap(),
+            TokenAmount::new(2_484)
+        );
+
+        // the marginal price is 4x the constant product one
+        assert_eq!(
+            const_prod
+                .calculate_spot_swap(mint_a, tokens_to_swap, mint_b)
+                .unwrap(),
+            TokenAmount::new(10_000)
+        );
+        assert_eq!(
+            weighted
+                .calculate_spot_swap(mint_a, tokens_to_swap, mint_b)
+                .unwrap(),
+            TokenAmount::new(40_000)
+        );
+        assert_eq!(
+            weighted
+                .calculate_spot_swap(mint_b, tokens_to_swap, mint_a)
+                .unwrap(),
+            TokenAmount::new(2_500)
+        );
+    }
+
+    #[test]
+    fn weighted_curve_preserves_invariant_on_swap() -> Result<()> {
+        let mint_a = Pubkey::new_unique();
+        let mint_b = Pubkey::new_unique();
+
+        let mut pool = weighted_pool(
+            Curve::Weighted {
+                weight_a: 80,
+                weight_b: 20,
+            },
+            mint_a,
+            mint_b,
+            1_000_000,
+        );
+
+        // x^0.8 y^0.2 = k <=> x^4 y = k^5
+        let invariant = |pool: &Pool| -> Result<Decimal> {
+            let x = Decimal::from(pool.reserves[0].tokens.amount)
+                .try_div(Decimal::from(1_000_000u64))?;
+            let y = Decimal::from(pool.reserves[1].tokens.amount)
+                .try_div(Decimal::from(1_000_000u64))?;
+            Ok(x.try_pow(4)?.try_mul(y)?)
+        };
+
+        let invariant_before_swap = invariant(&pool)?;
+        pool.swap(mint_a, TokenAmount::new(10_000), mint_b)?;
+        let invariant_after_swap = invariant(&pool)?;
+
+        // rounding is in favour of the pool, but only by a fraction of a
+        // token
+        assert!(invariant_after_swap >= invariant_before_swap);
+        assert!(
+            invariant_after_swap.try_sub(invariant_before_swap)?
+                < Decimal::from_scaled_val(10_000_000_000_000)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_redeems_tokens_into_reserve() -> Result<()> {
+        let mint_a = Pubkey::new_unique();
+        let mint_b = Pubkey::new_unique();
+
+        let mut pool = weighted_pool(Curve::ConstProd, mint_a, mint_b, 10_000);
+        let lp_supply = TokenAmount::new(1_000);
+
+        // redeems 1_000 of each reserve, then swaps 1_000 of A into B
+        //
+        // 9_000 - 9_000 * 9_000 / 10_000 = 900
+        let tokens = pool.redeem_tokens_into_reserve(
+            TokenAmount::new(100),
+            lp_supply,
+            mint_b,
+        )?;
+        assert_eq!(tokens, TokenAmount::new(1_900));
+        // the A tokens never left the pool
+        assert_eq!(pool.reserves[0].tokens, TokenAmount::new(10_000));
+        assert_eq!(pool.reserves[1].tokens, TokenAmount::new(8_100));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_pays_swap_fee_when_redeeming_tokens_into_reserve() -> Result<()> {
+        let mint_a = Pubkey::new_unique();
+        let mint_b = Pubkey::new_unique();
+
+        let mut pool = weighted_pool(Curve::ConstProd, mint_a, mint_b, 10_000);
+        pool.swap_fee = Permillion::from_percent(1);
+        let lp_supply = TokenAmount::new(1_000);
+
+        // 10 tokens of A are the fee, 990 are swapped
+        //
+        // 9_000 - 9_000 * 9_000 / 9_990 = 891.89
+        let tokens = pool.redeem_tokens_into_reserve(
+            TokenAmount::new(100),
+            lp_supply,
+            mint_b,
+        )?;
+        assert_eq!(tokens, TokenAmount::new(1_891));
+        assert_eq!(pool.reserves[0].tokens, TokenAmount::new(10_000));
+        assert_eq!(pool.reserves[1].tokens, TokenAmount::new(8_109));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_errs_redeem_tokens_into_reserve_of_unknown_mint() {
+        let mint_a = Pubkey::new_unique();
+        let mint_b = Pubkey::new_unique();
+
+        let mut pool = weighted_pool(Curve::ConstProd, mint_a, mint_b, 10_000);
+
+        assert!(pool
+            .redeem_tokens_into_reserve(
+                TokenAmount::new(100),
+                TokenAmount::new(1_000),
+                Pubkey::new_unique(),
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn it_finds_signer_address_with_bump() {
+        let pool = Pubkey::new_unique();
+
+        let PoolSigner { signer, bump } = Pool::find_signer_address(&pool);
+
+        // this is how the signer seeds of the endpoints are checked
+        assert_eq!(
+            Pubkey::create_program_address(
+                &[Pool::SIGNER_PDA_PREFIX, pool.as_ref(), &[bump]],
+                &crate::ID,
+            ),
+            Ok(signer)
+        );
+    }
+
+    #[test]
+    fn it_computes_curve_invariant() -> Result<()> {
+        let mint_a = Pubkey::new_unique();
+        let mint_b = Pubkey::new_unique();
+
+        let mut pool =
+            weighted_pool(Curve::ConstProd, mint_a, mint_b, 1_000_000);
+        pool.reserves[1].tokens = TokenAmount::new(4_000_000);
+        let invariant: Decimal = pool.curve_invariant()?.invariant.into();
+        assert_eq!(invariant, Decimal::from(1_000_000u64 * 4_000_000u64));
+
+        // x^0.5 y^0.5 = sqrt(1_000_000 * 4_000_000)
+        pool.curve = Curve::Weighted {
+            weight_a: 50,
+            weight_b: 50,
+        };
+        let invariant: Decimal = pool.curve_invariant()?.invariant.into();
+        assert!(invariant.almost_eq(&Decimal::from(2_000_000u64), 15));
+
+        // x^0.8 y^0.2 = 1_000_000 * 4^0.2
+        pool.curve = Curve::Weighted {
+            weight_a: 80,
+            weight_b: 20,
+        };
+        let invariant: Decimal = pool.curve_invariant()?.invariant.into();
+        assert!(invariant.almost_eq(
+            &Decimal::from_scaled_val(1_319_507_910_772_894_259_374_001),
+            15
+        ));
+
+        pool.curve = Curve::Stable {
+            amplifier: 10,
+            invariant: SDecimal::default(),
+        };
+        let result = pool.curve_invariant()?;
+        assert_eq!(result.curve, pool.curve);
+        assert_eq!(
+            Decimal::from(result.invariant),
+            math::stable_curve_invariant::compute(
+                10,
+                &[TokenAmount::new(1_000_000), TokenAmount::new(4_000_000)]
+            )?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_computes_zero_curve_invariant_if_reserve_empty() -> Result<()> {
+        let mint_a = Pubkey::new_unique();
+        let mint_b = Pubkey::new_unique();
+
+        let mut pool =
+            weighted_pool(Curve::ConstProd, mint_a, mint_b, 1_000_000);
+        pool.reserves[0].tokens = TokenAmount::new(0);
+
+        assert_eq!(
+            Decimal::from(pool.curve_invariant()?.invariant),
+            Decimal::zero()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_does_not_decrease_curve_invariant_on_swap() -> Result<()> {
+        let mint_a = Pubkey::new_unique();
+        let mint_b = Pubkey::new_unique();
+
+        for curve in [
+            Curve::ConstProd,
+            Curve::Weighted {
+                weight_a: 80,
+                weight_b: 20,
+            },
+        ] {
+            let mut pool = weighted_pool(curve, mint_a, mint_b, 1_000_000);
+
+            let before: Decimal = pool.curve_invariant()?.invariant.into();
+            pool.swap(mint_a, TokenAmount::new(10_000), mint_b)?;
+            let after: Decimal = pool.curve_invariant()?.invariant.into();
+
+            assert!(after >= before);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_validates_weighted_curve() {
+        let curve = |weight_a, weight_b| Curve::Weighted { weight_a, weight_b };
+
+        assert!(curve(80, 20).validate(2).is_ok());
+        assert!(curve(50, 50).validate(2).is_ok());
+        assert!(curve(1, 99).validate(2).is_ok());
+
+        assert!(curve(0, 100).validate(2).is_err());
+        assert!(curve(100, 0).validate(2).is_err());
+        assert!(curve(80, 30).validate(2).is_err());
+        assert!(curve(200, 100).validate(2).is_err());
+        assert!(curve(80, 20).validate(3).is_err());
+
+        assert!(Curve::ConstProd.validate(4).is_ok());
+    }
+
+    #[test]
+    fn works_if_stable_swap_curve() {
+        let deposit_mint = Pubkey::new_unique();
+        let withdraw_mint = Pubkey::new_unique();
+
+        let curve = Curve::Stable {
+            amplifier: 10,
+            invariant: 0_u64.into(),
+        };
+        let mut pool = Pool {
+            curve,
+            dimension: 2,
+            reserves: [
+                Reserve {
+                    tokens: TokenAmount { amount: 10 },
+                    mint: withdraw_mint,
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve {
+                    tokens: TokenAmount { amount: 100 },
+                    mint: deposit_mint,
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve::default(),
+                Reserve::default(),
+            ],
+            ..Default::default()
+        };
+        pool.update_curve_invariant().unwrap();
+        let tokens_to_swap = TokenAmount::new(50);
+
+        assert_eq!(
+            pool.calculate_swap(deposit_mint, tokens_to_swap, withdraw_mint)
+                .unwrap(),
+            // The result for the root polynomial is x = 1.007841346549156426
+            // Where x is the token balance of the token bought by the trader
+            // after the swap. This means that floor(10 - 1.007841346549156426)
+            // = floor(8.99) = 8
+            8_u64.into()
+        );
+    }
+
+    #[test]
+    fn stable_swap_curve_works_for_high_amounts() {
+        let sell_mint = Pubkey::new_unique();
+        let buy_mint = Pubkey::new_unique();
+
+        let tokens_to_swap = TokenAmount::new(50);
+
+        for tokens in [
+            TokenAmount::new(100),
+            TokenAmount::new(1000),
+            TokenAmount::new(100_000),
+            TokenAmount::new(1_000_000),
+            TokenAmount::new(100_000_000),
+            TokenAmount::new(1_000_000_000),
+            TokenAmount::new(10_000_000_000),
+            TokenAmount::new(100_000_000_000),
+        ] {
+            let curve: Curve = Curve::Stable {
+                amplifier: 10,
+                invariant: (2 * tokens.amount).into(),
+            };
+            let pool = Pool {
+                curve,
+                dimension: 2,
+                reserves: [
                     Reserve {
                         tokens,
                         mint: sell_mint,
@@ -2128,6 +3892,413 @@ mod tests {
         }
     }
 
+    #[test]
+    fn spot_swap_is_priced_at_reserve_ratio_if_constant_product_curve() {
+        let deposit_mint = Pubkey::new_unique();
+        let withdraw_mint = Pubkey::new_unique();
+
+        let pool = Pool {
+            dimension: 2,
+            reserves: [
+                Reserve {
+                    tokens: TokenAmount { amount: 10 },
+                    mint: withdraw_mint,
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve {
+                    tokens: TokenAmount { amount: 100 },
+                    mint: deposit_mint,
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve::default(),
+                Reserve::default(),
+            ],
+            ..Default::default()
+        };
+
+        let tokens_to_swap = TokenAmount::new(50);
+
+        // 50 * 10 / 100 = 5, whereas the actual swap gives only 3
+        assert_eq!(
+            pool.calculate_spot_swap(
+                deposit_mint,
+                tokens_to_swap,
+                withdraw_mint
+            )
+            .unwrap(),
+            5_u64.into()
+        );
+        assert_eq!(
+            pool.calculate_swap(deposit_mint, tokens_to_swap, withdraw_mint)
+                .unwrap(),
+            3_u64.into()
+        );
+    }
+
+    #[test]
+    fn spot_swap_is_at_parity_if_balanced_stable_swap_curve() {
+        let sell_mint = Pubkey::new_unique();
+        let buy_mint = Pubkey::new_unique();
+
+        let tokens_to_swap = TokenAmount::new(50);
+
+        for tokens in [
+            TokenAmount::new(100),
+            TokenAmount::new(1_000_000),
+            TokenAmount::new(100_000_000_000),
+        ] {
+            let curve: Curve = Curve::Stable {
+                amplifier: 10,
+                invariant: (2 * tokens.amount).into(),
+            };
+            let pool = Pool {
+                curve,
+                dimension: 2,
+                reserves: [
+                    Reserve {
+                        tokens,
+                        mint: sell_mint,
+                        vault: Pubkey::new_unique(),
+                    },
+                    Reserve {
+                        tokens,
+                        mint: buy_mint,
+                        vault: Pubkey::new_unique(),
+                    },
+                    Reserve::default(),
+                    Reserve::default(),
+                ],
+                ..Default::default()
+            };
+
+            assert_eq!(
+                pool.calculate_spot_swap(sell_mint, tokens_to_swap, buy_mint)
+                    .unwrap(),
+                tokens_to_swap
+            );
+        }
+    }
+
+    #[test]
+    fn spot_swap_is_upper_bound_of_swap_if_stable_swap_curve() {
+        let deposit_mint = Pubkey::new_unique();
+        let withdraw_mint = Pubkey::new_unique();
+
+        let curve = Curve::Stable {
+            amplifier: 10,
+            invariant: 0_u64.into(),
+        };
+        let mut pool = Pool {
+            curve,
+            dimension: 2,
+            reserves: [
+                Reserve {
+                    tokens: TokenAmount { amount: 10 },
+                    mint: withdraw_mint,
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve {
+                    tokens: TokenAmount { amount: 100 },
+                    mint: deposit_mint,
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve::default(),
+                Reserve::default(),
+            ],
+            ..Default::default()
+        };
+        pool.update_curve_invariant().unwrap();
+        let tokens_to_swap = TokenAmount::new(50);
+
+        let spot = pool
+            .calculate_spot_swap(deposit_mint, tokens_to_swap, withdraw_mint)
+            .unwrap();
+        let actual = pool
+            .calculate_swap(deposit_mint, tokens_to_swap, withdraw_mint)
+            .unwrap();
+        assert!(spot >= actual);
+    }
+
+    fn two_reserve_pool(curve: Curve, tokens_a: u64, tokens_b: u64) -> Pool {
+        let mut pool = Pool {
+            curve,
+            dimension: 2,
+            reserves: [
+                Reserve {
+                    tokens: TokenAmount::new(tokens_a),
+                    mint: Pubkey::new_unique(),
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve {
+                    tokens: TokenAmount::new(tokens_b),
+                    mint: Pubkey::new_unique(),
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve::default(),
+                Reserve::default(),
+            ],
+            ..Default::default()
+        };
+        pool.update_curve_invariant().unwrap();
+        pool
+    }
+
+    /// Applies the swap and returns the reserves of `a` and `b` afterwards.
+    fn apply_ratio_swap(pool: &mut Pool, swap: RatioSwap) -> (u64, u64) {
+        pool.swap(swap.sell_mint, swap.sell, swap.buy_mint).unwrap();
+        (
+            pool.reserves[0].tokens.amount,
+            pool.reserves[1].tokens.amount,
+        )
+    }
+
+    #[test]
+    fn it_calculates_swap_to_reach_ratio_on_constant_product_curve(
+    ) -> Result<()> {
+        let mut pool = two_reserve_pool(Curve::ConstProd, 1_000, 4_000);
+        let (mint_a, mint_b) = (pool.reserves[0].mint, pool.reserves[1].mint);
+
+        // a / b = 1 => a' = b' = sqrt(1_000 * 4_000) = 2_000
+        let swap =
+            pool.calculate_swap_to_reach_ratio(mint_a, mint_b, Decimal::one())?;
+        assert_eq!(swap.sell_mint, mint_a);
+        assert_eq!(swap.buy_mint, mint_b);
+        assert_eq!(swap.sell, TokenAmount::new(1_000));
+
+        let (a, b) = apply_ratio_swap(&mut pool, swap);
+        assert_eq!((a, b), (2_000, 2_000));
+
+        // a / b = 1 / 4 => b' = sqrt(2_000 * 2_000 * 4) = 4_000
+        let target = Decimal::one().try_div(Decimal::from(4_u64))?;
+        let swap =
+            pool.calculate_swap_to_reach_ratio(mint_a, mint_b, target)?;
+        assert_eq!(swap.sell_mint, mint_b);
+        assert_eq!(swap.sell, TokenAmount::new(2_000));
+
+        let (a, b) = apply_ratio_swap(&mut pool, swap);
+        assert_eq!((a, b), (1_000, 4_000));
+
+        // already at the target ratio
+        let swap =
+            pool.calculate_swap_to_reach_ratio(mint_a, mint_b, target)?;
+        assert_eq!(swap.sell, TokenAmount::new(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_calculates_swap_to_reach_ratio_on_stable_curve() -> Result<()> {
+        let curve = Curve::Stable {
+            amplifier: 10,
+            invariant: 0_u64.into(),
+        };
+
+        let mut pool = two_reserve_pool(curve, 10_000, 30_000);
+        let (mint_a, mint_b) = (pool.reserves[0].mint, pool.reserves[1].mint);
+        let swap =
+            pool.calculate_swap_to_reach_ratio(mint_a, mint_b, Decimal::one())?;
+        assert_eq!(swap.sell_mint, mint_a);
+        let (a, b) = apply_ratio_swap(&mut pool, swap);
+        // the price is close to parity, ie. one token sold moves the
+        // difference by about two tokens
+        assert!(a >= b && a - b <= 2, "{} vs {}", a, b);
+
+        let mut pool = two_reserve_pool(curve, 30_000, 10_000);
+        let target = Decimal::from(2_u64);
+        let swap = pool.calculate_swap_to_reach_ratio(mint_a, mint_b, target);
+        // mints of the previous pool are not reserves of this one
+        assert!(swap.is_err());
+        let (mint_a, mint_b) = (pool.reserves[0].mint, pool.reserves[1].mint);
+        let swap =
+            pool.calculate_swap_to_reach_ratio(mint_a, mint_b, target)?;
+        assert_eq!(swap.sell_mint, mint_b);
+        let (a, b) = apply_ratio_swap(&mut pool, swap);
+        assert!(a <= 2 * b && 2 * b - a <= 3, "{} vs {}", a, b);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_prices_stable_pool_with_mismatched_decimals() -> Result<()> {
+        let curve = Curve::Stable {
+            amplifier: 10,
+            invariant: 0_u64.into(),
+        };
+
+        // a million tokens of a mint with 6 decimals and of a mint with 9
+        let mut pool =
+            two_reserve_pool(curve, 1_000_000_000_000, 1_000_000_000_000_000);
+        pool.decimal_scales = [3, 0, 0, 0];
+        pool.update_curve_invariant()?;
+        let (mint_a, mint_b) = (pool.reserves[0].mint, pool.reserves[1].mint);
+
+        // a thousand tokens either way
+        let bought = pool.calculate_swap(
+            mint_a,
+            TokenAmount::new(1_000_000_000),
+            mint_b,
+        )?;
+        assert!(
+            bought.amount <= 1_000_000_000_000
+                && bought.amount >= 999_000_000_000,
+            "{}",
+            bought.amount
+        );
+        let spot = pool.calculate_spot_swap(
+            mint_a,
+            TokenAmount::new(1_000_000_000),
+            mint_b,
+        )?;
+        // reserves are balanced after normalization, hence the spot price is
+        // parity
+        assert!(
+            spot.amount <= 1_000_000_000_000 && spot.amount >= 999_999_999_999,
+            "{}",
+            spot.amount
+        );
+
+        let bought = pool.calculate_swap(
+            mint_b,
+            TokenAmount::new(1_000_000_000_000),
+            mint_a,
+        )?;
+        assert!(
+            bought.amount <= 1_000_000_000 && bought.amount >= 999_000_000,
+            "{}",
+            bought.amount
+        );
+
+        // without normalization the pool values the 6 decimal mint a thousand
+        // times less
+        pool.decimal_scales = [0; 4];
+        pool.update_curve_invariant()?;
+        let bought = pool.calculate_swap(
+            mint_a,
+            TokenAmount::new(1_000_000_000),
+            mint_b,
+        )?;
+        assert!(bought.amount < 100_000_000_000, "{}", bought.amount);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_has_stable_legacy_size() {
+        assert_eq!(LegacyPool::space(), 480);
+    }
+
+    #[test]
+    fn it_migrates_legacy_pool() -> Result<()> {
+        let legacy = LegacyPool {
+            admin: Pubkey::new_unique(),
+            signer: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            program_toll_wallet: Pubkey::new_unique(),
+            dimension: 2,
+            reserves: [
+                Reserve {
+                    tokens: TokenAmount::new(10),
+                    mint: Pubkey::new_unique(),
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve {
+                    tokens: TokenAmount::new(20),
+                    mint: Pubkey::new_unique(),
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve::default(),
+                Reserve::default(),
+            ],
+            curve: Curve::Stable {
+                amplifier: 10,
+                invariant: Decimal::from(30u64).into(),
+            },
+            swap_fee: Permillion::from_percent(1),
+        };
+
+        let mut data = Pool::discriminator().to_vec();
+        data.extend(legacy.try_to_vec()?);
+        data.resize(LegacyPool::space(), 0);
+        // the legacy account cannot be read as the current layout
+        assert!(Pool::try_deserialize(&mut data.as_slice()).is_err());
+
+        let pool = LegacyPool::deserialize(&mut &data[8..])?.migrate();
+        assert_eq!(pool.admin, legacy.admin);
+        assert_eq!(pool.signer, legacy.signer);
+        assert_eq!(pool.mint, legacy.mint);
+        assert_eq!(pool.program_toll_wallet, legacy.program_toll_wallet);
+        assert_eq!(pool.reserves(), &legacy.reserves[..2]);
+        assert_eq!(pool.curve, legacy.curve);
+        assert_eq!(pool.swap_fee, legacy.swap_fee);
+        assert_eq!(
+            pool.swap_fee_timelock_slots,
+            consts::DEFAULT_SWAP_FEE_TIMELOCK_SLOTS
+        );
+        assert!(!pool.has_pending_swap_fee());
+        assert_eq!(pool.max_lp_supply, TokenAmount::new(0));
+        assert_eq!(pool.lifetime_owner_fees, TokenAmount::new(0));
+
+        let mut migrated = Vec::new();
+        pool.try_serialize(&mut migrated)?;
+        assert!(migrated.len() <= Pool::space());
+        assert_eq!(Pool::try_deserialize(&mut migrated.as_slice())?, pool);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_reports_layout_of_current_and_older_pools() -> Result<()> {
+        let mut data = Vec::new();
+        Pool::default().try_serialize(&mut data)?;
+        data.resize(Pool::space(), 0);
+
+        let current = Pool::layout(&data)?;
+        assert_eq!(current.size, current.current_size);
+
+        // pools created before this release must be migrated
+        data.truncate(LegacyPool::space());
+        let older = Pool::layout(&data)?;
+        assert_eq!(older.size, LegacyPool::space() as u64);
+        assert!(older.size < older.current_size);
+
+        assert!(Pool::layout(&[0; 8]).is_err());
+        assert!(Pool::layout(&data[..4]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn spot_swap_fails_if_mint_is_invalid() {
+        let pool = Pool {
+            dimension: 2,
+            reserves: [
+                Reserve {
+                    tokens: TokenAmount { amount: 10 },
+                    mint: Pubkey::new_unique(),
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve {
+                    tokens: TokenAmount { amount: 100 },
+                    mint: Pubkey::new_unique(),
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve::default(),
+                Reserve::default(),
+            ],
+            ..Default::default()
+        };
+
+        assert!(pool
+            .calculate_spot_swap(
+                Pubkey::new_unique(),
+                TokenAmount::new(50),
+                pool.reserves[0].mint
+            )
+            .unwrap_err()
+            .to_string()
+            .contains("InvalidArg"));
+    }
+
     #[test]
     fn returns_zero_lp_tokens_to_mint_if_the_deposit_extremely_small(
     ) -> Result<()> {
@@ -0,0 +1,69 @@
+//! Lending protocols and other price consumers want to read a pool's reserves
+//! every block without deserializing the whole [`Pool`] account. The
+//! [`PoolMirror`] is a small read-only copy of the pool's reserves and LP
+//! supply. It's created with [`crate::endpoints::create_pool_mirror`] and
+//! anyone can crank it with [`crate::endpoints::refresh_pool_mirror`].
+//!
+//! The [`PoolMirror`] is always stored in an account with a PDA address for
+//! which the pool's pubkey is a seed.
+
+use crate::prelude::*;
+
+/// A one-to-one account with relationship to a pool. Pools don't have to have
+/// any mirror.
+#[account]
+#[derive(Default, PartialEq, Eq, Debug)]
+pub struct PoolMirror {
+    pub pool: Pubkey,
+    /// Same order as [`Pool::reserves`]. Only the first [`Pool::dimension`]
+    /// reserves are initialized.
+    ///
+    /// # Note
+    /// This len must match [`consts::MAX_RESERVES`].
+    pub reserves: [MirroredReserve; 4],
+    /// Supply of the pool's LP mint.
+    pub lp_supply: TokenAmount,
+    /// The mirror reflects the pool as it was at this slot. Consumers should
+    /// check this to reject a stale mirror.
+    pub refreshed_at: Slot,
+}
+
+#[derive(
+    AnchorDeserialize,
+    AnchorSerialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+)]
+pub struct MirroredReserve {
+    pub mint: Pubkey,
+    pub tokens: TokenAmount,
+}
+
+impl PoolMirror {
+    pub const PDA_PREFIX: &'static [u8; 11] = b"pool_mirror";
+
+    pub fn space() -> usize {
+        let discriminant = 8;
+        let pool = 32;
+        let reserves = consts::MAX_RESERVES * (32 + 8);
+        let lp_supply = 8;
+        let refreshed_at = 8;
+
+        discriminant + pool + reserves + lp_supply + refreshed_at
+    }
+
+    pub fn refresh(&mut self, pool: &Pool, lp_supply: TokenAmount, now: Slot) {
+        for (mirrored, reserve) in self.reserves.iter_mut().zip(pool.reserves) {
+            *mirrored = MirroredReserve {
+                mint: reserve.mint,
+                tokens: reserve.tokens,
+            };
+        }
+        self.lp_supply = lp_supply;
+        self.refreshed_at = now;
+    }
+}
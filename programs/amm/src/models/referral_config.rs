@@ -0,0 +1,42 @@
+//! Protocols which launch a pool with a referral program can route a share of
+//! the program toll to referrers. The [`ReferralConfig`] is created together
+//! with the pool in [`crate::endpoints::create_pool_referral_config`] and is
+//! consumed by [`crate::endpoints::swap::handle_with_referral`] and
+//! [`crate::endpoints::swap::handle_with_default_referral`].
+//!
+//! The [`ReferralConfig`] is always stored in an account with a PDA address
+//! for which the pool's pubkey is a seed.
+
+use crate::prelude::*;
+
+/// A one-to-one account with relationship to a pool. Pools don't have to have
+/// any referral config.
+#[account]
+#[derive(Default, PartialEq, Eq, Debug)]
+pub struct ReferralConfig {
+    /// What fraction of the program toll goes to the referrer. The rest is
+    /// minted into the pool's program toll wallet as usual.
+    ///
+    /// The maximum share is [`consts::MAX_REFERRAL_SHARE`].
+    pub share: Permillion,
+    /// An LP token wallet which gets the referral share of swaps which weren't
+    /// referred by anyone in particular, see
+    /// [`crate::endpoints::swap::handle_with_default_referral`].
+    ///
+    /// Initially it's a wallet of the program toll authority, which is the
+    /// only one who can change it with
+    /// [`crate::endpoints::set_pool_referral_default_recipient`].
+    pub default_recipient: Pubkey,
+}
+
+impl ReferralConfig {
+    pub const PDA_PREFIX: &'static [u8; 15] = b"referral_config";
+
+    pub fn space() -> usize {
+        let discriminant = 8;
+        let share = 8;
+        let default_recipient = 32;
+
+        discriminant + share + default_recipient
+    }
+}
@@ -0,0 +1,383 @@
+use ::amm::amm::batch_swap;
+use ::amm::endpoints::{simulate_swap, BatchSwapLeg, SwapFeeShares};
+use ::amm::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token;
+use anchortest::{
+    builder::*,
+    spl::{self, *},
+    stub,
+};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use solana_sdk::instruction::Instruction;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// How many remaining accounts form a single swap.
+const GROUP_LEN: usize = 12;
+
+#[test]
+#[serial]
+fn swaps_on_two_pools_in_one_instruction() -> Result<()> {
+    let mut test = Tester::new(2);
+    let pools_before = [test.pool_copy(0), test.pool_copy(1)];
+
+    test.batch_swap(&[
+        (TokenAmount::new(10_000), TokenAmount::new(6_254)),
+        (TokenAmount::new(1_000), TokenAmount::new(850)),
+    ])?;
+
+    // outputs are not chained, each swap sells from its own wallet
+    let pool = test.pool_copy(0);
+    assert_eq!(pool.reserves[0].tokens.amount, 30_000);
+    assert_eq!(pool.reserves[1].tokens.amount, 13_746);
+    assert_eq!(
+        test.buy_wallet_amount(0),
+        u64::MAX / 2 + pools_before[0].reserves[1].tokens.amount - 13_746
+    );
+
+    let pool = test.pool_copy(1);
+    assert_eq!(pool.reserves[0].tokens.amount, 21_000);
+    assert!(pool.reserves[1].tokens < pools_before[1].reserves[1].tokens);
+    assert!(test.buy_wallet_amount(1) >= u64::MAX / 2 + 850);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_any_swap_exceeds_slippage_and_reverts_all() -> Result<()> {
+    let mut test = Tester::new(2);
+    let pools_before = [test.pool_copy(0), test.pool_copy(1)];
+
+    let err = test
+        .batch_swap(&[
+            (TokenAmount::new(10_000), TokenAmount::new(6_254)),
+            // can receive at most 20_000 tokens
+            (TokenAmount::new(1_000), TokenAmount::new(20_000)),
+        ])
+        .unwrap_err();
+    assert!(err.to_string().contains("SlippageExceeded"));
+
+    // no pool has been written
+    assert_eq!(test.pool_copy(0), pools_before[0]);
+    assert_eq!(test.pool_copy(1), pools_before[1]);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_two_swaps_share_a_pool() -> Result<()> {
+    let mut test = Tester::new(1);
+    let group = test.remaining[..GROUP_LEN].to_vec();
+    test.remaining.extend(group);
+
+    let err = test
+        .batch_swap(&[
+            (TokenAmount::new(10), TokenAmount::new(0)),
+            (TokenAmount::new(10), TokenAmount::new(0)),
+        ])
+        .unwrap_err();
+    assert!(err.to_string().contains("is used by another swap"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_accounts_dont_match_swaps() -> Result<()> {
+    let mut test = Tester::new(2);
+
+    let err = test
+        .batch_swap(&[(TokenAmount::new(10), TokenAmount::new(0))])
+        .unwrap_err();
+    assert!(err.to_string().contains("remaining accounts for 1 swaps"));
+
+    Ok(())
+}
+
+struct Tester {
+    user: AccountInfoWrapper,
+    /// Groups of [`GROUP_LEN`] accounts, one per pool.
+    remaining: Vec<AccountInfoWrapper>,
+}
+
+impl Tester {
+    /// Each pool has two reserves. The first reserve is always base (sell),
+    /// the second is always quote (buy).
+    fn new(pools: usize) -> Self {
+        let user = AccountInfoWrapper::new().signer();
+        let token_program =
+            AccountInfoWrapper::with_key(anchor_spl::token::ID).program();
+
+        let remaining = (0..pools)
+            .flat_map(|_| Self::swap_group(user.clone(), token_program.clone()))
+            .collect();
+
+        Self { user, remaining }
+    }
+
+    fn swap_group(
+        user: AccountInfoWrapper,
+        token_program: AccountInfoWrapper,
+    ) -> Vec<AccountInfoWrapper> {
+        let mut pool_data = Pool {
+            dimension: 2,
+            program_toll_wallet: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            swap_fee: Permillion::from_percent(9),
+            reserves: [
+                Reserve {
+                    mint: Pubkey::new_unique(),
+                    vault: Pubkey::new_unique(),
+                    tokens: TokenAmount::new(20_000),
+                },
+                Reserve {
+                    mint: Pubkey::new_unique(),
+                    vault: Pubkey::new_unique(),
+                    tokens: TokenAmount::new(20_000),
+                },
+                Reserve::default(),
+                Reserve::default(),
+            ],
+            ..Default::default()
+        };
+        pool_data.update_curve_invariant().ok();
+
+        let discount = AccountInfoWrapper::pda(
+            amm::ID,
+            "discount",
+            &[Discount::PDA_PREFIX, user.key.as_ref()],
+        )
+        .owner(system_program::ID);
+        let pool = AccountInfoWrapper::new()
+            .owner(amm::ID)
+            .mutable()
+            .data(pool_data.clone());
+        let pool_signer = AccountInfoWrapper::pda(
+            amm::ID,
+            "pool_signer",
+            &[Pool::SIGNER_PDA_PREFIX, pool.key.as_ref()],
+        );
+        let fee_rebate = AccountInfoWrapper::pda(
+            amm::ID,
+            "fee_rebate",
+            &[FeeRebate::PDA_PREFIX, pool.key.as_ref()],
+        )
+        .owner(system_program::ID);
+        let lp_mint = AccountInfoWrapper::with_key(pool_data.mint)
+            .mutable()
+            .pack(spl::mint::new(pool_signer.key).supply(10_000))
+            .owner(token::ID);
+        let program_toll_wallet =
+            AccountInfoWrapper::with_key(pool_data.program_toll_wallet)
+                .mutable()
+                .pack(
+                    spl::token_account::new(Pubkey::new_unique())
+                        .mint(lp_mint.key),
+                )
+                .owner(token::ID);
+        let vault = |reserve: &Reserve| {
+            AccountInfoWrapper::with_key(reserve.vault)
+                .mutable()
+                .pack(
+                    spl::token_account::new(pool_signer.key)
+                        .mint(reserve.mint)
+                        .amount(reserve.tokens.amount),
+                )
+                .owner(token::ID)
+        };
+        let wallet = |reserve: &Reserve| {
+            AccountInfoWrapper::new()
+                .mutable()
+                .pack(
+                    spl::token_account::new(user.key)
+                        .mint(reserve.mint)
+                        .amount(u64::MAX / 2),
+                )
+                .owner(token::ID)
+        };
+        let sell_vault = vault(&pool_data.reserves[0]);
+        let buy_vault = vault(&pool_data.reserves[1]);
+        let sell_wallet = wallet(&pool_data.reserves[0]);
+        let buy_wallet = wallet(&pool_data.reserves[1]);
+
+        vec![
+            user,
+            discount,
+            pool,
+            pool_signer,
+            sell_wallet,
+            buy_wallet,
+            sell_vault,
+            buy_vault,
+            lp_mint,
+            program_toll_wallet,
+            token_program,
+            fee_rebate,
+        ]
+    }
+
+    fn group_acc(&self, group: usize, index: usize) -> &AccountInfoWrapper {
+        &self.remaining[group * GROUP_LEN + index]
+    }
+
+    fn pool_copy(&self, group: usize) -> Pool {
+        Pool::try_deserialize(&mut self.group_acc(group, 2).data.as_slice())
+            .unwrap()
+    }
+
+    fn buy_wallet_amount(&mut self, group: usize) -> u64 {
+        spl::token_account::from_acc_info(
+            &self.remaining[group * GROUP_LEN + 5].to_account_info(),
+        )
+        .amount
+    }
+
+    /// Expects the CPIs of each swap which doesn't fail, in order.
+    fn expected_cpis(
+        &self,
+        legs: &[(TokenAmount, TokenAmount)],
+    ) -> VecDeque<ExpectedCpi> {
+        let mut cpis = VecDeque::new();
+        for (group, (sell, min_buy)) in legs.iter().enumerate() {
+            if group * GROUP_LEN >= self.remaining.len() {
+                break;
+            }
+
+            let mut pool = self.pool_copy(group);
+            let sell_mint = pool.reserves[0].mint;
+            let buy_mint = pool.reserves[1].mint;
+            let lp_supply = spl::mint::from_acc_info(
+                &self.remaining[group * GROUP_LEN + 8].to_account_info(),
+            )
+            .supply;
+            let quote = match simulate_swap(
+                &mut pool,
+                Slot::new(0),
+                sell_mint,
+                *sell,
+                buy_mint,
+                SwapFeeShares::default(),
+                lp_supply.into(),
+            ) {
+                Ok(quote) if quote.bought >= *min_buy => quote,
+                // no more CPIs after a failed swap
+                _ => break,
+            };
+
+            let acc = |index| self.group_acc(group, index).key;
+            cpis.push_back(ExpectedCpi::Transfer(
+                token::spl_token::instruction::transfer(
+                    &token::ID,
+                    &acc(4),
+                    &acc(6),
+                    &acc(0),
+                    &[],
+                    sell.amount,
+                )
+                .unwrap(),
+                *sell,
+            ));
+            cpis.push_back(ExpectedCpi::Transfer(
+                token::spl_token::instruction::transfer(
+                    &token::ID,
+                    &acc(7),
+                    &acc(5),
+                    &acc(3),
+                    &[],
+                    quote.bought.amount,
+                )
+                .unwrap(),
+                quote.bought,
+            ));
+            if quote.program_toll.amount > 0 {
+                cpis.push_back(ExpectedCpi::MintTo(
+                    token::spl_token::instruction::mint_to(
+                        &token::ID,
+                        &acc(8),
+                        &acc(9),
+                        &acc(3),
+                        &[],
+                        quote.program_toll.amount,
+                    )
+                    .unwrap(),
+                    quote.program_toll,
+                ));
+            }
+        }
+
+        cpis
+    }
+
+    fn batch_swap(
+        &mut self,
+        legs: &[(TokenAmount, TokenAmount)],
+    ) -> Result<()> {
+        let state = Arc::new(Mutex::new(self.expected_cpis(legs)));
+        let syscalls = stub::Syscalls::new(CpiValidator(Arc::clone(&state)));
+        syscalls.slot(0);
+        syscalls.set();
+
+        let legs = legs
+            .iter()
+            .map(|(sell, min_buy)| BatchSwapLeg {
+                sell: *sell,
+                min_buy: *min_buy,
+            })
+            .collect();
+
+        let mut ctx = ContextWrapper::new(amm::ID)
+            .acc(&mut self.user)
+            .remaining_accounts(self.remaining.iter_mut());
+        let mut accounts = ctx.accounts()?;
+
+        batch_swap(ctx.build(&mut accounts), legs)?;
+        accounts.exit(&amm::ID)?;
+
+        assert!(state.lock().unwrap().is_empty());
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+enum ExpectedCpi {
+    Transfer(Instruction, TokenAmount),
+    MintTo(Instruction, TokenAmount),
+}
+
+#[derive(Debug, Clone)]
+struct CpiValidator(Arc<Mutex<VecDeque<ExpectedCpi>>>);
+
+impl stub::ValidateCpis for CpiValidator {
+    fn validate_next_instruction(
+        &mut self,
+        ix: &Instruction,
+        accounts: &[AccountInfo],
+    ) {
+        let expected =
+            self.0.lock().unwrap().pop_front().unwrap_or_else(|| {
+                panic!("No more instructions expected, got {:#?}", ix)
+            });
+
+        match expected {
+            ExpectedCpi::Transfer(expected_ix, tokens) => {
+                assert_eq!(&expected_ix, ix);
+                spl::token_account::transfer(
+                    &accounts[0],
+                    &accounts[1],
+                    tokens.amount,
+                )
+                .expect("Source account does not have enough tokens");
+            }
+            ExpectedCpi::MintTo(expected_ix, tokens) => {
+                assert_eq!(&expected_ix, ix);
+                spl::mint::mint_to(&accounts[0], &accounts[1], tokens.amount)
+                    .expect("Cannot mint LP tokens");
+            }
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use ::amm::amm::burn_lp_to_reserves;
+use ::amm::prelude::*;
+use anchor_spl::token;
+use anchortest::{
+    builder::*,
+    spl::{self, MintExt, TokenAccountExt},
+    stub,
+};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use solana_sdk::instruction::Instruction;
+
+#[test]
+#[serial]
+fn burns_lp_tokens_without_touching_reserves() -> Result<()> {
+    let mut test = Tester::default();
+    let pool_before = test.pool_copy();
+
+    // 10 = 10_000 / 1_000
+    for TokenLimit { tokens, .. } in test.tokens_per_lp_token() {
+        assert_eq!(tokens, TokenAmount::new(10));
+    }
+
+    test.burn_lp_to_reserves(TokenAmount::new(200))?;
+
+    // no reserve tokens were returned, only the LP tokens were burned
+    assert_eq!(test.lp_supply(), 800);
+    assert_eq!(test.lp_token_wallet_amount(), 0);
+    assert_eq!(test.pool_copy(), pool_before);
+
+    // each remaining LP token is now worth more of each reserve
+    for TokenLimit { tokens, .. } in test.tokens_per_lp_token() {
+        // 12 = 10_000 / 800
+        assert_eq!(tokens, TokenAmount::new(12));
+    }
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_lp_mint_does_not_match_pool() -> Result<()> {
+    let mut test = Tester::default();
+    test.lp_mint = AccountInfoWrapper::new()
+        .mutable()
+        .pack(spl::mint::new(Pubkey::new_unique()).supply(1_000))
+        .owner(token::ID);
+
+    assert!(test
+        .burn_lp_to_reserves(TokenAmount::new(100))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_lp_token_wallet_is_not_of_pool_mint() -> Result<()> {
+    let mut test = Tester::default();
+    test.lp_token_wallet = AccountInfoWrapper::new()
+        .mutable()
+        .pack(
+            spl::token_account::new(test.user.key)
+                .mint(Pubkey::new_unique())
+                .amount(200),
+        )
+        .owner(token::ID);
+
+    assert!(test
+        .burn_lp_to_reserves(TokenAmount::new(100))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_burning_zero() -> Result<()> {
+    let mut test = Tester::default();
+
+    assert!(test
+        .burn_lp_to_reserves(TokenAmount::new(0))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_burning_more_than_wallet_holds() -> Result<()> {
+    let mut test = Tester::default();
+
+    assert!(test
+        .burn_lp_to_reserves(TokenAmount::new(201))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidLpTokenAmount"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_burning_whole_lp_supply() -> Result<()> {
+    let mut test = Tester::default();
+    test.lp_mint = test
+        .lp_mint
+        .clone()
+        .pack(spl::mint::new(test.pool_signer_key).supply(200));
+
+    assert!(test
+        .burn_lp_to_reserves(TokenAmount::new(200))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    pool_signer_key: Pubkey,
+    user: AccountInfoWrapper,
+    pool: AccountInfoWrapper,
+    lp_mint: AccountInfoWrapper,
+    lp_token_wallet: AccountInfoWrapper,
+    token_program: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let user = AccountInfoWrapper::new().signer();
+        let pool_key = Pubkey::new_unique();
+        let (pool_signer_key, _) = Pubkey::find_program_address(
+            &[Pool::SIGNER_PDA_PREFIX, pool_key.as_ref()],
+            &amm::ID,
+        );
+        let lp_mint = AccountInfoWrapper::new()
+            .mutable()
+            .pack(spl::mint::new(pool_signer_key).supply(1_000))
+            .owner(token::ID);
+        let lp_token_wallet = AccountInfoWrapper::new()
+            .mutable()
+            .pack(
+                spl::token_account::new(user.key)
+                    .mint(lp_mint.key)
+                    .amount(200),
+            )
+            .owner(token::ID);
+        let pool =
+            AccountInfoWrapper::with_key(pool_key)
+                .owner(amm::ID)
+                .data(Pool {
+                    mint: lp_mint.key,
+                    signer: pool_signer_key,
+                    dimension: 2,
+                    reserves: [
+                        Reserve {
+                            tokens: TokenAmount::new(10_000),
+                            mint: Pubkey::new_unique(),
+                            vault: Pubkey::new_unique(),
+                        },
+                        Reserve {
+                            tokens: TokenAmount::new(10_000),
+                            mint: Pubkey::new_unique(),
+                            vault: Pubkey::new_unique(),
+                        },
+                        Reserve::default(),
+                        Reserve::default(),
+                    ],
+                    ..Default::default()
+                });
+        let token_program =
+            AccountInfoWrapper::with_key(anchor_spl::token::ID).program();
+
+        Self {
+            pool_signer_key,
+            user,
+            pool,
+            lp_mint,
+            lp_token_wallet,
+            token_program,
+        }
+    }
+}
+
+impl Tester {
+    fn burn_lp_to_reserves(
+        &mut self,
+        lp_tokens_to_burn: TokenAmount,
+    ) -> Result<()> {
+        stub::Syscalls::new(CpiValidator(CpiValidatorState::BurnLp {
+            mint: self.lp_mint.key,
+            source: self.lp_token_wallet.key,
+            authority: self.user.key,
+            tokens: lp_tokens_to_burn,
+        }))
+        .set();
+
+        let mut ctx = self.context_wrapper();
+        let mut accounts = ctx.accounts()?;
+
+        burn_lp_to_reserves(ctx.build(&mut accounts), lp_tokens_to_burn)?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(())
+    }
+
+    fn pool_copy(&self) -> Pool {
+        Pool::try_deserialize(&mut self.pool.data.as_slice()).unwrap()
+    }
+
+    fn lp_supply(&mut self) -> u64 {
+        spl::mint::from_acc_info(&self.lp_mint.to_account_info()).supply
+    }
+
+    fn lp_token_wallet_amount(&mut self) -> u64 {
+        spl::token_account::from_acc_info(
+            &self.lp_token_wallet.to_account_info(),
+        )
+        .amount
+    }
+
+    fn tokens_per_lp_token(&mut self) -> Vec<TokenLimit> {
+        let lp_supply = TokenAmount::new(self.lp_supply());
+        self.pool_copy()
+            .lp_value(lp_supply)
+            .unwrap()
+            .tokens_per_lp_token
+    }
+
+    fn context_wrapper(&mut self) -> ContextWrapper {
+        ContextWrapper::new(amm::ID)
+            .acc(&mut self.user)
+            .acc(&mut self.pool)
+            .acc(&mut self.lp_mint)
+            .acc(&mut self.lp_token_wallet)
+            .acc(&mut self.token_program)
+    }
+}
+
+struct CpiValidator(CpiValidatorState);
+enum CpiValidatorState {
+    BurnLp {
+        mint: Pubkey,
+        source: Pubkey,
+        authority: Pubkey,
+        tokens: TokenAmount,
+    },
+    Done,
+}
+
+impl stub::ValidateCpis for CpiValidator {
+    fn validate_next_instruction(
+        &mut self,
+        ix: &Instruction,
+        accounts: &[AccountInfo],
+    ) {
+        match self.0 {
+            CpiValidatorState::BurnLp {
+                mint,
+                source,
+                authority,
+                tokens,
+            } => {
+                let expected_ix = token::spl_token::instruction::burn(
+                    &token::ID,
+                    &source,
+                    &mint,
+                    &authority,
+                    &[],
+                    tokens.amount,
+                )
+                .unwrap();
+                assert_eq!(&expected_ix, ix);
+
+                let wallet = &accounts[0];
+                let lp_mint = &accounts[1];
+                assert_eq!(wallet.key(), source);
+                assert_eq!(lp_mint.key(), mint);
+
+                spl::mint::burn_from(wallet, lp_mint, tokens.amount)
+                    .expect("Cannot burn LP tokens");
+
+                self.0 = CpiValidatorState::Done;
+            }
+            CpiValidatorState::Done => {
+                panic!("No more instructions expected, got {:#?}", ix);
+            }
+        }
+    }
+}
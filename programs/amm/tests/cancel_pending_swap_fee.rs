@@ -0,0 +1,184 @@
+use ::amm::amm::{cancel_pending_swap_fee, set_pool_swap_fee};
+use ::amm::prelude::*;
+use anchortest::{builder::*, stub};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use solana_sdk::instruction::Instruction;
+
+const TIMELOCK_SLOTS: u64 = consts::DEFAULT_SWAP_FEE_TIMELOCK_SLOTS;
+
+#[test]
+#[serial]
+fn keeps_current_fee_past_would_be_effective_slot() -> Result<()> {
+    let mut test = Tester::default().slot(100);
+    let fee_before = test.pool_copy().swap_fee;
+
+    test.set_pool_swap_fee(Permillion { permillion: 5_000 })?;
+    let effective_at = Slot::new(100 + TIMELOCK_SLOTS);
+    assert_eq!(test.pool_copy().pending_swap_fee_effective_at, effective_at);
+
+    test.cancel_pending_swap_fee()?;
+
+    let pool = test.pool_copy();
+    assert_eq!(pool.swap_fee, fee_before);
+    assert!(!pool.has_pending_swap_fee());
+    assert_eq!(
+        pool.fee_schedule(Slot::new(effective_at.slot + 1)),
+        FeeSchedule {
+            swap_fee: fee_before,
+            pending_swap_fee: Permillion::default(),
+            pending_swap_fee_effective_at: Slot::default(),
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_there_is_no_pending_fee() -> Result<()> {
+    let mut test = Tester::default();
+
+    assert!(test
+        .cancel_pending_swap_fee()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_pending_fee_is_already_effective() -> Result<()> {
+    let mut test = Tester::default().slot(100);
+    test.set_pool_swap_fee(Permillion { permillion: 5_000 })?;
+
+    let mut test = test.slot(100 + TIMELOCK_SLOTS);
+    assert!(test
+        .cancel_pending_swap_fee()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_signer_is_not_admin() -> Result<()> {
+    let mut test = Tester::default().slot(100);
+    test.set_pool_swap_fee(Permillion { permillion: 5_000 })?;
+
+    test.admin = AccountInfoWrapper::new().signer();
+    assert!(test
+        .cancel_pending_swap_fee()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    slot: u64,
+    admin: AccountInfoWrapper,
+    pool: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let admin = AccountInfoWrapper::new().signer();
+        // with liquidity, so that fee increases are timelocked
+        let reserve = Reserve {
+            tokens: TokenAmount::new(1_000),
+            mint: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+        };
+        let pool =
+            AccountInfoWrapper::new()
+                .mutable()
+                .owner(amm::ID)
+                .data(Pool {
+                    admin: admin.key,
+                    dimension: 2,
+                    reserves: [
+                        reserve,
+                        Reserve {
+                            mint: Pubkey::new_unique(),
+                            vault: Pubkey::new_unique(),
+                            ..reserve
+                        },
+                        Reserve::default(),
+                        Reserve::default(),
+                    ],
+                    swap_fee: Permillion { permillion: 2_000 },
+                    swap_fee_timelock_slots: TIMELOCK_SLOTS,
+                    ..Default::default()
+                });
+
+        Self {
+            slot: 0,
+            admin,
+            pool,
+        }
+    }
+}
+
+impl Tester {
+    fn slot(mut self, slot: u64) -> Self {
+        self.slot = slot;
+        self
+    }
+
+    fn set_pool_swap_fee(&mut self, fee: Permillion) -> Result<()> {
+        self.set_syscalls();
+
+        let mut ctx = ContextWrapper::new(amm::ID)
+            .acc(&mut self.admin)
+            .acc(&mut self.pool);
+        let mut accounts = ctx.accounts()?;
+
+        set_pool_swap_fee(ctx.build(&mut accounts), fee)?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(())
+    }
+
+    fn cancel_pending_swap_fee(&mut self) -> Result<()> {
+        self.set_syscalls();
+
+        let mut ctx = ContextWrapper::new(amm::ID)
+            .acc(&mut self.admin)
+            .acc(&mut self.pool);
+        let mut accounts = ctx.accounts()?;
+
+        cancel_pending_swap_fee(ctx.build(&mut accounts))?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(())
+    }
+
+    fn set_syscalls(&self) {
+        let syscalls = stub::Syscalls::new(CpiValidator);
+        syscalls.slot(self.slot);
+        syscalls.set();
+    }
+
+    fn pool_copy(&self) -> Pool {
+        Pool::try_deserialize(&mut self.pool.data.as_slice()).unwrap()
+    }
+}
+
+struct CpiValidator;
+
+impl stub::ValidateCpis for CpiValidator {
+    fn validate_next_instruction(
+        &mut self,
+        ix: &Instruction,
+        _accounts: &[AccountInfo],
+    ) {
+        panic!("No instructions expected, got {:#?}", ix);
+    }
+}
@@ -1,4 +1,4 @@
-use ::amm::amm::create_pool;
+use ::amm::amm::{create_pool, create_pool_with_nonce};
 use ::amm::prelude::*;
 use anchor_lang::solana_program::system_instruction;
 use anchor_lang::system_program;
@@ -32,6 +32,10 @@ fn with_two_reserves() -> Result<()> {
     assert_eq!(pool.admin, test.admin.key);
     assert_eq!(pool.signer, test.pool_signer.key);
     assert_eq!(pool.curve, Curve::ConstProd);
+    assert_eq!(
+        pool.swap_fee_timelock_slots,
+        consts::DEFAULT_SWAP_FEE_TIMELOCK_SLOTS
+    );
     for (reserve, vault) in pool.reserves[0..2].iter().zip(&test.vaults) {
         assert_eq!(vault.key, reserve.vault);
         assert_eq!(reserve.tokens.amount, 0);
@@ -168,6 +172,23 @@ fn fails_if_less_than_two_reserves() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[serial]
+fn fails_if_too_few_vaults_before_deserializing_them() -> Result<()> {
+    let mut test = Tester::default();
+    // not a token account, the handler must fail before it attempts to
+    // deserialize it
+    test.vaults = vec![AccountInfoWrapper::new()];
+
+    assert!(test
+        .create_pool(CONST_PROD_AMPLIFIER)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
 #[test]
 #[serial]
 fn uses_stable_curve_if_amplifier_not_zero() -> Result<()> {
@@ -319,6 +340,26 @@ fn fails_if_vault_is_frozen() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[serial]
+fn fails_if_vault_is_of_lp_mint() -> Result<()> {
+    let mut test = Tester::default();
+    test.vaults[0] = AccountInfoWrapper::new()
+        .pack(
+            spl::token_account::new(test.pool_signer.key)
+                .mint(test.lp_mint.key),
+        )
+        .owner(token::ID);
+
+    assert!(test
+        .create_pool(CONST_PROD_AMPLIFIER)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
 #[test]
 #[serial]
 fn fails_if_vaults_not_empty_but_lp_mint_supply_is_zero() -> Result<()> {
@@ -357,6 +398,194 @@ fn fails_if_vaults_empty_but_lp_mint_supply_is_not_zero() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[serial]
+fn fails_if_reserve_mint_is_blocked() -> Result<()> {
+    let mut test = Tester::default();
+    let blocked_mint = Pubkey::new_unique();
+    test.vaults[1] = AccountInfoWrapper::new()
+        .pack(spl::token_account::new(test.pool_signer.key).mint(blocked_mint))
+        .owner(token::ID);
+    test.block_mints(vec![Pubkey::new_unique(), blocked_mint]);
+
+    assert!(test
+        .create_pool(CONST_PROD_AMPLIFIER)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn with_mint_blocklist_of_other_mints() -> Result<()> {
+    let mut test = Tester::default();
+    test.block_mints(vec![Pubkey::new_unique()]);
+
+    assert!(test.create_pool(CONST_PROD_AMPLIFIER).is_ok());
+
+    let pool = Pool::try_deserialize(&mut test.pool.data.as_slice())?;
+    assert_eq!(pool.dimension, 2);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_amplifier_is_out_of_curve_constraints() -> Result<()> {
+    let mut test = Tester::default();
+    test.constrain_amplifier(10, 100);
+
+    assert!(test
+        .create_pool(101)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn with_amplifier_within_curve_constraints() -> Result<()> {
+    let mut test = Tester::default();
+    test.constrain_amplifier(10, 100);
+
+    assert!(test.create_pool(100).is_ok());
+
+    let pool = Pool::try_deserialize(&mut test.pool.data.as_slice())?;
+    assert_eq!(
+        pool.curve,
+        Curve::Stable {
+            amplifier: 100,
+            invariant: SDecimal::default(),
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn with_const_prod_curve_under_curve_constraints() -> Result<()> {
+    let mut test = Tester::default();
+    test.constrain_amplifier(10, 100);
+
+    assert!(test.create_pool(CONST_PROD_AMPLIFIER).is_ok());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn creates_pool_at_creator_and_nonce_pda() -> Result<()> {
+    let creator = Pubkey::new_unique();
+    let mut test = Tester::with_creator_pool(creator, 7);
+
+    assert!(test.create_pool_with_nonce(CONST_PROD_AMPLIFIER, 7).is_ok());
+
+    let pool = Pool::try_deserialize(&mut test.pool.data.as_slice())?;
+    assert_eq!(pool.dimension, 2);
+    assert_eq!(pool.admin, creator);
+    assert_eq!(pool.mint, test.lp_mint.key);
+    assert_eq!(pool.signer, test.pool_signer.key);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_creator_pool_is_initialized_by_another_signer() -> Result<()> {
+    let creator = Pubkey::new_unique();
+    let mut test = Tester::with_creator_pool(creator, 7);
+    let front_runner = Tester::default();
+    test.admin = front_runner.admin;
+
+    assert!(test
+        .create_pool_with_nonce(CONST_PROD_AMPLIFIER, 7)
+        .unwrap_err()
+        .to_string()
+        .contains("ConstraintSeeds"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_nonce_does_not_match_creator_pool() -> Result<()> {
+    let mut test = Tester::with_creator_pool(Pubkey::new_unique(), 7);
+
+    assert!(test
+        .create_pool_with_nonce(CONST_PROD_AMPLIFIER, 8)
+        .unwrap_err()
+        .to_string()
+        .contains("ConstraintSeeds"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_creator_pool_lp_mint_authority_is_not_pool_signer() -> Result<()> {
+    let mut test = Tester::with_creator_pool(Pubkey::new_unique(), 7);
+    test.lp_mint = AccountInfoWrapper::with_key(test.lp_mint.key)
+        .pack(spl::mint::new(Pubkey::new_unique()))
+        .owner(token::ID);
+
+    assert!(test
+        .create_pool_with_nonce(CONST_PROD_AMPLIFIER, 7)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_creator_pool_toll_wallet_is_not_of_program_toll_authority(
+) -> Result<()> {
+    let mut test = Tester::with_creator_pool(Pubkey::new_unique(), 7);
+    test.program_toll_wallet = AccountInfoWrapper::new()
+        .pack(
+            spl::token_account::new(Pubkey::new_unique())
+                .mint(test.lp_mint.key),
+        )
+        .owner(token::ID);
+
+    assert!(test
+        .create_pool_with_nonce(CONST_PROD_AMPLIFIER, 7)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_creator_pool_toll_wallet_is_not_of_lp_mint() -> Result<()> {
+    let mut test = Tester::with_creator_pool(Pubkey::new_unique(), 7);
+    let program_toll_authority =
+        ProgramToll::try_deserialize(&mut test.program_toll.data.as_slice())?
+            .authority;
+    test.program_toll_wallet = AccountInfoWrapper::new()
+        .pack(
+            spl::token_account::new(program_toll_authority)
+                .mint(Pubkey::new_unique()),
+        )
+        .owner(token::ID);
+
+    assert!(test
+        .create_pool_with_nonce(CONST_PROD_AMPLIFIER, 7)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
 #[derive(Clone, Debug, PartialEq)]
 struct Tester {
     admin: AccountInfoWrapper,
@@ -364,6 +593,8 @@ struct Tester {
     pool_signer: AccountInfoWrapper,
     lp_mint: AccountInfoWrapper,
     program_toll: AccountInfoWrapper,
+    mint_blocklist: AccountInfoWrapper,
+    curve_constraints: AccountInfoWrapper,
     program_toll_wallet: AccountInfoWrapper,
     token_program: AccountInfoWrapper,
     system_program: AccountInfoWrapper,
@@ -378,6 +609,13 @@ impl Default for Tester {
             .owner(amm::ID)
             .mutable()
             .size(Pool::space());
+
+        Self::new(admin, pool)
+    }
+}
+
+impl Tester {
+    fn new(admin: AccountInfoWrapper, pool: AccountInfoWrapper) -> Self {
         let pool_signer = AccountInfoWrapper::pda(
             amm::ID,
             "pool_signer",
@@ -396,6 +634,18 @@ impl Default for Tester {
             authority: program_toll_authority,
         })
         .owner(amm::ID);
+        let mint_blocklist = AccountInfoWrapper::pda(
+            amm::ID,
+            "mint_blocklist",
+            &[MintBlocklist::PDA_SEED],
+        )
+        .owner(system_program::ID);
+        let curve_constraints = AccountInfoWrapper::pda(
+            amm::ID,
+            "curve_constraints",
+            &[CurveConstraints::PDA_SEED],
+        )
+        .owner(system_program::ID);
         let program_toll_wallet = AccountInfoWrapper::new()
             .pack(
                 spl::token_account::new(program_toll_authority)
@@ -419,15 +669,35 @@ impl Default for Tester {
             pool_signer,
             lp_mint,
             program_toll,
+            mint_blocklist,
+            curve_constraints,
             program_toll_wallet,
             token_program,
             system_program,
             vaults,
         }
     }
-}
 
-impl Tester {
+    /// The pool is the PDA of given creator and nonce, see
+    /// [`Pool::CREATOR_PDA_PREFIX`].
+    fn with_creator_pool(creator: Pubkey, nonce: u64) -> Self {
+        let admin = AccountInfoWrapper::with_key(creator).mutable().signer();
+        let pool = AccountInfoWrapper::pda(
+            amm::ID,
+            "pool",
+            &[
+                Pool::CREATOR_PDA_PREFIX,
+                creator.as_ref(),
+                &nonce.to_le_bytes(),
+            ],
+        )
+        .owner(amm::ID)
+        .mutable()
+        .size(Pool::space());
+
+        Self::new(admin, pool)
+    }
+
     fn create_pool(&mut self, amplifier: u64) -> Result<()> {
         self.set_syscalls();
 
@@ -440,12 +710,49 @@ impl Tester {
         Ok(())
     }
 
+    fn create_pool_with_nonce(
+        &mut self,
+        amplifier: u64,
+        nonce: u64,
+    ) -> Result<()> {
+        self.set_syscalls();
+
+        let mut ctx = self.context_wrapper();
+        let mut accounts = ctx.accounts()?;
+
+        create_pool_with_nonce(ctx.build(&mut accounts), amplifier, nonce)?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(())
+    }
+
+    fn block_mints(&mut self, mints: Vec<Pubkey>) {
+        self.mint_blocklist = self
+            .mint_blocklist
+            .clone()
+            .owner(amm::ID)
+            .data(MintBlocklist { mints });
+    }
+
+    fn constrain_amplifier(&mut self, min_amplifier: u64, max_amplifier: u64) {
+        self.curve_constraints =
+            self.curve_constraints.clone().owner(amm::ID).data(
+                CurveConstraints {
+                    min_amplifier,
+                    max_amplifier,
+                    ..Default::default()
+                },
+            );
+    }
+
     fn context_wrapper(&mut self) -> ContextWrapper {
         ContextWrapper::new(amm::ID)
             .acc(&mut self.admin)
             .acc(&mut self.pool)
             .acc(&mut self.pool_signer)
             .acc(&mut self.program_toll)
+            .acc(&mut self.mint_blocklist)
+            .acc(&mut self.curve_constraints)
             .acc(&mut self.program_toll_wallet)
             .acc(&mut self.lp_mint)
             .acc(&mut self.token_program)
@@ -0,0 +1,407 @@
+use ::amm::amm::create_pool_lp_mint_and_toll_wallet;
+use ::amm::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::{system_instruction, sysvar};
+use anchor_lang::system_program;
+use anchor_spl::token::{self, spl_token};
+use anchortest::{builder::*, stub};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use solana_sdk::instruction::Instruction;
+use std::sync::{Arc, Mutex};
+
+const LP_MINT_DECIMALS: u8 = 6;
+
+#[test]
+#[serial]
+fn works() -> Result<()> {
+    let mut test = Tester::default();
+
+    assert!(test.create_pool_lp_mint_and_toll_wallet().is_ok());
+
+    let lp_mint = spl_token::state::Mint::unpack(&test.lp_mint.data)?;
+    assert_eq!(lp_mint.mint_authority, COption::Some(test.pool_signer.key));
+    assert_eq!(lp_mint.freeze_authority, COption::None);
+    assert_eq!(lp_mint.decimals, LP_MINT_DECIMALS);
+    assert_eq!(lp_mint.supply, 0);
+
+    let program_toll_wallet =
+        spl_token::state::Account::unpack(&test.program_toll_wallet.data)?;
+    assert_eq!(program_toll_wallet.mint, test.lp_mint.key);
+    assert_eq!(program_toll_wallet.owner, test.program_toll_authority.key);
+    assert_eq!(program_toll_wallet.amount, 0);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_program_toll_authority_does_not_match() -> Result<()> {
+    let mut test = Tester::default();
+    test.program_toll_authority = AccountInfoWrapper::new();
+
+    assert!(test
+        .create_pool_lp_mint_and_toll_wallet()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_lp_mint_is_not_pool_pda() -> Result<()> {
+    let mut test = Tester::default();
+    test.lp_mint = AccountInfoWrapper::pda(
+        amm::ID,
+        "lp_mint",
+        &[Pool::LP_MINT_PDA_PREFIX, Pubkey::new_unique().as_ref()],
+    )
+    .mutable()
+    .owner(token::ID)
+    .size(spl_token::state::Mint::LEN);
+
+    assert!(test
+        .create_pool_lp_mint_and_toll_wallet()
+        .unwrap_err()
+        .to_string()
+        .contains("ConstraintSeeds"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_program_toll_wallet_is_not_pool_pda() -> Result<()> {
+    let mut test = Tester::default();
+    test.program_toll_wallet = AccountInfoWrapper::pda(
+        amm::ID,
+        "program_toll_wallet",
+        &[
+            Pool::PROGRAM_TOLL_WALLET_PDA_PREFIX,
+            Pubkey::new_unique().as_ref(),
+        ],
+    )
+    .mutable()
+    .owner(token::ID)
+    .size(spl_token::state::Account::LEN);
+
+    assert!(test
+        .create_pool_lp_mint_and_toll_wallet()
+        .unwrap_err()
+        .to_string()
+        .contains("ConstraintSeeds"));
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    admin: AccountInfoWrapper,
+    pool: AccountInfoWrapper,
+    pool_signer: AccountInfoWrapper,
+    program_toll: AccountInfoWrapper,
+    program_toll_authority: AccountInfoWrapper,
+    lp_mint: AccountInfoWrapper,
+    program_toll_wallet: AccountInfoWrapper,
+    token_program: AccountInfoWrapper,
+    system_program: AccountInfoWrapper,
+    rent: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let admin = AccountInfoWrapper::new().mutable().signer();
+        let pool = AccountInfoWrapper::new();
+        let pool_signer = AccountInfoWrapper::pda(
+            amm::ID,
+            "pool_signer",
+            &[Pool::SIGNER_PDA_PREFIX, pool.key.as_ref()],
+        );
+        let program_toll_authority = AccountInfoWrapper::new();
+        let program_toll = AccountInfoWrapper::pda(
+            amm::ID,
+            "program_toll",
+            &[ProgramToll::PDA_SEED],
+        )
+        .data(ProgramToll {
+            authority: program_toll_authority.key,
+        })
+        .owner(amm::ID);
+        let lp_mint = AccountInfoWrapper::pda(
+            amm::ID,
+            "lp_mint",
+            &[Pool::LP_MINT_PDA_PREFIX, pool.key.as_ref()],
+        )
+        .mutable()
+        .owner(token::ID)
+        .size(spl_token::state::Mint::LEN);
+        let program_toll_wallet = AccountInfoWrapper::pda(
+            amm::ID,
+            "program_toll_wallet",
+            &[Pool::PROGRAM_TOLL_WALLET_PDA_PREFIX, pool.key.as_ref()],
+        )
+        .mutable()
+        .owner(token::ID)
+        .size(spl_token::state::Account::LEN);
+        let token_program = AccountInfoWrapper::with_key(token::ID).program();
+        let system_program =
+            AccountInfoWrapper::with_key(system_program::ID).program();
+        let mut rent =
+            AccountInfoWrapper::with_key(sysvar::rent::ID).owner(sysvar::ID);
+        rent.data = rent_sysvar_data();
+
+        Self {
+            admin,
+            pool,
+            pool_signer,
+            program_toll,
+            program_toll_authority,
+            lp_mint,
+            program_toll_wallet,
+            token_program,
+            system_program,
+            rent,
+        }
+    }
+}
+
+impl Tester {
+    fn create_pool_lp_mint_and_toll_wallet(&mut self) -> Result<()> {
+        let state = self.set_syscalls(CpiValidatorState::CreateLpMint {
+            admin: self.admin.key,
+            lp_mint: self.lp_mint.key,
+            pool_signer: self.pool_signer.key,
+            program_toll_wallet: self.program_toll_wallet.key,
+            program_toll_authority: self.program_toll_authority.key,
+        });
+
+        let mut ctx = self.context_wrapper();
+        let mut accounts = ctx.accounts()?;
+
+        create_pool_lp_mint_and_toll_wallet(
+            ctx.build(&mut accounts),
+            LP_MINT_DECIMALS,
+        )?;
+        accounts.exit(&amm::ID)?;
+
+        assert_eq!(*state.lock().unwrap(), CpiValidatorState::Done);
+
+        Ok(())
+    }
+
+    fn context_wrapper(&mut self) -> ContextWrapper {
+        ContextWrapper::new(amm::ID)
+            .acc(&mut self.admin)
+            .acc(&mut self.pool)
+            .acc(&mut self.pool_signer)
+            .acc(&mut self.program_toll)
+            .acc(&mut self.program_toll_authority)
+            .acc(&mut self.lp_mint)
+            .acc(&mut self.program_toll_wallet)
+            .acc(&mut self.token_program)
+            .acc(&mut self.system_program)
+            .acc(&mut self.rent)
+    }
+
+    fn set_syscalls(
+        &self,
+        state: CpiValidatorState,
+    ) -> Arc<Mutex<CpiValidatorState>> {
+        let state = Arc::new(Mutex::new(state));
+
+        let syscalls = stub::Syscalls::new(CpiValidator(Arc::clone(&state)));
+        syscalls.set();
+
+        state
+    }
+}
+
+/// Bincode serialization of the default rent sysvar.
+fn rent_sysvar_data() -> Vec<u8> {
+    let rent = Rent::default();
+
+    let mut data = Vec::with_capacity(17);
+    data.extend_from_slice(&rent.lamports_per_byte_year.to_le_bytes());
+    data.extend_from_slice(&rent.exemption_threshold.to_le_bytes());
+    data.push(rent.burn_percent);
+
+    data
+}
+
+struct CpiValidator(Arc<Mutex<CpiValidatorState>>);
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+enum CpiValidatorState {
+    CreateLpMint {
+        admin: Pubkey,
+        lp_mint: Pubkey,
+        pool_signer: Pubkey,
+        program_toll_wallet: Pubkey,
+        program_toll_authority: Pubkey,
+    },
+    InitializeLpMint {
+        admin: Pubkey,
+        lp_mint: Pubkey,
+        pool_signer: Pubkey,
+        program_toll_wallet: Pubkey,
+        program_toll_authority: Pubkey,
+    },
+    CreateProgramTollWallet {
+        admin: Pubkey,
+        lp_mint: Pubkey,
+        program_toll_wallet: Pubkey,
+        program_toll_authority: Pubkey,
+    },
+    InitializeProgramTollWallet {
+        lp_mint: Pubkey,
+        program_toll_wallet: Pubkey,
+        program_toll_authority: Pubkey,
+    },
+    Done,
+}
+
+impl stub::ValidateCpis for CpiValidator {
+    fn validate_next_instruction(
+        &mut self,
+        ix: &Instruction,
+        accounts: &[AccountInfo],
+    ) {
+        let mut state = self.0.lock().unwrap();
+        match *state {
+            CpiValidatorState::CreateLpMint {
+                admin,
+                lp_mint,
+                pool_signer,
+                program_toll_wallet,
+                program_toll_authority,
+            } => {
+                let space = spl_token::state::Mint::LEN;
+                let rent = Rent::default().minimum_balance(space);
+                let expected_ix = system_instruction::create_account(
+                    &admin,
+                    &lp_mint,
+                    rent,
+                    space as u64,
+                    &token::ID,
+                );
+                assert_eq!(&expected_ix, ix);
+
+                set_lamports(accounts, lp_mint, rent);
+
+                *state = CpiValidatorState::InitializeLpMint {
+                    admin,
+                    lp_mint,
+                    pool_signer,
+                    program_toll_wallet,
+                    program_toll_authority,
+                };
+            }
+            CpiValidatorState::InitializeLpMint {
+                admin,
+                lp_mint,
+                pool_signer,
+                program_toll_wallet,
+                program_toll_authority,
+            } => {
+                let expected_ix = spl_token::instruction::initialize_mint(
+                    &token::ID,
+                    &lp_mint,
+                    &pool_signer,
+                    None,
+                    LP_MINT_DECIMALS,
+                )
+                .unwrap();
+                assert_eq!(&expected_ix, ix);
+
+                let mint = spl_token::state::Mint {
+                    mint_authority: COption::Some(pool_signer),
+                    supply: 0,
+                    decimals: LP_MINT_DECIMALS,
+                    is_initialized: true,
+                    freeze_authority: COption::None,
+                };
+                let lp_mint_info =
+                    accounts.iter().find(|acc| acc.key() == lp_mint).unwrap();
+                spl_token::state::Mint::pack(
+                    mint,
+                    &mut lp_mint_info.data.borrow_mut(),
+                )
+                .unwrap();
+
+                *state = CpiValidatorState::CreateProgramTollWallet {
+                    admin,
+                    lp_mint,
+                    program_toll_wallet,
+                    program_toll_authority,
+                };
+            }
+            CpiValidatorState::CreateProgramTollWallet {
+                admin,
+                lp_mint,
+                program_toll_wallet,
+                program_toll_authority,
+            } => {
+                let space = spl_token::state::Account::LEN;
+                let rent = Rent::default().minimum_balance(space);
+                let expected_ix = system_instruction::create_account(
+                    &admin,
+                    &program_toll_wallet,
+                    rent,
+                    space as u64,
+                    &token::ID,
+                );
+                assert_eq!(&expected_ix, ix);
+
+                set_lamports(accounts, program_toll_wallet, rent);
+
+                *state = CpiValidatorState::InitializeProgramTollWallet {
+                    lp_mint,
+                    program_toll_wallet,
+                    program_toll_authority,
+                };
+            }
+            CpiValidatorState::InitializeProgramTollWallet {
+                lp_mint,
+                program_toll_wallet,
+                program_toll_authority,
+            } => {
+                let expected_ix = spl_token::instruction::initialize_account(
+                    &token::ID,
+                    &program_toll_wallet,
+                    &lp_mint,
+                    &program_toll_authority,
+                )
+                .unwrap();
+                assert_eq!(&expected_ix, ix);
+
+                let wallet = spl_token::state::Account {
+                    mint: lp_mint,
+                    owner: program_toll_authority,
+                    state: spl_token::state::AccountState::Initialized,
+                    ..Default::default()
+                };
+                let wallet_info = accounts
+                    .iter()
+                    .find(|acc| acc.key() == program_toll_wallet)
+                    .unwrap();
+                spl_token::state::Account::pack(
+                    wallet,
+                    &mut wallet_info.data.borrow_mut(),
+                )
+                .unwrap();
+
+                *state = CpiValidatorState::Done;
+            }
+            CpiValidatorState::Done => {
+                panic!("No more instructions expected, got {:#?}", ix);
+            }
+        }
+    }
+}
+
+fn set_lamports(accounts: &[AccountInfo], key: Pubkey, lamports: u64) {
+    let acc = accounts.iter().find(|acc| acc.key() == key).unwrap();
+    **acc.lamports.borrow_mut() = lamports;
+}
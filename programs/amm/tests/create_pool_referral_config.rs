@@ -0,0 +1,305 @@
+use ::amm::amm::create_pool_referral_config;
+use ::amm::prelude::*;
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::system_program;
+use anchor_spl::token;
+use anchortest::{
+    builder::*,
+    spl::{self, MintExt, TokenAccountExt},
+    stub,
+};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use solana_sdk::instruction::Instruction;
+
+#[test]
+#[serial]
+fn works() -> Result<()> {
+    let mut test = Tester::default();
+    let og_state = test.clone();
+
+    let share = Permillion::from_percent(40);
+    assert!(test.create_pool_referral_config(share).is_ok());
+
+    let config = ReferralConfig::try_deserialize(
+        &mut test.referral_config.data.as_slice(),
+    )?;
+    assert_eq!(config.share, share);
+    assert_eq!(config.default_recipient, test.default_referral_wallet.key);
+
+    // no other changes should have happened
+    test.referral_config = og_state.referral_config.clone();
+    assert_eq!(test, og_state);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_signer_is_not_pool_admin() -> Result<()> {
+    let mut test = Tester::default();
+    test.admin = AccountInfoWrapper::new().mutable().signer();
+
+    assert!(test
+        .create_pool_referral_config(Permillion::from_percent(40))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_pool_has_liquidity() -> Result<()> {
+    let mut test = Tester::default();
+    test.lp_mint = test
+        .lp_mint
+        .clone()
+        .pack(spl::mint::new(Pubkey::new_unique()).supply(100));
+
+    assert!(test
+        .create_pool_referral_config(Permillion::from_percent(40))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_default_referral_wallet_is_not_of_lp_mint() -> Result<()> {
+    let mut test = Tester::default();
+    test.default_referral_wallet = AccountInfoWrapper::new()
+        .pack(
+            spl::token_account::new(Pubkey::new_unique())
+                .mint(Pubkey::new_unique()),
+        )
+        .owner(token::ID);
+
+    assert!(test
+        .create_pool_referral_config(Permillion::from_percent(40))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_default_referral_wallet_is_not_of_program_toll_authority(
+) -> Result<()> {
+    let mut test = Tester::default();
+    // the pool admin cannot direct the default referral share to themselves
+    test.default_referral_wallet = AccountInfoWrapper::new()
+        .pack(spl::token_account::new(test.admin.key).mint(test.lp_mint.key))
+        .owner(token::ID);
+
+    assert!(test
+        .create_pool_referral_config(Permillion::from_percent(40))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn works_with_max_share() -> Result<()> {
+    let mut test = Tester::default();
+
+    assert!(test
+        .create_pool_referral_config(consts::MAX_REFERRAL_SHARE)
+        .is_ok());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_share_is_more_than_max() -> Result<()> {
+    let mut test = Tester::default();
+
+    assert!(test
+        .create_pool_referral_config(Permillion {
+            permillion: consts::MAX_REFERRAL_SHARE.permillion + 1,
+        })
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+    assert!(test
+        .create_pool_referral_config(Permillion::from_percent(100))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_pda_does_not_match() -> Result<()> {
+    let mut test = Tester::default();
+    test.referral_config = AccountInfoWrapper::pda(
+        amm::ID,
+        "referral_config",
+        &[ReferralConfig::PDA_PREFIX, Pubkey::new_unique().as_ref()],
+    )
+    .size(ReferralConfig::space())
+    .mutable()
+    .owner(amm::ID);
+
+    assert!(test
+        .create_pool_referral_config(Permillion::from_percent(40))
+        .unwrap_err()
+        .to_string()
+        .contains("ConstraintSeeds"));
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    admin: AccountInfoWrapper,
+    pool: AccountInfoWrapper,
+    lp_mint: AccountInfoWrapper,
+    referral_config: AccountInfoWrapper,
+    program_toll: AccountInfoWrapper,
+    default_referral_wallet: AccountInfoWrapper,
+    system_program: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let admin = AccountInfoWrapper::new().mutable().signer();
+        let lp_mint = AccountInfoWrapper::new()
+            .pack(spl::mint::new(Pubkey::new_unique()))
+            .owner(token::ID);
+        let pool = AccountInfoWrapper::new().owner(amm::ID).data(Pool {
+            admin: admin.key,
+            mint: lp_mint.key,
+            ..Default::default()
+        });
+        let referral_config = AccountInfoWrapper::pda(
+            amm::ID,
+            "referral_config",
+            &[ReferralConfig::PDA_PREFIX, pool.key.as_ref()],
+        )
+        .size(ReferralConfig::space())
+        .mutable()
+        .owner(amm::ID);
+        let program_toll_authority = Pubkey::new_unique();
+        let program_toll = AccountInfoWrapper::pda(
+            amm::ID,
+            "program_toll",
+            &[ProgramToll::PDA_SEED],
+        )
+        .data(ProgramToll {
+            authority: program_toll_authority,
+        })
+        .owner(amm::ID);
+        let default_referral_wallet = AccountInfoWrapper::new()
+            .pack(
+                spl::token_account::new(program_toll_authority)
+                    .mint(lp_mint.key),
+            )
+            .owner(token::ID);
+        let system_program =
+            AccountInfoWrapper::with_key(system_program::ID).program();
+
+        Self {
+            admin,
+            pool,
+            lp_mint,
+            referral_config,
+            program_toll,
+            default_referral_wallet,
+            system_program,
+        }
+    }
+}
+
+impl Tester {
+    fn create_pool_referral_config(&mut self, share: Permillion) -> Result<()> {
+        self.set_syscalls();
+
+        let mut ctx = self.context_wrapper();
+        let mut accounts = ctx.accounts()?;
+
+        create_pool_referral_config(ctx.build(&mut accounts), share)?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(())
+    }
+
+    fn context_wrapper(&mut self) -> ContextWrapper {
+        ContextWrapper::new(amm::ID)
+            .acc(&mut self.admin)
+            .acc(&mut self.pool)
+            .acc(&mut self.lp_mint)
+            .acc(&mut self.referral_config)
+            .acc(&mut self.program_toll)
+            .acc(&mut self.default_referral_wallet)
+            .acc(&mut self.system_program)
+    }
+
+    fn set_syscalls(&self) {
+        stub::Syscalls::new(CpiValidator(
+            CpiValidatorState::CreateReferralConfig {
+                admin: self.admin.key,
+                referral_config: self.referral_config.key,
+            },
+        ))
+        .set();
+    }
+}
+
+struct CpiValidator(CpiValidatorState);
+enum CpiValidatorState {
+    CreateReferralConfig {
+        admin: Pubkey,
+        referral_config: Pubkey,
+    },
+    Done,
+}
+
+impl stub::ValidateCpis for CpiValidator {
+    fn validate_next_instruction(
+        &mut self,
+        ix: &Instruction,
+        accounts: &[AccountInfo],
+    ) {
+        match self.0 {
+            CpiValidatorState::CreateReferralConfig {
+                admin,
+                referral_config,
+            } => {
+                let rent =
+                    Rent::default().minimum_balance(ReferralConfig::space());
+                let expected_ix = system_instruction::create_account(
+                    &admin,
+                    &referral_config,
+                    rent,
+                    ReferralConfig::space() as u64,
+                    &amm::ID,
+                );
+                assert_eq!(&expected_ix, ix);
+
+                let referral_config = accounts
+                    .iter()
+                    .find(|acc| acc.key() == referral_config)
+                    .unwrap();
+                let mut lamports = referral_config.lamports.borrow_mut();
+                **lamports = rent;
+
+                self.0 = CpiValidatorState::Done;
+            }
+            CpiValidatorState::Done => {
+                panic!("No more instructions expected, got {:#?}", ix);
+            }
+        }
+    }
+}
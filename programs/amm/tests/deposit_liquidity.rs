@@ -1,5 +1,7 @@
 #[allow(dead_code)]
 mod deposit_redeem;
+#[allow(dead_code)]
+mod return_data;
 
 use ::amm::prelude::*;
 use anchor_spl::token;
@@ -135,6 +137,33 @@ fn fails_if_less_than_two_reserves() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[serial]
+fn fails_if_too_few_remaining_accounts() -> Result<()> {
+    let (mut tester, reserves) = Tester::new_const_prod(2);
+    // these are not even token accounts, the handler must fail before it
+    // attempts to deserialize them
+    tester.vaults_wallets = vec![
+        AccountInfoWrapper::new(),
+        AccountInfoWrapper::new(),
+        AccountInfoWrapper::new(),
+    ];
+
+    let error = tester
+        .deposit_liquidity(
+            reserves
+                .iter()
+                .map(|r| (r.mint, TokenAmount::new(10)))
+                .collect(),
+            &reserves,
+        )
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
 #[test]
 #[serial]
 fn fails_if_same_mint_passed_multiple_times() -> Result<()> {
@@ -651,6 +680,158 @@ fn fails_if_no_lp_tokens_would_be_minted() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[serial]
+fn fails_if_lp_supply_would_overflow() -> Result<()> {
+    let (mut tester, reserves) = Tester::new_const_prod(2);
+
+    tester.deposit_liquidity(
+        reserves
+            .iter()
+            .map(|r| (r.mint, TokenAmount::new(10_000)))
+            .collect(),
+        &reserves,
+    )?;
+
+    // LP supply is close to the ceiling
+    let lp_mint = spl::mint::from_acc_info(&tester.lp_mint.to_account_info())
+        .supply(u64::MAX - 10);
+    tester.lp_mint = tester.lp_mint.pack(lp_mint);
+
+    let error = tester
+        .deposit_liquidity(
+            reserves
+                .iter()
+                .map(|r| (r.mint, TokenAmount::new(10)))
+                .collect(),
+            &reserves,
+        )
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("MathOverflow"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn deposits_up_to_max_lp_supply() -> Result<()> {
+    let (mut tester, reserves) = Tester::new_const_prod(2);
+    set_max_lp_supply(&mut tester, 11_000)?;
+
+    tester.deposit_liquidity(
+        reserves_to_max_amount_tokens(&reserves, 10_000),
+        &reserves,
+    )?;
+    // the cap is inclusive
+    tester.deposit_liquidity(
+        reserves_to_max_amount_tokens(&reserves, 1_000),
+        &reserves,
+    )?;
+
+    let lp_mint = spl::mint::from_acc_info(&tester.lp_mint.to_account_info());
+    assert_eq!(lp_mint.supply, 11_000);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_deposit_would_exceed_max_lp_supply() -> Result<()> {
+    let (mut tester, reserves) = Tester::new_const_prod(2);
+    set_max_lp_supply(&mut tester, 11_000)?;
+
+    tester.deposit_liquidity(
+        reserves_to_max_amount_tokens(&reserves, 10_000),
+        &reserves,
+    )?;
+
+    let error = tester
+        .deposit_liquidity(
+            reserves_to_max_amount_tokens(&reserves, 1_001),
+            &reserves,
+        )
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("MaxLpSupplyExceeded"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_initial_deposit_would_exceed_max_lp_supply() -> Result<()> {
+    let (mut tester, reserves) = Tester::new_const_prod(2);
+    set_max_lp_supply(&mut tester, 100)?;
+
+    let error = tester
+        .deposit_liquidity(
+            reserves_to_max_amount_tokens(&reserves, 101),
+            &reserves,
+        )
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("MaxLpSupplyExceeded"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn returns_deposited_tokens_and_minted_lp_tokens() -> Result<()> {
+    let (mut tester, reserves) = Tester::new_const_prod(2);
+    tester.deposit_liquidity(
+        [(reserves[0].mint, 100), (reserves[1].mint, 10)]
+            .into_iter()
+            .map(|(mint, tokens)| (mint, TokenAmount::new(tokens)))
+            .collect(),
+        &reserves,
+    )?;
+    let lp_wallet_before = lp_wallet_amount(&mut tester);
+    let wallets_before = wallet_amounts(&mut tester);
+
+    tester.deposit_liquidity(
+        [(reserves[0].mint, 55), (reserves[1].mint, 5)]
+            .into_iter()
+            .map(|(mint, tokens)| (mint, TokenAmount::new(tokens)))
+            .collect(),
+        &reserves,
+    )?;
+    let result: LiquidityResult = return_data::decode_last();
+
+    let deposited: Vec<_> = reserves
+        .iter()
+        .zip(wallets_before)
+        .zip(wallet_amounts(&mut tester))
+        .map(|((r, before), after)| TokenLimit {
+            mint: r.mint,
+            tokens: TokenAmount::new(before - after),
+        })
+        .collect();
+    assert_eq!(
+        result,
+        LiquidityResult {
+            lp_tokens_minted: TokenAmount::new(
+                lp_wallet_amount(&mut tester) - lp_wallet_before
+            ),
+            lp_tokens_burned: TokenAmount::new(0),
+            tokens: deposited,
+            tokens_rounding: Rounding::Ceiling,
+        }
+    );
+    assert!(result.lp_tokens_minted.amount > 0);
+
+    Ok(())
+}
+
+fn set_max_lp_supply(tester: &mut Tester, max_lp_supply: u64) -> Result<()> {
+    let mut pool = Pool::try_deserialize(&mut tester.pool.data.as_slice())?;
+    pool.max_lp_supply = TokenAmount::new(max_lp_supply);
+    tester.pool = tester.pool.clone().data(pool);
+
+    Ok(())
+}
+
 // Creates input arg into the [`deposit_liquidity`] endpoint with all maxes
 // being the same.
 fn reserves_to_max_amount_tokens(
@@ -662,3 +843,22 @@ fn reserves_to_max_amount_tokens(
         .map(|r| (r.mint, TokenAmount::new(amounts)))
         .collect()
 }
+
+fn lp_wallet_amount(tester: &mut Tester) -> u64 {
+    spl::token_account::from_acc_info(&tester.lp_token_wallet.to_account_info())
+        .amount
+}
+
+// User's wallet amounts ordered by reserves.
+fn wallet_amounts(tester: &mut Tester) -> Vec<u64> {
+    tester
+        .vaults_wallets
+        .chunks_mut(2)
+        .map(|vault_wallet| {
+            spl::token_account::from_acc_info(
+                &vault_wallet[1].to_account_info(),
+            )
+            .amount
+        })
+        .collect()
+}
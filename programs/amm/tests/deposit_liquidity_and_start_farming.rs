@@ -0,0 +1,340 @@
+use ::amm::amm::deposit_liquidity_and_start_farming;
+use ::amm::prelude::*;
+use anchor_lang::InstructionData;
+use anchor_spl::token;
+use anchortest::{
+    builder::*,
+    spl::{self, *},
+    stub,
+};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use solana_sdk::instruction::Instruction;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+#[test]
+#[serial]
+fn stakes_all_minted_lp_tokens_in_farm() -> Result<()> {
+    let mut test = Tester::default();
+    let lp_tokens_minted = test.lp_tokens_for(1_000, 3_000);
+    assert!(lp_tokens_minted.amount > 0);
+
+    test.deposit_liquidity_and_start_farming(1_000, 3_000)?;
+
+    // the whole deposit ends up in the farm and the user keeps no LP tokens
+    assert_eq!(test.stake_vault_amount(), lp_tokens_minted.amount);
+    assert_eq!(test.lp_token_wallet_amount(), 0);
+
+    let pool = test.pool_copy();
+    assert_eq!(pool.reserves[0].tokens, TokenAmount::new(11_000));
+    assert_eq!(pool.reserves[1].tokens, TokenAmount::new(33_000));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_farming_program_is_not_farming() -> Result<()> {
+    let mut test = Tester::default();
+    test.farming_program =
+        AccountInfoWrapper::with_key(Pubkey::new_unique()).program();
+
+    assert!(test
+        .deposit_liquidity_and_start_farming(1_000, 3_000)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidProgramId"));
+
+    Ok(())
+}
+
+struct Tester {
+    user: AccountInfoWrapper,
+    pool: AccountInfoWrapper,
+    pool_signer: AccountInfoWrapper,
+    lp_mint: AccountInfoWrapper,
+    lp_token_wallet: AccountInfoWrapper,
+    token_program: AccountInfoWrapper,
+    farmer: AccountInfoWrapper,
+    farm: AccountInfoWrapper,
+    stake_vault: AccountInfoWrapper,
+    farming_program: AccountInfoWrapper,
+    vaults_wallets: Vec<AccountInfoWrapper>,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let user = AccountInfoWrapper::new().mutable().signer();
+        let pool = AccountInfoWrapper::new().owner(amm::ID).mutable();
+        let pool_signer = AccountInfoWrapper::pda(
+            amm::ID,
+            "pool_signer",
+            &[Pool::SIGNER_PDA_PREFIX, pool.key.as_ref()],
+        );
+        let lp_mint = AccountInfoWrapper::new()
+            .mutable()
+            .pack(spl::mint::new(pool_signer.key).supply(10_000))
+            .owner(token::ID);
+        let lp_token_wallet = AccountInfoWrapper::new()
+            .mutable()
+            .pack(spl::token_account::new(user.key).mint(lp_mint.key))
+            .owner(token::ID);
+        let token_program = AccountInfoWrapper::with_key(token::ID).program();
+        let farm = AccountInfoWrapper::new().owner(farming::ID);
+        let farmer = AccountInfoWrapper::new().mutable().owner(farming::ID);
+        let stake_vault = AccountInfoWrapper::new()
+            .mutable()
+            .pack(
+                spl::token_account::new(Pubkey::new_unique()).mint(lp_mint.key),
+            )
+            .owner(token::ID);
+        let farming_program =
+            AccountInfoWrapper::with_key(farming::ID).program();
+
+        let mut reserves = [Reserve::default(); consts::MAX_RESERVES];
+        let mut vaults_wallets = vec![];
+        for (index, tokens) in [10_000, 30_000].into_iter().enumerate() {
+            let mint = Pubkey::new_unique();
+            let vault = AccountInfoWrapper::new()
+                .mutable()
+                .pack(
+                    spl::token_account::new(pool_signer.key)
+                        .mint(mint)
+                        .amount(tokens),
+                )
+                .owner(token::ID);
+            let wallet = AccountInfoWrapper::new()
+                .mutable()
+                .pack(
+                    spl::token_account::new(user.key)
+                        .mint(mint)
+                        .amount(1_000_000),
+                )
+                .owner(token::ID);
+
+            reserves[index] = Reserve {
+                vault: vault.key,
+                mint,
+                tokens: TokenAmount::new(tokens),
+            };
+            vaults_wallets.push(vault);
+            vaults_wallets.push(wallet);
+        }
+
+        let pool = pool.data(Pool {
+            signer: pool_signer.key,
+            mint: lp_mint.key,
+            dimension: 2,
+            reserves,
+            ..Default::default()
+        });
+
+        Self {
+            user,
+            pool,
+            pool_signer,
+            lp_mint,
+            lp_token_wallet,
+            token_program,
+            farmer,
+            farm,
+            stake_vault,
+            farming_program,
+            vaults_wallets,
+        }
+    }
+}
+
+impl Tester {
+    fn pool_copy(&self) -> Pool {
+        Pool::try_deserialize(&mut self.pool.data.as_slice()).unwrap()
+    }
+
+    fn stake_vault_amount(&mut self) -> u64 {
+        spl::token_account::from_acc_info(&self.stake_vault.to_account_info())
+            .amount
+    }
+
+    fn lp_token_wallet_amount(&mut self) -> u64 {
+        spl::token_account::from_acc_info(
+            &self.lp_token_wallet.to_account_info(),
+        )
+        .amount
+    }
+
+    fn max_amount_tokens(
+        &self,
+        first: u64,
+        second: u64,
+    ) -> BTreeMap<Pubkey, TokenAmount> {
+        let pool = self.pool_copy();
+        BTreeMap::from([
+            (pool.reserves[0].mint, TokenAmount::new(first)),
+            (pool.reserves[1].mint, TokenAmount::new(second)),
+        ])
+    }
+
+    fn lp_tokens_for(&self, first: u64, second: u64) -> TokenAmount {
+        self.pool_copy()
+            .deposit_tokens(
+                self.max_amount_tokens(first, second),
+                TokenAmount::new(10_000),
+            )
+            .unwrap()
+            .lp_tokens_to_distribute
+            .unwrap()
+    }
+
+    /// Expects the deposit transfers, then the LP tokens to be minted and
+    /// then all of them to be staked.
+    fn expected_cpis(&self, first: u64, second: u64) -> VecDeque<ExpectedCpi> {
+        let pool = self.pool_copy();
+        let DepositResult {
+            lp_tokens_to_distribute,
+            tokens_to_deposit,
+        } = pool
+            .clone()
+            .deposit_tokens(
+                self.max_amount_tokens(first, second),
+                TokenAmount::new(10_000),
+            )
+            .unwrap();
+        let lp_tokens = lp_tokens_to_distribute.unwrap();
+
+        let mut cpis = VecDeque::new();
+        // vaults and wallets are in the same order as the reserves
+        for (reserve, vault_wallet) in
+            pool.reserves().iter().zip(self.vaults_wallets.chunks(2))
+        {
+            let tokens = tokens_to_deposit[&reserve.mint];
+            cpis.push_back(ExpectedCpi::Transfer(
+                token::spl_token::instruction::transfer(
+                    &token::ID,
+                    &vault_wallet[1].key,
+                    &vault_wallet[0].key,
+                    &self.user.key,
+                    &[],
+                    tokens.amount,
+                )
+                .unwrap(),
+                tokens,
+            ));
+        }
+        cpis.push_back(ExpectedCpi::MintTo(
+            token::spl_token::instruction::mint_to(
+                &token::ID,
+                &self.lp_mint.key,
+                &self.lp_token_wallet.key,
+                &self.pool_signer.key,
+                &[],
+                lp_tokens.amount,
+            )
+            .unwrap(),
+            lp_tokens,
+        ));
+        cpis.push_back(ExpectedCpi::StartFarming(lp_tokens));
+
+        cpis
+    }
+
+    fn deposit_liquidity_and_start_farming(
+        &mut self,
+        first: u64,
+        second: u64,
+    ) -> Result<()> {
+        let state = Arc::new(Mutex::new(self.expected_cpis(first, second)));
+        let syscalls = stub::Syscalls::new(CpiValidator(Arc::clone(&state)));
+        syscalls.slot(0);
+        syscalls.set();
+
+        let max_amount_tokens = self
+            .max_amount_tokens(first, second)
+            .into_iter()
+            .map(|(mint, tokens)| TokenLimit { mint, tokens })
+            .collect();
+
+        let mut ctx = ContextWrapper::new(amm::ID)
+            .acc(&mut self.user)
+            .acc(&mut self.pool)
+            .acc(&mut self.pool_signer)
+            .acc(&mut self.lp_mint)
+            .acc(&mut self.lp_token_wallet)
+            .acc(&mut self.token_program)
+            .acc(&mut self.farmer)
+            .acc(&mut self.farm)
+            .acc(&mut self.stake_vault)
+            .acc(&mut self.farming_program)
+            .remaining_accounts(self.vaults_wallets.iter_mut());
+        let mut accounts = ctx.accounts()?;
+
+        deposit_liquidity_and_start_farming(
+            ctx.build(&mut accounts),
+            max_amount_tokens,
+        )?;
+        accounts.exit(&amm::ID)?;
+
+        assert!(state.lock().unwrap().is_empty());
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+enum ExpectedCpi {
+    Transfer(Instruction, TokenAmount),
+    MintTo(Instruction, TokenAmount),
+    StartFarming(TokenAmount),
+}
+
+#[derive(Debug, Clone)]
+struct CpiValidator(Arc<Mutex<VecDeque<ExpectedCpi>>>);
+
+impl stub::ValidateCpis for CpiValidator {
+    fn validate_next_instruction(
+        &mut self,
+        ix: &Instruction,
+        accounts: &[AccountInfo],
+    ) {
+        let expected =
+            self.0.lock().unwrap().pop_front().unwrap_or_else(|| {
+                panic!("No more instructions expected, got {:#?}", ix)
+            });
+
+        match expected {
+            ExpectedCpi::Transfer(expected_ix, tokens) => {
+                assert_eq!(&expected_ix, ix);
+                spl::token_account::transfer(
+                    &accounts[0],
+                    &accounts[1],
+                    tokens.amount,
+                )
+                .expect("Source account does not have enough tokens");
+            }
+            ExpectedCpi::MintTo(expected_ix, tokens) => {
+                assert_eq!(&expected_ix, ix);
+                spl::mint::mint_to(&accounts[0], &accounts[1], tokens.amount)
+                    .expect("Cannot mint LP tokens");
+            }
+            ExpectedCpi::StartFarming(stake) => {
+                assert_eq!(ix.program_id, farming::ID);
+                assert_eq!(
+                    ix.data,
+                    farming::instruction::StartFarming {
+                        stake: farming::models::TokenAmount::new(stake.amount),
+                    }
+                    .data()
+                );
+
+                // wallet authority, farmer, stake wallet, farm, stake vault
+                assert!(ix.accounts[0].is_signer);
+                spl::token_account::transfer(
+                    &accounts[2],
+                    &accounts[4],
+                    stake.amount,
+                )
+                .expect("LP wallet does not have enough tokens to stake");
+            }
+        }
+    }
+}
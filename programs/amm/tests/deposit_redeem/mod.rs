@@ -1,3 +1,4 @@
+use super::return_data::ReturnData;
 use ::amm::amm::deposit_liquidity;
 use ::amm::amm::redeem_liquidity;
 use ::amm::prelude::*;
@@ -285,7 +286,8 @@ impl Tester {
     ) -> stub::Syscalls<CpiValidator> {
         let validator = CpiValidator(Arc::new(Mutex::new(state)));
         let syscalls = stub::Syscalls::new(validator);
-        syscalls.clone().set();
+        // also captures the return data, see `return_data::decode_last`
+        ReturnData::wrap(syscalls.clone());
 
         syscalls
     }
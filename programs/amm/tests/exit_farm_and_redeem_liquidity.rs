@@ -0,0 +1,404 @@
+use ::amm::amm::exit_farm_and_redeem_liquidity;
+use ::amm::prelude::*;
+use anchor_lang::InstructionData;
+use anchor_spl::token;
+use anchortest::{
+    builder::*,
+    spl::{self, *},
+    stub,
+};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use solana_sdk::instruction::Instruction;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+const STAKED: u64 = 1_000;
+const HARVEST: u64 = 500;
+
+#[test]
+#[serial]
+fn returns_harvest_and_underlying_and_closes_farmer() -> Result<()> {
+    let mut test = Tester::default();
+
+    test.exit_farm_and_redeem_liquidity(1_000, 3_000)?;
+
+    // the harvest is claimed
+    assert_eq!(token_amount(&mut test.harvest_wallet), HARVEST);
+    // all the unstaked LP tokens are redeemed, the ones the user held before
+    // are kept
+    assert_eq!(token_amount(&mut test.stake_vault), 0);
+    assert_eq!(token_amount(&mut test.lp_token_wallet), 100);
+    assert_eq!(token_amount(&mut test.vaults_wallets[1]), 1_000_000 + 1_000);
+    assert_eq!(token_amount(&mut test.vaults_wallets[3]), 1_000_000 + 3_000);
+
+    let pool = test.pool_copy();
+    assert_eq!(pool.reserves[0].tokens, TokenAmount::new(9_000));
+    assert_eq!(pool.reserves[1].tokens, TokenAmount::new(27_000));
+
+    // the farmer account is closed
+    assert!(test.farmer.data.iter().all(|byte| *byte == 0));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_redeemed_tokens_are_below_min() -> Result<()> {
+    let mut test = Tester::default();
+
+    assert!(test
+        .exit_farm_and_redeem_liquidity(1_001, 3_000)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_user_is_not_writable() -> Result<()> {
+    let mut test = Tester::default();
+    test.user.is_writable = false;
+
+    assert!(test
+        .exit_farm_and_redeem_liquidity(1_000, 3_000)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_farming_program_is_not_farming() -> Result<()> {
+    let mut test = Tester::default();
+    test.farming_program =
+        AccountInfoWrapper::with_key(Pubkey::new_unique()).program();
+
+    assert!(test
+        .exit_farm_and_redeem_liquidity(1_000, 3_000)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidProgramId"));
+
+    Ok(())
+}
+
+struct Tester {
+    user: AccountInfoWrapper,
+    pool: AccountInfoWrapper,
+    pool_signer: AccountInfoWrapper,
+    lp_mint: AccountInfoWrapper,
+    lp_token_wallet: AccountInfoWrapper,
+    token_program: AccountInfoWrapper,
+    farmer: AccountInfoWrapper,
+    farm: AccountInfoWrapper,
+    farm_signer_pda: AccountInfoWrapper,
+    stake_vault: AccountInfoWrapper,
+    farming_program: AccountInfoWrapper,
+    vaults_wallets: Vec<AccountInfoWrapper>,
+    harvest_vault: AccountInfoWrapper,
+    harvest_wallet: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let user = AccountInfoWrapper::new().mutable().signer();
+        let pool = AccountInfoWrapper::new().owner(amm::ID).mutable();
+        let pool_signer = AccountInfoWrapper::pda(
+            amm::ID,
+            "pool_signer",
+            &[Pool::SIGNER_PDA_PREFIX, pool.key.as_ref()],
+        );
+        let lp_mint = AccountInfoWrapper::new()
+            .mutable()
+            .pack(spl::mint::new(pool_signer.key).supply(10_000))
+            .owner(token::ID);
+        let lp_token_wallet = AccountInfoWrapper::new()
+            .mutable()
+            .pack(
+                spl::token_account::new(user.key)
+                    .mint(lp_mint.key)
+                    .amount(100),
+            )
+            .owner(token::ID);
+        let token_program = AccountInfoWrapper::with_key(token::ID).program();
+        let farm = AccountInfoWrapper::new().mutable().owner(farming::ID);
+        let farmer = AccountInfoWrapper::new()
+            .mutable()
+            .owner(farming::ID)
+            .data(farming::models::Farmer {
+                authority: user.key,
+                farm: farm.key,
+                ..Default::default()
+            });
+        let farm_signer_pda = AccountInfoWrapper::new();
+        let stake_vault = AccountInfoWrapper::new()
+            .mutable()
+            .pack(
+                spl::token_account::new(farm_signer_pda.key)
+                    .mint(lp_mint.key)
+                    .amount(STAKED),
+            )
+            .owner(token::ID);
+        let farming_program =
+            AccountInfoWrapper::with_key(farming::ID).program();
+        let harvest_mint = Pubkey::new_unique();
+        let harvest_vault = AccountInfoWrapper::new()
+            .mutable()
+            .pack(
+                spl::token_account::new(farm_signer_pda.key)
+                    .mint(harvest_mint)
+                    .amount(HARVEST),
+            )
+            .owner(token::ID);
+        let harvest_wallet = AccountInfoWrapper::new()
+            .mutable()
+            .pack(spl::token_account::new(user.key).mint(harvest_mint))
+            .owner(token::ID);
+
+        let mut reserves = [Reserve::default(); consts::MAX_RESERVES];
+        let mut vaults_wallets = vec![];
+        for (index, tokens) in [10_000, 30_000].into_iter().enumerate() {
+            let mint = Pubkey::new_unique();
+            let vault = AccountInfoWrapper::new()
+                .mutable()
+                .pack(
+                    spl::token_account::new(pool_signer.key)
+                        .mint(mint)
+                        .amount(tokens),
+                )
+                .owner(token::ID);
+            let wallet = AccountInfoWrapper::new()
+                .mutable()
+                .pack(
+                    spl::token_account::new(user.key)
+                        .mint(mint)
+                        .amount(1_000_000),
+                )
+                .owner(token::ID);
+
+            reserves[index] = Reserve {
+                vault: vault.key,
+                mint,
+                tokens: TokenAmount::new(tokens),
+            };
+            vaults_wallets.push(vault);
+            vaults_wallets.push(wallet);
+        }
+
+        let pool = pool.data(Pool {
+            signer: pool_signer.key,
+            mint: lp_mint.key,
+            dimension: 2,
+            reserves,
+            ..Default::default()
+        });
+
+        Self {
+            user,
+            pool,
+            pool_signer,
+            lp_mint,
+            lp_token_wallet,
+            token_program,
+            farmer,
+            farm,
+            farm_signer_pda,
+            stake_vault,
+            farming_program,
+            vaults_wallets,
+            harvest_vault,
+            harvest_wallet,
+        }
+    }
+}
+
+impl Tester {
+    fn pool_copy(&self) -> Pool {
+        Pool::try_deserialize(&mut self.pool.data.as_slice()).unwrap()
+    }
+
+    fn min_amount_tokens(
+        &self,
+        first: u64,
+        second: u64,
+    ) -> BTreeMap<Pubkey, TokenAmount> {
+        let pool = self.pool_copy();
+        BTreeMap::from([
+            (pool.reserves[0].mint, TokenAmount::new(first)),
+            (pool.reserves[1].mint, TokenAmount::new(second)),
+        ])
+    }
+
+    /// Expects the farm to be exited, then the redemption transfers and then
+    /// the unstaked LP tokens to be burned.
+    fn expected_cpis(&self, first: u64, second: u64) -> VecDeque<ExpectedCpi> {
+        let mut cpis = VecDeque::new();
+        cpis.push_back(ExpectedCpi::ExitFarm);
+
+        let mut pool = self.pool_copy();
+        let tokens_to_redeem = match pool.redeem_tokens(
+            self.min_amount_tokens(first, second),
+            TokenAmount::new(STAKED),
+            TokenAmount::new(10_000),
+        ) {
+            Ok(tokens_to_redeem) => tokens_to_redeem,
+            // no more CPIs after a failed redemption
+            Err(_) => return cpis,
+        };
+
+        // vaults and wallets are in the same order as the reserves
+        for (reserve, vault_wallet) in
+            pool.reserves().iter().zip(self.vaults_wallets.chunks(2))
+        {
+            let tokens = tokens_to_redeem[&reserve.mint];
+            cpis.push_back(ExpectedCpi::Transfer(
+                token::spl_token::instruction::transfer(
+                    &token::ID,
+                    &vault_wallet[0].key,
+                    &vault_wallet[1].key,
+                    &self.pool_signer.key,
+                    &[],
+                    tokens.amount,
+                )
+                .unwrap(),
+                tokens,
+            ));
+        }
+        cpis.push_back(ExpectedCpi::Burn(
+            token::spl_token::instruction::burn(
+                &token::ID,
+                &self.lp_token_wallet.key,
+                &self.lp_mint.key,
+                &self.user.key,
+                &[],
+                STAKED,
+            )
+            .unwrap(),
+            TokenAmount::new(STAKED),
+        ));
+
+        cpis
+    }
+
+    fn exit_farm_and_redeem_liquidity(
+        &mut self,
+        first: u64,
+        second: u64,
+    ) -> Result<()> {
+        let state = Arc::new(Mutex::new(self.expected_cpis(first, second)));
+        let syscalls = stub::Syscalls::new(CpiValidator(Arc::clone(&state)));
+        syscalls.slot(0);
+        syscalls.set();
+
+        let min_amount_tokens = self
+            .min_amount_tokens(first, second)
+            .into_iter()
+            .map(|(mint, tokens)| TokenLimit { mint, tokens })
+            .collect();
+
+        let mut ctx = ContextWrapper::new(amm::ID)
+            .acc(&mut self.user)
+            .acc(&mut self.pool)
+            .acc(&mut self.pool_signer)
+            .acc(&mut self.lp_mint)
+            .acc(&mut self.lp_token_wallet)
+            .acc(&mut self.token_program)
+            .acc(&mut self.farmer)
+            .acc(&mut self.farm)
+            .acc(&mut self.farm_signer_pda)
+            .acc(&mut self.stake_vault)
+            .acc(&mut self.farming_program)
+            .remaining_accounts(
+                self.vaults_wallets
+                    .iter_mut()
+                    .chain([&mut self.harvest_vault, &mut self.harvest_wallet]),
+            );
+        let mut accounts = ctx.accounts()?;
+
+        exit_farm_and_redeem_liquidity(
+            ctx.build(&mut accounts),
+            min_amount_tokens,
+        )?;
+        accounts.exit(&amm::ID)?;
+
+        assert!(state.lock().unwrap().is_empty());
+
+        Ok(())
+    }
+}
+
+fn token_amount(wallet: &mut AccountInfoWrapper) -> u64 {
+    spl::token_account::from_acc_info(&wallet.to_account_info()).amount
+}
+
+#[derive(Debug)]
+enum ExpectedCpi {
+    ExitFarm,
+    Transfer(Instruction, TokenAmount),
+    Burn(Instruction, TokenAmount),
+}
+
+#[derive(Debug, Clone)]
+struct CpiValidator(Arc<Mutex<VecDeque<ExpectedCpi>>>);
+
+impl stub::ValidateCpis for CpiValidator {
+    fn validate_next_instruction(
+        &mut self,
+        ix: &Instruction,
+        accounts: &[AccountInfo],
+    ) {
+        let expected =
+            self.0.lock().unwrap().pop_front().unwrap_or_else(|| {
+                panic!("No more instructions expected, got {:#?}", ix)
+            });
+
+        match expected {
+            ExpectedCpi::ExitFarm => {
+                assert_eq!(ix.program_id, farming::ID);
+                assert_eq!(ix.data, farming::instruction::ExitFarm {}.data());
+
+                // authority, farmer, stake wallet, farm, farm signer pda,
+                // stake vault, token program, harvest vault, harvest wallet
+                assert!(ix.accounts[0].is_signer);
+                assert!(ix.accounts[0].is_writable);
+                assert_eq!(ix.accounts.len(), 9);
+
+                // unstakes everything
+                spl::token_account::transfer(
+                    &accounts[5],
+                    &accounts[2],
+                    STAKED,
+                )
+                .expect("Stake vault does not have enough tokens");
+                // claims the harvest
+                spl::token_account::transfer(
+                    &accounts[7],
+                    &accounts[8],
+                    HARVEST,
+                )
+                .expect("Harvest vault does not have enough tokens");
+                // closes the farmer
+                accounts[1].try_borrow_mut_data().unwrap().fill(0);
+            }
+            ExpectedCpi::Transfer(expected_ix, tokens) => {
+                assert_eq!(&expected_ix, ix);
+                spl::token_account::transfer(
+                    &accounts[0],
+                    &accounts[1],
+                    tokens.amount,
+                )
+                .expect("Source account does not have enough tokens");
+            }
+            ExpectedCpi::Burn(expected_ix, tokens) => {
+                assert_eq!(&expected_ix, ix);
+                spl::mint::burn_from(&accounts[0], &accounts[1], tokens.amount)
+                    .expect("Cannot burn LP tokens");
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use ::amm::amm::get_curve_invariant;
+use ::amm::prelude::*;
+use anchortest::builder::*;
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn works() -> Result<()> {
+    let mut test = Tester::default();
+    let pool_before = test.pool.data.clone();
+
+    test.get_curve_invariant()?;
+
+    assert_eq!(test.pool.data, pool_before);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_pool_is_not_owned_by_program() -> Result<()> {
+    let mut test = Tester::default();
+    test.pool = test.pool.clone().owner(Pubkey::new_unique());
+
+    assert!(test.get_curve_invariant().is_err());
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    pool: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let pool = AccountInfoWrapper::new().owner(amm::ID).data(Pool {
+            mint: Pubkey::new_unique(),
+            dimension: 2,
+            reserves: [
+                Reserve {
+                    tokens: TokenAmount::new(1_000),
+                    mint: Pubkey::new_unique(),
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve {
+                    tokens: TokenAmount::new(3_299),
+                    mint: Pubkey::new_unique(),
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve::default(),
+                Reserve::default(),
+            ],
+            ..Default::default()
+        });
+
+        Self { pool }
+    }
+}
+
+impl Tester {
+    fn get_curve_invariant(&mut self) -> Result<()> {
+        let mut ctx = self.context_wrapper();
+        let mut accounts = ctx.accounts()?;
+
+        get_curve_invariant(ctx.build(&mut accounts))?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(())
+    }
+
+    fn context_wrapper(&mut self) -> ContextWrapper {
+        ContextWrapper::new(amm::ID).acc(&mut self.pool)
+    }
+}
@@ -0,0 +1,86 @@
+use ::amm::amm::get_fee_schedule;
+use ::amm::prelude::*;
+use anchortest::{builder::*, stub};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use solana_sdk::instruction::Instruction;
+
+#[test]
+#[serial]
+fn works_without_changing_pool() -> Result<()> {
+    for slot in [99, 100] {
+        let mut test = Tester::default();
+        test.slot = slot;
+        let pool_before = test.pool.data.clone();
+
+        test.get_fee_schedule()?;
+
+        assert_eq!(test.pool.data, pool_before);
+    }
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_pool_is_not_owned_by_program() -> Result<()> {
+    let mut test = Tester::default();
+    test.pool = test.pool.clone().owner(Pubkey::new_unique());
+
+    assert!(test.get_fee_schedule().is_err());
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    slot: u64,
+    pool: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let pool = AccountInfoWrapper::new().owner(amm::ID).data(Pool {
+            mint: Pubkey::new_unique(),
+            dimension: 2,
+            swap_fee: Permillion::from_percent(1),
+            pending_swap_fee: Permillion::from_percent(2),
+            pending_swap_fee_effective_at: Slot::new(100),
+            ..Default::default()
+        });
+
+        Self { slot: 0, pool }
+    }
+}
+
+impl Tester {
+    fn get_fee_schedule(&mut self) -> Result<()> {
+        let syscalls = stub::Syscalls::new(CpiValidator);
+        syscalls.slot(self.slot);
+        syscalls.set();
+
+        let mut ctx = self.context_wrapper();
+        let mut accounts = ctx.accounts()?;
+
+        get_fee_schedule(ctx.build(&mut accounts))?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(())
+    }
+
+    fn context_wrapper(&mut self) -> ContextWrapper {
+        ContextWrapper::new(amm::ID).acc(&mut self.pool)
+    }
+}
+
+struct CpiValidator;
+
+impl stub::ValidateCpis for CpiValidator {
+    fn validate_next_instruction(
+        &mut self,
+        ix: &Instruction,
+        _accounts: &[AccountInfo],
+    ) {
+        panic!("No instructions expected, got {:#?}", ix);
+    }
+}
@@ -0,0 +1,215 @@
+#[allow(dead_code)]
+mod deposit_redeem;
+#[allow(dead_code)]
+mod return_data;
+
+use ::amm::amm::get_lp_value;
+use ::amm::prelude::*;
+use anchor_spl::token;
+use anchortest::{builder::*, spl};
+use pretty_assertions::assert_eq;
+use return_data::ReturnData;
+use serial_test::serial;
+use std::collections::BTreeMap;
+
+#[test]
+#[serial]
+fn works() -> Result<()> {
+    let mut test = Tester::default();
+    let pool_before = test.pool.data.clone();
+
+    let lp_value = test.get_lp_value()?;
+
+    assert_eq!(test.pool.data, pool_before);
+
+    let pool = Pool::try_deserialize(&mut test.pool.data.as_slice())?;
+    let reserves = pool.reserves();
+    assert_eq!(lp_value.lp_supply, TokenAmount::new(300));
+    assert_eq!(
+        lp_value.reserves,
+        vec![
+            TokenLimit {
+                mint: reserves[0].mint,
+                tokens: TokenAmount::new(1_000),
+            },
+            TokenLimit {
+                mint: reserves[1].mint,
+                tokens: TokenAmount::new(3_299),
+            },
+        ]
+    );
+    assert_eq!(
+        lp_value.tokens_per_lp_token,
+        vec![
+            TokenLimit {
+                mint: reserves[0].mint,
+                tokens: TokenAmount::new(3),
+            },
+            TokenLimit {
+                mint: reserves[1].mint,
+                tokens: TokenAmount::new(10),
+            },
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn tokens_per_lp_token_match_redeem_of_one_lp_token() -> Result<()> {
+    let (mut tester, reserves) = deposit_redeem::Tester::new_const_prod(2);
+    tester.deposit_liquidity(
+        [(reserves[0].mint, 1_000), (reserves[1].mint, 3_299)]
+            .into_iter()
+            .map(|(mint, tokens)| (mint, TokenAmount::new(tokens)))
+            .collect(),
+        &reserves,
+    )?;
+
+    let lp_value: LpValue = {
+        let return_data = ReturnData::set(Slot::new(0));
+        let mut ctx = ContextWrapper::new(amm::ID)
+            .acc(&mut tester.pool)
+            .acc(&mut tester.lp_mint);
+        let mut accounts = ctx.accounts()?;
+        get_lp_value(ctx.build(&mut accounts))?;
+
+        return_data.decode()
+    };
+
+    let wallets_before: Vec<_> = tester
+        .vaults_wallets
+        .chunks_mut(2)
+        .map(wallet_amount)
+        .collect();
+
+    tester.redeem_liquidity(
+        reserves
+            .iter()
+            .map(|r| (r.mint, TokenAmount::new(0)))
+            .collect::<BTreeMap<_, _>>(),
+        TokenAmount::new(1),
+        &reserves,
+    )?;
+
+    let redeemed: Vec<_> = reserves
+        .iter()
+        .zip(tester.vaults_wallets.chunks_mut(2))
+        .zip(wallets_before)
+        .map(|((r, vault_wallet), before)| TokenLimit {
+            mint: r.mint,
+            tokens: TokenAmount::new(wallet_amount(vault_wallet) - before),
+        })
+        .collect();
+    assert_eq!(lp_value.tokens_per_lp_token, redeemed);
+    assert_eq!(
+        lp_value.tokens_per_lp_token,
+        vec![
+            TokenLimit {
+                mint: reserves[0].mint,
+                tokens: TokenAmount::new(1),
+            },
+            TokenLimit {
+                mint: reserves[1].mint,
+                tokens: TokenAmount::new(3),
+            },
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_lp_mint_mismatches_pool_mint() -> Result<()> {
+    let mut test = Tester::default();
+    test.lp_mint = AccountInfoWrapper::new()
+        .pack(spl::mint::new(Pubkey::new_unique()).supply(300))
+        .owner(token::ID);
+
+    assert!(test
+        .get_lp_value()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_lp_mint_supply_is_zero() -> Result<()> {
+    let mut test = Tester::default();
+    test.lp_mint = test
+        .lp_mint
+        .clone()
+        .pack(spl::mint::new(Pubkey::new_unique()));
+
+    assert!(test
+        .get_lp_value()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    pool: AccountInfoWrapper,
+    lp_mint: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let lp_mint = AccountInfoWrapper::new()
+            .pack(spl::mint::new(Pubkey::new_unique()).supply(300))
+            .owner(token::ID);
+        let pool = AccountInfoWrapper::new().owner(amm::ID).data(Pool {
+            mint: lp_mint.key,
+            dimension: 2,
+            reserves: [
+                Reserve {
+                    tokens: TokenAmount::new(1_000),
+                    mint: Pubkey::new_unique(),
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve {
+                    tokens: TokenAmount::new(3_299),
+                    mint: Pubkey::new_unique(),
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve::default(),
+                Reserve::default(),
+            ],
+            ..Default::default()
+        });
+
+        Self { pool, lp_mint }
+    }
+}
+
+impl Tester {
+    fn get_lp_value(&mut self) -> Result<LpValue> {
+        let return_data = ReturnData::set(Slot::new(0));
+
+        let mut ctx = self.context_wrapper();
+        let mut accounts = ctx.accounts()?;
+
+        get_lp_value(ctx.build(&mut accounts))?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(return_data.decode())
+    }
+
+    fn context_wrapper(&mut self) -> ContextWrapper {
+        ContextWrapper::new(amm::ID)
+            .acc(&mut self.pool)
+            .acc(&mut self.lp_mint)
+    }
+}
+
+fn wallet_amount(vault_wallet: &mut [AccountInfoWrapper]) -> u64 {
+    spl::token_account::from_acc_info(&vault_wallet[1].to_account_info()).amount
+}
@@ -0,0 +1,89 @@
+use ::amm::amm::get_pool_layout;
+use ::amm::prelude::*;
+use anchortest::builder::*;
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn works_with_current_layout() -> Result<()> {
+    let mut test = Tester::default();
+    let pool_before = test.pool.data.clone();
+
+    test.get_pool_layout()?;
+
+    assert_eq!(test.pool.data, pool_before);
+    assert_eq!(test.pool.data.len(), Pool::space());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn works_with_legacy_layout() -> Result<()> {
+    let mut test = Tester::default();
+    // pools created before this release are smaller and must be migrated
+    test.pool.data.truncate(LegacyPool::space());
+
+    test.get_pool_layout()?;
+
+    assert_eq!(test.pool.data.len(), LegacyPool::space());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_account_is_not_a_pool() -> Result<()> {
+    let mut test = Tester::default();
+    test.pool = test.pool.clone().data(PoolMirror::default());
+
+    assert!(test
+        .get_pool_layout()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_pool_is_not_owned_by_program() -> Result<()> {
+    let mut test = Tester::default();
+    test.pool = test.pool.clone().owner(Pubkey::new_unique());
+
+    assert!(test.get_pool_layout().is_err());
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    pool: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let mut pool = AccountInfoWrapper::new().owner(amm::ID).data(Pool {
+            dimension: 2,
+            ..Default::default()
+        });
+        // the account is allocated with the size of the current layout,
+        // which is larger than the serialized pool
+        pool.data.resize(Pool::space(), 0);
+
+        Self { pool }
+    }
+}
+
+impl Tester {
+    fn get_pool_layout(&mut self) -> Result<()> {
+        let mut ctx = ContextWrapper::new(amm::ID).acc(&mut self.pool);
+        let mut accounts = ctx.accounts()?;
+
+        get_pool_layout(ctx.build(&mut accounts))?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(())
+    }
+}
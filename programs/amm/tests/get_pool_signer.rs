@@ -0,0 +1,111 @@
+use ::amm::amm::get_pool_signer;
+use ::amm::prelude::*;
+use anchortest::builder::*;
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn works() -> Result<()> {
+    let mut test = Tester::default();
+    let pool_before = test.pool.data.clone();
+
+    test.get_pool_signer()?;
+
+    assert_eq!(test.pool.data, pool_before);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn derives_same_signer_as_seeds_constraint() {
+    let test = Tester::default();
+
+    // endpoints which require the pool signer check it with these seeds
+    let pool_signer = AccountInfoWrapper::pda(
+        amm::ID,
+        "pool_signer",
+        &[Pool::SIGNER_PDA_PREFIX, test.pool.key.as_ref()],
+    );
+
+    let PoolSigner { signer, bump } = Pool::find_signer_address(&test.pool.key);
+    assert_eq!(signer, pool_signer.key);
+    assert_eq!(
+        Pubkey::find_program_address(
+            &[Pool::SIGNER_PDA_PREFIX, test.pool.key.as_ref()],
+            &amm::ID
+        )
+        .1,
+        bump
+    );
+}
+
+#[test]
+#[serial]
+fn fails_if_pool_stores_different_signer() -> Result<()> {
+    let mut test = Tester::default();
+    test.pool = test.pool.clone().data(Pool {
+        signer: Pubkey::new_unique(),
+        ..Default::default()
+    });
+
+    assert!(test
+        .get_pool_signer()
+        .unwrap_err()
+        .to_string()
+        .contains("InvariantViolation"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_pool_is_not_owned_by_program() -> Result<()> {
+    let mut test = Tester::default();
+    test.pool = test.pool.clone().owner(Pubkey::new_unique());
+
+    assert!(test.get_pool_signer().is_err());
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    pool: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let pool_key = Pubkey::new_unique();
+        let (signer, _) = Pubkey::find_program_address(
+            &[Pool::SIGNER_PDA_PREFIX, pool_key.as_ref()],
+            &amm::ID,
+        );
+        let pool =
+            AccountInfoWrapper::with_key(pool_key)
+                .owner(amm::ID)
+                .data(Pool {
+                    signer,
+                    ..Default::default()
+                });
+
+        Self { pool }
+    }
+}
+
+impl Tester {
+    fn get_pool_signer(&mut self) -> Result<()> {
+        let mut ctx = self.context_wrapper();
+        let mut accounts = ctx.accounts()?;
+
+        get_pool_signer(ctx.build(&mut accounts))?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(())
+    }
+
+    fn context_wrapper(&mut self) -> ContextWrapper {
+        ContextWrapper::new(amm::ID).acc(&mut self.pool)
+    }
+}
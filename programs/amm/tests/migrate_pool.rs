@@ -0,0 +1,95 @@
+use ::amm::amm::migrate_pool;
+use ::amm::prelude::*;
+use anchor_lang::system_program;
+use anchortest::builder::*;
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn fails_if_pool_is_already_migrated() -> Result<()> {
+    let mut test = Tester::default();
+    let pool_before = test.pool.data.clone();
+
+    assert!(test
+        .migrate_pool()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+    assert_eq!(test.pool.data, pool_before);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_account_is_not_a_pool() -> Result<()> {
+    let mut test = Tester::default();
+    test.pool = test.pool.clone().data(PoolMirror::default()).mutable();
+    test.pool.data.resize(LegacyPool::space(), 0);
+
+    assert!(test
+        .migrate_pool()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_pool_is_not_owned_by_program() -> Result<()> {
+    let mut test = Tester::default();
+    test.pool = test.pool.clone().owner(Pubkey::new_unique());
+
+    assert!(test.migrate_pool().is_err());
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    payer: AccountInfoWrapper,
+    pool: AccountInfoWrapper,
+    system_program: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let payer = AccountInfoWrapper::new().mutable().signer();
+        let mut pool =
+            AccountInfoWrapper::new()
+                .owner(amm::ID)
+                .mutable()
+                .data(Pool {
+                    dimension: 2,
+                    ..Default::default()
+                });
+        // the account is allocated with the size of the current layout,
+        // which is larger than the serialized pool
+        pool.data.resize(Pool::space(), 0);
+        let system_program =
+            AccountInfoWrapper::with_key(system_program::ID).program();
+
+        Self {
+            payer,
+            pool,
+            system_program,
+        }
+    }
+}
+
+impl Tester {
+    fn migrate_pool(&mut self) -> Result<()> {
+        let mut ctx = ContextWrapper::new(amm::ID)
+            .acc(&mut self.payer)
+            .acc(&mut self.pool)
+            .acc(&mut self.system_program);
+        let mut accounts = ctx.accounts()?;
+
+        migrate_pool(ctx.build(&mut accounts))?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,282 @@
+use ::amm::amm::put_curve_constraints;
+use ::amm::prelude::*;
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::system_program;
+use anchortest::{builder::*, stub};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use solana_sdk::instruction::Instruction;
+use std::sync::{Arc, Mutex};
+
+#[test]
+#[serial]
+fn creates_new_curve_constraints() -> Result<()> {
+    let mut test = Tester::default();
+    let og_state = test.clone();
+
+    assert!(test.put_curve_constraints_create(constraints()).is_ok());
+
+    let stored = CurveConstraints::try_deserialize(
+        &mut test.curve_constraints.data.as_slice(),
+    )?;
+    assert_eq!(stored, constraints());
+
+    // no other changes should have happened
+    test.curve_constraints = og_state.curve_constraints.clone();
+    assert_eq!(test, og_state);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn updates_existing_curve_constraints() -> Result<()> {
+    let mut test = Tester::default();
+    let og_state = test.clone();
+
+    assert!(test.put_curve_constraints_create(constraints()).is_ok());
+    // the stub of the system_program::create_account doesn't change the
+    // owner of the account, so we need to change it manually
+    test.curve_constraints.owner = amm::ID;
+
+    test.authority.is_writable = false;
+    let updated = CurveConstraints {
+        max_amplifier: 50,
+        ..constraints()
+    };
+    assert!(test.put_curve_constraints_update(updated.clone()).is_ok());
+
+    let stored = CurveConstraints::try_deserialize(
+        &mut test.curve_constraints.data.as_slice(),
+    )?;
+    assert_eq!(stored, updated);
+
+    // no other changes should have happened
+    test.curve_constraints = og_state.curve_constraints.clone();
+    test.authority.is_writable = og_state.authority.is_writable;
+    assert_eq!(test, og_state);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_signer_is_not_program_toll_authority() -> Result<()> {
+    let mut test = Tester::default();
+    test.authority = AccountInfoWrapper::new().mutable().signer();
+
+    assert!(test
+        .put_curve_constraints_create(constraints())
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_authority_is_not_mutable_on_creation() -> Result<()> {
+    let mut test = Tester::default();
+    test.authority.is_writable = false;
+
+    assert!(test
+        .put_curve_constraints_create(constraints())
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_bounds_are_not_ordered() -> Result<()> {
+    let mut test = Tester::default();
+
+    assert!(test
+        .put_curve_constraints_create(CurveConstraints {
+            min_amplifier: 101,
+            ..constraints()
+        })
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    assert!(test
+        .put_curve_constraints_create(CurveConstraints {
+            max_weight: 101,
+            ..constraints()
+        })
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+fn constraints() -> CurveConstraints {
+    CurveConstraints {
+        min_amplifier: 10,
+        max_amplifier: 100,
+        min_weight: 20,
+        max_weight: 80,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    authority: AccountInfoWrapper,
+    program_toll: AccountInfoWrapper,
+    curve_constraints: AccountInfoWrapper,
+    system_program: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let authority = AccountInfoWrapper::new().mutable().signer();
+        let program_toll = AccountInfoWrapper::pda(
+            amm::ID,
+            "program_toll",
+            &[ProgramToll::PDA_SEED],
+        )
+        .data(ProgramToll {
+            authority: authority.key,
+        })
+        .owner(amm::ID);
+        let curve_constraints = AccountInfoWrapper::pda(
+            amm::ID,
+            "curve_constraints",
+            &[CurveConstraints::PDA_SEED],
+        )
+        .owner(system_program::ID)
+        .mutable()
+        .size(CurveConstraints::space());
+        let system_program =
+            AccountInfoWrapper::with_key(system_program::ID).program();
+
+        Self {
+            authority,
+            program_toll,
+            curve_constraints,
+            system_program,
+        }
+    }
+}
+
+impl Tester {
+    /// Does not expect a call to [`system_program::create_account`]
+    fn put_curve_constraints_update(
+        &mut self,
+        constraints: CurveConstraints,
+    ) -> Result<()> {
+        self.put_curve_constraints(CpiValidatorState::Done, constraints)
+    }
+
+    /// Will expect a call to [`system_program::create_account`]
+    fn put_curve_constraints_create(
+        &mut self,
+        constraints: CurveConstraints,
+    ) -> Result<()> {
+        self.put_curve_constraints(
+            CpiValidatorState::CreateCurveConstraints {
+                payer: self.authority.key,
+                curve_constraints: self.curve_constraints.key,
+            },
+            constraints,
+        )
+    }
+
+    fn put_curve_constraints(
+        &mut self,
+        state: CpiValidatorState,
+        constraints: CurveConstraints,
+    ) -> Result<()> {
+        let state = self.set_syscalls(state);
+
+        let mut ctx = self.context_wrapper();
+        let mut accounts = ctx.accounts()?;
+
+        put_curve_constraints(
+            ctx.build(&mut accounts),
+            constraints.min_amplifier,
+            constraints.max_amplifier,
+            constraints.min_weight,
+            constraints.max_weight,
+        )?;
+        accounts.exit(&amm::ID)?;
+
+        assert_eq!(*state.lock().unwrap(), CpiValidatorState::Done);
+
+        Ok(())
+    }
+
+    fn context_wrapper(&mut self) -> ContextWrapper {
+        ContextWrapper::new(amm::ID)
+            .acc(&mut self.authority)
+            .acc(&mut self.program_toll)
+            .acc(&mut self.curve_constraints)
+            .acc(&mut self.system_program)
+    }
+
+    fn set_syscalls(
+        &self,
+        state: CpiValidatorState,
+    ) -> Arc<Mutex<CpiValidatorState>> {
+        let state = Arc::new(Mutex::new(state));
+
+        let syscalls = stub::Syscalls::new(CpiValidator(Arc::clone(&state)));
+        syscalls.set();
+
+        state
+    }
+}
+
+struct CpiValidator(Arc<Mutex<CpiValidatorState>>);
+#[derive(Debug, Eq, PartialEq)]
+enum CpiValidatorState {
+    CreateCurveConstraints {
+        payer: Pubkey,
+        curve_constraints: Pubkey,
+    },
+    Done,
+}
+
+impl stub::ValidateCpis for CpiValidator {
+    fn validate_next_instruction(
+        &mut self,
+        ix: &Instruction,
+        accounts: &[AccountInfo],
+    ) {
+        let mut state = self.0.lock().unwrap();
+        match *state {
+            CpiValidatorState::CreateCurveConstraints {
+                payer,
+                curve_constraints,
+            } => {
+                let rent =
+                    Rent::default().minimum_balance(CurveConstraints::space());
+                let expected_ix = system_instruction::create_account(
+                    &payer,
+                    &curve_constraints,
+                    rent,
+                    CurveConstraints::space() as u64,
+                    &amm::ID,
+                );
+                assert_eq!(&expected_ix, ix);
+
+                let limit = accounts
+                    .iter()
+                    .find(|acc| acc.key() == curve_constraints)
+                    .unwrap();
+                let mut lamports = limit.lamports.borrow_mut();
+                **lamports = rent;
+
+                *state = CpiValidatorState::Done;
+            }
+            CpiValidatorState::Done => {
+                panic!("No more instructions expected, got {:#?}", ix);
+            }
+        }
+    }
+}
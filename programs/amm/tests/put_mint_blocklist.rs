@@ -0,0 +1,253 @@
+use ::amm::amm::put_mint_blocklist;
+use ::amm::prelude::*;
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::system_program;
+use anchortest::{builder::*, stub};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use solana_sdk::instruction::Instruction;
+use std::sync::{Arc, Mutex};
+
+#[test]
+#[serial]
+fn creates_new_mint_blocklist() -> Result<()> {
+    let mints = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+
+    let mut test = Tester::default();
+    let og_state = test.clone();
+
+    assert!(test.put_mint_blocklist_create(mints.clone()).is_ok());
+
+    let blocklist = MintBlocklist::try_deserialize(
+        &mut test.mint_blocklist.data.as_slice(),
+    )?;
+    assert_eq!(blocklist, MintBlocklist { mints });
+
+    // no other changes should have happened
+    test.mint_blocklist = og_state.mint_blocklist.clone();
+    assert_eq!(test, og_state);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn updates_existing_mint_blocklist() -> Result<()> {
+    let mut test = Tester::default();
+    let og_state = test.clone();
+
+    assert!(test
+        .put_mint_blocklist_create(vec![Pubkey::new_unique()])
+        .is_ok());
+    // the stub of the system_program::create_account doesn't change the
+    // owner of the account, so we need to change it manually
+    test.mint_blocklist.owner = amm::ID;
+
+    let mints = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+    test.authority.is_writable = false;
+    assert!(test.put_mint_blocklist_update(mints.clone()).is_ok());
+
+    let blocklist = MintBlocklist::try_deserialize(
+        &mut test.mint_blocklist.data.as_slice(),
+    )?;
+    assert_eq!(blocklist, MintBlocklist { mints });
+
+    // no other changes should have happened
+    test.mint_blocklist = og_state.mint_blocklist.clone();
+    test.authority.is_writable = og_state.authority.is_writable;
+    assert_eq!(test, og_state);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_too_many_mints() -> Result<()> {
+    let mut test = Tester::default();
+    let mints = (0..=consts::MAX_BLOCKED_MINTS)
+        .map(|_| Pubkey::new_unique())
+        .collect();
+
+    assert!(test
+        .put_mint_blocklist_create(mints)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_signer_is_not_program_toll_authority() -> Result<()> {
+    let mut test = Tester::default();
+    test.authority = AccountInfoWrapper::new().mutable().signer();
+
+    assert!(test
+        .put_mint_blocklist_create(vec![Pubkey::new_unique()])
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_authority_is_not_mutable_on_creation() -> Result<()> {
+    let mut test = Tester::default();
+    test.authority.is_writable = false;
+
+    assert!(test
+        .put_mint_blocklist_create(vec![Pubkey::new_unique()])
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    authority: AccountInfoWrapper,
+    program_toll: AccountInfoWrapper,
+    mint_blocklist: AccountInfoWrapper,
+    system_program: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let authority = AccountInfoWrapper::new().mutable().signer();
+        let program_toll = AccountInfoWrapper::pda(
+            amm::ID,
+            "program_toll",
+            &[ProgramToll::PDA_SEED],
+        )
+        .data(ProgramToll {
+            authority: authority.key,
+        })
+        .owner(amm::ID);
+        let mint_blocklist = AccountInfoWrapper::pda(
+            amm::ID,
+            "mint_blocklist",
+            &[MintBlocklist::PDA_SEED],
+        )
+        .owner(system_program::ID)
+        .mutable()
+        .size(MintBlocklist::space());
+        let system_program =
+            AccountInfoWrapper::with_key(system_program::ID).program();
+
+        Self {
+            authority,
+            program_toll,
+            mint_blocklist,
+            system_program,
+        }
+    }
+}
+
+impl Tester {
+    /// Does not expect a call to [`system_program::create_account`]
+    fn put_mint_blocklist_update(&mut self, mints: Vec<Pubkey>) -> Result<()> {
+        self.put_mint_blocklist(CpiValidatorState::Done, mints)
+    }
+
+    /// Will expect a call to [`system_program::create_account`]
+    fn put_mint_blocklist_create(&mut self, mints: Vec<Pubkey>) -> Result<()> {
+        self.put_mint_blocklist(
+            CpiValidatorState::CreateMintBlocklist {
+                payer: self.authority.key,
+                mint_blocklist: self.mint_blocklist.key,
+            },
+            mints,
+        )
+    }
+
+    fn put_mint_blocklist(
+        &mut self,
+        state: CpiValidatorState,
+        mints: Vec<Pubkey>,
+    ) -> Result<()> {
+        let state = self.set_syscalls(state);
+
+        let mut ctx = self.context_wrapper();
+        let mut accounts = ctx.accounts()?;
+
+        put_mint_blocklist(ctx.build(&mut accounts), mints)?;
+        accounts.exit(&amm::ID)?;
+
+        assert_eq!(*state.lock().unwrap(), CpiValidatorState::Done);
+
+        Ok(())
+    }
+
+    fn context_wrapper(&mut self) -> ContextWrapper {
+        ContextWrapper::new(amm::ID)
+            .acc(&mut self.authority)
+            .acc(&mut self.program_toll)
+            .acc(&mut self.mint_blocklist)
+            .acc(&mut self.system_program)
+    }
+
+    fn set_syscalls(
+        &self,
+        state: CpiValidatorState,
+    ) -> Arc<Mutex<CpiValidatorState>> {
+        let state = Arc::new(Mutex::new(state));
+
+        let syscalls = stub::Syscalls::new(CpiValidator(Arc::clone(&state)));
+        syscalls.set();
+
+        state
+    }
+}
+
+struct CpiValidator(Arc<Mutex<CpiValidatorState>>);
+#[derive(Debug, Eq, PartialEq)]
+enum CpiValidatorState {
+    CreateMintBlocklist {
+        payer: Pubkey,
+        mint_blocklist: Pubkey,
+    },
+    Done,
+}
+
+impl stub::ValidateCpis for CpiValidator {
+    fn validate_next_instruction(
+        &mut self,
+        ix: &Instruction,
+        accounts: &[AccountInfo],
+    ) {
+        let mut state = self.0.lock().unwrap();
+        match *state {
+            CpiValidatorState::CreateMintBlocklist {
+                payer,
+                mint_blocklist,
+            } => {
+                let rent =
+                    Rent::default().minimum_balance(MintBlocklist::space());
+                let expected_ix = system_instruction::create_account(
+                    &payer,
+                    &mint_blocklist,
+                    rent,
+                    MintBlocklist::space() as u64,
+                    &amm::ID,
+                );
+                assert_eq!(&expected_ix, ix);
+
+                let blocklist = accounts
+                    .iter()
+                    .find(|acc| acc.key() == mint_blocklist)
+                    .unwrap();
+                let mut lamports = blocklist.lamports.borrow_mut();
+                **lamports = rent;
+
+                *state = CpiValidatorState::Done;
+            }
+            CpiValidatorState::Done => {
+                panic!("No more instructions expected, got {:#?}", ix);
+            }
+        }
+    }
+}
@@ -0,0 +1,399 @@
+use ::amm::amm::put_pool_fee_rebate;
+use ::amm::prelude::*;
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::system_program;
+use anchortest::{builder::*, stub};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use solana_sdk::instruction::Instruction;
+use std::sync::{Arc, Mutex};
+
+#[test]
+#[serial]
+fn creates_new_fee_rebate() -> Result<()> {
+    let fee_rebate = FeeRebate {
+        share: Permillion::from_percent(50),
+        starts_at: Slot::new(100),
+        ends_at: Slot::new(500),
+    };
+
+    let mut test = Tester::default();
+    let og_state = test.clone();
+
+    assert!(test.put_pool_fee_rebate_create(&fee_rebate).is_ok());
+
+    assert_eq!(
+        FeeRebate::try_deserialize(&mut test.fee_rebate.data.as_slice())?,
+        fee_rebate
+    );
+
+    // no other changes should have happened
+    test.fee_rebate = og_state.fee_rebate.clone();
+    assert_eq!(test, og_state);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn creates_new_fee_rebate_if_pda_is_prefunded() -> Result<()> {
+    let fee_rebate = FeeRebate {
+        share: Permillion::from_percent(50),
+        starts_at: Slot::new(100),
+        ends_at: Slot::new(500),
+    };
+
+    let mut test = Tester::default();
+    // anyone can transfer lamports to the PDA before it's created
+    test.fee_rebate.lamports = 1_000;
+
+    assert!(test
+        .put_pool_fee_rebate_create_prefunded(&fee_rebate)
+        .is_ok());
+
+    assert_eq!(
+        FeeRebate::try_deserialize(&mut test.fee_rebate.data.as_slice())?,
+        fee_rebate
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn updates_existing_fee_rebate() -> Result<()> {
+    let mut test = Tester::default();
+
+    assert!(test
+        .put_pool_fee_rebate_create(&FeeRebate {
+            share: Permillion::from_percent(50),
+            starts_at: Slot::new(100),
+            ends_at: Slot::new(500),
+        })
+        .is_ok());
+    // the stub of the system_program::create_account doesn't change the
+    // owner of the account, so we need to change it manually
+    test.fee_rebate.owner = amm::ID;
+
+    let fee_rebate = FeeRebate {
+        share: Permillion::from_percent(0),
+        starts_at: Slot::new(0),
+        ends_at: Slot::new(1),
+    };
+    test.admin.is_writable = false;
+    assert!(test.put_pool_fee_rebate_update(&fee_rebate).is_ok());
+
+    assert_eq!(
+        FeeRebate::try_deserialize(&mut test.fee_rebate.data.as_slice())?,
+        fee_rebate
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_signer_is_not_pool_admin() -> Result<()> {
+    let mut test = Tester::default();
+    test.admin = AccountInfoWrapper::new().mutable().signer();
+
+    assert!(test
+        .put_pool_fee_rebate_create(&FeeRebate {
+            share: Permillion::from_percent(50),
+            starts_at: Slot::new(100),
+            ends_at: Slot::new(500),
+        })
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_rebate_is_100_percent_or_more() -> Result<()> {
+    for percent in [100, 101] {
+        let mut test = Tester::default();
+        assert!(test
+            .put_pool_fee_rebate_create(&FeeRebate {
+                share: Permillion::from_percent(percent),
+                starts_at: Slot::new(100),
+                ends_at: Slot::new(500),
+            })
+            .unwrap_err()
+            .to_string()
+            .contains("InvalidArg"));
+    }
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_rebate_ends_before_it_starts() -> Result<()> {
+    let mut test = Tester::default();
+
+    assert!(test
+        .put_pool_fee_rebate_create(&FeeRebate {
+            share: Permillion::from_percent(50),
+            starts_at: Slot::new(500),
+            ends_at: Slot::new(100),
+        })
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_rebate_ends_in_the_past() -> Result<()> {
+    let mut test = Tester::default().slot(500);
+
+    assert!(test
+        .put_pool_fee_rebate_create(&FeeRebate {
+            share: Permillion::from_percent(50),
+            starts_at: Slot::new(100),
+            ends_at: Slot::new(500),
+        })
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_admin_is_not_mutable_on_creation() -> Result<()> {
+    let mut test = Tester::default();
+    test.admin.is_writable = false;
+
+    assert!(test
+        .put_pool_fee_rebate_create(&FeeRebate {
+            share: Permillion::from_percent(50),
+            starts_at: Slot::new(100),
+            ends_at: Slot::new(500),
+        })
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    slot: u64,
+    admin: AccountInfoWrapper,
+    pool: AccountInfoWrapper,
+    fee_rebate: AccountInfoWrapper,
+    system_program: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let admin = AccountInfoWrapper::new().mutable().signer();
+        let pool = AccountInfoWrapper::new().owner(amm::ID).data(Pool {
+            admin: admin.key,
+            ..Default::default()
+        });
+        let fee_rebate = AccountInfoWrapper::pda(
+            amm::ID,
+            "fee_rebate",
+            &[FeeRebate::PDA_PREFIX, pool.key.as_ref()],
+        )
+        .owner(system_program::ID)
+        .mutable()
+        .size(FeeRebate::space());
+        let system_program =
+            AccountInfoWrapper::with_key(system_program::ID).program();
+
+        Self {
+            slot: 0,
+            admin,
+            pool,
+            fee_rebate,
+            system_program,
+        }
+    }
+}
+
+impl Tester {
+    fn slot(mut self, slot: u64) -> Self {
+        self.slot = slot;
+        self
+    }
+
+    /// Does not expect a call to [`system_program::create_account`]
+    fn put_pool_fee_rebate_update(
+        &mut self,
+        fee_rebate: &FeeRebate,
+    ) -> Result<()> {
+        self.put_pool_fee_rebate(CpiValidatorState::Done, fee_rebate)
+    }
+
+    /// Will expect a call to [`system_program::create_account`]
+    fn put_pool_fee_rebate_create(
+        &mut self,
+        fee_rebate: &FeeRebate,
+    ) -> Result<()> {
+        self.put_pool_fee_rebate(
+            CpiValidatorState::CreateFeeRebate {
+                payer: self.admin.key,
+                fee_rebate: self.fee_rebate.key,
+            },
+            fee_rebate,
+        )
+    }
+
+    /// Will expect calls to [`system_program::transfer`],
+    /// [`system_program::allocate`] and [`system_program::assign`]
+    fn put_pool_fee_rebate_create_prefunded(
+        &mut self,
+        fee_rebate: &FeeRebate,
+    ) -> Result<()> {
+        let rent = Rent::default().minimum_balance(FeeRebate::space());
+        self.put_pool_fee_rebate(
+            CpiValidatorState::TopUpFeeRebate {
+                payer: self.admin.key,
+                fee_rebate: self.fee_rebate.key,
+                lamports: rent - self.fee_rebate.lamports,
+            },
+            fee_rebate,
+        )
+    }
+
+    fn put_pool_fee_rebate(
+        &mut self,
+        state: CpiValidatorState,
+        fee_rebate: &FeeRebate,
+    ) -> Result<()> {
+        let state = self.set_syscalls(state);
+
+        let mut ctx = self.context_wrapper();
+        let mut accounts = ctx.accounts()?;
+
+        put_pool_fee_rebate(
+            ctx.build(&mut accounts),
+            fee_rebate.share,
+            fee_rebate.starts_at,
+            fee_rebate.ends_at,
+        )?;
+        accounts.exit(&amm::ID)?;
+
+        assert_eq!(*state.lock().unwrap(), CpiValidatorState::Done);
+
+        Ok(())
+    }
+
+    fn context_wrapper(&mut self) -> ContextWrapper {
+        ContextWrapper::new(amm::ID)
+            .acc(&mut self.admin)
+            .acc(&mut self.pool)
+            .acc(&mut self.fee_rebate)
+            .acc(&mut self.system_program)
+    }
+
+    fn set_syscalls(
+        &self,
+        state: CpiValidatorState,
+    ) -> Arc<Mutex<CpiValidatorState>> {
+        let state = Arc::new(Mutex::new(state));
+
+        let syscalls = stub::Syscalls::new(CpiValidator(Arc::clone(&state)));
+        syscalls.slot(self.slot);
+        syscalls.set();
+
+        state
+    }
+}
+
+struct CpiValidator(Arc<Mutex<CpiValidatorState>>);
+#[derive(Debug, Eq, PartialEq)]
+enum CpiValidatorState {
+    CreateFeeRebate {
+        payer: Pubkey,
+        fee_rebate: Pubkey,
+    },
+    TopUpFeeRebate {
+        payer: Pubkey,
+        fee_rebate: Pubkey,
+        lamports: u64,
+    },
+    AllocateFeeRebate(Pubkey),
+    AssignFeeRebate(Pubkey),
+    Done,
+}
+
+impl stub::ValidateCpis for CpiValidator {
+    fn validate_next_instruction(
+        &mut self,
+        ix: &Instruction,
+        accounts: &[AccountInfo],
+    ) {
+        let mut state = self.0.lock().unwrap();
+        match *state {
+            CpiValidatorState::CreateFeeRebate { payer, fee_rebate } => {
+                let rent = Rent::default().minimum_balance(FeeRebate::space());
+                let expected_ix = system_instruction::create_account(
+                    &payer,
+                    &fee_rebate,
+                    rent,
+                    FeeRebate::space() as u64,
+                    &amm::ID,
+                );
+                assert_eq!(&expected_ix, ix);
+
+                let fee_rebate = accounts
+                    .iter()
+                    .find(|acc| acc.key() == fee_rebate)
+                    .unwrap();
+                let mut lamports = fee_rebate.lamports.borrow_mut();
+                **lamports = rent;
+
+                *state = CpiValidatorState::Done;
+            }
+            CpiValidatorState::TopUpFeeRebate {
+                payer,
+                fee_rebate,
+                lamports,
+            } => {
+                let expected_ix =
+                    system_instruction::transfer(&payer, &fee_rebate, lamports);
+                assert_eq!(&expected_ix, ix);
+
+                let fee_rebate_info = accounts
+                    .iter()
+                    .find(|acc| acc.key() == fee_rebate)
+                    .unwrap();
+                let mut fee_rebate_lamports =
+                    fee_rebate_info.lamports.borrow_mut();
+                **fee_rebate_lamports += lamports;
+
+                *state = CpiValidatorState::AllocateFeeRebate(fee_rebate);
+            }
+            CpiValidatorState::AllocateFeeRebate(fee_rebate) => {
+                let expected_ix = system_instruction::allocate(
+                    &fee_rebate,
+                    FeeRebate::space() as u64,
+                );
+                assert_eq!(&expected_ix, ix);
+
+                *state = CpiValidatorState::AssignFeeRebate(fee_rebate);
+            }
+            CpiValidatorState::AssignFeeRebate(fee_rebate) => {
+                let expected_ix =
+                    system_instruction::assign(&fee_rebate, &amm::ID);
+                assert_eq!(&expected_ix, ix);
+
+                *state = CpiValidatorState::Done;
+            }
+            CpiValidatorState::Done => {
+                panic!("No more instructions expected, got {:#?}", ix);
+            }
+        }
+    }
+}
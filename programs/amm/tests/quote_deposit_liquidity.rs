@@ -0,0 +1,174 @@
+#[allow(dead_code)]
+mod deposit_redeem;
+#[allow(dead_code)]
+mod return_data;
+
+use ::amm::amm::quote_deposit_liquidity;
+use ::amm::prelude::*;
+use anchor_spl::token;
+pub use anchor_spl::token::spl_token::state::{Account as TokenAccount, Mint};
+use anchortest::{builder::*, spl};
+use deposit_redeem::*;
+use pretty_assertions::assert_eq;
+use return_data::ReturnData;
+use serial_test::serial;
+use solana_sdk::program_pack::Pack;
+use std::collections::BTreeMap;
+
+#[test]
+#[serial]
+fn quotes_same_as_deposit_into_const_prod() -> Result<()> {
+    let (mut tester, reserves) = Tester::new_const_prod(2);
+    tester.deposit_liquidity(
+        max_amount_tokens(&reserves, &[1_000, 3_299]),
+        &reserves,
+    )?;
+
+    assert_quote_matches_deposit(
+        &mut tester,
+        &reserves,
+        max_amount_tokens(&reserves, &[101, 1_000]),
+    )
+}
+
+#[test]
+#[serial]
+fn quotes_same_as_deposit_into_stable_curve() -> Result<()> {
+    let (mut tester, reserves) =
+        Tester::new_stable_curve(3, 10, Decimal::default());
+    tester.deposit_liquidity(
+        max_amount_tokens(&reserves, &[1_000, 2_000, 3_001]),
+        &reserves,
+    )?;
+
+    assert_quote_matches_deposit(
+        &mut tester,
+        &reserves,
+        max_amount_tokens(&reserves, &[333, 1_000, 1_000]),
+    )
+}
+
+#[test]
+#[serial]
+fn quotes_same_as_initial_deposit() -> Result<()> {
+    let (mut tester, reserves) = Tester::new_const_prod(2);
+
+    assert_quote_matches_deposit(
+        &mut tester,
+        &reserves,
+        max_amount_tokens(&reserves, &[10, 20]),
+    )
+}
+
+#[test]
+#[serial]
+fn fails_if_lp_mint_mismatches_pool_mint() -> Result<()> {
+    let (mut tester, reserves) = Tester::new_const_prod(2);
+    tester.lp_mint = AccountInfoWrapper::new()
+        .pack(spl::mint::new(Pubkey::new_unique()))
+        .owner(token::ID);
+
+    assert!(quote(&mut tester, max_amount_tokens(&reserves, &[10, 10]))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_max_amount_tokens_do_not_match_reserves() -> Result<()> {
+    let (mut tester, reserves) = Tester::new_const_prod(2);
+    let max_amount_tokens = [
+        (reserves[0].mint, TokenAmount::new(10)),
+        (Pubkey::new_unique(), TokenAmount::new(10)),
+    ]
+    .into_iter()
+    .collect();
+
+    assert!(quote(&mut tester, max_amount_tokens)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+fn assert_quote_matches_deposit(
+    tester: &mut Tester,
+    reserves: &[Reserve],
+    max_amount_tokens: BTreeMap<Pubkey, TokenAmount>,
+) -> Result<()> {
+    let pool_before = tester.pool.data.clone();
+    let quote = quote(tester, max_amount_tokens.clone())?;
+    assert_eq!(tester.pool.data, pool_before);
+
+    let lp_tokens_before = token_amount(&tester.lp_token_wallet);
+    let vaults_before: Vec<_> = tester
+        .vaults_wallets
+        .iter()
+        .step_by(2)
+        .map(token_amount)
+        .collect();
+
+    tester.deposit_liquidity(max_amount_tokens, reserves)?;
+    let deposited: LiquidityResult = return_data::decode_last();
+    assert_eq!(quote, deposited);
+
+    assert_eq!(
+        quote.lp_tokens_minted.amount,
+        token_amount(&tester.lp_token_wallet) - lp_tokens_before
+    );
+    assert_eq!(quote.lp_tokens_burned, TokenAmount::new(0));
+    for (index, vault) in tester.vaults_wallets.iter().step_by(2).enumerate() {
+        let deposited = quote
+            .tokens
+            .iter()
+            .find(|t| t.mint == reserves[index].mint)
+            .unwrap();
+        assert_eq!(
+            deposited.tokens.amount,
+            token_amount(vault) - vaults_before[index]
+        );
+    }
+
+    Ok(())
+}
+
+fn quote(
+    tester: &mut Tester,
+    max_amount_tokens: BTreeMap<Pubkey, TokenAmount>,
+) -> Result<LiquidityResult> {
+    let return_data = ReturnData::set(Slot::new(0));
+
+    let mut ctx = ContextWrapper::new(amm::ID)
+        .acc(&mut tester.pool)
+        .acc(&mut tester.lp_mint);
+    let mut accounts = ctx.accounts()?;
+
+    let max_amount_tokens = max_amount_tokens
+        .into_iter()
+        .map(|(mint, tokens)| TokenLimit { mint, tokens })
+        .collect();
+
+    quote_deposit_liquidity(ctx.build(&mut accounts), max_amount_tokens)?;
+    accounts.exit(&amm::ID)?;
+
+    Ok(return_data.decode())
+}
+
+fn token_amount(acc: &AccountInfoWrapper) -> u64 {
+    TokenAccount::unpack(&acc.data).unwrap().amount
+}
+
+fn max_amount_tokens(
+    reserves: &[Reserve],
+    amounts: &[u64],
+) -> BTreeMap<Pubkey, TokenAmount> {
+    reserves
+        .iter()
+        .zip(amounts)
+        .map(|(r, amount)| (r.mint, TokenAmount::new(*amount)))
+        .collect()
+}
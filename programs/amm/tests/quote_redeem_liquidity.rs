@@ -0,0 +1,171 @@
+#[allow(dead_code)]
+mod deposit_redeem;
+#[allow(dead_code)]
+mod return_data;
+
+use ::amm::amm::quote_redeem_liquidity;
+use ::amm::prelude::*;
+use anchor_spl::token;
+pub use anchor_spl::token::spl_token::state::{Account as TokenAccount, Mint};
+use anchortest::{builder::*, spl};
+use deposit_redeem::*;
+use pretty_assertions::assert_eq;
+use return_data::ReturnData;
+use serial_test::serial;
+use solana_sdk::program_pack::Pack;
+use std::collections::BTreeMap;
+
+#[test]
+#[serial]
+fn quotes_same_as_redeem_from_const_prod() -> Result<()> {
+    let (mut tester, reserves) = Tester::new_const_prod(2);
+    tester.deposit_liquidity(
+        max_amount_tokens(&reserves, &[1_000, 3_299]),
+        &reserves,
+    )?;
+
+    assert_quote_matches_redeem(&mut tester, &reserves, TokenAmount::new(333))
+}
+
+#[test]
+#[serial]
+fn quotes_same_as_redeem_from_stable_curve() -> Result<()> {
+    let (mut tester, reserves) =
+        Tester::new_stable_curve(3, 10, Decimal::default());
+    tester.deposit_liquidity(
+        max_amount_tokens(&reserves, &[1_000, 2_000, 3_001]),
+        &reserves,
+    )?;
+
+    assert_quote_matches_redeem(&mut tester, &reserves, TokenAmount::new(7))
+}
+
+#[test]
+#[serial]
+fn fails_if_lp_mint_mismatches_pool_mint() -> Result<()> {
+    let (mut tester, reserves) = Tester::new_const_prod(2);
+    tester.deposit_liquidity(
+        max_amount_tokens(&reserves, &[100, 100]),
+        &reserves,
+    )?;
+    tester.lp_mint = AccountInfoWrapper::new()
+        .pack(spl::mint::new(Pubkey::new_unique()).supply(100))
+        .owner(token::ID);
+
+    assert!(quote(&mut tester, TokenAmount::new(10))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_burning_zero() -> Result<()> {
+    let (mut tester, reserves) = Tester::new_const_prod(2);
+    tester.deposit_liquidity(
+        max_amount_tokens(&reserves, &[100, 100]),
+        &reserves,
+    )?;
+
+    assert!(quote(&mut tester, TokenAmount::new(0))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_burning_more_than_lp_supply() -> Result<()> {
+    let (mut tester, reserves) = Tester::new_const_prod(2);
+    tester.deposit_liquidity(
+        max_amount_tokens(&reserves, &[100, 100]),
+        &reserves,
+    )?;
+
+    assert!(quote(&mut tester, TokenAmount::new(101))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+fn assert_quote_matches_redeem(
+    tester: &mut Tester,
+    reserves: &[Reserve],
+    lp_tokens_to_burn: TokenAmount,
+) -> Result<()> {
+    let pool_before = tester.pool.data.clone();
+    let quote = quote(tester, lp_tokens_to_burn)?;
+    assert_eq!(tester.pool.data, pool_before);
+
+    let vaults_before: Vec<_> = tester
+        .vaults_wallets
+        .iter()
+        .step_by(2)
+        .map(token_amount)
+        .collect();
+
+    tester.redeem_liquidity(
+        reserves
+            .iter()
+            .map(|r| (r.mint, TokenAmount::new(0)))
+            .collect(),
+        lp_tokens_to_burn,
+        reserves,
+    )?;
+    let redeemed: LiquidityResult = return_data::decode_last();
+    assert_eq!(quote, redeemed);
+
+    assert_eq!(quote.lp_tokens_burned, lp_tokens_to_burn);
+    assert_eq!(quote.lp_tokens_minted, TokenAmount::new(0));
+    for (index, vault) in tester.vaults_wallets.iter().step_by(2).enumerate() {
+        let redeemed = quote
+            .tokens
+            .iter()
+            .find(|t| t.mint == reserves[index].mint)
+            .unwrap();
+        assert_eq!(
+            redeemed.tokens.amount,
+            vaults_before[index] - token_amount(vault)
+        );
+    }
+
+    Ok(())
+}
+
+fn quote(
+    tester: &mut Tester,
+    lp_tokens_to_burn: TokenAmount,
+) -> Result<LiquidityResult> {
+    let return_data = ReturnData::set(Slot::new(0));
+
+    let mut ctx = ContextWrapper::new(amm::ID)
+        .acc(&mut tester.pool)
+        .acc(&mut tester.lp_mint);
+    let mut accounts = ctx.accounts()?;
+
+    quote_redeem_liquidity(ctx.build(&mut accounts), lp_tokens_to_burn)?;
+    accounts.exit(&amm::ID)?;
+
+    Ok(return_data.decode())
+}
+
+fn token_amount(acc: &AccountInfoWrapper) -> u64 {
+    TokenAccount::unpack(&acc.data).unwrap().amount
+}
+
+fn max_amount_tokens(
+    reserves: &[Reserve],
+    amounts: &[u64],
+) -> BTreeMap<Pubkey, TokenAmount> {
+    reserves
+        .iter()
+        .zip(amounts)
+        .map(|(r, amount)| (r.mint, TokenAmount::new(*amount)))
+        .collect()
+}
@@ -0,0 +1,174 @@
+#[allow(dead_code)]
+mod deposit_redeem;
+#[allow(dead_code)]
+mod return_data;
+
+use ::amm::amm::quote_single_deposit;
+use ::amm::prelude::*;
+pub use anchor_spl::token::spl_token::state::{Account as TokenAccount, Mint};
+use anchortest::builder::*;
+use deposit_redeem::*;
+use pretty_assertions::assert_eq;
+use return_data::ReturnData;
+use serial_test::serial;
+use solana_sdk::program_pack::Pack;
+use std::collections::BTreeMap;
+
+#[test]
+#[serial]
+fn quotes_same_as_deposit_into_const_prod() -> Result<()> {
+    let (mut tester, reserves) = Tester::new_const_prod(2);
+    tester.deposit_liquidity(
+        max_amount_tokens(&reserves, &[1_000, 3_299]),
+        &reserves,
+    )?;
+
+    assert_quote_matches_deposit(
+        &mut tester,
+        &reserves,
+        TokenLimit {
+            mint: reserves[0].mint,
+            tokens: TokenAmount::new(101),
+        },
+    )?;
+    assert_quote_matches_deposit(
+        &mut tester,
+        &reserves,
+        TokenLimit {
+            mint: reserves[1].mint,
+            tokens: TokenAmount::new(1_000),
+        },
+    )
+}
+
+#[test]
+#[serial]
+fn quotes_same_as_deposit_into_stable_curve() -> Result<()> {
+    let (mut tester, reserves) =
+        Tester::new_stable_curve(3, 10, Decimal::default());
+    tester.deposit_liquidity(
+        max_amount_tokens(&reserves, &[1_000, 2_000, 3_001]),
+        &reserves,
+    )?;
+
+    assert_quote_matches_deposit(
+        &mut tester,
+        &reserves,
+        TokenLimit {
+            mint: reserves[2].mint,
+            tokens: TokenAmount::new(333),
+        },
+    )
+}
+
+#[test]
+#[serial]
+fn fails_if_pool_has_no_liquidity() -> Result<()> {
+    let (mut tester, reserves) = Tester::new_const_prod(2);
+
+    assert!(quote(
+        &mut tester,
+        TokenLimit {
+            mint: reserves[0].mint,
+            tokens: TokenAmount::new(10),
+        }
+    )
+    .unwrap_err()
+    .to_string()
+    .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_mint_is_not_of_pool() -> Result<()> {
+    let (mut tester, reserves) = Tester::new_const_prod(2);
+    tester.deposit_liquidity(
+        max_amount_tokens(&reserves, &[1_000, 3_299]),
+        &reserves,
+    )?;
+
+    assert!(quote(
+        &mut tester,
+        TokenLimit {
+            mint: Pubkey::new_unique(),
+            tokens: TokenAmount::new(10),
+        }
+    )
+    .unwrap_err()
+    .to_string()
+    .contains("InvalidArg"));
+
+    Ok(())
+}
+
+fn assert_quote_matches_deposit(
+    tester: &mut Tester,
+    reserves: &[Reserve],
+    tokens: TokenLimit,
+) -> Result<()> {
+    let pool_before = tester.pool.data.clone();
+    let quote = quote(tester, tokens)?;
+    assert_eq!(tester.pool.data, pool_before);
+    assert!(quote.lp_tokens_minted.amount > 0);
+
+    // the quoted amounts are used as the max amounts of the deposit
+    let max_amount_tokens: BTreeMap<_, _> =
+        quote.tokens.iter().map(|t| (t.mint, t.tokens)).collect();
+    assert!(max_amount_tokens[&tokens.mint] <= tokens.tokens);
+
+    let lp_tokens_before = token_amount(&tester.lp_token_wallet);
+    let vaults_before: Vec<_> = tester
+        .vaults_wallets
+        .iter()
+        .step_by(2)
+        .map(token_amount)
+        .collect();
+
+    tester.deposit_liquidity(max_amount_tokens.clone(), reserves)?;
+    let deposited: LiquidityResult = return_data::decode_last();
+    assert_eq!(quote, deposited);
+
+    assert_eq!(
+        quote.lp_tokens_minted.amount,
+        token_amount(&tester.lp_token_wallet) - lp_tokens_before
+    );
+    for (index, vault) in tester.vaults_wallets.iter().step_by(2).enumerate() {
+        assert_eq!(
+            max_amount_tokens[&reserves[index].mint].amount,
+            token_amount(vault) - vaults_before[index]
+        );
+    }
+
+    Ok(())
+}
+
+fn quote(tester: &mut Tester, tokens: TokenLimit) -> Result<LiquidityResult> {
+    let return_data = ReturnData::set(Slot::new(0));
+
+    let mut ctx = ContextWrapper::new(amm::ID)
+        .acc(&mut tester.pool)
+        .acc(&mut tester.lp_mint);
+    let mut accounts = ctx.accounts()?;
+
+    quote_single_deposit(ctx.build(&mut accounts), tokens)?;
+    accounts.exit(&amm::ID)?;
+
+    Ok(return_data.decode())
+}
+
+fn token_amount(acc: &AccountInfoWrapper) -> u64 {
+    TokenAccount::unpack(&acc.data).unwrap().amount
+}
+
+fn max_amount_tokens(
+    reserves: &[Reserve],
+    amounts: &[u64],
+) -> BTreeMap<Pubkey, TokenAmount> {
+    reserves
+        .iter()
+        .zip(amounts)
+        .map(|(r, amount)| (r.mint, TokenAmount::new(*amount)))
+        .collect()
+}
@@ -0,0 +1,249 @@
+#[allow(dead_code)]
+mod return_data;
+
+use ::amm::amm::quote_swap;
+use ::amm::endpoints::SwapQuote;
+use ::amm::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token;
+use anchortest::{builder::*, spl};
+use pretty_assertions::assert_eq;
+use return_data::ReturnData;
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn quotes_without_changing_pool() -> Result<()> {
+    let mut test = Tester::default();
+    let pool_before = test.pool.data.clone();
+
+    let quote = test.quote_swap(TokenAmount::new(10_000))?;
+
+    assert_eq!(test.pool.data, pool_before);
+    assert_eq!(
+        quote,
+        SwapQuote {
+            bought: TokenAmount::new(6_254),
+            swap_fee: TokenAmount::new(900),
+            fee_rebate: TokenAmount::new(0),
+            program_toll: TokenAmount::new(50),
+            referral: TokenAmount::new(0),
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn quotes_with_referral_config() -> Result<()> {
+    let mut test = Tester::default();
+    test.referral_config =
+        test.referral_config
+            .clone()
+            .owner(amm::ID)
+            .data(ReferralConfig {
+                share: Permillion::from_percent(40),
+                default_recipient: Pubkey::new_unique(),
+            });
+
+    // not referred, the referral config is ignored
+    let quote = test.quote_swap(TokenAmount::new(10_000))?;
+    assert_eq!(quote.program_toll, TokenAmount::new(50));
+    assert_eq!(quote.referral, TokenAmount::new(0));
+
+    test.with_referral = true;
+    let quote = test.quote_swap(TokenAmount::new(10_000))?;
+    assert_eq!(quote.bought, TokenAmount::new(6_254));
+    // 40% of the toll
+    assert_eq!(quote.program_toll, TokenAmount::new(30));
+    assert_eq!(quote.referral, TokenAmount::new(20));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_referred_but_pool_has_no_referral_config() -> Result<()> {
+    let mut test = Tester::default();
+    test.with_referral = true;
+
+    assert!(test
+        .quote_swap(TokenAmount::new(10_000))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_sell_amount_is_zero() -> Result<()> {
+    let mut test = Tester::default();
+
+    assert!(test
+        .quote_swap(TokenAmount::new(0))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_sell_mint_equals_buy_mint() -> Result<()> {
+    let mut test = Tester::default();
+    test.buy_mint = test.sell_mint;
+
+    assert!(test
+        .quote_swap(TokenAmount::new(10_000))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_lp_mint_mismatches_pool_mint() -> Result<()> {
+    let mut test = Tester::default();
+    test.lp_mint = AccountInfoWrapper::new()
+        .pack(spl::mint::new(Pubkey::new_unique()).supply(10_000))
+        .owner(token::ID);
+
+    assert!(test
+        .quote_swap(TokenAmount::new(10_000))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_lp_mint_supply_is_zero() -> Result<()> {
+    let mut test = Tester::default();
+    test.lp_mint = test
+        .lp_mint
+        .clone()
+        .pack(spl::mint::new(Pubkey::new_unique()));
+
+    assert!(test
+        .quote_swap(TokenAmount::new(10_000))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    sell_mint: Pubkey,
+    buy_mint: Pubkey,
+    with_referral: bool,
+    user: AccountInfoWrapper,
+    discount: AccountInfoWrapper,
+    pool: AccountInfoWrapper,
+    fee_rebate: AccountInfoWrapper,
+    referral_config: AccountInfoWrapper,
+    lp_mint: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let sell_mint = Pubkey::new_unique();
+        let buy_mint = Pubkey::new_unique();
+        let user = AccountInfoWrapper::new();
+        let discount = AccountInfoWrapper::pda(
+            amm::ID,
+            "discount",
+            &[Discount::PDA_PREFIX, user.key.as_ref()],
+        )
+        .owner(system_program::ID);
+        let lp_mint = AccountInfoWrapper::new()
+            .pack(spl::mint::new(Pubkey::new_unique()).supply(10_000))
+            .owner(token::ID);
+        let pool = AccountInfoWrapper::new().owner(amm::ID).data(Pool {
+            mint: lp_mint.key,
+            dimension: 2,
+            swap_fee: Permillion::from_percent(9),
+            reserves: [
+                Reserve {
+                    tokens: TokenAmount::new(20_000),
+                    mint: sell_mint,
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve {
+                    tokens: TokenAmount::new(20_000),
+                    mint: buy_mint,
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve::default(),
+                Reserve::default(),
+            ],
+            ..Default::default()
+        });
+        let fee_rebate = AccountInfoWrapper::pda(
+            amm::ID,
+            "fee_rebate",
+            &[FeeRebate::PDA_PREFIX, pool.key.as_ref()],
+        )
+        .owner(system_program::ID);
+        let referral_config = AccountInfoWrapper::pda(
+            amm::ID,
+            "referral_config",
+            &[ReferralConfig::PDA_PREFIX, pool.key.as_ref()],
+        )
+        .owner(system_program::ID);
+
+        Self {
+            sell_mint,
+            buy_mint,
+            with_referral: false,
+            user,
+            discount,
+            pool,
+            fee_rebate,
+            referral_config,
+            lp_mint,
+        }
+    }
+}
+
+impl Tester {
+    /// Returns the quote decoded from the return data.
+    fn quote_swap(&mut self, sell: TokenAmount) -> Result<SwapQuote> {
+        let (sell_mint, buy_mint) = (self.sell_mint, self.buy_mint);
+        let with_referral = self.with_referral;
+        let return_data = ReturnData::set(Slot::new(0));
+
+        let mut ctx = self.context_wrapper();
+        let mut accounts = ctx.accounts()?;
+
+        quote_swap(
+            ctx.build(&mut accounts),
+            sell_mint,
+            sell,
+            buy_mint,
+            with_referral,
+        )?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(return_data.decode())
+    }
+
+    fn context_wrapper(&mut self) -> ContextWrapper {
+        ContextWrapper::new(amm::ID)
+            .acc(&mut self.user)
+            .acc(&mut self.discount)
+            .acc(&mut self.pool)
+            .acc(&mut self.fee_rebate)
+            .acc(&mut self.referral_config)
+            .acc(&mut self.lp_mint)
+    }
+}
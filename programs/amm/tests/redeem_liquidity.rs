@@ -1,5 +1,7 @@
 #[allow(dead_code)]
 mod deposit_redeem;
+#[allow(dead_code)]
+mod return_data;
 
 use ::amm::prelude::*;
 use anchor_spl::token;
@@ -321,6 +323,43 @@ fn fails_if_same_mint_passed_multiple_times() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[serial]
+fn fails_if_too_few_remaining_accounts() -> Result<()> {
+    let (mut tester, reserves) = Tester::new_const_prod(2);
+
+    tester.deposit_liquidity(
+        reserves
+            .iter()
+            .map(|r| (r.mint, TokenAmount::new(10)))
+            .collect(),
+        &reserves,
+    )?;
+
+    // these are not even token accounts, the handler must fail before it
+    // attempts to deserialize them
+    tester.vaults_wallets = vec![
+        AccountInfoWrapper::new(),
+        AccountInfoWrapper::new(),
+        AccountInfoWrapper::new(),
+    ];
+
+    let error = tester
+        .redeem_liquidity(
+            reserves
+                .iter()
+                .map(|r| (r.mint, TokenAmount::new(5)))
+                .collect(),
+            TokenAmount::new(5),
+            &reserves,
+        )
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
 #[test]
 #[serial]
 fn fails_if_user_does_not_have_enough_lp_tokens() -> Result<()> {
@@ -650,6 +689,54 @@ fn fails_if_multiple_vault_wallet_pair_mint_mismatches() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[serial]
+fn fails_before_any_transfer_if_destination_wallet_has_wrong_mint() -> Result<()>
+{
+    let (mut tester, reserves) = Tester::new_const_prod(2);
+    let lp_tokens_to_burn = TokenAmount::new(10);
+
+    tester.deposit_liquidity(
+        reserves
+            .iter()
+            .map(|r| (r.mint, TokenAmount::new(10)))
+            .collect(),
+        &reserves,
+    )?;
+
+    // the first pair is valid, the second wallet is of the first reserve mint
+    tester.vaults_wallets[3] = tester.vaults_wallets[3].clone().pack(
+        spl::token_account::new(tester.user.key)
+            .amount(100_000)
+            .mint(reserves[0].mint),
+    );
+    let vault_amount = |tester: &mut Tester| {
+        spl::token_account::from_acc_info(
+            &tester.vaults_wallets[0].to_account_info(),
+        )
+        .amount
+    };
+    let first_vault_before = vault_amount(&mut tester);
+
+    let error = tester
+        .redeem_liquidity(
+            reserves
+                .iter()
+                .map(|r| (r.mint, TokenAmount::new(0)))
+                .collect(),
+            lp_tokens_to_burn,
+            &reserves,
+        )
+        .unwrap_err()
+        .to_string();
+
+    assert!(error.contains("InvalidAccountInput"));
+    // no tokens left the first vault
+    assert_eq!(vault_amount(&mut tester), first_vault_before);
+
+    Ok(())
+}
+
 #[test]
 #[serial]
 fn fails_if_user_is_not_authority_over_a_wallet() -> Result<()> {
@@ -801,3 +888,63 @@ fn prints_lp_token_supply_in_redeem_and_deposit_liquidity() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+#[serial]
+fn returns_redeemed_tokens_and_burned_lp_tokens() -> Result<()> {
+    let (mut tester, reserves) = Tester::new_const_prod(2);
+    tester.deposit_liquidity(
+        [(reserves[0].mint, 100), (reserves[1].mint, 33)]
+            .into_iter()
+            .map(|(mint, tokens)| (mint, TokenAmount::new(tokens)))
+            .collect(),
+        &reserves,
+    )?;
+    let wallets_before = wallet_amounts(&mut tester);
+
+    tester.redeem_liquidity(
+        reserves
+            .iter()
+            .map(|r| (r.mint, TokenAmount::new(0)))
+            .collect(),
+        TokenAmount::new(7),
+        &reserves,
+    )?;
+    let result: LiquidityResult = return_data::decode_last();
+
+    let redeemed: Vec<_> = reserves
+        .iter()
+        .zip(wallets_before)
+        .zip(wallet_amounts(&mut tester))
+        .map(|((r, before), after)| TokenLimit {
+            mint: r.mint,
+            tokens: TokenAmount::new(after - before),
+        })
+        .collect();
+    assert_eq!(
+        result,
+        LiquidityResult {
+            lp_tokens_minted: TokenAmount::new(0),
+            lp_tokens_burned: TokenAmount::new(7),
+            tokens: redeemed,
+            tokens_rounding: Rounding::Floor,
+        }
+    );
+    assert!(result.tokens.iter().all(|t| t.tokens.amount > 0));
+
+    Ok(())
+}
+
+// User's wallet amounts ordered by reserves.
+fn wallet_amounts(tester: &mut Tester) -> Vec<u64> {
+    tester
+        .vaults_wallets
+        .chunks_mut(2)
+        .map(|vault_wallet| {
+            spl::token_account::from_acc_info(
+                &vault_wallet[1].to_account_info(),
+            )
+            .amount
+        })
+        .collect()
+}
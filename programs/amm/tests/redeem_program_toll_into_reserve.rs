@@ -0,0 +1,444 @@
+use ::amm::amm::redeem_program_toll_into_reserve;
+use ::amm::prelude::*;
+use anchor_spl::token::{self, spl_token::instruction::TokenInstruction};
+use anchortest::{
+    builder::*,
+    spl::{self, MintExt, TokenAccountExt},
+    stub,
+};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use solana_sdk::instruction::Instruction;
+
+#[test]
+#[serial]
+fn redeems_toll_into_single_reserve() -> Result<()> {
+    let mut test = Tester::default();
+    let mint_a = test.pool_copy().reserves[0].mint;
+    let mint_b = test.pool_copy().reserves[1].mint;
+
+    // 1_000 of each reserve are redeemed, and 1_000 of A are swapped for
+    // 9_000 - 9_000 * 9_000 / 10_000 = 900 of B
+    test.redeem_program_toll_into_reserve(
+        TokenAmount::new(100),
+        TokenAmount::new(1_900),
+    )?;
+
+    assert_eq!(test.lp_supply(), 900);
+    assert_eq!(test.token_amount_of(&test.program_toll_wallet.clone()), 0);
+    assert_eq!(
+        test.token_amount_of(&test.destination_wallet.clone()),
+        1_900
+    );
+    assert_eq!(test.token_amount_of(&test.reserve_vault.clone()), 8_100);
+
+    let pool = test.pool_copy();
+    assert_eq!(pool.reserves[0].mint, mint_a);
+    assert_eq!(pool.reserves[0].tokens, TokenAmount::new(10_000));
+    assert_eq!(pool.reserves[1].mint, mint_b);
+    assert_eq!(pool.reserves[1].tokens, TokenAmount::new(8_100));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_less_than_min_tokens() -> Result<()> {
+    let mut test = Tester::default();
+
+    assert!(test
+        .redeem_program_toll_into_reserve(
+            TokenAmount::new(100),
+            TokenAmount::new(1_901),
+        )
+        .unwrap_err()
+        .to_string()
+        .contains("SlippageExceeded"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_signer_is_not_program_toll_authority() -> Result<()> {
+    let mut test = Tester::default();
+    test.program_toll_authority = AccountInfoWrapper::new().signer();
+
+    assert!(test
+        .redeem_program_toll_into_reserve(
+            TokenAmount::new(100),
+            TokenAmount::new(0),
+        )
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_reserve_vault_is_not_pool_vault() -> Result<()> {
+    let mut test = Tester::default();
+    let mint_b = test.pool_copy().reserves[1].mint;
+    test.reserve_vault = AccountInfoWrapper::new()
+        .mutable()
+        .pack(
+            spl::token_account::new(test.pool_signer.key)
+                .mint(mint_b)
+                .amount(10_000),
+        )
+        .owner(token::ID);
+
+    assert!(test
+        .redeem_program_toll_into_reserve(
+            TokenAmount::new(100),
+            TokenAmount::new(0),
+        )
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_destination_wallet_mint_mismatches_vault() -> Result<()> {
+    let mut test = Tester::default();
+    test.destination_wallet = AccountInfoWrapper::new()
+        .mutable()
+        .pack(spl::token_account::new(Pubkey::new_unique()))
+        .owner(token::ID);
+
+    assert!(test
+        .redeem_program_toll_into_reserve(
+            TokenAmount::new(100),
+            TokenAmount::new(0),
+        )
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_burning_more_than_toll_wallet_holds() -> Result<()> {
+    let mut test = Tester::default();
+
+    assert!(test
+        .redeem_program_toll_into_reserve(
+            TokenAmount::new(101),
+            TokenAmount::new(0),
+        )
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidLpTokenAmount"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_pool_has_more_than_two_reserves() -> Result<()> {
+    let mut test = Tester::default();
+    let mut pool = test.pool_copy();
+    pool.dimension = 3;
+    pool.reserves[2] = Reserve {
+        tokens: TokenAmount::new(10_000),
+        mint: Pubkey::new_unique(),
+        vault: Pubkey::new_unique(),
+    };
+    test.pool = test.pool.clone().data(pool);
+
+    assert!(test
+        .redeem_program_toll_into_reserve(
+            TokenAmount::new(100),
+            TokenAmount::new(0),
+        )
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_program_toll_wallet_is_not_of_lp_mint() -> Result<()> {
+    let mut test = Tester::default();
+    // same address as stored on the pool, but of another mint
+    test.program_toll_wallet =
+        AccountInfoWrapper::with_key(test.program_toll_wallet.key)
+            .mutable()
+            .pack(
+                spl::token_account::new(test.program_toll_authority.key)
+                    .mint(Pubkey::new_unique())
+                    .amount(100),
+            )
+            .owner(token::ID);
+
+    assert!(test
+        .redeem_program_toll_into_reserve(
+            TokenAmount::new(100),
+            TokenAmount::new(0),
+        )
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    program_toll_authority: AccountInfoWrapper,
+    program_toll: AccountInfoWrapper,
+    pool: AccountInfoWrapper,
+    pool_signer: AccountInfoWrapper,
+    lp_mint: AccountInfoWrapper,
+    program_toll_wallet: AccountInfoWrapper,
+    reserve_vault: AccountInfoWrapper,
+    destination_wallet: AccountInfoWrapper,
+    token_program: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let program_toll_authority = AccountInfoWrapper::new().signer();
+        let program_toll = AccountInfoWrapper::pda(
+            amm::ID,
+            "program_toll",
+            &[ProgramToll::PDA_SEED],
+        )
+        .data(ProgramToll {
+            authority: program_toll_authority.key,
+        })
+        .owner(amm::ID);
+        let pool_key = Pubkey::new_unique();
+        let pool_signer = AccountInfoWrapper::pda(
+            amm::ID,
+            "pool_signer",
+            &[Pool::SIGNER_PDA_PREFIX, pool_key.as_ref()],
+        );
+        let lp_mint = AccountInfoWrapper::new()
+            .mutable()
+            .pack(spl::mint::new(pool_signer.key).supply(1_000))
+            .owner(token::ID);
+        let program_toll_wallet = AccountInfoWrapper::new()
+            .mutable()
+            .pack(
+                spl::token_account::new(program_toll_authority.key)
+                    .mint(lp_mint.key)
+                    .amount(100),
+            )
+            .owner(token::ID);
+        let mint_b = Pubkey::new_unique();
+        let reserve_vault = AccountInfoWrapper::new()
+            .mutable()
+            .pack(
+                spl::token_account::new(pool_signer.key)
+                    .mint(mint_b)
+                    .amount(10_000),
+            )
+            .owner(token::ID);
+        let destination_wallet = AccountInfoWrapper::new()
+            .mutable()
+            .pack(
+                spl::token_account::new(program_toll_authority.key)
+                    .mint(mint_b),
+            )
+            .owner(token::ID);
+        let pool = AccountInfoWrapper::with_key(pool_key)
+            .mutable()
+            .owner(amm::ID)
+            .data(Pool {
+                mint: lp_mint.key,
+                signer: pool_signer.key,
+                program_toll_wallet: program_toll_wallet.key,
+                dimension: 2,
+                reserves: [
+                    Reserve {
+                        tokens: TokenAmount::new(10_000),
+                        mint: Pubkey::new_unique(),
+                        vault: Pubkey::new_unique(),
+                    },
+                    Reserve {
+                        tokens: TokenAmount::new(10_000),
+                        mint: mint_b,
+                        vault: reserve_vault.key,
+                    },
+                    Reserve::default(),
+                    Reserve::default(),
+                ],
+                ..Default::default()
+            });
+        let token_program =
+            AccountInfoWrapper::with_key(anchor_spl::token::ID).program();
+
+        Self {
+            program_toll_authority,
+            program_toll,
+            pool,
+            pool_signer,
+            lp_mint,
+            program_toll_wallet,
+            reserve_vault,
+            destination_wallet,
+            token_program,
+        }
+    }
+}
+
+impl Tester {
+    fn redeem_program_toll_into_reserve(
+        &mut self,
+        lp_tokens_to_burn: TokenAmount,
+        min_tokens: TokenAmount,
+    ) -> Result<()> {
+        stub::Syscalls::new(CpiValidator(CpiValidatorState::BurnToll {
+            mint: self.lp_mint.key,
+            source: self.program_toll_wallet.key,
+            authority: self.program_toll_authority.key,
+            tokens: lp_tokens_to_burn,
+            next_cpi: TransferTokens {
+                vault: self.reserve_vault.key,
+                wallet: self.destination_wallet.key,
+                signer: self.pool_signer.key,
+            },
+        }))
+        .set();
+
+        let mut ctx = self.context_wrapper();
+        let mut accounts = ctx.accounts()?;
+
+        redeem_program_toll_into_reserve(
+            ctx.build(&mut accounts),
+            lp_tokens_to_burn,
+            min_tokens,
+        )?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(())
+    }
+
+    fn pool_copy(&self) -> Pool {
+        Pool::try_deserialize(&mut self.pool.data.as_slice()).unwrap()
+    }
+
+    fn lp_supply(&mut self) -> u64 {
+        spl::mint::from_acc_info(&self.lp_mint.to_account_info()).supply
+    }
+
+    fn token_amount_of(&self, wallet: &AccountInfoWrapper) -> u64 {
+        spl::token_account::from_acc_info(&wallet.clone().to_account_info())
+            .amount
+    }
+
+    fn context_wrapper(&mut self) -> ContextWrapper {
+        ContextWrapper::new(amm::ID)
+            .acc(&mut self.program_toll_authority)
+            .acc(&mut self.program_toll)
+            .acc(&mut self.pool)
+            .acc(&mut self.pool_signer)
+            .acc(&mut self.lp_mint)
+            .acc(&mut self.program_toll_wallet)
+            .acc(&mut self.reserve_vault)
+            .acc(&mut self.destination_wallet)
+            .acc(&mut self.token_program)
+    }
+}
+
+struct CpiValidator(CpiValidatorState);
+enum CpiValidatorState {
+    BurnToll {
+        mint: Pubkey,
+        source: Pubkey,
+        authority: Pubkey,
+        tokens: TokenAmount,
+        next_cpi: TransferTokens,
+    },
+    TransferTokens(TransferTokens),
+    Done,
+}
+
+#[derive(Clone, Copy)]
+struct TransferTokens {
+    vault: Pubkey,
+    wallet: Pubkey,
+    signer: Pubkey,
+}
+
+impl stub::ValidateCpis for CpiValidator {
+    fn validate_next_instruction(
+        &mut self,
+        ix: &Instruction,
+        accounts: &[AccountInfo],
+    ) {
+        match self.0 {
+            CpiValidatorState::BurnToll {
+                mint,
+                source,
+                authority,
+                tokens,
+                next_cpi,
+            } => {
+                let expected_ix = token::spl_token::instruction::burn(
+                    &token::ID,
+                    &source,
+                    &mint,
+                    &authority,
+                    &[],
+                    tokens.amount,
+                )
+                .unwrap();
+                assert_eq!(&expected_ix, ix);
+
+                let wallet = &accounts[0];
+                let lp_mint = &accounts[1];
+                assert_eq!(wallet.key(), source);
+                assert_eq!(lp_mint.key(), mint);
+
+                spl::mint::burn_from(wallet, lp_mint, tokens.amount)
+                    .expect("Cannot burn LP tokens");
+
+                self.0 = CpiValidatorState::TransferTokens(next_cpi);
+            }
+            CpiValidatorState::TransferTokens(TransferTokens {
+                vault,
+                wallet,
+                signer,
+            }) => {
+                let from_vault = &accounts[0];
+                let to_wallet = &accounts[1];
+                assert_eq!(from_vault.key(), vault);
+                assert_eq!(to_wallet.key(), wallet);
+
+                let tokens = match TokenInstruction::unpack(&ix.data).unwrap() {
+                    TokenInstruction::Transfer { amount } => amount,
+                    other => panic!("Expected transfer, got {:?}", other),
+                };
+                let expected_ix = token::spl_token::instruction::transfer(
+                    &token::ID,
+                    &vault,
+                    &wallet,
+                    &signer,
+                    &[],
+                    tokens,
+                )
+                .unwrap();
+                assert_eq!(&expected_ix, ix);
+
+                spl::token_account::transfer(from_vault, to_wallet, tokens)
+                    .expect("Source vault does not have enough tokens");
+
+                self.0 = CpiValidatorState::Done;
+            }
+            CpiValidatorState::Done => {
+                panic!("No more instructions expected, got {:#?}", ix);
+            }
+        }
+    }
+}
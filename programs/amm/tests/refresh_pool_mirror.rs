@@ -0,0 +1,192 @@
+use ::amm::amm::refresh_pool_mirror;
+use ::amm::prelude::*;
+use anchor_spl::token;
+use anchortest::{builder::*, spl, stub};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use solana_sdk::instruction::Instruction;
+
+#[test]
+#[serial]
+fn mirrors_pool_reserves_and_lp_supply() -> Result<()> {
+    let mut test = Tester::default().slot(100);
+    let pool = test.pool_copy();
+
+    test.refresh_pool_mirror()?;
+
+    let mirror = test.mirror_copy();
+    assert_eq!(mirror.pool, test.pool.key);
+    assert_eq!(mirror.lp_supply, TokenAmount::new(300));
+    assert_eq!(mirror.refreshed_at, Slot::new(100));
+    for (mirrored, reserve) in mirror.reserves.iter().zip(pool.reserves) {
+        assert_eq!(mirrored.mint, reserve.mint);
+        assert_eq!(mirrored.tokens, reserve.tokens);
+    }
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn updates_mirror_once_pool_changes() -> Result<()> {
+    let mut test = Tester::default().slot(100);
+    test.refresh_pool_mirror()?;
+
+    // eg. a swap happened
+    let mut pool = test.pool_copy();
+    pool.reserves[0].tokens = TokenAmount::new(1_500);
+    pool.reserves[1].tokens = TokenAmount::new(2_200);
+    test.pool = test.pool.clone().data(pool);
+    test.lp_mint = test
+        .lp_mint
+        .clone()
+        .pack(spl::mint::new(Pubkey::new_unique()).supply(400));
+
+    let mut test = test.slot(101);
+    test.refresh_pool_mirror()?;
+
+    let mirror = test.mirror_copy();
+    assert_eq!(mirror.reserves[0].tokens, TokenAmount::new(1_500));
+    assert_eq!(mirror.reserves[1].tokens, TokenAmount::new(2_200));
+    assert_eq!(mirror.lp_supply, TokenAmount::new(400));
+    assert_eq!(mirror.refreshed_at, Slot::new(101));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_lp_mint_mismatches_pool_mint() -> Result<()> {
+    let mut test = Tester::default();
+    test.lp_mint = AccountInfoWrapper::new()
+        .pack(spl::mint::new(Pubkey::new_unique()).supply(300))
+        .owner(token::ID);
+
+    assert!(test
+        .refresh_pool_mirror()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_mirror_belongs_to_another_pool() -> Result<()> {
+    let mut test = Tester::default();
+    test.pool_mirror = AccountInfoWrapper::pda(
+        amm::ID,
+        "pool_mirror",
+        &[PoolMirror::PDA_PREFIX, Pubkey::new_unique().as_ref()],
+    )
+    .mutable()
+    .owner(amm::ID)
+    .data(PoolMirror::default());
+
+    assert!(test
+        .refresh_pool_mirror()
+        .unwrap_err()
+        .to_string()
+        .contains("ConstraintSeeds"));
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    slot: u64,
+    pool: AccountInfoWrapper,
+    lp_mint: AccountInfoWrapper,
+    pool_mirror: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let lp_mint = AccountInfoWrapper::new()
+            .pack(spl::mint::new(Pubkey::new_unique()).supply(300))
+            .owner(token::ID);
+        let pool = AccountInfoWrapper::new().owner(amm::ID).data(Pool {
+            mint: lp_mint.key,
+            dimension: 2,
+            reserves: [
+                Reserve {
+                    tokens: TokenAmount::new(1_000),
+                    mint: Pubkey::new_unique(),
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve {
+                    tokens: TokenAmount::new(3_299),
+                    mint: Pubkey::new_unique(),
+                    vault: Pubkey::new_unique(),
+                },
+                Reserve::default(),
+                Reserve::default(),
+            ],
+            ..Default::default()
+        });
+        let pool_mirror = AccountInfoWrapper::pda(
+            amm::ID,
+            "pool_mirror",
+            &[PoolMirror::PDA_PREFIX, pool.key.as_ref()],
+        )
+        .mutable()
+        .owner(amm::ID)
+        .data(PoolMirror {
+            pool: pool.key,
+            ..Default::default()
+        });
+
+        Self {
+            slot: 0,
+            pool,
+            lp_mint,
+            pool_mirror,
+        }
+    }
+}
+
+impl Tester {
+    fn slot(mut self, slot: u64) -> Self {
+        self.slot = slot;
+        self
+    }
+
+    fn refresh_pool_mirror(&mut self) -> Result<()> {
+        let syscalls = stub::Syscalls::new(CpiValidator);
+        syscalls.slot(self.slot);
+        syscalls.set();
+
+        let mut ctx = ContextWrapper::new(amm::ID)
+            .acc(&mut self.pool)
+            .acc(&mut self.lp_mint)
+            .acc(&mut self.pool_mirror);
+        let mut accounts = ctx.accounts()?;
+
+        refresh_pool_mirror(ctx.build(&mut accounts))?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(())
+    }
+
+    fn pool_copy(&self) -> Pool {
+        Pool::try_deserialize(&mut self.pool.data.as_slice()).unwrap()
+    }
+
+    fn mirror_copy(&self) -> PoolMirror {
+        PoolMirror::try_deserialize(&mut self.pool_mirror.data.as_slice())
+            .unwrap()
+    }
+}
+
+struct CpiValidator;
+
+impl stub::ValidateCpis for CpiValidator {
+    fn validate_next_instruction(
+        &mut self,
+        ix: &Instruction,
+        _accounts: &[AccountInfo],
+    ) {
+        panic!("No instructions expected, got {:#?}", ix);
+    }
+}
@@ -0,0 +1,360 @@
+use ::amm::amm::reinvest_program_toll;
+use ::amm::prelude::*;
+use anchor_spl::token;
+use anchortest::{
+    builder::*,
+    spl::{self, MintExt, TokenAccountExt},
+    stub,
+};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use solana_sdk::instruction::Instruction;
+use std::collections::BTreeMap;
+
+#[test]
+#[serial]
+fn burns_toll_without_touching_reserves() -> Result<()> {
+    let mut test = Tester::default();
+    let pool_before = test.pool_copy();
+
+    // how many reserve tokens would 100 LPs redeem before the reinvestment
+    let redeemable_before = test.redeemable_for(TokenAmount::new(100));
+
+    test.reinvest_program_toll(TokenAmount::new(100))?;
+
+    // no new LP tokens were minted, only the toll was burned
+    assert_eq!(test.lp_supply(), 900);
+    assert_eq!(test.program_toll_wallet_amount(), 0);
+
+    // the reserves haven't changed at all
+    assert_eq!(test.pool_copy(), pool_before);
+
+    // but each LP token is now worth more of each reserve
+    let redeemable_after = test.redeemable_for(TokenAmount::new(100));
+    for (mint, tokens_before) in redeemable_before {
+        assert_eq!(tokens_before, TokenAmount::new(100));
+        // 111 = 100 * 1_000 / 900
+        assert_eq!(redeemable_after[&mint], TokenAmount::new(111));
+    }
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_signer_is_not_program_toll_authority() -> Result<()> {
+    let mut test = Tester::default();
+    test.program_toll_authority = AccountInfoWrapper::new().signer();
+
+    assert!(test
+        .reinvest_program_toll(TokenAmount::new(100))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_program_toll_wallet_does_not_match_pool() -> Result<()> {
+    let mut test = Tester::default();
+    test.program_toll_wallet = AccountInfoWrapper::new()
+        .mutable()
+        .pack(
+            spl::token_account::new(test.program_toll_authority.key)
+                .mint(test.lp_mint.key)
+                .amount(100),
+        )
+        .owner(token::ID);
+
+    assert!(test
+        .reinvest_program_toll(TokenAmount::new(100))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_lp_mint_does_not_match_pool() -> Result<()> {
+    let mut test = Tester::default();
+    test.lp_mint = AccountInfoWrapper::new()
+        .mutable()
+        .pack(spl::mint::new(Pubkey::new_unique()).supply(1_000))
+        .owner(token::ID);
+
+    assert!(test
+        .reinvest_program_toll(TokenAmount::new(100))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_burning_zero() -> Result<()> {
+    let mut test = Tester::default();
+
+    assert!(test
+        .reinvest_program_toll(TokenAmount::new(0))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_burning_more_than_toll_wallet_holds() -> Result<()> {
+    let mut test = Tester::default();
+
+    assert!(test
+        .reinvest_program_toll(TokenAmount::new(101))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidLpTokenAmount"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_burning_whole_lp_supply() -> Result<()> {
+    let mut test = Tester::default();
+    test.lp_mint = test
+        .lp_mint
+        .clone()
+        .pack(spl::mint::new(test.pool_signer_key).supply(100));
+
+    assert!(test
+        .reinvest_program_toll(TokenAmount::new(100))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_program_toll_wallet_is_not_of_lp_mint() -> Result<()> {
+    let mut test = Tester::default();
+    // same address as stored on the pool, but of another mint
+    test.program_toll_wallet =
+        AccountInfoWrapper::with_key(test.program_toll_wallet.key)
+            .mutable()
+            .pack(
+                spl::token_account::new(test.program_toll_authority.key)
+                    .mint(Pubkey::new_unique())
+                    .amount(100),
+            )
+            .owner(token::ID);
+
+    assert!(test
+        .reinvest_program_toll(TokenAmount::new(100))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    pool_signer_key: Pubkey,
+    program_toll_authority: AccountInfoWrapper,
+    program_toll: AccountInfoWrapper,
+    pool: AccountInfoWrapper,
+    lp_mint: AccountInfoWrapper,
+    program_toll_wallet: AccountInfoWrapper,
+    token_program: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let program_toll_authority = AccountInfoWrapper::new().signer();
+        let program_toll = AccountInfoWrapper::pda(
+            amm::ID,
+            "program_toll",
+            &[ProgramToll::PDA_SEED],
+        )
+        .data(ProgramToll {
+            authority: program_toll_authority.key,
+        })
+        .owner(amm::ID);
+        let pool_key = Pubkey::new_unique();
+        let (pool_signer_key, _) = Pubkey::find_program_address(
+            &[Pool::SIGNER_PDA_PREFIX, pool_key.as_ref()],
+            &amm::ID,
+        );
+        let lp_mint = AccountInfoWrapper::new()
+            .mutable()
+            .pack(spl::mint::new(pool_signer_key).supply(1_000))
+            .owner(token::ID);
+        let program_toll_wallet = AccountInfoWrapper::new()
+            .mutable()
+            .pack(
+                spl::token_account::new(program_toll_authority.key)
+                    .mint(lp_mint.key)
+                    .amount(100),
+            )
+            .owner(token::ID);
+        let pool =
+            AccountInfoWrapper::with_key(pool_key)
+                .owner(amm::ID)
+                .data(Pool {
+                    mint: lp_mint.key,
+                    signer: pool_signer_key,
+                    program_toll_wallet: program_toll_wallet.key,
+                    dimension: 2,
+                    reserves: [
+                        Reserve {
+                            tokens: TokenAmount::new(1_000),
+                            mint: Pubkey::new_unique(),
+                            vault: Pubkey::new_unique(),
+                        },
+                        Reserve {
+                            tokens: TokenAmount::new(1_000),
+                            mint: Pubkey::new_unique(),
+                            vault: Pubkey::new_unique(),
+                        },
+                        Reserve::default(),
+                        Reserve::default(),
+                    ],
+                    ..Default::default()
+                });
+        let token_program =
+            AccountInfoWrapper::with_key(anchor_spl::token::ID).program();
+
+        Self {
+            pool_signer_key,
+            program_toll_authority,
+            program_toll,
+            pool,
+            lp_mint,
+            program_toll_wallet,
+            token_program,
+        }
+    }
+}
+
+impl Tester {
+    fn reinvest_program_toll(
+        &mut self,
+        lp_tokens_to_burn: TokenAmount,
+    ) -> Result<()> {
+        stub::Syscalls::new(CpiValidator(CpiValidatorState::BurnToll {
+            mint: self.lp_mint.key,
+            source: self.program_toll_wallet.key,
+            authority: self.program_toll_authority.key,
+            tokens: lp_tokens_to_burn,
+        }))
+        .set();
+
+        let mut ctx = self.context_wrapper();
+        let mut accounts = ctx.accounts()?;
+
+        reinvest_program_toll(ctx.build(&mut accounts), lp_tokens_to_burn)?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(())
+    }
+
+    fn pool_copy(&self) -> Pool {
+        Pool::try_deserialize(&mut self.pool.data.as_slice()).unwrap()
+    }
+
+    fn lp_supply(&mut self) -> u64 {
+        spl::mint::from_acc_info(&self.lp_mint.to_account_info()).supply
+    }
+
+    fn program_toll_wallet_amount(&mut self) -> u64 {
+        spl::token_account::from_acc_info(
+            &self.program_toll_wallet.to_account_info(),
+        )
+        .amount
+    }
+
+    fn redeemable_for(
+        &mut self,
+        lp_tokens: TokenAmount,
+    ) -> BTreeMap<Pubkey, TokenAmount> {
+        let lp_supply = TokenAmount::new(self.lp_supply());
+        let mut pool = self.pool_copy();
+        let min_tokens = pool
+            .reserves()
+            .iter()
+            .map(|r| (r.mint, TokenAmount::new(0)))
+            .collect();
+        pool.redeem_tokens(min_tokens, lp_tokens, lp_supply)
+            .unwrap()
+    }
+
+    fn context_wrapper(&mut self) -> ContextWrapper {
+        ContextWrapper::new(amm::ID)
+            .acc(&mut self.program_toll_authority)
+            .acc(&mut self.program_toll)
+            .acc(&mut self.pool)
+            .acc(&mut self.lp_mint)
+            .acc(&mut self.program_toll_wallet)
+            .acc(&mut self.token_program)
+    }
+}
+
+struct CpiValidator(CpiValidatorState);
+enum CpiValidatorState {
+    BurnToll {
+        mint: Pubkey,
+        source: Pubkey,
+        authority: Pubkey,
+        tokens: TokenAmount,
+    },
+    Done,
+}
+
+impl stub::ValidateCpis for CpiValidator {
+    fn validate_next_instruction(
+        &mut self,
+        ix: &Instruction,
+        accounts: &[AccountInfo],
+    ) {
+        match self.0 {
+            CpiValidatorState::BurnToll {
+                mint,
+                source,
+                authority,
+                tokens,
+            } => {
+                let expected_ix = token::spl_token::instruction::burn(
+                    &token::ID,
+                    &source,
+                    &mint,
+                    &authority,
+                    &[],
+                    tokens.amount,
+                )
+                .unwrap();
+                assert_eq!(&expected_ix, ix);
+
+                let wallet = &accounts[0];
+                let lp_mint = &accounts[1];
+                assert_eq!(wallet.key(), source);
+                assert_eq!(lp_mint.key(), mint);
+
+                spl::mint::burn_from(wallet, lp_mint, tokens.amount)
+                    .expect("Cannot burn LP tokens");
+
+                self.0 = CpiValidatorState::Done;
+            }
+            CpiValidatorState::Done => {
+                panic!("No more instructions expected, got {:#?}", ix);
+            }
+        }
+    }
+}
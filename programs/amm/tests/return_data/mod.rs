@@ -0,0 +1,120 @@
+//! Syscall stubs which capture the return data written by an endpoint, so
+//! that tests can decode it the same way as clients do.
+
+use ::amm::prelude::*;
+use solana_sdk::entrypoint::{ProgramResult, SUCCESS};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::program::get_return_data;
+use solana_sdk::program_stubs::{self, SyscallStubs};
+use std::sync::{Arc, Mutex};
+
+/// The return data written by the last endpoint call.
+#[derive(Clone, Debug, Default)]
+pub struct ReturnData(Arc<Mutex<Option<(Pubkey, Vec<u8>)>>>);
+
+impl ReturnData {
+    /// Sets the syscall stubs with the clock at given slot. Endpoints which
+    /// invoke other programs cannot be called with these stubs, see
+    /// [`ReturnData::wrap`].
+    pub fn set(slot: Slot) -> Self {
+        Self::wrap(ClockStubs { slot })
+    }
+
+    /// Sets the syscall stubs which delegate everything but the return data
+    /// to given stubs, eg. to validate CPIs.
+    pub fn wrap(inner: impl SyscallStubs + 'static) -> Self {
+        let return_data = Self::default();
+        program_stubs::set_syscall_stubs(Box::new(Stubs {
+            inner: Box::new(inner),
+            return_data: return_data.clone(),
+        }));
+
+        return_data
+    }
+
+    /// Deserializes the return data with borsh.
+    pub fn decode<T: AnchorDeserialize>(&self) -> T {
+        let return_data = self.0.lock().unwrap().clone();
+
+        decode_return_data(return_data)
+    }
+}
+
+/// Deserializes with borsh the return data which is visible to the currently
+/// set syscall stubs, see [`ReturnData::wrap`].
+pub fn decode_last<T: AnchorDeserialize>() -> T {
+    decode_return_data(get_return_data())
+}
+
+fn decode_return_data<T: AnchorDeserialize>(
+    return_data: Option<(Pubkey, Vec<u8>)>,
+) -> T {
+    let (program_id, data) = return_data.expect("No return data was set");
+    assert_eq!(program_id, amm::ID);
+
+    T::try_from_slice(&data).expect("Cannot decode return data")
+}
+
+struct Stubs {
+    inner: Box<dyn SyscallStubs>,
+    return_data: ReturnData,
+}
+
+impl SyscallStubs for Stubs {
+    fn sol_log(&self, message: &str) {
+        self.inner.sol_log(message)
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        self.inner
+            .sol_invoke_signed(instruction, account_infos, signers_seeds)
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner.sol_get_clock_sysvar(var_addr)
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner.sol_get_rent_sysvar(var_addr)
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        *self.return_data.0.lock().unwrap() = Some((amm::ID, data.to_vec()));
+    }
+
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        self.return_data.0.lock().unwrap().clone()
+    }
+}
+
+struct ClockStubs {
+    slot: Slot,
+}
+
+impl SyscallStubs for ClockStubs {
+    fn sol_log(&self, message: &str) {
+        println!("[LOG] {}", message);
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe {
+            *(var_addr as *mut Clock) = Clock {
+                slot: self.slot.slot,
+                ..Default::default()
+            };
+        }
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe {
+            *(var_addr as *mut Rent) = Rent::default();
+        }
+        SUCCESS
+    }
+}
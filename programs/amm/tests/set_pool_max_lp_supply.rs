@@ -0,0 +1,183 @@
+use ::amm::amm::set_pool_max_lp_supply;
+use ::amm::prelude::*;
+use anchor_spl::token;
+use anchortest::{builder::*, spl, stub};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use solana_sdk::instruction::Instruction;
+
+#[test]
+#[serial]
+fn works() -> Result<()> {
+    let mut test = Tester::default();
+
+    test.set_pool_max_lp_supply(TokenAmount::new(1_000))?;
+    assert_eq!(test.pool_copy().max_lp_supply, TokenAmount::new(1_000));
+
+    // without liquidity, the cap can also be lowered
+    test.set_pool_max_lp_supply(TokenAmount::new(500))?;
+    assert_eq!(test.pool_copy().max_lp_supply, TokenAmount::new(500));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn can_raise_or_remove_cap_once_pool_has_liquidity() -> Result<()> {
+    let mut test = Tester::default().with_liquidity();
+    test.set_max_lp_supply(2_000);
+
+    test.set_pool_max_lp_supply(TokenAmount::new(3_000))?;
+    assert_eq!(test.pool_copy().max_lp_supply, TokenAmount::new(3_000));
+
+    test.set_pool_max_lp_supply(TokenAmount::new(0))?;
+    assert_eq!(test.pool_copy().max_lp_supply, TokenAmount::new(0));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_to_lower_cap_once_pool_has_liquidity() -> Result<()> {
+    let mut test = Tester::default().with_liquidity();
+    test.set_max_lp_supply(2_000);
+
+    assert!(test
+        .set_pool_max_lp_supply(TokenAmount::new(1_999))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_to_introduce_cap_once_pool_has_liquidity() -> Result<()> {
+    let mut test = Tester::default().with_liquidity();
+
+    assert!(test
+        .set_pool_max_lp_supply(TokenAmount::new(2_000))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_signer_is_not_admin() -> Result<()> {
+    let mut test = Tester::default();
+    test.admin = AccountInfoWrapper::new().signer();
+
+    assert!(test
+        .set_pool_max_lp_supply(TokenAmount::new(1_000))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_lp_mint_does_not_match_pool() -> Result<()> {
+    let mut test = Tester::default();
+    test.lp_mint = AccountInfoWrapper::new()
+        .pack(spl::mint::new(Pubkey::new_unique()))
+        .owner(token::ID);
+
+    assert!(test
+        .set_pool_max_lp_supply(TokenAmount::new(1_000))
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    admin: AccountInfoWrapper,
+    pool: AccountInfoWrapper,
+    lp_mint: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let admin = AccountInfoWrapper::new().signer();
+        let lp_mint = AccountInfoWrapper::new()
+            .pack(spl::mint::new(Pubkey::new_unique()))
+            .owner(token::ID);
+        let pool =
+            AccountInfoWrapper::new()
+                .mutable()
+                .owner(amm::ID)
+                .data(Pool {
+                    admin: admin.key,
+                    mint: lp_mint.key,
+                    ..Default::default()
+                });
+
+        Self {
+            admin,
+            pool,
+            lp_mint,
+        }
+    }
+}
+
+impl Tester {
+    fn with_liquidity(mut self) -> Self {
+        self.lp_mint = self
+            .lp_mint
+            .clone()
+            .pack(spl::mint::new(Pubkey::new_unique()).supply(1_000));
+        self
+    }
+
+    fn set_max_lp_supply(&mut self, max_lp_supply: u64) {
+        let mut pool = self.pool_copy();
+        pool.max_lp_supply = TokenAmount::new(max_lp_supply);
+        self.pool = self.pool.clone().data(pool);
+    }
+
+    fn set_pool_max_lp_supply(
+        &mut self,
+        max_lp_supply: TokenAmount,
+    ) -> Result<()> {
+        stub::Syscalls::new(CpiValidator).set();
+
+        let mut ctx = self.context_wrapper();
+        let mut accounts = ctx.accounts()?;
+
+        set_pool_max_lp_supply(ctx.build(&mut accounts), max_lp_supply)?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(())
+    }
+
+    fn pool_copy(&self) -> Pool {
+        Pool::try_deserialize(&mut self.pool.data.as_slice()).unwrap()
+    }
+
+    fn context_wrapper(&mut self) -> ContextWrapper {
+        ContextWrapper::new(amm::ID)
+            .acc(&mut self.admin)
+            .acc(&mut self.pool)
+            .acc(&mut self.lp_mint)
+    }
+}
+
+struct CpiValidator;
+
+impl stub::ValidateCpis for CpiValidator {
+    fn validate_next_instruction(
+        &mut self,
+        ix: &Instruction,
+        _accounts: &[AccountInfo],
+    ) {
+        panic!("No instructions expected, got {:#?}", ix);
+    }
+}
@@ -0,0 +1,166 @@
+use ::amm::amm::set_pool_program_toll_wallet;
+use ::amm::prelude::*;
+use anchor_spl::token;
+use anchortest::{
+    builder::*,
+    spl::{self, TokenAccountExt},
+};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn works() -> Result<()> {
+    let mut test = Tester::default();
+
+    assert!(test.set_pool_program_toll_wallet().is_ok());
+
+    let pool = Pool::try_deserialize(&mut test.pool.data.as_slice())?;
+    assert_eq!(pool.program_toll_wallet, test.new_program_toll_wallet.key);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_signer_is_not_program_toll_authority() -> Result<()> {
+    let mut test = Tester::default();
+    test.program_toll_authority = AccountInfoWrapper::new().signer();
+
+    assert!(test
+        .set_pool_program_toll_wallet()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_new_wallet_is_not_of_lp_mint() -> Result<()> {
+    let mut test = Tester::default();
+    test.new_program_toll_wallet = AccountInfoWrapper::new()
+        .pack(
+            spl::token_account::new(test.program_toll_authority.key)
+                .mint(Pubkey::new_unique()),
+        )
+        .owner(token::ID);
+
+    assert!(test
+        .set_pool_program_toll_wallet()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_new_wallet_is_not_owned_by_program_toll_authority() -> Result<()> {
+    let mut test = Tester::default();
+    test.new_program_toll_wallet = AccountInfoWrapper::new()
+        .pack(spl::token_account::new(Pubkey::new_unique()).mint(test.lp_mint))
+        .owner(token::ID);
+
+    assert!(test
+        .set_pool_program_toll_wallet()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_new_wallet_is_already_used() -> Result<()> {
+    let mut test = Tester::default();
+    let current_wallet = test.current_program_toll_wallet;
+    test.new_program_toll_wallet = AccountInfoWrapper::with_key(current_wallet)
+        .pack(
+            spl::token_account::new(test.program_toll_authority.key)
+                .mint(test.lp_mint),
+        )
+        .owner(token::ID);
+
+    assert!(test
+        .set_pool_program_toll_wallet()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    lp_mint: Pubkey,
+    current_program_toll_wallet: Pubkey,
+    program_toll_authority: AccountInfoWrapper,
+    program_toll: AccountInfoWrapper,
+    pool: AccountInfoWrapper,
+    new_program_toll_wallet: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let lp_mint = Pubkey::new_unique();
+        let current_program_toll_wallet = Pubkey::new_unique();
+        let program_toll_authority = AccountInfoWrapper::new().signer();
+        let program_toll = AccountInfoWrapper::pda(
+            amm::ID,
+            "program_toll",
+            &[ProgramToll::PDA_SEED],
+        )
+        .data(ProgramToll {
+            authority: program_toll_authority.key,
+        })
+        .owner(amm::ID);
+        let pool =
+            AccountInfoWrapper::new()
+                .mutable()
+                .owner(amm::ID)
+                .data(Pool {
+                    mint: lp_mint,
+                    program_toll_wallet: current_program_toll_wallet,
+                    ..Default::default()
+                });
+        let new_program_toll_wallet = AccountInfoWrapper::new()
+            .pack(
+                spl::token_account::new(program_toll_authority.key)
+                    .mint(lp_mint),
+            )
+            .owner(token::ID);
+
+        Self {
+            lp_mint,
+            current_program_toll_wallet,
+            program_toll_authority,
+            program_toll,
+            pool,
+            new_program_toll_wallet,
+        }
+    }
+}
+
+impl Tester {
+    fn set_pool_program_toll_wallet(&mut self) -> Result<()> {
+        let mut ctx = self.context_wrapper();
+        let mut accounts = ctx.accounts()?;
+
+        set_pool_program_toll_wallet(ctx.build(&mut accounts))?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(())
+    }
+
+    fn context_wrapper(&mut self) -> ContextWrapper {
+        ContextWrapper::new(amm::ID)
+            .acc(&mut self.program_toll_authority)
+            .acc(&mut self.program_toll)
+            .acc(&mut self.pool)
+            .acc(&mut self.new_program_toll_wallet)
+    }
+}
@@ -0,0 +1,194 @@
+use ::amm::amm::set_pool_referral_default_recipient;
+use ::amm::prelude::*;
+use anchor_spl::token;
+use anchortest::{
+    builder::*,
+    spl::{self, TokenAccountExt},
+    stub,
+};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use solana_sdk::instruction::Instruction;
+
+#[test]
+#[serial]
+fn works() -> Result<()> {
+    let mut test = Tester::default();
+    let og_state = test.clone();
+
+    test.set_pool_referral_default_recipient()?;
+
+    let config = ReferralConfig::try_deserialize(
+        &mut test.referral_config.data.as_slice(),
+    )?;
+    assert_eq!(config.share, Permillion::from_percent(40));
+    assert_eq!(
+        config.default_recipient,
+        test.new_default_referral_wallet.key
+    );
+
+    // no other changes should have happened
+    test.referral_config = og_state.referral_config.clone();
+    assert_eq!(test, og_state);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_signer_is_not_program_toll_authority() -> Result<()> {
+    let mut test = Tester::default();
+    test.program_toll_authority = AccountInfoWrapper::new().signer();
+
+    assert!(test
+        .set_pool_referral_default_recipient()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_signer_is_pool_admin() -> Result<()> {
+    let mut test = Tester::default();
+    test.program_toll_authority = test.pool_admin.clone();
+
+    assert!(test
+        .set_pool_referral_default_recipient()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_new_default_referral_wallet_is_not_of_lp_mint() -> Result<()> {
+    let mut test = Tester::default();
+    test.new_default_referral_wallet = AccountInfoWrapper::new()
+        .pack(
+            spl::token_account::new(Pubkey::new_unique())
+                .mint(Pubkey::new_unique()),
+        )
+        .owner(token::ID);
+
+    assert!(test
+        .set_pool_referral_default_recipient()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_config_belongs_to_another_pool() -> Result<()> {
+    let mut test = Tester::default();
+    test.referral_config = AccountInfoWrapper::pda(
+        amm::ID,
+        "referral_config",
+        &[ReferralConfig::PDA_PREFIX, Pubkey::new_unique().as_ref()],
+    )
+    .mutable()
+    .owner(amm::ID)
+    .data(ReferralConfig::default());
+
+    assert!(test
+        .set_pool_referral_default_recipient()
+        .unwrap_err()
+        .to_string()
+        .contains("ConstraintSeeds"));
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    program_toll_authority: AccountInfoWrapper,
+    program_toll: AccountInfoWrapper,
+    pool: AccountInfoWrapper,
+    referral_config: AccountInfoWrapper,
+    new_default_referral_wallet: AccountInfoWrapper,
+    // not passed to the endpoint unless a test signs with it
+    pool_admin: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let program_toll_authority = AccountInfoWrapper::new().signer();
+        let program_toll = AccountInfoWrapper::pda(
+            amm::ID,
+            "program_toll",
+            &[ProgramToll::PDA_SEED],
+        )
+        .data(ProgramToll {
+            authority: program_toll_authority.key,
+        })
+        .owner(amm::ID);
+        let pool_admin = AccountInfoWrapper::new().signer();
+        let lp_mint = Pubkey::new_unique();
+        let pool = AccountInfoWrapper::new().owner(amm::ID).data(Pool {
+            admin: pool_admin.key,
+            mint: lp_mint,
+            ..Default::default()
+        });
+        let referral_config = AccountInfoWrapper::pda(
+            amm::ID,
+            "referral_config",
+            &[ReferralConfig::PDA_PREFIX, pool.key.as_ref()],
+        )
+        .mutable()
+        .owner(amm::ID)
+        .data(ReferralConfig {
+            share: Permillion::from_percent(40),
+            default_recipient: Pubkey::new_unique(),
+        });
+        let new_default_referral_wallet = AccountInfoWrapper::new()
+            .pack(spl::token_account::new(Pubkey::new_unique()).mint(lp_mint))
+            .owner(token::ID);
+
+        Self {
+            program_toll_authority,
+            program_toll,
+            pool,
+            referral_config,
+            new_default_referral_wallet,
+            pool_admin,
+        }
+    }
+}
+
+impl Tester {
+    fn set_pool_referral_default_recipient(&mut self) -> Result<()> {
+        stub::Syscalls::new(CpiValidator).set();
+
+        let mut ctx = ContextWrapper::new(amm::ID)
+            .acc(&mut self.program_toll_authority)
+            .acc(&mut self.program_toll)
+            .acc(&mut self.pool)
+            .acc(&mut self.referral_config)
+            .acc(&mut self.new_default_referral_wallet);
+        let mut accounts = ctx.accounts()?;
+
+        set_pool_referral_default_recipient(ctx.build(&mut accounts))?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(())
+    }
+}
+
+struct CpiValidator;
+
+impl stub::ValidateCpis for CpiValidator {
+    fn validate_next_instruction(
+        &mut self,
+        ix: &Instruction,
+        _accounts: &[AccountInfo],
+    ) {
+        panic!("No instructions expected, got {:#?}", ix);
+    }
+}
@@ -1,8 +1,11 @@
 use ::amm::amm::set_pool_swap_fee;
 use ::amm::prelude::*;
-use anchortest::builder::*;
+use anchortest::{builder::*, stub};
 use pretty_assertions::assert_eq;
 use serial_test::serial;
+use solana_sdk::instruction::Instruction;
+
+const TIMELOCK_SLOTS: u64 = consts::DEFAULT_SWAP_FEE_TIMELOCK_SLOTS;
 
 #[test]
 #[serial]
@@ -12,8 +15,9 @@ fn works() -> Result<()> {
     let fee = Permillion { permillion: 5_000 };
     assert!(test.set_pool_swap_fee(fee).is_ok());
 
-    let pool = Pool::try_deserialize(&mut test.pool.data.as_slice())?;
+    let pool = test.pool_copy();
     assert_eq!(pool.swap_fee, fee);
+    assert!(!pool.has_pending_swap_fee());
 
     Ok(())
 }
@@ -25,7 +29,7 @@ fn max_swap_fee_is_inclusive() -> Result<()> {
 
     assert!(test.set_pool_swap_fee(consts::MAX_SWAP_FEE).is_ok());
 
-    let pool = Pool::try_deserialize(&mut test.pool.data.as_slice())?;
+    let pool = test.pool_copy();
     assert_eq!(pool.swap_fee, consts::MAX_SWAP_FEE);
 
     Ok(())
@@ -48,8 +52,96 @@ fn fails_if_discount_more_than_1_percent() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[serial]
+fn increase_is_pending_until_timelock_elapses() -> Result<()> {
+    let mut test = Tester::default().with_liquidity().slot(100);
+    let fee_before = test.pool_copy().swap_fee;
+
+    let fee = Permillion { permillion: 5_000 };
+    test.set_pool_swap_fee(fee)?;
+
+    let effective_at = Slot::new(100 + TIMELOCK_SLOTS);
+    let mut pool = test.pool_copy();
+    assert_eq!(pool.swap_fee, fee_before);
+    assert_eq!(pool.pending_swap_fee, fee);
+    assert_eq!(pool.pending_swap_fee_effective_at, effective_at);
+
+    // doesn't apply a slot before
+    pool.apply_pending_swap_fee(Slot::new(effective_at.slot - 1));
+    assert_eq!(pool.swap_fee, fee_before);
+    assert!(pool.has_pending_swap_fee());
+
+    // applies from the effective slot on
+    pool.apply_pending_swap_fee(effective_at);
+    assert_eq!(pool.swap_fee, fee);
+    assert!(!pool.has_pending_swap_fee());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn increase_uses_pool_timelock() -> Result<()> {
+    let mut test = Tester::default().with_liquidity().slot(100);
+    let mut pool = test.pool_copy();
+    pool.swap_fee_timelock_slots = consts::MAX_SWAP_FEE_TIMELOCK_SLOTS;
+    test.pool = test.pool.clone().data(pool);
+
+    test.set_pool_swap_fee(Permillion { permillion: 5_000 })?;
+
+    assert_eq!(
+        test.pool_copy().pending_swap_fee_effective_at,
+        Slot::new(100 + consts::MAX_SWAP_FEE_TIMELOCK_SLOTS)
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn decrease_applies_immediately_and_cancels_pending_increase() -> Result<()> {
+    let mut test = Tester::default().with_liquidity().slot(100);
+
+    test.set_pool_swap_fee(Permillion { permillion: 5_000 })?;
+    assert!(test.pool_copy().has_pending_swap_fee());
+
+    let fee = Permillion { permillion: 1_000 };
+    test.set_pool_swap_fee(fee)?;
+
+    let pool = test.pool_copy();
+    assert_eq!(pool.swap_fee, fee);
+    assert!(!pool.has_pending_swap_fee());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn effective_pending_fee_is_applied_before_change() -> Result<()> {
+    let mut test = Tester::default()
+        .with_liquidity()
+        .slot(100 + TIMELOCK_SLOTS);
+    let mut pool = test.pool_copy();
+    pool.pending_swap_fee = Permillion { permillion: 5_000 };
+    pool.pending_swap_fee_effective_at = Slot::new(100 + TIMELOCK_SLOTS);
+    test.pool = test.pool.clone().data(pool);
+
+    // it's an increase compared to the original fee, but a decrease compared
+    // to the effective pending one
+    let fee = Permillion { permillion: 4_000 };
+    test.set_pool_swap_fee(fee)?;
+
+    let pool = test.pool_copy();
+    assert_eq!(pool.swap_fee, fee);
+    assert!(!pool.has_pending_swap_fee());
+
+    Ok(())
+}
+
 #[derive(Clone, Debug, PartialEq)]
 struct Tester {
+    slot: u64,
     admin: AccountInfoWrapper,
     pool: AccountInfoWrapper,
 }
@@ -63,15 +155,53 @@ impl Default for Tester {
                 .owner(amm::ID)
                 .data(Pool {
                     admin: admin.key,
+                    dimension: 2,
+                    reserves: [
+                        Reserve {
+                            mint: Pubkey::new_unique(),
+                            vault: Pubkey::new_unique(),
+                            ..Default::default()
+                        },
+                        Reserve {
+                            mint: Pubkey::new_unique(),
+                            vault: Pubkey::new_unique(),
+                            ..Default::default()
+                        },
+                        Reserve::default(),
+                        Reserve::default(),
+                    ],
+                    swap_fee: Permillion { permillion: 2_000 },
+                    swap_fee_timelock_slots: TIMELOCK_SLOTS,
                     ..Default::default()
                 });
 
-        Self { admin, pool }
+        Self {
+            slot: 0,
+            admin,
+            pool,
+        }
     }
 }
 
 impl Tester {
+    fn slot(mut self, slot: u64) -> Self {
+        self.slot = slot;
+        self
+    }
+
+    fn with_liquidity(mut self) -> Self {
+        let mut pool = self.pool_copy();
+        pool.reserves[0].tokens = TokenAmount::new(1_000);
+        pool.reserves[1].tokens = TokenAmount::new(1_000);
+        self.pool = self.pool.clone().data(pool);
+        self
+    }
+
     fn set_pool_swap_fee(&mut self, fee: Permillion) -> Result<()> {
+        let syscalls = stub::Syscalls::new(CpiValidator);
+        syscalls.slot(self.slot);
+        syscalls.set();
+
         let mut ctx = self.context_wrapper();
         let mut accounts = ctx.accounts()?;
 
@@ -81,9 +211,25 @@ impl Tester {
         Ok(())
     }
 
+    fn pool_copy(&self) -> Pool {
+        Pool::try_deserialize(&mut self.pool.data.as_slice()).unwrap()
+    }
+
     fn context_wrapper(&mut self) -> ContextWrapper {
         ContextWrapper::new(amm::ID)
             .acc(&mut self.admin)
             .acc(&mut self.pool)
     }
 }
+
+struct CpiValidator;
+
+impl stub::ValidateCpis for CpiValidator {
+    fn validate_next_instruction(
+        &mut self,
+        ix: &Instruction,
+        _accounts: &[AccountInfo],
+    ) {
+        panic!("No instructions expected, got {:#?}", ix);
+    }
+}
@@ -0,0 +1,135 @@
+use ::amm::amm::set_pool_swap_fee_timelock;
+use ::amm::prelude::*;
+use anchortest::builder::*;
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn works() -> Result<()> {
+    let mut test = Tester::default();
+
+    test.set_pool_swap_fee_timelock(50_000)?;
+
+    let pool = test.pool_copy();
+    assert_eq!(pool.swap_fee_timelock_slots, 50_000);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn bounds_are_inclusive() -> Result<()> {
+    let mut test = Tester::default();
+
+    test.set_pool_swap_fee_timelock(consts::MIN_SWAP_FEE_TIMELOCK_SLOTS)?;
+    assert_eq!(
+        test.pool_copy().swap_fee_timelock_slots,
+        consts::MIN_SWAP_FEE_TIMELOCK_SLOTS
+    );
+
+    test.set_pool_swap_fee_timelock(consts::MAX_SWAP_FEE_TIMELOCK_SLOTS)?;
+    assert_eq!(
+        test.pool_copy().swap_fee_timelock_slots,
+        consts::MAX_SWAP_FEE_TIMELOCK_SLOTS
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn keeps_effective_slot_of_pending_fee() -> Result<()> {
+    let mut test = Tester::default();
+    let mut pool = test.pool_copy();
+    pool.pending_swap_fee = Permillion { permillion: 5_000 };
+    pool.pending_swap_fee_effective_at = Slot::new(100_000);
+    test.pool = test.pool.clone().data(pool);
+
+    test.set_pool_swap_fee_timelock(consts::MIN_SWAP_FEE_TIMELOCK_SLOTS)?;
+
+    let pool = test.pool_copy();
+    assert_eq!(pool.pending_swap_fee, Permillion { permillion: 5_000 });
+    assert_eq!(pool.pending_swap_fee_effective_at, Slot::new(100_000));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_timelock_is_out_of_bounds() -> Result<()> {
+    let mut test = Tester::default();
+
+    assert!(test
+        .set_pool_swap_fee_timelock(consts::MIN_SWAP_FEE_TIMELOCK_SLOTS - 1)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+    assert!(test
+        .set_pool_swap_fee_timelock(consts::MAX_SWAP_FEE_TIMELOCK_SLOTS + 1)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_signer_is_not_admin() -> Result<()> {
+    let mut test = Tester::default();
+    test.admin = AccountInfoWrapper::new().signer();
+
+    assert!(test
+        .set_pool_swap_fee_timelock(50_000)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    admin: AccountInfoWrapper,
+    pool: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let admin = AccountInfoWrapper::new().signer();
+        let pool =
+            AccountInfoWrapper::new()
+                .mutable()
+                .owner(amm::ID)
+                .data(Pool {
+                    admin: admin.key,
+                    swap_fee_timelock_slots:
+                        consts::DEFAULT_SWAP_FEE_TIMELOCK_SLOTS,
+                    ..Default::default()
+                });
+
+        Self { admin, pool }
+    }
+}
+
+impl Tester {
+    fn set_pool_swap_fee_timelock(
+        &mut self,
+        timelock_slots: u64,
+    ) -> Result<()> {
+        let mut ctx = ContextWrapper::new(amm::ID)
+            .acc(&mut self.admin)
+            .acc(&mut self.pool);
+        let mut accounts = ctx.accounts()?;
+
+        set_pool_swap_fee_timelock(ctx.build(&mut accounts), timelock_slots)?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(())
+    }
+
+    fn pool_copy(&self) -> Pool {
+        Pool::try_deserialize(&mut self.pool.data.as_slice()).unwrap()
+    }
+}
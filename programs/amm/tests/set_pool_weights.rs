@@ -0,0 +1,192 @@
+use ::amm::amm::set_pool_weights;
+use ::amm::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token;
+use anchortest::{builder::*, spl};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn works() -> Result<()> {
+    let mut test = Tester::default();
+
+    test.set_pool_weights(80, 20)?;
+
+    let pool = Pool::try_deserialize(&mut test.pool.data.as_slice())?;
+    assert_eq!(
+        pool.curve,
+        Curve::Weighted {
+            weight_a: 80,
+            weight_b: 20
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_signer_is_not_pool_admin() -> Result<()> {
+    let mut test = Tester::default();
+    test.admin = AccountInfoWrapper::new().signer();
+
+    assert!(test
+        .set_pool_weights(80, 20)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_pool_has_liquidity() -> Result<()> {
+    let mut test = Tester::default();
+    test.lp_mint = test
+        .lp_mint
+        .clone()
+        .pack(spl::mint::new(test.admin.key).supply(1));
+
+    assert!(test
+        .set_pool_weights(80, 20)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_weight_is_zero() -> Result<()> {
+    let mut test = Tester::default();
+
+    assert!(test
+        .set_pool_weights(0, 100)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_weights_do_not_sum_to_100() -> Result<()> {
+    let mut test = Tester::default();
+
+    assert!(test
+        .set_pool_weights(80, 30)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_pool_has_more_than_two_reserves() -> Result<()> {
+    let mut test = Tester::default();
+    let mut pool = Pool::try_deserialize(&mut test.pool.data.as_slice())?;
+    pool.dimension = 3;
+    test.pool = test.pool.clone().data(pool);
+
+    assert!(test
+        .set_pool_weights(80, 20)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_weight_is_out_of_curve_constraints() -> Result<()> {
+    let mut test = Tester::default();
+    test.constrain_weights(20, 80);
+
+    assert!(test
+        .set_pool_weights(90, 10)
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidArg"));
+
+    assert!(test.set_pool_weights(70, 30).is_ok());
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    admin: AccountInfoWrapper,
+    pool: AccountInfoWrapper,
+    lp_mint: AccountInfoWrapper,
+    curve_constraints: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let admin = AccountInfoWrapper::new().signer();
+        let lp_mint = AccountInfoWrapper::new()
+            .pack(spl::mint::new(admin.key))
+            .owner(token::ID);
+        let pool =
+            AccountInfoWrapper::new()
+                .mutable()
+                .owner(amm::ID)
+                .data(Pool {
+                    admin: admin.key,
+                    mint: lp_mint.key,
+                    dimension: 2,
+                    ..Default::default()
+                });
+        let curve_constraints = AccountInfoWrapper::pda(
+            amm::ID,
+            "curve_constraints",
+            &[CurveConstraints::PDA_SEED],
+        )
+        .owner(system_program::ID);
+
+        Self {
+            admin,
+            pool,
+            lp_mint,
+            curve_constraints,
+        }
+    }
+}
+
+impl Tester {
+    fn constrain_weights(&mut self, min_weight: u8, max_weight: u8) {
+        self.curve_constraints =
+            self.curve_constraints.clone().owner(amm::ID).data(
+                CurveConstraints {
+                    min_weight,
+                    max_weight,
+                    ..Default::default()
+                },
+            );
+    }
+
+    fn set_pool_weights(&mut self, weight_a: u8, weight_b: u8) -> Result<()> {
+        let mut ctx = self.context_wrapper();
+        let mut accounts = ctx.accounts()?;
+
+        set_pool_weights(ctx.build(&mut accounts), weight_a, weight_b)?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(())
+    }
+
+    fn context_wrapper(&mut self) -> ContextWrapper {
+        ContextWrapper::new(amm::ID)
+            .acc(&mut self.admin)
+            .acc(&mut self.pool)
+            .acc(&mut self.lp_mint)
+            .acc(&mut self.curve_constraints)
+    }
+}
@@ -1,5 +1,14 @@
-use ::amm::amm::swap;
-use ::amm::endpoints::{calculate_swap_fee, calculate_toll_in_lp_tokens};
+#[allow(dead_code)]
+mod return_data;
+
+use ::amm::amm::{
+    quote_swap, swap, swap_with_default_referral, swap_with_referral,
+    swap_with_round_trip_guard, swap_with_slippage_bps,
+};
+use ::amm::endpoints::{
+    calculate_fee_rebate, calculate_referral_share, calculate_swap_fee,
+    calculate_toll_in_lp_tokens, simulate_swap, SwapFeeShares, SwapQuote,
+};
 use ::amm::prelude::*;
 use anchor_lang::system_program;
 use anchor_spl::token;
@@ -9,6 +18,8 @@ use anchortest::{
     stub,
 };
 use pretty_assertions::assert_eq;
+use proptest::prelude::*;
+use return_data::ReturnData;
 use serial_test::serial;
 use solana_sdk::instruction::Instruction;
 use std::iter;
@@ -46,10 +57,12 @@ fn swaps_const_prod_two_reserves_no_discount() -> Result<()> {
     // 13_746 = 29_000 / K, whereas K = 20_000 * 20_000
     assert_eq!(pool_after.reserves[1].tokens.amount, 13_746);
 
-    // only these 3 values can change
+    // only these values can change
     pool_after.reserves[0].tokens = pool_before.reserves[0].tokens;
     pool_after.reserves[1].tokens = pool_before.reserves[1].tokens;
     pool_after.curve = pool_before.curve;
+    pool_after.lifetime_trade_fees = pool_before.lifetime_trade_fees;
+    pool_after.lifetime_owner_fees = pool_before.lifetime_owner_fees;
     assert_eq!(pool_before, pool_after);
 
     let supply_after = test.lp_supply();
@@ -95,10 +108,12 @@ fn swaps_stable_curve_three_reserves_no_discount() -> Result<()> {
     assert_eq!(pool_after.reserves[1].tokens.amount, 19_979_900_101);
     assert_eq!(pool_after.reserves[2].tokens.amount, 20_002_000_000);
 
-    // only these 3 values can change
+    // only these values can change
     pool_after.reserves[0].tokens = pool_before.reserves[0].tokens;
     pool_after.reserves[1].tokens = pool_before.reserves[1].tokens;
     pool_after.curve = pool_before.curve;
+    pool_after.lifetime_trade_fees = pool_before.lifetime_trade_fees;
+    pool_after.lifetime_owner_fees = pool_before.lifetime_owner_fees;
     assert_eq!(pool_before, pool_after);
 
     let supply_after = test.lp_supply();
@@ -203,8 +218,8 @@ fn ignores_discount_if_not_valid_anymore() -> Result<()> {
 
 #[test]
 #[serial]
-fn fails_if_sell_amount_is_zero() -> Result<()> {
-    let pool = Pool {
+fn swaps_with_fee_rebate_during_rebate_period() -> Result<()> {
+    let pool_before = Pool {
         dimension: 2,
         program_toll_wallet: Pubkey::new_unique(),
         swap_fee: Permillion::from_percent(9),
@@ -215,122 +230,128 @@ fn fails_if_sell_amount_is_zero() -> Result<()> {
         ..Default::default()
     };
 
-    let mut test = Tester::no_discount(pool.clone());
+    let mut test = Tester::no_discount(pool_before.clone())
+        .fee_rebate(FeeRebate {
+            share: Permillion::from_percent(50),
+            starts_at: Slot::new(100),
+            ends_at: Slot::new(200),
+        })
+        .slot(Slot::new(150));
 
-    let error = test
-        .swap(
-            TokenAmount::new(0),
-            TokenAmount::new(9_500),
-            pool.reserves[0].mint,
-            pool.reserves[1].mint,
-        )
-        .unwrap_err()
-        .to_string();
-    assert!(error.contains("InvalidArg"));
+    let supply_before = test.lp_supply();
 
-    Ok(())
-}
+    test.swap(
+        TokenAmount::new(10_000),
+        TokenAmount::new(6_254),
+        pool_before.reserves[0].mint,
+        pool_before.reserves[1].mint,
+    )?;
 
-#[test]
-#[serial]
-fn fails_if_slippage_exceeded() -> Result<()> {
-    let pool = Pool {
-        dimension: 2,
-        program_toll_wallet: Pubkey::new_unique(),
-        swap_fee: Permillion::from_percent(9),
-        reserves: create_two_reserves(
-            TokenAmount::new(20_000),
-            TokenAmount::new(20_000),
-        ),
-        ..Default::default()
-    };
+    let pool = test.pool_copy();
 
-    let mut test = Tester::no_discount(pool.clone());
+    // the swap fee is 900, half of which is rebated, so the user transfers
+    // only 10_000 - 450 to the vault
+    assert_eq!(pool.reserves[0].tokens.amount, 29_550);
+    // the rebate doesn't affect the amount of bought tokens, see
+    // swaps_const_prod_two_reserves_no_discount
+    assert_eq!(pool.reserves[1].tokens.amount, 13_746);
 
-    let error = test
-        .swap(
-            TokenAmount::new(10_000),
-            TokenAmount::new(20_000),
-            pool.reserves[0].mint,
-            pool.reserves[1].mint,
-        )
-        .unwrap_err()
-        .to_string();
-    assert!(error.contains("SlippageExceeded"));
+    // the toll is calculated from the fee which the pool actually collected
+    let supply_after = test.lp_supply();
+    assert_eq!(supply_before + 25, supply_after);
 
     Ok(())
 }
 
 #[test]
 #[serial]
-fn updates_stable_curve_invariant() -> Result<()> {
-    let pool = Pool {
+fn ignores_fee_rebate_outside_of_rebate_period() -> Result<()> {
+    let pool_before = Pool {
         dimension: 2,
         program_toll_wallet: Pubkey::new_unique(),
-        swap_fee: Permillion::from_percent(0),
+        swap_fee: Permillion::from_percent(9),
         reserves: create_two_reserves(
             TokenAmount::new(20_000),
             TokenAmount::new(20_000),
         ),
-        curve: Curve::Stable {
-            amplifier: 2,
-            invariant: Default::default(),
-        },
         ..Default::default()
     };
 
-    let mut test = Tester::no_discount(pool.clone());
+    for slot in [99, 201] {
+        let mut test = Tester::no_discount(pool_before.clone())
+            .fee_rebate(FeeRebate {
+                share: Permillion::from_percent(50),
+                starts_at: Slot::new(100),
+                ends_at: Slot::new(200),
+            })
+            .slot(Slot::new(slot));
 
-    let pool_before = test.pool_copy();
+        let supply_before = test.lp_supply();
 
-    test.swap(
-        TokenAmount::new(10_000),
-        TokenAmount::new(8_000),
-        pool.reserves[0].mint,
-        pool.reserves[1].mint,
-    )?;
+        test.swap(
+            TokenAmount::new(10_000),
+            TokenAmount::new(6_254),
+            pool_before.reserves[0].mint,
+            pool_before.reserves[1].mint,
+        )?;
 
-    let pool_after = test.pool_copy();
+        let pool = test.pool_copy();
+        assert_eq!(pool.reserves[0].tokens.amount, 30_000);
+        assert_eq!(pool.reserves[1].tokens.amount, 13_746);
 
-    // invariant changes because fees are collected
-    assert_ne!(pool_before.curve, pool_after.curve);
+        let supply_after = test.lp_supply();
+        assert_eq!(supply_before + 50, supply_after);
+    }
 
     Ok(())
 }
 
 #[test]
 #[serial]
-fn skips_lp_mint_if_fee_would_not_allow_for_it() -> Result<()> {
-    let pool = Pool {
+fn ignores_fee_rebate_if_account_is_not_provided() -> Result<()> {
+    let pool_before = Pool {
         dimension: 2,
         program_toll_wallet: Pubkey::new_unique(),
-        swap_fee: Permillion::from_percent(1), // tiny fee
+        swap_fee: Permillion::from_percent(9),
         reserves: create_two_reserves(
-            TokenAmount::new(10),
-            TokenAmount::new(10),
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
         ),
         ..Default::default()
     };
-    let mut test = Tester::no_discount(pool.clone());
+
+    // clients which don't know about rebates don't provide the account
+    let mut test = Tester::no_discount(pool_before.clone())
+        .fee_rebate(FeeRebate {
+            share: Permillion::from_percent(50),
+            starts_at: Slot::new(100),
+            ends_at: Slot::new(200),
+        })
+        .without_fee_rebate_account()
+        .slot(Slot::new(150));
 
     let supply_before = test.lp_supply();
 
     test.swap(
-        TokenAmount::new(2),
-        TokenAmount::new(0),
-        pool.reserves[0].mint,
-        pool.reserves[1].mint,
+        TokenAmount::new(10_000),
+        TokenAmount::new(6_254),
+        pool_before.reserves[0].mint,
+        pool_before.reserves[1].mint,
     )?;
 
+    let pool = test.pool_copy();
+    assert_eq!(pool.reserves[0].tokens.amount, 30_000);
+    assert_eq!(pool.reserves[1].tokens.amount, 13_746);
+
     let supply_after = test.lp_supply();
-    assert_eq!(supply_before, supply_after);
+    assert_eq!(supply_before + 50, supply_after);
 
     Ok(())
 }
 
 #[test]
 #[serial]
-fn fails_if_pool_signer_mismatches() -> Result<()> {
+fn fails_if_remaining_account_is_not_pool_fee_rebate() -> Result<()> {
     let pool = Pool {
         dimension: 2,
         program_toll_wallet: Pubkey::new_unique(),
@@ -343,29 +364,31 @@ fn fails_if_pool_signer_mismatches() -> Result<()> {
     };
 
     let mut test = Tester::no_discount(pool.clone());
-    test.pool_signer = AccountInfoWrapper::new();
+    test.fee_rebate.key = Pubkey::new_unique();
 
     let error = test
         .swap(
             TokenAmount::new(10_000),
-            TokenAmount::new(9_500),
+            TokenAmount::new(6_254),
             pool.reserves[0].mint,
             pool.reserves[1].mint,
         )
         .unwrap_err()
         .to_string();
-    assert!(error.contains("ConstraintSeeds"));
+    assert!(error.contains("InvalidAccountInput"));
 
     Ok(())
 }
 
 #[test]
 #[serial]
-fn fails_if_sell_wallet_mint_matches_buy_wallet_mint() -> Result<()> {
-    let pool = Pool {
+fn swaps_with_pending_swap_fee_only_once_effective() -> Result<()> {
+    let pool_before = Pool {
         dimension: 2,
         program_toll_wallet: Pubkey::new_unique(),
         swap_fee: Permillion::from_percent(9),
+        pending_swap_fee: Permillion::from_percent(18),
+        pending_swap_fee_effective_at: Slot::new(100),
         reserves: create_two_reserves(
             TokenAmount::new(20_000),
             TokenAmount::new(20_000),
@@ -373,29 +396,40 @@ fn fails_if_sell_wallet_mint_matches_buy_wallet_mint() -> Result<()> {
         ..Default::default()
     };
 
-    let mut test = Tester::no_discount(pool.clone());
-    test.sell_wallet = test.sell_wallet.clone().pack(
-        spl::token_account::from_acc_info(&test.sell_wallet.to_account_info())
-            .mint(pool.reserves[1].mint),
-    );
+    // same as swaps_const_prod_two_reserves_no_discount
+    let mut test = Tester::no_discount(pool_before.clone()).slot(Slot::new(99));
+    test.swap(
+        TokenAmount::new(10_000),
+        TokenAmount::new(6_254),
+        pool_before.reserves[0].mint,
+        pool_before.reserves[1].mint,
+    )?;
+    let pool_after = test.pool_copy();
+    assert_eq!(pool_after.reserves[1].tokens.amount, 13_746);
+    assert_eq!(pool_after.swap_fee, Permillion::from_percent(9));
+    assert!(pool_after.has_pending_swap_fee());
 
-    let error = test
-        .swap(
-            TokenAmount::new(10_000),
-            TokenAmount::new(9_500),
-            pool.reserves[0].mint,
-            pool.reserves[1].mint,
-        )
-        .unwrap_err()
-        .to_string();
-    assert!(error.contains("InvalidAccountInput"));
+    let mut test =
+        Tester::no_discount(pool_before.clone()).slot(Slot::new(100));
+    test.swap(
+        TokenAmount::new(10_000),
+        TokenAmount::new(5_815),
+        pool_before.reserves[0].mint,
+        pool_before.reserves[1].mint,
+    )?;
+    let pool_after = test.pool_copy();
+    assert_eq!(pool_after.reserves[0].tokens.amount, 30_000);
+    // 14_185 = 20_000 - floor(20_000 - 20_000 * 20_000 / 28_200)
+    assert_eq!(pool_after.reserves[1].tokens.amount, 14_185);
+    assert_eq!(pool_after.swap_fee, Permillion::from_percent(18));
+    assert!(!pool_after.has_pending_swap_fee());
 
     Ok(())
 }
 
 #[test]
 #[serial]
-fn fails_if_sell_vault_mint_not_eq_sell_wallet_mint() -> Result<()> {
+fn fails_if_sell_amount_is_zero() -> Result<()> {
     let pool = Pool {
         dimension: 2,
         program_toll_wallet: Pubkey::new_unique(),
@@ -408,28 +442,24 @@ fn fails_if_sell_vault_mint_not_eq_sell_wallet_mint() -> Result<()> {
     };
 
     let mut test = Tester::no_discount(pool.clone());
-    test.sell_vault = test.sell_vault.clone().pack(
-        spl::token_account::from_acc_info(&test.sell_vault.to_account_info())
-            .mint(Pubkey::new_unique()),
-    );
 
     let error = test
         .swap(
-            TokenAmount::new(10_000),
+            TokenAmount::new(0),
             TokenAmount::new(9_500),
             pool.reserves[0].mint,
             pool.reserves[1].mint,
         )
         .unwrap_err()
         .to_string();
-    assert!(error.contains("InvalidAccountInput"));
+    assert!(error.contains("InvalidArg"));
 
     Ok(())
 }
 
 #[test]
 #[serial]
-fn fails_if_buy_wallet_mint_not_eq_buy_vault_mint() -> Result<()> {
+fn fails_if_slippage_exceeded() -> Result<()> {
     let pool = Pool {
         dimension: 2,
         program_toll_wallet: Pubkey::new_unique(),
@@ -442,28 +472,25 @@ fn fails_if_buy_wallet_mint_not_eq_buy_vault_mint() -> Result<()> {
     };
 
     let mut test = Tester::no_discount(pool.clone());
-    test.buy_wallet = test.buy_wallet.clone().pack(
-        spl::token_account::from_acc_info(&test.buy_wallet.to_account_info())
-            .mint(Pubkey::new_unique()),
-    );
 
     let error = test
         .swap(
             TokenAmount::new(10_000),
-            TokenAmount::new(9_500),
+            TokenAmount::new(20_000),
             pool.reserves[0].mint,
             pool.reserves[1].mint,
         )
         .unwrap_err()
         .to_string();
-    assert!(error.contains("InvalidAccountInput"));
+    assert!(error.contains("SlippageExceeded"));
 
     Ok(())
 }
 
 #[test]
 #[serial]
-fn fails_if_buy_vault_does_not_match_pool_reserve_vault() -> Result<()> {
+fn fails_if_slippage_bps_exceeded_on_shallow_pool() -> Result<()> {
+    // the pool is shallow relative to the trade, hence the price impact
     let pool = Pool {
         dimension: 2,
         program_toll_wallet: Pubkey::new_unique(),
@@ -476,26 +503,27 @@ fn fails_if_buy_vault_does_not_match_pool_reserve_vault() -> Result<()> {
     };
 
     let mut test = Tester::no_discount(pool.clone());
-    test.buy_vault.key = Pubkey::new_unique();
 
+    // spot price is 1:1, so we'd expect 10_000 but only get 6_254, which is
+    // ~37.5% slippage
     let error = test
-        .swap(
+        .swap_with_slippage_bps(
             TokenAmount::new(10_000),
-            TokenAmount::new(9_500),
+            1_000,
             pool.reserves[0].mint,
             pool.reserves[1].mint,
         )
         .unwrap_err()
         .to_string();
-    assert!(error.contains("InvalidAccountInput"));
+    assert!(error.contains("SlippageExceeded"));
 
     Ok(())
 }
 
 #[test]
 #[serial]
-fn fails_if_sell_vault_does_not_match_pool_reserve_vault() -> Result<()> {
-    let pool = Pool {
+fn swaps_if_slippage_bps_not_exceeded_on_shallow_pool() -> Result<()> {
+    let pool_before = Pool {
         dimension: 2,
         program_toll_wallet: Pubkey::new_unique(),
         swap_fee: Permillion::from_percent(9),
@@ -506,26 +534,25 @@ fn fails_if_sell_vault_does_not_match_pool_reserve_vault() -> Result<()> {
         ..Default::default()
     };
 
-    let mut test = Tester::no_discount(pool.clone());
-    test.sell_vault.key = Pubkey::new_unique();
+    let mut test = Tester::no_discount(pool_before.clone());
 
-    let error = test
-        .swap(
-            TokenAmount::new(10_000),
-            TokenAmount::new(9_500),
-            pool.reserves[0].mint,
-            pool.reserves[1].mint,
-        )
-        .unwrap_err()
-        .to_string();
-    assert!(error.contains("InvalidAccountInput"));
+    test.swap_with_slippage_bps(
+        TokenAmount::new(10_000),
+        4_000,
+        pool_before.reserves[0].mint,
+        pool_before.reserves[1].mint,
+    )?;
+
+    let pool_after = test.pool_copy();
+    assert_eq!(pool_after.reserves[0].tokens.amount, 30_000);
+    assert_eq!(pool_after.reserves[1].tokens.amount, 13_746);
 
     Ok(())
 }
 
 #[test]
 #[serial]
-fn fails_if_lp_mint_mismatches_pool_mint() -> Result<()> {
+fn fails_if_slippage_bps_is_more_than_100_percent() -> Result<()> {
     let pool = Pool {
         dimension: 2,
         program_toll_wallet: Pubkey::new_unique(),
@@ -538,55 +565,67 @@ fn fails_if_lp_mint_mismatches_pool_mint() -> Result<()> {
     };
 
     let mut test = Tester::no_discount(pool.clone());
-    test.lp_mint.key = Pubkey::new_unique();
 
     let error = test
-        .swap(
+        .swap_with_slippage_bps(
             TokenAmount::new(10_000),
-            TokenAmount::new(9_500),
+            10_001,
             pool.reserves[0].mint,
             pool.reserves[1].mint,
         )
         .unwrap_err()
         .to_string();
-    assert!(error.contains("InvalidAccountInput"));
+    assert!(error.contains("InvalidArg"));
 
     Ok(())
 }
 
 #[test]
 #[serial]
-fn fails_if_lp_mint_supply_is_zero() -> Result<()> {
-    let pool = Pool {
+fn fails_if_round_trip_value_is_lost_on_imbalanced_pool() -> Result<()> {
+    let pool_before = Pool {
         dimension: 2,
         program_toll_wallet: Pubkey::new_unique(),
         swap_fee: Permillion::from_percent(9),
-        reserves: create_two_reserves(TokenAmount::new(0), TokenAmount::new(0)),
+        reserves: create_two_reserves(
+            TokenAmount::new(1_000_000_000),
+            TokenAmount::new(100),
+        ),
         ..Default::default()
     };
 
-    let mut test = Tester::no_discount(pool.clone());
-    test.lp_mint = test.lp_mint.clone().pack(
-        spl::mint::from_acc_info(&test.lp_mint.to_account_info()).supply(0),
-    );
+    let mut test = Tester::no_discount(pool_before.clone());
 
+    // 1.3466 buy tokens are rounded down to 1, which after the swap is worth
+    // 1_015_000_000 / 99 ~ 10_252_525 sell tokens, ie. ~68% of the sell amount
     let error = test
-        .swap(
-            TokenAmount::new(10_000),
-            TokenAmount::new(9_500),
-            pool.reserves[0].mint,
-            pool.reserves[1].mint,
+        .swap_with_round_trip_guard(
+            TokenAmount::new(15_000_000),
+            TokenAmount::new(1),
+            9_000,
+            pool_before.reserves[0].mint,
+            pool_before.reserves[1].mint,
         )
         .unwrap_err()
         .to_string();
-    assert!(error.contains("InvalidAccountInput"));
+    assert!(error.contains("SlippageExceeded"));
+    assert_eq!(test.pool_copy(), pool_before);
+
+    // the same swap passes the minimum buy amount alone
+    test.swap(
+        TokenAmount::new(15_000_000),
+        TokenAmount::new(1),
+        pool_before.reserves[0].mint,
+        pool_before.reserves[1].mint,
+    )?;
+    assert_eq!(test.pool_copy().reserves[1].tokens.amount, 99);
 
     Ok(())
 }
 
 #[test]
 #[serial]
-fn prints_lp_token_supply() -> Result<()> {
+fn swaps_if_round_trip_value_is_kept() -> Result<()> {
     let pool_before = Pool {
         dimension: 2,
         program_toll_wallet: Pubkey::new_unique(),
@@ -600,52 +639,1056 @@ fn prints_lp_token_supply() -> Result<()> {
 
     let mut test = Tester::no_discount(pool_before.clone());
 
-    let supply_before = test.lp_supply();
-
-    let syscalls = test.swap(
+    // 6_254 bought tokens are worth 6_254 * 30_000 / 13_746 ~ 13_648 sell
+    // tokens after the swap
+    test.swap_with_round_trip_guard(
         TokenAmount::new(10_000),
-        TokenAmount::new(6_000),
+        TokenAmount::new(6_254),
+        9_000,
         pool_before.reserves[0].mint,
         pool_before.reserves[1].mint,
     )?;
 
-    let mut pool_after = test.pool_copy();
-
+    let pool_after = test.pool_copy();
     assert_eq!(pool_after.reserves[0].tokens.amount, 30_000);
     assert_eq!(pool_after.reserves[1].tokens.amount, 13_746);
 
-    // only these 3 values can change
-    pool_after.reserves[0].tokens = pool_before.reserves[0].tokens;
-    pool_after.reserves[1].tokens = pool_before.reserves[1].tokens;
-    pool_after.curve = pool_before.curve;
-    assert_eq!(pool_before, pool_after);
-
-    let supply_after = test.lp_supply();
-
-    assert_eq!(supply_before + 50, supply_after);
-
-    let logs = syscalls.logs();
-    assert!(logs
-        .into_iter()
-        .any(|log| log == format!("lp-supply={}", supply_after)));
-
     Ok(())
 }
 
-#[derive(Clone, Debug, PartialEq)]
-struct Tester {
-    time: Slot,
-    user: AccountInfoWrapper,
-    discount: AccountInfoWrapper,
-    pool: AccountInfoWrapper,
-    pool_signer: AccountInfoWrapper,
-    sell_wallet: AccountInfoWrapper,
-    buy_wallet: AccountInfoWrapper,
-    sell_vault: AccountInfoWrapper,
-    buy_vault: AccountInfoWrapper,
-    lp_mint: AccountInfoWrapper,
-    program_toll_wallet: AccountInfoWrapper,
+#[test]
+#[serial]
+fn updates_stable_curve_invariant() -> Result<()> {
+    let pool = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(0),
+        reserves: create_two_reserves(
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
+        ),
+        curve: Curve::Stable {
+            amplifier: 2,
+            invariant: Default::default(),
+        },
+        ..Default::default()
+    };
+
+    let mut test = Tester::no_discount(pool.clone());
+
+    let pool_before = test.pool_copy();
+
+    test.swap(
+        TokenAmount::new(10_000),
+        TokenAmount::new(8_000),
+        pool.reserves[0].mint,
+        pool.reserves[1].mint,
+    )?;
+
+    let pool_after = test.pool_copy();
+
+    // invariant changes because fees are collected
+    assert_ne!(pool_before.curve, pool_after.curve);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn skips_lp_mint_if_fee_would_not_allow_for_it() -> Result<()> {
+    let pool = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(1), // tiny fee
+        reserves: create_two_reserves(
+            TokenAmount::new(10),
+            TokenAmount::new(10),
+        ),
+        ..Default::default()
+    };
+    let mut test = Tester::no_discount(pool.clone());
+
+    let supply_before = test.lp_supply();
+
+    test.swap(
+        TokenAmount::new(2),
+        TokenAmount::new(0),
+        pool.reserves[0].mint,
+        pool.reserves[1].mint,
+    )?;
+
+    let supply_after = test.lp_supply();
+    assert_eq!(supply_before, supply_after);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_pool_signer_mismatches() -> Result<()> {
+    let pool = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(9),
+        reserves: create_two_reserves(
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
+        ),
+        ..Default::default()
+    };
+
+    let mut test = Tester::no_discount(pool.clone());
+    test.pool_signer = AccountInfoWrapper::new();
+
+    let error = test
+        .swap(
+            TokenAmount::new(10_000),
+            TokenAmount::new(9_500),
+            pool.reserves[0].mint,
+            pool.reserves[1].mint,
+        )
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("ConstraintSeeds"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_sell_wallet_mint_matches_buy_wallet_mint() -> Result<()> {
+    let pool = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(9),
+        reserves: create_two_reserves(
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
+        ),
+        ..Default::default()
+    };
+
+    let mut test = Tester::no_discount(pool.clone());
+    test.sell_wallet = test.sell_wallet.clone().pack(
+        spl::token_account::from_acc_info(&test.sell_wallet.to_account_info())
+            .mint(pool.reserves[1].mint),
+    );
+
+    let error = test
+        .swap(
+            TokenAmount::new(10_000),
+            TokenAmount::new(9_500),
+            pool.reserves[0].mint,
+            pool.reserves[1].mint,
+        )
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_sell_wallet_is_buy_wallet() -> Result<()> {
+    let pool = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(9),
+        reserves: create_two_reserves(
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
+        ),
+        ..Default::default()
+    };
+
+    let mut test = Tester::no_discount(pool.clone());
+    test.buy_wallet = test.sell_wallet.clone();
+
+    let error = test
+        .swap(
+            TokenAmount::new(10_000),
+            TokenAmount::new(9_500),
+            pool.reserves[0].mint,
+            pool.reserves[1].mint,
+        )
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_sell_vault_mint_not_eq_sell_wallet_mint() -> Result<()> {
+    let pool = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(9),
+        reserves: create_two_reserves(
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
+        ),
+        ..Default::default()
+    };
+
+    let mut test = Tester::no_discount(pool.clone());
+    test.sell_vault = test.sell_vault.clone().pack(
+        spl::token_account::from_acc_info(&test.sell_vault.to_account_info())
+            .mint(Pubkey::new_unique()),
+    );
+
+    let error = test
+        .swap(
+            TokenAmount::new(10_000),
+            TokenAmount::new(9_500),
+            pool.reserves[0].mint,
+            pool.reserves[1].mint,
+        )
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_buy_wallet_mint_not_eq_buy_vault_mint() -> Result<()> {
+    let pool = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(9),
+        reserves: create_two_reserves(
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
+        ),
+        ..Default::default()
+    };
+
+    let mut test = Tester::no_discount(pool.clone());
+    test.buy_wallet = test.buy_wallet.clone().pack(
+        spl::token_account::from_acc_info(&test.buy_wallet.to_account_info())
+            .mint(Pubkey::new_unique()),
+    );
+
+    let error = test
+        .swap(
+            TokenAmount::new(10_000),
+            TokenAmount::new(9_500),
+            pool.reserves[0].mint,
+            pool.reserves[1].mint,
+        )
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_buy_vault_does_not_match_pool_reserve_vault() -> Result<()> {
+    let pool = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(9),
+        reserves: create_two_reserves(
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
+        ),
+        ..Default::default()
+    };
+
+    let mut test = Tester::no_discount(pool.clone());
+    test.buy_vault.key = Pubkey::new_unique();
+
+    let error = test
+        .swap(
+            TokenAmount::new(10_000),
+            TokenAmount::new(9_500),
+            pool.reserves[0].mint,
+            pool.reserves[1].mint,
+        )
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_sell_vault_does_not_match_pool_reserve_vault() -> Result<()> {
+    let pool = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(9),
+        reserves: create_two_reserves(
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
+        ),
+        ..Default::default()
+    };
+
+    let mut test = Tester::no_discount(pool.clone());
+    test.sell_vault.key = Pubkey::new_unique();
+
+    let error = test
+        .swap(
+            TokenAmount::new(10_000),
+            TokenAmount::new(9_500),
+            pool.reserves[0].mint,
+            pool.reserves[1].mint,
+        )
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_lp_mint_mismatches_pool_mint() -> Result<()> {
+    let pool = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(9),
+        reserves: create_two_reserves(
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
+        ),
+        ..Default::default()
+    };
+
+    let mut test = Tester::no_discount(pool.clone());
+    test.lp_mint.key = Pubkey::new_unique();
+
+    let error = test
+        .swap(
+            TokenAmount::new(10_000),
+            TokenAmount::new(9_500),
+            pool.reserves[0].mint,
+            pool.reserves[1].mint,
+        )
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_program_toll_wallet_is_not_pool_current_one() -> Result<()> {
+    let pool = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(9),
+        reserves: create_two_reserves(
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
+        ),
+        ..Default::default()
+    };
+
+    // eg. the toll wallet which the pool used before it was rotated with
+    // set_pool_program_toll_wallet
+    let mut test = Tester::no_discount(pool.clone());
+    test.program_toll_wallet.key = Pubkey::new_unique();
+
+    let error = test
+        .swap(
+            TokenAmount::new(10_000),
+            TokenAmount::new(6_254),
+            pool.reserves[0].mint,
+            pool.reserves[1].mint,
+        )
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn swaps_and_mints_toll_to_rotated_program_toll_wallet() -> Result<()> {
+    let old_program_toll_wallet = Pubkey::new_unique();
+    let mut pool = Pool {
+        dimension: 2,
+        program_toll_wallet: old_program_toll_wallet,
+        swap_fee: Permillion::from_percent(9),
+        reserves: create_two_reserves(
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
+        ),
+        ..Default::default()
+    };
+    // eg. the toll wallet was rotated with set_pool_program_toll_wallet
+    pool.program_toll_wallet = Pubkey::new_unique();
+
+    let mut test = Tester::no_discount(pool.clone());
+    assert_eq!(test.program_toll_wallet.key, pool.program_toll_wallet);
+    assert_ne!(test.program_toll_wallet.key, old_program_toll_wallet);
+
+    let supply_before = test.lp_supply();
+    let buy_wallet_before = test.buy_wallet_amount();
+    assert_eq!(test.program_toll_wallet_amount(), 0);
+
+    test.swap(
+        TokenAmount::new(10_000),
+        TokenAmount::new(6_254),
+        pool.reserves[0].mint,
+        pool.reserves[1].mint,
+    )?;
+
+    let sell_vault =
+        spl::token_account::from_acc_info(&test.sell_vault.to_account_info());
+    assert_eq!(sell_vault.amount, 30_000);
+    let buy_vault =
+        spl::token_account::from_acc_info(&test.buy_vault.to_account_info());
+    assert_eq!(buy_vault.amount, 13_746);
+    assert_eq!(test.buy_wallet_amount(), buy_wallet_before + 6_254);
+
+    // the whole toll goes to the wallet the pool was rotated to
+    assert_eq!(test.program_toll_wallet_amount(), 50);
+    assert_eq!(test.lp_supply(), supply_before + 50);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_lp_mint_supply_is_zero() -> Result<()> {
+    let pool = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(9),
+        reserves: create_two_reserves(TokenAmount::new(0), TokenAmount::new(0)),
+        ..Default::default()
+    };
+
+    let mut test = Tester::no_discount(pool.clone());
+    test.lp_mint = test.lp_mint.clone().pack(
+        spl::mint::from_acc_info(&test.lp_mint.to_account_info()).supply(0),
+    );
+
+    let error = test
+        .swap(
+            TokenAmount::new(10_000),
+            TokenAmount::new(9_500),
+            pool.reserves[0].mint,
+            pool.reserves[1].mint,
+        )
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn prints_lp_token_supply() -> Result<()> {
+    let pool_before = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(9),
+        reserves: create_two_reserves(
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
+        ),
+        ..Default::default()
+    };
+
+    let mut test = Tester::no_discount(pool_before.clone());
+
+    let supply_before = test.lp_supply();
+
+    let syscalls = test.swap(
+        TokenAmount::new(10_000),
+        TokenAmount::new(6_000),
+        pool_before.reserves[0].mint,
+        pool_before.reserves[1].mint,
+    )?;
+
+    let mut pool_after = test.pool_copy();
+
+    assert_eq!(pool_after.reserves[0].tokens.amount, 30_000);
+    assert_eq!(pool_after.reserves[1].tokens.amount, 13_746);
+
+    // only these values can change
+    pool_after.reserves[0].tokens = pool_before.reserves[0].tokens;
+    pool_after.reserves[1].tokens = pool_before.reserves[1].tokens;
+    pool_after.curve = pool_before.curve;
+    pool_after.lifetime_trade_fees = pool_before.lifetime_trade_fees;
+    pool_after.lifetime_owner_fees = pool_before.lifetime_owner_fees;
+    assert_eq!(pool_before, pool_after);
+
+    let supply_after = test.lp_supply();
+
+    assert_eq!(supply_before + 50, supply_after);
+
+    let logs = syscalls.logs();
+    assert!(logs
+        .into_iter()
+        .any(|log| log == format!("lp-supply={}", supply_after)));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn accumulates_lifetime_fees_across_swaps() -> Result<()> {
+    let pool_before = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(9),
+        reserves: create_two_reserves(
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
+        ),
+        ..Default::default()
+    };
+    let sell_mint = pool_before.reserves[0].mint;
+    let buy_mint = pool_before.reserves[1].mint;
+
+    let mut test = Tester::no_discount(pool_before.clone());
+
+    // 9% of 10_000, the program toll is 50 LP tokens, see
+    // swaps_const_prod_two_reserves_no_discount
+    test.swap(
+        TokenAmount::new(10_000),
+        TokenAmount::new(0),
+        sell_mint,
+        buy_mint,
+    )?;
+    let pool_after = test.pool_copy();
+    assert_eq!(pool_after.lifetime_trade_fees[0], TokenAmount::new(900));
+    assert_eq!(pool_after.lifetime_owner_fees, TokenAmount::new(50));
+
+    for _ in 0..2 {
+        test.swap(
+            TokenAmount::new(1_000),
+            TokenAmount::new(0),
+            sell_mint,
+            buy_mint,
+        )?;
+    }
+    let syscalls = test.swap(
+        TokenAmount::new(1_000),
+        TokenAmount::new(0),
+        sell_mint,
+        buy_mint,
+    )?;
+
+    // 900 + 3 * 9% of 1_000
+    let pool_after = test.pool_copy();
+    assert_eq!(pool_after.lifetime_trade_fees[0], TokenAmount::new(1_170));
+    // nothing has been sold from the other reserve
+    assert_eq!(pool_after.lifetime_trade_fees[1], TokenAmount::new(0));
+    assert_eq!(
+        pool_after.lifetime_owner_fees.amount,
+        test.program_toll_wallet_amount()
+    );
+    assert!(pool_after.lifetime_owner_fees.amount > 50);
+
+    // each swap logs the new totals
+    let logs = syscalls.logs();
+    assert!(logs.into_iter().any(|log| log
+        == format!(
+            "lifetime-trade-fees=1170 mint={} lifetime-owner-fees={}",
+            sell_mint, pool_after.lifetime_owner_fees.amount
+        )));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn swaps_with_referral_and_routes_share_of_toll_to_referrer() -> Result<()> {
+    let pool_before = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(9),
+        reserves: create_two_reserves(
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
+        ),
+        ..Default::default()
+    };
+
+    let mut test = Tester::no_discount(pool_before.clone())
+        .referral_share(Permillion::from_percent(40));
+
+    let supply_before = test.lp_supply();
+
+    test.swap_with_referral(
+        TokenAmount::new(10_000),
+        TokenAmount::new(6_254),
+        pool_before.reserves[0].mint,
+        pool_before.reserves[1].mint,
+    )?;
+
+    let pool_after = test.pool_copy();
+    assert_eq!(pool_after.reserves[0].tokens.amount, 30_000);
+    assert_eq!(pool_after.reserves[1].tokens.amount, 13_746);
+
+    // the toll is the same as without referral, see
+    // swaps_const_prod_two_reserves_no_discount
+    let supply_after = test.lp_supply();
+    assert_eq!(supply_before + 50, supply_after);
+
+    // 40% of the toll goes to the referrer
+    assert_eq!(test.referrer_wallet_amount(), 20);
+    assert_eq!(test.program_toll_wallet_amount(), 30);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn quote_matches_executed_swap_with_referral() -> Result<()> {
+    let pool_before = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(9),
+        reserves: create_two_reserves(
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
+        ),
+        ..Default::default()
+    };
+    let sell_mint = pool_before.reserves[0].mint;
+    let buy_mint = pool_before.reserves[1].mint;
+
+    let mut test = Tester::no_discount(pool_before.clone())
+        .referral_share(Permillion::from_percent(40));
+
+    let sell = TokenAmount::new(10_000);
+    let supply_before = test.lp_supply();
+    let pool_before_quote = test.pool_copy();
+    let quote = test.quote_swap(sell, sell_mint, buy_mint, true)?;
+    assert_eq!(test.pool_copy(), pool_before_quote);
+
+    let buy_wallet_before = test.buy_wallet_amount();
+    test.swap_with_referral(sell, quote.bought, sell_mint, buy_mint)?;
+
+    assert_eq!(quote.bought, TokenAmount::new(6_254));
+    assert_eq!(
+        test.buy_wallet_amount() - buy_wallet_before,
+        quote.bought.amount
+    );
+    // 9% of the sell amount
+    assert_eq!(quote.swap_fee, TokenAmount::new(900));
+    assert_eq!(quote.fee_rebate, TokenAmount::new(0));
+    assert_eq!(
+        test.pool_copy().reserves[0].tokens.amount,
+        20_000 + sell.amount
+    );
+    assert_eq!(quote.program_toll.amount, test.program_toll_wallet_amount());
+    assert_eq!(quote.referral.amount, test.referrer_wallet_amount());
+    assert_eq!(
+        supply_before + quote.program_toll.amount + quote.referral.amount,
+        test.lp_supply()
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn quote_matches_executed_swap_with_fee_rebate() -> Result<()> {
+    let pool_before = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(9),
+        reserves: create_two_reserves(
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
+        ),
+        ..Default::default()
+    };
+    let sell_mint = pool_before.reserves[0].mint;
+    let buy_mint = pool_before.reserves[1].mint;
+
+    let mut test = Tester::no_discount(pool_before.clone())
+        .fee_rebate(FeeRebate {
+            share: Permillion::from_percent(50),
+            starts_at: Slot::new(100),
+            ends_at: Slot::new(200),
+        })
+        .referral_share(Permillion::from_percent(40))
+        .slot(Slot::new(150));
+
+    let sell = TokenAmount::new(10_000);
+    let supply_before = test.lp_supply();
+    // the pool has a referral config, but the swap isn't referred
+    let quote = test.quote_swap(sell, sell_mint, buy_mint, false)?;
+
+    let buy_wallet_before = test.buy_wallet_amount();
+    test.swap(sell, quote.bought, sell_mint, buy_mint)?;
+
+    assert_eq!(quote.bought, TokenAmount::new(6_254));
+    assert_eq!(
+        test.buy_wallet_amount() - buy_wallet_before,
+        quote.bought.amount
+    );
+    // half of the 900 swap fee is rebated
+    assert_eq!(quote.fee_rebate, TokenAmount::new(450));
+    assert_eq!(quote.swap_fee, TokenAmount::new(450));
+    assert_eq!(
+        test.pool_copy().reserves[0].tokens.amount,
+        20_000 + sell.amount - quote.fee_rebate.amount
+    );
+    assert_eq!(quote.referral, TokenAmount::new(0));
+    assert_eq!(quote.program_toll.amount, test.program_toll_wallet_amount());
+    assert_eq!(supply_before + quote.program_toll.amount, test.lp_supply());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_to_quote_referred_swap_if_pool_has_no_referral_config() -> Result<()> {
+    let pool = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(9),
+        reserves: create_two_reserves(
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
+        ),
+        ..Default::default()
+    };
+
+    let mut test = Tester::no_discount(pool.clone());
+    test.referral_config = test.referral_config.owner(system_program::ID);
+
+    assert!(test
+        .quote_swap(
+            TokenAmount::new(10_000),
+            pool.reserves[0].mint,
+            pool.reserves[1].mint,
+            true,
+        )
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn charges_fee_reported_by_fee_schedule() -> Result<()> {
+    let pool_before = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(9),
+        pending_swap_fee: Permillion::from_percent(18),
+        pending_swap_fee_effective_at: Slot::new(100),
+        reserves: create_two_reserves(
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
+        ),
+        ..Default::default()
+    };
+    let sell_mint = pool_before.reserves[0].mint;
+    let buy_mint = pool_before.reserves[1].mint;
+    let sell = TokenAmount::new(10_000);
+
+    for (slot, expected_fee, expected_pending) in
+        [(99, 9, Some(18)), (100, 18, None), (150, 18, None)]
+    {
+        let now = Slot::new(slot);
+        let schedule = pool_before.fee_schedule(now);
+        assert_eq!(schedule.swap_fee, Permillion::from_percent(expected_fee));
+        match expected_pending {
+            Some(pending) => {
+                assert_eq!(
+                    schedule.pending_swap_fee,
+                    Permillion::from_percent(pending)
+                );
+                assert_eq!(
+                    schedule.pending_swap_fee_effective_at,
+                    Slot::new(100)
+                );
+            }
+            None => {
+                assert_eq!(schedule.pending_swap_fee, Permillion::default());
+                assert_eq!(
+                    schedule.pending_swap_fee_effective_at,
+                    Slot::default()
+                );
+            }
+        }
+
+        // a pool which charges the reported fee with no pending change
+        let mut pool_with_reported_fee = Pool {
+            swap_fee: schedule.swap_fee,
+            pending_swap_fee: Permillion::default(),
+            pending_swap_fee_effective_at: Slot::default(),
+            ..pool_before.clone()
+        };
+        let mut test = Tester::no_discount(pool_before.clone()).slot(now);
+        let quote = simulate_swap(
+            &mut pool_with_reported_fee,
+            now,
+            sell_mint,
+            sell,
+            buy_mint,
+            SwapFeeShares::default(),
+            test.lp_supply().into(),
+        )?;
+        assert_eq!(
+            quote.swap_fee,
+            TokenAmount::new(sell.amount * expected_fee / 100)
+        );
+
+        let buy_wallet_before = test.buy_wallet_amount();
+        test.swap(sell, TokenAmount::new(0), sell_mint, buy_mint)?;
+        assert_eq!(
+            test.buy_wallet_amount() - buy_wallet_before,
+            quote.bought.amount
+        );
+        assert_eq!(test.pool_copy().swap_fee, schedule.swap_fee);
+    }
+
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    #[serial]
+    fn simulated_swap_matches_executed_swap(
+        sell_reserve in 1_000..1_000_000_u64,
+        buy_reserve in 1_000..1_000_000_u64,
+        sell in 1..1_000_000_u64,
+        swap_fee in 0..100_000_u64,
+        discount in prop::option::of(0..=1_000_000_u64),
+        fee_rebate in 0..1_000_000_u64,
+        slot in 0..300_u64,
+    ) {
+        let pool_before = Pool {
+            dimension: 2,
+            program_toll_wallet: Pubkey::new_unique(),
+            swap_fee: Permillion {
+                permillion: swap_fee,
+            },
+            reserves: create_two_reserves(
+                TokenAmount::new(sell_reserve),
+                TokenAmount::new(buy_reserve),
+            ),
+            ..Default::default()
+        };
+        let sell_mint = pool_before.reserves[0].mint;
+        let buy_mint = pool_before.reserves[1].mint;
+        let sell = TokenAmount::new(sell);
+
+        let discount = discount.map(|permillion| Discount {
+            amount: Permillion { permillion },
+            valid_until: Slot::new(200),
+        });
+        let fee_rebate = FeeRebate {
+            share: Permillion {
+                permillion: fee_rebate,
+            },
+            starts_at: Slot::new(100),
+            ends_at: Slot::new(200),
+        };
+        let mut test = match discount.clone() {
+            Some(d) => Tester::with_discount(pool_before.clone(), d),
+            None => Tester::no_discount(pool_before.clone()),
+        }
+        .fee_rebate(fee_rebate.clone())
+        .slot(Slot::new(slot));
+
+        // what a simulator would read from the accounts off-chain
+        let now = Slot::new(slot);
+        let shares = SwapFeeShares {
+            discount: discount
+                .as_ref()
+                .filter(|d| now <= d.valid_until)
+                .map(|d| d.amount)
+                .unwrap_or_default(),
+            fee_rebate: if fee_rebate.starts_at <= now
+                && now <= fee_rebate.ends_at
+            {
+                fee_rebate.share
+            } else {
+                Permillion::default()
+            },
+        };
+
+        let supply_before = test.lp_supply();
+        let mut simulated_pool = test.pool_copy();
+        // the inputs are bounded such that the swap always succeeds
+        let quote = simulate_swap(
+            &mut simulated_pool,
+            now,
+            sell_mint,
+            sell,
+            buy_mint,
+            shares,
+            supply_before.into(),
+        )
+        .unwrap();
+
+        let buy_wallet_before = test.buy_wallet_amount();
+        test.swap(sell, quote.bought, sell_mint, buy_mint).unwrap();
+
+        prop_assert_eq!(
+            test.buy_wallet_amount() - buy_wallet_before,
+            quote.bought.amount
+        );
+        prop_assert_eq!(
+            test.program_toll_wallet_amount(),
+            quote.program_toll.amount
+        );
+        prop_assert_eq!(
+            test.lp_supply(),
+            supply_before + quote.program_toll.amount
+        );
+        prop_assert_eq!(test.pool_copy(), simulated_pool);
+    }
+}
+
+#[test]
+#[serial]
+fn fails_to_swap_with_referral_if_config_belongs_to_another_pool() -> Result<()>
+{
+    let pool_before = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(9),
+        reserves: create_two_reserves(
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
+        ),
+        ..Default::default()
+    };
+
+    let mut test = Tester::no_discount(pool_before.clone());
+    test.referral_config = AccountInfoWrapper::pda(
+        amm::ID,
+        "referral_config",
+        &[ReferralConfig::PDA_PREFIX, Pubkey::new_unique().as_ref()],
+    )
+    .owner(amm::ID)
+    .data(ReferralConfig {
+        share: Permillion::from_percent(40),
+        default_recipient: test.referrer_wallet.key,
+    });
+
+    assert!(test
+        .swap_with_referral(
+            TokenAmount::new(10_000),
+            TokenAmount::new(6_254),
+            pool_before.reserves[0].mint,
+            pool_before.reserves[1].mint,
+        )
+        .unwrap_err()
+        .to_string()
+        .contains("ConstraintSeeds"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn swaps_with_default_referral_and_routes_share_of_toll_to_default_recipient(
+) -> Result<()> {
+    let pool_before = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(9),
+        reserves: create_two_reserves(
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
+        ),
+        ..Default::default()
+    };
+
+    // the referrer wallet is configured as the default recipient
+    let mut test = Tester::no_discount(pool_before.clone())
+        .referral_share(Permillion::from_percent(40));
+
+    test.swap_with_default_referral(
+        TokenAmount::new(10_000),
+        TokenAmount::new(6_254),
+        pool_before.reserves[0].mint,
+        pool_before.reserves[1].mint,
+    )?;
+
+    // see swaps_with_referral_and_routes_share_of_toll_to_referrer
+    assert_eq!(test.referrer_wallet_amount(), 20);
+    assert_eq!(test.program_toll_wallet_amount(), 30);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_to_swap_with_default_referral_if_wallet_is_not_default_recipient(
+) -> Result<()> {
+    let pool_before = Pool {
+        dimension: 2,
+        program_toll_wallet: Pubkey::new_unique(),
+        swap_fee: Permillion::from_percent(9),
+        reserves: create_two_reserves(
+            TokenAmount::new(20_000),
+            TokenAmount::new(20_000),
+        ),
+        ..Default::default()
+    };
+
+    let mut test = Tester::no_discount(pool_before.clone());
+    let referral_config = test.referral_config.clone();
+    test.referral_config = referral_config.data(ReferralConfig {
+        share: Permillion::from_percent(40),
+        default_recipient: Pubkey::new_unique(),
+    });
+
+    assert!(test
+        .swap_with_default_referral(
+            TokenAmount::new(10_000),
+            TokenAmount::new(6_254),
+            pool_before.reserves[0].mint,
+            pool_before.reserves[1].mint,
+        )
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    time: Slot,
+    user: AccountInfoWrapper,
+    discount: AccountInfoWrapper,
+    pool: AccountInfoWrapper,
+    pool_signer: AccountInfoWrapper,
+    fee_rebate: AccountInfoWrapper,
+    /// The fee rebate is an optional remaining account of the swap.
+    omit_fee_rebate: bool,
+    sell_wallet: AccountInfoWrapper,
+    buy_wallet: AccountInfoWrapper,
+    sell_vault: AccountInfoWrapper,
+    buy_vault: AccountInfoWrapper,
+    lp_mint: AccountInfoWrapper,
+    program_toll_wallet: AccountInfoWrapper,
     token_program: AccountInfoWrapper,
+    // only used by the referral swap
+    referral_config: AccountInfoWrapper,
+    referrer_wallet: AccountInfoWrapper,
 }
 
 impl Tester {
@@ -662,6 +1705,24 @@ impl Tester {
         self
     }
 
+    fn fee_rebate(mut self, fee_rebate: FeeRebate) -> Self {
+        self.fee_rebate = self.fee_rebate.owner(amm::ID).data(fee_rebate);
+        self
+    }
+
+    fn without_fee_rebate_account(mut self) -> Self {
+        self.omit_fee_rebate = true;
+        self
+    }
+
+    fn referral_share(mut self, share: Permillion) -> Self {
+        self.referral_config = self.referral_config.data(ReferralConfig {
+            share,
+            default_recipient: self.referrer_wallet.key,
+        });
+        self
+    }
+
     // Since the order of the reserves does not matter (that's unit tested),
     // we make a convention for parametrizing the tests:
     // The first reserve is always base (sell);
@@ -689,6 +1750,12 @@ impl Tester {
             "pool_signer",
             &[Pool::SIGNER_PDA_PREFIX, pool.key.as_ref()],
         );
+        let fee_rebate = AccountInfoWrapper::pda(
+            amm::ID,
+            "fee_rebate",
+            &[FeeRebate::PDA_PREFIX, pool.key.as_ref()],
+        )
+        .owner(system_program::ID);
         let lp_mint = AccountInfoWrapper::with_key(pool_data.mint)
             .mutable()
             .pack(spl::mint::new(pool_signer.key).supply(10_000))
@@ -745,6 +1812,22 @@ impl Tester {
             .owner(token::ID);
         let token_program =
             AccountInfoWrapper::with_key(anchor_spl::token::ID).program();
+        let referrer_wallet = AccountInfoWrapper::new()
+            .mutable()
+            .pack(
+                spl::token_account::new(Pubkey::new_unique()).mint(lp_mint.key),
+            )
+            .owner(token::ID);
+        let referral_config = AccountInfoWrapper::pda(
+            amm::ID,
+            "referral_config",
+            &[ReferralConfig::PDA_PREFIX, pool.key.as_ref()],
+        )
+        .owner(amm::ID)
+        .data(ReferralConfig {
+            share: Permillion::from_percent(0),
+            default_recipient: referrer_wallet.key,
+        });
 
         Self {
             time: Slot::new(0),
@@ -752,6 +1835,8 @@ impl Tester {
             discount,
             pool,
             pool_signer,
+            fee_rebate,
+            omit_fee_rebate: false,
             sell_wallet,
             buy_wallet,
             sell_vault,
@@ -759,6 +1844,8 @@ impl Tester {
             lp_mint,
             program_toll_wallet,
             token_program,
+            referral_config,
+            referrer_wallet,
         }
     }
 
@@ -770,24 +1857,152 @@ impl Tester {
         spl::mint::from_acc_info(&self.lp_mint.to_account_info()).supply
     }
 
+    fn program_toll_wallet_amount(&mut self) -> u64 {
+        spl::token_account::from_acc_info(
+            &self.program_toll_wallet.to_account_info(),
+        )
+        .amount
+    }
+
+    fn buy_wallet_amount(&mut self) -> u64 {
+        spl::token_account::from_acc_info(&self.buy_wallet.to_account_info())
+            .amount
+    }
+
+    fn referrer_wallet_amount(&mut self) -> u64 {
+        spl::token_account::from_acc_info(
+            &self.referrer_wallet.to_account_info(),
+        )
+        .amount
+    }
+
+    /// Calls the read-only endpoint and decodes its return data.
+    fn quote_swap(
+        &mut self,
+        sell: TokenAmount,
+        sell_mint: Pubkey,
+        buy_mint: Pubkey,
+        with_referral: bool,
+    ) -> Result<SwapQuote> {
+        let return_data = ReturnData::set(self.time);
+
+        let mut ctx = ContextWrapper::new(amm::ID)
+            .acc(&mut self.user)
+            .acc(&mut self.discount)
+            .acc(&mut self.pool)
+            .acc(&mut self.fee_rebate)
+            .acc(&mut self.referral_config)
+            .acc(&mut self.lp_mint);
+        let mut accounts = ctx.accounts()?;
+
+        quote_swap(
+            ctx.build(&mut accounts),
+            sell_mint,
+            sell,
+            buy_mint,
+            with_referral,
+        )?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(return_data.decode())
+    }
+
     fn swap(
         &mut self,
         sell: TokenAmount,
         min_buy: TokenAmount,
         sell_mint: Pubkey,
         buy_mint: Pubkey,
+    ) -> Result<stub::Syscalls<CpiValidator>> {
+        self.swap_with_limit(sell, Limit::MinBuy(min_buy), sell_mint, buy_mint)
+    }
+
+    fn swap_with_referral(
+        &mut self,
+        sell: TokenAmount,
+        min_buy: TokenAmount,
+        sell_mint: Pubkey,
+        buy_mint: Pubkey,
+    ) -> Result<stub::Syscalls<CpiValidator>> {
+        self.swap_with_limit(
+            sell,
+            Limit::MinBuyWithReferral(min_buy),
+            sell_mint,
+            buy_mint,
+        )
+    }
+
+    fn swap_with_default_referral(
+        &mut self,
+        sell: TokenAmount,
+        min_buy: TokenAmount,
+        sell_mint: Pubkey,
+        buy_mint: Pubkey,
+    ) -> Result<stub::Syscalls<CpiValidator>> {
+        self.swap_with_limit(
+            sell,
+            Limit::MinBuyWithDefaultReferral(min_buy),
+            sell_mint,
+            buy_mint,
+        )
+    }
+
+    fn swap_with_slippage_bps(
+        &mut self,
+        sell: TokenAmount,
+        max_slippage_bps: u64,
+        sell_mint: Pubkey,
+        buy_mint: Pubkey,
+    ) -> Result<stub::Syscalls<CpiValidator>> {
+        self.swap_with_limit(
+            sell,
+            Limit::SlippageBps(max_slippage_bps),
+            sell_mint,
+            buy_mint,
+        )
+    }
+
+    fn swap_with_round_trip_guard(
+        &mut self,
+        sell: TokenAmount,
+        min_buy: TokenAmount,
+        min_round_trip_bps: u64,
+        sell_mint: Pubkey,
+        buy_mint: Pubkey,
+    ) -> Result<stub::Syscalls<CpiValidator>> {
+        self.swap_with_limit(
+            sell,
+            Limit::MinBuyWithRoundTripGuard(min_buy, min_round_trip_bps),
+            sell_mint,
+            buy_mint,
+        )
+    }
+
+    fn swap_with_limit(
+        &mut self,
+        sell: TokenAmount,
+        limit: Limit,
+        sell_mint: Pubkey,
+        buy_mint: Pubkey,
     ) -> Result<stub::Syscalls<CpiValidator>> {
         // we set it to done initially just so that we can set the slot, will
         // overwrite it later
         self.set_syscalls(CpiValidatorState::Done);
 
         let mut pool = self.pool_copy();
+        pool.apply_pending_swap_fee(self.time);
         let fee = calculate_swap_fee(
             sell,
             pool.swap_fee,
             &self.discount.to_account_info(),
         )
         .unwrap_or_default();
+        let rebate = if self.omit_fee_rebate {
+            TokenAmount::new(0)
+        } else {
+            calculate_fee_rebate(fee, &self.fee_rebate.to_account_info())
+                .unwrap_or_default()
+        };
         let receive_tokens = pool
             .swap(
                 sell_mint,
@@ -795,9 +2010,19 @@ impl Tester {
                 buy_mint,
             )
             .unwrap_or_default();
+        let fee = TokenAmount::new(fee.amount - rebate.amount);
         pool.reserve_mut(sell_mint).unwrap().add_tokens(fee)?;
         let supply =
             spl::mint::from_acc_info(&self.lp_mint.to_account_info()).supply;
+        let referral_share = if limit.is_referred() {
+            ReferralConfig::try_deserialize(
+                &mut self.referral_config.data.as_slice(),
+            )
+            .map(|config| config.share)
+            .unwrap_or_default()
+        } else {
+            Permillion::default()
+        };
         let mint_toll = calculate_toll_in_lp_tokens(
             &pool,
             fee,
@@ -806,17 +2031,30 @@ impl Tester {
         )
         .ok()
         .flatten()
-        .map(|tokens| MintToll {
-            tokens,
-            signer: self.pool_signer.key,
-            destination: self.program_toll_wallet.key,
-            mint: self.lp_mint.key,
+        .map(|tokens| {
+            let referral =
+                calculate_referral_share(tokens, referral_share).unwrap();
+            MintToll {
+                tokens: TokenAmount::new(tokens.amount - referral.amount),
+                signer: self.pool_signer.key,
+                destination: self.program_toll_wallet.key,
+                mint: self.lp_mint.key,
+                next_cpi: (referral.amount > 0).then(|| {
+                    Box::new(MintToll {
+                        tokens: referral,
+                        signer: self.pool_signer.key,
+                        destination: self.referrer_wallet.key,
+                        mint: self.lp_mint.key,
+                        next_cpi: None,
+                    })
+                }),
+            }
         });
         let state = CpiValidatorState::TransferSoldTokens {
             user: self.user.key,
             vault: self.sell_vault.key,
             wallet: self.sell_wallet.key,
-            tokens: sell,
+            tokens: TokenAmount::new(sell.amount - rebate.amount),
             next_cpi: TransferBoughtTokens {
                 signer: self.pool_signer.key,
                 vault: self.buy_vault.key,
@@ -827,10 +2065,41 @@ impl Tester {
         };
         let syscalls = self.set_syscalls(state);
 
-        let mut ctx = self.context_wrapper();
+        let mut ctx = if limit.is_referred() {
+            self.context_wrapper_with_referral()
+        } else {
+            self.context_wrapper()
+        };
         let mut accounts = ctx.accounts()?;
 
-        swap(ctx.build(&mut accounts), sell, min_buy)?;
+        match limit {
+            Limit::MinBuy(min_buy) => {
+                swap(ctx.build(&mut accounts), sell, min_buy)?
+            }
+            Limit::SlippageBps(max_slippage_bps) => swap_with_slippage_bps(
+                ctx.build(&mut accounts),
+                sell,
+                max_slippage_bps,
+            )?,
+            Limit::MinBuyWithReferral(min_buy) => {
+                swap_with_referral(ctx.build(&mut accounts), sell, min_buy)?
+            }
+            Limit::MinBuyWithDefaultReferral(min_buy) => {
+                swap_with_default_referral(
+                    ctx.build(&mut accounts),
+                    sell,
+                    min_buy,
+                )?
+            }
+            Limit::MinBuyWithRoundTripGuard(min_buy, min_round_trip_bps) => {
+                swap_with_round_trip_guard(
+                    ctx.build(&mut accounts),
+                    sell,
+                    min_buy,
+                    min_round_trip_bps,
+                )?
+            }
+        };
         accounts.exit(&amm::ID)?;
 
         let CpiValidator(state) =
@@ -841,6 +2110,26 @@ impl Tester {
     }
 
     fn context_wrapper(&mut self) -> ContextWrapper {
+        let pass_fee_rebate = !self.omit_fee_rebate;
+        ContextWrapper::new(amm::ID)
+            .acc(&mut self.user)
+            .acc(&mut self.discount)
+            .acc(&mut self.pool)
+            .acc(&mut self.pool_signer)
+            .acc(&mut self.sell_wallet)
+            .acc(&mut self.buy_wallet)
+            .acc(&mut self.sell_vault)
+            .acc(&mut self.buy_vault)
+            .acc(&mut self.lp_mint)
+            .acc(&mut self.program_toll_wallet)
+            .acc(&mut self.token_program)
+            .remaining_accounts(
+                iter::once(&mut self.fee_rebate).filter(|_| pass_fee_rebate),
+            )
+    }
+
+    fn context_wrapper_with_referral(&mut self) -> ContextWrapper {
+        let pass_fee_rebate = !self.omit_fee_rebate;
         ContextWrapper::new(amm::ID)
             .acc(&mut self.user)
             .acc(&mut self.discount)
@@ -853,6 +2142,11 @@ impl Tester {
             .acc(&mut self.lp_mint)
             .acc(&mut self.program_toll_wallet)
             .acc(&mut self.token_program)
+            .acc(&mut self.referral_config)
+            .acc(&mut self.referrer_wallet)
+            .remaining_accounts(
+                iter::once(&mut self.fee_rebate).filter(|_| pass_fee_rebate),
+            )
     }
 
     fn set_syscalls(
@@ -868,6 +2162,25 @@ impl Tester {
     }
 }
 
+enum Limit {
+    MinBuy(TokenAmount),
+    SlippageBps(u64),
+    MinBuyWithReferral(TokenAmount),
+    MinBuyWithDefaultReferral(TokenAmount),
+    MinBuyWithRoundTripGuard(TokenAmount, u64),
+}
+
+impl Limit {
+    /// Whether the swap is routed through an endpoint which takes the
+    /// referral config and the referrer wallet.
+    fn is_referred(&self) -> bool {
+        matches!(
+            self,
+            Limit::MinBuyWithReferral(_) | Limit::MinBuyWithDefaultReferral(_)
+        )
+    }
+}
+
 #[derive(Debug, Clone)]
 struct CpiValidator(Arc<Mutex<CpiValidatorState>>);
 #[derive(Debug, Eq, PartialEq)]
@@ -897,6 +2210,7 @@ struct MintToll {
     mint: Pubkey,
     destination: Pubkey,
     tokens: TokenAmount,
+    next_cpi: Option<Box<MintToll>>,
 }
 
 impl stub::ValidateCpis for CpiValidator {
@@ -981,6 +2295,7 @@ impl stub::ValidateCpis for CpiValidator {
                 destination,
                 signer,
                 tokens,
+                ref next_cpi,
             }) => {
                 let expected_ix = token::spl_token::instruction::mint_to(
                     &token::ID,
@@ -1001,7 +2316,11 @@ impl stub::ValidateCpis for CpiValidator {
                 spl::mint::mint_to(wallet, lp_mint, tokens.amount)
                     .expect("Cannot mint LP tokens");
 
-                *state = CpiValidatorState::Done;
+                *state = if let Some(next_cpi) = next_cpi {
+                    CpiValidatorState::MintToll((**next_cpi).clone())
+                } else {
+                    CpiValidatorState::Done
+                };
             }
             CpiValidatorState::Done => {
                 panic!("No more instructions expected, got {:#?}", ix);
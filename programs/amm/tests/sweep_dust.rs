@@ -0,0 +1,395 @@
+use ::amm::amm::sweep_dust;
+use ::amm::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::{self, spl_token::state::Mint};
+use anchortest::{
+    builder::*,
+    spl::{self, TokenAccountExt},
+    stub,
+};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use solana_sdk::instruction::Instruction;
+
+#[test]
+#[serial]
+fn sweeps_dust_into_program_toll_wallet() -> Result<()> {
+    let mut test = Tester::default();
+
+    test.sweep_dust()?;
+
+    assert_eq!(test.amount_of(Acc::DustWallet), 0);
+    assert_eq!(test.amount_of(Acc::ProgramTollWallet), 500);
+    // reserve vaults are not touched
+    assert_eq!(test.amount_of(Acc::ReserveVault), 100);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_signer_is_not_pool_admin() -> Result<()> {
+    let mut test = Tester::default();
+    test.admin = AccountInfoWrapper::new().signer();
+
+    assert!(test
+        .sweep_dust()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_dust_wallet_is_reserve_vault() -> Result<()> {
+    let mut test = Tester::default();
+    test.dust_wallet = test.reserve_vault.clone();
+
+    assert!(test
+        .sweep_dust()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+    assert_eq!(test.amount_of(Acc::ReserveVault), 100);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_dust_wallet_is_not_owned_by_pool_signer() -> Result<()> {
+    let mut test = Tester::default();
+    // the program toll wallet is protected because it's not owned by the pool
+    // signer
+    test.dust_wallet = test.program_toll_wallet.clone();
+
+    assert!(test
+        .sweep_dust()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_program_toll_wallet_is_not_owned_by_toll_authority() -> Result<()> {
+    let mut test = Tester::default();
+    test.program_toll_wallet = AccountInfoWrapper::new()
+        .mutable()
+        .pack(spl::token_account::new(test.admin.key).mint(test.dust_mint.key))
+        .owner(token::ID);
+
+    assert!(test
+        .sweep_dust()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_wallet_holds_more_than_dust() -> Result<()> {
+    let mut test = Tester::default();
+    // 10^6 / 100 for a mint with 6 decimals
+    test.dust_wallet = test.dust_wallet.clone().pack(
+        spl::token_account::new(test.pool_signer.key)
+            .mint(test.dust_mint.key)
+            .amount(10_000),
+    );
+
+    assert!(test
+        .sweep_dust()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+    assert_eq!(test.amount_of(Acc::DustWallet), 10_000);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn scales_dust_by_mint_decimals() -> Result<()> {
+    // 10^9 / 100, hence 5 million is dust
+    let mut test = Tester::default();
+    test.dust_mint = test.dust_mint.clone().pack(mint_with_decimals(9));
+    test.dust_wallet = test.dust_wallet.clone().pack(
+        spl::token_account::new(test.pool_signer.key)
+            .mint(test.dust_mint.key)
+            .amount(5_000_000),
+    );
+
+    test.sweep_dust()?;
+    assert_eq!(test.amount_of(Acc::DustWallet), 0);
+    assert_eq!(test.amount_of(Acc::ProgramTollWallet), 5_000_000);
+
+    // 10^2 / 100, hence no positive balance is dust
+    let mut test = Tester::default();
+    test.dust_mint = test.dust_mint.clone().pack(mint_with_decimals(2));
+
+    assert!(test
+        .sweep_dust()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+    assert_eq!(test.amount_of(Acc::DustWallet), 500);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_dust_mint_does_not_match_dust_wallet() -> Result<()> {
+    let mut test = Tester::default();
+    test.dust_mint = AccountInfoWrapper::new()
+        .pack(mint_with_decimals(9))
+        .owner(token::ID);
+
+    assert!(test
+        .sweep_dust()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fails_if_there_is_no_dust() -> Result<()> {
+    let mut test = Tester::default();
+    test.dust_wallet = test.dust_wallet.clone().pack(
+        spl::token_account::new(test.pool_signer.key).mint(test.dust_mint.key),
+    );
+
+    assert!(test
+        .sweep_dust()
+        .unwrap_err()
+        .to_string()
+        .contains("InvalidAccountInput"));
+
+    Ok(())
+}
+
+enum Acc {
+    DustWallet,
+    ProgramTollWallet,
+    ReserveVault,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tester {
+    dust_mint: AccountInfoWrapper,
+    admin: AccountInfoWrapper,
+    pool: AccountInfoWrapper,
+    pool_signer: AccountInfoWrapper,
+    program_toll: AccountInfoWrapper,
+    dust_wallet: AccountInfoWrapper,
+    program_toll_wallet: AccountInfoWrapper,
+    token_program: AccountInfoWrapper,
+    // not passed to the endpoint unless a test replaces the dust wallet with it
+    reserve_vault: AccountInfoWrapper,
+}
+
+impl Default for Tester {
+    fn default() -> Self {
+        let dust_mint = AccountInfoWrapper::new()
+            .pack(mint_with_decimals(6))
+            .owner(token::ID);
+        let admin = AccountInfoWrapper::new().signer();
+        let program_toll_authority = Pubkey::new_unique();
+        let program_toll = AccountInfoWrapper::pda(
+            amm::ID,
+            "program_toll",
+            &[ProgramToll::PDA_SEED],
+        )
+        .data(ProgramToll {
+            authority: program_toll_authority,
+        })
+        .owner(amm::ID);
+        let pool_key = Pubkey::new_unique();
+        let pool_signer = AccountInfoWrapper::pda(
+            amm::ID,
+            "pool_signer",
+            &[Pool::SIGNER_PDA_PREFIX, pool_key.as_ref()],
+        );
+        let reserve_vault = AccountInfoWrapper::new()
+            .mutable()
+            .pack(
+                spl::token_account::new(pool_signer.key)
+                    .mint(dust_mint.key)
+                    .amount(100),
+            )
+            .owner(token::ID);
+        let pool =
+            AccountInfoWrapper::with_key(pool_key)
+                .owner(amm::ID)
+                .data(Pool {
+                    admin: admin.key,
+                    signer: pool_signer.key,
+                    dimension: 2,
+                    reserves: [
+                        Reserve {
+                            tokens: TokenAmount::new(100),
+                            mint: dust_mint.key,
+                            vault: reserve_vault.key,
+                        },
+                        Reserve {
+                            tokens: TokenAmount::new(100),
+                            mint: Pubkey::new_unique(),
+                            vault: Pubkey::new_unique(),
+                        },
+                        Reserve::default(),
+                        Reserve::default(),
+                    ],
+                    ..Default::default()
+                });
+        let dust_wallet = AccountInfoWrapper::new()
+            .mutable()
+            .pack(
+                spl::token_account::new(pool_signer.key)
+                    .mint(dust_mint.key)
+                    .amount(500),
+            )
+            .owner(token::ID);
+        let program_toll_wallet = AccountInfoWrapper::new()
+            .mutable()
+            .pack(
+                spl::token_account::new(program_toll_authority)
+                    .mint(dust_mint.key),
+            )
+            .owner(token::ID);
+        let token_program =
+            AccountInfoWrapper::with_key(anchor_spl::token::ID).program();
+
+        Self {
+            dust_mint,
+            admin,
+            pool,
+            pool_signer,
+            program_toll,
+            dust_wallet,
+            program_toll_wallet,
+            token_program,
+            reserve_vault,
+        }
+    }
+}
+
+impl Tester {
+    fn sweep_dust(&mut self) -> Result<()> {
+        let dust = spl::token_account::from_acc_info(
+            &self.dust_wallet.to_account_info(),
+        )
+        .amount;
+        stub::Syscalls::new(CpiValidator(CpiValidatorState::SweepDust {
+            from: self.dust_wallet.key,
+            to: self.program_toll_wallet.key,
+            signer: self.pool_signer.key,
+            tokens: TokenAmount::new(dust),
+        }))
+        .set();
+
+        let mut ctx = self.context_wrapper();
+        let mut accounts = ctx.accounts()?;
+
+        sweep_dust(ctx.build(&mut accounts))?;
+        accounts.exit(&amm::ID)?;
+
+        Ok(())
+    }
+
+    fn amount_of(&mut self, acc: Acc) -> u64 {
+        let acc = match acc {
+            Acc::DustWallet => &mut self.dust_wallet,
+            Acc::ProgramTollWallet => &mut self.program_toll_wallet,
+            Acc::ReserveVault => &mut self.reserve_vault,
+        };
+
+        spl::token_account::from_acc_info(&acc.to_account_info()).amount
+    }
+
+    fn context_wrapper(&mut self) -> ContextWrapper {
+        ContextWrapper::new(amm::ID)
+            .acc(&mut self.admin)
+            .acc(&mut self.pool)
+            .acc(&mut self.pool_signer)
+            .acc(&mut self.program_toll)
+            .acc(&mut self.dust_wallet)
+            .acc(&mut self.dust_mint)
+            .acc(&mut self.program_toll_wallet)
+            .acc(&mut self.token_program)
+    }
+}
+
+fn mint_with_decimals(decimals: u8) -> Mint {
+    Mint {
+        mint_authority: COption::Some(Pubkey::new_unique()),
+        supply: 1_000_000,
+        decimals,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+}
+
+struct CpiValidator(CpiValidatorState);
+enum CpiValidatorState {
+    SweepDust {
+        from: Pubkey,
+        to: Pubkey,
+        signer: Pubkey,
+        tokens: TokenAmount,
+    },
+    Done,
+}
+
+impl stub::ValidateCpis for CpiValidator {
+    fn validate_next_instruction(
+        &mut self,
+        ix: &Instruction,
+        accounts: &[AccountInfo],
+    ) {
+        match self.0 {
+            CpiValidatorState::SweepDust {
+                from,
+                to,
+                signer,
+                tokens,
+            } => {
+                let expected_ix = token::spl_token::instruction::transfer(
+                    &token::ID,
+                    &from,
+                    &to,
+                    &signer,
+                    &[],
+                    tokens.amount,
+                )
+                .unwrap();
+                assert_eq!(&expected_ix, ix);
+
+                let from_wallet = &accounts[0];
+                let to_wallet = &accounts[1];
+                assert_eq!(from_wallet.key(), from);
+                assert_eq!(to_wallet.key(), to);
+
+                spl::token_account::transfer(
+                    from_wallet,
+                    to_wallet,
+                    tokens.amount,
+                )
+                .expect("Dust wallet does not have enough tokens");
+
+                self.0 = CpiValidatorState::Done;
+            }
+            CpiValidatorState::Done => {
+                panic!("No more instructions expected, got {:#?}", ix);
+            }
+        }
+    }
+}
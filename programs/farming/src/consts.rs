@@ -23,6 +23,10 @@ pub const HARVEST_PERIODS_LEN: usize = 10;
 /// kept.
 pub const SNAPSHOTS_LEN: usize = 1000;
 
+/// How many snapshots can [`crate::endpoints::get_snapshots`] return at most.
+/// Return data is limited to 1024 bytes and each snapshot takes 16 bytes.
+pub const MAX_SNAPSHOTS_PER_PAGE: usize = 60;
+
 /// Automation must wait at least this many slots before it can take a new
 /// snapshot.
 ///
@@ -30,3 +34,25 @@ pub const SNAPSHOTS_LEN: usize = 1000;
 /// [`crate::endpoints::take_snapshot`] endpoint is available for a
 /// single [`crate::models::Farm`] at most this often.
 pub const MIN_SNAPSHOT_WINDOW_SLOTS: u64 = 2 * 3600;
+
+/// [`crate::endpoints::crank_snapshot`] pays the keeper reward at most once
+/// per this many slots, regardless of the farm's snapshot window.
+pub const KEEPER_REWARD_WINDOW_SLOTS: u64 = MIN_SNAPSHOT_WINDOW_SLOTS;
+
+/// Increases of the lockup done with [`crate::endpoints::set_lockup_slots`]
+/// only take effect after this many slots, which is roughly a day. Decreases
+/// take effect immediately.
+pub const LOCKUP_INCREASE_TIMELOCK_SLOTS: u64 = 216_000;
+
+/// An upper bound on the lockup which the admin can configure with
+/// [`crate::endpoints::set_lockup_slots`], roughly 30 days.
+pub const MAX_LOCKUP_SLOTS: u64 = 6_480_000;
+
+/// An upper bound on the multiplier which the admin can configure with
+/// [`crate::endpoints::set_emission_multiplier`], ie. 10x.
+pub const MAX_EMISSION_MULTIPLIER_PERCENT: u64 = 1_000;
+
+/// An upper bound on the front-loading which the admin can configure with
+/// [`crate::endpoints::set_emission_front_load`]. At 100% the last slot of a
+/// harvest period emits nothing.
+pub const MAX_EMISSION_FRONT_LOAD_PERCENT: u64 = 100;
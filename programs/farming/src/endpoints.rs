@@ -1,37 +1,87 @@
 pub mod add_harvest;
 pub mod airdrop;
+pub mod claim_and_restake;
 pub mod claim_eligible_harvest;
+pub mod claim_eligible_harvest_across_farms;
+pub mod claim_vested_harvest;
 pub mod close_farmer;
 pub mod compound_across_farms;
 pub mod compound_same_farm;
+pub mod correct_snapshot;
+pub mod crank_snapshot;
 pub mod create_farm;
 pub mod create_farmer;
 pub mod dewhitelist_farm_for_compounding;
+pub mod donate_rewards;
+pub mod exit_farm;
+pub mod get_compounding_eligibility;
+pub mod get_farm_stats;
+pub mod get_farmer_average_stake;
+pub mod get_harvest_runways;
+pub mod get_snapshots;
+pub mod migrate_farm;
+pub mod migrate_farmer;
 pub mod new_harvest_period;
+pub mod pause_harvest;
 pub mod remove_harvest;
+pub mod resume_harvest;
+pub mod set_block_farming_once_ended;
+pub mod set_claim_cooldown_slots;
+pub mod set_claim_vesting_slots;
+pub mod set_compound_bounds;
+pub mod set_emission_front_load;
+pub mod set_emission_multiplier;
 pub mod set_farm_owner;
+pub mod set_keeper_reward;
+pub mod set_lockup_slots;
 pub mod set_min_snapshot_window;
 pub mod start_farming;
 pub mod stop_farming;
 pub mod take_snapshot;
+pub mod transfer_farmer;
 pub mod update_eligible_harvest;
 pub mod whitelist_farm_for_compounding;
 
 pub use add_harvest::*;
 pub use airdrop::*;
+pub use claim_and_restake::*;
 pub use claim_eligible_harvest::*;
+pub use claim_eligible_harvest_across_farms::*;
+pub use claim_vested_harvest::*;
 pub use close_farmer::*;
 pub use compound_across_farms::*;
 pub use compound_same_farm::*;
+pub use correct_snapshot::*;
+pub use crank_snapshot::*;
 pub use create_farm::*;
 pub use create_farmer::*;
 pub use dewhitelist_farm_for_compounding::*;
+pub use donate_rewards::*;
+pub use exit_farm::*;
+pub use get_compounding_eligibility::*;
+pub use get_farm_stats::*;
+pub use get_farmer_average_stake::*;
+pub use get_harvest_runways::*;
+pub use get_snapshots::*;
+pub use migrate_farm::*;
+pub use migrate_farmer::*;
 pub use new_harvest_period::*;
+pub use pause_harvest::*;
 pub use remove_harvest::*;
+pub use resume_harvest::*;
+pub use set_block_farming_once_ended::*;
+pub use set_claim_cooldown_slots::*;
+pub use set_claim_vesting_slots::*;
+pub use set_compound_bounds::*;
+pub use set_emission_front_load::*;
+pub use set_emission_multiplier::*;
 pub use set_farm_owner::*;
+pub use set_keeper_reward::*;
+pub use set_lockup_slots::*;
 pub use set_min_snapshot_window::*;
 pub use start_farming::*;
 pub use stop_farming::*;
 pub use take_snapshot::*;
+pub use transfer_farmer::*;
 pub use update_eligible_harvest::*;
 pub use whitelist_farm_for_compounding::*;
@@ -0,0 +1,161 @@
+//! Farmer uses this endpoint to migrate their harvest from one farm into
+//! another atomically. The eligible harvest of the source farmer which is of
+//! the same mint as the target farm's stake mint is claimed and staked into
+//! the target farm on behalf of the same user.
+//!
+//! Unlike [`crate::endpoints::compound_across_farms`], which is meant to be
+//! called by bots, this endpoint must be signed by the farmer's authority and
+//! therefore doesn't require the target farm to be whitelisted.
+//!
+//! The staked tokens are marked as vested and locked in the target farm like
+//! a deposit with [`crate::endpoints::start_farming`], which also fails if
+//! farming in the target farm is blocked once it ended.
+//!
+//! If the source farm vests claimed harvest, see
+//! [`Farm::claim_vesting_slots`], the harvest is put into the farmer's escrow
+//! and only its vested part is restaked. Restaking counts as a claim for the
+//! source farm's cooldown, see [`Farm::claim_cooldown_slots`].
+
+use crate::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct ClaimAndRestake<'info> {
+    pub authority: Signer<'info>,
+    /// Farm which emits the harvest.
+    #[account(mut)]
+    pub source_farm: AccountLoader<'info, Farm>,
+    /// Farm into which the harvest is staked.
+    #[account(
+        constraint = target_farm.key() != source_farm.key()
+            @ err::acc("Cannot restake into the same farm"),
+    )]
+    pub target_farm: AccountLoader<'info, Farm>,
+    /// CHECK: UNSAFE_CODES.md#signer
+    #[account(
+        seeds = [Farm::SIGNER_PDA_PREFIX, source_farm.key().as_ref()],
+        bump,
+    )]
+    pub source_farm_signer_pda: AccountInfo<'info>,
+    /// Harvest of this farmer is claimed.
+    #[account(
+        mut,
+        seeds = [
+            Farmer::ACCOUNT_PREFIX,
+            source_farm.key().as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub source_farmer: Box<Account<'info, Farmer>>,
+    /// Claimed tokens are added to this farmer's vested tokens.
+    #[account(
+        mut,
+        seeds = [
+            Farmer::ACCOUNT_PREFIX,
+            target_farm.key().as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub target_farmer: Box<Account<'info, Farmer>>,
+    /// Claimed harvest is transferred FROM this vault.
+    #[account(
+        mut,
+        constraint = source_harvest_vault.mint == target_stake_vault.mint
+            @ err::acc(
+                "Restaking is only possible if target farm's stake mint is \
+                a harvestable mint of the source farm"
+            ),
+        seeds = [
+            Harvest::VAULT_PREFIX,
+            source_farm.key().as_ref(),
+            source_harvest_vault.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub source_harvest_vault: Account<'info, TokenAccount>,
+    /// Claimed harvest is transferred INTO this vault.
+    #[account(
+        mut,
+        seeds = [
+            Farm::STAKE_VAULT_PREFIX,
+            target_farm.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub target_stake_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handle(ctx: Context<ClaimAndRestake>) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    let mut source_farm = accounts.source_farm.load_mut()?;
+    let target_farm = accounts.target_farm.load()?;
+    let current_slot = Slot::current()?;
+
+    // restaking is a deposit into the target farm, see
+    // [`crate::endpoints::start_farming`]
+    target_farm.check_farming_not_blocked(current_slot)?;
+
+    accounts
+        .source_farmer
+        .check_vested_period_and_update_harvest(&source_farm, current_slot)?;
+    accounts
+        .target_farmer
+        .check_vested_period_and_update_harvest(&target_farm, current_slot)?;
+
+    let restake = accounts.source_farmer.claim_harvest_to_compound(
+        &source_farm,
+        target_farm.stake_mint,
+        current_slot,
+    )?;
+
+    if restake.amount == 0 {
+        return Err(error!(err::arg(
+            "There is no eligible harvest to restake"
+        )));
+    }
+
+    // marks the funds as vested, they won't be eligible for harvest until the
+    // next snapshot
+    accounts
+        .target_farmer
+        .add_to_vested(current_slot, restake)?;
+    // restaked tokens cannot be unstaked until the target farm's lockup
+    // elapses
+    accounts
+        .target_farmer
+        .lock_stake(&target_farm, current_slot, restake)?;
+    source_farm.add_to_total_distributed(target_farm.stake_mint, restake);
+
+    // from source farm's harvest vault to target farm's stake vault
+    let pda_seeds = &[
+        Farm::SIGNER_PDA_PREFIX,
+        &accounts.source_farm.key().to_bytes()[..],
+        &[*ctx.bumps.get("source_farm_signer_pda").unwrap()],
+    ];
+    token::transfer(
+        accounts
+            .as_transfer_from_harvest_vault_to_stake_vault_context()
+            .with_signer(&[&pda_seeds[..]]),
+        restake.amount,
+    )?;
+
+    Ok(())
+}
+
+impl<'info> ClaimAndRestake<'info> {
+    fn as_transfer_from_harvest_vault_to_stake_vault_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+        let cpi_accounts = token::Transfer {
+            from: self.source_harvest_vault.to_account_info(),
+            to: self.target_stake_vault.to_account_info(),
+            authority: self.source_farm_signer_pda.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
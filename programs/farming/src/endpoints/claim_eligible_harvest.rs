@@ -14,10 +14,19 @@
 //!
 //! You don't have to provide all harvestable mints. The pairs for mints which
 //! you don't provide are still going to be eligible for claiming later.
+//!
+//! # Vesting
+//! If the farm has [`Farm::claim_vesting_slots`] set, the claimed harvest is
+//! put into the farmer's escrow instead, and only the part of the escrow which
+//! has vested so far is transferred. See also
+//! [`crate::endpoints::claim_vested_harvest`].
+//!
+//! # Cooldown
+//! If the farm has [`Farm::claim_cooldown_slots`] set, the farmer cannot claim
+//! again until the cooldown since their last claim elapses.
 
 use crate::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount};
-use std::collections::BTreeMap;
 
 #[derive(Accounts)]
 pub struct ClaimEligibleHarvest<'info> {
@@ -32,6 +41,13 @@ pub struct ClaimEligibleHarvest<'info> {
         bump,
     )]
     pub farmer: Account<'info, Farmer>,
+    /// Keeps track of how many tokens were distributed per harvest mint.
+    #[account(
+        mut,
+        constraint = farm.key() == farmer.farm
+            @ err::acc("Farmer is set up for a different farm"),
+    )]
+    pub farm: AccountLoader<'info, Farm>,
     /// CHECK: UNSAFE_CODES.md#signer
     #[account(
         seeds = [Farm::SIGNER_PDA_PREFIX, farmer.farm.as_ref()],
@@ -49,7 +65,9 @@ pub fn handle<'info>(
     if ctx.remaining_accounts.is_empty()
         || ctx.remaining_accounts.len() % 2 != 0
     {
-        return Err(error!(err::acc("Remaining accounts must come in pairs")));
+        return Err(error!(err::acc(
+            "Remaining accounts must come in pairs for claim_eligible_harvest"
+        )));
     }
 
     // all transfers are authorized by the same PDA
@@ -59,14 +77,13 @@ pub fn handle<'info>(
         &[*ctx.bumps.get("farm_signer_pda").unwrap()],
     ];
 
-    // collect all available harvests into a map for fast access, in the end
-    // we convert the map back into the array
-    let mut farmer_harvests: BTreeMap<_, _> = accounts
+    let mut farm = accounts.farm.load_mut()?;
+    let current_slot = Slot::current()?;
+
+    accounts
         .farmer
-        .harvests
-        .iter()
-        .map(|h| (h.mint, h.tokens))
-        .collect();
+        .check_and_record_claim(current_slot, farm.claim_cooldown_slots)?;
+
     // for each [vault, wallet] pair (must be same mint) we transfer eligible
     // harvest from vault to wallet
     for accs in ctx.remaining_accounts.chunks(2) {
@@ -102,29 +119,25 @@ pub fn handle<'info>(
                 vault.key()
             ))));
         }
-        if let Some(eligible_harvest) =
-            farmer_harvests.get_mut(&mint).filter(|h| h.amount > 0)
-        {
+        let transfer = accounts.farmer.claim_eligible_harvest(
+            mint,
+            current_slot,
+            farm.claim_vesting_slots,
+        )?;
+
+        if transfer.amount > 0 {
             let vault = vault.to_account_info();
             let wallet = wallet.to_account_info();
             token::transfer(
                 accounts
                     .as_transfer_eligible_harvest_context(vault, wallet)
                     .with_signer(&[&pda_seeds[..]]),
-                eligible_harvest.amount,
+                transfer.amount,
             )?;
-
-            // update the map as we will eventually convert it back
-            *eligible_harvest = TokenAmount::new(0);
+            farm.add_to_total_distributed(mint, transfer);
         }
     }
 
-    // the amounts which have been claimed were set to 0, update the array
-    //
-    // note that not all harvest mints may have been claimed, but the ones which
-    // were are set to 0 now
-    accounts.farmer.set_harvests(farmer_harvests)?;
-
     Ok(())
 }
 
@@ -0,0 +1,223 @@
+//! Same as [`crate::endpoints::claim_eligible_harvest`], but claims for a
+//! single user from several farms in one transaction. The eligible harvest of
+//! each farmer is updated first, ie. the user doesn't have to call
+//! [`crate::endpoints::update_eligible_harvest`] beforehand.
+//!
+//! # Additional accounts
+//! Groups of five accounts, one group per farm and harvest mint. To claim
+//! several mints of the same farm, repeat the farm and the farmer in another
+//! group.
+//!
+//! ```text
+//! [
+//!   farm1,
+//!   farmer1,
+//!   farm_signer_pda1,
+//!   harvest_vault1,
+//!   harvest_wallet1,
+//!   farm2,
+//!   ...
+//! ]
+//! ```
+//!
+//! The farm, farmer, harvest vault and harvest wallet must be writable. The
+//! farm signer PDA is required because it authorizes the transfer from the
+//! harvest vault.
+//!
+//! A group which doesn't validate, eg. the farmer belongs to another user or
+//! the harvest vault is not the farm's vault, is logged and skipped, and the
+//! remaining groups are still claimed. However, if a farmer is still in its
+//! farm's claim cooldown, see [`Farm::claim_cooldown_slots`], the whole
+//! instruction fails.
+
+use crate::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+/// How many remaining accounts form a single group.
+const GROUP_LEN: usize = 5;
+
+#[derive(Accounts)]
+pub struct ClaimEligibleHarvestAcrossFarms<'info> {
+    /// Must be the authority of all the farmers.
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// A group of remaining accounts which passed the validation.
+struct ClaimGroup<'info> {
+    farm: AccountLoader<'info, Farm>,
+    farmer: Account<'info, Farmer>,
+    farm_signer_pda: AccountInfo<'info>,
+    farm_signer_bump: u8,
+    harvest_vault: Account<'info, TokenAccount>,
+    harvest_wallet: Account<'info, TokenAccount>,
+}
+
+pub fn handle<'info>(
+    ctx: Context<'_, '_, '_, 'info, ClaimEligibleHarvestAcrossFarms<'info>>,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    if ctx.remaining_accounts.is_empty()
+        || ctx.remaining_accounts.len() % GROUP_LEN != 0
+    {
+        return Err(error!(err::acc(
+            "Remaining accounts must come in groups of five for \
+            claim_eligible_harvest_across_farms"
+        )));
+    }
+
+    let current_slot = Slot::current()?;
+
+    let mut claimed_groups = 0;
+    for (index, accs) in ctx.remaining_accounts.chunks(GROUP_LEN).enumerate() {
+        let group = match ClaimGroup::parse(
+            ctx.program_id,
+            accounts.authority.key(),
+            accs,
+        ) {
+            Ok(group) => group,
+            Err(reason) => {
+                msg!("Skipping group #{}: {}", index, reason);
+                continue;
+            }
+        };
+
+        group.claim(&accounts.token_program, current_slot)?;
+        claimed_groups += 1;
+    }
+
+    msg!(
+        "Claimed {} out of {} groups",
+        claimed_groups,
+        ctx.remaining_accounts.len() / GROUP_LEN
+    );
+
+    Ok(())
+}
+
+impl<'info> ClaimGroup<'info> {
+    /// Returns the reason why the group is malformed as an error.
+    fn parse(
+        program_id: &Pubkey,
+        authority: Pubkey,
+        accs: &[AccountInfo<'info>],
+    ) -> std::result::Result<Self, String> {
+        let (farm, farmer, farm_signer_pda, harvest_vault, harvest_wallet) =
+            (&accs[0], &accs[1], &accs[2], &accs[3], &accs[4]);
+
+        if !farm.is_writable
+            || !farmer.is_writable
+            || !harvest_vault.is_writable
+            || !harvest_wallet.is_writable
+        {
+            return Err(
+                "Farm, farmer, vault and wallet must be writable".into()
+            );
+        }
+
+        let farm = AccountLoader::<Farm>::try_from(farm)
+            .map_err(|_| format!("Account '{}' is not a farm", farm.key()))?;
+        let farmer = Account::<Farmer>::try_from(farmer).map_err(|_| {
+            format!("Account '{}' is not a farmer", farmer.key())
+        })?;
+        if farmer.farm != farm.key() {
+            return Err("Farmer is set up for a different farm".into());
+        }
+        if farmer.authority != authority {
+            return Err("Signer must be the farmer's authority".into());
+        }
+
+        let (expected_signer, farm_signer_bump) = Pubkey::find_program_address(
+            &[Farm::SIGNER_PDA_PREFIX, farm.key().as_ref()],
+            program_id,
+        );
+        if expected_signer != farm_signer_pda.key() {
+            return Err("Farm signer PDA mismatch".into());
+        }
+
+        let harvest_vault = Account::<TokenAccount>::try_from(harvest_vault)
+            .map_err(|_| "Harvest vault must be a token account".to_string())?;
+        let (expected_vault, _) = Pubkey::find_program_address(
+            &[
+                Harvest::VAULT_PREFIX,
+                farm.key().as_ref(),
+                harvest_vault.mint.as_ref(),
+            ],
+            program_id,
+        );
+        if expected_vault != harvest_vault.key() {
+            return Err(format!(
+                "Harvest vault for mint '{}' expected to be '{}' but got '{}'",
+                harvest_vault.mint,
+                expected_vault,
+                harvest_vault.key()
+            ));
+        }
+
+        let harvest_wallet = Account::<TokenAccount>::try_from(harvest_wallet)
+            .map_err(|_| {
+                "Harvest wallet must be a token account".to_string()
+            })?;
+        if harvest_wallet.mint != harvest_vault.mint {
+            return Err("Harvest wallet mint must match vault mint".into());
+        }
+
+        Ok(Self {
+            farm,
+            farmer,
+            farm_signer_pda: farm_signer_pda.clone(),
+            farm_signer_bump,
+            harvest_vault,
+            harvest_wallet,
+        })
+    }
+
+    fn claim(
+        mut self,
+        token_program: &Program<'info, Token>,
+        current_slot: Slot,
+    ) -> Result<()> {
+        let mint = self.harvest_vault.mint;
+        let mut farm = self.farm.load_mut()?;
+
+        self.farmer
+            .check_vested_period_and_update_harvest(&farm, current_slot)?;
+        self.farmer
+            .check_and_record_claim(current_slot, farm.claim_cooldown_slots)?;
+        let transfer = self.farmer.claim_eligible_harvest(
+            mint,
+            current_slot,
+            farm.claim_vesting_slots,
+        )?;
+
+        if transfer.amount > 0 {
+            let farm_key = self.farm.key();
+            let pda_seeds = &[
+                Farm::SIGNER_PDA_PREFIX,
+                farm_key.as_ref(),
+                &[self.farm_signer_bump],
+            ];
+            token::transfer(
+                CpiContext::new(
+                    token_program.to_account_info(),
+                    token::Transfer {
+                        from: self.harvest_vault.to_account_info(),
+                        to: self.harvest_wallet.to_account_info(),
+                        authority: self.farm_signer_pda.clone(),
+                    },
+                )
+                .with_signer(&[&pda_seeds[..]]),
+                transfer.amount,
+            )?;
+            farm.add_to_total_distributed(mint, transfer);
+        }
+
+        // the same farmer can appear in a later group, therefore persist the
+        // changes right away
+        drop(farm);
+        self.farmer.exit(&crate::ID)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+//! If the farm has [`Farm::claim_vesting_slots`] set, the harvest claimed with
+//! [`crate::endpoints::claim_eligible_harvest`] is put into the farmer's escrow.
+//! Farmer uses this endpoint to transfer the part of the escrow of a single
+//! harvest mint which has vested linearly so far, see
+//! [`crate::models::AvailableHarvest::escrowed`].
+
+use crate::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct ClaimVestedHarvest<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            Farmer::ACCOUNT_PREFIX,
+            farmer.farm.as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub farmer: Account<'info, Farmer>,
+    /// Keeps track of how many tokens were distributed per harvest mint.
+    #[account(
+        mut,
+        constraint = farm.key() == farmer.farm
+            @ err::acc("Farmer is set up for a different farm"),
+    )]
+    pub farm: AccountLoader<'info, Farm>,
+    /// CHECK: UNSAFE_CODES.md#signer
+    #[account(
+        seeds = [Farm::SIGNER_PDA_PREFIX, farmer.farm.as_ref()],
+        bump,
+    )]
+    pub farm_signer_pda: AccountInfo<'info>,
+    /// Vested tokens are transferred FROM this vault.
+    #[account(
+        mut,
+        seeds = [
+            Harvest::VAULT_PREFIX,
+            farmer.farm.as_ref(),
+            harvest_vault.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub harvest_vault: Account<'info, TokenAccount>,
+    /// Vested tokens are transferred INTO this wallet.
+    #[account(mut)]
+    pub harvest_wallet: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handle(ctx: Context<ClaimVestedHarvest>) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    let current_slot = Slot::current()?;
+    let mint = accounts.harvest_vault.mint;
+
+    let released = accounts.farmer.release_escrow(mint, current_slot)?;
+    if released.amount == 0 {
+        return Err(error!(err::arg("There are no vested tokens to claim")));
+    }
+
+    let pda_seeds = &[
+        Farm::SIGNER_PDA_PREFIX,
+        accounts.farmer.farm.as_ref(),
+        &[*ctx.bumps.get("farm_signer_pda").unwrap()],
+    ];
+    token::transfer(
+        accounts
+            .as_transfer_vested_harvest_context()
+            .with_signer(&[&pda_seeds[..]]),
+        released.amount,
+    )?;
+
+    accounts
+        .farm
+        .load_mut()?
+        .add_to_total_distributed(mint, released);
+
+    Ok(())
+}
+
+impl<'info> ClaimVestedHarvest<'info> {
+    fn as_transfer_vested_harvest_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+        let cpi_accounts = token::Transfer {
+            from: self.harvest_vault.to_account_info(),
+            to: self.harvest_wallet.to_account_info(),
+            authority: self.farm_signer_pda.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
@@ -1,6 +1,9 @@
 //! If the [`Farmer`] has no more staked/vested tokens and all harvests have
 //! been claimed, then the account is empty and can be closed without losing
-//! funds.
+//! funds, see [`Farmer::check_is_empty`].
+//!
+//! To unstake, claim and close in one transaction, see
+//! [`crate::endpoints::exit_farm`].
 
 use crate::prelude::*;
 
@@ -18,15 +21,5 @@ pub struct CloseFarmer<'info> {
 }
 
 pub fn handle(ctx: Context<CloseFarmer>) -> Result<()> {
-    let farmer = &ctx.accounts.farmer;
-
-    if farmer.total_deposited()?.amount != 0 {
-        return Err(error!(err::acc("Unstake all farmer's tokens")));
-    }
-
-    if farmer.harvests.iter().any(|h| h.tokens.amount != 0) {
-        return Err(error!(err::acc("Claim all farmer's harvest")));
-    }
-
-    Ok(())
+    ctx.accounts.farmer.check_is_empty()
 }
@@ -8,6 +8,19 @@
 //! [`crate::endpoints::whitelist_farm_for_compounding`]. For the same logic but
 //! operating only in single farm (harvest and stake vault both under the same
 //! farm), see endpoint [`crate::endpoints::compound_same_farm`].
+//!
+//! Since the compounded amount depends on when the transaction lands, the
+//! farmer bounds it with a minimum amount to compound and a deadline slot
+//! after which the transaction fails, see
+//! [`crate::endpoints::set_compound_bounds`]. The bounds are stored on the
+//! source farmer, because anyone can call this endpoint.
+//!
+//! If the source farm vests claimed harvest, see
+//! [`Farm::claim_vesting_slots`], the harvest is put into the farmer's escrow
+//! and only its vested part is compounded. Compounding counts as a claim for
+//! the source farm's cooldown, see [`Farm::claim_cooldown_slots`], therefore
+//! a bot which compounds the farmer's harvest also postpones the farmer's
+//! next claim.
 
 use crate::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount};
@@ -15,6 +28,7 @@ use anchor_spl::token::{self, Token, TokenAccount};
 #[derive(Accounts)]
 pub struct CompoundAcrossFarms<'info> {
     /// Farm which emits the harvest.
+    #[account(mut)]
     pub source_farm: AccountLoader<'info, Farm>,
     /// Farm which receives the harvest.
     #[account(
@@ -87,7 +101,7 @@ pub struct CompoundAcrossFarms<'info> {
 pub fn handle(ctx: Context<CompoundAcrossFarms>) -> Result<()> {
     let accounts = ctx.accounts;
 
-    let source_farm = accounts.source_farm.load()?;
+    let mut source_farm = accounts.source_farm.load_mut()?;
     let target_farm = accounts.target_farm.load()?;
     let current_slot = Slot::current()?;
 
@@ -95,14 +109,21 @@ pub fn handle(ctx: Context<CompoundAcrossFarms>) -> Result<()> {
         .source_farmer
         .check_vested_period_and_update_harvest(&source_farm, current_slot)?;
 
-    // get all harvestable tokens of the farmer and add them to their vested
-    // tokens
-    let compound_tokens = accounts
+    // get all harvestable tokens of the farmer which aren't vesting and add
+    // them to their vested tokens
+    let compound_tokens = accounts.source_farmer.claim_harvest_to_compound(
+        &source_farm,
+        target_farm.stake_mint,
+        current_slot,
+    )?;
+    accounts
         .source_farmer
-        .claim_harvest(target_farm.stake_mint)?;
+        .check_compound_bounds(current_slot, compound_tokens)?;
     accounts
         .target_farmer
         .add_to_vested(current_slot, compound_tokens)?;
+    source_farm
+        .add_to_total_distributed(target_farm.stake_mint, compound_tokens);
 
     // transfer all those harvestable tokens to the stake vault
     let pda_seeds = &[
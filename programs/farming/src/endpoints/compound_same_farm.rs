@@ -7,6 +7,16 @@
 //! [`crate::endpoints::whitelist_farm_for_compounding`]. For the same logic
 //! across different farms see endpoint
 //! [`crate::endpoints::compound_across_farms`].
+//!
+//! If the farm vests claimed harvest, see [`Farm::claim_vesting_slots`], the
+//! harvest is put into the farmer's escrow and only its vested part is
+//! compounded. Compounding counts as a claim for the farm's cooldown, see
+//! [`Farm::claim_cooldown_slots`], therefore a bot which compounds the
+//! farmer's harvest also postpones the farmer's next claim.
+//!
+//! The farmer bounds the compounded amount with a minimum and a deadline slot
+//! after which the transaction fails, see
+//! [`crate::endpoints::set_compound_bounds`].
 
 use crate::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount};
@@ -14,6 +24,7 @@ use anchor_spl::token::{self, Token, TokenAccount};
 #[derive(Accounts)]
 pub struct CompoundSameFarm<'info> {
     /// Used to update eligible harvest of the farmer.
+    #[account(mut)]
     pub farm: AccountLoader<'info, Farm>,
     /// CHECK: UNSAFE_CODES.md#signer
     #[account(
@@ -70,19 +81,28 @@ pub struct CompoundSameFarm<'info> {
 pub fn handle(ctx: Context<CompoundSameFarm>) -> Result<()> {
     let accounts = ctx.accounts;
 
-    let farm = accounts.farm.load()?;
+    let mut farm = accounts.farm.load_mut()?;
     let current_slot = Slot::current()?;
 
     accounts
         .farmer
         .check_vested_period_and_update_harvest(&farm, current_slot)?;
 
-    // get all harvestable tokens of the farmer and add them to their vested
-    // tokens
-    let compound_tokens = accounts.farmer.claim_harvest(farm.stake_mint)?;
+    // get all harvestable tokens of the farmer which aren't vesting and add
+    // them to their vested tokens
+    let stake_mint = farm.stake_mint;
+    let compound_tokens = accounts.farmer.claim_harvest_to_compound(
+        &farm,
+        stake_mint,
+        current_slot,
+    )?;
+    accounts
+        .farmer
+        .check_compound_bounds(current_slot, compound_tokens)?;
     accounts
         .farmer
         .add_to_vested(current_slot, compound_tokens)?;
+    farm.add_to_total_distributed(stake_mint, compound_tokens);
 
     // transfer all those harvestable tokens to the stake vault
     let pda_seeds = &[
@@ -0,0 +1,58 @@
+//! Admin raises the amount of staked tokens recorded by the latest snapshot,
+//! e.g. if a bug caused a snapshot to record less than the stake vault
+//! balance. Farmers' share of the harvest in the snapshot window is their
+//! stake divided by the recorded amount, therefore a too low amount
+//! distributes more harvest than the periods emit.
+//!
+//! Only the latest snapshot can be corrected, and the corrected amount must
+//! be more than the recorded one and at most the current stake vault balance,
+//! see [`Farm::correct_latest_snapshot`]. Harvest which farmers have already
+//! been credited with for the window is not recalculated.
+//!
+//! The previous and the corrected amount are logged for auditability.
+
+use crate::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+#[derive(Accounts)]
+pub struct CorrectSnapshot<'info> {
+    /// The ownership over the farm is checked in the [`handle`] function.
+    pub admin: Signer<'info>,
+    #[account(mut)]
+    pub farm: AccountLoader<'info, Farm>,
+    /// The link to the farm is checked in the [`handle`] function.
+    pub stake_vault: Account<'info, TokenAccount>,
+}
+
+pub fn handle(
+    ctx: Context<CorrectSnapshot>,
+    staked: TokenAmount,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    let mut farm = accounts.farm.load_mut()?;
+
+    if farm.admin != accounts.admin.key() {
+        return Err(error!(FarmingError::FarmAdminMismatch));
+    }
+
+    if accounts.stake_vault.key() != farm.stake_vault {
+        return Err(error!(err::acc(
+            "The provided stake vault does \
+            not correspond to the Farm stake vault"
+        )));
+    }
+
+    let previous = farm.correct_latest_snapshot(
+        staked,
+        TokenAmount::new(accounts.stake_vault.amount),
+    )?;
+    msg!(
+        "Snapshot started at slot {} corrected from {} to {} staked tokens",
+        farm.latest_snapshot().started_at.slot,
+        previous.amount,
+        staked.amount
+    );
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+//! Permission-less alternative to [`crate::endpoints::take_snapshot`] which
+//! rewards the caller for keeping the accrual up to date.
+//!
+//! The snapshot is subject to the same minimum window as any other snapshot.
+//! If the stake vault amount changed since the latest snapshot, the caller
+//! receives [`Farm::keeper_reward`] tokens from [`Farm::keeper_reward_vault`].
+//! If the stake didn't change, the snapshot is still taken, but there's no
+//! reward, so that an idle farm doesn't pay for snapshots which don't change
+//! the accrual. The paid reward is recorded in
+//! [`Harvest::total_distributed`].
+//!
+//! The reward is paid at most once per
+//! [`consts::KEEPER_REWARD_WINDOW_SLOTS`], even if the farm's snapshot window
+//! is shorter. Otherwise a keeper could change their own stake by a single
+//! token before each snapshot and earn the reward repeatedly.
+//!
+//! If the keeper reward vault doesn't hold enough tokens or the remaining
+//! allowance of the farm signer is too small, the reward is skipped rather
+//! than failing the snapshot.
+
+use crate::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct CrankSnapshot<'info> {
+    #[account(mut)]
+    pub farm: AccountLoader<'info, Farm>,
+    /// The link to the farm is checked in the [`handle`] function.
+    pub stake_vault: Account<'info, TokenAccount>,
+    /// CHECK: UNSAFE_CODES.md#signer
+    #[account(
+        seeds = [Farm::SIGNER_PDA_PREFIX, farm.key().as_ref()],
+        bump,
+    )]
+    pub farm_signer_pda: AccountInfo<'info>,
+    /// Keeper reward is transferred FROM this vault. If the farm pays a
+    /// reward, it's checked against [`Farm::keeper_reward_vault`] in the
+    /// [`handle`] function.
+    #[account(mut)]
+    pub keeper_reward_vault: Account<'info, TokenAccount>,
+    /// Keeper reward is transferred INTO this wallet.
+    #[account(mut)]
+    pub keeper_wallet: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handle(ctx: Context<CrankSnapshot>) -> Result<()> {
+    let farm_signer_bump_seed = *ctx.bumps.get("farm_signer_pda").unwrap();
+
+    let accounts = ctx.accounts;
+
+    let mut farm = accounts.farm.load_mut()?;
+
+    if accounts.stake_vault.key() != farm.stake_vault {
+        return Err(error!(err::acc(
+            "The provided stake vault does \
+            not correspond to the Farm stake vault"
+        )));
+    }
+
+    let reward = farm.keeper_reward.amount;
+    if reward != 0
+        && accounts.keeper_reward_vault.key() != farm.keeper_reward_vault
+    {
+        return Err(error!(err::acc(
+            "The provided keeper reward vault does \
+            not correspond to the Farm keeper reward vault"
+        )));
+    }
+
+    let now = Slot::current()?;
+    let staked = TokenAmount::new(accounts.stake_vault.amount);
+    let has_stake_changed = farm.has_stake_changed_since_last_snapshot(staked);
+
+    farm.take_snapshot(now, staked)?;
+
+    if !has_stake_changed || reward == 0 {
+        return Ok(());
+    }
+
+    if !farm.is_keeper_reward_due(now) {
+        msg!(
+            "Keeper reward was already paid at slot {}",
+            farm.keeper_rewarded_at.slot
+        );
+        return Ok(());
+    }
+
+    let vault = &accounts.keeper_reward_vault;
+    let can_pay_reward = vault.delegate
+        == COption::Some(accounts.farm_signer_pda.key())
+        && vault.delegated_amount >= reward
+        && vault.amount >= reward;
+    if !can_pay_reward {
+        msg!("Keeper reward vault cannot pay the reward of {}", reward);
+        return Ok(());
+    }
+
+    let signer_seed = &[
+        Farm::SIGNER_PDA_PREFIX,
+        &accounts.farm.key().to_bytes()[..],
+        &[farm_signer_bump_seed],
+    ];
+
+    msg!("Paying keeper reward of {} tokens", reward);
+    token::transfer(
+        CpiContext::new(
+            accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: accounts.keeper_reward_vault.to_account_info(),
+                to: accounts.keeper_wallet.to_account_info(),
+                authority: accounts.farm_signer_pda.to_account_info(),
+            },
+        )
+        .with_signer(&[&signer_seed[..]]),
+        reward,
+    )?;
+    farm.keeper_rewarded_at = now;
+    farm.add_to_total_distributed(
+        accounts.keeper_reward_vault.mint,
+        TokenAmount::new(reward),
+    );
+
+    Ok(())
+}
@@ -1,6 +1,10 @@
 //! Initializes new [`Farm`] account. After this call, the admin must
 //! add [`Harvest`] for each reward mint they want to distribute using
 //! the [`crate::endpoints::add_harvest`] endpoint.
+//!
+//! The admin can delay the start of harvest emissions with `starts_at`, see
+//! [`Farm::harvests_start_at`]. No harvest period can start before that slot.
+//! Use `starts_at = 0` to allow harvest periods to start immediately.
 
 use crate::prelude::*;
 use anchor_spl::token::{self, Mint, Token, TokenAccount};
@@ -38,15 +42,23 @@ pub struct CreateFarm<'info> {
     pub rent: AccountInfo<'info>,
 }
 
-pub fn handle(ctx: Context<CreateFarm>) -> Result<()> {
+pub fn handle(ctx: Context<CreateFarm>, starts_at: Slot) -> Result<()> {
     let farm_signer_bump_seed = *ctx.bumps.get("farm_signer_pda").unwrap();
     let accounts = ctx.accounts;
 
+    if starts_at.slot != 0 && starts_at < Slot::current()? {
+        return Err(error!(err::arg(
+            "Farm's harvests cannot start in the past, \
+            use 0 to allow harvest periods to start immediately"
+        )));
+    }
+
     let mut farm = accounts.farm.load_init()?;
 
     farm.admin = accounts.admin.key();
     farm.stake_mint = accounts.stake_mint.key();
     farm.stake_vault = accounts.stake_vault.key();
+    farm.harvests_start_at = starts_at;
 
     msg!("Initializing stake vault");
 
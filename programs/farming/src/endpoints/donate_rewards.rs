@@ -0,0 +1,92 @@
+//! Anyone can donate harvest tokens to the farmers who currently stake. Unlike
+//! a harvest period, which emits tokens over time, or a plain transfer into
+//! the harvest vault, which only the admin can later reclaim, donated tokens
+//! accrue immediately.
+//!
+//! This endpoint transfers given amount of tokens into a harvest vault and
+//! splits them among farmers proportionally to the tokens they deposited,
+//! whether already staked or still vested, see [`Farm::donate`]. The total is
+//! read from the stake vault, which holds the deposits of all farmers. A
+//! farmer's share is added to their eligible harvest the next time the farmer
+//! is updated, e.g. with [`crate::endpoints::update_eligible_harvest`], and
+//! can then be claimed right away.
+//!
+//! Tokens which someone transferred into the stake vault directly don't
+//! belong to any farmer. Their share is never allocated and stays in the
+//! harvest vault.
+
+use crate::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct DonateRewards<'info> {
+    /// Authority over the `donor_wallet`.
+    pub donor: Signer<'info>,
+    #[account(mut)]
+    pub farm: AccountLoader<'info, Farm>,
+    /// Holds the tokens deposited by all farmers, which the donation is split
+    /// by.
+    #[account(
+        seeds = [Farm::STAKE_VAULT_PREFIX, farm.key().as_ref()],
+        bump,
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+    /// Donated amount is transferred FROM this wallet.
+    ///
+    /// CHECK: UNSAFE_CODES.md#token
+    #[account(mut)]
+    pub donor_wallet: Account<'info, TokenAccount>,
+    /// Donated amount is transferred INTO this vault.
+    ///
+    /// CHECK: UNSAFE_CODES.md#token
+    #[account(
+        mut,
+        seeds = [
+            Harvest::VAULT_PREFIX,
+            farm.key().as_ref(),
+            donor_wallet.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub harvest_vault: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handle(
+    ctx: Context<DonateRewards>,
+    donation: TokenAmount,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    if donation.amount == 0 {
+        return Err(error!(err::arg(
+            "The provided donation amount needs to be bigger than zero"
+        )));
+    }
+
+    let mut farm = accounts.farm.load_mut()?;
+    farm.donate(
+        accounts.donor_wallet.mint,
+        donation,
+        TokenAmount::new(accounts.stake_vault.amount),
+    )?;
+
+    // from donor's wallet to farm's vault
+    token::transfer(accounts.as_donate_context(), donation.amount)?;
+
+    Ok(())
+}
+
+impl<'info> DonateRewards<'info> {
+    fn as_donate_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+        let cpi_accounts = token::Transfer {
+            from: self.donor_wallet.to_account_info(),
+            to: self.harvest_vault.to_account_info(),
+            authority: self.donor.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
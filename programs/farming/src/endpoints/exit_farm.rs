@@ -0,0 +1,185 @@
+//! Unstakes all of the farmer's tokens, claims their remaining harvest and
+//! closes the [`Farmer`] account, returning its rent to the authority. This is
+//! the same as calling [`crate::endpoints::stop_farming`],
+//! [`crate::endpoints::claim_eligible_harvest`] and
+//! [`crate::endpoints::close_farmer`] in this order, but the user cannot end
+//! up with a stale farmer account if one of them fails.
+//!
+//! Fails if the stake is still locked, see [`Farm::lockup_slots`], or if any
+//! harvest is left in the farmer after the claim, e.g. because a pair for one
+//! of the harvest mints wasn't provided or because the farm vests claimed
+//! harvest, see [`Farm::claim_vesting_slots`]. The claim cooldown, see
+//! [`Farm::claim_cooldown_slots`], doesn't apply because the farmer is closed.
+//!
+//! # Additional accounts
+//! Pairs of token accounts for each harvest mint the farmer is eligible for,
+//! where the first member of each pair is the harvest vault and the second
+//! member is the farmer's harvest wallet, same as in
+//! [`crate::endpoints::claim_eligible_harvest`].
+//!
+//! ```text
+//! [
+//!   harvest_vault1,
+//!   harvest_wallet1,
+//!   harvest_vault2,
+//!   harvest_wallet2,
+//!   ...
+//! ]
+//! ```
+
+use crate::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct ExitFarm<'info> {
+    /// Over the [`Farmer`] account. Receives the farmer's rent.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            Farmer::ACCOUNT_PREFIX,
+            farm.key().as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump,
+        close = authority,
+    )]
+    pub farmer: Account<'info, Farmer>,
+    /// Unstaked tokens are transferred INTO this wallet.
+    ///
+    /// CHECK: UNSAFE_CODES.md#token
+    #[account(mut)]
+    pub stake_wallet: AccountInfo<'info>,
+    #[account(mut)]
+    pub farm: AccountLoader<'info, Farm>,
+    /// CHECK: UNSAFE_CODES.md#signer
+    #[account(
+        seeds = [Farm::SIGNER_PDA_PREFIX, farm.key().as_ref()],
+        bump,
+    )]
+    pub farm_signer_pda: AccountInfo<'info>,
+    /// CHECK: UNSAFE_CODES.md#token
+    #[account(
+        mut,
+        seeds = [
+            Farm::STAKE_VAULT_PREFIX,
+            farm.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub stake_vault: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handle<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExitFarm<'info>>,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    if ctx.remaining_accounts.len() % 2 != 0 {
+        return Err(error!(err::acc(
+            "Remaining accounts must come in pairs for exit_farm"
+        )));
+    }
+
+    let farm_key = accounts.farm.key();
+    let pda_seeds = &[
+        Farm::SIGNER_PDA_PREFIX,
+        farm_key.as_ref(),
+        &[*ctx.bumps.get("farm_signer_pda").unwrap()],
+    ];
+
+    let mut farm = accounts.farm.load_mut()?;
+    let current_slot = Slot::current()?;
+
+    accounts.farmer.check_stake_unlocked(&farm, current_slot)?;
+
+    accounts
+        .farmer
+        .check_vested_period_and_update_harvest(&farm, current_slot)?;
+
+    accounts.farmer.accumulate_stake(current_slot)?;
+    let total_deposited = accounts.farmer.total_deposited()?;
+    let unstake = accounts.farmer.unstake(total_deposited)?;
+    if unstake.amount > 0 {
+        token::transfer(
+            accounts
+                .as_unstake_tokens_context()
+                .with_signer(&[&pda_seeds[..]]),
+            unstake.amount,
+        )?;
+    }
+
+    for accs in ctx.remaining_accounts.chunks(2) {
+        let vault = &accs[0];
+        let wallet = &accs[1];
+
+        let data_ref = vault.try_borrow_data()?;
+        let mut data: &[u8] = &data_ref;
+        let mint = TokenAccount::try_deserialize(&mut data)?.mint;
+        // decrement borrow ref before we give the buffer to token program
+        drop(data_ref);
+
+        let (expected_vault, _) = Pubkey::find_program_address(
+            &[Harvest::VAULT_PREFIX, farm_key.as_ref(), mint.as_ref()],
+            ctx.program_id,
+        );
+        if expected_vault != vault.key() {
+            return Err(error!(err::acc(format!(
+                "Harvest vault for mint '{}' expected to be '{}' but got '{}'",
+                mint,
+                expected_vault,
+                vault.key()
+            ))));
+        }
+
+        let transfer = accounts.farmer.claim_eligible_harvest(
+            mint,
+            current_slot,
+            farm.claim_vesting_slots,
+        )?;
+        if transfer.amount > 0 {
+            token::transfer(
+                accounts
+                    .as_transfer_eligible_harvest_context(
+                        vault.to_account_info(),
+                        wallet.to_account_info(),
+                    )
+                    .with_signer(&[&pda_seeds[..]]),
+                transfer.amount,
+            )?;
+            farm.add_to_total_distributed(mint, transfer);
+        }
+    }
+
+    accounts.farmer.check_is_empty()
+}
+
+impl<'info> ExitFarm<'info> {
+    fn as_unstake_tokens_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+        let cpi_accounts = token::Transfer {
+            from: self.stake_vault.to_account_info(),
+            to: self.stake_wallet.to_account_info(),
+            authority: self.farm_signer_pda.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+
+    fn as_transfer_eligible_harvest_context(
+        &self,
+        vault: AccountInfo<'info>,
+        wallet: AccountInfo<'info>,
+    ) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+        let cpi_accounts = token::Transfer {
+            from: vault,
+            to: wallet,
+            authority: self.farm_signer_pda.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
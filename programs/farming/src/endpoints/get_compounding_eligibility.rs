@@ -0,0 +1,54 @@
+//! Read-only endpoint for clients and compounding bots. It writes whether the
+//! farmer's harvest can be compounded into the target farm as the
+//! instruction's return data, see [`CompoundingEligibility`].
+//!
+//! For [`crate::endpoints::compound_same_farm`] provide the farm as the target
+//! farm as well.
+
+use crate::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
+
+#[derive(Accounts)]
+pub struct GetCompoundingEligibility<'info> {
+    /// The farm which harvests the tokens to compound.
+    pub farm: AccountLoader<'info, Farm>,
+    /// The farm into which the tokens are staked.
+    pub target_farm: AccountLoader<'info, Farm>,
+    #[account(
+        constraint = farmer.farm == farm.key()
+            @ err::acc("Farmer is set up for a different farm"),
+    )]
+    pub farmer: Account<'info, Farmer>,
+    /// CHECK: UNSAFE_CODES.md#signer
+    /// The whitelist is signaled by the existence of the following PDA, which
+    /// therefore might not be initialized.
+    #[account(
+        seeds = [
+            Farm::WHITELIST_PDA_PREFIX,
+            farm.key().as_ref(),
+            target_farm.key().as_ref()
+        ],
+        bump,
+    )]
+    pub whitelist_compounding: AccountInfo<'info>,
+}
+
+pub fn handle(ctx: Context<GetCompoundingEligibility>) -> Result<()> {
+    let accs = ctx.accounts;
+
+    // closed accounts are reassigned to the system program
+    let is_whitelisted = *accs.whitelist_compounding.owner == crate::ID
+        && !accs.whitelist_compounding.data_is_empty();
+
+    let farm = accs.farm.load()?;
+    let target_stake_mint = accs.target_farm.load()?.stake_mint;
+    let eligibility = farm.compounding_eligibility(
+        is_whitelisted,
+        target_stake_mint,
+        &accs.farmer,
+    );
+
+    set_return_data(&eligibility.try_to_vec()?);
+
+    Ok(())
+}
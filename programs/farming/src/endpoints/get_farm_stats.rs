@@ -0,0 +1,34 @@
+//! Read-only endpoint for dashboards. It writes the aggregate stats of the
+//! farm as the instruction's return data, see [`FarmStats`].
+//!
+//! The total staked amount is read from the stake vault, the rest are counters
+//! maintained on the [`Farm`] account.
+//!
+//! The farm doesn't count its farmers, because that would write-lock the farm
+//! whenever a farmer is created or closed. Dashboards can count the
+//! [`Farmer`] accounts of a farm with a `getProgramAccounts` filter instead.
+
+use crate::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
+use anchor_spl::token::TokenAccount;
+
+#[derive(Accounts)]
+pub struct GetFarmStats<'info> {
+    pub farm: AccountLoader<'info, Farm>,
+    #[account(
+        seeds = [Farm::STAKE_VAULT_PREFIX, farm.key().as_ref()],
+        bump,
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+}
+
+pub fn handle(ctx: Context<GetFarmStats>) -> Result<()> {
+    let accs = ctx.accounts;
+
+    let farm = accs.farm.load()?;
+    let stats = farm.stats(TokenAmount::new(accs.stake_vault.amount));
+
+    set_return_data(&stats.try_to_vec()?);
+
+    Ok(())
+}
@@ -0,0 +1,22 @@
+//! Read-only endpoint for reward campaigns which weight by the average stake
+//! over time rather than by the stake in a snapshot. It writes the farmer's
+//! time-weighted average of deposited tokens until the current slot as the
+//! instruction's return data, see [`AverageStake`].
+
+use crate::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
+
+#[derive(Accounts)]
+pub struct GetFarmerAverageStake<'info> {
+    pub farmer: Account<'info, Farmer>,
+}
+
+pub fn handle(ctx: Context<GetFarmerAverageStake>) -> Result<()> {
+    let accs = ctx.accounts;
+
+    let average_stake = accs.farmer.average_stake(Slot::current()?)?;
+
+    set_return_data(&average_stake.try_to_vec()?);
+
+    Ok(())
+}
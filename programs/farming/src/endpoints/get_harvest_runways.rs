@@ -0,0 +1,53 @@
+//! Read-only endpoint for operators who want to know how long the current
+//! emissions last. It writes a [`HarvestRunway`] for each provided harvest
+//! vault as the instruction's return data, see [`Farm::harvest_runway`].
+//!
+//! # Remaining accounts
+//! Harvest vaults of the farm, in any order. The return data lists the
+//! runways in the same order.
+
+use crate::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
+use anchor_spl::token::TokenAccount;
+
+#[derive(Accounts)]
+pub struct GetHarvestRunways<'info> {
+    pub farm: AccountLoader<'info, Farm>,
+}
+
+pub fn handle(ctx: Context<GetHarvestRunways>) -> Result<()> {
+    let accs = ctx.accounts;
+
+    if ctx.remaining_accounts.is_empty() {
+        return Err(error!(err::acc(
+            "Provide at least one harvest vault as remaining account"
+        )));
+    }
+
+    let farm = accs.farm.load()?;
+    let now = Slot::current()?;
+
+    let runways = ctx
+        .remaining_accounts
+        .iter()
+        .map(|vault_info| {
+            let vault = Account::<TokenAccount>::try_from(vault_info)?;
+            let is_farm_vault = farm
+                .harvests
+                .iter()
+                .any(|h| h.mint == vault.mint && h.vault == vault.key());
+            if !is_farm_vault {
+                return Err(error!(err::acc(format!(
+                    "Account '{}' is not a harvest vault of the farm",
+                    vault.key()
+                ))));
+            }
+
+            farm.harvest_runway(now, vault.mint, TokenAmount::new(vault.amount))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    set_return_data(&runways.try_to_vec()?);
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+//! Read-only endpoint for auditors and analytics who want to reconstruct the
+//! accrual history without replaying instructions. It writes a page of the
+//! farm's snapshots as the instruction's return data, see [`SnapshotsPage`].
+//!
+//! The return data is limited in size, therefore the snapshots are paged. A
+//! page starts with the `offset`-th oldest snapshot and has at most `limit`
+//! snapshots, but no more than [`consts::MAX_SNAPSHOTS_PER_PAGE`]. The page is
+//! empty once the offset reaches the total number of snapshots.
+
+use crate::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
+
+#[derive(Accounts)]
+pub struct GetSnapshots<'info> {
+    pub farm: AccountLoader<'info, Farm>,
+}
+
+pub fn handle(
+    ctx: Context<GetSnapshots>,
+    offset: u64,
+    limit: u64,
+) -> Result<()> {
+    let accs = ctx.accounts;
+
+    if limit == 0 {
+        return Err(error!(err::arg("Page must have at least one snapshot")));
+    }
+
+    let farm = accs.farm.load()?;
+    let limit = (limit as usize).min(consts::MAX_SNAPSHOTS_PER_PAGE);
+    let page = farm.snapshots_page(offset as usize, limit);
+
+    set_return_data(&page.try_to_vec()?);
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+//! Converts a [`Farm`] account created before the harvests tracked pauses,
+//! distributed tokens and donations into the current layout, see
+//! [`LegacyFarm`]. Such an account cannot be used by any other endpoint until
+//! it's migrated, including [`crate::endpoints::migrate_farmer`] for the
+//! farm's farmers.
+//!
+//! The account is reallocated in place, therefore its pubkey doesn't change.
+//! Anyone can migrate a farm, the payer tops up the rent of the larger
+//! account.
+
+use crate::prelude::*;
+use anchor_lang::system_program;
+use std::mem;
+
+#[derive(Accounts)]
+pub struct MigrateFarm<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The layout is checked in the [`handle`] function, the farm is only
+    /// loaded once reallocated.
+    #[account(mut)]
+    pub farm: AccountLoader<'info, Farm>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle(ctx: Context<MigrateFarm>) -> Result<()> {
+    let accs = ctx.accounts;
+    let farm_info = accs.farm.to_account_info();
+
+    // the legacy farm is copied onto the heap, because the account data are
+    // overwritten below and the farm doesn't fit onto the stack
+    let legacy = {
+        let data = farm_info.try_borrow_data()?;
+        if data.len() != LegacyFarm::space() {
+            return Err(error!(err::acc(
+                "Farm account is not in the legacy layout"
+            )));
+        }
+
+        data[8..].to_vec()
+    };
+    let legacy: &LegacyFarm = bytemuck::from_bytes(&legacy);
+
+    let space = 8 + mem::size_of::<Farm>();
+    let rent = Rent::get()?.minimum_balance(space);
+    let top_up = rent.saturating_sub(farm_info.lamports());
+    if top_up != 0 {
+        system_program::transfer(accs.as_top_up_rent_context(), top_up)?;
+    }
+
+    farm_info.realloc(space, false)?;
+    farm_info.try_borrow_mut_data()?[8..].fill(0);
+    legacy.migrate(&mut *accs.farm.load_mut()?);
+
+    msg!("Farm {} migrated", accs.farm.key());
+
+    Ok(())
+}
+
+impl<'info> MigrateFarm<'info> {
+    fn as_top_up_rent_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, system_program::Transfer<'info>> {
+        let cpi_accounts = system_program::Transfer {
+            from: self.payer.to_account_info(),
+            to: self.farm.to_account_info(),
+        };
+        let cpi_program = self.system_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
@@ -0,0 +1,76 @@
+//! Converts a [`Farmer`] account created before the farmer tracked claimed
+//! totals, escrows, donations and the average stake into the current layout,
+//! see [`LegacyFarmer`]. Such an account cannot be used by any other endpoint
+//! until it's migrated.
+//!
+//! The account is reallocated in place, therefore its pubkey, which is a PDA
+//! of the farm and the authority, doesn't change. Anyone can migrate a farmer,
+//! the payer tops up the rent of the larger account.
+
+use crate::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+
+#[derive(Accounts)]
+pub struct MigrateFarmer<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub farm: AccountLoader<'info, Farm>,
+    /// CHECK: the account is in the legacy layout and therefore cannot be
+    /// deserialized as [`Farmer`], the layout, the discriminator and the farm
+    /// are checked in the [`handle`] function
+    #[account(mut, owner = crate::ID)]
+    pub farmer: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle(ctx: Context<MigrateFarmer>) -> Result<()> {
+    let accs = ctx.accounts;
+
+    let legacy = {
+        let data = accs.farmer.try_borrow_data()?;
+        if data.len() != LegacyFarmer::space() {
+            return Err(error!(err::acc(
+                "Farmer account is not in the legacy layout"
+            )));
+        }
+        if data[..8] != Farmer::discriminator() {
+            return Err(error!(err::acc("Account is not a farmer")));
+        }
+
+        LegacyFarmer::deserialize(&mut &data[8..])?
+    };
+
+    if legacy.farm != accs.farm.key() {
+        return Err(error!(err::acc("Farmer doesn't belong to this farm")));
+    }
+
+    let farmer = legacy.migrate(&*accs.farm.load()?);
+
+    let rent = Rent::get()?.minimum_balance(Farmer::space());
+    let top_up = rent.saturating_sub(accs.farmer.lamports());
+    if top_up != 0 {
+        system_program::transfer(accs.as_top_up_rent_context(), top_up)?;
+    }
+
+    accs.farmer.realloc(Farmer::space(), false)?;
+    let mut data = accs.farmer.try_borrow_mut_data()?;
+    farmer.try_serialize(&mut data.as_mut())?;
+
+    msg!("Farmer {} migrated", accs.farmer.key());
+
+    Ok(())
+}
+
+impl<'info> MigrateFarmer<'info> {
+    fn as_top_up_rent_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, system_program::Transfer<'info>> {
+        let cpi_accounts = system_program::Transfer {
+            from: self.payer.to_account_info(),
+            to: self.farmer.to_account_info(),
+        };
+        let cpi_program = self.system_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
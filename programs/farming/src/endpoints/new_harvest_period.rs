@@ -2,7 +2,9 @@
 //! already, then we create a new one (optionally in future as a scheduled
 //! launch.)
 //!
-//! The admin can default to current slot by using `starts_at = 0`.
+//! The admin can default to current slot by using `starts_at = 0`. If the
+//! farm's harvests start in future, see [`Farm::harvests_start_at`], then
+//! `starts_at = 0` defaults to that slot instead.
 //!
 //! Both `starts_at` and `ends_at` are inclusive.
 //!
@@ -10,7 +12,7 @@
 
 use crate::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount};
-use std::cmp::Ordering;
+use std::cmp::{self, Ordering};
 
 #[derive(Accounts)]
 #[instruction(harvest_mint: Pubkey)]
@@ -70,7 +72,7 @@ pub fn handle(
     let current_slot = Slot::current()?;
 
     if starts_at.slot == 0 {
-        starts_at = current_slot;
+        starts_at = cmp::max(current_slot, farm.harvests_start_at);
     } else if starts_at < current_slot {
         msg!(
             "Cannot start a new farming period in the past, \
@@ -90,27 +92,16 @@ pub fn handle(
             - 1,
     );
 
-    let scheduled_launch = farm.new_harvest_period(
+    // if we're overwriting a scheduled launch, then there have been tokens
+    // deposited already, so we only need to cover the difference
+    let tokens_deposited_for_scheduled_launch = farm.new_harvest_period(
         current_slot,
         harvest_mint,
         (starts_at, ends_at),
         tps,
     )?;
-
-    // if we're overwriting a scheduled launch, then there have been tokens
-    // deposited already, so we only need to cover the difference
     let new_period_total_tokens =
         total_tokens_emitted_per_period((starts_at, ends_at), tps)?;
-    let tokens_deposited_for_scheduled_launch = if let Some(HarvestPeriod {
-        starts_at,
-        ends_at,
-        tps,
-    }) = scheduled_launch
-    {
-        total_tokens_emitted_per_period((starts_at, ends_at), tps)?
-    } else {
-        TokenAmount::new(0)
-    };
 
     match new_period_total_tokens
         .amount
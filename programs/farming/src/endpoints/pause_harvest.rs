@@ -0,0 +1,30 @@
+//! Admin stops the emission of a single harvest of the farm, eg. when the
+//! reward token is compromised. Other harvests keep being emitted.
+//!
+//! The current slot is still emitted, the harvest stops from the next slot
+//! on. Farmers keep the harvest they've been eligible for until then. See
+//! [`crate::endpoints::resume_harvest`] to start the emission again.
+
+use crate::prelude::*;
+
+#[derive(Accounts)]
+pub struct PauseHarvest<'info> {
+    /// The ownership over the farm is checked in the [`handle`] function.
+    pub admin: Signer<'info>,
+    #[account(mut)]
+    pub farm: AccountLoader<'info, Farm>,
+}
+
+pub fn handle(ctx: Context<PauseHarvest>, harvest_mint: Pubkey) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    let mut farm = accounts.farm.load_mut()?;
+
+    if farm.admin != accounts.admin.key() {
+        return Err(error!(FarmingError::FarmAdminMismatch));
+    }
+
+    farm.pause_harvest(Slot::current()?, harvest_mint)?;
+
+    Ok(())
+}
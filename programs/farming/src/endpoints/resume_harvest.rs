@@ -0,0 +1,32 @@
+//! Admin resumes the emission of a harvest which was paused with
+//! [`crate::endpoints::pause_harvest`]. The harvest is emitted again from the
+//! next slot on, as defined by its periods.
+//!
+//! Nothing is emitted for the slots during which the harvest was paused, these
+//! slots are cut out of the harvest periods. The periods are not prolonged,
+//! so the tokens which would've been emitted during the pause stay in the
+//! harvest vault.
+
+use crate::prelude::*;
+
+#[derive(Accounts)]
+pub struct ResumeHarvest<'info> {
+    /// The ownership over the farm is checked in the [`handle`] function.
+    pub admin: Signer<'info>,
+    #[account(mut)]
+    pub farm: AccountLoader<'info, Farm>,
+}
+
+pub fn handle(ctx: Context<ResumeHarvest>, harvest_mint: Pubkey) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    let mut farm = accounts.farm.load_mut()?;
+
+    if farm.admin != accounts.admin.key() {
+        return Err(error!(FarmingError::FarmAdminMismatch));
+    }
+
+    farm.resume_harvest(Slot::current()?, harvest_mint)?;
+
+    Ok(())
+}
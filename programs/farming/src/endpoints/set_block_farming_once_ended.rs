@@ -0,0 +1,30 @@
+//! Admin decides whether [`crate::endpoints::start_farming`] should fail once
+//! all harvest periods of the farm have ended, see
+//! [`Farm::block_farming_once_ended`].
+
+use crate::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetBlockFarmingOnceEnded<'info> {
+    /// The ownership over the farm is checked in the [`handle`] function.
+    pub admin: Signer<'info>,
+    #[account(mut)]
+    pub farm: AccountLoader<'info, Farm>,
+}
+
+pub fn handle(
+    ctx: Context<SetBlockFarmingOnceEnded>,
+    block: bool,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    let mut farm = accounts.farm.load_mut()?;
+
+    if farm.admin != accounts.admin.key() {
+        return Err(error!(FarmingError::FarmAdminMismatch));
+    }
+
+    farm.block_farming_once_ended = block as u64;
+
+    Ok(())
+}
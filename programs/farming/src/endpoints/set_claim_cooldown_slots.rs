@@ -0,0 +1,29 @@
+//! Admin configures how many slots must pass between two claims of the same
+//! farmer, see [`Farm::claim_cooldown_slots`]. Zero disables the cooldown.
+
+use crate::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetClaimCooldownSlots<'info> {
+    /// The ownership over the farm is checked in the [`handle`] function.
+    pub admin: Signer<'info>,
+    #[account(mut)]
+    pub farm: AccountLoader<'info, Farm>,
+}
+
+pub fn handle(
+    ctx: Context<SetClaimCooldownSlots>,
+    claim_cooldown_slots: u64,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    let mut farm = accounts.farm.load_mut()?;
+
+    if farm.admin != accounts.admin.key() {
+        return Err(error!(FarmingError::FarmAdminMismatch));
+    }
+
+    farm.claim_cooldown_slots = claim_cooldown_slots;
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+//! Admin configures over how many slots the claimed harvest vests, see
+//! [`Farm::claim_vesting_slots`]. Zero disables vesting, but tokens which are
+//! already in farmers' escrows are still released as scheduled.
+
+use crate::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetClaimVestingSlots<'info> {
+    /// The ownership over the farm is checked in the [`handle`] function.
+    pub admin: Signer<'info>,
+    #[account(mut)]
+    pub farm: AccountLoader<'info, Farm>,
+}
+
+pub fn handle(
+    ctx: Context<SetClaimVestingSlots>,
+    claim_vesting_slots: u64,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    let mut farm = accounts.farm.load_mut()?;
+
+    if farm.admin != accounts.admin.key() {
+        return Err(error!(FarmingError::FarmAdminMismatch));
+    }
+
+    farm.claim_vesting_slots = claim_vesting_slots;
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+//! Farmer configures when bots may compound their harvest with
+//! [`crate::endpoints::compound_same_farm`] and
+//! [`crate::endpoints::compound_across_farms`], see [`Farmer::min_compound`]
+//! and [`Farmer::compound_deadline`]. Zero disables the respective bound.
+//!
+//! The compounding endpoints are permission-less, therefore the bounds must
+//! be stored on the farmer rather than chosen by whoever calls them.
+
+use crate::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetCompoundBounds<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            Farmer::ACCOUNT_PREFIX,
+            farmer.farm.as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub farmer: Account<'info, Farmer>,
+}
+
+pub fn handle(
+    ctx: Context<SetCompoundBounds>,
+    min_compound: TokenAmount,
+    compound_deadline: Slot,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    accounts.farmer.min_compound = min_compound;
+    accounts.farmer.compound_deadline = compound_deadline;
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+//! Admin configures whether harvest periods emit more tokens early and less
+//! later, see [`Farm::emission_front_load_percent`]. Zero percent means that
+//! periods emit a constant amount of tokens per slot.
+//!
+//! The setting applies to periods which are created afterwards with
+//! [`crate::endpoints::new_harvest_period`]. Existing periods keep their
+//! front-loading, therefore farmers' harvest which is calculated lazily
+//! doesn't change.
+
+use crate::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetEmissionFrontLoad<'info> {
+    /// The ownership over the farm is checked in the [`handle`] function.
+    pub admin: Signer<'info>,
+    #[account(mut)]
+    pub farm: AccountLoader<'info, Farm>,
+}
+
+pub fn handle(ctx: Context<SetEmissionFrontLoad>, percent: u64) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    let mut farm = accounts.farm.load_mut()?;
+
+    if farm.admin != accounts.admin.key() {
+        return Err(error!(FarmingError::FarmAdminMismatch));
+    }
+
+    if percent > consts::MAX_EMISSION_FRONT_LOAD_PERCENT {
+        return Err(error!(err::arg(format!(
+            "Emission front-load can be at most {}%",
+            consts::MAX_EMISSION_FRONT_LOAD_PERCENT
+        ))));
+    }
+
+    msg!("New harvest periods are front-loaded by {}%", percent);
+
+    farm.emission_front_load_percent = percent;
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+//! Admin schedules an event during which the harvest emission of given
+//! harvest mint is multiplied. Both `starts_at` and `ends_at` are inclusive.
+//! Once the event ends, the emission reverts to what the harvest periods
+//! configure.
+//!
+//! The multiplied emission is written into the harvest periods, see
+//! [`Farm::multiply_emission`], therefore the event only applies to slots
+//! covered by a harvest period. The admin deposits the extra tokens which the
+//! periods emit during the event into the harvest vault.
+//!
+//! The event must start after the current slot, because farmers' harvest is
+//! calculated lazily and might already include the current slot. Once
+//! scheduled, the event cannot be cancelled, however a harvest period which
+//! hasn't started yet can still be overwritten with
+//! [`crate::endpoints::new_harvest_period`].
+
+use crate::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+#[derive(Accounts)]
+#[instruction(harvest_mint: Pubkey)]
+pub struct SetEmissionMultiplier<'info> {
+    /// The ownership over the farm is checked in the [`handle`] function.
+    pub admin: Signer<'info>,
+    #[account(mut)]
+    pub farm: AccountLoader<'info, Farm>,
+    /// Admin's wallet which transfers the extra harvest tokens.
+    #[account(mut)]
+    pub harvest_wallet: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [
+            Harvest::VAULT_PREFIX,
+            farm.key().as_ref(),
+            harvest_mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub harvest_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handle(
+    ctx: Context<SetEmissionMultiplier>,
+    harvest_mint: Pubkey,
+    percent: u64,
+    starts_at: Slot,
+    ends_at: Slot,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    let mut farm = accounts.farm.load_mut()?;
+
+    if farm.admin != accounts.admin.key() {
+        return Err(error!(FarmingError::FarmAdminMismatch));
+    }
+
+    if !(101..=consts::MAX_EMISSION_MULTIPLIER_PERCENT).contains(&percent) {
+        return Err(error!(err::arg(format!(
+            "Emission multiplier must be more than 100% and at most {}%",
+            consts::MAX_EMISSION_MULTIPLIER_PERCENT
+        ))));
+    }
+
+    let current_slot = Slot::current()?;
+    if starts_at <= current_slot {
+        msg!("Emission multiplier event must start after the current slot");
+        return Err(error!(
+            FarmingError::HarvestPeriodMustStartAtOrAfterCurrentSlot
+        ));
+    }
+
+    let extra_tokens = farm.multiply_emission(
+        current_slot,
+        harvest_mint,
+        percent,
+        (starts_at, ends_at),
+    )?;
+
+    msg!(
+        "Emission is multiplied by {}% from slot {} until slot {}",
+        percent,
+        starts_at.slot,
+        ends_at.slot
+    );
+
+    if extra_tokens.amount > accounts.harvest_wallet.amount {
+        return Err(error!(err::acc(format!(
+            "Insufficient tokens in harvest wallet, must deposit {}",
+            extra_tokens.amount
+        ))));
+    }
+
+    if extra_tokens.amount != 0 {
+        token::transfer(
+            accounts.as_deposit_harvest_context(),
+            extra_tokens.amount,
+        )?;
+    }
+
+    Ok(())
+}
+
+impl<'info> SetEmissionMultiplier<'info> {
+    fn as_deposit_harvest_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+        let cpi_accounts = token::Transfer {
+            from: self.harvest_wallet.to_account_info(),
+            to: self.harvest_vault.to_account_info(),
+            authority: self.admin.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
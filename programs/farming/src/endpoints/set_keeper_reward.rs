@@ -0,0 +1,58 @@
+//! Admin configures the reward paid to whoever cranks a snapshot which records
+//! a stake change, see [`crate::endpoints::crank_snapshot`]. Zero tokens
+//! disables the reward.
+//!
+//! The reward is paid out of a token account of a harvest mint which delegates
+//! to the farm signer, see [`Farm::keeper_reward_vault`]. Since anyone can
+//! make the stake change, e.g. by transferring a single token into the stake
+//! vault, the keeper rewards are bounded only by the allowance the admin
+//! approves.
+
+use crate::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::TokenAccount;
+
+#[derive(Accounts)]
+pub struct SetKeeperReward<'info> {
+    /// The ownership over the farm is checked in the [`handle`] function.
+    pub admin: Signer<'info>,
+    #[account(mut)]
+    pub farm: AccountLoader<'info, Farm>,
+    /// CHECK: UNSAFE_CODES.md#signer
+    #[account(
+        seeds = [Farm::SIGNER_PDA_PREFIX, farm.key().as_ref()],
+        bump,
+    )]
+    pub farm_signer_pda: AccountInfo<'info>,
+    /// The farm's own vaults never delegate, therefore this cannot be one of
+    /// them.
+    #[account(
+        constraint = keeper_reward_vault.delegate
+            == COption::Some(farm_signer_pda.key())
+            @ err::acc("Keeper reward vault must delegate to the farm signer"),
+    )]
+    pub keeper_reward_vault: Account<'info, TokenAccount>,
+}
+
+pub fn handle(
+    ctx: Context<SetKeeperReward>,
+    tokens: TokenAmount,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    let mut farm = accounts.farm.load_mut()?;
+
+    if farm.admin != accounts.admin.key() {
+        return Err(error!(FarmingError::FarmAdminMismatch));
+    }
+
+    let mint = accounts.keeper_reward_vault.mint;
+    if !farm.harvests.iter().any(|h| h.mint == mint) {
+        return Err(error!(FarmingError::UnknownHarvestMintPubKey));
+    }
+
+    farm.keeper_reward_vault = accounts.keeper_reward_vault.key();
+    farm.keeper_reward = tokens;
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! Admin configures for how many slots after staking farmers cannot unstake,
+//! see [`Farm::lockup_slots`].
+//!
+//! A decrease of the lockup takes effect immediately and cancels any pending
+//! increase. An increase only takes effect after
+//! [`consts::LOCKUP_INCREASE_TIMELOCK_SLOTS`], so that farmers are not trapped
+//! by it unexpectedly. Until then, it's stored as the farm's pending lockup.
+//! The lockup cannot exceed [`consts::MAX_LOCKUP_SLOTS`].
+
+use crate::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetLockupSlots<'info> {
+    /// The ownership over the farm is checked in the [`handle`] function.
+    pub admin: Signer<'info>,
+    #[account(mut)]
+    pub farm: AccountLoader<'info, Farm>,
+}
+
+pub fn handle(ctx: Context<SetLockupSlots>, lockup_slots: u64) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    let mut farm = accounts.farm.load_mut()?;
+
+    if farm.admin != accounts.admin.key() {
+        return Err(error!(FarmingError::FarmAdminMismatch));
+    }
+
+    if lockup_slots > consts::MAX_LOCKUP_SLOTS {
+        return Err(error!(err::arg(format!(
+            "Lockup cannot be longer than {} slots",
+            consts::MAX_LOCKUP_SLOTS
+        ))));
+    }
+
+    let current_slot = Slot::current()?;
+    // a pending lockup which is already effective is not overwritten below
+    farm.apply_pending_lockup(current_slot);
+
+    if lockup_slots > farm.lockup_slots {
+        let effective_at = Slot::new(
+            current_slot.slot + consts::LOCKUP_INCREASE_TIMELOCK_SLOTS,
+        );
+        msg!(
+            "Lockup of {} slots takes effect at slot {}",
+            lockup_slots,
+            effective_at.slot
+        );
+
+        farm.pending_lockup_slots = lockup_slots;
+        farm.pending_lockup_effective_at = effective_at;
+    } else {
+        farm.lockup_slots = lockup_slots;
+        farm.cancel_pending_lockup();
+    }
+
+    Ok(())
+}
@@ -6,6 +6,10 @@
 //! This endpoint also updates all eligible harvest up until this point and sets
 //! [`Farmer`]'s `harvest_calculated_until` property to the current slot. This
 //! avoid a scenario where the newly staked tokens would affect past harvest.
+//!
+//! If the admin enabled [`Farm::block_farming_once_ended`], staking fails once
+//! all harvest periods of the farm have ended, because the farmer wouldn't
+//! earn anything.
 
 use crate::prelude::*;
 use anchor_spl::token::{self, Token};
@@ -57,6 +61,8 @@ pub fn handle(ctx: Context<StartFarming>, stake: TokenAmount) -> Result<()> {
     let farm = accounts.farm.load()?;
     let current_slot = Slot::current()?;
 
+    farm.check_farming_not_blocked(current_slot)?;
+
     accounts
         .farmer
         .check_vested_period_and_update_harvest(&farm, current_slot)?;
@@ -64,6 +70,8 @@ pub fn handle(ctx: Context<StartFarming>, stake: TokenAmount) -> Result<()> {
     // marks the funds as vested, they won't be eligible for harvest until the
     // next snapshot
     accounts.farmer.add_to_vested(current_slot, stake)?;
+    // deposited tokens cannot be unstaked until the farm's lockup elapses
+    accounts.farmer.lock_stake(&farm, current_slot, stake)?;
     // from farmer's wallet to farm's vault
     token::transfer(accounts.as_stake_tokens_context(), stake.amount)?;
 
@@ -1,5 +1,6 @@
 use crate::prelude::*;
 use anchor_spl::token::{self, Token};
+use std::cmp;
 
 #[derive(Accounts)]
 pub struct StopFarming<'info> {
@@ -51,13 +52,21 @@ pub fn handle(
     }
 
     let farm = accounts.farm.load()?;
+    let current_slot = Slot::current()?;
+
+    // only the tokens which are not locked can be unstaked
+    let unlocked = accounts.farmer.unlocked_stake(&farm, current_slot)?;
+    if unlocked.amount == 0 {
+        accounts.farmer.check_stake_unlocked(&farm, current_slot)?;
+    }
 
     accounts
         .farmer
-        .check_vested_period_and_update_harvest(&farm, Slot::current()?)?;
+        .check_vested_period_and_update_harvest(&farm, current_slot)?;
 
+    accounts.farmer.accumulate_stake(current_slot)?;
     // removes the amount of tokens to be unstaked from the
-    let unstake = accounts.farmer.unstake(unstake_max)?;
+    let unstake = accounts.farmer.unstake(cmp::min(unstake_max, unlocked))?;
     let pda_seeds = &[
         Farm::SIGNER_PDA_PREFIX,
         &accounts.farm.key().to_bytes()[..],
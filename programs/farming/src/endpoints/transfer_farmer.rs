@@ -0,0 +1,65 @@
+//! Moves a [`Farmer`] position to another user without unstaking. The
+//! [`Farmer`] account is a PDA of its authority, see
+//! [`crate::endpoints::create_farmer`], therefore the position cannot be
+//! transferred in place. Instead, a new [`Farmer`] account is created for the
+//! new authority, all of the staked, vested and harvest state is copied into
+//! it and the old account is closed. Rent of the old account is returned to
+//! the current authority, who pays for the new account.
+//!
+//! The new authority mustn't have a [`Farmer`] account in the same farm yet.
+
+use crate::prelude::*;
+
+#[derive(Accounts)]
+pub struct TransferFarmer<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: the user who becomes the authority over withdrawals and claims
+    #[account(
+        constraint = new_authority.key() != Pubkey::default()
+            @ err::acc("New authority mustn't be the default pubkey"),
+        constraint = new_authority.key() != authority.key()
+            @ err::acc("New authority must differ from the current one"),
+    )]
+    pub new_authority: AccountInfo<'info>,
+    pub farm: AccountLoader<'info, Farm>,
+    #[account(
+        mut,
+        constraint = farmer.authority == authority.key()
+            @ err::acc("Authority does not own this farmer"),
+        seeds = [
+            Farmer::ACCOUNT_PREFIX,
+            farm.key().as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump,
+        close = authority,
+    )]
+    pub farmer: Account<'info, Farmer>,
+    #[account(
+        init,
+        payer = authority,
+        space = Farmer::space(),
+        seeds = [
+            Farmer::ACCOUNT_PREFIX,
+            farm.key().as_ref(),
+            new_authority.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub new_farmer: Account<'info, Farmer>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle(ctx: Context<TransferFarmer>) -> Result<()> {
+    let accs = ctx.accounts;
+
+    // everything but the authority is preserved, the old account is closed
+    // on exit
+    *accs.new_farmer = Farmer {
+        authority: accs.new_authority.key(),
+        ..Farmer::clone(&accs.farmer)
+    };
+
+    Ok(())
+}
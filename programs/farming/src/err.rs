@@ -36,6 +36,20 @@ pub enum FarmingError {
     HarvestPeriodMustStartAtOrAfterCurrentSlot,
     #[msg("Cannot have a period that lasts 0 slots")]
     HarvestPeriodMustBeAtLeastOneSlot,
+    #[msg("Cannot start a harvest period before the farm's harvests start")]
+    HarvestPeriodMustStartAtOrAfterFarmStart,
+    #[msg("Would compound less tokens than the requested minimum")]
+    CompoundedLessThanMinimum,
+    #[msg("Farm's harvests have ended and it doesn't accept new stake")]
+    FarmingBlockedOnceEnded,
+    #[msg("Staked tokens are locked, see logs for when they unlock")]
+    StakeLocked,
+    #[msg("Transaction was executed after its deadline slot")]
+    DeadlineExceeded,
+    #[msg(
+        "Claimed too recently, see logs for when the farmer can claim again"
+    )]
+    ClaimCooldownNotElapsed,
 }
 
 pub fn acc(msg: impl Display) -> FarmingError {
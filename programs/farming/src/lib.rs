@@ -19,8 +19,11 @@ declare_id!("DFarMhaRkdYqhK5jZsexMftaJuWHrY7VzAfkXx5ZmxqZ");
 pub mod farming {
     use super::*;
 
-    pub fn create_farm(ctx: Context<CreateFarm>) -> Result<()> {
-        endpoints::create_farm::handle(ctx)
+    pub fn create_farm(
+        ctx: Context<CreateFarm>,
+        starts_at: Slot,
+    ) -> Result<()> {
+        endpoints::create_farm::handle(ctx, starts_at)
     }
 
     pub fn add_harvest(ctx: Context<AddHarvest>) -> Result<()> {
@@ -54,10 +57,42 @@ pub mod farming {
         )
     }
 
+    pub fn pause_harvest(
+        ctx: Context<PauseHarvest>,
+        harvest_mint: Pubkey,
+    ) -> Result<()> {
+        endpoints::pause_harvest::handle(ctx, harvest_mint)
+    }
+
+    pub fn resume_harvest(
+        ctx: Context<ResumeHarvest>,
+        harvest_mint: Pubkey,
+    ) -> Result<()> {
+        endpoints::resume_harvest::handle(ctx, harvest_mint)
+    }
+
     pub fn take_snapshot(ctx: Context<TakeSnapshot>) -> Result<()> {
         endpoints::take_snapshot::handle(ctx)
     }
 
+    pub fn correct_snapshot(
+        ctx: Context<CorrectSnapshot>,
+        staked: TokenAmount,
+    ) -> Result<()> {
+        endpoints::correct_snapshot::handle(ctx, staked)
+    }
+
+    pub fn crank_snapshot(ctx: Context<CrankSnapshot>) -> Result<()> {
+        endpoints::crank_snapshot::handle(ctx)
+    }
+
+    pub fn set_keeper_reward(
+        ctx: Context<SetKeeperReward>,
+        tokens: TokenAmount,
+    ) -> Result<()> {
+        endpoints::set_keeper_reward::handle(ctx, tokens)
+    }
+
     pub fn set_min_snapshot_window(
         ctx: Context<SetMinSnapshotWindow>,
         min_snapshot_window_slots: u64,
@@ -68,6 +103,57 @@ pub mod farming {
         )
     }
 
+    pub fn set_block_farming_once_ended(
+        ctx: Context<SetBlockFarmingOnceEnded>,
+        block: bool,
+    ) -> Result<()> {
+        endpoints::set_block_farming_once_ended::handle(ctx, block)
+    }
+
+    pub fn set_claim_cooldown_slots(
+        ctx: Context<SetClaimCooldownSlots>,
+        claim_cooldown_slots: u64,
+    ) -> Result<()> {
+        endpoints::set_claim_cooldown_slots::handle(ctx, claim_cooldown_slots)
+    }
+
+    pub fn set_claim_vesting_slots(
+        ctx: Context<SetClaimVestingSlots>,
+        claim_vesting_slots: u64,
+    ) -> Result<()> {
+        endpoints::set_claim_vesting_slots::handle(ctx, claim_vesting_slots)
+    }
+
+    pub fn set_emission_front_load(
+        ctx: Context<SetEmissionFrontLoad>,
+        percent: u64,
+    ) -> Result<()> {
+        endpoints::set_emission_front_load::handle(ctx, percent)
+    }
+
+    pub fn set_emission_multiplier(
+        ctx: Context<SetEmissionMultiplier>,
+        harvest_mint: Pubkey,
+        percent: u64,
+        starts_at: Slot,
+        ends_at: Slot,
+    ) -> Result<()> {
+        endpoints::set_emission_multiplier::handle(
+            ctx,
+            harvest_mint,
+            percent,
+            starts_at,
+            ends_at,
+        )
+    }
+
+    pub fn set_lockup_slots(
+        ctx: Context<SetLockupSlots>,
+        lockup_slots: u64,
+    ) -> Result<()> {
+        endpoints::set_lockup_slots::handle(ctx, lockup_slots)
+    }
+
     pub fn create_farmer(ctx: Context<CreateFarmer>) -> Result<()> {
         endpoints::create_farmer::handle(ctx)
     }
@@ -76,6 +162,16 @@ pub mod farming {
         endpoints::close_farmer::handle(ctx)
     }
 
+    pub fn exit_farm<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExitFarm<'info>>,
+    ) -> Result<()> {
+        endpoints::exit_farm::handle(ctx)
+    }
+
+    pub fn transfer_farmer(ctx: Context<TransferFarmer>) -> Result<()> {
+        endpoints::transfer_farmer::handle(ctx)
+    }
+
     pub fn start_farming(
         ctx: Context<StartFarming>,
         stake: TokenAmount,
@@ -113,6 +209,18 @@ pub mod farming {
         endpoints::claim_eligible_harvest::handle(ctx)
     }
 
+    pub fn claim_eligible_harvest_across_farms<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimEligibleHarvestAcrossFarms<'info>>,
+    ) -> Result<()> {
+        endpoints::claim_eligible_harvest_across_farms::handle(ctx)
+    }
+
+    pub fn claim_vested_harvest(
+        ctx: Context<ClaimVestedHarvest>,
+    ) -> Result<()> {
+        endpoints::claim_vested_harvest::handle(ctx)
+    }
+
     pub fn whitelist_farm_for_compounding(
         ctx: Context<WhitelistFarmForCompouding>,
     ) -> Result<()> {
@@ -135,7 +243,66 @@ pub mod farming {
         endpoints::compound_across_farms::handle(ctx)
     }
 
+    pub fn set_compound_bounds(
+        ctx: Context<SetCompoundBounds>,
+        min_compound: TokenAmount,
+        compound_deadline: Slot,
+    ) -> Result<()> {
+        endpoints::set_compound_bounds::handle(
+            ctx,
+            min_compound,
+            compound_deadline,
+        )
+    }
+
+    pub fn claim_and_restake(ctx: Context<ClaimAndRestake>) -> Result<()> {
+        endpoints::claim_and_restake::handle(ctx)
+    }
+
     pub fn airdrop(ctx: Context<Airdrop>, airdrop: TokenAmount) -> Result<()> {
         endpoints::airdrop::handle(ctx, airdrop)
     }
+
+    pub fn donate_rewards(
+        ctx: Context<DonateRewards>,
+        donation: TokenAmount,
+    ) -> Result<()> {
+        endpoints::donate_rewards::handle(ctx, donation)
+    }
+
+    pub fn get_compounding_eligibility(
+        ctx: Context<GetCompoundingEligibility>,
+    ) -> Result<()> {
+        endpoints::get_compounding_eligibility::handle(ctx)
+    }
+
+    pub fn get_farm_stats(ctx: Context<GetFarmStats>) -> Result<()> {
+        endpoints::get_farm_stats::handle(ctx)
+    }
+
+    pub fn get_farmer_average_stake(
+        ctx: Context<GetFarmerAverageStake>,
+    ) -> Result<()> {
+        endpoints::get_farmer_average_stake::handle(ctx)
+    }
+
+    pub fn get_harvest_runways(ctx: Context<GetHarvestRunways>) -> Result<()> {
+        endpoints::get_harvest_runways::handle(ctx)
+    }
+
+    pub fn get_snapshots(
+        ctx: Context<GetSnapshots>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<()> {
+        endpoints::get_snapshots::handle(ctx, offset, limit)
+    }
+
+    pub fn migrate_farm(ctx: Context<MigrateFarm>) -> Result<()> {
+        endpoints::migrate_farm::handle(ctx)
+    }
+
+    pub fn migrate_farmer(ctx: Context<MigrateFarmer>) -> Result<()> {
+        endpoints::migrate_farmer::handle(ctx)
+    }
 }
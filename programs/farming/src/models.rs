@@ -7,6 +7,7 @@ pub use farm::*;
 pub use farmer::*;
 
 use crate::prelude::*;
+use decimal::U192;
 
 #[derive(
     AnchorDeserialize,
@@ -40,6 +41,25 @@ pub struct Slot {
     pub slot: u64,
 }
 
+/// We use storable decimal (hence [`SDecimal`]) when storing a [`Decimal`]
+/// into an account, because Anchor's IDL TS library doesn't work with tuple
+/// structs.
+///
+/// The number is encoded as three u64s in little-endian.
+#[derive(
+    AnchorDeserialize,
+    AnchorSerialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+)]
+pub struct SDecimal {
+    u192: [u64; 3],
+}
+
 impl TokenAmount {
     pub fn new(amount: u64) -> Self {
         Self { amount }
@@ -57,3 +77,15 @@ impl Slot {
         })
     }
 }
+
+impl From<SDecimal> for Decimal {
+    fn from(dec: SDecimal) -> Self {
+        Self(U192(dec.u192))
+    }
+}
+
+impl From<Decimal> for SDecimal {
+    fn from(dec: Decimal) -> Self {
+        Self { u192: dec.0 .0 }
+    }
+}
@@ -3,8 +3,8 @@
 use crate::models::{Slot, TokenAmount};
 use crate::prelude::*;
 use std::cmp::Ordering;
-use std::iter;
 use std::ops::RangeInclusive;
+use std::{iter, mem};
 
 /// To create a user incentive for token possession, we distribute time
 /// dependent rewards. A farmer stakes tokens of a mint `S`, ie. they lock them
@@ -47,6 +47,74 @@ pub struct Farm {
     /// This field is configurable via the endpoint set_min_snapshot_window
     /// which can be called by the admin.
     pub min_snapshot_window_slots: u64,
+    /// No harvest period can start before this slot. It's set upon farm
+    /// creation and enables the admin to e.g. pre-fund the harvest vaults
+    /// before any harvest is emitted. Zero means no restriction.
+    pub harvests_start_at: Slot,
+    /// If not zero, [`crate::endpoints::start_farming`] fails once all
+    /// harvest periods of the farm have ended, see [`Farm::has_ended`].
+    /// Farmers can still unstake and claim. The admin configures this with
+    /// [`crate::endpoints::set_block_farming_once_ended`].
+    ///
+    /// # Note
+    /// It's [`u64`] and not [`bool`] for the same reason as
+    /// [`Snapshots::ring_buffer_tip`].
+    pub block_farming_once_ended: u64,
+    /// If not zero, harvest claimed with
+    /// [`crate::endpoints::claim_eligible_harvest`] is put into the farmer's
+    /// escrow and released linearly over this many slots, see
+    /// [`crate::models::AvailableHarvest::escrowed`]. The admin configures
+    /// this with [`crate::endpoints::set_claim_vesting_slots`].
+    ///
+    /// Compounding and restaking claim through the same escrow, therefore
+    /// only the vested part of the harvest is staked.
+    pub claim_vesting_slots: u64,
+    /// Token account of a harvest mint which delegates to the farm signer, out
+    /// of which [`crate::endpoints::crank_snapshot`] pays the keeper reward.
+    /// It's separate from the harvest vaults, so that the keeper reward never
+    /// takes from the farmers' harvest. The admin funds it by approving an
+    /// allowance and keeps custody of the tokens. Default pubkey if the admin
+    /// hasn't configured any.
+    pub keeper_reward_vault: Pubkey,
+    /// How many tokens from [`Farm::keeper_reward_vault`] does the caller of
+    /// [`crate::endpoints::crank_snapshot`] receive for a snapshot which
+    /// records a stake change. The admin configures this with
+    /// [`crate::endpoints::set_keeper_reward`].
+    pub keeper_reward: TokenAmount,
+    /// When was the keeper reward last paid. The reward is paid at most once
+    /// per [`consts::KEEPER_REWARD_WINDOW_SLOTS`], see
+    /// [`Farm::is_keeper_reward_due`]. Zero if it was never paid.
+    pub keeper_rewarded_at: Slot,
+    /// Farmers cannot unstake tokens deposited with
+    /// [`crate::endpoints::start_farming`] until this many slots have passed,
+    /// see [`crate::models::Farmer::lock_stake`]. Zero means no lockup.
+    ///
+    /// Lockup increases are timelocked, see
+    /// [`crate::endpoints::set_lockup_slots`]. Until the slot
+    /// `pending_lockup_effective_at`, the farm keeps using `lockup_slots`.
+    pub lockup_slots: u64,
+    pub pending_lockup_slots: u64,
+    /// At this slot `pending_lockup_slots` replaces `lockup_slots`. Zero if
+    /// there's no pending change.
+    pub pending_lockup_effective_at: Slot,
+    /// If not zero, a farmer can claim harvest with
+    /// [`crate::endpoints::claim_eligible_harvest`] at most once per this many
+    /// slots, see [`Farmer::last_claimed_at`]. Harvest keeps accruing in the
+    /// meantime. The admin configures this with
+    /// [`crate::endpoints::set_claim_cooldown_slots`].
+    ///
+    /// Compounding and restaking count as claims, otherwise the cooldown could
+    /// be skipped by compounding the harvest and unstaking it.
+    pub claim_cooldown_slots: u64,
+    /// If not zero, harvest periods created from now on are front-loaded, ie.
+    /// they emit more tokens early and less later instead of a constant `ρ`.
+    /// With `f` percent, the rate decreases linearly from `100 + f` percent of
+    /// `ρ` in the first slot of a period to `100 - f` percent in its last
+    /// slot, therefore each period emits the same amount of tokens in total as
+    /// without front-loading. Each period stores its own front-loading, see
+    /// [`HarvestPeriod::front_load_percent`]. The admin configures this with
+    /// [`crate::endpoints::set_emission_front_load`].
+    pub emission_front_load_percent: u64,
 }
 
 /// # Important
@@ -91,6 +159,134 @@ pub struct Harvest {
     /// # Note
     /// This len must match [`consts::HARVEST_PERIODS_LEN`].
     pub periods: [HarvestPeriod; 10],
+    /// If the harvest is paused, this is the first slot at which no tokens
+    /// are emitted, regardless of the periods. Zero means the harvest is not
+    /// paused.
+    ///
+    /// The admin pauses a harvest with [`crate::endpoints::pause_harvest`].
+    /// Upon [`crate::endpoints::resume_harvest`] the paused slots are cut out
+    /// of the periods, so that the history stays correct, and this is reset.
+    pub paused_at: Slot,
+    /// How many tokens of this mint have been transferred out of the harvest
+    /// vault to farmers so far, be it by claiming or by compounding, plus the
+    /// keeper rewards of this mint paid by
+    /// [`crate::endpoints::crank_snapshot`].
+    ///
+    /// The counter saturates instead of overflowing. It's dropped if the admin
+    /// removes the harvest mint from the farm.
+    pub total_distributed: TokenAmount,
+    /// Sum over all donations made with [`crate::endpoints::donate_rewards`]
+    /// of the donated tokens divided by the stake vault amount at the time of
+    /// the donation. A farmer is eligible for their deposited tokens, both
+    /// staked and vested, times the increase of this value since they were
+    /// last updated, see [`crate::models::AvailableHarvest::donations_paid`].
+    pub donations_per_staked_token: SDecimal,
+}
+
+/// Aggregate view of a farm for dashboards, returned by
+/// [`crate::endpoints::get_farm_stats`].
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug, Eq, PartialEq)]
+pub struct FarmStats {
+    /// Amount of tokens in the stake vault, ie. staked and vested tokens of
+    /// all farmers.
+    pub total_staked: TokenAmount,
+    /// One entry for each initialized harvest of the farm.
+    pub harvests: Vec<HarvestStats>,
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug, Eq, PartialEq)]
+pub struct HarvestStats {
+    pub mint: Pubkey,
+    /// See [`Harvest::total_distributed`].
+    pub total_distributed: TokenAmount,
+}
+
+/// How long can the tokens in a harvest vault sustain the current emission,
+/// returned by [`Farm::harvest_runway`].
+#[derive(
+    AnchorDeserialize,
+    AnchorSerialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+)]
+pub struct HarvestRunway {
+    pub mint: Pubkey,
+    /// Amount of tokens in the harvest vault. This includes eligible harvest
+    /// which farmers haven't claimed yet, hence the runway is an upper bound.
+    pub remaining: TokenAmount,
+    /// How many tokens are emitted at the current slot. For a front-loaded
+    /// period this decreases over time. Zero if the harvest is paused or no
+    /// period is running.
+    pub tps: TokenAmount,
+    /// The first slot at which the remaining tokens don't cover the emission
+    /// anymore if the current rate was kept. [`None`] if nothing is emitted.
+    pub exhausted_at: Option<Slot>,
+}
+
+/// Whether a farmer can have their harvest compounded into a target farm,
+/// returned by [`Farm::compounding_eligibility`].
+#[derive(
+    AnchorDeserialize,
+    AnchorSerialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+)]
+pub struct CompoundingEligibility {
+    /// The farm's admin whitelisted the target farm with
+    /// [`crate::endpoints::whitelist_farm_for_compounding`].
+    pub is_whitelisted: bool,
+    /// The farm harvests the target farm's stake mint, otherwise there's
+    /// nothing to compound.
+    pub harvests_target_stake_mint: bool,
+    /// The farmer's eligible harvest of the target farm's stake mint as of the
+    /// last time the farmer was updated. This is what compounding would
+    /// restake at least.
+    pub eligible_harvest: TokenAmount,
+}
+
+impl CompoundingEligibility {
+    /// Both [`crate::endpoints::compound_same_farm`] and
+    /// [`crate::endpoints::compound_across_farms`] fail unless this holds.
+    pub fn is_eligible(&self) -> bool {
+        self.is_whitelisted && self.harvests_target_stake_mint
+    }
+}
+
+/// A page of the farm's snapshot history, returned by
+/// [`Farm::snapshots_page`].
+#[derive(
+    AnchorDeserialize, AnchorSerialize, Clone, Debug, Default, Eq, PartialEq,
+)]
+pub struct SnapshotsPage {
+    /// How many snapshots the history has in total. Clients read pages until
+    /// the offset reaches this number.
+    pub total: u64,
+    /// Ordered from the oldest to the latest snapshot.
+    pub snapshots: Vec<SnapshotRecord>,
+}
+
+/// Serializable copy of a [`Snapshot`].
+#[derive(
+    AnchorDeserialize,
+    AnchorSerialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+)]
+pub struct SnapshotRecord {
+    pub started_at: Slot,
+    pub staked: TokenAmount,
 }
 
 #[derive(Debug, Default, Eq, PartialEq)]
@@ -99,6 +295,18 @@ pub struct HarvestPeriod {
     pub tps: TokenAmount,
     pub starts_at: Slot,
     pub ends_at: Slot,
+    /// If not zero, the period is front-loaded, see
+    /// [`Farm::emission_front_load_percent`]. It's copied from the farm when
+    /// the period is created, therefore changing the farm's setting doesn't
+    /// affect existing periods.
+    pub front_load_percent: u64,
+    /// The front-loading curve spans the inclusive range of slots from
+    /// `front_load_starts_at` to `front_load_ends_at`, which is the range of
+    /// the period as created by the admin. When the period is split, e.g. by
+    /// [`Farm::resume_harvest`], its parts keep following the original curve,
+    /// so that harvest which farmers already calculated doesn't change.
+    pub front_load_starts_at: Slot,
+    pub front_load_ends_at: Slot,
 }
 
 #[derive(Eq, PartialEq)]
@@ -130,6 +338,9 @@ pub struct Snapshots {
 #[derive(Debug, Default, Eq, PartialEq)]
 #[zero_copy]
 pub struct Snapshot {
+    /// Total amount of tokens staked in the farm by all farmers when the
+    /// snapshot was taken, ie. the stake vault balance. A farmer's share of
+    /// the harvest in this window is their stake divided by this amount.
     pub staked: TokenAmount,
     pub started_at: Slot,
 }
@@ -146,6 +357,38 @@ pub struct Snapshot {
 #[account]
 pub struct WhitelistCompounding {}
 
+/// The [`Farm`] account as it was laid out before the harvests tracked pauses,
+/// distributed tokens and donations, and before the farm stored any settings
+/// but the snapshot window. Such accounts are too small to be loaded as
+/// [`Farm`] and must be converted with [`crate::endpoints::migrate_farm`]
+/// first.
+#[derive(Default)]
+#[zero_copy]
+pub struct LegacyFarm {
+    pub admin: Pubkey,
+    pub stake_mint: Pubkey,
+    pub stake_vault: Pubkey,
+    pub harvests: [LegacyHarvest; 10],
+    pub snapshots: Snapshots,
+    pub min_snapshot_window_slots: u64,
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+#[zero_copy]
+pub struct LegacyHarvest {
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub periods: [LegacyHarvestPeriod; 10],
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+#[zero_copy]
+pub struct LegacyHarvestPeriod {
+    pub tps: TokenAmount,
+    pub starts_at: Slot,
+    pub ends_at: Slot,
+}
+
 impl Default for Snapshots {
     fn default() -> Self {
         Self {
@@ -155,6 +398,44 @@ impl Default for Snapshots {
     }
 }
 
+impl LegacyFarm {
+    /// Includes the discriminator, which is the same as [`Farm`]'s.
+    pub fn space() -> usize {
+        8 + mem::size_of::<Self>()
+    }
+
+    /// Writes the account into given farm in the current layout. The farm
+    /// must be zeroed, so that what the legacy layout didn't have is unset,
+    /// e.g. no harvest is paused, periods are not front-loaded and there's no
+    /// lockup.
+    ///
+    /// The fields are copied one by one instead of building a new [`Farm`],
+    /// because the farm doesn't fit onto the stack.
+    pub fn migrate(&self, farm: &mut Farm) {
+        farm.admin = self.admin;
+        farm.stake_mint = self.stake_mint;
+        farm.stake_vault = self.stake_vault;
+
+        for (harvest, legacy) in farm.harvests.iter_mut().zip(&self.harvests) {
+            harvest.mint = legacy.mint;
+            harvest.vault = legacy.vault;
+            for (period, legacy) in
+                harvest.periods.iter_mut().zip(&legacy.periods)
+            {
+                period.tps = legacy.tps;
+                period.starts_at = legacy.starts_at;
+                period.ends_at = legacy.ends_at;
+            }
+        }
+
+        farm.snapshots.ring_buffer_tip = self.snapshots.ring_buffer_tip;
+        farm.snapshots
+            .ring_buffer
+            .copy_from_slice(&self.snapshots.ring_buffer);
+        farm.min_snapshot_window_slots = self.min_snapshot_window_slots;
+    }
+}
+
 impl Farm {
     pub const SIGNER_PDA_PREFIX: &'static [u8; 6] = b"signer";
     pub const STAKE_VAULT_PREFIX: &'static [u8; 11] = b"stake_vault";
@@ -204,16 +485,25 @@ impl Farm {
     ///
     /// A scheduled launch is when the latest harvest period hasn't started yet.
     /// In that case, instead of creating a new one, we overwrite the existing
-    /// one. The return value indicates whether a scheduled launch overwrite
-    /// happened by returning the previous scheduled period.
+    /// one. Since [`Farm::multiply_emission`] can split a scheduled launch,
+    /// it can consist of several periods, all of which are overwritten. The
+    /// return value is how many tokens the overwritten periods emit, ie. how
+    /// many tokens were deposited for them.
+    ///
+    /// A period which hasn't started yet, but continues the latest started
+    /// period without a gap, can be a part of it split by
+    /// [`Farm::multiply_emission`]. Therefore, like the started period, it's
+    /// never overwritten.
     pub fn new_harvest_period(
         &mut self,
         current_slot: Slot,
         harvest_mint: Pubkey,
         period: (Slot, Slot),
         tps: TokenAmount,
-    ) -> Result<Option<HarvestPeriod>> {
+    ) -> Result<TokenAmount> {
         let oldest_snapshot = self.oldest_snapshot();
+        let harvests_start_at = self.harvests_start_at;
+        let emission_front_load_percent = self.emission_front_load_percent;
 
         let harvest = self
             .harvests
@@ -230,64 +520,95 @@ impl Farm {
             ));
         }
 
-        // the admin can schedule a new launch as long as it starts after the
-        // following period ends
-        //
-        // latest started != currently active, it might have finished already
-        let latest_started_period = harvest
+        if starts_at < harvests_start_at {
+            msg!(
+                "Harvests of this farm start at slot {}",
+                harvests_start_at.slot
+            );
+            return Err(error!(
+                FarmingError::HarvestPeriodMustStartAtOrAfterFarmStart
+            ));
+        }
+
+        // periods are sorted by start slot DESC, so the scheduled launch is
+        // a prefix of the periods which haven't started yet, except for those
+        // which continue the latest started period
+        let not_started = harvest
             .periods
             .iter()
-            .find(|p| p.starts_at <= current_slot)
-            .ok_or_else(|| {
-                // currently we don't allow all periods have `started_at` in
-                // future
-                msg!("All harvest periods cannot be scheduled");
-                FarmingError::InvariantViolation
-            })?;
-        if latest_started_period.ends_at >= starts_at {
+            .take_while(|p| p.starts_at > current_slot)
+            .count();
+        let mut scheduled_launch = not_started;
+        let mut latest_kept_period = harvest
+            .periods
+            .get(not_started)
+            .copied()
+            .unwrap_or_default();
+        while scheduled_launch > 0
+            && latest_kept_period.ends_at.slot != 0
+            && harvest.periods[scheduled_launch - 1].starts_at.slot
+                == latest_kept_period.ends_at.slot + 1
+        {
+            scheduled_launch -= 1;
+            latest_kept_period = harvest.periods[scheduled_launch];
+        }
+
+        // the admin can schedule a new launch as long as it starts after the
+        // latest kept period ends
+        if latest_kept_period.ends_at >= starts_at {
             msg!(
-                "Latest started harvest period ends at slot {}, \
+                "Latest harvest period ends at slot {}, \
                 new period must start later than that",
-                latest_started_period.ends_at.slot
+                latest_kept_period.ends_at.slot
             );
             return Err(error!(FarmingError::CannotOverwriteOpenHarvestPeriod));
         }
 
-        // if the latest period is at a future slot, then we update its
-        // value
-        //
-        // this enables editing of scheduled launches
-        let latest_period = &mut harvest.periods[0];
-        if latest_period.starts_at > current_slot {
-            let previous_latest_period = *latest_period;
-            *latest_period = HarvestPeriod {
+        let new_period = if emission_front_load_percent == 0 {
+            HarvestPeriod {
                 tps,
                 starts_at,
                 ends_at,
-            };
-            return Ok(Some(previous_latest_period));
+                ..Default::default()
+            }
+        } else {
+            HarvestPeriod {
+                tps,
+                starts_at,
+                ends_at,
+                front_load_percent: emission_front_load_percent,
+                front_load_starts_at: starts_at,
+                front_load_ends_at: ends_at,
+            }
+        };
+
+        // this enables editing of scheduled launches
+        if scheduled_launch != 0 {
+            let mut deposited = 0u64;
+            for period in &harvest.periods[..scheduled_launch] {
+                deposited = deposited
+                    .checked_add(period.total_tokens()?.amount)
+                    .ok_or(FarmingError::MathOverflow)?;
+            }
+
+            let periods: Vec<_> = iter::once(new_period)
+                .chain(harvest.periods[scheduled_launch..].iter().copied())
+                .chain(iter::repeat(HarvestPeriod::default()))
+                .take(consts::HARVEST_PERIODS_LEN)
+                .collect();
+            harvest.periods = periods.try_into().map_err(|_| {
+                msg!("Cannot convert harvest period history vector into array");
+                FarmingError::InvariantViolation
+            })?;
+
+            return Ok(TokenAmount::new(deposited));
         }
 
         // we know a priori that harvest: HarvestPeriod was at least
         // already initialized as a fixed number length array of default
         // elements so unwrap `.last()` is safe
         let oldest_period = harvest.periods.last().unwrap();
-        let is_oldest_period_initialized = if cfg!(any(feature = "prod", test))
-        {
-            // if the oldest period is within the current snapshot history,
-            // we are unable to update its value, the admin already passed the
-            // allowed max number of possible configuration updates
-            oldest_period.ends_at.slot != 0
-        } else {
-            // always enable updates in dev
-            false
-        };
-        if is_oldest_period_initialized
-            && oldest_period.ends_at.slot >= oldest_snapshot.started_at.slot
-        {
-            msg!("Oldest period is still within the ring buffer history");
-            return Err(error!(FarmingError::ConfigurationUpdateLimitExceeded));
-        }
+        oldest_period.ensure_can_be_dropped(oldest_snapshot)?;
 
         // At this step, we know that the oldest period end slot is strictly
         // less than the oldest snapshot slot. In this case, we update
@@ -295,13 +616,469 @@ impl Farm {
         // a new harvest period starting and ending at the slots
         // given by the `period` parameter.
         harvest.periods.rotate_right(1);
-        harvest.periods[0] = HarvestPeriod {
+        harvest.periods[0] = new_period;
+
+        Ok(TokenAmount::new(0))
+    }
+
+    /// Stops the emission of the harvest of given mint from the next slot on.
+    /// Other harvests of the farm are not affected.
+    pub fn pause_harvest(
+        &mut self,
+        current_slot: Slot,
+        harvest_mint: Pubkey,
+    ) -> Result<()> {
+        let harvest = self
+            .harvests
+            .iter_mut()
+            .find(|h| h.mint == harvest_mint)
+            .ok_or(FarmingError::UnknownHarvestMintPubKey)?;
+
+        if harvest.is_paused() {
+            return Err(error!(err::arg("Harvest is already paused")));
+        }
+
+        // the current slot is still emitted, because farmers might have
+        // already calculated their harvest for it
+        harvest.paused_at = Slot::new(current_slot.slot + 1);
+
+        Ok(())
+    }
+
+    /// Resumes the emission of a paused harvest from the next slot on.
+    ///
+    /// The slots during which the harvest was paused are cut out of the
+    /// periods. A period which was running before the pause and is still
+    /// running is split in two, which takes up one more entry in the periods
+    /// history. The tokens which would've been emitted during the pause remain
+    /// in the harvest vault.
+    pub fn resume_harvest(
+        &mut self,
+        current_slot: Slot,
+        harvest_mint: Pubkey,
+    ) -> Result<()> {
+        let oldest_snapshot = self.oldest_snapshot();
+
+        let harvest = self
+            .harvests
+            .iter_mut()
+            .find(|h| h.mint == harvest_mint)
+            .ok_or(FarmingError::UnknownHarvestMintPubKey)?;
+
+        if !harvest.is_paused() {
+            return Err(error!(err::arg("Harvest is not paused")));
+        }
+
+        // inclusive range of slots during which nothing was emitted
+        let (paused_from, paused_until) = (harvest.paused_at, current_slot);
+
+        // paused and resumed in the same slot, nothing to cut out
+        if paused_until < paused_from {
+            harvest.paused_at = Slot::new(0);
+            return Ok(());
+        }
+
+        let mut periods = Vec::with_capacity(consts::HARVEST_PERIODS_LEN + 1);
+        for period in harvest.periods {
+            // uninitialized periods end at slot 0 and so are kept as they are
+            if period.ends_at < paused_from || period.starts_at > paused_until {
+                periods.push(period);
+                continue;
+            }
+
+            // periods are sorted by start slot DESC, so the part after the
+            // pause goes first
+            if period.ends_at > paused_until {
+                periods.push(HarvestPeriod {
+                    starts_at: Slot::new(paused_until.slot + 1),
+                    ..period
+                });
+            }
+            if period.starts_at < paused_from {
+                periods.push(HarvestPeriod {
+                    ends_at: Slot::new(paused_from.slot - 1),
+                    ..period
+                });
+            }
+            // a period which falls entirely into the pause is dropped
+        }
+
+        if periods.len() > consts::HARVEST_PERIODS_LEN {
+            // we know there's one more period than fits into the array
+            periods
+                .last()
+                .unwrap()
+                .ensure_can_be_dropped(oldest_snapshot)?;
+        }
+
+        periods.resize(consts::HARVEST_PERIODS_LEN, HarvestPeriod::default());
+        harvest.periods = periods.try_into().map_err(|_| {
+            msg!("Cannot convert harvest period history vector into array");
+            FarmingError::InvariantViolation
+        })?;
+        harvest.paused_at = Slot::new(0);
+
+        Ok(())
+    }
+
+    /// Multiplies the emission of the harvest of given mint by given percent
+    /// in the inclusive range of slots `window`.
+    ///
+    /// The multiplied `ρ` is written into the periods. A period which overlaps
+    /// the window only partially is split, which like
+    /// [`Farm::resume_harvest`] takes up more entries in the periods history.
+    /// Slots which aren't covered by any period are not affected.
+    ///
+    /// The window must start after the current slot, because farmers might
+    /// have already calculated their harvest up to it.
+    ///
+    /// Returns how many more tokens the periods emit, which the admin must
+    /// deposit into the harvest vault.
+    pub fn multiply_emission(
+        &mut self,
+        current_slot: Slot,
+        harvest_mint: Pubkey,
+        percent: u64,
+        window: (Slot, Slot),
+    ) -> Result<TokenAmount> {
+        let oldest_snapshot = self.oldest_snapshot();
+
+        let harvest = self
+            .harvests
+            .iter_mut()
+            .find(|h| h.mint == harvest_mint)
+            .ok_or(FarmingError::UnknownHarvestMintPubKey)?;
+
+        let (starts_at, ends_at) = window;
+        if starts_at <= current_slot {
+            return Err(error!(
+                FarmingError::HarvestPeriodMustStartAtOrAfterCurrentSlot
+            ));
+        }
+        if ends_at < starts_at {
+            return Err(error!(
+                FarmingError::HarvestPeriodCannotHaveNegativeLength
+            ));
+        }
+
+        let mut extra_tokens = 0u64;
+        let mut periods = Vec::with_capacity(consts::HARVEST_PERIODS_LEN + 2);
+        for period in harvest.periods {
+            // uninitialized periods end at slot 0 and so are kept as they are
+            if period.ends_at.slot == 0
+                || period.ends_at < starts_at
+                || period.starts_at > ends_at
+            {
+                periods.push(period);
+                continue;
+            }
+
+            // a front-loaded period keeps its curve, only scaled
+            let multiplied = HarvestPeriod {
+                tps: TokenAmount::new(
+                    (period.tps.amount as u128 * percent as u128 / 100)
+                        .try_into()
+                        .map_err(|_| FarmingError::MathOverflow)?,
+                ),
+                starts_at: period.starts_at.max(starts_at),
+                ends_at: period.ends_at.min(ends_at),
+                ..period
+            };
+            let emitted_before = HarvestPeriod {
+                tps: period.tps,
+                ..multiplied
+            }
+            .total_tokens()?;
+            extra_tokens = multiplied
+                .total_tokens()?
+                .amount
+                .checked_sub(emitted_before.amount)
+                .and_then(|extra| extra.checked_add(extra_tokens))
+                .ok_or(FarmingError::MathOverflow)?;
+
+            // periods are sorted by start slot DESC, so the part after the
+            // window goes first
+            if period.ends_at > ends_at {
+                periods.push(HarvestPeriod {
+                    starts_at: Slot::new(ends_at.slot + 1),
+                    ..period
+                });
+            }
+            periods.push(multiplied);
+            if period.starts_at < starts_at {
+                periods.push(HarvestPeriod {
+                    ends_at: Slot::new(starts_at.slot - 1),
+                    ..period
+                });
+            }
+        }
+
+        while periods.len() > consts::HARVEST_PERIODS_LEN {
+            // we know there are more periods than fit into the array
+            periods
+                .pop()
+                .unwrap()
+                .ensure_can_be_dropped(oldest_snapshot)?;
+        }
+
+        harvest.periods = periods.try_into().map_err(|_| {
+            msg!("Cannot convert harvest period history vector into array");
+            FarmingError::InvariantViolation
+        })?;
+
+        Ok(TokenAmount::new(extra_tokens))
+    }
+
+    /// Whether there's at least one harvest period and all harvest periods of
+    /// the farm ended before the current slot, ie. no more harvest is going
+    /// to be emitted unless the admin schedules a new period.
+    pub fn has_ended(&self, current_slot: Slot) -> bool {
+        let mut periods = self
+            .harvests
+            .iter()
+            .filter(|h| h.mint != Pubkey::default())
+            .flat_map(|h| h.periods.iter())
+            // uninitialized periods end at slot 0
+            .filter(|p| p.ends_at.slot != 0)
+            .peekable();
+
+        periods.peek().is_some() && periods.all(|p| p.ends_at < current_slot)
+    }
+
+    /// Fails if the admin blocked farming once the farm ended and it has, see
+    /// [`Farm::block_farming_once_ended`]. Checked wherever tokens are staked
+    /// on the farmer's behalf, but not when harvest is compounded.
+    pub fn check_farming_not_blocked(&self, current_slot: Slot) -> Result<()> {
+        if self.block_farming_once_ended != 0 && self.has_ended(current_slot) {
+            return Err(error!(FarmingError::FarmingBlockedOnceEnded));
+        }
+
+        Ok(())
+    }
+
+    pub fn has_pending_lockup(&self) -> bool {
+        self.pending_lockup_effective_at != Slot::default()
+    }
+
+    /// If the timelock of the pending lockup has elapsed, the pending lockup
+    /// becomes the farm's lockup.
+    pub fn apply_pending_lockup(&mut self, now: Slot) {
+        if self.has_pending_lockup() && now >= self.pending_lockup_effective_at
+        {
+            self.lockup_slots = self.pending_lockup_slots;
+            self.cancel_pending_lockup();
+        }
+    }
+
+    pub fn cancel_pending_lockup(&mut self) {
+        self.pending_lockup_slots = 0;
+        self.pending_lockup_effective_at = Slot::default();
+    }
+
+    /// The lockup which applies at given slot, ie. the pending one if its
+    /// timelock has elapsed. Unlike [`Farm::apply_pending_lockup`] this
+    /// doesn't require the farm to be mutable.
+    pub fn active_lockup_slots(&self, now: Slot) -> u64 {
+        if self.has_pending_lockup() && now >= self.pending_lockup_effective_at
+        {
+            self.pending_lockup_slots
+        } else {
+            self.lockup_slots
+        }
+    }
+
+    /// Donated tokens accrue immediately to farmers with stake, proportionally
+    /// to the tokens they deposited, see
+    /// [`Harvest::donations_per_staked_token`]. The deposits of all farmers
+    /// are given by the stake vault amount.
+    pub fn donate(
+        &mut self,
+        harvest_mint: Pubkey,
+        tokens: TokenAmount,
+        stake_vault: TokenAmount,
+    ) -> Result<()> {
+        if stake_vault.amount == 0 {
+            return Err(error!(err::arg(
+                "There's no stake in the stake vault to donate to"
+            )));
+        }
+
+        let harvest = self
+            .harvests
+            .iter_mut()
+            .find(|h| h.mint == harvest_mint && h.mint != Pubkey::default())
+            .ok_or(FarmingError::UnknownHarvestMintPubKey)?;
+
+        let per_staked_token = Decimal::from(tokens.amount)
+            .try_div(Decimal::from(stake_vault.amount))?;
+        harvest.donations_per_staked_token =
+            Decimal::from(harvest.donations_per_staked_token)
+                .try_add(per_staked_token)?
+                .into();
+
+        Ok(())
+    }
+
+    /// Increments the counter of distributed tokens of given harvest mint, see
+    /// [`Harvest::total_distributed`]. No-op if the farm has no harvest of
+    /// such mint.
+    pub fn add_to_total_distributed(
+        &mut self,
+        mint: Pubkey,
+        tokens: TokenAmount,
+    ) {
+        if let Some(harvest) = self.harvests.iter_mut().find(|h| h.mint == mint)
+        {
+            harvest.total_distributed.amount = harvest
+                .total_distributed
+                .amount
+                .saturating_add(tokens.amount);
+        }
+    }
+
+    /// Aggregates the farm's counters. The total staked amount is the balance
+    /// of the stake vault, which is not stored on the farm.
+    pub fn stats(&self, total_staked: TokenAmount) -> FarmStats {
+        FarmStats {
+            total_staked,
+            harvests: self
+                .harvests
+                .iter()
+                .filter(|h| h.mint != Pubkey::default())
+                .map(|h| HarvestStats {
+                    mint: h.mint,
+                    total_distributed: h.total_distributed,
+                })
+                .collect(),
+        }
+    }
+
+    /// Projects when the tokens in the vault of given harvest mint run out if
+    /// they keep being emitted at the rate of the current slot.
+    ///
+    /// `exhausted_at = now + floor(remaining / tps)`
+    pub fn harvest_runway(
+        &self,
+        now: Slot,
+        harvest_mint: Pubkey,
+        remaining: TokenAmount,
+    ) -> Result<HarvestRunway> {
+        let harvest = self
+            .harvests
+            .iter()
+            .find(|h| h.mint == harvest_mint && h.mint != Pubkey::default())
+            .ok_or(FarmingError::UnknownHarvestMintPubKey)?;
+
+        // a front-loaded period emits a different amount in each slot
+        let tps = match harvest.period_at(now) {
+            Some(period) if harvest.tps_at(now).amount != 0 => {
+                period.tokens_emitted((now, now))?
+            }
+            _ => TokenAmount::new(0),
+        };
+
+        let exhausted_at = if tps.amount == 0 {
+            None
+        } else {
+            Some(Slot::new(
+                now.slot.saturating_add(remaining.amount / tps.amount),
+            ))
+        };
+
+        Ok(HarvestRunway {
+            mint: harvest.mint,
+            remaining,
             tps,
-            starts_at,
-            ends_at,
+            exhausted_at,
+        })
+    }
+
+    /// The whitelist is an account whose existence is checked by the caller,
+    /// see [`WhitelistCompounding`]. For compounding in the same farm, the
+    /// target stake mint is this farm's stake mint.
+    pub fn compounding_eligibility(
+        &self,
+        is_whitelisted: bool,
+        target_stake_mint: Pubkey,
+        farmer: &Farmer,
+    ) -> CompoundingEligibility {
+        let harvests_target_stake_mint = self.harvests.iter().any(|h| {
+            h.mint == target_stake_mint && h.mint != Pubkey::default()
+        });
+        let eligible_harvest = farmer
+            .harvests
+            .iter()
+            .find(|h| h.mint == target_stake_mint)
+            .filter(|_| harvests_target_stake_mint)
+            .map(|h| h.tokens)
+            .unwrap_or_default();
+
+        CompoundingEligibility {
+            is_whitelisted,
+            harvests_target_stake_mint,
+            eligible_harvest,
+        }
+    }
+
+    /// Whether the staked amount differs from the one recorded by the latest
+    /// snapshot, ie. whether the accrual is based on stale stake.
+    pub fn has_stake_changed_since_last_snapshot(
+        &self,
+        stake_vault: TokenAmount,
+    ) -> bool {
+        self.latest_snapshot().staked != stake_vault
+    }
+
+    /// Whether [`crate::endpoints::crank_snapshot`] pays the keeper reward at
+    /// given slot. Otherwise anyone could earn a reward per snapshot window by
+    /// changing their stake back and forth, and the window can be as short as
+    /// a single slot.
+    pub fn is_keeper_reward_due(&self, now: Slot) -> bool {
+        self.keeper_rewarded_at.slot == 0
+            || now.slot
+                >= self
+                    .keeper_rewarded_at
+                    .slot
+                    .saturating_add(consts::KEEPER_REWARD_WINDOW_SLOTS)
+    }
+
+    /// Returns at most `limit` snapshots of the history starting with the
+    /// `offset`-th oldest one.
+    ///
+    /// Until the ring buffer wraps around for the first time, the history
+    /// starts with the empty snapshot at index 0 which the farm is created
+    /// with. Afterwards it has [`consts::SNAPSHOTS_LEN`] snapshots.
+    pub fn snapshots_page(&self, offset: usize, limit: usize) -> SnapshotsPage {
+        let tip = self.snapshots.ring_buffer_tip as usize;
+        let has_wrapped = self.snapshots.ring_buffer[consts::SNAPSHOTS_LEN - 1]
+            .started_at
+            .slot
+            != 0;
+        let (oldest, total) = if has_wrapped {
+            (self.oldest_snapshot_index(), consts::SNAPSHOTS_LEN)
+        } else {
+            (0, tip + 1)
         };
 
-        Ok(None)
+        let snapshots = self
+            .snapshots
+            .ring_buffer
+            .iter()
+            .cycle()
+            .skip(oldest)
+            .take(total)
+            .skip(offset)
+            .take(limit)
+            .map(|s| SnapshotRecord {
+                started_at: s.started_at,
+                staked: s.staked,
+            })
+            .collect();
+
+        SnapshotsPage {
+            total: total as u64,
+            snapshots,
+        }
     }
 
     pub fn latest_snapshot(&self) -> Snapshot {
@@ -478,6 +1255,42 @@ impl Farm {
         Ok(())
     }
 
+    /// Raises the amount of staked tokens recorded by the latest snapshot and
+    /// returns the amount recorded before, see
+    /// [`crate::endpoints::correct_snapshot`].
+    ///
+    /// The amount can only be raised, because a lower amount would increase
+    /// every farmer's share of the window and over-distribute the harvest
+    /// vault. A snapshot records the stake vault balance, therefore the
+    /// corrected amount cannot be more than what's currently in the stake
+    /// vault.
+    pub fn correct_latest_snapshot(
+        &mut self,
+        staked: TokenAmount,
+        stake_vault: TokenAmount,
+    ) -> Result<TokenAmount> {
+        let mut snapshot = self.latest_snapshot();
+
+        if snapshot.started_at.slot == 0 {
+            return Err(error!(err::acc("Farm has no snapshot to correct")));
+        }
+
+        if staked <= snapshot.staked || staked > stake_vault {
+            return Err(error!(err::arg(format!(
+                "Corrected amount must be more than {} recorded tokens \
+                and at most {} tokens in the stake vault",
+                snapshot.staked.amount, stake_vault.amount
+            ))));
+        }
+
+        let previous = snapshot.staked;
+        snapshot.staked = staked;
+        self.snapshots.ring_buffer[self.snapshots.ring_buffer_tip as usize] =
+            snapshot;
+
+        Ok(previous)
+    }
+
     fn oldest_snapshot_index(&self) -> usize {
         if self.snapshots.ring_buffer_tip as usize != consts::SNAPSHOTS_LEN - 1
         {
@@ -491,6 +1304,33 @@ impl Farm {
 impl Harvest {
     pub const VAULT_PREFIX: &'static [u8; 13] = b"harvest_vault";
 
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.slot != 0
+    }
+
+    /// Tokens emitted per slot at given slot. Zero if the harvest is paused at
+    /// that slot or no period is running.
+    pub fn tps_at(&self, slot: Slot) -> TokenAmount {
+        if self.is_paused() && slot >= self.paused_at {
+            return TokenAmount::new(0);
+        }
+
+        self.period_at(slot)
+            .map(|p| p.tps)
+            .unwrap_or_else(|| TokenAmount::new(0))
+    }
+
+    /// The period which covers given slot, if any, regardless of whether the
+    /// harvest is paused.
+    pub fn period_at(&self, slot: Slot) -> Option<HarvestPeriod> {
+        self.periods
+            .iter()
+            // uninitialized periods end at slot 0
+            .filter(|p| p.ends_at.slot != 0)
+            .find(|p| p.starts_at <= slot && slot <= p.ends_at)
+            .copied()
+    }
+
     /// Returns a vec of all periods and their corresponding `ρ` ordered by
     /// the period's start slot _ASC_. That is, you can pop from this vec to get
     /// the most recent period.
@@ -532,6 +1372,7 @@ impl Harvest {
                 tps: TokenAmount::new(0),
                 starts_at: Slot::new(0),
                 ends_at: Slot::new(0),
+                ..Default::default()
             },
         ];
         let history = self
@@ -576,7 +1417,7 @@ impl Harvest {
             })
             .flatten();
 
-        if self.periods[0].ends_at < current {
+        let history: Vec<_> = if self.periods[0].ends_at < current {
             // and pad the end as well if the latest period ended before the
             // current slot, ie. in past
             iter::once((
@@ -587,9 +1428,159 @@ impl Harvest {
             .rev()
             .collect()
         } else {
-            history.rev().collect()
+            history.rev().collect()
+        };
+
+        if !self.is_paused() {
+            return history;
+        }
+
+        // nothing is emitted from the slot the harvest was paused at
+        let paused_at = self.paused_at;
+        history
+            .into_iter()
+            .flat_map(|(range, tps)| {
+                let (starts_at, ends_at) = (*range.start(), *range.end());
+                if ends_at < paused_at {
+                    vec![(range, tps)]
+                } else if starts_at >= paused_at {
+                    vec![(range, TokenAmount::new(0))]
+                } else {
+                    vec![
+                        (starts_at..=Slot::new(paused_at.slot - 1), tps),
+                        (paused_at..=ends_at, TokenAmount::new(0)),
+                    ]
+                }
+            })
+            .collect()
+    }
+}
+
+impl HarvestPeriod {
+    /// How many tokens the period emits over all its slots. The admin deposits
+    /// this amount into the harvest vault.
+    pub fn total_tokens(&self) -> Result<TokenAmount> {
+        self.tokens_emitted((self.starts_at, self.ends_at))
+    }
+
+    /// How many tokens the period emits over the inclusive range of slots
+    /// `from..=to`, which must be within the period.
+    ///
+    /// The emission is calculated in [`u128`] and fails with a math overflow
+    /// only if it doesn't fit into [`u64`]. The admin deposits the tokens of
+    /// the whole period into the harvest vault, see
+    /// [`crate::endpoints::new_harvest_period`], therefore that's only the
+    /// case for a period which could never be funded.
+    pub fn tokens_emitted(
+        &self,
+        (from, to): (Slot, Slot),
+    ) -> Result<TokenAmount> {
+        if from > to || from < self.starts_at || to > self.ends_at {
+            msg!(
+                "Range {}..={} is not within the period {}..={}",
+                from.slot,
+                to.slot,
+                self.starts_at.slot,
+                self.ends_at.slot
+            );
+            return Err(error!(FarmingError::InvariantViolation));
+        }
+
+        let emitted = tokens_emitted_over(to.slot - from.slot + 1, self.tps)?;
+        if self.front_load_percent == 0 {
+            Ok(emitted)
+        } else {
+            tokens_emitted_with_front_load(
+                self.front_load_percent,
+                (self.front_load_starts_at, self.front_load_ends_at),
+                (from, to),
+                emitted,
+            )
+        }
+    }
+
+    /// The periods history has a fixed length. To make room for a new period,
+    /// the oldest one must be dropped, which is only possible if it's not
+    /// initialized or if no snapshot refers to it anymore.
+    fn ensure_can_be_dropped(&self, oldest_snapshot: Snapshot) -> Result<()> {
+        let is_initialized = if cfg!(any(feature = "prod", test)) {
+            // if the oldest period is within the current snapshot history,
+            // we are unable to update its value, the admin already passed the
+            // allowed max number of possible configuration updates
+            self.ends_at.slot != 0
+        } else {
+            // always enable updates in dev
+            false
+        };
+        if is_initialized
+            && self.ends_at.slot >= oldest_snapshot.started_at.slot
+        {
+            msg!("Oldest period is still within the ring buffer history");
+            return Err(error!(FarmingError::ConfigurationUpdateLimitExceeded));
         }
+
+        Ok(())
+    }
+}
+
+/// How many tokens are emitted over given number of slots with given tps, see
+/// [`HarvestPeriod::tokens_emitted`].
+fn tokens_emitted_over(slots: u64, tps: TokenAmount) -> Result<TokenAmount> {
+    let emitted = slots as u128 * tps.amount as u128;
+
+    Ok(TokenAmount::new(
+        emitted.try_into().map_err(|_| FarmingError::MathOverflow)?,
+    ))
+}
+
+/// Given `emitted` tokens over the slots of the inclusive range `from..=to`
+/// at a constant `ρ` of the period, how many tokens are emitted if the period
+/// is front-loaded by given percent along given curve, see
+/// [`HarvestPeriod::front_load_percent`].
+///
+/// The rate in the `i`-th slot of a curve whose last slot has the index `n`
+/// is `ρ * (1 + f * (n - 2i) / n)`. Summed over the indexes `a..=b` that's
+/// `emitted * (1 + f * (n - a - b) / n)`, which is `emitted` over the whole
+/// curve.
+fn tokens_emitted_with_front_load(
+    front_load_percent: u64,
+    (curve_starts_at, curve_ends_at): (Slot, Slot),
+    (from, to): (Slot, Slot),
+    emitted: TokenAmount,
+) -> Result<TokenAmount> {
+    // a split period keeps the curve of the original period, which therefore
+    // spans all of its slots
+    if from < curve_starts_at || to > curve_ends_at {
+        return Err(error!(FarmingError::InvariantViolation));
+    }
+
+    let n = curve_ends_at.slot - curve_starts_at.slot;
+    if n == 0 {
+        return Ok(emitted);
     }
+
+    let a = from.slot - curve_starts_at.slot;
+    let b = to.slot - curve_starts_at.slot;
+    let (is_early, distance) = if a + b <= n {
+        (true, n - a - b)
+    } else {
+        (false, a + b - n)
+    };
+
+    // distance is at most n and the percent at most 100, therefore the
+    // adjustment is at most the emitted amount
+    let emitted = Decimal::from(emitted.amount);
+    let adjustment = emitted
+        .try_mul(Decimal::from(distance).try_div(Decimal::from(n))?)?
+        .try_mul(Decimal::from(front_load_percent))?
+        .try_div(Decimal::from(100u64))?;
+    let emitted = if is_early {
+        emitted.try_add(adjustment)?
+    } else {
+        emitted.try_sub(adjustment)?
+    };
+
+    Ok(TokenAmount::new(emitted.try_floor()?))
 }
 
 #[cfg(test)]
@@ -633,7 +1624,140 @@ mod tests {
     fn it_has_stable_size() {
         let farm = Farm::default();
 
-        assert_eq!(8 + std::mem::size_of_val(&farm), 19_160);
+        assert_eq!(8 + std::mem::size_of_val(&farm), 22_072);
+    }
+
+    #[test]
+    fn it_has_stable_legacy_size() {
+        assert_eq!(LegacyFarm::space(), 19_160);
+    }
+
+    #[test]
+    fn it_migrates_legacy_farm() {
+        let harvest_mint = Pubkey::new_unique();
+        let mut legacy = LegacyFarm {
+            admin: Pubkey::new_unique(),
+            stake_mint: Pubkey::new_unique(),
+            stake_vault: Pubkey::new_unique(),
+            min_snapshot_window_slots: 10,
+            ..Default::default()
+        };
+        legacy.harvests[1].mint = harvest_mint;
+        legacy.harvests[1].vault = Pubkey::new_unique();
+        legacy.harvests[1].periods[0] = LegacyHarvestPeriod {
+            tps: TokenAmount::new(5),
+            starts_at: Slot::new(20),
+            ends_at: Slot::new(30),
+        };
+        legacy.snapshots.ring_buffer_tip = 1;
+        legacy.snapshots.ring_buffer[1] = Snapshot {
+            staked: TokenAmount::new(100),
+            started_at: Slot::new(15),
+        };
+
+        let mut farm = Farm::default();
+        legacy.migrate(&mut farm);
+
+        assert_eq!(farm.admin, legacy.admin);
+        assert_eq!(farm.stake_mint, legacy.stake_mint);
+        assert_eq!(farm.stake_vault, legacy.stake_vault);
+        assert_eq!(farm.min_snapshot_window_slots, 10);
+        assert_eq!(farm.harvests[0], Harvest::default());
+        assert_eq!(farm.harvests[1].mint, harvest_mint);
+        assert_eq!(farm.harvests[1].vault, legacy.harvests[1].vault);
+        assert_eq!(
+            farm.harvests[1].periods[0],
+            HarvestPeriod {
+                tps: TokenAmount::new(5),
+                starts_at: Slot::new(20),
+                ends_at: Slot::new(30),
+                ..Default::default()
+            }
+        );
+        assert_eq!(farm.harvests[1].periods[1], HarvestPeriod::default());
+        assert!(!farm.harvests[1].is_paused());
+        assert!(farm.snapshots == legacy.snapshots);
+        assert_eq!(farm.latest_snapshot().staked, TokenAmount::new(100));
+        assert_eq!(farm.lockup_slots, 0);
+        assert_eq!(farm.harvests_start_at, Slot::new(0));
+    }
+
+    #[test]
+    fn it_calculates_tokens_emitted_over_slots() -> Result<()> {
+        assert_eq!(
+            tokens_emitted_over(10, TokenAmount::new(5))?,
+            TokenAmount::new(50)
+        );
+        assert_eq!(
+            tokens_emitted_over(0, TokenAmount::new(u64::MAX))?,
+            TokenAmount::new(0)
+        );
+        assert_eq!(
+            tokens_emitted_over(2, TokenAmount::new(u64::MAX / 2))?,
+            TokenAmount::new(u64::MAX - 1)
+        );
+        // an emission which doesn't fit into a vault was never funded
+        assert!(tokens_emitted_over(3, TokenAmount::new(u64::MAX / 2)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_errs_if_emission_range_is_not_within_period() -> Result<()> {
+        let period = HarvestPeriod {
+            tps: TokenAmount::new(5),
+            starts_at: Slot::new(10),
+            ends_at: Slot::new(20),
+            front_load_starts_at: Slot::new(10),
+            front_load_ends_at: Slot::new(20),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            period.tokens_emitted((Slot::new(10), Slot::new(20)))?,
+            TokenAmount::new(55)
+        );
+        assert!(period
+            .tokens_emitted((Slot::new(15), Slot::new(14)))
+            .is_err());
+        assert!(period
+            .tokens_emitted((Slot::new(9), Slot::new(14)))
+            .is_err());
+        assert!(period
+            .tokens_emitted((Slot::new(15), Slot::new(21)))
+            .is_err());
+        assert!(period
+            .tokens_emitted((Slot::new(21), Slot::new(25)))
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_fails_to_donate_without_stake_or_unknown_mint() -> Result<()> {
+        let harvest_mint = Pubkey::new_unique();
+        let mut farm = Farm::default();
+        farm.min_snapshot_window_slots = 1;
+        farm.add_harvest(harvest_mint, Pubkey::new_unique())?;
+
+        assert!(farm
+            .donate(harvest_mint, TokenAmount::new(10), TokenAmount::new(0))
+            .is_err());
+        assert!(farm
+            .donate(
+                Pubkey::new_unique(),
+                TokenAmount::new(10),
+                TokenAmount::new(4)
+            )
+            .is_err());
+
+        farm.donate(harvest_mint, TokenAmount::new(10), TokenAmount::new(4))?;
+        assert_eq!(
+            Decimal::from(farm.harvests[0].donations_per_staked_token),
+            Decimal::from(10u64).try_div(Decimal::from(4u64))?
+        );
+
+        Ok(())
     }
 
     #[test]
@@ -718,16 +1842,19 @@ mod tests {
             tps: TokenAmount::new(1),
             starts_at: Slot::new(20),
             ends_at: Slot::new(25),
+            ..Default::default()
         };
         harvest.periods[1] = HarvestPeriod {
             tps: TokenAmount::new(2),
             starts_at: Slot::new(10),
             ends_at: Slot::new(19),
+            ..Default::default()
         };
         harvest.periods[2] = HarvestPeriod {
             tps: TokenAmount::new(3),
             starts_at: Slot::new(5),
             ends_at: Slot::new(8),
+            ..Default::default()
         };
 
         assert_eq!(
@@ -809,6 +1936,75 @@ mod tests {
         }
     }
 
+    #[test]
+    fn it_pages_snapshots_from_oldest_to_latest() -> Result<()> {
+        let mut farm = Farm::default();
+        farm.min_snapshot_window_slots = 1;
+        for slot in 1..=5 {
+            farm.take_snapshot(Slot::new(slot), TokenAmount::new(slot * 10))?;
+        }
+
+        let page = farm.snapshots_page(0, 4);
+        assert_eq!(page.total, 6);
+        assert_eq!(page.snapshots.len(), 4);
+        assert_eq!(page.snapshots[0], SnapshotRecord::default());
+        assert_eq!(
+            page.snapshots[1],
+            SnapshotRecord {
+                started_at: Slot::new(1),
+                staked: TokenAmount::new(10),
+            }
+        );
+
+        let page = farm.snapshots_page(4, 4);
+        assert_eq!(page.total, 6);
+        assert_eq!(
+            page.snapshots,
+            vec![
+                SnapshotRecord {
+                    started_at: Slot::new(4),
+                    staked: TokenAmount::new(40),
+                },
+                SnapshotRecord {
+                    started_at: Slot::new(5),
+                    staked: TokenAmount::new(50),
+                },
+            ]
+        );
+
+        assert!(farm.snapshots_page(6, 4).snapshots.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_pages_snapshots_once_ring_buffer_wraps() -> Result<()> {
+        let mut farm = Farm::default();
+        farm.min_snapshot_window_slots = 1;
+        let snapshots_taken = consts::SNAPSHOTS_LEN as u64 + 2;
+        for slot in 1..=snapshots_taken {
+            farm.take_snapshot(Slot::new(slot), TokenAmount::new(slot))?;
+        }
+
+        let page = farm.snapshots_page(0, 2);
+        assert_eq!(page.total, consts::SNAPSHOTS_LEN as u64);
+        assert_eq!(
+            page.snapshots[0].started_at,
+            farm.oldest_snapshot().started_at
+        );
+        assert_eq!(
+            page.snapshots[1].started_at.slot,
+            page.snapshots[0].started_at.slot + 1
+        );
+
+        let page = farm
+            .snapshots_page(consts::SNAPSHOTS_LEN - 1, consts::SNAPSHOTS_LEN);
+        assert_eq!(page.snapshots.len(), 1);
+        assert_eq!(page.snapshots[0].started_at, Slot::new(snapshots_taken));
+
+        Ok(())
+    }
+
     #[test]
     fn it_takes_snapshot() {
         let mut farm = Farm::default();
@@ -829,35 +2025,358 @@ mod tests {
         assert_eq!(farm.snapshots.ring_buffer_tip, 1);
 
         assert_eq!(
-            farm.snapshots.ring_buffer[1].staked,
-            TokenAmount { amount: 10 }
+            farm.snapshots.ring_buffer[1].staked,
+            TokenAmount { amount: 10 }
+        );
+
+        assert_eq!(farm.snapshots.ring_buffer[1].started_at, Slot { slot: 5 });
+    }
+
+    #[test]
+    fn it_corrects_latest_snapshot_within_bounds() -> Result<()> {
+        let mut farm = Farm::default();
+        farm.min_snapshot_window_slots = 1;
+
+        // nothing to correct yet
+        assert!(farm
+            .correct_latest_snapshot(TokenAmount::new(10), TokenAmount::new(10))
+            .is_err());
+
+        farm.take_snapshot(Slot::new(5), TokenAmount::new(10))?;
+        farm.take_snapshot(Slot::new(6), TokenAmount::new(15))?;
+
+        // can only be raised
+        assert!(farm
+            .correct_latest_snapshot(TokenAmount::new(1), TokenAmount::new(20))
+            .is_err());
+        assert!(farm
+            .correct_latest_snapshot(TokenAmount::new(15), TokenAmount::new(20))
+            .is_err());
+        assert!(farm
+            .correct_latest_snapshot(TokenAmount::new(21), TokenAmount::new(20))
+            .is_err());
+
+        let previous = farm.correct_latest_snapshot(
+            TokenAmount::new(20),
+            TokenAmount::new(20),
+        )?;
+        assert_eq!(previous, TokenAmount::new(15));
+        assert_eq!(farm.latest_snapshot().staked, TokenAmount::new(20));
+        assert_eq!(farm.latest_snapshot().started_at, Slot::new(6));
+        // older snapshots are left as they are
+        assert_eq!(farm.snapshots.ring_buffer[1].staked, TokenAmount::new(10));
+        assert_eq!(farm.snapshots.ring_buffer_tip, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_tells_whether_stake_changed_since_last_snapshot() -> Result<()> {
+        let mut farm = Farm::default();
+        farm.min_snapshot_window_slots = 1;
+
+        assert!(
+            !farm.has_stake_changed_since_last_snapshot(TokenAmount::new(0))
+        );
+        assert!(
+            farm.has_stake_changed_since_last_snapshot(TokenAmount::new(10))
+        );
+
+        farm.take_snapshot(Slot::new(5), TokenAmount::new(10))?;
+        assert!(
+            !farm.has_stake_changed_since_last_snapshot(TokenAmount::new(10))
+        );
+        assert!(farm.has_stake_changed_since_last_snapshot(TokenAmount::new(9)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_pays_keeper_reward_at_most_once_per_window() {
+        let mut farm = Farm::default();
+        assert!(farm.is_keeper_reward_due(Slot::new(5)));
+
+        farm.keeper_rewarded_at = Slot::new(5);
+        assert!(!farm.is_keeper_reward_due(Slot::new(6)));
+        assert!(!farm.is_keeper_reward_due(Slot::new(
+            5 + consts::KEEPER_REWARD_WINDOW_SLOTS - 1
+        )));
+        assert!(farm.is_keeper_reward_due(Slot::new(
+            5 + consts::KEEPER_REWARD_WINDOW_SLOTS
+        )));
+    }
+
+    #[test]
+    fn it_applies_pending_lockup_only_once_effective() {
+        let mut farm = Farm::default();
+        farm.lockup_slots = 10;
+        farm.pending_lockup_slots = 100;
+        farm.pending_lockup_effective_at = Slot::new(50);
+
+        assert_eq!(farm.active_lockup_slots(Slot::new(49)), 10);
+        farm.apply_pending_lockup(Slot::new(49));
+        assert_eq!(farm.lockup_slots, 10);
+        assert!(farm.has_pending_lockup());
+
+        assert_eq!(farm.active_lockup_slots(Slot::new(50)), 100);
+        farm.apply_pending_lockup(Slot::new(50));
+        assert_eq!(farm.lockup_slots, 100);
+        assert!(!farm.has_pending_lockup());
+        assert_eq!(farm.active_lockup_slots(Slot::new(50)), 100);
+    }
+
+    #[test]
+    fn it_cannot_add_harvest_which_already_exists() -> Result<()> {
+        let mint = Pubkey::new_unique();
+
+        let mut farm = Farm::default();
+        farm.add_harvest(mint, Pubkey::new_unique())?;
+        assert!(farm.add_harvest(mint, Pubkey::new_unique()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_returns_err_if_max_harvests_reached() -> Result<()> {
+        let mint = Pubkey::new_unique();
+
+        let mut farm = Farm::default();
+
+        for _ in 0..consts::MAX_HARVEST_MINTS {
+            farm.add_harvest(Pubkey::new_unique(), Pubkey::new_unique())?;
+        }
+
+        assert!(farm.add_harvest(mint, Pubkey::new_unique()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_tells_whether_farm_has_ended() -> Result<()> {
+        let mut farm = Farm::default();
+        assert!(!farm.has_ended(Slot::new(100)));
+
+        let mint1 = Pubkey::new_unique();
+        let mint2 = Pubkey::new_unique();
+        farm.add_harvest(mint1, Pubkey::new_unique())?;
+        farm.add_harvest(mint2, Pubkey::new_unique())?;
+        // no periods scheduled yet
+        assert!(!farm.has_ended(Slot::new(100)));
+
+        farm.new_harvest_period(
+            Slot::new(0),
+            mint1,
+            (Slot::new(1), Slot::new(10)),
+            TokenAmount::new(10),
+        )?;
+        farm.new_harvest_period(
+            Slot::new(0),
+            mint2,
+            (Slot::new(5), Slot::new(20)),
+            TokenAmount::new(10),
+        )?;
+
+        assert!(!farm.has_ended(Slot::new(15)));
+        assert!(!farm.has_ended(Slot::new(20)));
+        assert!(farm.has_ended(Slot::new(21)));
+
+        farm.check_farming_not_blocked(Slot::new(21))?;
+        farm.block_farming_once_ended = 1;
+        farm.check_farming_not_blocked(Slot::new(20))?;
+        assert!(farm
+            .check_farming_not_blocked(Slot::new(21))
+            .unwrap_err()
+            .to_string()
+            .contains("FarmingBlockedOnceEnded"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_projects_when_harvest_vault_runs_out() -> Result<()> {
+        let mint = Pubkey::new_unique();
+        let mut farm = Farm::default();
+        farm.add_harvest(mint, Pubkey::new_unique())?;
+        farm.new_harvest_period(
+            Slot::new(0),
+            mint,
+            (Slot::new(10), Slot::new(1_000)),
+            TokenAmount::new(7),
+        )?;
+
+        // 100 + floor(1_000 / 7) = 100 + 142
+        let runway =
+            farm.harvest_runway(Slot::new(100), mint, TokenAmount::new(1_000))?;
+        assert_eq!(
+            runway,
+            HarvestRunway {
+                mint,
+                remaining: TokenAmount::new(1_000),
+                tps: TokenAmount::new(7),
+                exhausted_at: Some(Slot::new(242)),
+            }
+        );
+
+        // 100 + floor(1_000 / 14) = 100 + 71
+        farm.multiply_emission(
+            Slot::new(60),
+            mint,
+            200,
+            (Slot::new(61), Slot::new(500)),
+        )?;
+        let runway =
+            farm.harvest_runway(Slot::new(100), mint, TokenAmount::new(1_000))?;
+        assert_eq!(runway.tps, TokenAmount::new(14));
+        assert_eq!(runway.exhausted_at, Some(Slot::new(171)));
+
+        // nothing is emitted once all periods have ended
+        let runway = farm.harvest_runway(
+            Slot::new(1_001),
+            mint,
+            TokenAmount::new(1_000),
+        )?;
+        assert_eq!(runway.tps, TokenAmount::new(0));
+        assert_eq!(runway.exhausted_at, None);
+
+        assert!(farm
+            .harvest_runway(
+                Slot::new(100),
+                Pubkey::new_unique(),
+                TokenAmount::new(1)
+            )
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_projects_runway_at_front_loaded_rate() -> Result<()> {
+        let mint = Pubkey::new_unique();
+        let mut farm = Farm::default();
+        farm.emission_front_load_percent = 50;
+        farm.add_harvest(mint, Pubkey::new_unique())?;
+        farm.new_harvest_period(
+            Slot::new(0),
+            mint,
+            (Slot::new(1), Slot::new(101)),
+            TokenAmount::new(100),
+        )?;
+
+        // 1 + floor(1_000 / 150)
+        let runway =
+            farm.harvest_runway(Slot::new(1), mint, TokenAmount::new(1_000))?;
+        assert_eq!(runway.tps, TokenAmount::new(150));
+        assert_eq!(runway.exhausted_at, Some(Slot::new(7)));
+
+        let runway =
+            farm.harvest_runway(Slot::new(51), mint, TokenAmount::new(1_000))?;
+        assert_eq!(runway.tps, TokenAmount::new(100));
+
+        let runway =
+            farm.harvest_runway(Slot::new(101), mint, TokenAmount::new(1_000))?;
+        assert_eq!(runway.tps, TokenAmount::new(50));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_front_loads_only_periods_created_afterwards() -> Result<()> {
+        let mint = Pubkey::new_unique();
+        let mut farm = Farm::default();
+        farm.add_harvest(mint, Pubkey::new_unique())?;
+        farm.new_harvest_period(
+            Slot::new(0),
+            mint,
+            (Slot::new(1), Slot::new(10)),
+            TokenAmount::new(100),
+        )?;
+
+        farm.emission_front_load_percent = 50;
+        farm.new_harvest_period(
+            Slot::new(20),
+            mint,
+            (Slot::new(20), Slot::new(30)),
+            TokenAmount::new(100),
+        )?;
+
+        let harvest = farm.get_harvest(mint);
+        assert_eq!(
+            harvest.periods[0],
+            HarvestPeriod {
+                tps: TokenAmount::new(100),
+                starts_at: Slot::new(20),
+                ends_at: Slot::new(30),
+                front_load_percent: 50,
+                front_load_starts_at: Slot::new(20),
+                front_load_ends_at: Slot::new(30),
+            }
         );
+        assert_eq!(harvest.periods[1].front_load_percent, 0);
 
-        assert_eq!(farm.snapshots.ring_buffer[1].started_at, Slot { slot: 5 });
+        // both periods emit what the admin funded
+        assert_eq!(harvest.periods[0].total_tokens()?, TokenAmount::new(1_100));
+        assert_eq!(harvest.periods[1].total_tokens()?, TokenAmount::new(1_000));
+
+        Ok(())
     }
 
     #[test]
-    fn it_cannot_add_harvest_which_already_exists() -> Result<()> {
+    fn it_saturates_farm_stats_counters() -> Result<()> {
         let mint = Pubkey::new_unique();
-
         let mut farm = Farm::default();
         farm.add_harvest(mint, Pubkey::new_unique())?;
-        assert!(farm.add_harvest(mint, Pubkey::new_unique()).is_err());
+
+        farm.add_to_total_distributed(mint, TokenAmount::new(u64::MAX));
+        farm.add_to_total_distributed(mint, TokenAmount::new(1));
+        assert_eq!(farm.harvests[0].total_distributed.amount, u64::MAX);
+
+        // unknown mint is a no-op
+        farm.add_to_total_distributed(
+            Pubkey::new_unique(),
+            TokenAmount::new(1),
+        );
+        assert_eq!(farm.stats(TokenAmount::new(0)).harvests.len(), 1);
 
         Ok(())
     }
 
     #[test]
-    fn it_returns_err_if_max_harvests_reached() -> Result<()> {
-        let mint = Pubkey::new_unique();
-
-        let mut farm = Farm::default();
-
-        for _ in 0..consts::MAX_HARVEST_MINTS {
-            farm.add_harvest(Pubkey::new_unique(), Pubkey::new_unique())?;
-        }
-
-        assert!(farm.add_harvest(mint, Pubkey::new_unique()).is_err());
+    fn it_reports_compounding_eligibility() -> Result<()> {
+        let stake_mint = Pubkey::new_unique();
+        let mut farm = Farm {
+            stake_mint,
+            ..Default::default()
+        };
+        let mut farmer = Farmer::default();
+        farmer.harvests[0].mint = stake_mint;
+        farmer.harvests[0].tokens = TokenAmount::new(100);
+
+        // stake mint is not harvested yet
+        let eligibility =
+            farm.compounding_eligibility(true, stake_mint, &farmer);
+        assert!(!eligibility.is_eligible());
+        assert!(!eligibility.harvests_target_stake_mint);
+        assert_eq!(eligibility.eligible_harvest, TokenAmount::new(0));
+
+        farm.add_harvest(stake_mint, Pubkey::new_unique())?;
+
+        // not whitelisted
+        let eligibility =
+            farm.compounding_eligibility(false, stake_mint, &farmer);
+        assert!(!eligibility.is_eligible());
+        assert!(eligibility.harvests_target_stake_mint);
+        assert_eq!(eligibility.eligible_harvest, TokenAmount::new(100));
+
+        // whitelisted
+        let eligibility =
+            farm.compounding_eligibility(true, stake_mint, &farmer);
+        assert!(eligibility.is_eligible());
+        assert_eq!(eligibility.eligible_harvest, TokenAmount::new(100));
+
+        // other farm's stake mint is not harvested
+        let eligibility =
+            farm.compounding_eligibility(true, Pubkey::new_unique(), &farmer);
+        assert!(!eligibility.is_eligible());
 
         Ok(())
     }
@@ -929,6 +2448,7 @@ mod tests {
                 starts_at: Slot::new(40),
                 ends_at: Slot::new(50),
                 tps: TokenAmount::new(20),
+                ..Default::default()
             }
         );
         assert_eq!(
@@ -937,6 +2457,7 @@ mod tests {
                 starts_at: Slot::new(5),
                 ends_at: Slot::new(25),
                 tps: TokenAmount::new(20),
+                ..Default::default()
             }
         );
 
@@ -989,6 +2510,7 @@ mod tests {
             starts_at: Slot::new(10),
             ends_at: Slot::new(20),
             tps: TokenAmount::new(10),
+            ..Default::default()
         };
         // call new_harvest_period method which should overwrite it
         farm.new_harvest_period(
@@ -1002,7 +2524,7 @@ mod tests {
             HarvestPeriod {
                 starts_at: Slot::new(15),
                 ends_at: Slot::new(25),
-                tps: TokenAmount::new(20)
+                tps: TokenAmount::new(20)..Default::default()
             }
         );
 
@@ -1012,7 +2534,7 @@ mod tests {
                 HarvestPeriod {
                     starts_at: Slot::new(0),
                     ends_at: Slot::new(0),
-                    tps: TokenAmount::new(0)
+                    tps: TokenAmount::new(0)..Default::default()
                 }
             );
         }
@@ -1032,6 +2554,7 @@ mod tests {
                 starts_at: Slot::new(u * 10),
                 ends_at: Slot::new(u * 10 + 5),
                 tps: TokenAmount::new(100 * u),
+                ..Default::default()
             });
 
         let output = farm.new_harvest_period(
@@ -1059,6 +2582,7 @@ mod tests {
                 starts_at: Slot::new(160),
                 ends_at: Slot::new(165),
                 tps: TokenAmount::new(10),
+                ..Default::default()
             }
         );
         assert_eq!(
@@ -1067,8 +2591,402 @@ mod tests {
                 starts_at: Slot::new(20),
                 ends_at: Slot::new(25),
                 tps: TokenAmount::new(200),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn it_stops_tps_history_of_paused_harvest() -> Result<()> {
+        let mut farm = Farm::default();
+        let harvest_mint = Pubkey::new_unique();
+        farm.add_harvest(harvest_mint, Pubkey::new_unique())?;
+        farm.new_harvest_period(
+            Slot::new(1),
+            harvest_mint,
+            (Slot::new(1), Slot::new(100)),
+            TokenAmount::new(10),
+        )?;
+
+        farm.pause_harvest(Slot::new(20), harvest_mint)?;
+        assert_eq!(farm.get_harvest(harvest_mint).paused_at, Slot::new(21));
+
+        assert_eq!(
+            farm.get_harvest(harvest_mint).tps_history(Slot::new(50)),
+            vec![
+                (Slot::new(1)..=Slot::new(20), TokenAmount::new(10)),
+                (Slot::new(21)..=Slot::new(100), TokenAmount::new(0)),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_cuts_paused_slots_out_of_periods_on_resume() -> Result<()> {
+        let mut farm = Farm::default();
+        let harvest_mint = Pubkey::new_unique();
+        farm.add_harvest(harvest_mint, Pubkey::new_unique())?;
+        farm.new_harvest_period(
+            Slot::new(1),
+            harvest_mint,
+            (Slot::new(1), Slot::new(100)),
+            TokenAmount::new(10),
+        )?;
+
+        farm.pause_harvest(Slot::new(20), harvest_mint)?;
+        farm.resume_harvest(Slot::new(30), harvest_mint)?;
+
+        let harvest = farm.get_harvest(harvest_mint);
+        assert!(!harvest.is_paused());
+        assert_eq!(
+            harvest.periods[0],
+            HarvestPeriod {
+                starts_at: Slot::new(31),
+                ends_at: Slot::new(100),
+                tps: TokenAmount::new(10),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            harvest.periods[1],
+            HarvestPeriod {
+                starts_at: Slot::new(1),
+                ends_at: Slot::new(20),
+                tps: TokenAmount::new(10),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            harvest.tps_history(Slot::new(200)),
+            vec![
+                (Slot::new(1)..=Slot::new(20), TokenAmount::new(10)),
+                (Slot::new(21)..=Slot::new(30), TokenAmount::new(0)),
+                (Slot::new(31)..=Slot::new(100), TokenAmount::new(10)),
+                (Slot::new(101)..=Slot::new(200), TokenAmount::new(0)),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_drops_periods_which_fall_into_pause_on_resume() -> Result<()> {
+        let mut farm = Farm::default();
+        let harvest_mint = Pubkey::new_unique();
+        farm.add_harvest(harvest_mint, Pubkey::new_unique())?;
+        farm.new_harvest_period(
+            Slot::new(1),
+            harvest_mint,
+            (Slot::new(1), Slot::new(10)),
+            TokenAmount::new(10),
+        )?;
+        farm.pause_harvest(Slot::new(5), harvest_mint)?;
+        farm.new_harvest_period(
+            Slot::new(11),
+            harvest_mint,
+            (Slot::new(15), Slot::new(20)),
+            TokenAmount::new(20),
+        )?;
+
+        farm.resume_harvest(Slot::new(30), harvest_mint)?;
+
+        let harvest = farm.get_harvest(harvest_mint);
+        assert_eq!(
+            harvest.periods[0],
+            HarvestPeriod {
+                starts_at: Slot::new(1),
+                ends_at: Slot::new(5),
+                tps: TokenAmount::new(10),
+                ..Default::default()
             }
         );
+        assert_eq!(harvest.periods[1], HarvestPeriod::default());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_resumes_harvest_paused_in_same_slot() -> Result<()> {
+        let mut farm = Farm::default();
+        let harvest_mint = Pubkey::new_unique();
+        farm.add_harvest(harvest_mint, Pubkey::new_unique())?;
+        farm.new_harvest_period(
+            Slot::new(1),
+            harvest_mint,
+            (Slot::new(1), Slot::new(100)),
+            TokenAmount::new(10),
+        )?;
+        let periods = farm.get_harvest(harvest_mint).periods;
+
+        farm.pause_harvest(Slot::new(20), harvest_mint)?;
+        farm.resume_harvest(Slot::new(20), harvest_mint)?;
+
+        let harvest = farm.get_harvest(harvest_mint);
+        assert!(!harvest.is_paused());
+        assert_eq!(harvest.periods, periods);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_errs_if_harvest_paused_twice_or_resumed_if_not_paused() -> Result<()>
+    {
+        let mut farm = Farm::default();
+        let harvest_mint = Pubkey::new_unique();
+        farm.add_harvest(harvest_mint, Pubkey::new_unique())?;
+
+        assert!(farm.resume_harvest(Slot::new(10), harvest_mint).is_err());
+
+        farm.pause_harvest(Slot::new(10), harvest_mint)?;
+        assert!(farm.pause_harvest(Slot::new(11), harvest_mint).is_err());
+
+        assert!(farm
+            .pause_harvest(Slot::new(11), Pubkey::new_unique())
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_errs_resume_if_periods_history_is_full() -> Result<()> {
+        let mut farm = Farm::default();
+
+        let harvest_mint = Pubkey::new_unique();
+        farm.harvests[0].mint = harvest_mint;
+        farm.harvests[0].periods =
+            [10, 9, 8, 7, 6, 5, 4, 3, 2, 1].map(|u| HarvestPeriod {
+                starts_at: Slot::new(u * 10),
+                ends_at: Slot::new(u * 10 + 5),
+                tps: TokenAmount::new(100 * u),
+                ..Default::default()
+            });
+
+        // the running period would be split in two
+        farm.pause_harvest(Slot::new(101), harvest_mint)?;
+        assert!(farm.resume_harvest(Slot::new(102), harvest_mint).is_err());
+        assert!(farm.get_harvest(harvest_mint).is_paused());
+
+        // unless no snapshot refers to the oldest period anymore
+        farm.snapshots.ring_buffer[farm.oldest_snapshot_index()] = Snapshot {
+            staked: TokenAmount::new(0),
+            started_at: Slot::new(90),
+        };
+        farm.resume_harvest(Slot::new(102), harvest_mint)?;
+
+        let harvest = farm.get_harvest(harvest_mint);
+        assert_eq!(harvest.periods[0].starts_at, Slot::new(103));
+        assert_eq!(harvest.periods[1].ends_at, Slot::new(101));
+        assert_eq!(harvest.periods[9].starts_at, Slot::new(20));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_multiplies_emission_by_splitting_periods() -> Result<()> {
+        let mut farm = Farm::default();
+        let harvest_mint = Pubkey::new_unique();
+        farm.add_harvest(harvest_mint, Pubkey::new_unique())?;
+        farm.new_harvest_period(
+            Slot::new(1),
+            harvest_mint,
+            (Slot::new(1), Slot::new(100)),
+            TokenAmount::new(10),
+        )?;
+
+        // 3x in slots 41-50
+        let extra_tokens = farm.multiply_emission(
+            Slot::new(20),
+            harvest_mint,
+            300,
+            (Slot::new(41), Slot::new(50)),
+        )?;
+        assert_eq!(extra_tokens, TokenAmount::new(10 * 10 * 2));
+
+        let harvest = farm.get_harvest(harvest_mint);
+        assert_eq!(
+            harvest.tps_history(Slot::new(200)),
+            vec![
+                (Slot::new(1)..=Slot::new(40), TokenAmount::new(10)),
+                (Slot::new(41)..=Slot::new(50), TokenAmount::new(30)),
+                (Slot::new(51)..=Slot::new(100), TokenAmount::new(10)),
+                (Slot::new(101)..=Slot::new(200), TokenAmount::new(0)),
+            ]
+        );
+
+        // the event outlasts the period, slots without a period are not
+        // affected
+        let extra_tokens = farm.multiply_emission(
+            Slot::new(20),
+            harvest_mint,
+            200,
+            (Slot::new(91), Slot::new(1_000)),
+        )?;
+        assert_eq!(extra_tokens, TokenAmount::new(10 * 10));
+
+        let harvest = farm.get_harvest(harvest_mint);
+        assert_eq!(
+            harvest.tps_history(Slot::new(200)),
+            vec![
+                (Slot::new(1)..=Slot::new(40), TokenAmount::new(10)),
+                (Slot::new(41)..=Slot::new(50), TokenAmount::new(30)),
+                (Slot::new(51)..=Slot::new(90), TokenAmount::new(10)),
+                (Slot::new(91)..=Slot::new(100), TokenAmount::new(20)),
+                (Slot::new(101)..=Slot::new(200), TokenAmount::new(0)),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_errs_if_multiplied_emission_starts_at_or_before_current_slot(
+    ) -> Result<()> {
+        let mut farm = Farm::default();
+        let harvest_mint = Pubkey::new_unique();
+        farm.add_harvest(harvest_mint, Pubkey::new_unique())?;
+        farm.new_harvest_period(
+            Slot::new(1),
+            harvest_mint,
+            (Slot::new(1), Slot::new(100)),
+            TokenAmount::new(10),
+        )?;
+
+        assert!(farm
+            .multiply_emission(
+                Slot::new(20),
+                harvest_mint,
+                200,
+                (Slot::new(20), Slot::new(30)),
+            )
+            .is_err());
+        assert!(farm
+            .multiply_emission(
+                Slot::new(20),
+                harvest_mint,
+                200,
+                (Slot::new(30), Slot::new(29)),
+            )
+            .is_err());
+        assert!(farm
+            .multiply_emission(
+                Slot::new(20),
+                Pubkey::new_unique(),
+                200,
+                (Slot::new(21), Slot::new(30)),
+            )
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_overwrites_all_parts_of_split_scheduled_launch() -> Result<()> {
+        let mut farm = Farm::default();
+        let harvest_mint = Pubkey::new_unique();
+        farm.add_harvest(harvest_mint, Pubkey::new_unique())?;
+        farm.new_harvest_period(
+            Slot::new(1),
+            harvest_mint,
+            (Slot::new(1), Slot::new(5)),
+            TokenAmount::new(1),
+        )?;
+        farm.new_harvest_period(
+            Slot::new(1),
+            harvest_mint,
+            (Slot::new(10), Slot::new(100)),
+            TokenAmount::new(10),
+        )?;
+        let extra_tokens = farm.multiply_emission(
+            Slot::new(1),
+            harvest_mint,
+            200,
+            (Slot::new(30), Slot::new(50)),
+        )?;
+        assert_eq!(extra_tokens, TokenAmount::new(21 * 10));
+
+        // the scheduled launch is now split into 10-29, 30-50 and 51-100, all
+        // of them are overwritten and everything deposited for them returned
+        let deposited = farm.new_harvest_period(
+            Slot::new(1),
+            harvest_mint,
+            (Slot::new(20), Slot::new(40)),
+            TokenAmount::new(5),
+        )?;
+        assert_eq!(deposited, TokenAmount::new(91 * 10 + 21 * 10));
+
+        let harvest = farm.get_harvest(harvest_mint);
+        assert_eq!(
+            harvest.tps_history(Slot::new(200)),
+            vec![
+                (Slot::new(1)..=Slot::new(5), TokenAmount::new(1)),
+                (Slot::new(6)..=Slot::new(19), TokenAmount::new(0)),
+                (Slot::new(20)..=Slot::new(40), TokenAmount::new(5)),
+                (Slot::new(41)..=Slot::new(200), TokenAmount::new(0)),
+            ]
+        );
+        assert_eq!(harvest.periods[2], HarvestPeriod::default());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_keeps_parts_of_running_period_split_by_multiplier() -> Result<()> {
+        let mut farm = Farm::default();
+        let harvest_mint = Pubkey::new_unique();
+        farm.add_harvest(harvest_mint, Pubkey::new_unique())?;
+        farm.new_harvest_period(
+            Slot::new(1),
+            harvest_mint,
+            (Slot::new(1), Slot::new(100)),
+            TokenAmount::new(10),
+        )?;
+        farm.multiply_emission(
+            Slot::new(20),
+            harvest_mint,
+            200,
+            (Slot::new(41), Slot::new(50)),
+        )?;
+
+        // the parts 41-50 and 51-100 haven't started yet, but they belong to
+        // the running period
+        assert!(farm
+            .new_harvest_period(
+                Slot::new(20),
+                harvest_mint,
+                (Slot::new(60), Slot::new(200)),
+                TokenAmount::new(5),
+            )
+            .is_err());
+
+        // a launch scheduled afterwards is appended and can be overwritten
+        let deposited = farm.new_harvest_period(
+            Slot::new(20),
+            harvest_mint,
+            (Slot::new(150), Slot::new(200)),
+            TokenAmount::new(5),
+        )?;
+        assert_eq!(deposited, TokenAmount::new(0));
+        let deposited = farm.new_harvest_period(
+            Slot::new(20),
+            harvest_mint,
+            (Slot::new(101), Slot::new(200)),
+            TokenAmount::new(5),
+        )?;
+        assert_eq!(deposited, TokenAmount::new(51 * 5));
+
+        let harvest = farm.get_harvest(harvest_mint);
+        assert_eq!(
+            harvest.tps_history(Slot::new(200)),
+            vec![
+                (Slot::new(1)..=Slot::new(40), TokenAmount::new(10)),
+                (Slot::new(41)..=Slot::new(50), TokenAmount::new(20)),
+                (Slot::new(51)..=Slot::new(100), TokenAmount::new(10)),
+                (Slot::new(101)..=Slot::new(200), TokenAmount::new(5)),
+            ]
+        );
+
+        Ok(())
     }
 
     #[test]
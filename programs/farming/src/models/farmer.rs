@@ -60,6 +60,124 @@ pub struct Farmer {
     /// There's no particular order to the harvest mints below, only guarantee
     /// is a uniqueness of pubkeys unless [`Pubkey::default`].
     pub harvests: [AvailableHarvest; 10],
+    /// Sum of the farmer's deposited tokens, ie. staked and vested, over
+    /// every slot since `stake_tracked_since` until `stake_accumulated_until`.
+    /// It's updated whenever the deposited amount changes, see
+    /// [`Farmer::accumulate_stake`], and divided by the elapsed slots gives
+    /// the time-weighted average stake, see [`Farmer::average_stake`].
+    pub cumulative_stake_slots: u128,
+    /// Zero until the farmer's deposited amount changes for the first time.
+    pub stake_tracked_since: Slot,
+    pub stake_accumulated_until: Slot,
+    /// Zero if the farmer never claimed. Used to enforce
+    /// [`Farm::claim_cooldown_slots`], see [`Farmer::check_and_record_claim`].
+    pub last_claimed_at: Slot,
+    /// How many of the deposited tokens cannot be unstaked until the farm's
+    /// lockup elapses since `locked_at`, see [`Farmer::lock_stake`]. Only
+    /// tokens deposited with [`crate::endpoints::start_farming`] are locked,
+    /// compounded harvest isn't.
+    pub locked: TokenAmount,
+    pub locked_at: Slot,
+    /// Bots compound the farmer's harvest only if it's at least this many
+    /// tokens, see [`Farmer::check_compound_bounds`]. The farmer's authority
+    /// configures this with [`crate::endpoints::set_compound_bounds`],
+    /// because compounding is permission-less.
+    pub min_compound: TokenAmount,
+    /// If not zero, bots cannot compound the farmer's harvest after this slot.
+    pub compound_deadline: Slot,
+}
+
+/// The [`Farmer`] account as it was laid out before the farmer tracked claimed
+/// totals, escrows, donations and the average stake. Such accounts are too
+/// small to be deserialized as [`Farmer`] and must be converted with
+/// [`crate::endpoints::migrate_farmer`] first.
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug, Eq, PartialEq)]
+pub struct LegacyFarmer {
+    pub authority: Pubkey,
+    pub farm: Pubkey,
+    pub staked: TokenAmount,
+    pub vested: TokenAmount,
+    pub vested_at: Slot,
+    pub calculate_next_harvest_from: Slot,
+    pub harvests: [LegacyAvailableHarvest; 10],
+}
+
+#[derive(
+    AnchorDeserialize,
+    AnchorSerialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+)]
+pub struct LegacyAvailableHarvest {
+    pub mint: Pubkey,
+    pub tokens: TokenAmount,
+}
+
+/// Time-weighted average of the farmer's deposited tokens, returned by
+/// [`crate::endpoints::get_farmer_average_stake`].
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug, Eq, PartialEq)]
+pub struct AverageStake {
+    /// Rounded down.
+    pub average_stake: TokenAmount,
+    /// The average is calculated over the slots from this one until
+    /// `until`. See [`Farmer::stake_tracked_since`].
+    pub since: Slot,
+    pub until: Slot,
+    /// See [`Farmer::cumulative_stake_slots`], but including the slots since
+    /// the last stake change until `until`.
+    pub cumulative_stake_slots: u128,
+}
+
+impl LegacyFarmer {
+    /// Includes the discriminator, which is the same as [`Farmer`]'s.
+    pub fn space() -> usize {
+        const DISCRIMINANT: usize = 8;
+        const PUBKEY: usize = mem::size_of::<Pubkey>();
+
+        DISCRIMINANT
+            + PUBKEY
+            + PUBKEY
+            + 8
+            + 8
+            + 8
+            + 8
+            + consts::MAX_HARVEST_MINTS * (PUBKEY + 8)
+    }
+
+    /// Converts the account into the current layout. The counters which the
+    /// legacy layout didn't have start at zero, except for
+    /// [`AvailableHarvest::donations_paid`]. That one is set to what the farm
+    /// has distributed so far, because the farmer's share of past donations
+    /// cannot be reconstructed and mustn't be paid out of other farmers'
+    /// share.
+    pub fn migrate(self, farm: &Farm) -> Farmer {
+        let harvests = self.harvests.map(|h| AvailableHarvest {
+            mint: h.mint,
+            tokens: h.tokens,
+            donations_paid: farm
+                .harvests
+                .iter()
+                .find(|fh| h.mint != Pubkey::default() && fh.mint == h.mint)
+                .map(|fh| fh.donations_per_staked_token)
+                .unwrap_or_default(),
+            ..Default::default()
+        });
+
+        Farmer {
+            authority: self.authority,
+            farm: self.farm,
+            staked: self.staked,
+            vested: self.vested,
+            vested_at: self.vested_at,
+            calculate_next_harvest_from: self.calculate_next_harvest_from,
+            harvests,
+            ..Default::default()
+        }
+    }
 }
 
 /// Since there are multiple harvestable mints, this must be an array. The
@@ -80,6 +198,24 @@ pub struct Farmer {
 pub struct AvailableHarvest {
     pub mint: Pubkey,
     pub tokens: TokenAmount,
+    /// How many tokens of this mint has the farmer claimed so far. This
+    /// enables indexers to e.g. rank farmers without replaying the history.
+    ///
+    /// The counter saturates instead of overflowing. It's dropped if the admin
+    /// removes the harvest mint from the farm.
+    pub total_claimed: TokenAmount,
+    /// If the farm has [`Farm::claim_vesting_slots`] set, claimed tokens are
+    /// not transferred to the farmer right away. They stay in the harvest vault
+    /// and are released linearly until [`AvailableHarvest::escrow_ends_at`].
+    pub escrowed: TokenAmount,
+    /// The escrow has been released until this slot, ie. the escrowed tokens
+    /// are released linearly from this slot on.
+    pub escrow_released_until: Slot,
+    /// All escrowed tokens are released at this slot.
+    pub escrow_ends_at: Slot,
+    /// The harvest's [`Harvest::donations_per_staked_token`] at the time the
+    /// farmer's share of donations was last added to `tokens`.
+    pub donations_paid: SDecimal,
 }
 
 impl Farmer {
@@ -103,6 +239,8 @@ impl Farmer {
         current_slot: Slot,
         tokens: TokenAmount,
     ) -> Result<()> {
+        self.accumulate_stake(current_slot)?;
+
         self.vested_at = current_slot;
         self.vested.amount = self
             .vested
@@ -122,21 +260,190 @@ impl Farmer {
     }
 
     pub fn unstake(&mut self, max: TokenAmount) -> Result<TokenAmount> {
-        if self.vested >= max {
+        let unstaked = if self.vested >= max {
             self.vested.amount -= max.amount;
-            Ok(max)
+            max
         } else {
             let total = self.total_deposited()?;
             if total > max {
                 self.staked.amount -= max.amount - self.vested.amount;
                 self.vested.amount = 0;
-                Ok(max)
+                max
             } else {
                 self.staked.amount = 0;
                 self.vested.amount = 0;
-                Ok(total)
+                total
             }
+        };
+
+        // locked tokens are only unstaked once the lock has elapsed, after
+        // which they mustn't count towards the next deposit's lock
+        self.locked.amount =
+            cmp::min(self.locked.amount, self.total_deposited()?.amount);
+
+        Ok(unstaked)
+    }
+
+    /// Locks newly deposited tokens for the farm's lockup, see
+    /// [`Farm::active_lockup_slots`]. If tokens of earlier deposits are still
+    /// locked, the lock is moved to the average of `locked_at` and the current
+    /// slot weighted by the amounts. Therefore a deposit prolongs the lock of
+    /// the earlier tokens only in proportion to its size, and a small deposit,
+    /// which anyone can make on the farmer's behalf, cannot re-lock the stake.
+    pub fn lock_stake(
+        &mut self,
+        farm: &Farm,
+        current_slot: Slot,
+        tokens: TokenAmount,
+    ) -> Result<()> {
+        if current_slot.slot >= self.unlocks_at(farm, current_slot) {
+            self.locked = TokenAmount::new(0);
+        }
+
+        let locked = self
+            .locked
+            .amount
+            .checked_add(tokens.amount)
+            .ok_or(FarmingError::MathOverflow)?;
+        if locked == 0 {
+            return Ok(());
         }
+
+        // can't overflow as both factors fit into u64
+        let elapsed = current_slot.slot.saturating_sub(self.locked_at.slot);
+        let shift = elapsed as u128 * tokens.amount as u128 / locked as u128;
+        self.locked_at = Slot::new(self.locked_at.slot + shift as u64);
+        self.locked = TokenAmount::new(locked);
+
+        Ok(())
+    }
+
+    /// How many of the deposited tokens can be unstaked at given slot.
+    pub fn unlocked_stake(
+        &self,
+        farm: &Farm,
+        current_slot: Slot,
+    ) -> Result<TokenAmount> {
+        let deposited = self.total_deposited()?;
+        if current_slot.slot >= self.unlocks_at(farm, current_slot) {
+            Ok(deposited)
+        } else {
+            Ok(TokenAmount::new(
+                deposited.amount.saturating_sub(self.locked.amount),
+            ))
+        }
+    }
+
+    /// Fails if any of the deposited tokens are still locked.
+    pub fn check_stake_unlocked(
+        &self,
+        farm: &Farm,
+        current_slot: Slot,
+    ) -> Result<()> {
+        if self.unlocked_stake(farm, current_slot)? < self.total_deposited()? {
+            msg!(
+                "Staked tokens unlock at slot {}",
+                self.unlocks_at(farm, current_slot)
+            );
+            return Err(error!(FarmingError::StakeLocked));
+        }
+
+        Ok(())
+    }
+
+    fn unlocks_at(&self, farm: &Farm, current_slot: Slot) -> u64 {
+        self.locked_at
+            .slot
+            .saturating_add(farm.active_lockup_slots(current_slot))
+    }
+
+    /// If the farmer has no more staked/vested tokens and all harvests have
+    /// been claimed, then the account is empty and can be closed without
+    /// losing funds.
+    pub fn check_is_empty(&self) -> Result<()> {
+        if self.total_deposited()?.amount != 0 {
+            return Err(error!(err::acc("Unstake all farmer's tokens")));
+        }
+
+        if self
+            .harvests
+            .iter()
+            .any(|h| h.tokens.amount != 0 || h.escrowed.amount != 0)
+        {
+            return Err(error!(err::acc("Claim all farmer's harvest")));
+        }
+
+        Ok(())
+    }
+
+    /// Adds the currently deposited tokens over the slots since the last call
+    /// to [`Farmer::cumulative_stake_slots`]. Must be called before the
+    /// deposited amount changes.
+    pub fn accumulate_stake(&mut self, current_slot: Slot) -> Result<()> {
+        if self.stake_tracked_since.slot == 0 {
+            // there's no history to accumulate
+            self.stake_tracked_since = current_slot;
+        } else {
+            self.cumulative_stake_slots =
+                self.cumulative_stake_slots_until(current_slot)?;
+        }
+        self.stake_accumulated_until = current_slot;
+
+        Ok(())
+    }
+
+    /// Time-weighted average of the deposited tokens since
+    /// [`Farmer::stake_tracked_since`] until given slot. If no slot has
+    /// elapsed yet, the average is the currently deposited amount.
+    pub fn average_stake(&self, until: Slot) -> Result<AverageStake> {
+        let deposited = self.total_deposited()?;
+        if self.stake_tracked_since.slot == 0 {
+            return Ok(AverageStake {
+                average_stake: deposited,
+                since: until,
+                until,
+                cumulative_stake_slots: 0,
+            });
+        }
+
+        let cumulative_stake_slots =
+            self.cumulative_stake_slots_until(until)?;
+        let elapsed = until
+            .slot
+            .checked_sub(self.stake_tracked_since.slot)
+            .ok_or(FarmingError::MathOverflow)?;
+        let average_stake = if elapsed == 0 {
+            deposited
+        } else {
+            // the average is never larger than the largest deposited amount,
+            // which fits into u64
+            let average = cumulative_stake_slots / elapsed as u128;
+            TokenAmount::new(
+                average
+                    .try_into()
+                    .map_err(|_| FarmingError::InvariantViolation)?,
+            )
+        };
+
+        Ok(AverageStake {
+            average_stake,
+            since: self.stake_tracked_since,
+            until,
+            cumulative_stake_slots,
+        })
+    }
+
+    fn cumulative_stake_slots_until(&self, until: Slot) -> Result<u128> {
+        let slots = until
+            .slot
+            .checked_sub(self.stake_accumulated_until.slot)
+            .ok_or(FarmingError::MathOverflow)?;
+        let stake_slots =
+            self.total_deposited()?.amount as u128 * slots as u128;
+
+        self.cumulative_stake_slots
+            .checked_add(stake_slots)
+            .ok_or_else(|| error!(FarmingError::MathOverflow))
     }
 
     /// Moves funds from vested to staked if possible and then calculates
@@ -146,6 +453,9 @@ impl Farmer {
         farm: &Farm,
         current_slot: Slot,
     ) -> Result<()> {
+        // donations are shared by the staked tokens before they change
+        self.add_donations(farm)?;
+
         if self.vested.amount != 0 {
             // if there was a new snapshot, vested funds are ready to be
             // moved to staked funds
@@ -159,11 +469,14 @@ impl Farmer {
                 // calculate harvest for which the vested tokens should not be
                 // counted yet, ie. all harvest until first snapshot after
                 // vested at slot
-                let farmer_harvests = self.eligible_harvest_until(
-                    farm,
-                    Slot::new(snapshot.started_at.slot - 1),
-                )?;
-                self.set_harvests(farmer_harvests)?;
+                let until = Slot::new(snapshot.started_at.slot - 1);
+                // if the farmer staked on the slot just before the snapshot,
+                // the harvest has already been calculated until then
+                if self.calculate_next_harvest_from <= until {
+                    let farmer_harvests =
+                        self.eligible_harvest_until(farm, until)?;
+                    self.set_harvests(farmer_harvests)?;
+                }
                 // and take a note that we calculated harvest until this
                 // point
                 self.calculate_next_harvest_from = snapshot.started_at;
@@ -184,6 +497,51 @@ impl Farmer {
         Ok(())
     }
 
+    /// Adds the farmer's share of the tokens donated since the last call to
+    /// their eligible harvest, see [`Harvest::donations_per_staked_token`].
+    /// Both staked and vested tokens are eligible, because the donations are
+    /// split by the stake vault amount, which includes both.
+    ///
+    /// Must be called before the deposited amount changes.
+    pub fn add_donations(&mut self, farm: &Farm) -> Result<()> {
+        let farm_harvests: BTreeMap<_, _> =
+            farm.harvests.iter().map(|h| (h.mint, h)).collect();
+        let mut farmer_harvests: BTreeMap<_, _> =
+            self.harvests.iter().map(|h| (h.mint, h.tokens)).collect();
+
+        // newly added harvest mints must be accounted for too
+        sync_harvest_mints(&farm_harvests, &mut farmer_harvests);
+        self.set_harvests(farmer_harvests)?;
+
+        let deposited = Decimal::from(self.total_deposited()?.amount);
+        for farmer_harvest in self
+            .harvests
+            .iter_mut()
+            .filter(|h| h.mint != Pubkey::default())
+        {
+            let donations = farm_harvests
+                .get(&farmer_harvest.mint)
+                .ok_or(FarmingError::InvariantViolation)?
+                .donations_per_staked_token;
+
+            let paid = Decimal::from(farmer_harvest.donations_paid);
+            // the harvest mint might've been removed and added back since the
+            // farmer was last updated, which resets the farm's counter
+            if Decimal::from(donations) > paid {
+                let share: u64 = Decimal::from(donations)
+                    .try_sub(paid)?
+                    .try_mul(deposited)?
+                    .try_floor()?;
+                // no vault can hold more than u64::MAX tokens
+                farmer_harvest.tokens.amount =
+                    farmer_harvest.tokens.amount.saturating_add(share);
+            }
+            farmer_harvest.donations_paid = donations;
+        }
+
+        Ok(())
+    }
+
     /// Withdraw all tokens of given stake mint and return how much that was.
     pub fn claim_harvest(&mut self, stake_mint: Pubkey) -> Result<TokenAmount> {
         let harvest =
@@ -195,10 +553,214 @@ impl Farmer {
         let stake = harvest.tokens;
 
         harvest.tokens.amount = 0;
+        harvest.total_claimed.amount =
+            harvest.total_claimed.amount.saturating_add(stake.amount);
 
         Ok(stake)
     }
 
+    /// Claims all eligible harvest of given stake mint to be compounded and
+    /// returns how many tokens can be staked right away. The claim is subject
+    /// to the farm's cooldown and vesting like any other, therefore it fails
+    /// within [`Farm::claim_cooldown_slots`] of the last claim and with
+    /// [`Farm::claim_vesting_slots`] only the part of the escrow which has
+    /// vested so far is compounded, see [`Farmer::check_and_record_claim`] and
+    /// [`Farmer::claim_eligible_harvest`].
+    pub fn claim_harvest_to_compound(
+        &mut self,
+        farm: &Farm,
+        stake_mint: Pubkey,
+        current_slot: Slot,
+    ) -> Result<TokenAmount> {
+        if self.get_harvest_mut(stake_mint).is_none() {
+            return Err(error!(
+                FarmingError::CannotCompoundIfStakeMintIsNotHarvest
+            ));
+        }
+
+        self.check_and_record_claim(current_slot, farm.claim_cooldown_slots)?;
+        self.claim_eligible_harvest(
+            stake_mint,
+            current_slot,
+            farm.claim_vesting_slots,
+        )
+    }
+
+    /// Fails if the compounded tokens are less than [`Farmer::min_compound`]
+    /// or the current slot is after [`Farmer::compound_deadline`].
+    pub fn check_compound_bounds(
+        &self,
+        current_slot: Slot,
+        compound_tokens: TokenAmount,
+    ) -> Result<()> {
+        if self.compound_deadline.slot != 0
+            && current_slot > self.compound_deadline
+        {
+            msg!(
+                "Current slot is {}, but the deadline was slot {}",
+                current_slot.slot,
+                self.compound_deadline.slot
+            );
+            return Err(error!(FarmingError::DeadlineExceeded));
+        }
+
+        if compound_tokens < self.min_compound {
+            msg!(
+                "Would compound {}, but requested minimum of {}",
+                compound_tokens.amount,
+                self.min_compound.amount
+            );
+            return Err(error!(FarmingError::CompoundedLessThanMinimum));
+        }
+
+        Ok(())
+    }
+
+    /// Increments the counter of claimed tokens of given harvest mint, see
+    /// [`AvailableHarvest::total_claimed`]. No-op if the farmer has no harvest
+    /// of such mint.
+    pub fn add_to_total_claimed(&mut self, mint: Pubkey, tokens: TokenAmount) {
+        if let Some(harvest) = self.harvests.iter_mut().find(|h| h.mint == mint)
+        {
+            harvest.total_claimed.amount =
+                harvest.total_claimed.amount.saturating_add(tokens.amount);
+        }
+    }
+
+    /// Releases the part of the escrow of given mint which vested linearly
+    /// since the last release and returns it. No-op if the farmer has no
+    /// harvest of such mint.
+    pub fn release_escrow(
+        &mut self,
+        mint: Pubkey,
+        current_slot: Slot,
+    ) -> Result<TokenAmount> {
+        let harvest = match self.harvests.iter_mut().find(|h| h.mint == mint) {
+            Some(harvest) if harvest.escrowed.amount != 0 => harvest,
+            _ => return Ok(TokenAmount::new(0)),
+        };
+
+        let released = if current_slot >= harvest.escrow_ends_at {
+            harvest.escrowed.amount
+        } else if current_slot <= harvest.escrow_released_until {
+            0
+        } else {
+            // escrowed * elapsed / remaining, where elapsed < remaining
+            let elapsed =
+                current_slot.slot - harvest.escrow_released_until.slot;
+            let remaining = harvest.escrow_ends_at.slot
+                - harvest.escrow_released_until.slot;
+            (harvest.escrowed.amount as u128 * elapsed as u128
+                / remaining as u128)
+                .try_into()
+                .map_err(|_| FarmingError::MathOverflow)?
+        };
+
+        harvest.escrowed.amount -= released;
+        harvest.escrow_released_until =
+            current_slot.max(harvest.escrow_released_until);
+
+        Ok(TokenAmount::new(released))
+    }
+
+    /// Puts claimed tokens of given mint into the escrow, which releases them
+    /// linearly over the next `vesting_slots` slots.
+    ///
+    /// The part of the escrow which has vested until `current_slot` is
+    /// released first and returned, because the release of the new tokens
+    /// starts at `current_slot`. Only the tokens which are still locked are
+    /// merged with the new ones.
+    pub fn add_to_escrow(
+        &mut self,
+        mint: Pubkey,
+        current_slot: Slot,
+        tokens: TokenAmount,
+        vesting_slots: u64,
+    ) -> Result<TokenAmount> {
+        let released = self.release_escrow(mint, current_slot)?;
+        if tokens.amount == 0 {
+            return Ok(released);
+        }
+
+        let harvest = self
+            .harvests
+            .iter_mut()
+            .find(|h| h.mint == mint)
+            .ok_or(FarmingError::UnknownHarvestMintPubKey)?;
+
+        harvest.escrowed.amount = harvest
+            .escrowed
+            .amount
+            .checked_add(tokens.amount)
+            .ok_or(FarmingError::MathOverflow)?;
+        harvest.escrow_released_until = current_slot;
+        harvest.escrow_ends_at = harvest
+            .escrow_ends_at
+            .max(Slot::new(current_slot.slot.saturating_add(vesting_slots)));
+
+        Ok(released)
+    }
+
+    /// Fails if less than `claim_cooldown_slots` passed since the farmer last
+    /// claimed, otherwise records `current_slot` as the last claim. Zero
+    /// cooldown disables the check.
+    ///
+    /// Claims within the same slot count as one, so that several harvest mints
+    /// can be claimed in one transaction.
+    pub fn check_and_record_claim(
+        &mut self,
+        current_slot: Slot,
+        claim_cooldown_slots: u64,
+    ) -> Result<()> {
+        let next_claim_at = self
+            .last_claimed_at
+            .slot
+            .saturating_add(claim_cooldown_slots);
+        if claim_cooldown_slots != 0
+            && self.last_claimed_at.slot != 0
+            && self.last_claimed_at != current_slot
+            && current_slot.slot < next_claim_at
+        {
+            msg!("Farmer can claim again at slot {}", next_claim_at);
+            return Err(error!(FarmingError::ClaimCooldownNotElapsed));
+        }
+
+        self.last_claimed_at = current_slot;
+
+        Ok(())
+    }
+
+    /// Claims all eligible harvest of given mint and returns how many tokens
+    /// should be transferred to the farmer's wallet right away.
+    ///
+    /// If `claim_vesting_slots` is non-zero, the claimed harvest is put into
+    /// the escrow instead and only the part of the escrow which has vested so
+    /// far is returned. No-op if the farmer has no harvest of such mint.
+    pub fn claim_eligible_harvest(
+        &mut self,
+        mint: Pubkey,
+        current_slot: Slot,
+        claim_vesting_slots: u64,
+    ) -> Result<TokenAmount> {
+        let claimed = match self.get_harvest_mut(mint) {
+            Some(tokens) => mem::take(tokens),
+            None => return Ok(TokenAmount::new(0)),
+        };
+        self.add_to_total_claimed(mint, claimed);
+
+        if claim_vesting_slots == 0 {
+            // the escrow is released even if vesting has been disabled since
+            let released = self.release_escrow(mint, current_slot)?;
+            let transfer = released
+                .amount
+                .checked_add(claimed.amount)
+                .ok_or(FarmingError::MathOverflow)?;
+            Ok(TokenAmount::new(transfer))
+        } else {
+            self.add_to_escrow(mint, current_slot, claimed, claim_vesting_slots)
+        }
+    }
+
     /// Calculates how many tokens for each harvest mint is the farmer eligible
     /// for by iterating over the snapshot history (if the farmer last harvest
     /// was before last snapshot) and then calculating it in the open window
@@ -267,18 +829,25 @@ impl Farmer {
     ///
     /// If the map contains less entries, we pad the rest with
     /// `(Pubkey::default(), TokenAmount::new(0))`.
+    ///
+    /// The [`AvailableHarvest::total_claimed`] counters and the escrows of
+    /// mints which remain in the array are preserved.
     pub fn set_harvests(
         &mut self,
         harvests: impl IntoIterator<Item = (Pubkey, TokenAmount)>,
     ) -> Result<()> {
+        let current: BTreeMap<_, _> =
+            self.harvests.iter().map(|h| (h.mint, *h)).collect();
+
         self.harvests = harvests
             .into_iter()
-            .map(|(mint, tokens)| AvailableHarvest { mint, tokens })
+            .map(|(mint, tokens)| AvailableHarvest {
+                mint,
+                tokens,
+                ..current.get(&mint).copied().unwrap_or_default()
+            })
             // pad with uninitialized harvests
-            .chain(iter::repeat_with(|| AvailableHarvest {
-                mint: Pubkey::default(),
-                tokens: TokenAmount::default(),
-            }))
+            .chain(iter::repeat_with(AvailableHarvest::default))
             .take(consts::MAX_HARVEST_MINTS)
             .collect::<Vec<_>>()
             .try_into()
@@ -301,7 +870,16 @@ impl Farmer {
         let vested = 8;
         let vested_at = 8;
         let harvest_calculated_until = 8;
-        let harvests = consts::MAX_HARVEST_MINTS * (PUBKEY + 8);
+        let harvests =
+            consts::MAX_HARVEST_MINTS * (PUBKEY + 8 + 8 + 8 + 8 + 8 + 24);
+        let cumulative_stake_slots = 16;
+        let stake_tracked_since = 8;
+        let stake_accumulated_until = 8;
+        let last_claimed_at = 8;
+        let locked = 8;
+        let locked_at = 8;
+        let min_compound = 8;
+        let compound_deadline = 8;
 
         DISCRIMINANT
             + authority
@@ -311,6 +889,14 @@ impl Farmer {
             + staked
             + vested
             + harvests
+            + cumulative_stake_slots
+            + stake_tracked_since
+            + stake_accumulated_until
+            + last_claimed_at
+            + locked
+            + locked_at
+            + min_compound
+            + compound_deadline
     }
 }
 
@@ -385,6 +971,7 @@ fn eligible_harvest_until<'a>(
         // oldest_slot_to_skip cannot be 0
         let ends_at = Slot::new(oldest_slot_to_skip.slot - 1);
 
+        // the snapshot holds the total stake of the farm at the time, hence
         // sum over all farmers' share is 1
         let farmer_share = Decimal::from(farmer_staked.amount)
             .try_div(Decimal::from(snapshot.staked.amount))?;
@@ -420,20 +1007,20 @@ fn eligible_harvest_until<'a>(
                 }
 
                 if tps.amount != 0 {
-                    let slots = range
-                        .end()
-                        .slot
-                        .min(calculate_until_slot)
-                        .checked_sub(range.start().slot.max(starts_at.slot))
-                        // This should never happen, since we skip the
-                        // this function call whenever
-                        // calculate_next_harvest_from >= current_slot
-                        .ok_or(FarmingError::MathOverflow)?
-                        + 1; // +1 bcs inclusiveness
+                    let from = range.start().slot.max(starts_at.slot);
+                    let to = range.end().slot.min(calculate_until_slot);
+                    // ranges are cut short when the harvest is paused,
+                    // therefore the emission follows the period itself
+                    let period = farm_harvest
+                        .period_at(Slot::new(from))
+                        .ok_or(FarmingError::InvariantViolation)?;
+                    // `to` is never before `from`, since we skip this
+                    // function call whenever
+                    // calculate_next_harvest_from >= current_slot
+                    let emitted = period
+                        .tokens_emitted((Slot::new(from), Slot::new(to)))?;
                     eligible_harvest = eligible_harvest.try_add(
-                        Decimal::from(slots)
-                            .try_mul(farmer_share)?
-                            .try_mul(Decimal::from(tps.amount))?,
+                        Decimal::from(emitted.amount).try_mul(farmer_share)?,
                     )?;
                 }
 
@@ -453,13 +1040,16 @@ fn eligible_harvest_until<'a>(
                 }
             }
 
+            // the farmer's share is at most 1, hence the eligible harvest fits
+            // into u64 because the emitted tokens do
+            let eligible_harvest: u64 = eligible_harvest.try_floor()?;
             let farmer_harvest =
                 farmer_harvests.entry(farm_harvest.mint).or_default();
+            // no vault can hold more than u64::MAX tokens, so instead of
+            // failing the whole calculation we clamp to what's still left
+            // until that limit
             *farmer_harvest = TokenAmount {
-                amount: farmer_harvest
-                    .amount
-                    .checked_add(eligible_harvest.try_floor()?)
-                    .ok_or(FarmingError::MathOverflow)?,
+                amount: farmer_harvest.amount.saturating_add(eligible_harvest),
             };
         }
 
@@ -492,6 +1082,7 @@ fn sync_harvest_mints(
 mod tests {
     use super::*;
     use crate::prelude::utils::*;
+    use anchor_lang::Discriminator;
     use serial_test::serial;
 
     impl Farmer {
@@ -505,30 +1096,572 @@ mod tests {
 
     #[test]
     fn it_has_stable_size() {
-        assert_eq!(Farmer::space(), 504);
+        assert_eq!(Farmer::space(), 1_136);
+    }
+
+    #[test]
+    fn it_has_stable_legacy_size() {
+        assert_eq!(LegacyFarmer::space(), 504);
+        assert_eq!(
+            LegacyFarmer::space(),
+            8 + LegacyFarmer {
+                authority: Pubkey::default(),
+                farm: Pubkey::default(),
+                staked: TokenAmount::default(),
+                vested: TokenAmount::default(),
+                vested_at: Slot::default(),
+                calculate_next_harvest_from: Slot::default(),
+                harvests: Default::default(),
+            }
+            .try_to_vec()
+            .unwrap()
+            .len()
+        );
+    }
+
+    #[test]
+    fn it_migrates_legacy_farmer() -> Result<()> {
+        let (harvest_mint, mut farm) = dummy_farm_1()?;
+        farm.harvests[0].donations_per_staked_token =
+            Decimal::from(1u64).into();
+
+        let mut harvests = [LegacyAvailableHarvest::default(); 10];
+        harvests[0] = LegacyAvailableHarvest {
+            mint: harvest_mint,
+            tokens: TokenAmount::new(50),
+        };
+        let legacy = LegacyFarmer {
+            authority: Pubkey::new_unique(),
+            farm: Pubkey::new_unique(),
+            staked: TokenAmount::new(100),
+            vested: TokenAmount::new(10),
+            vested_at: Slot::new(5),
+            calculate_next_harvest_from: Slot::new(6),
+            harvests,
+        };
+
+        let mut data = Farmer::discriminator().to_vec();
+        data.extend(legacy.try_to_vec()?);
+        assert_eq!(data.len(), LegacyFarmer::space());
+        // the legacy account cannot be read as the current layout
+        assert!(Farmer::try_deserialize(&mut data.as_slice()).is_err());
+
+        let mut farmer =
+            LegacyFarmer::deserialize(&mut &data[8..])?.migrate(&farm);
+        assert_eq!(farmer.authority, legacy.authority);
+        assert_eq!(farmer.farm, legacy.farm);
+        assert_eq!(farmer.staked, legacy.staked);
+        assert_eq!(farmer.vested, legacy.vested);
+        assert_eq!(farmer.vested_at, legacy.vested_at);
+        assert_eq!(
+            farmer.calculate_next_harvest_from,
+            legacy.calculate_next_harvest_from
+        );
+        assert_eq!(farmer.get_harvest(harvest_mint), TokenAmount::new(50));
+        assert_eq!(farmer.harvests[0].total_claimed, TokenAmount::new(0));
+        assert_eq!(farmer.harvests[1], AvailableHarvest::default());
+        assert_eq!(farmer.cumulative_stake_slots, 0);
+        assert_eq!(farmer.last_claimed_at, Slot::new(0));
+
+        let mut migrated = Vec::new();
+        farmer.try_serialize(&mut migrated)?;
+        assert_eq!(migrated.len(), Farmer::space());
+        assert_eq!(Farmer::try_deserialize(&mut migrated.as_slice())?, farmer);
+
+        // donations made before the migration are not paid out again
+        farmer.add_donations(&farm)?;
+        assert_eq!(farmer.get_harvest(harvest_mint), TokenAmount::new(50));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_rejects_unstake_before_lockup_elapses() -> Result<()> {
+        let farm = Farm {
+            lockup_slots: 10,
+            ..Default::default()
+        };
+        let mut farmer = Farmer::default();
+        farmer.add_to_vested(Slot::new(100), TokenAmount::new(50))?;
+        farmer.lock_stake(&farm, Slot::new(100), TokenAmount::new(50))?;
+
+        assert!(farmer.check_stake_unlocked(&farm, Slot::new(109)).is_err());
+        assert_eq!(
+            farmer.unlocked_stake(&farm, Slot::new(109))?,
+            TokenAmount::new(0)
+        );
+        farmer.check_stake_unlocked(&farm, Slot::new(110))?;
+        assert_eq!(
+            farmer.unlocked_stake(&farm, Slot::new(110))?,
+            TokenAmount::new(50)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_doesnt_lock_compounded_tokens() -> Result<()> {
+        let farm = Farm {
+            lockup_slots: 10,
+            ..Default::default()
+        };
+        let mut farmer = Farmer::default();
+        farmer.add_to_vested(Slot::new(100), TokenAmount::new(50))?;
+        farmer.lock_stake(&farm, Slot::new(100), TokenAmount::new(50))?;
+
+        // compounding only adds to vested
+        farmer.add_to_vested(Slot::new(105), TokenAmount::new(20))?;
+        farmer.add_to_vested(Slot::new(109), TokenAmount::new(0))?;
+
+        assert_eq!(
+            farmer.unlocked_stake(&farm, Slot::new(109))?,
+            TokenAmount::new(20)
+        );
+        farmer.check_stake_unlocked(&farm, Slot::new(110))?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_doesnt_relock_unlocked_stake_on_deposit() -> Result<()> {
+        let farm = Farm {
+            lockup_slots: 10,
+            ..Default::default()
+        };
+        let mut farmer = Farmer::default();
+        farmer.add_to_vested(Slot::new(100), TokenAmount::new(50))?;
+        farmer.lock_stake(&farm, Slot::new(100), TokenAmount::new(50))?;
+
+        farmer.add_to_vested(Slot::new(120), TokenAmount::new(30))?;
+        farmer.lock_stake(&farm, Slot::new(120), TokenAmount::new(30))?;
+
+        assert_eq!(farmer.locked, TokenAmount::new(30));
+        assert_eq!(farmer.locked_at, Slot::new(120));
+        assert_eq!(
+            farmer.unlocked_stake(&farm, Slot::new(125))?,
+            TokenAmount::new(50)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_prolongs_lock_in_proportion_to_deposit() -> Result<()> {
+        let farm = Farm {
+            lockup_slots: 100,
+            ..Default::default()
+        };
+        let mut farmer = Farmer::default();
+        farmer.add_to_vested(Slot::new(100), TokenAmount::new(990))?;
+        farmer.lock_stake(&farm, Slot::new(100), TokenAmount::new(990))?;
+
+        // a dust deposit barely moves the lock
+        farmer.add_to_vested(Slot::new(190), TokenAmount::new(10))?;
+        farmer.lock_stake(&farm, Slot::new(190), TokenAmount::new(10))?;
+        assert_eq!(farmer.locked, TokenAmount::new(1_000));
+        assert_eq!(farmer.locked_at, Slot::new(100));
+
+        farmer.add_to_vested(Slot::new(190), TokenAmount::new(1_000))?;
+        farmer.lock_stake(&farm, Slot::new(190), TokenAmount::new(1_000))?;
+        assert_eq!(farmer.locked, TokenAmount::new(2_000));
+        assert_eq!(farmer.locked_at, Slot::new(145));
+
+        // unstaking what's unlocked keeps the lock
+        assert_eq!(
+            farmer.unlocked_stake(&farm, Slot::new(200))?,
+            TokenAmount::new(0)
+        );
+        assert_eq!(
+            farmer.unlocked_stake(&farm, Slot::new(245))?,
+            TokenAmount::new(2_000)
+        );
+        farmer.unstake(TokenAmount::new(1_500))?;
+        assert_eq!(farmer.locked, TokenAmount::new(500));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_is_empty_only_without_deposit_and_harvest() {
+        let mint = Pubkey::new_unique();
+        let mut farmer = Farmer {
+            staked: TokenAmount::new(10),
+            harvests: generate_farmer_harvests(&mut vec![(mint, 100)])
+                .try_into()
+                .unwrap(),
+            ..Default::default()
+        };
+        assert!(farmer.check_is_empty().is_err());
+
+        farmer.unstake(TokenAmount::new(10)).unwrap();
+        assert!(farmer.check_is_empty().is_err());
+
+        farmer.claim_harvest(mint).unwrap();
+        farmer.check_is_empty().unwrap();
+    }
+
+    #[test]
+    fn it_rejects_claim_before_cooldown_elapses() {
+        let mut farmer = Farmer::default();
+
+        // never claimed before
+        farmer.check_and_record_claim(Slot::new(100), 10).unwrap();
+        assert_eq!(farmer.last_claimed_at, Slot::new(100));
+
+        // several mints claimed in the same slot
+        farmer.check_and_record_claim(Slot::new(100), 10).unwrap();
+
+        assert!(farmer.check_and_record_claim(Slot::new(101), 10).is_err());
+        assert!(farmer.check_and_record_claim(Slot::new(109), 10).is_err());
+        assert_eq!(farmer.last_claimed_at, Slot::new(100));
+
+        farmer.check_and_record_claim(Slot::new(110), 10).unwrap();
+        assert_eq!(farmer.last_claimed_at, Slot::new(110));
+    }
+
+    #[test]
+    fn it_allows_any_claim_without_cooldown() {
+        let mut farmer = Farmer::default();
+
+        farmer.check_and_record_claim(Slot::new(100), 0).unwrap();
+        farmer.check_and_record_claim(Slot::new(101), 0).unwrap();
+        assert_eq!(farmer.last_claimed_at, Slot::new(101));
+    }
+
+    #[test]
+    fn it_claims_harvest() {
+        let mint = Pubkey::new_unique();
+
+        let mut farmer = Farmer {
+            staked: TokenAmount { amount: 10 },
+            vested: TokenAmount { amount: 10 },
+            vested_at: Slot { slot: 0 },
+            harvests: generate_farmer_harvests(&mut vec![(mint, 100)])
+                .try_into()
+                .unwrap(),
+            ..Default::default()
+        };
+
+        assert_eq!(farmer.harvests[0].mint, mint);
+        assert_eq!(farmer.harvests[0].tokens, TokenAmount { amount: 100 });
+
+        let amount_claimed = farmer.claim_harvest(mint).unwrap();
+
+        assert_eq!(farmer.harvests[0].tokens, TokenAmount { amount: 0 });
+        assert_eq!(amount_claimed, TokenAmount { amount: 100 });
+    }
+
+    #[test]
+    fn it_accumulates_total_claimed_over_multiple_claims() {
+        let mint = Pubkey::new_unique();
+        let other_mint = Pubkey::new_unique();
+
+        let mut farmer = Farmer {
+            harvests: generate_farmer_harvests(&mut vec![
+                (mint, 100),
+                (other_mint, 50),
+            ])
+            .try_into()
+            .unwrap(),
+            ..Default::default()
+        };
+
+        assert_eq!(farmer.claim_harvest(mint).unwrap(), TokenAmount::new(100));
+
+        // harvest recalculation must preserve the counter
+        farmer
+            .set_harvests(vec![
+                (mint, TokenAmount::new(30)),
+                (other_mint, TokenAmount::new(50)),
+            ])
+            .unwrap();
+        assert_eq!(farmer.claim_harvest(mint).unwrap(), TokenAmount::new(30));
+
+        // nothing to claim doesn't change the counter
+        assert_eq!(farmer.claim_harvest(mint).unwrap(), TokenAmount::new(0));
+
+        farmer.add_to_total_claimed(mint, TokenAmount::new(5));
+
+        let harvest = farmer.harvests.iter().find(|h| h.mint == mint).unwrap();
+        assert_eq!(harvest.tokens, TokenAmount::new(0));
+        assert_eq!(harvest.total_claimed, TokenAmount::new(135));
+
+        // other mints are not affected
+        let other_harvest = farmer
+            .harvests
+            .iter()
+            .find(|h| h.mint == other_mint)
+            .unwrap();
+        assert_eq!(other_harvest.total_claimed, TokenAmount::new(0));
+    }
+
+    #[test]
+    fn it_saturates_total_claimed() {
+        let mint = Pubkey::new_unique();
+
+        let mut farmer = Farmer {
+            harvests: generate_farmer_harvests(&mut vec![(mint, 100)])
+                .try_into()
+                .unwrap(),
+            ..Default::default()
+        };
+
+        farmer.add_to_total_claimed(mint, TokenAmount::new(u64::MAX));
+        assert_eq!(farmer.claim_harvest(mint).unwrap(), TokenAmount::new(100));
+
+        assert_eq!(
+            farmer.harvests[0].total_claimed,
+            TokenAmount::new(u64::MAX)
+        );
+    }
+
+    #[test]
+    fn it_releases_escrow_linearly() -> Result<()> {
+        let mint = Pubkey::new_unique();
+
+        let mut farmer = Farmer {
+            harvests: generate_farmer_harvests(&mut vec![(mint, 0)])
+                .try_into()
+                .unwrap(),
+            ..Default::default()
+        };
+
+        farmer.add_to_escrow(
+            mint,
+            Slot::new(100),
+            TokenAmount::new(1_000),
+            10,
+        )?;
+        assert_eq!(farmer.harvests[0].escrowed, TokenAmount::new(1_000));
+        assert_eq!(farmer.harvests[0].escrow_ends_at, Slot::new(110));
+
+        // nothing has vested in the same slot
+        assert_eq!(
+            farmer.release_escrow(mint, Slot::new(100))?,
+            TokenAmount::new(0)
+        );
+        // mid-vest
+        assert_eq!(
+            farmer.release_escrow(mint, Slot::new(103))?,
+            TokenAmount::new(300)
+        );
+        // 700 * 4 / 7
+        assert_eq!(
+            farmer.release_escrow(mint, Slot::new(107))?,
+            TokenAmount::new(400)
+        );
+        // after full vest
+        assert_eq!(
+            farmer.release_escrow(mint, Slot::new(200))?,
+            TokenAmount::new(300)
+        );
+        assert_eq!(farmer.harvests[0].escrowed, TokenAmount::new(0));
+        assert_eq!(
+            farmer.release_escrow(mint, Slot::new(201))?,
+            TokenAmount::new(0)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_vests_escrow_together_with_newly_added_tokens() -> Result<()> {
+        let mint = Pubkey::new_unique();
+
+        let mut farmer = Farmer {
+            harvests: generate_farmer_harvests(&mut vec![(mint, 0)])
+                .try_into()
+                .unwrap(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            farmer.add_to_escrow(
+                mint,
+                Slot::new(0),
+                TokenAmount::new(100),
+                10
+            )?,
+            TokenAmount::new(0)
+        );
+        // the half which vested until slot 5 is released before merging
+        assert_eq!(
+            farmer.add_to_escrow(
+                mint,
+                Slot::new(5),
+                TokenAmount::new(100),
+                10
+            )?,
+            TokenAmount::new(50)
+        );
+
+        // 150 remaining tokens vest from slot 5 until slot 15
+        assert_eq!(farmer.harvests[0].escrow_ends_at, Slot::new(15));
+        assert_eq!(
+            farmer.release_escrow(mint, Slot::new(10))?,
+            TokenAmount::new(75)
+        );
+
+        // preserved when harvests are recalculated
+        farmer.set_harvests(vec![(mint, TokenAmount::new(10))])?;
+        assert_eq!(farmer.harvests[0].escrowed, TokenAmount::new(75));
+        assert_eq!(
+            farmer.release_escrow(mint, Slot::new(15))?,
+            TokenAmount::new(75)
+        );
+
+        // unknown mint cannot be escrowed and has nothing to release
+        let unknown_mint = Pubkey::new_unique();
+        assert!(farmer
+            .add_to_escrow(unknown_mint, Slot::new(15), TokenAmount::new(1), 1)
+            .is_err());
+        assert_eq!(
+            farmer.release_escrow(unknown_mint, Slot::new(15))?,
+            TokenAmount::new(0)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_claims_eligible_harvest_with_and_without_vesting() -> Result<()> {
+        let mint = Pubkey::new_unique();
+
+        let mut farmer = Farmer {
+            harvests: generate_farmer_harvests(&mut vec![(mint, 100)])
+                .try_into()
+                .unwrap(),
+            ..Default::default()
+        };
+
+        // with vesting, the claim goes into the escrow
+        assert_eq!(
+            farmer.claim_eligible_harvest(mint, Slot::new(0), 10)?,
+            TokenAmount::new(0)
+        );
+        assert_eq!(farmer.harvests[0].tokens, TokenAmount::new(0));
+        assert_eq!(farmer.harvests[0].escrowed, TokenAmount::new(100));
+        assert_eq!(farmer.harvests[0].total_claimed, TokenAmount::new(100));
+
+        // without vesting, the new claim is transferred together with what
+        // has vested so far
+        farmer.harvests[0].tokens = TokenAmount::new(20);
+        assert_eq!(
+            farmer.claim_eligible_harvest(mint, Slot::new(5), 0)?,
+            TokenAmount::new(70)
+        );
+        assert_eq!(farmer.harvests[0].escrowed, TokenAmount::new(50));
+        assert_eq!(farmer.harvests[0].total_claimed, TokenAmount::new(120));
+
+        // unknown mint has nothing to claim
+        assert_eq!(
+            farmer.claim_eligible_harvest(
+                Pubkey::new_unique(),
+                Slot::new(5),
+                0
+            )?,
+            TokenAmount::new(0)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_compounds_only_vested_harvest() -> Result<()> {
+        let mint = Pubkey::new_unique();
+        let farm = Farm {
+            claim_vesting_slots: 10,
+            ..Default::default()
+        };
+
+        let mut farmer = Farmer {
+            harvests: generate_farmer_harvests(&mut vec![(mint, 100)])
+                .try_into()
+                .unwrap(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            farmer.claim_harvest_to_compound(&farm, mint, Slot::new(0))?,
+            TokenAmount::new(0)
+        );
+        assert_eq!(farmer.harvests[0].escrowed, TokenAmount::new(100));
+
+        assert_eq!(
+            farmer.claim_harvest_to_compound(&farm, mint, Slot::new(5))?,
+            TokenAmount::new(50)
+        );
+        assert_eq!(farmer.harvests[0].escrowed, TokenAmount::new(50));
+
+        assert!(farmer
+            .claim_harvest_to_compound(
+                &farm,
+                Pubkey::new_unique(),
+                Slot::new(5)
+            )
+            .unwrap_err()
+            .to_string()
+            .contains("CannotCompoundIfStakeMintIsNotHarvest"));
+
+        Ok(())
     }
 
     #[test]
-    fn it_claims_harvest() {
+    fn it_rejects_compounding_before_cooldown_elapses() -> Result<()> {
         let mint = Pubkey::new_unique();
+        let farm = Farm {
+            claim_cooldown_slots: 10,
+            ..Default::default()
+        };
 
         let mut farmer = Farmer {
-            staked: TokenAmount { amount: 10 },
-            vested: TokenAmount { amount: 10 },
-            vested_at: Slot { slot: 0 },
             harvests: generate_farmer_harvests(&mut vec![(mint, 100)])
                 .try_into()
                 .unwrap(),
             ..Default::default()
         };
+        farmer.check_and_record_claim(Slot::new(100), 10)?;
 
-        assert_eq!(farmer.harvests[0].mint, mint);
-        assert_eq!(farmer.harvests[0].tokens, TokenAmount { amount: 100 });
+        assert!(farmer
+            .claim_harvest_to_compound(&farm, mint, Slot::new(105))
+            .unwrap_err()
+            .to_string()
+            .contains("ClaimCooldownNotElapsed"));
 
-        let amount_claimed = farmer.claim_harvest(mint).unwrap();
+        assert_eq!(
+            farmer.claim_harvest_to_compound(&farm, mint, Slot::new(110))?,
+            TokenAmount::new(100)
+        );
+        assert_eq!(farmer.last_claimed_at, Slot::new(110));
 
-        assert_eq!(farmer.harvests[0].tokens, TokenAmount { amount: 0 });
-        assert_eq!(amount_claimed, TokenAmount { amount: 100 });
+        Ok(())
+    }
+
+    #[test]
+    fn it_checks_compound_bounds() {
+        let mut farmer = Farmer::default();
+
+        // no bounds set
+        farmer
+            .check_compound_bounds(Slot::new(100), TokenAmount::new(0))
+            .unwrap();
+
+        farmer.min_compound = TokenAmount::new(10);
+        farmer.compound_deadline = Slot::new(100);
+
+        farmer
+            .check_compound_bounds(Slot::new(100), TokenAmount::new(10))
+            .unwrap();
+        assert!(farmer
+            .check_compound_bounds(Slot::new(100), TokenAmount::new(9))
+            .unwrap_err()
+            .to_string()
+            .contains("CompoundedLessThanMinimum"));
+        assert!(farmer
+            .check_compound_bounds(Slot::new(101), TokenAmount::new(10))
+            .unwrap_err()
+            .to_string()
+            .contains("DeadlineExceeded"));
     }
 
     #[test]
@@ -698,6 +1831,44 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn it_calculates_harvest_after_snapshot_is_corrected() -> Result<()> {
+        let (harvest_mint, mut farm) = dummy_farm_1()?;
+        // the latest snapshot should have recorded 400 tokens
+        let tip = farm.snapshots.ring_buffer_tip as usize;
+        farm.snapshots.ring_buffer[tip].staked = TokenAmount::new(4_000);
+
+        let mut farmer = Farmer {
+            staked: TokenAmount::new(100),
+            calculate_next_harvest_from: Slot::new(15),
+            ..Default::default()
+        };
+        farmer.update_eligible_harvest(&farm, Slot::new(50))?;
+        // 4rd period, 15-30, 2.5% share of 30 tps
+        assert_eq!(farmer.get_harvest(harvest_mint), TokenAmount::new(12));
+
+        farm.correct_latest_snapshot(
+            TokenAmount::new(400),
+            TokenAmount::new(400),
+        )?;
+
+        let mut farmer = Farmer {
+            staked: TokenAmount::new(100),
+            calculate_next_harvest_from: Slot::new(15),
+            ..Default::default()
+        };
+        farmer.update_eligible_harvest(&farm, Slot::new(50))?;
+        assert_eq!(
+            farmer.get_harvest(harvest_mint),
+            TokenAmount::new(
+                // 4rd period, 15-30, 25% share of 30 tps
+                16 * 30 / 4
+            )
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn it_checks_vested_period_and_update_harvest() -> Result<()> {
         let (harvest_mint, farm) = dummy_farm_1()?;
@@ -809,6 +1980,126 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn it_front_loads_emission_over_same_period_as_linear() -> Result<()> {
+        let farm_with_front_load = |percent| -> Result<(Pubkey, Farm)> {
+            let harvest_mint = Pubkey::new_unique();
+            let mut farm = Farm::default();
+            farm.min_snapshot_window_slots = 1;
+            farm.emission_front_load_percent = percent;
+            farm.add_harvest(harvest_mint, Pubkey::new_unique())?;
+            farm.take_snapshot(Slot::new(1), TokenAmount::new(100))?;
+            farm.new_harvest_period(
+                Slot::new(11),
+                harvest_mint,
+                (Slot::new(11), Slot::new(30)),
+                TokenAmount::new(100),
+            )?;
+            Ok((harvest_mint, farm))
+        };
+        let farmer = || Farmer {
+            staked: TokenAmount::new(100),
+            calculate_next_harvest_from: Slot::new(0),
+            ..Default::default()
+        };
+
+        let (linear_mint, linear) = farm_with_front_load(0)?;
+        let (front_loaded_mint, front_loaded) = farm_with_front_load(50)?;
+
+        // first half of the period, 11-20, 100% share of 100 tps
+        let mut linear_farmer = farmer();
+        linear_farmer.update_eligible_harvest(&linear, Slot::new(20))?;
+        assert_eq!(
+            linear_farmer.get_harvest(linear_mint),
+            TokenAmount::new(1_000)
+        );
+        let mut front_loaded_farmer = farmer();
+        front_loaded_farmer
+            .update_eligible_harvest(&front_loaded, Slot::new(20))?;
+        assert_eq!(
+            front_loaded_farmer.get_harvest(front_loaded_mint),
+            // 1_000 * (1 + 0.5 * (19 - 0 - 9) / 19)
+            TokenAmount::new(1_263)
+        );
+
+        // second half of the period, 21-30
+        linear_farmer.update_eligible_harvest(&linear, Slot::new(40))?;
+        assert_eq!(
+            linear_farmer.get_harvest(linear_mint),
+            TokenAmount::new(2_000)
+        );
+        front_loaded_farmer
+            .update_eligible_harvest(&front_loaded, Slot::new(40))?;
+        assert_eq!(
+            front_loaded_farmer.get_harvest(front_loaded_mint),
+            // 1_000 * (1 - 0.5 * (10 + 19 - 19) / 19) rounds down
+            TokenAmount::new(1_263 + 736)
+        );
+
+        // over the whole period both emit the same
+        let mut front_loaded_farmer = farmer();
+        front_loaded_farmer
+            .update_eligible_harvest(&front_loaded, Slot::new(40))?;
+        assert_eq!(
+            front_loaded_farmer.get_harvest(front_loaded_mint),
+            TokenAmount::new(2_000)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_keeps_front_loaded_harvest_when_period_is_split() -> Result<()> {
+        let harvest_mint = Pubkey::new_unique();
+        let mut farm = Farm::default();
+        farm.min_snapshot_window_slots = 1;
+        farm.emission_front_load_percent = 50;
+        farm.add_harvest(harvest_mint, Pubkey::new_unique())?;
+        farm.take_snapshot(Slot::new(1), TokenAmount::new(100))?;
+        farm.new_harvest_period(
+            Slot::new(11),
+            harvest_mint,
+            (Slot::new(11), Slot::new(30)),
+            TokenAmount::new(100),
+        )?;
+        let farmer = || Farmer {
+            staked: TokenAmount::new(100),
+            calculate_next_harvest_from: Slot::new(0),
+            ..Default::default()
+        };
+
+        // 1_000 * (1 + 0.5 * (19 - 0 - 9) / 19)
+        let mut farmer_before_split = farmer();
+        farmer_before_split.update_eligible_harvest(&farm, Slot::new(20))?;
+        assert_eq!(
+            farmer_before_split.get_harvest(harvest_mint),
+            TokenAmount::new(1_263)
+        );
+
+        // the period is split into 11-20 and 26-30
+        farm.pause_harvest(Slot::new(20), harvest_mint)?;
+        farm.resume_harvest(Slot::new(25), harvest_mint)?;
+        // the farm's setting only applies to new periods
+        farm.emission_front_load_percent = 0;
+
+        let mut farmer_after_split = farmer();
+        farmer_after_split.update_eligible_harvest(&farm, Slot::new(20))?;
+        assert_eq!(
+            farmer_after_split.get_harvest(harvest_mint),
+            TokenAmount::new(1_263)
+        );
+
+        // the last 5 slots of the curve
+        // 500 * (1 - 0.5 * (15 + 19 - 19) / 19) rounds down
+        farmer_after_split.update_eligible_harvest(&farm, Slot::new(40))?;
+        assert_eq!(
+            farmer_after_split.get_harvest(harvest_mint),
+            TokenAmount::new(1_263 + 302)
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn it_works_with_three_periods_in_one_snapshot() -> Result<()> {
         let harvest_mint = Pubkey::new_unique();
@@ -858,6 +2149,65 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn it_multiplies_emission_during_event_window() -> Result<()> {
+        fn harvest_at_slot_50(
+            multiplier: Option<(u64, Slot, Slot)>,
+        ) -> Result<TokenAmount> {
+            let harvest_mint = Pubkey::new_unique();
+            let mut farm = Farm::default();
+            farm.min_snapshot_window_slots = 1;
+            farm.add_harvest(harvest_mint, Pubkey::new_unique())?;
+
+            farm.take_snapshot(Slot::new(1), TokenAmount::new(100))?;
+            farm.new_harvest_period(
+                Slot::new(1),
+                harvest_mint,
+                (Slot::new(1), Slot::new(100)),
+                TokenAmount::new(10),
+            )?;
+            if let Some((percent, starts_at, ends_at)) = multiplier {
+                farm.multiply_emission(
+                    Slot::new(1),
+                    harvest_mint,
+                    percent,
+                    (starts_at, ends_at),
+                )?;
+            }
+            farm.take_snapshot(Slot::new(50), TokenAmount::new(100))?;
+
+            let mut farmer = Farmer {
+                staked: TokenAmount::new(50),
+                calculate_next_harvest_from: Slot::new(0),
+                ..Default::default()
+            };
+            farmer
+                .check_vested_period_and_update_harvest(&farm, Slot::new(50))?;
+
+            Ok(farmer.get_harvest(harvest_mint))
+        }
+
+        // no event, 50 slots, 50% share of 10 tps
+        assert_eq!(harvest_at_slot_50(None)?, TokenAmount::new(250));
+        // the event ends before the calculation, 3x in slots 11-20
+        assert_eq!(
+            harvest_at_slot_50(Some((300, Slot::new(11), Slot::new(20))))?,
+            TokenAmount::new(250 + 10 * 5 * 2)
+        );
+        // the event is still running, 2x in slots 41-50
+        assert_eq!(
+            harvest_at_slot_50(Some((200, Slot::new(41), Slot::new(1_000))))?,
+            TokenAmount::new(250 + 10 * 5)
+        );
+        // the event hasn't started yet
+        assert_eq!(
+            harvest_at_slot_50(Some((300, Slot::new(60), Slot::new(70))))?,
+            TokenAmount::new(250)
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn it_is_idempotent_when_updating_harvest() -> Result<()> {
         let (harvest_mint, farm) = dummy_farm_1()?;
@@ -888,6 +2238,73 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn it_calculates_harvest_with_largest_fundable_tps() -> Result<()> {
+        fn harvest_with_tps(tps: u64) -> Result<TokenAmount> {
+            let harvest_mint = Pubkey::new_unique();
+            let mut farm = Farm::default();
+            farm.min_snapshot_window_slots = 1;
+            farm.add_harvest(harvest_mint, Pubkey::new_unique())?;
+            farm.take_snapshot(Slot::new(1), TokenAmount::new(100))?;
+            farm.new_harvest_period(
+                Slot::new(1),
+                harvest_mint,
+                (Slot::new(1), Slot::new(1_000)),
+                TokenAmount::new(tps),
+            )?;
+            farm.take_snapshot(Slot::new(10), TokenAmount::new(100))?;
+
+            let mut farmer = Farmer {
+                staked: TokenAmount::new(100),
+                calculate_next_harvest_from: Slot::new(1),
+                ..Default::default()
+            };
+            farmer
+                .check_vested_period_and_update_harvest(&farm, Slot::new(20))?;
+            farmer
+                .check_vested_period_and_update_harvest(&farm, Slot::new(30))?;
+
+            Ok(farmer.get_harvest(harvest_mint))
+        }
+
+        // the whole period emits just under u64::MAX tokens
+        let tps = u64::MAX / 1_000;
+        assert_eq!(
+            harvest_with_tps(tps)?,
+            TokenAmount::new(harvest_with_tps(1)?.amount * tps)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_errs_if_harvest_period_is_larger_than_any_vault() -> Result<()> {
+        let harvest_mint = Pubkey::new_unique();
+        let mut farm = Farm::default();
+        farm.min_snapshot_window_slots = 1;
+        farm.add_harvest(harvest_mint, Pubkey::new_unique())?;
+        farm.take_snapshot(Slot::new(1), TokenAmount::new(100))?;
+        // such period cannot be funded
+        farm.new_harvest_period(
+            Slot::new(1),
+            harvest_mint,
+            (Slot::new(1), Slot::new(1_000)),
+            TokenAmount::new(u64::MAX / 2),
+        )?;
+        farm.take_snapshot(Slot::new(10), TokenAmount::new(100))?;
+
+        let mut farmer = Farmer {
+            staked: TokenAmount::new(100),
+            calculate_next_harvest_from: Slot::new(1),
+            ..Default::default()
+        };
+        assert!(farmer
+            .check_vested_period_and_update_harvest(&farm, Slot::new(20))
+            .is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn it_works_with_no_harvests() -> Result<()> {
         let mut farm = Farm::default();
@@ -910,6 +2327,126 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn it_splits_donation_by_stake_and_makes_it_eligible_right_away(
+    ) -> Result<()> {
+        let harvest_mint = Pubkey::new_unique();
+        let mut farm = Farm::default();
+        farm.min_snapshot_window_slots = 1;
+        farm.add_harvest(harvest_mint, Pubkey::new_unique())?;
+        farm.take_snapshot(Slot::new(1), TokenAmount::new(100))?;
+        farm.take_snapshot(Slot::new(10), TokenAmount::new(100))?;
+
+        let mut small_farmer = Farmer {
+            staked: TokenAmount::new(25),
+            calculate_next_harvest_from: Slot::new(10),
+            ..Default::default()
+        };
+        let mut big_farmer = Farmer {
+            staked: TokenAmount::new(75),
+            calculate_next_harvest_from: Slot::new(10),
+            ..Default::default()
+        };
+        small_farmer
+            .check_vested_period_and_update_harvest(&farm, Slot::new(11))?;
+        big_farmer
+            .check_vested_period_and_update_harvest(&farm, Slot::new(11))?;
+
+        farm.donate(
+            harvest_mint,
+            TokenAmount::new(1_000),
+            TokenAmount::new(100),
+        )?;
+
+        // only tokens deposited after the donation was made, not eligible
+        let mut late_farmer = Farmer {
+            calculate_next_harvest_from: Slot::new(10),
+            ..Default::default()
+        };
+        late_farmer
+            .check_vested_period_and_update_harvest(&farm, Slot::new(12))?;
+        late_farmer.add_to_vested(Slot::new(12), TokenAmount::new(50))?;
+
+        // no new snapshot is necessary
+        small_farmer
+            .check_vested_period_and_update_harvest(&farm, Slot::new(12))?;
+        big_farmer
+            .check_vested_period_and_update_harvest(&farm, Slot::new(12))?;
+        late_farmer
+            .check_vested_period_and_update_harvest(&farm, Slot::new(12))?;
+        assert_eq!(
+            small_farmer.get_harvest(harvest_mint),
+            TokenAmount::new(250)
+        );
+        assert_eq!(big_farmer.get_harvest(harvest_mint), TokenAmount::new(750));
+        assert_eq!(late_farmer.get_harvest(harvest_mint), TokenAmount::new(0));
+
+        // the same donation is not added twice
+        small_farmer
+            .check_vested_period_and_update_harvest(&farm, Slot::new(13))?;
+        assert_eq!(
+            small_farmer.get_harvest(harvest_mint),
+            TokenAmount::new(250)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_allocates_whole_donation_to_staked_and_vested_tokens() -> Result<()> {
+        let harvest_mint = Pubkey::new_unique();
+        let mut farm = Farm::default();
+        farm.min_snapshot_window_slots = 1;
+        farm.add_harvest(harvest_mint, Pubkey::new_unique())?;
+        farm.take_snapshot(Slot::new(1), TokenAmount::new(60))?;
+
+        let mut staked_farmer = Farmer {
+            staked: TokenAmount::new(60),
+            calculate_next_harvest_from: Slot::new(1),
+            ..Default::default()
+        };
+        // deposited after the latest snapshot, therefore still vested
+        let mut vested_farmer = Farmer {
+            calculate_next_harvest_from: Slot::new(1),
+            ..Default::default()
+        };
+        vested_farmer
+            .check_vested_period_and_update_harvest(&farm, Slot::new(2))?;
+        vested_farmer.add_to_vested(Slot::new(2), TokenAmount::new(40))?;
+
+        // the stake vault holds the deposits of both farmers
+        farm.donate(
+            harvest_mint,
+            TokenAmount::new(1_000),
+            TokenAmount::new(100),
+        )?;
+        farm.take_snapshot(Slot::new(5), TokenAmount::new(100))?;
+
+        staked_farmer
+            .check_vested_period_and_update_harvest(&farm, Slot::new(6))?;
+        vested_farmer
+            .check_vested_period_and_update_harvest(&farm, Slot::new(6))?;
+        assert_eq!(vested_farmer.staked, TokenAmount::new(40));
+        assert_eq!(
+            staked_farmer.get_harvest(harvest_mint),
+            TokenAmount::new(600)
+        );
+        assert_eq!(
+            vested_farmer.get_harvest(harvest_mint),
+            TokenAmount::new(400)
+        );
+
+        // the vested tokens are now staked, but the donation isn't paid again
+        vested_farmer
+            .check_vested_period_and_update_harvest(&farm, Slot::new(7))?;
+        assert_eq!(
+            vested_farmer.get_harvest(harvest_mint),
+            TokenAmount::new(400)
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn it_skips_update_of_eligible_harvest_if_all_snapshots_empty() -> Result<()>
     {
@@ -1012,6 +2549,65 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn it_averages_stake_which_changed_midway() -> Result<()> {
+        let mut farmer = Farmer::default();
+
+        farmer.add_to_vested(Slot::new(100), TokenAmount::new(10))?;
+        assert_eq!(
+            farmer.average_stake(Slot::new(100))?.average_stake,
+            TokenAmount::new(10)
+        );
+
+        // 10 tokens for 50 slots
+        farmer.add_to_vested(Slot::new(150), TokenAmount::new(20))?;
+        assert_eq!(
+            farmer.average_stake(Slot::new(150))?.average_stake,
+            TokenAmount::new(10)
+        );
+
+        // 30 tokens for 50 slots, then 15 tokens for 100 slots
+        farmer.accumulate_stake(Slot::new(200))?;
+        farmer.unstake(TokenAmount::new(15))?;
+        let average = farmer.average_stake(Slot::new(300))?;
+        assert_eq!(
+            average,
+            AverageStake {
+                // (10 * 50 + 30 * 50 + 15 * 100) / 200
+                average_stake: TokenAmount::new(17),
+                since: Slot::new(100),
+                until: Slot::new(300),
+                cumulative_stake_slots: 3_500,
+            }
+        );
+
+        // the query doesn't change the accumulator
+        assert_eq!(farmer.cumulative_stake_slots, 2_000);
+        assert_eq!(farmer.stake_accumulated_until, Slot::new(200));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_starts_averaging_stake_of_farmer_without_history() -> Result<()> {
+        let mut farmer = Farmer {
+            staked: TokenAmount::new(10),
+            ..Default::default()
+        };
+
+        // the stake before the first change is not known
+        let average = farmer.average_stake(Slot::new(100))?;
+        assert_eq!(average.average_stake, TokenAmount::new(10));
+        assert_eq!(average.cumulative_stake_slots, 0);
+
+        farmer.add_to_vested(Slot::new(100), TokenAmount::new(10))?;
+        let average = farmer.average_stake(Slot::new(110))?;
+        assert_eq!(average.average_stake, TokenAmount::new(20));
+        assert_eq!(average.since, Slot::new(100));
+
+        Ok(())
+    }
+
     #[test]
     fn it_unstakes_when_unstake_max_is_gt_vested() -> Result<()> {
         let mut farmer = Farmer::default();
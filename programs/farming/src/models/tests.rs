@@ -72,3 +72,308 @@ fn it_updates_eligible_harvest_for_multiple_farmers() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn it_splits_harvest_pro_rata_across_stake_changes() -> Result<()> {
+    let mut farm = Farm {
+        min_snapshot_window_slots: 1,
+        ..Default::default()
+    };
+
+    let harvest = Pubkey::new_unique();
+    let tps = 100;
+
+    farm.add_harvest(harvest, Pubkey::new_unique())?;
+    farm.new_harvest_period(
+        Slot::new(0),
+        harvest,
+        (Slot::new(1), Slot::new(u64::MAX)),
+        TokenAmount::new(tps),
+    )?;
+    farm.take_snapshot(Slot::new(1), TokenAmount::new(0))?;
+
+    let mut farmer1 = Farmer {
+        calculate_next_harvest_from: Slot::new(1),
+        ..Default::default()
+    };
+    let mut farmer2 = Farmer {
+        calculate_next_harvest_from: Slot::new(1),
+        ..Default::default()
+    };
+    farmer1.add_to_vested(Slot::new(1), TokenAmount::new(30))?;
+    farmer2.add_to_vested(Slot::new(1), TokenAmount::new(10))?;
+
+    // both farmers earn harvest from slot 4, total stake is 40
+    farm.take_snapshot(Slot::new(4), TokenAmount::new(40))?;
+
+    // farmer2 stakes more tokens on the slot before the next snapshot
+    farmer2.check_vested_period_and_update_harvest(&farm, Slot::new(7))?;
+    farmer2.add_to_vested(Slot::new(7), TokenAmount::new(60))?;
+
+    // the stake vault now holds 100 tokens in total
+    farm.take_snapshot(Slot::new(8), TokenAmount::new(100))?;
+
+    farmer1.check_vested_period_and_update_harvest(&farm, Slot::new(11))?;
+    farmer2.check_vested_period_and_update_harvest(&farm, Slot::new(11))?;
+
+    assert_eq!(farmer1.staked, TokenAmount::new(30));
+    assert_eq!(farmer2.staked, TokenAmount::new(70));
+
+    let harvest_of = |f: &Farmer| {
+        f.harvests
+            .iter()
+            .find(|h| h.mint == harvest)
+            .unwrap()
+            .tokens
+            .amount
+    };
+
+    // slots 4, 5, 6, 7 with 30/40 share and slots 8, 9, 10, 11 with 30/100
+    assert_eq!(harvest_of(&farmer1), 4 * tps * 30 / 40 + 4 * tps * 30 / 100);
+    // slots 4, 5, 6, 7 with 10/40 share and slots 8, 9, 10, 11 with 70/100
+    assert_eq!(harvest_of(&farmer2), 4 * tps * 10 / 40 + 4 * tps * 70 / 100);
+    // the whole harvest is distributed between both farmers
+    assert_eq!(harvest_of(&farmer1) + harvest_of(&farmer2), 8 * tps);
+
+    Ok(())
+}
+
+#[test]
+fn it_does_not_emit_harvest_before_farm_harvests_start() -> Result<()> {
+    let mut farm = Farm {
+        min_snapshot_window_slots: 1,
+        harvests_start_at: Slot::new(10),
+        ..Default::default()
+    };
+
+    let harvest = Pubkey::new_unique();
+    let tps = 100;
+
+    farm.add_harvest(harvest, Pubkey::new_unique())?;
+    assert!(farm
+        .new_harvest_period(
+            Slot::new(0),
+            harvest,
+            (Slot::new(5), Slot::new(u64::MAX)),
+            TokenAmount::new(tps),
+        )
+        .is_err());
+    farm.new_harvest_period(
+        Slot::new(0),
+        harvest,
+        (Slot::new(10), Slot::new(u64::MAX)),
+        TokenAmount::new(tps),
+    )?;
+    farm.take_snapshot(Slot::new(1), TokenAmount::new(0))?;
+
+    let mut farmer = Farmer {
+        calculate_next_harvest_from: Slot::new(1),
+        ..Default::default()
+    };
+    farmer.add_to_vested(Slot::new(1), TokenAmount::new(100))?;
+
+    farm.take_snapshot(Slot::new(4), TokenAmount::new(100))?;
+
+    let harvest_of = |f: &Farmer| {
+        f.harvests
+            .iter()
+            .find(|h| h.mint == harvest)
+            .unwrap()
+            .tokens
+            .amount
+    };
+
+    // the farmer is staked since slot 4, but harvests start only at slot 10
+    farmer.check_vested_period_and_update_harvest(&farm, Slot::new(9))?;
+    assert_eq!(harvest_of(&farmer), 0);
+
+    // 10th, 11th, 12th, 13th, 14th
+    farmer.check_vested_period_and_update_harvest(&farm, Slot::new(14))?;
+    assert_eq!(harvest_of(&farmer), 5 * tps);
+
+    Ok(())
+}
+
+#[test]
+fn it_stops_accruing_paused_harvest_while_others_continue() -> Result<()> {
+    let mut farm = Farm {
+        min_snapshot_window_slots: 1,
+        ..Default::default()
+    };
+
+    let paused = Pubkey::new_unique();
+    let running = Pubkey::new_unique();
+    let tps = 10;
+
+    for harvest in [paused, running] {
+        farm.add_harvest(harvest, Pubkey::new_unique())?;
+        farm.new_harvest_period(
+            Slot::new(1),
+            harvest,
+            (Slot::new(1), Slot::new(1_000)),
+            TokenAmount::new(tps),
+        )?;
+    }
+    farm.take_snapshot(Slot::new(1), TokenAmount::new(100))?;
+
+    // one farmer updates their harvest during the pause, the other one only
+    // after the harvest is resumed
+    let mut farmer1 = Farmer {
+        staked: TokenAmount::new(50),
+        calculate_next_harvest_from: Slot::new(1),
+        ..Default::default()
+    };
+    let mut farmer2 = farmer1.clone();
+
+    let harvest_of = |f: &Farmer, mint: Pubkey| {
+        f.harvests
+            .iter()
+            .find(|h| h.mint == mint)
+            .unwrap()
+            .tokens
+            .amount
+    };
+
+    // the harvest is emitted until and including slot 10
+    farm.pause_harvest(Slot::new(10), paused)?;
+
+    farmer1.check_vested_period_and_update_harvest(&farm, Slot::new(20))?;
+    // 1st, 2nd, ..., 10th
+    assert_eq!(harvest_of(&farmer1, paused), 10 * tps / 2);
+    // 1st, 2nd, ..., 20th
+    assert_eq!(harvest_of(&farmer1, running), 20 * tps / 2);
+
+    farmer1.check_vested_period_and_update_harvest(&farm, Slot::new(25))?;
+    assert_eq!(harvest_of(&farmer1, paused), 10 * tps / 2);
+    assert_eq!(harvest_of(&farmer1, running), 25 * tps / 2);
+
+    // the harvest is emitted again from slot 31
+    farm.resume_harvest(Slot::new(30), paused)?;
+
+    farmer1.check_vested_period_and_update_harvest(&farm, Slot::new(40))?;
+    farmer2.check_vested_period_and_update_harvest(&farm, Slot::new(40))?;
+    for farmer in [&farmer1, &farmer2] {
+        // 1st, 2nd, ..., 10th and 31st, 32nd, ..., 40th
+        assert_eq!(harvest_of(farmer, paused), 20 * tps / 2);
+        // 1st, 2nd, ..., 40th
+        assert_eq!(harvest_of(farmer, running), 40 * tps / 2);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_accrues_harvests_with_different_windows_independently() -> Result<()> {
+    let mut farm = Farm {
+        min_snapshot_window_slots: 1,
+        ..Default::default()
+    };
+
+    // each harvest has its own periods, so the schedules can be staggered
+    let early = Pubkey::new_unique();
+    let late = Pubkey::new_unique();
+    farm.add_harvest(early, Pubkey::new_unique())?;
+    farm.add_harvest(late, Pubkey::new_unique())?;
+    farm.new_harvest_period(
+        Slot::new(1),
+        early,
+        (Slot::new(1), Slot::new(20)),
+        TokenAmount::new(10),
+    )?;
+    farm.new_harvest_period(
+        Slot::new(1),
+        late,
+        (Slot::new(11), Slot::new(40)),
+        TokenAmount::new(100),
+    )?;
+    farm.take_snapshot(Slot::new(1), TokenAmount::new(100))?;
+
+    let mut farmer = Farmer {
+        staked: TokenAmount::new(100),
+        calculate_next_harvest_from: Slot::new(1),
+        ..Default::default()
+    };
+
+    let harvest_of = |f: &Farmer, mint: Pubkey| {
+        f.harvests
+            .iter()
+            .find(|h| h.mint == mint)
+            .unwrap()
+            .tokens
+            .amount
+    };
+
+    // only the early harvest has started
+    farmer.check_vested_period_and_update_harvest(&farm, Slot::new(10))?;
+    assert_eq!(harvest_of(&farmer, early), 10 * 10);
+    assert_eq!(harvest_of(&farmer, late), 0);
+
+    // both harvests are emitted
+    farmer.check_vested_period_and_update_harvest(&farm, Slot::new(20))?;
+    assert_eq!(harvest_of(&farmer, early), 20 * 10);
+    assert_eq!(harvest_of(&farmer, late), 10 * 100);
+
+    // the early harvest has ended, the late one continues until slot 40
+    farmer.check_vested_period_and_update_harvest(&farm, Slot::new(50))?;
+    assert_eq!(harvest_of(&farmer, early), 20 * 10);
+    assert_eq!(harvest_of(&farmer, late), 30 * 100);
+
+    Ok(())
+}
+
+#[test]
+fn it_aggregates_farm_stats_after_staking_and_claiming() -> Result<()> {
+    let mut farm = Farm {
+        min_snapshot_window_slots: 1,
+        ..Default::default()
+    };
+
+    let harvest = Pubkey::new_unique();
+    farm.add_harvest(harvest, Pubkey::new_unique())?;
+    farm.new_harvest_period(
+        Slot::new(0),
+        harvest,
+        (Slot::new(1), Slot::new(u64::MAX)),
+        TokenAmount::new(100),
+    )?;
+    farm.take_snapshot(Slot::new(1), TokenAmount::new(0))?;
+
+    let mut farmers = vec![Farmer::default(), Farmer::default()];
+    farmers[0].add_to_vested(Slot::new(1), TokenAmount::new(60))?;
+    farmers[1].add_to_vested(Slot::new(1), TokenAmount::new(40))?;
+    let total_staked = TokenAmount::new(100);
+
+    let stats = farm.stats(total_staked);
+    assert_eq!(stats.total_staked, total_staked);
+    assert_eq!(
+        stats.harvests,
+        vec![HarvestStats {
+            mint: harvest,
+            total_distributed: TokenAmount::new(0),
+        }]
+    );
+
+    farm.take_snapshot(Slot::new(4), total_staked)?;
+    farm.take_snapshot(Slot::new(8), total_staked)?;
+
+    // as in claim_eligible_harvest endpoint
+    let mut claimed = 0;
+    for farmer in farmers.iter_mut() {
+        farmer.check_vested_period_and_update_harvest(&farm, Slot::new(10))?;
+        let tokens = farmer.claim_harvest(harvest)?;
+        assert_ne!(tokens.amount, 0);
+        farm.add_to_total_distributed(harvest, tokens);
+        claimed += tokens.amount;
+    }
+
+    let stats = farm.stats(total_staked);
+    assert_eq!(
+        stats.harvests,
+        vec![HarvestStats {
+            mint: harvest,
+            total_distributed: TokenAmount::new(claimed),
+        }]
+    );
+
+    Ok(())
+}
@@ -2,7 +2,7 @@ pub use crate::err::{self, FarmingError};
 pub use crate::models::*;
 pub use crate::{consts, endpoints};
 pub use anchor_lang::prelude::*;
-pub use decimal::{Decimal, TryAdd, TryDiv, TryMul, TryRound};
+pub use decimal::{Decimal, TryAdd, TryDiv, TryMul, TryRound, TrySub};
 
 #[cfg(test)]
 pub mod utils {
@@ -104,6 +104,7 @@ pub mod utils {
             .map(|(mint, tokens)| AvailableHarvest {
                 mint: *mint,
                 tokens: TokenAmount { amount: *tokens },
+                ..Default::default()
             })
             .collect();
 
@@ -125,6 +126,7 @@ pub mod utils {
                 tps: TokenAmount { amount: *tps },
                 starts_at: Slot { slot: *starts_at },
                 ends_at: Slot { slot: *ends_at },
+                ..Default::default()
             })
             .collect();
 
@@ -161,6 +163,7 @@ pub mod utils {
                 mint: *mint,
                 vault: *vault,
                 periods: *periods,
+                ..Default::default()
             })
             .collect();
 
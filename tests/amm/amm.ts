@@ -33,6 +33,22 @@ export function programTollAddress(): PublicKey {
   return programToll;
 }
 
+export function mintBlocklistAddress(): PublicKey {
+  const [mintBlocklist, _bumpSeed] = PublicKey.findProgramAddressSync(
+    [Buffer.from("mint_blocklist")],
+    amm.programId
+  );
+  return mintBlocklist;
+}
+
+export function curveConstraintsAddress(): PublicKey {
+  const [curveConstraints, _bumpSeed] = PublicKey.findProgramAddressSync(
+    [Buffer.from("curve_constraints")],
+    amm.programId
+  );
+  return curveConstraints;
+}
+
 /**
  * This is a call to the dev version of this endpoint. Due to the way the anchor
  * loads programs on localnet (so that we can use any pubkey and don't have to
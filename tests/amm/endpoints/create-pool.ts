@@ -1,4 +1,6 @@
 import { expect } from "chai";
+import { getAccount, getMint } from "@solana/spl-token";
+import { provider } from "../../helpers";
 import { Pool } from "../pool";
 
 export function test() {
@@ -22,5 +24,25 @@ export function test() {
         .which.has.property("amplifier");
       expect(info.dimension.toNumber()).to.eq(2);
     });
+
+    it("creates pool with PDA LP mint and program toll wallet", async () => {
+      const pool = await Pool.init(0, true);
+
+      const info = await pool.fetch();
+
+      const lpMint = Pool.lpMintFrom(pool.id.publicKey);
+      expect(info.mint).to.deep.eq(lpMint);
+      const lpMintInfo = await getMint(provider.connection, lpMint);
+      expect(lpMintInfo.mintAuthority).to.deep.eq(pool.signer());
+      expect(lpMintInfo.freezeAuthority).to.be.null;
+
+      const programTollWallet = Pool.programTollWalletFrom(pool.id.publicKey);
+      expect(info.programTollWallet).to.deep.eq(programTollWallet);
+      const walletInfo = await getAccount(
+        provider.connection,
+        programTollWallet
+      );
+      expect(walletInfo.mint).to.deep.eq(lpMint);
+    });
   });
 }
@@ -23,4 +23,25 @@ export function test() {
       expect(infoAfter.swapFee.permillion.toNumber()).to.eq(5_000);
     });
   });
+
+  describe("set_pool_swap_fee_timelock", () => {
+    it("fails if timelock is less than an hour", async () => {
+      const pool = await Pool.init();
+
+      const logs = await errLogs(pool.setSwapFeeTimelock(100));
+      expect(logs).to.contain("Swap fee timelock must be between");
+    });
+
+    it("works", async () => {
+      const pool = await Pool.init();
+
+      const infoBefore = await pool.fetch();
+      expect(infoBefore.swapFeeTimelockSlots.toNumber()).to.eq(216_000);
+
+      await pool.setSwapFeeTimelock(50_000);
+
+      const infoAfter = await pool.fetch();
+      expect(infoAfter.swapFeeTimelockSlots.toNumber()).to.eq(50_000);
+    });
+  });
 }
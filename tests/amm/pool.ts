@@ -11,9 +11,16 @@ import {
   Keypair,
   Signer,
   SystemProgram,
+  SYSVAR_RENT_PUBKEY,
 } from "@solana/web3.js";
 import { airdrop, amm, payer, provider } from "../helpers";
-import { createProgramToll, discountAddress, programTollAddress } from "./amm";
+import {
+  createProgramToll,
+  curveConstraintsAddress,
+  discountAddress,
+  mintBlocklistAddress,
+  programTollAddress,
+} from "./amm";
 import { BN } from "@project-serum/anchor";
 
 export interface DepositLiquidityArgs {
@@ -42,7 +49,7 @@ export class Pool {
     //
   }
 
-  public static async init(amplifier = 0): Promise<Pool> {
+  public static async init(amplifier = 0, withPdas = false): Promise<Pool> {
     const id = Keypair.generate();
 
     const admin = Keypair.generate();
@@ -59,19 +66,42 @@ export class Pool {
 
     const poolSigner = Pool.signerFrom(id.publicKey);
 
-    const lpMint = await createMint(
-      provider.connection,
-      payer,
-      poolSigner,
-      null,
-      9
-    );
-    const programTollWallet = await createAccount(
-      provider.connection,
-      payer,
-      lpMint,
-      tollAuthority
-    );
+    let lpMint: PublicKey, programTollWallet: PublicKey;
+    if (withPdas) {
+      lpMint = Pool.lpMintFrom(id.publicKey);
+      programTollWallet = Pool.programTollWalletFrom(id.publicKey);
+
+      await amm.methods
+        .createPoolLpMintAndTollWallet(9)
+        .accounts({
+          admin: admin.publicKey,
+          pool: id.publicKey,
+          poolSigner,
+          programToll: toll,
+          programTollAuthority: tollAuthority,
+          lpMint,
+          programTollWallet,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([admin])
+        .rpc();
+    } else {
+      lpMint = await createMint(
+        provider.connection,
+        payer,
+        poolSigner,
+        null,
+        9
+      );
+      programTollWallet = await createAccount(
+        provider.connection,
+        payer,
+        lpMint,
+        tollAuthority
+      );
+    }
 
     const vaults = await Promise.all(
       new Array(2).fill(undefined).map(async () => {
@@ -98,6 +128,8 @@ export class Pool {
         admin: admin.publicKey,
         pool: id.publicKey,
         programToll: toll,
+        mintBlocklist: mintBlocklistAddress(),
+        curveConstraints: curveConstraintsAddress(),
         poolSigner,
         programTollWallet,
         lpMint,
@@ -122,6 +154,20 @@ export class Pool {
     )[0];
   }
 
+  public static lpMintFrom(publicKey: PublicKey): PublicKey {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("lp_mint"), publicKey.toBytes()],
+      amm.programId
+    )[0];
+  }
+
+  public static programTollWalletFrom(publicKey: PublicKey): PublicKey {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("program_toll_wallet"), publicKey.toBytes()],
+      amm.programId
+    )[0];
+  }
+
   public signer(): PublicKey {
     return Pool.signerFrom(this.id.publicKey);
   }
@@ -130,6 +176,13 @@ export class Pool {
     return Pool.signerFrom(this.id.publicKey);
   }
 
+  public feeRebatePda(): PublicKey {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_rebate"), this.id.publicKey.toBytes()],
+      amm.programId
+    )[0];
+  }
+
   public static async airdropLiquidityTokens(
     mint: PublicKey,
     wallet: PublicKey,
@@ -367,6 +420,9 @@ export class Pool {
         programTollWallet: pool.programTollWallet,
         lpMint: pool.mint,
       })
+      .remainingAccounts([
+        { pubkey: this.feeRebatePda(), isSigner: false, isWritable: false },
+      ])
       .signers([user])
       .rpc();
   }
@@ -380,4 +436,12 @@ export class Pool {
       .signers([this.admin])
       .rpc();
   }
+
+  public async setSwapFeeTimelock(timelockSlots: number) {
+    await amm.methods
+      .setPoolSwapFeeTimelock(new BN(timelockSlots))
+      .accounts({ admin: this.admin.publicKey, pool: this.id.publicKey })
+      .signers([this.admin])
+      .rpc();
+  }
 }
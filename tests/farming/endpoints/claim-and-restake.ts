@@ -0,0 +1,235 @@
+import {
+  provider,
+  airdrop,
+  errLogs,
+  sleep,
+  getCurrentSlot,
+} from "../../helpers";
+import { Keypair } from "@solana/web3.js";
+import { getAccount } from "@solana/spl-token";
+import { expect } from "chai";
+import { Farm } from "../farm";
+import { Farmer } from "../farmer";
+
+export function test() {
+  describe("claim_and_restake", () => {
+    const admin = Keypair.generate();
+    let sourceFarm: Farm;
+    let targetFarm: Farm;
+
+    before("airdrop to admin", async () => {
+      await airdrop(admin.publicKey);
+    });
+
+    beforeEach("create farms", async () => {
+      sourceFarm = await Farm.init({ adminKeypair: admin });
+      targetFarm = await Farm.init({ adminKeypair: admin });
+    });
+
+    async function earnHarvest(sourceFarmer: Farmer) {
+      const sourceHarvest = await sourceFarm.addHarvest({
+        harvestMint: targetFarm.stakeMint,
+      });
+      await sourceFarm.newHarvestPeriod(sourceHarvest.mint, 0, 100, 10);
+
+      await sourceFarmer.airdropStakeTokens();
+      await sourceFarm.setMinSnapshotWindow(1);
+      await sourceFarm.takeSnapshot();
+
+      await sourceFarmer.startFarming(10);
+      await sleep(1000);
+      await sourceFarm.takeSnapshot();
+      await sleep(1000);
+      await sourceFarm.takeSnapshot();
+    }
+
+    it("fails if authority doesn't sign", async () => {
+      const user = Keypair.generate();
+      const sourceFarmer = await Farmer.init(sourceFarm, { authority: user });
+      await Farmer.init(targetFarm, { authority: user });
+      await sourceFarm.addHarvest({ harvestMint: targetFarm.stakeMint });
+
+      await expect(
+        sourceFarmer.claimAndRestake(targetFarm, {
+          skipAuthoritySignature: true,
+        })
+      ).to.be.rejected;
+    });
+
+    it("fails if target farmer belongs to a different user", async () => {
+      const sourceFarmer = await Farmer.init(sourceFarm);
+      const otherTargetFarmer = await Farmer.init(targetFarm);
+      await sourceFarm.addHarvest({ harvestMint: targetFarm.stakeMint });
+
+      const logs = await errLogs(
+        sourceFarmer.claimAndRestake(targetFarm, {
+          targetFarmer: await otherTargetFarmer.id(),
+        })
+      );
+
+      expect(logs).to.contain("target_farmer");
+      expect(logs).to.contain("ConstraintSeeds.");
+    });
+
+    it("fails if farms are the same", async () => {
+      const user = Keypair.generate();
+      const sourceFarmer = await Farmer.init(sourceFarm, { authority: user });
+      await sourceFarm.addHarvest({ harvestMint: sourceFarm.stakeMint });
+
+      const logs = await errLogs(sourceFarmer.claimAndRestake(sourceFarm));
+
+      expect(logs).to.contain("Cannot restake into the same farm");
+    });
+
+    it("fails if target stake mint is not harvest of source farm", async () => {
+      const user = Keypair.generate();
+      const sourceFarmer = await Farmer.init(sourceFarm, { authority: user });
+      await Farmer.init(targetFarm, { authority: user });
+      const harvest = await sourceFarm.addHarvest();
+
+      const logs = await errLogs(
+        sourceFarmer.claimAndRestake(targetFarm, {
+          sourceHarvestVault: harvest.vault,
+        })
+      );
+
+      expect(logs).to.contain(
+        "Restaking is only possible if target farm's stake mint is " +
+          "a harvestable mint of the source farm"
+      );
+    });
+
+    it("fails if there's no eligible harvest", async () => {
+      const user = Keypair.generate();
+      const sourceFarmer = await Farmer.init(sourceFarm, { authority: user });
+      await Farmer.init(targetFarm, { authority: user });
+      await sourceFarm.addHarvest({ harvestMint: targetFarm.stakeMint });
+
+      const logs = await errLogs(sourceFarmer.claimAndRestake(targetFarm));
+
+      expect(logs).to.contain("There is no eligible harvest to restake");
+    });
+
+    it("fails if target farm ended and farming is blocked", async () => {
+      const user = Keypair.generate();
+      const sourceFarmer = await Farmer.init(sourceFarm, { authority: user });
+      await Farmer.init(targetFarm, { authority: user });
+      await earnHarvest(sourceFarmer);
+
+      const targetHarvest = await targetFarm.addHarvest();
+      await targetFarm.newHarvestPeriod(targetHarvest.mint, 0, 1, 10);
+      await targetFarm.setBlockFarmingOnceEnded(true);
+      await sleep(1000);
+
+      const logs = await errLogs(sourceFarmer.claimAndRestake(targetFarm));
+
+      expect(logs).to.contain(
+        "Farm's harvests have ended and it doesn't accept new stake"
+      );
+    });
+
+    it("fails if source farmer claimed within cooldown", async () => {
+      const user = Keypair.generate();
+      const sourceFarmer = await Farmer.init(sourceFarm, { authority: user });
+      await Farmer.init(targetFarm, { authority: user });
+      await sourceFarm.setClaimCooldownSlots(10_000);
+      await earnHarvest(sourceFarmer);
+
+      await sourceFarmer.claimAndRestake(targetFarm);
+      await sleep(1000);
+
+      const logs = await errLogs(sourceFarmer.claimAndRestake(targetFarm));
+
+      expect(logs).to.contain("Claimed too recently");
+    });
+
+    it("fails if source farm's harvest is still vesting", async () => {
+      const user = Keypair.generate();
+      const sourceFarmer = await Farmer.init(sourceFarm, { authority: user });
+      await Farmer.init(targetFarm, { authority: user });
+      await sourceFarm.setClaimVestingSlots(10_000);
+      await earnHarvest(sourceFarmer);
+
+      // the whole harvest would be put into the escrow and none of it has
+      // vested yet
+      const logs = await errLogs(sourceFarmer.claimAndRestake(targetFarm));
+
+      expect(logs).to.contain("There is no eligible harvest to restake");
+    });
+
+    it("locks restaked tokens in target farm", async () => {
+      const user = Keypair.generate();
+      const sourceFarmer = await Farmer.init(sourceFarm, { authority: user });
+      const targetFarmer = await Farmer.init(targetFarm, { authority: user });
+      await earnHarvest(sourceFarmer);
+
+      await sourceFarmer.claimAndRestake(targetFarm);
+
+      const { vested, locked } = await targetFarmer.fetch();
+      expect(vested.amount.toNumber()).to.be.greaterThan(0);
+      expect(locked.amount.toNumber()).to.eq(vested.amount.toNumber());
+    });
+
+    it("migrates harvest from source farm into target farm", async () => {
+      const user = Keypair.generate();
+      const sourceFarmer = await Farmer.init(sourceFarm, { authority: user });
+      const targetFarmer = await Farmer.init(targetFarm, { authority: user });
+      const targetStakeVault = await targetFarm.stakeVault();
+
+      const sourceHarvest = await sourceFarm.addHarvest({
+        harvestMint: targetFarm.stakeMint,
+      });
+
+      const tps = 10;
+      await sourceFarm.newHarvestPeriod(sourceHarvest.mint, 0, 100, tps);
+      const sourceHarvestVaultBeforeInfo = await getAccount(
+        provider.connection,
+        sourceHarvest.vault
+      );
+
+      await sourceFarmer.airdropStakeTokens();
+      await sourceFarm.setMinSnapshotWindow(1);
+      await sourceFarm.takeSnapshot();
+
+      await sourceFarmer.startFarming(10);
+      await sleep(1000);
+      await sourceFarm.takeSnapshot();
+      const earningRewardsFromSlot = await getCurrentSlot();
+      await sleep(1000);
+      await sourceFarm.takeSnapshot();
+
+      await sourceFarmer.claimAndRestake(targetFarm);
+      const earnedRewardsToSlot = await getCurrentSlot();
+
+      const sourceHarvestVaultAfterInfo = await getAccount(
+        provider.connection,
+        sourceHarvest.vault
+      );
+      const targetStakeVaultInfo = await getAccount(
+        provider.connection,
+        targetStakeVault
+      );
+      const restaked = Number(targetStakeVaultInfo.amount);
+
+      expect(restaked).to.be.approximately(
+        (earnedRewardsToSlot - earningRewardsFromSlot) * tps,
+        // there's a possibility that we will get different slot in our call
+        // than the one that was active during the restake
+        2 * tps
+      );
+      expect(Number(sourceHarvestVaultBeforeInfo.amount)).to.eq(
+        Number(sourceHarvestVaultAfterInfo.amount) + restaked
+      );
+
+      const sourceFarmerInfo = await sourceFarmer.fetch();
+      const { tokens } = (sourceFarmerInfo.harvests as any[]).find(
+        (h) => h.mint.toString() === sourceHarvest.mint.toString()
+      );
+      expect(tokens.amount.toNumber()).to.eq(0);
+
+      const targetFarmerInfo = await targetFarmer.fetch();
+      expect(targetFarmerInfo.vested.amount.toNumber()).to.eq(restaked);
+      expect(targetFarmerInfo.staked.amount.toNumber()).to.eq(0);
+    });
+  });
+}
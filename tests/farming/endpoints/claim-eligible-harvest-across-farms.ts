@@ -0,0 +1,118 @@
+import { expect } from "chai";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { getAccount } from "@solana/spl-token";
+import { Farm } from "../farm";
+import { Farmer } from "../farmer";
+import { airdrop, provider, sleep } from "../../helpers";
+
+export function test() {
+  describe("claim_eligible_harvest_across_farms", () => {
+    const tokensPerSlot = 100;
+    const user = Keypair.generate();
+
+    let farm1: Farm,
+      farm2: Farm,
+      farmer1: Farmer,
+      farmer2: Farmer,
+      harvest1: { mint: PublicKey; vault: PublicKey },
+      harvest2: { mint: PublicKey; vault: PublicKey };
+
+    before("airdrop to user", async () => {
+      await airdrop(user.publicKey);
+    });
+
+    beforeEach("create farms", async () => {
+      farm1 = await Farm.init();
+      farm2 = await Farm.init();
+
+      await farm1.setMinSnapshotWindow(1);
+      await farm2.setMinSnapshotWindow(1);
+    });
+
+    beforeEach("create farmers of the same user", async () => {
+      farmer1 = await Farmer.init(farm1, { authority: user });
+      farmer2 = await Farmer.init(farm2, { authority: user });
+      await farmer1.airdropStakeTokens();
+      await farmer2.airdropStakeTokens();
+    });
+
+    beforeEach("create harvests", async () => {
+      harvest1 = await farm1.addHarvest();
+      await farm1.newHarvestPeriod(harvest1.mint, 0, 100, tokensPerSlot);
+
+      harvest2 = await farm2.addHarvest();
+      await farm2.newHarvestPeriod(harvest2.mint, 0, 100, tokensPerSlot);
+    });
+
+    async function earnHarvest(farm: Farm, farmer: Farmer) {
+      await farm.takeSnapshot();
+
+      await farmer.startFarming(10);
+
+      await sleep(1000);
+      await farm.takeSnapshot();
+      await sleep(1000);
+      await farm.takeSnapshot();
+
+      await farmer.stopFarming(10);
+    }
+
+    async function eligibleHarvest(farmer: Farmer, mint: PublicKey) {
+      const farmerInfo = await farmer.fetch();
+      const { tokens } = (farmerInfo.harvests as any[]).find(
+        (h) => h.mint.toBase58() === mint.toBase58()
+      );
+      return tokens.amount.toNumber();
+    }
+
+    async function walletAmount(wallet: PublicKey) {
+      const walletInfo = await getAccount(provider.connection, wallet);
+      return Number(walletInfo.amount);
+    }
+
+    it("claims from two farms in a single call", async () => {
+      await earnHarvest(farm1, farmer1);
+      await earnHarvest(farm2, farmer2);
+
+      const eligible1 = await eligibleHarvest(farmer1, harvest1.mint);
+      const eligible2 = await eligibleHarvest(farmer2, harvest2.mint);
+      expect(eligible1).to.be.greaterThan(0);
+      expect(eligible2).to.be.greaterThan(0);
+
+      const wallet1 = await farmer1.harvestWalletPubkey(harvest1.mint);
+      const wallet2 = await farmer2.harvestWalletPubkey(harvest2.mint);
+
+      await Farmer.claimEligibleHarvestAcrossFarms(user, [
+        { farmer: farmer1, mint: harvest1.mint, wallet: wallet1 },
+        { farmer: farmer2, mint: harvest2.mint, wallet: wallet2 },
+      ]);
+
+      expect(await walletAmount(wallet1)).to.eq(eligible1);
+      expect(await walletAmount(wallet2)).to.eq(eligible2);
+      expect(await eligibleHarvest(farmer1, harvest1.mint)).to.eq(0);
+      expect(await eligibleHarvest(farmer2, harvest2.mint)).to.eq(0);
+    });
+
+    it("skips malformed groups and claims the rest", async () => {
+      await earnHarvest(farm1, farmer1);
+      await earnHarvest(farm2, farmer2);
+
+      const eligible2 = await eligibleHarvest(farmer2, harvest2.mint);
+
+      const wallet1 = await farmer1.harvestWalletPubkey(harvest1.mint);
+      const wallet2 = await farmer2.harvestWalletPubkey(harvest2.mint);
+
+      // the vault of farm1 doesn't exist in farm2
+      await Farmer.claimEligibleHarvestAcrossFarms(user, [
+        { farmer: farmer2, mint: harvest1.mint, wallet: wallet1 },
+        { farmer: farmer2, mint: harvest2.mint, wallet: wallet2 },
+      ]);
+
+      expect(await walletAmount(wallet1)).to.eq(0);
+      expect(await walletAmount(wallet2)).to.eq(eligible2);
+      expect(await eligibleHarvest(farmer1, harvest1.mint)).to.be.greaterThan(
+        0
+      );
+    });
+  });
+}
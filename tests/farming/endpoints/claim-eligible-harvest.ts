@@ -96,6 +96,42 @@ export function test() {
       );
     });
 
+    it("fails if signer of cooldown change isn't admin", async () => {
+      const logs = await errLogs(
+        farm.setClaimCooldownSlots(10, { admin: Keypair.generate() })
+      );
+
+      expect(logs).to.contain("FarmAdminMismatch");
+    });
+
+    it("fails if claimed again before cooldown elapses", async () => {
+      await farm.setClaimCooldownSlots(10_000);
+
+      await farmer.claimEligibleHarvest(farmerVaultWalletPairs);
+      await sleep(1000);
+
+      const logs = await errLogs(
+        farmer.claimEligibleHarvest(farmerVaultWalletPairs)
+      );
+      expect(logs).to.contain("ClaimCooldownNotElapsed");
+      expect(logs).to.contain("Farmer can claim again at slot");
+    });
+
+    it("claims again once cooldown elapses", async () => {
+      await farm.setClaimCooldownSlots(2);
+
+      await farmer.claimEligibleHarvest(farmerVaultWalletPairs);
+      const { lastClaimedAt: firstClaimAt } = await farmer.fetch();
+
+      await sleep(2000);
+      await farmer.claimEligibleHarvest(farmerVaultWalletPairs);
+
+      const { lastClaimedAt } = await farmer.fetch();
+      expect(lastClaimedAt.slot.toNumber()).to.be.greaterThan(
+        firstClaimAt.slot.toNumber() + 1
+      );
+    });
+
     it("works", async () => {
       await farm.takeSnapshot();
 
@@ -125,6 +161,7 @@ export function test() {
 
       const farmerInfoAfter = await farmer.fetch();
       const harvestsAfter = farmerInfoAfter.harvests as any[];
+      const farmHarvestsAfter = (await farm.fetch()).harvests as any[];
 
       expect(
         harvestsAfter
@@ -158,6 +195,16 @@ export function test() {
               .find((h) => h.mint.toBase58() === vaultInfo.mint.toBase58())
               .tokens.amount.toNumber()
           ).to.eq(walletAmountAfter);
+          expect(
+            harvestsAfter
+              .find((h) => h.mint.toBase58() === vaultInfo.mint.toBase58())
+              .totalClaimed.amount.toNumber()
+          ).to.eq(walletAmountAfter);
+          expect(
+            farmHarvestsAfter
+              .find((h) => h.mint.toBase58() === vaultInfo.mint.toBase58())
+              .totalDistributed.amount.toNumber()
+          ).to.eq(walletAmountAfter);
         })
       );
     });
@@ -0,0 +1,134 @@
+import { expect } from "chai";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { getAccount } from "@solana/spl-token";
+import { Farm } from "../farm";
+import { Farmer } from "../farmer";
+import { errLogs, provider, sleep } from "../../helpers";
+
+export function test() {
+  describe("claim_vested_harvest", () => {
+    const tokensPerSlot = 100;
+
+    let farm: Farm,
+      farmer: Farmer,
+      harvest: { mint: PublicKey; vault: PublicKey },
+      harvestWallet: PublicKey;
+
+    beforeEach("create farm", async () => {
+      farm = await Farm.init();
+
+      await farm.setMinSnapshotWindow(1);
+    });
+
+    beforeEach("create farmer", async () => {
+      farmer = await Farmer.init(farm);
+      await farmer.airdropStakeTokens();
+    });
+
+    beforeEach("create harvest", async () => {
+      harvest = await farm.addHarvest();
+      await farm.newHarvestPeriod(harvest.mint, 0, 100, tokensPerSlot);
+
+      harvestWallet = await farmer.harvestWalletPubkey(harvest.mint);
+    });
+
+    async function earnHarvest() {
+      await farm.takeSnapshot();
+
+      await farmer.startFarming(10);
+
+      await sleep(1000);
+      await farm.takeSnapshot();
+      await sleep(1000);
+      await farm.takeSnapshot();
+
+      await farmer.stopFarming(10);
+    }
+
+    async function farmerHarvest() {
+      const farmerInfo = await farmer.fetch();
+      return (farmerInfo.harvests as any[]).find(
+        (h) => h.mint.toBase58() === harvest.mint.toBase58()
+      );
+    }
+
+    async function walletAmount() {
+      const walletInfo = await getAccount(provider.connection, harvestWallet);
+      return Number(walletInfo.amount);
+    }
+
+    it("fails if signer isn't admin", async () => {
+      const logs = await errLogs(
+        farm.setClaimVestingSlots(10, { admin: Keypair.generate() })
+      );
+
+      expect(logs).to.contain("FarmAdminMismatch");
+    });
+
+    it("fails if there are no vested tokens", async () => {
+      const logs = await errLogs(farmer.claimVestedHarvest(harvest.mint));
+
+      expect(logs).to.contain("There are no vested tokens to claim");
+    });
+
+    it("escrows claimed harvest and releases it mid-vest", async () => {
+      await farm.setClaimVestingSlots(100);
+      await earnHarvest();
+
+      const { tokens } = await farmerHarvest();
+      const claimed = tokens.amount.toNumber();
+      expect(claimed).to.be.greaterThan(0);
+
+      await farmer.claimEligibleHarvest([[harvest.vault, harvestWallet]]);
+
+      const { escrowed, totalClaimed } = await farmerHarvest();
+      expect(totalClaimed.amount.toNumber()).to.eq(claimed);
+      expect(escrowed.amount.toNumber()).to.eq(claimed);
+      expect(await walletAmount()).to.eq(0);
+
+      await sleep(1000);
+      await farmer.claimVestedHarvest(harvest.mint);
+
+      const released = await walletAmount();
+      expect(released).to.be.greaterThan(0);
+      expect(released).to.be.lessThan(claimed);
+
+      const { escrowed: escrowedAfter } = await farmerHarvest();
+      expect(escrowedAfter.amount.toNumber()).to.eq(claimed - released);
+
+      const farmInfo = await farm.fetch();
+      const { totalDistributed } = (farmInfo.harvests as any[]).find(
+        (h) => h.mint.toBase58() === harvest.mint.toBase58()
+      );
+      expect(totalDistributed.amount.toNumber()).to.eq(released);
+    });
+
+    it("releases whole escrow after full vest", async () => {
+      await farm.setClaimVestingSlots(1);
+      await earnHarvest();
+
+      const { tokens } = await farmerHarvest();
+      const claimed = tokens.amount.toNumber();
+
+      await farmer.claimEligibleHarvest([[harvest.vault, harvestWallet]]);
+      expect(await walletAmount()).to.eq(0);
+
+      await sleep(1000);
+      await farmer.claimVestedHarvest(harvest.mint);
+
+      expect(await walletAmount()).to.eq(claimed);
+      const { escrowed } = await farmerHarvest();
+      expect(escrowed.amount.toNumber()).to.eq(0);
+    });
+
+    it("fails to close farmer with escrowed harvest", async () => {
+      await farm.setClaimVestingSlots(10_000);
+      await earnHarvest();
+      await farmer.claimEligibleHarvest([[harvest.vault, harvestWallet]]);
+
+      const logs = await errLogs(farmer.close());
+
+      expect(logs).to.contain("Claim all farmer's harvest");
+    });
+  });
+}
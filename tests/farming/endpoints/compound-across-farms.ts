@@ -225,6 +225,80 @@ export function test() {
       );
     });
 
+    async function setupCompounding() {
+      const sourceFarmer = await Farmer.init(sourceFarm, {
+        authority: admin,
+      });
+      const targetFarmer = await Farmer.init(targetFarm, {
+        authority: admin,
+      });
+      const targetStakeVault = await targetFarm.stakeVault();
+
+      const sourceHarvest = await sourceFarm.addHarvest({
+        harvestMint: targetFarm.stakeMint,
+      });
+
+      await sourceFarm.whitelistFarmForCompounding({
+        targetFarm: targetFarm.id,
+      });
+
+      return {
+        sourceFarmer,
+        accounts: {
+          targetFarm: targetFarm.id,
+          sourceFarmer: await sourceFarmer.id(),
+          targetFarmer: await targetFarmer.id(),
+          sourceHarvestVault: sourceHarvest.vault,
+          targetStakeVault,
+        },
+      };
+    }
+
+    it("fails if farmer's deadline slot has passed", async () => {
+      const { sourceFarmer, accounts } = await setupCompounding();
+      await sourceFarmer.setCompoundBounds(0, await getCurrentSlot());
+      await sleep(1000);
+
+      const logs = await errLogs(
+        sourceFarm.compoundAcrossFarms(targetFarm.stakeMint, accounts)
+      );
+
+      expect(logs).to.contain("DeadlineExceeded");
+    });
+
+    it("fails if would compound less than farmer's minimum", async () => {
+      const { sourceFarmer, accounts } = await setupCompounding();
+      await sourceFarmer.setCompoundBounds(1, 0);
+
+      const logs = await errLogs(
+        sourceFarm.compoundAcrossFarms(targetFarm.stakeMint, accounts)
+      );
+
+      expect(logs).to.contain("Would compound 0, but requested minimum of 1");
+      expect(logs).to.contain("CompoundedLessThanMinimum");
+    });
+
+    it("fails to set compound bounds if authority doesn't sign", async () => {
+      const { sourceFarmer } = await setupCompounding();
+
+      await expect(
+        sourceFarmer.setCompoundBounds(1, 0, {
+          authority: Keypair.generate(),
+        })
+      ).to.be.rejected;
+    });
+
+    it("works within farmer's bounds", async () => {
+      const { sourceFarmer, accounts } = await setupCompounding();
+      await sourceFarmer.setCompoundBounds(0, (await getCurrentSlot()) + 1000);
+
+      await sourceFarm.compoundAcrossFarms(targetFarm.stakeMint, accounts);
+
+      const { minCompound, compoundDeadline } = await sourceFarmer.fetch();
+      expect(minCompound.amount.toNumber()).to.eq(0);
+      expect(compoundDeadline.slot.toNumber()).to.be.greaterThan(0);
+    });
+
     it("works even if no tokens eligible to claim", async () => {
       const sourceFarmer = await Farmer.init(sourceFarm, {
         authority: admin,
@@ -210,6 +210,34 @@ export function test() {
       expect(Number(stakeVaultInfo.amount)).to.eq(0);
     });
 
+    it("fails if farmer claimed within cooldown", async () => {
+      const farmer = await Farmer.init(farm);
+      const stakeVault = await farm.stakeVault();
+
+      const harvest = await farm.addHarvest({
+        harvestMint: farm.stakeMint,
+      });
+
+      await farm.whitelistFarmForCompounding({
+        targetFarm: farm.id,
+      });
+      await farm.setClaimCooldownSlots(10_000);
+
+      const farmerId = await farmer.id();
+      const compound = () =>
+        farm.compoundSameFarm(farm.stakeMint, {
+          farmer: farmerId,
+          harvestVault: harvest.vault,
+          stakeVault,
+        });
+
+      await compound();
+      await sleep(1000);
+      const logs = await errLogs(compound());
+
+      expect(logs).to.contain("Claimed too recently");
+    });
+
     it("works", async () => {
       const farmer = await Farmer.init(farm);
       const stakeVault = await farm.stakeVault();
@@ -272,5 +300,132 @@ export function test() {
       stakeVaultInfo = await getAccount(provider.connection, stakeVault);
       expect(Number(stakeVaultInfo.amount)).to.eq(actuaRewards);
     });
+
+    describe("with farmer's minimum compound", () => {
+      let farmer: Farmer;
+      let harvest: { mint: PublicKey; vault: PublicKey };
+      let stakeVault: PublicKey;
+      let rewards: number;
+
+      beforeEach("farm some harvest", async () => {
+        farmer = await Farmer.init(farm);
+        stakeVault = await farm.stakeVault();
+        harvest = await farm.addHarvest({
+          harvestMint: farm.stakeMint,
+        });
+        await farm.whitelistFarmForCompounding({
+          targetFarm: farm.id,
+        });
+        await farmer.airdropStakeTokens();
+
+        await farm.newHarvestPeriod(harvest.mint, 0, 100, 10);
+        await farm.setMinSnapshotWindow(1);
+        await farm.takeSnapshot();
+
+        await farmer.startFarming(10);
+        await sleep(1000);
+        await farm.takeSnapshot();
+        await sleep(1000);
+        await farm.takeSnapshot();
+
+        await farmer.stopFarming(10);
+
+        const farmerInfo = await farmer.fetch();
+        const { tokens } = (farmerInfo.harvests as any[]).find(
+          (h) => h.mint.toString() === harvest.mint.toString()
+        );
+        rewards = tokens.amount.toNumber();
+        expect(rewards).to.be.greaterThan(0);
+      });
+
+      it("fails if would compound less than minimum", async () => {
+        await farmer.setCompoundBounds(rewards + 1, 0);
+
+        const logs = await errLogs(
+          farm.compoundSameFarm(farm.stakeMint, {
+            farmer: await farmer.id(),
+            harvestVault: harvest.vault,
+            stakeVault,
+          })
+        );
+
+        expect(logs).to.contain(
+          "Would compound less tokens than the requested minimum"
+        );
+
+        const stakeVaultInfo = await getAccount(
+          provider.connection,
+          stakeVault
+        );
+        expect(Number(stakeVaultInfo.amount)).to.eq(0);
+      });
+
+      it("works if compounds at least minimum", async () => {
+        await farmer.setCompoundBounds(rewards, 0);
+
+        await farm.compoundSameFarm(farm.stakeMint, {
+          farmer: await farmer.id(),
+          harvestVault: harvest.vault,
+          stakeVault,
+        });
+
+        const stakeVaultInfo = await getAccount(
+          provider.connection,
+          stakeVault
+        );
+        expect(Number(stakeVaultInfo.amount)).to.eq(rewards);
+      });
+    });
+
+    it("escrows harvest if farm vests claims", async () => {
+      const farmer = await Farmer.init(farm);
+      const stakeVault = await farm.stakeVault();
+
+      const harvest = await farm.addHarvest({
+        harvestMint: farm.stakeMint,
+      });
+
+      await farm.whitelistFarmForCompounding({
+        targetFarm: farm.id,
+      });
+      await farm.setClaimVestingSlots(10_000);
+
+      await farmer.airdropStakeTokens();
+
+      await farm.newHarvestPeriod(harvest.mint, 0, 100, 10);
+      await farm.setMinSnapshotWindow(1);
+      await farm.takeSnapshot();
+
+      await farmer.startFarming(10);
+      await sleep(1000);
+      await farm.takeSnapshot();
+      await sleep(1000);
+      await farm.takeSnapshot();
+
+      await farmer.stopFarming(10);
+
+      const harvestOf = async () => {
+        const { harvests } = await farmer.fetch();
+        return (harvests as any[]).find(
+          (h) => h.mint.toString() === harvest.mint.toString()
+        );
+      };
+      const { tokens } = await harvestOf();
+      const earned = tokens.amount.toNumber();
+      expect(earned).to.be.greaterThan(0);
+
+      await farm.compoundSameFarm(farm.stakeMint, {
+        farmer: await farmer.id(),
+        harvestVault: harvest.vault,
+        stakeVault,
+      });
+
+      // the escrow starts vesting at the compounding slot, therefore none of
+      // it is compounded yet
+      const { escrowed } = await harvestOf();
+      expect(escrowed.amount.toNumber()).to.eq(earned);
+      const stakeVaultInfo = await getAccount(provider.connection, stakeVault);
+      expect(Number(stakeVaultInfo.amount)).to.eq(0);
+    });
   });
 }
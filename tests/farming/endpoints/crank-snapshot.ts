@@ -0,0 +1,212 @@
+import { expect } from "chai";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { createAccount, createMint, getAccount } from "@solana/spl-token";
+import { Farm } from "../farm";
+import { errLogs, payer, provider, sleep } from "../../helpers";
+
+export function test() {
+  describe("crank_snapshot", () => {
+    const keeperReward = 5;
+
+    let farm: Farm,
+      harvest: { mint: PublicKey; vault: PublicKey },
+      keeperRewardVault: PublicKey,
+      keeperWallet: PublicKey,
+      depositorStakeWallet: PublicKey;
+
+    beforeEach("create farm", async () => {
+      farm = await Farm.init();
+      await farm.setMinSnapshotWindow(1);
+
+      depositorStakeWallet = await farm.createStakeWallet(10_000);
+    });
+
+    beforeEach("configure keeper reward", async () => {
+      harvest = await farm.addHarvest();
+      await farm.newHarvestPeriod(harvest.mint, 0, 100, 10);
+      keeperRewardVault = await farm.createKeeperRewardVault(
+        harvest.mint,
+        keeperReward
+      );
+      await farm.setKeeperReward(keeperRewardVault, keeperReward);
+
+      keeperWallet = await createAccount(
+        provider.connection,
+        payer,
+        harvest.mint,
+        Keypair.generate().publicKey
+      );
+    });
+
+    async function keeperWalletAmount() {
+      const walletInfo = await getAccount(provider.connection, keeperWallet);
+      return Number(walletInfo.amount);
+    }
+
+    async function tip() {
+      const { snapshots } = await farm.fetch();
+      return snapshots.ringBufferTip.toNumber();
+    }
+
+    it("fails if signer isn't admin", async () => {
+      const logs = await errLogs(
+        farm.setKeeperReward(keeperRewardVault, 1, {
+          admin: Keypair.generate(),
+        })
+      );
+
+      expect(logs).to.contain("FarmAdminMismatch");
+    });
+
+    it("fails if keeper reward vault isn't of farm's harvest", async () => {
+      const otherMint = await createMint(
+        provider.connection,
+        payer,
+        farm.admin.publicKey,
+        null,
+        6
+      );
+      const otherVault = await farm.createKeeperRewardVault(otherMint, 0);
+
+      const logs = await errLogs(farm.setKeeperReward(otherVault, 1));
+
+      expect(logs).to.contain("UnknownHarvestMintPubKey");
+    });
+
+    it("fails if keeper reward vault doesn't delegate to farm", async () => {
+      const logs = await errLogs(farm.setKeeperReward(harvest.vault, 1));
+
+      expect(logs).to.contain("must delegate to the farm signer");
+    });
+
+    it("records new snapshot and pays keeper after stake change", async () => {
+      await farm.transferToStakeVault(depositorStakeWallet, 100);
+
+      const harvestVaultBefore = await farm.harvestVaultAccount(harvest.mint);
+
+      await sleep(1000);
+      await farm.crankSnapshot(keeperRewardVault, keeperWallet);
+
+      const { snapshots, harvests } = await farm.fetch();
+      const tip = snapshots.ringBufferTip.toNumber();
+      expect(tip).to.eq(1);
+      const ringBuffer = snapshots.ringBuffer as any[];
+      expect(ringBuffer[tip].staked.amount.toNumber()).to.eq(100);
+
+      expect(await keeperWalletAmount()).to.eq(keeperReward);
+      const keeperRewardVaultInfo = await getAccount(
+        provider.connection,
+        keeperRewardVault
+      );
+      expect(Number(keeperRewardVaultInfo.delegatedAmount)).to.eq(0);
+
+      // farmers' harvest is untouched
+      const harvestVaultAfter = await farm.harvestVaultAccount(harvest.mint);
+      expect(harvestVaultAfter.amount).to.eq(harvestVaultBefore.amount);
+
+      const { totalDistributed } = (harvests as any[]).find(
+        (h) => h.mint.toBase58() === harvest.mint.toBase58()
+      );
+      expect(totalDistributed.amount.toNumber()).to.eq(keeperReward);
+    });
+
+    it("skips reward if allowance is too small", async () => {
+      const smallVault = await farm.createKeeperRewardVault(
+        harvest.mint,
+        keeperReward - 1
+      );
+      await farm.setKeeperReward(smallVault, keeperReward);
+
+      await farm.transferToStakeVault(depositorStakeWallet, 100);
+      await sleep(1000);
+      await farm.crankSnapshot(smallVault, keeperWallet);
+
+      expect(await tip()).to.eq(1);
+      expect(await keeperWalletAmount()).to.eq(0);
+    });
+
+    it("doesn't pay keeper repeatedly for toggling stake", async () => {
+      keeperRewardVault = await farm.createKeeperRewardVault(
+        harvest.mint,
+        3 * keeperReward
+      );
+      await farm.setKeeperReward(keeperRewardVault, keeperReward);
+
+      await farm.transferToStakeVault(depositorStakeWallet, 100);
+      await sleep(1000);
+      await farm.crankSnapshot(keeperRewardVault, keeperWallet);
+      expect(await keeperWalletAmount()).to.eq(keeperReward);
+
+      // each snapshot records a stake change, but the reward was paid within
+      // the keeper reward window
+      await farm.transferToStakeVault(depositorStakeWallet, 1);
+      await sleep(1000);
+      await farm.crankSnapshot(keeperRewardVault, keeperWallet);
+      await farm.transferToStakeVault(depositorStakeWallet, 1);
+      await sleep(1000);
+      await farm.crankSnapshot(keeperRewardVault, keeperWallet);
+
+      expect(await tip()).to.eq(3);
+      expect(await keeperWalletAmount()).to.eq(keeperReward);
+      const { keeperRewardedAt } = await farm.fetch();
+      expect(keeperRewardedAt.slot.toNumber()).to.be.greaterThan(0);
+    });
+
+    it("fails if other than configured keeper reward vault", async () => {
+      const otherVault = await farm.createKeeperRewardVault(
+        harvest.mint,
+        keeperReward
+      );
+      await farm.transferToStakeVault(depositorStakeWallet, 100);
+
+      await sleep(1000);
+      const logs = await errLogs(farm.crankSnapshot(otherVault, keeperWallet));
+
+      expect(logs).to.contain(
+        "keeper reward vault does not correspond to the Farm keeper reward vault"
+      );
+      expect(await tip()).to.eq(0);
+    });
+
+    it("doesn't pay keeper if stake didn't change", async () => {
+      await farm.transferToStakeVault(depositorStakeWallet, 100);
+      await farm.takeSnapshot();
+
+      await sleep(1000);
+      await farm.crankSnapshot(keeperRewardVault, keeperWallet);
+
+      expect(await tip()).to.eq(2);
+
+      expect(await keeperWalletAmount()).to.eq(0);
+    });
+
+    it("fails if foreign stake vault with balance is provided", async () => {
+      // a wallet of the stake mint which holds tokens, but isn't the farm's
+      // vault, must not be recorded as the farm's stake
+      const foreignVault = await farm.createStakeWallet(10_000);
+
+      await sleep(1000);
+      const logs = await errLogs(
+        farm.crankSnapshot(keeperRewardVault, keeperWallet, {
+          stakeVault: foreignVault,
+        })
+      );
+
+      expect(logs).to.contain(
+        "stake vault does not correspond to the Farm stake vault"
+      );
+      expect(await tip()).to.eq(0);
+      expect(await keeperWalletAmount()).to.eq(0);
+    });
+
+    it("fails if time elapsed between slots < minSnapshotWindow", async () => {
+      await farm.crankSnapshot(keeperRewardVault, keeperWallet);
+
+      const logs = await errLogs(
+        farm.crankSnapshot(keeperRewardVault, keeperWallet)
+      );
+
+      expect(logs).to.contain("InsufficientSlotTime");
+    });
+  });
+}
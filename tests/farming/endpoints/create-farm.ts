@@ -1,4 +1,4 @@
-import { errLogs, provider } from "../../helpers";
+import { errLogs, getCurrentSlot, provider } from "../../helpers";
 import { Keypair, PublicKey } from "@solana/web3.js";
 import { getAccount } from "@solana/spl-token";
 import { expect } from "chai";
@@ -78,6 +78,23 @@ export function test() {
       );
 
       expect(farmInfo.minSnapshotWindowSlots.toNumber()).to.eq(0);
+      expect(farmInfo.harvestsStartAt.slot.toNumber()).to.eq(0);
+    });
+
+    it("fails if harvests start in the past", async () => {
+      const currentSlot = await getCurrentSlot();
+
+      const logs = await errLogs(Farm.init({ startsAt: currentSlot - 5 }));
+      expect(logs).to.contain("Farm's harvests cannot start in the past");
+    });
+
+    it("sets the slot at which harvests start", async () => {
+      const startsAt = (await getCurrentSlot()) + 100;
+
+      const farm = await Farm.init({ startsAt });
+      const farmInfo = await farm.fetch();
+
+      expect(farmInfo.harvestsStartAt.slot.toNumber()).to.eq(startsAt);
     });
   });
 }
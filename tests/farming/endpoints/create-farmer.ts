@@ -13,7 +13,7 @@ export function test() {
     });
 
     it("has stable size", () => {
-      expect(farming.account.farmer.size).to.eq(504);
+      expect(farming.account.farmer.size).to.eq(824);
     });
 
     it("fails if farmer already exists", async () => {
@@ -0,0 +1,128 @@
+import { getAccount } from "@solana/spl-token";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { expect } from "chai";
+import { Farm } from "../farm";
+import { Farmer } from "../farmer";
+import { errLogs, provider, sleep } from "../../helpers";
+
+export function test() {
+  describe("donate_rewards", () => {
+    let farm: Farm, harvest: { mint: PublicKey; vault: PublicKey };
+
+    beforeEach("create farm", async () => {
+      farm = await Farm.init();
+
+      await farm.setMinSnapshotWindow(1);
+    });
+
+    beforeEach("create harvest", async () => {
+      // no periods, therefore any harvest comes from donations
+      harvest = await farm.addHarvest();
+    });
+
+    // stakes into the farm and makes sure the stake is recorded in a snapshot
+    async function stakedFarmer(amount: number): Promise<Farmer> {
+      const farmer = await Farmer.init(farm);
+      await farmer.airdropStakeTokens();
+      await farmer.startFarming(amount);
+
+      await sleep(1000);
+      await farm.takeSnapshot();
+      await farmer.updateEligibleHarvest();
+
+      return farmer;
+    }
+
+    async function farmerHarvest(farmer: Farmer): Promise<number> {
+      const farmerInfo = await farmer.fetch();
+      const { tokens } = (farmerInfo.harvests as any[]).find(
+        (h) => h.mint.toBase58() === harvest.mint.toBase58()
+      );
+      return tokens.amount.toNumber();
+    }
+
+    it("fails if harvest vault seed is wrong", async () => {
+      const logs = await errLogs(
+        farm.donateRewards(harvest.mint, 10, {
+          harvestVault: Keypair.generate().publicKey,
+        })
+      );
+
+      expect(logs).to.contain("seeds constraint was violated");
+    });
+
+    it("fails if donation is zero", async () => {
+      const logs = await errLogs(farm.donateRewards(harvest.mint, 0));
+
+      expect(logs).to.contain("donation amount needs to be bigger than zero");
+    });
+
+    it("fails if there's no stake to donate to", async () => {
+      await farm.takeSnapshot();
+
+      const logs = await errLogs(farm.donateRewards(harvest.mint, 100));
+
+      expect(logs).to.contain("no stake in the stake vault");
+    });
+
+    it("makes donation claimable by current stakers right away", async () => {
+      const farmer = await stakedFarmer(10);
+      expect(await farmerHarvest(farmer)).to.eq(0);
+
+      const { amount: vaultAmountBefore } = await getAccount(
+        provider.connection,
+        harvest.vault
+      );
+
+      await farm.donateRewards(harvest.mint, 1_000);
+
+      const { amount: vaultAmountAfter } = await getAccount(
+        provider.connection,
+        harvest.vault
+      );
+      expect(Number(vaultAmountAfter - vaultAmountBefore)).to.eq(1_000);
+
+      // no new snapshot or period is necessary
+      await farmer.updateEligibleHarvest();
+      expect(await farmerHarvest(farmer)).to.eq(1_000);
+
+      const harvestWallet = await farmer.harvestWalletPubkey(harvest.mint);
+      await farmer.claimEligibleHarvest([[harvest.vault, harvestWallet]]);
+
+      const { amount } = await getAccount(provider.connection, harvestWallet);
+      expect(Number(amount)).to.eq(1_000);
+    });
+
+    it("allocates whole donation to staked and vested tokens", async () => {
+      const farmerWithStake = await stakedFarmer(30);
+      // not in any snapshot yet, therefore the tokens are still vested
+      const vestedFarmer = await Farmer.init(farm);
+      await vestedFarmer.airdropStakeTokens();
+      await vestedFarmer.startFarming(70);
+
+      await farm.donateRewards(harvest.mint, 1_000);
+
+      for (const farmer of [farmerWithStake, vestedFarmer]) {
+        const harvestWallet = await farmer.harvestWalletPubkey(harvest.mint);
+        await farmer.claimEligibleHarvest([[harvest.vault, harvestWallet]]);
+      }
+      expect(await farmerHarvest(farmerWithStake)).to.eq(0);
+      expect(await farmerHarvest(vestedFarmer)).to.eq(0);
+
+      const { amount } = await getAccount(provider.connection, harvest.vault);
+      expect(Number(amount)).to.eq(0);
+    });
+
+    it("doesn't give donation to farmer who stakes afterwards", async () => {
+      const farmer = await stakedFarmer(10);
+
+      await farm.donateRewards(harvest.mint, 1_000);
+
+      const lateFarmer = await stakedFarmer(10);
+      expect(await farmerHarvest(lateFarmer)).to.eq(0);
+
+      await farmer.updateEligibleHarvest();
+      expect(await farmerHarvest(farmer)).to.eq(1_000);
+    });
+  });
+}
@@ -0,0 +1,88 @@
+import { expect } from "chai";
+import { PublicKey } from "@solana/web3.js";
+import { getAccount } from "@solana/spl-token";
+import { Farm } from "../farm";
+import { Farmer } from "../farmer";
+import { errLogs, provider, sleep } from "../../helpers";
+
+export function test() {
+  describe("exit_farm", () => {
+    let farm: Farm,
+      farmer: Farmer,
+      harvest: { mint: PublicKey; vault: PublicKey },
+      vaultWalletPairs: [PublicKey, PublicKey][];
+
+    beforeEach("create farm", async () => {
+      farm = await Farm.init();
+      await farm.setMinSnapshotWindow(1);
+    });
+
+    beforeEach("create farmer", async () => {
+      farmer = await Farmer.init(farm);
+      await farmer.airdropStakeTokens(100);
+    });
+
+    beforeEach("create harvest", async () => {
+      harvest = await farm.addHarvest();
+      await farm.newHarvestPeriod(harvest.mint, 0, 1000, 10);
+      await farm.takeSnapshot();
+
+      vaultWalletPairs = [
+        [harvest.vault, await farmer.harvestWalletPubkey(harvest.mint)],
+      ];
+    });
+
+    it("fails if authority doesn't sign", async () => {
+      await expect(
+        farmer.exitFarm(vaultWalletPairs, { skipAuthoritySignature: true })
+      ).to.be.rejectedWith(/signature verification failed/i);
+    });
+
+    it("fails if harvest of some mint is not claimed", async () => {
+      await farmer.startFarming(100);
+      await sleep(1000);
+      await farm.takeSnapshot();
+      await sleep(1000);
+
+      const logs = await errLogs(farmer.exitFarm([]));
+      expect(logs).to.contain("Claim all farmer's harvest");
+
+      const { vested, staked } = await farmer.fetch();
+      expect(vested.amount.toNumber() + staked.amount.toNumber()).to.eq(100);
+    });
+
+    it("works", async () => {
+      await farmer.startFarming(100);
+      await sleep(1000);
+      await farm.takeSnapshot();
+      await sleep(1000);
+
+      const rentBefore = await provider.connection.getBalance(
+        farmer.authority.publicKey
+      );
+      const farmerRent = await provider.connection.getBalance(
+        await farmer.id()
+      );
+
+      await farmer.exitFarm(vaultWalletPairs);
+
+      await expect(farmer.fetch()).to.be.rejected;
+
+      const stakeWallet = await farmer.stakeWallet();
+      expect(Number(stakeWallet.amount)).to.eq(100);
+
+      const harvestWallet = await getAccount(
+        provider.connection,
+        vaultWalletPairs[0][1]
+      );
+      expect(Number(harvestWallet.amount)).to.be.greaterThan(0);
+
+      const rentAfter = await provider.connection.getBalance(
+        farmer.authority.publicKey
+      );
+      // the authority paid the transaction fee
+      expect(rentAfter).to.be.greaterThan(rentBefore);
+      expect(rentAfter).to.be.lessThanOrEqual(rentBefore + farmerRent);
+    });
+  });
+}
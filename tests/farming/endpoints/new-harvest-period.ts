@@ -87,6 +87,31 @@ export function test() {
       expect(logs).to.contain("HarvestPeriodMustBeAtLeastOneSlot");
     });
 
+    it("fails if period starts before farm's harvests start", async () => {
+      const startsAt = (await getCurrentSlot()) + 100;
+      const delayedFarm = await Farm.init({ startsAt });
+      const { mint } = await delayedFarm.addHarvest();
+
+      const logs = await errLogs(
+        delayedFarm.newHarvestPeriod(mint, startsAt - 50, 10, defTps)
+      );
+      expect(logs).to.contain("HarvestPeriodMustStartAtOrAfterFarmStart");
+    });
+
+    it("interprets 0 as to start when farm's harvests start", async () => {
+      const startsAt = (await getCurrentSlot()) + 100;
+      const delayedFarm = await Farm.init({ startsAt });
+      const { mint } = await delayedFarm.addHarvest();
+
+      await delayedFarm.newHarvestPeriod(mint, 0, 10, defTps);
+
+      const farmInfo = await delayedFarm.fetch();
+      const harvest = (farmInfo.harvests as any[]).find(
+        (h) => h.mint.toString() === mint.toString()
+      );
+      expect(harvest.periods[0].startsAt.slot.toNumber()).to.eq(startsAt);
+    });
+
     it("fails with wrong signer pda", async () => {
       const fakePda = Keypair.generate().publicKey;
 
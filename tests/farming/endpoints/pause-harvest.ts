@@ -0,0 +1,110 @@
+import { airdrop, errLogs, sleep } from "../../helpers";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { expect } from "chai";
+import { Farm } from "../farm";
+import { Farmer } from "../farmer";
+
+export function test() {
+  describe("pause_harvest and resume_harvest", () => {
+    const admin = Keypair.generate();
+    let farm: Farm;
+    let pausedMint: PublicKey;
+    let runningMint: PublicKey;
+
+    before("airdrop to admin", async () => {
+      await airdrop(admin.publicKey);
+    });
+
+    beforeEach("create farm with two harvests", async () => {
+      farm = await Farm.init({ adminKeypair: admin });
+      await farm.setMinSnapshotWindow(1);
+
+      pausedMint = (await farm.addHarvest()).mint;
+      runningMint = (await farm.addHarvest()).mint;
+    });
+
+    it("fails if admin signer mismatches farm", async () => {
+      const fakeAdmin = Keypair.generate();
+      await airdrop(fakeAdmin.publicKey);
+
+      const logs = await errLogs(
+        farm.pauseHarvest(pausedMint, { admin: fakeAdmin })
+      );
+      expect(logs).to.contain("FarmAdminMismatch");
+    });
+
+    it("fails if admin is not signer", async () => {
+      await expect(
+        farm.pauseHarvest(pausedMint, { skipAdminSignature: true })
+      ).to.be.rejected;
+    });
+
+    it("fails if harvest mint doesn't exist", async () => {
+      const logs = await errLogs(
+        farm.pauseHarvest(Keypair.generate().publicKey)
+      );
+      expect(logs).to.contain("UnknownHarvestMintPubKey");
+    });
+
+    it("fails to pause harvest twice", async () => {
+      await farm.pauseHarvest(pausedMint);
+
+      const logs = await errLogs(farm.pauseHarvest(pausedMint));
+      expect(logs).to.contain("Harvest is already paused");
+    });
+
+    it("fails to resume harvest which is not paused", async () => {
+      const logs = await errLogs(farm.resumeHarvest(pausedMint));
+      expect(logs).to.contain("Harvest is not paused");
+    });
+
+    it("stops accruing paused harvest while others continue", async () => {
+      const tps = 10;
+      await farm.newHarvestPeriod(pausedMint, 0, 1_000, tps);
+      await farm.newHarvestPeriod(runningMint, 0, 1_000, tps);
+      await farm.takeSnapshot();
+
+      const farmer = await Farmer.init(farm);
+      await farmer.airdropStakeTokens(10);
+      await farmer.startFarming(10);
+      await sleep(1000);
+      await farm.takeSnapshot();
+      await sleep(1000);
+
+      await farm.pauseHarvest(pausedMint);
+      const farmInfo = await farm.fetch();
+      const pausedHarvest = (farmInfo.harvests as any[]).find(
+        (h) => h.mint.toString() === pausedMint.toString()
+      );
+      expect(pausedHarvest.pausedAt.slot.toNumber()).to.be.greaterThan(0);
+
+      await farmer.updateEligibleHarvest();
+      const before = await farmer.fetch();
+
+      await sleep(1000);
+      await farm.takeSnapshot();
+      await farmer.updateEligibleHarvest();
+      const after = await farmer.fetch();
+
+      const tokensOf = (farmerInfo: any, mint: PublicKey) =>
+        (farmerInfo.harvests as any[])
+          .find((h) => h.mint.toString() === mint.toString())
+          .tokens.amount.toNumber();
+
+      expect(tokensOf(after, pausedMint)).to.eq(tokensOf(before, pausedMint));
+      expect(tokensOf(after, runningMint)).to.be.greaterThan(
+        tokensOf(before, runningMint)
+      );
+
+      await farm.resumeHarvest(pausedMint);
+      await sleep(1000);
+      await farm.takeSnapshot();
+      await farmer.updateEligibleHarvest();
+      const resumed = await farmer.fetch();
+
+      expect(tokensOf(resumed, pausedMint)).to.be.greaterThan(
+        tokensOf(after, pausedMint)
+      );
+    });
+  });
+}
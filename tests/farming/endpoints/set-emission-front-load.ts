@@ -0,0 +1,46 @@
+import { expect } from "chai";
+import { Keypair } from "@solana/web3.js";
+import { Farm } from "../farm";
+import { errLogs } from "../../helpers";
+
+export function test() {
+  describe("set_emission_front_load", () => {
+    let farm: Farm;
+
+    beforeEach("create farm", async () => {
+      farm = await Farm.init();
+    });
+
+    it("fails if signer isn't admin", async () => {
+      const logs = await errLogs(
+        farm.setEmissionFrontLoad(50, { admin: Keypair.generate() })
+      );
+
+      expect(logs).to.contain("FarmAdminMismatch");
+    });
+
+    it("fails if front-load is out of bounds", async () => {
+      const logs = await errLogs(farm.setEmissionFrontLoad(101));
+
+      expect(logs).to.contain("Emission front-load can be at most 100%");
+    });
+
+    it("works", async () => {
+      await farm.setEmissionFrontLoad(50);
+
+      const { emissionFrontLoadPercent } = await farm.fetch();
+      expect(emissionFrontLoadPercent.toNumber()).to.eq(50);
+    });
+
+    it("applies only to harvest periods created afterwards", async () => {
+      const harvest = await farm.addHarvest();
+      await farm.newHarvestPeriod(harvest.mint, 0, 1_000, 100);
+
+      await farm.setEmissionFrontLoad(50);
+
+      const { harvests } = await farm.fetch();
+      const [running] = (harvests as any[])[0].periods;
+      expect(running.frontLoadPercent.toNumber()).to.eq(0);
+    });
+  });
+}
@@ -0,0 +1,218 @@
+import { expect } from "chai";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { Farm } from "../farm";
+import { Farmer } from "../farmer";
+import { errLogs, getCurrentSlot, sleep } from "../../helpers";
+
+export function test() {
+  describe("set_emission_multiplier", () => {
+    const tokensPerSlot = 100;
+
+    let farm: Farm;
+    let harvestMint: PublicKey;
+
+    beforeEach("create farm", async () => {
+      farm = await Farm.init();
+
+      await farm.setMinSnapshotWindow(1);
+      const harvest = await farm.addHarvest();
+      harvestMint = harvest.mint;
+      await farm.newHarvestPeriod(harvestMint, 0, 1_000, tokensPerSlot);
+    });
+
+    // stakes into the farm for a few snapshot windows and returns the harvest
+    // the farmer has earned
+    async function earnHarvest(): Promise<number> {
+      const farmer = await Farmer.init(farm);
+      await farmer.airdropStakeTokens();
+
+      await farm.takeSnapshot();
+      await farmer.startFarming(10);
+
+      await sleep(1000);
+      await farm.takeSnapshot();
+      await sleep(1000);
+      await farm.takeSnapshot();
+
+      await farmer.updateEligibleHarvest();
+
+      const farmerInfo = await farmer.fetch();
+      const { tokens } = (farmerInfo.harvests as any[]).find(
+        (h) => h.mint.toBase58() === harvestMint.toBase58()
+      );
+      return tokens.amount.toNumber();
+    }
+
+    it("fails if signer isn't admin", async () => {
+      const slot = await getCurrentSlot();
+      const logs = await errLogs(
+        farm.setEmissionMultiplier(
+          harvestMint,
+          200,
+          slot + 100,
+          slot + 200,
+          { admin: Keypair.generate() }
+        )
+      );
+
+      expect(logs).to.contain("FarmAdminMismatch");
+    });
+
+    it("fails if multiplier is out of bounds", async () => {
+      const slot = await getCurrentSlot();
+
+      const logsLow = await errLogs(
+        farm.setEmissionMultiplier(harvestMint, 100, slot + 100, slot + 200)
+      );
+      expect(logsLow).to.contain("Emission multiplier must be more than");
+
+      const logsHigh = await errLogs(
+        farm.setEmissionMultiplier(
+          harvestMint,
+          1_001,
+          slot + 100,
+          slot + 200
+        )
+      );
+      expect(logsHigh).to.contain("Emission multiplier must be more than");
+    });
+
+    it("fails if event ends before it starts", async () => {
+      const slot = await getCurrentSlot();
+      const logs = await errLogs(
+        farm.setEmissionMultiplier(harvestMint, 200, slot + 200, slot + 100)
+      );
+
+      expect(logs).to.contain("HarvestPeriodCannotHaveNegativeLength");
+    });
+
+    it("fails if event doesn't start in future", async () => {
+      const slot = await getCurrentSlot();
+      const logs = await errLogs(
+        farm.setEmissionMultiplier(harvestMint, 200, slot, slot + 100)
+      );
+
+      expect(logs).to.contain("must start after the current slot");
+    });
+
+    it("fails if admin doesn't deposit extra tokens", async () => {
+      const slot = await getCurrentSlot();
+      const logs = await errLogs(
+        farm.setEmissionMultiplier(
+          harvestMint,
+          200,
+          slot + 100,
+          slot + 199,
+          { depositTokens: 0 }
+        )
+      );
+
+      expect(logs).to.contain("Insufficient tokens in harvest wallet");
+    });
+
+    it("deposits extra tokens and splits harvest period", async () => {
+      const vaultBefore = await farm.harvestVaultAccount(harvestMint);
+
+      const slot = await getCurrentSlot();
+      await farm.setEmissionMultiplier(
+        harvestMint,
+        300,
+        slot + 100,
+        slot + 199
+      );
+
+      const vaultAfter = await farm.harvestVaultAccount(harvestMint);
+      expect(Number(vaultAfter.amount) - Number(vaultBefore.amount)).to.eq(
+        100 * tokensPerSlot * 2
+      );
+
+      const { harvests } = await farm.fetch();
+      const [after, during, before] = (harvests as any[])[0].periods;
+      expect(after.startsAt.slot.toNumber()).to.eq(slot + 200);
+      expect(after.tps.amount.toNumber()).to.eq(tokensPerSlot);
+      expect(during.startsAt.slot.toNumber()).to.eq(slot + 100);
+      expect(during.endsAt.slot.toNumber()).to.eq(slot + 199);
+      expect(during.tps.amount.toNumber()).to.eq(3 * tokensPerSlot);
+      expect(before.endsAt.slot.toNumber()).to.eq(slot + 99);
+      expect(before.tps.amount.toNumber()).to.eq(tokensPerSlot);
+    });
+
+    it("returns all tokens of split launch when it's rescheduled", async () => {
+      const vaultBefore = await farm.harvestVaultAccount(harvestMint);
+
+      const slot = await getCurrentSlot();
+      await farm.newHarvestPeriod(
+        harvestMint,
+        slot + 2_000,
+        100,
+        tokensPerSlot
+      );
+      await farm.setEmissionMultiplier(
+        harvestMint,
+        200,
+        slot + 2_020,
+        slot + 2_039
+      );
+
+      await farm.newHarvestPeriod(
+        harvestMint,
+        slot + 3_000,
+        10,
+        tokensPerSlot
+      );
+
+      const vaultAfter = await farm.harvestVaultAccount(harvestMint);
+      expect(Number(vaultAfter.amount) - Number(vaultBefore.amount)).to.eq(
+        10 * tokensPerSlot
+      );
+
+      const { harvests } = await farm.fetch();
+      const [rescheduled, running, empty] = (harvests as any[])[0].periods;
+      expect(rescheduled.startsAt.slot.toNumber()).to.eq(slot + 3_000);
+      expect(rescheduled.endsAt.slot.toNumber()).to.eq(slot + 3_009);
+      expect(running.endsAt.slot.toNumber()).to.be.lessThan(slot + 1_000);
+      expect(empty.endsAt.slot.toNumber()).to.eq(0);
+    });
+
+    it("boosts accrual during event window", async () => {
+      const harvestWithoutEvent = await earnHarvest();
+      expect(harvestWithoutEvent).to.be.greaterThan(0);
+
+      farm = await Farm.init();
+      await farm.setMinSnapshotWindow(1);
+      harvestMint = (await farm.addHarvest()).mint;
+      await farm.newHarvestPeriod(harvestMint, 0, 1_000, tokensPerSlot);
+      const slot = await getCurrentSlot();
+      await farm.setEmissionMultiplier(
+        harvestMint,
+        1_000,
+        slot + 5,
+        slot + 999
+      );
+
+      // roughly the same number of slots is accrued, but 10x the emission
+      const harvestDuringEvent = await earnHarvest();
+      expect(harvestDuringEvent).to.be.greaterThan(2 * harvestWithoutEvent);
+    });
+
+    it("doesn't boost accrual outside event window", async () => {
+      const harvestWithoutEvent = await earnHarvest();
+      expect(harvestWithoutEvent).to.be.greaterThan(0);
+
+      farm = await Farm.init();
+      await farm.setMinSnapshotWindow(1);
+      harvestMint = (await farm.addHarvest()).mint;
+      await farm.newHarvestPeriod(harvestMint, 0, 1_000, tokensPerSlot);
+      const slot = await getCurrentSlot();
+      await farm.setEmissionMultiplier(
+        harvestMint,
+        1_000,
+        slot + 900,
+        slot + 999
+      );
+
+      const harvestBeforeEvent = await earnHarvest();
+      expect(harvestBeforeEvent).to.be.lessThan(2 * harvestWithoutEvent);
+    });
+  });
+}
@@ -0,0 +1,63 @@
+import { expect } from "chai";
+import { Keypair } from "@solana/web3.js";
+import { Farm } from "../farm";
+import { Farmer } from "../farmer";
+import { errLogs, getCurrentSlot } from "../../helpers";
+
+export function test() {
+  describe("set_lockup_slots", () => {
+    let farm: Farm, farmer: Farmer;
+
+    beforeEach("create farm", async () => {
+      farm = await Farm.init();
+    });
+
+    beforeEach("create farmer", async () => {
+      farmer = await Farmer.init(farm);
+      await farmer.airdropStakeTokens();
+    });
+
+    it("fails if signer isn't admin", async () => {
+      const logs = await errLogs(
+        farm.setLockupSlots(10, { admin: Keypair.generate() })
+      );
+
+      expect(logs).to.contain("FarmAdminMismatch");
+    });
+
+    it("fails if lockup is longer than max", async () => {
+      const logs = await errLogs(farm.setLockupSlots(6_480_001));
+
+      expect(logs).to.contain("Lockup cannot be longer than 6480000 slots");
+    });
+
+    it("doesn't apply lockup increase until its slot", async () => {
+      const slotBefore = await getCurrentSlot();
+      await farm.setLockupSlots(10_000);
+
+      const {
+        lockupSlots,
+        pendingLockupSlots,
+        pendingLockupEffectiveAt,
+      } = await farm.fetch();
+      expect(lockupSlots.toNumber()).to.eq(0);
+      expect(pendingLockupSlots.toNumber()).to.eq(10_000);
+      expect(pendingLockupEffectiveAt.slot.toNumber()).to.be.greaterThan(
+        slotBefore
+      );
+
+      // the pending lockup doesn't apply yet, farmer can unstake
+      await farmer.startFarming(10);
+      await farmer.stopFarming(10);
+    });
+
+    it("applies lockup decrease immediately", async () => {
+      await farm.setLockupSlots(10_000);
+      await farm.setLockupSlots(0);
+
+      const { lockupSlots, pendingLockupEffectiveAt } = await farm.fetch();
+      expect(lockupSlots.toNumber()).to.eq(0);
+      expect(pendingLockupEffectiveAt.slot.toNumber()).to.eq(0);
+    });
+  });
+}
@@ -83,6 +83,45 @@ export function test() {
       );
     });
 
+    it("fails if farm's harvests ended and farming is blocked", async () => {
+      const harvest = await farm.addHarvest();
+      await farm.newHarvestPeriod(harvest.mint, 0, 1, 10);
+      await farm.setBlockFarmingOnceEnded(true);
+      await farmer.startFarming(10);
+
+      await sleep(1000);
+
+      const logs = await errLogs(farmer.startFarming(10));
+      expect(logs).to.contain(
+        "Farm's harvests have ended and it doesn't accept new stake"
+      );
+
+      // existing stakers can still withdraw
+      await farmer.stopFarming(10);
+      const farmerInfo = await farmer.fetch();
+      expect(farmerInfo.staked.amount.toNumber()).to.eq(0);
+      expect(farmerInfo.vested.amount.toNumber()).to.eq(0);
+    });
+
+    it("allows farming after harvests ended if not blocked", async () => {
+      const harvest = await farm.addHarvest();
+      await farm.newHarvestPeriod(harvest.mint, 0, 1, 10);
+
+      await sleep(1000);
+
+      await farmer.startFarming(10);
+      const farmerInfo = await farmer.fetch();
+      expect(farmerInfo.vested.amount.toNumber()).to.eq(10);
+    });
+
+    it("fails to block farming if signer isn't admin", async () => {
+      const logs = await errLogs(
+        farm.setBlockFarmingOnceEnded(true, { admin: Keypair.generate() })
+      );
+
+      expect(logs).to.contain("FarmAdminMismatch");
+    });
+
     it("adds farmer's funds to vested", async () => {
       await farmer.startFarming(10);
       const farmerInfo1 = await farmer.fetch();
@@ -226,5 +226,28 @@ export function test() {
       expect(farmerInfoAfter.staked.amount.toNumber()).to.eq(0);
       expect(farmerInfoAfter.staked.amount.toNumber()).to.eq(0);
     });
+
+    it("returns time-weighted average stake", async () => {
+      await farmer.airdropStakeTokens(100);
+      await farmer.startFarming(100);
+      await sleep(1000);
+
+      const beforeUnstake = await farmer.averageStake();
+      expect(beforeUnstake.averageStake).to.eq(100);
+      expect(beforeUnstake.until).to.be.greaterThan(beforeUnstake.since);
+
+      await farmer.stopFarming(50);
+      await sleep(1000);
+
+      const afterUnstake = await farmer.averageStake();
+      expect(afterUnstake.since).to.eq(beforeUnstake.since);
+      expect(afterUnstake.averageStake).to.be.greaterThan(50);
+      expect(afterUnstake.averageStake).to.be.lessThan(100);
+
+      const slots = afterUnstake.until - afterUnstake.since;
+      expect(afterUnstake.cumulativeStakeSlots.divn(slots).toNumber()).to.eq(
+        afterUnstake.averageStake
+      );
+    });
   });
 }
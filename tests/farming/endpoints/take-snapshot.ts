@@ -1,7 +1,16 @@
-import { PublicKey } from "@solana/web3.js";
+import { Keypair, PublicKey } from "@solana/web3.js";
 import { expect } from "chai";
-import { payer, errLogs, sleep, assertApproxCurrentSlot } from "../../helpers";
+import { getAccount } from "@solana/spl-token";
+import {
+  payer,
+  errLogs,
+  sleep,
+  assertApproxCurrentSlot,
+  getCurrentSlot,
+  provider,
+} from "../../helpers";
 import { Farm } from "../farm";
+import { Farmer } from "../farmer";
 
 export function test() {
   describe("take_snapshot", () => {
@@ -84,6 +93,136 @@ export function test() {
       }
     });
 
+    it("corrects latest snapshot", async () => {
+      await farm.setMinSnapshotWindow(1);
+      await farm.transferToStakeVault(depositorStakeWallet, 100);
+      await farm.takeSnapshot();
+      await farm.transferToStakeVault(depositorStakeWallet, 50);
+
+      await farm.correctSnapshot(140);
+
+      const { snapshots } = await farm.fetch();
+      const ringBuffer = snapshots.ringBuffer as any[];
+      expect(snapshots.ringBufferTip.toNumber()).to.eq(1);
+      expect(ringBuffer[1].staked.amount.toNumber()).to.eq(140);
+    });
+
+    it("fails to lower latest snapshot", async () => {
+      await farm.setMinSnapshotWindow(1);
+      await farm.transferToStakeVault(depositorStakeWallet, 100);
+      await farm.takeSnapshot();
+
+      const logs = await errLogs(farm.correctSnapshot(60));
+
+      expect(logs).to.contain(
+        "Corrected amount must be more than 100 recorded tokens"
+      );
+    });
+
+    it("fails to correct snapshot above stake vault balance", async () => {
+      await farm.setMinSnapshotWindow(1);
+      await farm.transferToStakeVault(depositorStakeWallet, 100);
+      await farm.takeSnapshot();
+
+      const logs = await errLogs(farm.correctSnapshot(101));
+
+      expect(logs).to.contain("at most 100 tokens in the stake vault");
+    });
+
+    it("pays corrected share of harvest and lets farmer unstake", async () => {
+      const tps = 10;
+      await farm.setMinSnapshotWindow(1);
+      const harvest = await farm.addHarvest();
+      await farm.newHarvestPeriod(harvest.mint, 0, 1_000, tps);
+
+      const farmer = await Farmer.init(farm);
+      await farmer.airdropStakeTokens(100);
+      await farmer.startFarming(100);
+      await sleep(1000);
+      await farm.takeSnapshot();
+      const earningFromSlot = await getCurrentSlot();
+
+      // the snapshot missed tokens which are in the stake vault
+      await farm.transferToStakeVault(depositorStakeWallet, 100);
+      await farm.correctSnapshot(200);
+
+      await sleep(1000);
+      await farm.takeSnapshot();
+      await sleep(1000);
+      await farm.takeSnapshot();
+
+      await farmer.updateEligibleHarvest();
+      const earningToSlot = await getCurrentSlot();
+
+      const { harvests } = await farmer.fetch();
+      const { tokens } = (harvests as any[]).find(
+        (h) => h.mint.toBase58() === harvest.mint.toBase58()
+      );
+      // the farmer owns half of the corrected stake
+      expect(tokens.amount.toNumber()).to.be.approximately(
+        ((earningToSlot - earningFromSlot) * tps) / 2,
+        tps
+      );
+
+      const harvestWallet = await farmer.harvestWalletPubkey(harvest.mint);
+      await farmer.claimEligibleHarvest([[harvest.vault, harvestWallet]]);
+      const harvestWalletInfo = await getAccount(
+        provider.connection,
+        harvestWallet
+      );
+      expect(Number(harvestWalletInfo.amount)).to.be.at.least(
+        tokens.amount.toNumber()
+      );
+
+      await farmer.stopFarming(100);
+      const stakeWallet = await farmer.stakeWallet();
+      expect(Number(stakeWallet.amount)).to.eq(100);
+      const stakeVault = await farm.stakeVaultInfo();
+      expect(Number(stakeVault.amount)).to.eq(100);
+    });
+
+    it("fails to correct snapshot if signer isn't admin", async () => {
+      await farm.setMinSnapshotWindow(1);
+      await farm.transferToStakeVault(depositorStakeWallet, 100);
+      await farm.takeSnapshot();
+
+      const logs = await errLogs(
+        farm.correctSnapshot(60, { admin: Keypair.generate() })
+      );
+
+      expect(logs).to.contain("FarmAdminMismatch");
+    });
+
+    it("reads snapshots back page by page", async () => {
+      await farm.setMinSnapshotWindow(1);
+
+      for (let i = 1; i <= 4; i++) {
+        await farm.transferToStakeVault(depositorStakeWallet, 10);
+        await farm.takeSnapshot();
+        sleep(1_000);
+      }
+
+      const { snapshots } = await farm.fetch();
+      const ringBuffer = snapshots.ringBuffer as any[];
+
+      // the history starts with the empty snapshot the farm is created with
+      const firstPage = await farm.snapshotsPage(0, 3);
+      expect(firstPage.total).to.eq(5);
+      expect(firstPage.snapshots).to.have.lengthOf(3);
+      const secondPage = await farm.snapshotsPage(3, 3);
+      expect(secondPage.total).to.eq(5);
+      expect(secondPage.snapshots).to.have.lengthOf(2);
+
+      const read = [...firstPage.snapshots, ...secondPage.snapshots];
+      read.forEach(({ startedAt, staked }, index) => {
+        expect(startedAt).to.eq(ringBuffer[index].startedAt.slot.toNumber());
+        expect(staked).to.eq(index * 10);
+      });
+
+      const emptyPage = await farm.snapshotsPage(5, 3);
+      expect(emptyPage.snapshots).to.be.empty;
+    });
+
     it("is initialised to defaulted values", async () => {
       const { snapshots } = await farm.fetch();
 
@@ -0,0 +1,125 @@
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { getAccount } from "@solana/spl-token";
+import { expect } from "chai";
+import { Farm } from "../farm";
+import { Farmer } from "../farmer";
+import { errLogs, provider, sleep } from "../../helpers";
+
+export function test() {
+  describe("transfer_farmer", () => {
+    let farm: Farm, farmer: Farmer;
+
+    beforeEach("create farm", async () => {
+      farm = await Farm.init();
+    });
+
+    beforeEach("create farmer", async () => {
+      farmer = await Farmer.init(farm);
+    });
+
+    it("fails if authority isn't signer", async () => {
+      await expect(
+        farmer.transfer(Keypair.generate(), {
+          skipAuthoritySignature: true,
+        })
+      ).to.be.rejected;
+    });
+
+    it("fails on wrong authority signer", async () => {
+      const otherAuthority = Keypair.generate();
+      await Farmer.init(farm, { authority: otherAuthority });
+
+      const logs = await errLogs(
+        farmer.transfer(Keypair.generate(), {
+          authority: otherAuthority,
+        })
+      );
+      expect(logs).to.contain("Authority does not own this farmer");
+    });
+
+    it("fails if new authority is default pubkey", async () => {
+      const logs = await errLogs(
+        farmer.transfer(Keypair.generate(), {
+          newAuthority: PublicKey.default,
+        })
+      );
+      expect(logs).to.contain("New authority mustn't be the default pubkey");
+    });
+
+    it("fails if new authority is the current one", async () => {
+      const logs = await errLogs(
+        farmer.transfer(Keypair.generate(), {
+          newAuthority: farmer.authority.publicKey,
+        })
+      );
+      expect(logs).to.contain(
+        "New authority must differ from the current one"
+      );
+    });
+
+    it("fails if new authority already has a farmer", async () => {
+      const newAuthority = Keypair.generate();
+      await Farmer.init(farm, { authority: newAuthority });
+
+      await expect(farmer.transfer(newAuthority)).to.be.rejected;
+    });
+
+    it("transfers position and new owner claims harvest", async () => {
+      const tokensPerSlot = 10;
+      await farm.setMinSnapshotWindow(1);
+      const harvest = await farm.addHarvest();
+      await farm.newHarvestPeriod(harvest.mint, 0, 1000, tokensPerSlot);
+      await farm.takeSnapshot();
+
+      await farmer.airdropStakeTokens(10);
+      await farmer.startFarming(10);
+      await sleep(1000);
+      await farm.takeSnapshot();
+      await sleep(1000);
+      await farm.takeSnapshot();
+      await farmer.updateEligibleHarvest();
+
+      const farmerInfoBefore = await farmer.fetch();
+
+      const newFarmer = await farmer.transfer();
+
+      await expect(farmer.fetch()).to.be.rejected;
+
+      const newFarmerInfo = await newFarmer.fetch();
+      expect(newFarmerInfo.authority).to.deep.eq(
+        newFarmer.authority.publicKey
+      );
+      expect(newFarmerInfo.farm).to.deep.eq(farm.id);
+      expect(newFarmerInfo.staked.amount.toNumber()).to.eq(
+        farmerInfoBefore.staked.amount.toNumber()
+      );
+      expect(newFarmerInfo.vested.amount.toNumber()).to.eq(
+        farmerInfoBefore.vested.amount.toNumber()
+      );
+      expect(newFarmerInfo.calculateNextHarvestFrom.slot.toNumber()).to.eq(
+        farmerInfoBefore.calculateNextHarvestFrom.slot.toNumber()
+      );
+      const harvestBefore = (farmerInfoBefore.harvests as any[]).find(
+        (h) => h.mint.toBase58() === harvest.mint.toBase58()
+      );
+      expect(harvestBefore.tokens.amount.toNumber()).to.be.greaterThan(0);
+
+      // the old owner cannot claim anymore
+      const oldOwnerWallet = await farmer.harvestWalletPubkey(harvest.mint);
+      await expect(
+        farmer.claimEligibleHarvest([[harvest.vault, oldOwnerWallet]])
+      ).to.be.rejected;
+
+      const newOwnerWallet = await newFarmer.harvestWalletPubkey(harvest.mint);
+      await newFarmer.claimEligibleHarvest([[harvest.vault, newOwnerWallet]]);
+
+      const newOwnerWalletInfo = await getAccount(
+        provider.connection,
+        newOwnerWallet
+      );
+      expect(Number(newOwnerWalletInfo.amount)).to.be.greaterThanOrEqual(
+        harvestBefore.tokens.amount.toNumber()
+      );
+    });
+  });
+}
@@ -2,6 +2,7 @@ import { airdrop, provider, errLogs } from "../../helpers";
 import { Keypair } from "@solana/web3.js";
 import { expect } from "chai";
 import { Farm } from "../farm";
+import { Farmer } from "../farmer";
 
 export function test() {
   describe("whitelist_farm_for_compounding", () => {
@@ -103,6 +104,31 @@ export function test() {
       expect(whitelistPdaAccount).not.to.eq(null);
     });
 
+    it("makes farmer eligible for compounding", async () => {
+      const farmer = await Farmer.init(sourceFarm);
+      await sourceFarm.addHarvest({ harvestMint: sourceFarm.stakeMint });
+
+      const before = await farmer.compoundingEligibility();
+      expect(before.isWhitelisted).to.eq(false);
+      expect(before.harvestsTargetStakeMint).to.eq(true);
+      expect(before.eligibleHarvest.toNumber()).to.eq(0);
+
+      await sourceFarm.whitelistFarmForCompounding({
+        targetFarm: sourceFarm.id,
+      });
+
+      const after = await farmer.compoundingEligibility();
+      expect(after.isWhitelisted).to.eq(true);
+      expect(after.harvestsTargetStakeMint).to.eq(true);
+
+      await sourceFarm.dewhitelistFarmForCompounding({
+        targetFarm: sourceFarm.id,
+      });
+
+      const dewhitelisted = await farmer.compoundingEligibility();
+      expect(dewhitelisted.isWhitelisted).to.eq(false);
+    });
+
     it("works", async () => {
       const targetFarm = await Farm.init({ adminKeypair: targetAdmin });
 
@@ -1,6 +1,7 @@
 import { farming, payer, provider } from "../helpers";
 import { Keypair, PublicKey } from "@solana/web3.js";
 import {
+  approve,
   createAccount,
   createMint,
   transfer,
@@ -21,6 +22,7 @@ export interface InitFarmArgs {
   skipKeypairSignature: boolean;
   stakeVault: PublicKey;
   stakeMint: PublicKey;
+  startsAt: number;
 }
 
 export interface AddHarvestArgs {
@@ -47,12 +49,80 @@ export interface TakeSnapshotArgs {
   clock: PublicKey;
 }
 
+export interface CrankSnapshotArgs {
+  stakeVault: PublicKey;
+}
+
+export interface CorrectSnapshotArgs {
+  admin: Keypair;
+  stakeVault: PublicKey;
+  skipAdminSignature: boolean;
+}
+
+export interface SetKeeperRewardArgs {
+  admin: Keypair;
+  farm: PublicKey;
+  skipAdminSignature: boolean;
+}
+
 export interface SetMinSnapshotWindowArgs {
   admin: Keypair;
   farm: PublicKey;
   skipAdminSignature: boolean;
 }
 
+export interface PauseHarvestArgs {
+  admin: Keypair;
+  farm: PublicKey;
+  skipAdminSignature: boolean;
+}
+
+export interface SetBlockFarmingOnceEndedArgs {
+  admin: Keypair;
+  farm: PublicKey;
+  skipAdminSignature: boolean;
+}
+
+export interface SetClaimCooldownSlotsArgs {
+  admin: Keypair;
+  farm: PublicKey;
+  skipAdminSignature: boolean;
+}
+
+export interface SetClaimVestingSlotsArgs {
+  admin: Keypair;
+  farm: PublicKey;
+  skipAdminSignature: boolean;
+}
+
+export interface SetLockupSlotsArgs {
+  admin: Keypair;
+  farm: PublicKey;
+  skipAdminSignature: boolean;
+}
+
+export interface SetEmissionFrontLoadArgs {
+  admin: Keypair;
+  farm: PublicKey;
+  skipAdminSignature: boolean;
+}
+
+export interface SetEmissionMultiplierArgs {
+  admin: Keypair;
+  farm: PublicKey;
+  skipAdminSignature: boolean;
+  harvestVault: PublicKey;
+  harvestWallet: PublicKey;
+  depositTokens: number;
+}
+
+export interface DonateRewardsArgs {
+  donor: Keypair;
+  donorWallet: PublicKey;
+  farm: PublicKey;
+  harvestVault: PublicKey;
+}
+
 export interface SetFarmOwnerArgs {
   admin: Keypair;
   farm: PublicKey;
@@ -87,6 +157,11 @@ export interface CompoundSameFarmArgs {
   whitelistCompounding: PublicKey;
 }
 
+export interface SnapshotsPage {
+  total: number;
+  snapshots: { startedAt: number; staked: number }[];
+}
+
 export interface CompoundAcrossFarmsArgs {
   sourceFarm: PublicKey;
   targetFarm: PublicKey;
@@ -116,6 +191,7 @@ export class Farm {
     const farmKeypair = input.keypair ?? Keypair.generate();
     const skipAdminSignature = input.skipAdminSignature ?? false;
     const skipCreateFarm = input.skipCreateFarm ?? false;
+    const startsAt = input.startsAt ?? 0;
     const skipKeypairSignature = input.skipAdminSignature ?? skipCreateFarm;
     const farmSignerPda =
       input.pda ??
@@ -162,7 +238,7 @@ export class Farm {
     }
 
     await farming.methods
-      .createFarm()
+      .createFarm({ slot: new BN(startsAt) })
       .accounts({
         admin: adminKeypair.publicKey,
         farm: farmKeypair.publicKey,
@@ -312,6 +388,138 @@ export class Farm {
       .rpc();
   }
 
+  /**
+   * Simulates the read-only endpoint and decodes its return data.
+   */
+  public async snapshotsPage(
+    offset: number,
+    limit: number
+  ): Promise<SnapshotsPage> {
+    const { raw } = await farming.methods
+      .getSnapshots(new BN(offset), new BN(limit))
+      .accounts({ farm: this.id })
+      .simulate();
+
+    const prefix = `Program return: ${farming.programId} `;
+    const returnLog = raw.find((log) => log.startsWith(prefix));
+    const data = Buffer.from(returnLog.slice(prefix.length), "base64");
+
+    const len = data.readUInt32LE(8);
+    const snapshots = [];
+    for (let i = 0; i < len; i++) {
+      const start = 12 + i * 16;
+      snapshots.push({
+        startedAt: new BN(data.subarray(start, start + 8), "le").toNumber(),
+        staked: new BN(
+          data.subarray(start + 8, start + 16),
+          "le"
+        ).toNumber(),
+      });
+    }
+
+    return {
+      total: new BN(data.subarray(0, 8), "le").toNumber(),
+      snapshots,
+    };
+  }
+
+  public async crankSnapshot(
+    keeperRewardVault: PublicKey,
+    keeperWallet: PublicKey,
+    input: Partial<CrankSnapshotArgs> = {}
+  ) {
+    const stakeVault = input.stakeVault ?? (await this.stakeVault());
+
+    await farming.methods
+      .crankSnapshot()
+      .accounts({
+        farm: this.id,
+        stakeVault,
+        farmSignerPda: await this.signerPda(),
+        keeperRewardVault,
+        keeperWallet,
+      })
+      .rpc();
+  }
+
+  public async correctSnapshot(
+    staked: number,
+    input: Partial<CorrectSnapshotArgs> = {}
+  ) {
+    const admin = input.admin ?? this.admin;
+    const stakeVault = input.stakeVault ?? (await this.stakeVault());
+    const skipAdminSignature = input.skipAdminSignature ?? false;
+
+    const signers = [];
+    if (!skipAdminSignature) {
+      signers.push(admin);
+    }
+
+    await farming.methods
+      .correctSnapshot({ amount: new BN(staked) })
+      .accounts({
+        admin: admin.publicKey,
+        farm: this.id,
+        stakeVault,
+      })
+      .signers(signers)
+      .rpc();
+  }
+
+  /**
+   * Creates admin's token account of given harvest mint, funds it and approves
+   * the farm signer to transfer the keeper rewards out of it.
+   */
+  public async createKeeperRewardVault(
+    harvestMint: PublicKey,
+    allowance: number
+  ): Promise<PublicKey> {
+    const keeperRewardVault = await createAccount(
+      provider.connection,
+      payer,
+      harvestMint,
+      this.admin.publicKey,
+      Keypair.generate()
+    );
+    await this.airdropHarvestTokens(harvestMint, keeperRewardVault);
+    await approve(
+      provider.connection,
+      payer,
+      keeperRewardVault,
+      await this.signerPda(),
+      this.admin,
+      allowance
+    );
+
+    return keeperRewardVault;
+  }
+
+  public async setKeeperReward(
+    keeperRewardVault: PublicKey,
+    tokens: number,
+    input: Partial<SetKeeperRewardArgs> = {}
+  ) {
+    const farm = input.farm ?? this.id;
+    const admin = input.admin ?? this.admin;
+    const skipAdminSignature = input.skipAdminSignature ?? false;
+
+    const signers = [];
+    if (!skipAdminSignature) {
+      signers.push(admin);
+    }
+
+    await farming.methods
+      .setKeeperReward({ amount: new BN(tokens) })
+      .accounts({
+        admin: admin.publicKey,
+        farm,
+        farmSignerPda: await this.signerPda(),
+        keeperRewardVault,
+      })
+      .signers(signers)
+      .rpc();
+  }
+
   // To test take_snapshot endpoint to see if the snapshots store the correct amount staked tokens
   // we first need to transfer tokens to the stakeVault
   public async transferToStakeVault(
@@ -354,6 +562,246 @@ export class Farm {
       .rpc();
   }
 
+  public async setBlockFarmingOnceEnded(
+    block: boolean,
+    input: Partial<SetBlockFarmingOnceEndedArgs> = {}
+  ) {
+    const farm = input.farm ?? this.id;
+    const admin = input.admin ?? this.admin;
+    const skipAdminSignature = input.skipAdminSignature ?? false;
+
+    const signers = [];
+    if (!skipAdminSignature) {
+      signers.push(admin);
+    }
+
+    await farming.methods
+      .setBlockFarmingOnceEnded(block)
+      .accounts({
+        admin: admin.publicKey,
+        farm,
+      })
+      .signers(signers)
+      .rpc();
+  }
+
+  public async setClaimCooldownSlots(
+    claimCooldownSlots: number,
+    input: Partial<SetClaimCooldownSlotsArgs> = {}
+  ) {
+    const farm = input.farm ?? this.id;
+    const admin = input.admin ?? this.admin;
+    const skipAdminSignature = input.skipAdminSignature ?? false;
+
+    const signers = [];
+    if (!skipAdminSignature) {
+      signers.push(admin);
+    }
+
+    await farming.methods
+      .setClaimCooldownSlots(new BN(claimCooldownSlots))
+      .accounts({
+        admin: admin.publicKey,
+        farm,
+      })
+      .signers(signers)
+      .rpc();
+  }
+
+  public async setClaimVestingSlots(
+    claimVestingSlots: number,
+    input: Partial<SetClaimVestingSlotsArgs> = {}
+  ) {
+    const farm = input.farm ?? this.id;
+    const admin = input.admin ?? this.admin;
+    const skipAdminSignature = input.skipAdminSignature ?? false;
+
+    const signers = [];
+    if (!skipAdminSignature) {
+      signers.push(admin);
+    }
+
+    await farming.methods
+      .setClaimVestingSlots(new BN(claimVestingSlots))
+      .accounts({
+        admin: admin.publicKey,
+        farm,
+      })
+      .signers(signers)
+      .rpc();
+  }
+
+  public async setLockupSlots(
+    lockupSlots: number,
+    input: Partial<SetLockupSlotsArgs> = {}
+  ) {
+    const farm = input.farm ?? this.id;
+    const admin = input.admin ?? this.admin;
+    const skipAdminSignature = input.skipAdminSignature ?? false;
+
+    const signers = [];
+    if (!skipAdminSignature) {
+      signers.push(admin);
+    }
+
+    await farming.methods
+      .setLockupSlots(new BN(lockupSlots))
+      .accounts({
+        admin: admin.publicKey,
+        farm,
+      })
+      .signers(signers)
+      .rpc();
+  }
+
+  public async setEmissionFrontLoad(
+    percent: number,
+    input: Partial<SetEmissionFrontLoadArgs> = {}
+  ) {
+    const farm = input.farm ?? this.id;
+    const admin = input.admin ?? this.admin;
+    const skipAdminSignature = input.skipAdminSignature ?? false;
+
+    const signers = [];
+    if (!skipAdminSignature) {
+      signers.push(admin);
+    }
+
+    await farming.methods
+      .setEmissionFrontLoad(new BN(percent))
+      .accounts({
+        admin: admin.publicKey,
+        farm,
+      })
+      .signers(signers)
+      .rpc();
+  }
+
+  public async setEmissionMultiplier(
+    harvestMint: PublicKey,
+    percent: number,
+    startsAt: number,
+    endsAt: number,
+    input: Partial<SetEmissionMultiplierArgs> = {}
+  ) {
+    const farm = input.farm ?? this.id;
+    const admin = input.admin ?? this.admin;
+    const skipAdminSignature = input.skipAdminSignature ?? false;
+    const harvestVault = input.harvestVault ?? this.harvestVault(harvestMint);
+    const harvestWallet =
+      input.harvestWallet ?? (await this.adminHarvestWallet(harvestMint));
+
+    const depositTokens = input.depositTokens ?? 1_000_000;
+    if (depositTokens > 0) {
+      await this.airdropHarvestTokens(
+        harvestMint,
+        harvestWallet,
+        depositTokens
+      );
+    }
+
+    const signers = [];
+    if (!skipAdminSignature) {
+      signers.push(admin);
+    }
+
+    await farming.methods
+      .setEmissionMultiplier(
+        harvestMint,
+        new BN(percent),
+        { slot: new BN(startsAt) },
+        { slot: new BN(endsAt) }
+      )
+      .accounts({
+        admin: admin.publicKey,
+        farm,
+        harvestVault,
+        harvestWallet,
+      })
+      .signers(signers)
+      .rpc();
+  }
+
+  public async donateRewards(
+    harvestMint: PublicKey,
+    amount: number,
+    input: Partial<DonateRewardsArgs> = {}
+  ) {
+    const donor = input.donor ?? Keypair.generate();
+    const donorWallet =
+      input.donorWallet ??
+      (await (async () => {
+        const { address } = await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          payer,
+          harvestMint,
+          donor.publicKey
+        );
+        await this.airdropHarvestTokens(harvestMint, address, amount);
+        return address;
+      })());
+    const farm = input.farm ?? this.id;
+    const harvestVault = input.harvestVault ?? this.harvestVault(harvestMint);
+
+    await farming.methods
+      .donateRewards({ amount: new BN(amount) })
+      .accounts({
+        donor: donor.publicKey,
+        farm,
+        stakeVault: await this.stakeVault(),
+        donorWallet,
+        harvestVault,
+      })
+      .signers([donor])
+      .rpc();
+  }
+
+  public async pauseHarvest(
+    harvestMint: PublicKey,
+    input: Partial<PauseHarvestArgs> = {}
+  ) {
+    const farm = input.farm ?? this.id;
+    const admin = input.admin ?? this.admin;
+    const skipAdminSignature = input.skipAdminSignature ?? false;
+
+    const signers = [];
+    if (!skipAdminSignature) {
+      signers.push(admin);
+    }
+
+    await farming.methods
+      .pauseHarvest(harvestMint)
+      .accounts({
+        admin: admin.publicKey,
+        farm,
+      })
+      .signers(signers)
+      .rpc();
+  }
+
+  public async resumeHarvest(
+    harvestMint: PublicKey,
+    input: Partial<PauseHarvestArgs> = {}
+  ) {
+    const farm = input.farm ?? this.id;
+    const admin = input.admin ?? this.admin;
+    const skipAdminSignature = input.skipAdminSignature ?? false;
+
+    const signers = [];
+    if (!skipAdminSignature) {
+      signers.push(admin);
+    }
+
+    await farming.methods
+      .resumeHarvest(harvestMint)
+      .accounts({
+        admin: admin.publicKey,
+        farm,
+      })
+      .signers(signers)
+      .rpc();
+  }
+
   public async createStakeWallet(
     withAmount: number = 0,
     owner: PublicKey = this.admin.publicKey
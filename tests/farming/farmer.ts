@@ -24,6 +24,20 @@ export interface CloseFarmerArgs {
   farmer: PublicKey;
 }
 
+export interface ExitFarmArgs {
+  authority: Keypair;
+  skipAuthoritySignature: boolean;
+  stakeWallet: PublicKey;
+  farmSignerPda: PublicKey;
+}
+
+export interface TransferFarmerArgs {
+  authority: Keypair;
+  skipAuthoritySignature: boolean;
+  newAuthority: PublicKey;
+  newFarmer: PublicKey;
+}
+
 export interface StopFarmingArgs {
   authority: Keypair;
   farm: PublicKey;
@@ -41,6 +55,29 @@ export interface ClaimEligibleHarvestArgs {
   authority: Keypair;
   skipAuthoritySignature: boolean;
   farmSignerPda: PublicKey;
+  farm: PublicKey;
+}
+
+export interface ClaimVestedHarvestArgs {
+  authority: Keypair;
+  skipAuthoritySignature: boolean;
+  farm: PublicKey;
+  harvestVault: PublicKey;
+  harvestWallet: PublicKey;
+}
+
+export interface SetCompoundBoundsArgs {
+  authority: Keypair;
+  skipAuthoritySignature: boolean;
+}
+
+export interface ClaimAndRestakeArgs {
+  authority: Keypair;
+  skipAuthoritySignature: boolean;
+  sourceFarmSignerPda: PublicKey;
+  sourceHarvestVault: PublicKey;
+  targetFarmer: PublicKey;
+  targetStakeVault: PublicKey;
 }
 
 export interface AirdropArgs {
@@ -49,6 +86,19 @@ export interface AirdropArgs {
   harvestVault: PublicKey;
 }
 
+export interface CompoundingEligibility {
+  isWhitelisted: boolean;
+  harvestsTargetStakeMint: boolean;
+  eligibleHarvest: BN;
+}
+
+export interface AverageStake {
+  averageStake: number;
+  since: number;
+  until: number;
+  cumulativeStakeSlots: BN;
+}
+
 export class Farmer {
   public async id(): Promise<PublicKey> {
     const [pda, _] = await Farmer.signerFrom(
@@ -182,6 +232,86 @@ export class Farmer {
       .rpc();
   }
 
+  public async exitFarm(
+    vaultWalletPairs: [PublicKey, PublicKey][],
+    input: Partial<ExitFarmArgs> = {}
+  ) {
+    const authority = input.authority ?? this.authority;
+    const skipAuthoritySignature = input.skipAuthoritySignature ?? false;
+    const stakeWallet = input.stakeWallet ?? (await this.stakeWallet()).address;
+
+    const [correctPda, _correctBumpSeed] = PublicKey.findProgramAddressSync(
+      [Buffer.from("signer"), this.farm.id.toBytes()],
+      farming.programId
+    );
+    const farmSignerPda = input.farmSignerPda ?? correctPda;
+
+    const remainingAccounts: AccountMeta[] = vaultWalletPairs
+      .map(([vault, wallet]) => [
+        { pubkey: vault, isSigner: false, isWritable: true },
+        { pubkey: wallet, isSigner: false, isWritable: true },
+      ])
+      .flat();
+
+    const signers = [];
+    if (!skipAuthoritySignature) {
+      signers.push(authority);
+    }
+
+    await farming.methods
+      .exitFarm()
+      .accounts({
+        authority: authority.publicKey,
+        farmer: await this.id(),
+        stakeWallet,
+        farm: this.farm.id,
+        farmSignerPda,
+        stakeVault: await this.farm.stakeVault(),
+      })
+      .remainingAccounts(remainingAccounts)
+      .signers(signers)
+      .rpc();
+  }
+
+  /**
+   * Returns a farmer instance for the new authority. If the new authority
+   * keypair is not provided, a new one is generated.
+   */
+  public async transfer(
+    newAuthorityKeypair: Keypair = Keypair.generate(),
+    input: Partial<TransferFarmerArgs> = {}
+  ): Promise<Farmer> {
+    const authority = input.authority ?? this.authority;
+    const skipAuthoritySignature = input.skipAuthoritySignature ?? false;
+    const newAuthority = input.newAuthority ?? newAuthorityKeypair.publicKey;
+    const [correctNewFarmer, _bumpSeed] = await Farmer.signerFrom(
+      this.farm.id,
+      newAuthority
+    );
+    const newFarmer = input.newFarmer ?? correctNewFarmer;
+
+    const signers = [];
+    if (!skipAuthoritySignature) {
+      signers.push(authority);
+    }
+
+    await farming.methods
+      .transferFarmer()
+      .accounts({
+        authority: authority.publicKey,
+        newAuthority,
+        farm: this.farm.id,
+        farmer: await this.id(),
+        newFarmer,
+      })
+      .signers(signers)
+      .rpc();
+
+    await airdrop(newAuthorityKeypair.publicKey);
+
+    return new Farmer(this.farm, newAuthorityKeypair);
+  }
+
   public async stopFarming(
     amount: number,
     input: Partial<StopFarmingArgs> = {}
@@ -258,6 +388,7 @@ export class Farmer {
       farming.programId
     );
     const farmSignerPda = input.farmSignerPda ?? correctPda;
+    const farm = input.farm ?? this.farm.id;
 
     const remainingAccounts: AccountMeta[] = vaultWalletPairs
       .map((tuple) => [
@@ -284,6 +415,7 @@ export class Farmer {
       .accounts({
         authority: authority.publicKey,
         farmer: await this.id(),
+        farm,
         farmSignerPda,
       })
       .remainingAccounts(remainingAccounts)
@@ -291,6 +423,192 @@ export class Farmer {
       .rpc();
   }
 
+  public static async claimEligibleHarvestAcrossFarms(
+    authority: Keypair,
+    groups: { farmer: Farmer; mint: PublicKey; wallet: PublicKey }[]
+  ) {
+    const remainingAccounts: AccountMeta[] = [];
+    for (const { farmer, mint, wallet } of groups) {
+      const [farmSignerPda, _bumpSeed] = PublicKey.findProgramAddressSync(
+        [Buffer.from("signer"), farmer.farm.id.toBytes()],
+        farming.programId
+      );
+
+      remainingAccounts.push(
+        { pubkey: farmer.farm.id, isSigner: false, isWritable: true },
+        { pubkey: await farmer.id(), isSigner: false, isWritable: true },
+        { pubkey: farmSignerPda, isSigner: false, isWritable: false },
+        {
+          pubkey: farmer.farm.harvestVault(mint),
+          isSigner: false,
+          isWritable: true,
+        },
+        { pubkey: wallet, isSigner: false, isWritable: true }
+      );
+    }
+
+    await farming.methods
+      .claimEligibleHarvestAcrossFarms()
+      .accounts({
+        authority: authority.publicKey,
+      })
+      .remainingAccounts(remainingAccounts)
+      .signers([authority])
+      .rpc();
+  }
+
+  public async claimVestedHarvest(
+    mint: PublicKey,
+    input: Partial<ClaimVestedHarvestArgs> = {}
+  ) {
+    const authority = input.authority ?? this.authority;
+    const skipAuthoritySignature = input.skipAuthoritySignature ?? false;
+    const farm = input.farm ?? this.farm.id;
+    const harvestVault = input.harvestVault ?? this.farm.harvestVault(mint);
+    const harvestWallet =
+      input.harvestWallet ?? (await this.harvestWalletPubkey(mint));
+
+    const signers = [];
+    if (!skipAuthoritySignature) {
+      signers.push(authority);
+    }
+
+    await farming.methods
+      .claimVestedHarvest()
+      .accounts({
+        authority: authority.publicKey,
+        farmer: await this.id(),
+        farm,
+        farmSignerPda: await this.farm.signerPda(),
+        harvestVault,
+        harvestWallet,
+      })
+      .signers(signers)
+      .rpc();
+  }
+
+  public async setCompoundBounds(
+    minCompound: number,
+    compoundDeadline: number,
+    input: Partial<SetCompoundBoundsArgs> = {}
+  ) {
+    const authority = input.authority ?? this.authority;
+    const skipAuthoritySignature = input.skipAuthoritySignature ?? false;
+
+    const signers = [];
+    if (!skipAuthoritySignature) {
+      signers.push(authority);
+    }
+
+    await farming.methods
+      .setCompoundBounds(
+        { amount: new BN(minCompound) },
+        { slot: new BN(compoundDeadline) }
+      )
+      .accounts({
+        authority: authority.publicKey,
+        farmer: await this.id(),
+      })
+      .signers(signers)
+      .rpc();
+  }
+
+  public async claimAndRestake(
+    targetFarm: Farm,
+    input: Partial<ClaimAndRestakeArgs> = {}
+  ) {
+    const authority = input.authority ?? this.authority;
+    const skipAuthoritySignature = input.skipAuthoritySignature ?? false;
+    const sourceFarmSignerPda =
+      input.sourceFarmSignerPda ?? (await this.farm.signerPda());
+    const sourceHarvestVault =
+      input.sourceHarvestVault ?? this.farm.harvestVault(targetFarm.stakeMint);
+    const [correctTargetFarmer, _bumpSeed] = await Farmer.signerFrom(
+      targetFarm.id,
+      this.authority.publicKey
+    );
+    const targetFarmer = input.targetFarmer ?? correctTargetFarmer;
+    const targetStakeVault =
+      input.targetStakeVault ?? (await targetFarm.stakeVault());
+
+    const signers = [];
+    if (!skipAuthoritySignature) {
+      signers.push(authority);
+    }
+
+    await farming.methods
+      .claimAndRestake()
+      .accounts({
+        authority: authority.publicKey,
+        sourceFarm: this.farm.id,
+        targetFarm: targetFarm.id,
+        sourceFarmSignerPda,
+        sourceFarmer: await this.id(),
+        targetFarmer,
+        sourceHarvestVault,
+        targetStakeVault,
+      })
+      .signers(signers)
+      .rpc();
+  }
+
+  /**
+   * Simulates the read-only endpoint and decodes its return data.
+   */
+  public async compoundingEligibility(
+    targetFarm: PublicKey = this.farm.id
+  ): Promise<CompoundingEligibility> {
+    const [whitelistCompounding, _bumpSeed] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("whitelist_compounding"),
+        this.farm.id.toBytes(),
+        targetFarm.toBytes(),
+      ],
+      farming.programId
+    );
+
+    const { raw } = await farming.methods
+      .getCompoundingEligibility()
+      .accounts({
+        farm: this.farm.id,
+        targetFarm,
+        farmer: await this.id(),
+        whitelistCompounding,
+      })
+      .simulate();
+
+    const prefix = `Program return: ${farming.programId} `;
+    const returnLog = raw.find((log) => log.startsWith(prefix));
+    const data = Buffer.from(returnLog.slice(prefix.length), "base64");
+
+    return {
+      isWhitelisted: data[0] === 1,
+      harvestsTargetStakeMint: data[1] === 1,
+      eligibleHarvest: new BN(data.subarray(2, 10), "le"),
+    };
+  }
+
+  /**
+   * Simulates the read-only endpoint and decodes its return data.
+   */
+  public async averageStake(): Promise<AverageStake> {
+    const { raw } = await farming.methods
+      .getFarmerAverageStake()
+      .accounts({ farmer: await this.id() })
+      .simulate();
+
+    const prefix = `Program return: ${farming.programId} `;
+    const returnLog = raw.find((log) => log.startsWith(prefix));
+    const data = Buffer.from(returnLog.slice(prefix.length), "base64");
+
+    return {
+      averageStake: new BN(data.subarray(0, 8), "le").toNumber(),
+      since: new BN(data.subarray(8, 16), "le").toNumber(),
+      until: new BN(data.subarray(16, 24), "le").toNumber(),
+      cumulativeStakeSlots: new BN(data.subarray(24, 40), "le"),
+    };
+  }
+
   public async airdrop(
     amount: number,
     mint: PublicKey,
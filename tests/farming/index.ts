@@ -6,20 +6,31 @@ import * as createFarm from "./endpoints/create-farm";
 import * as addHarvest from "./endpoints/add-harvest";
 import * as removeHarvest from "./endpoints/remove-harvest";
 import * as takeSnapshot from "./endpoints/take-snapshot";
+import * as crankSnapshot from "./endpoints/crank-snapshot";
 import * as setMinSnapshotWindow from "./endpoints/set-min-snapshot-window";
 import * as newHarvestPeriod from "./endpoints/new-harvest-period";
+import * as pauseHarvest from "./endpoints/pause-harvest";
 import * as setFarmOwner from "./endpoints/set-farm-owner";
 import * as createFarmer from "./endpoints/create-farmer";
 import * as closeFarmer from "./endpoints/close-farmer";
+import * as exitFarm from "./endpoints/exit-farm";
+import * as transferFarmer from "./endpoints/transfer-farmer";
 import * as startFarming from "./endpoints/start-farming";
 import * as whitelistFarmForCompounding from "./endpoints/whitelist-farm-for-compouding";
 import * as dewhitelistFarmForCompounding from "./endpoints/dewhitelist-farm-for-compounding";
 import * as compoundSameFarm from "./endpoints/compound-same-farm";
 import * as compoundAcrossFarms from "./endpoints/compound-across-farms";
 import * as stopFarming from "./endpoints/stop-farming";
+import * as setLockupSlots from "./endpoints/set-lockup-slots";
+import * as setEmissionMultiplier from "./endpoints/set-emission-multiplier";
+import * as setEmissionFrontLoad from "./endpoints/set-emission-front-load";
 import * as updateEligibleHarvest from "./endpoints/update-eligible-harvest";
 import * as claimEligibleHarvest from "./endpoints/claim-eligible-harvest";
+import * as claimEligibleHarvestAcrossFarms from "./endpoints/claim-eligible-harvest-across-farms";
+import * as claimVestedHarvest from "./endpoints/claim-vested-harvest";
+import * as claimAndRestake from "./endpoints/claim-and-restake";
 import * as airdropEndpoint from "./endpoints/airdrop";
+import * as donateRewards from "./endpoints/donate-rewards";
 
 import { airdrop, provider } from "../helpers";
 
@@ -28,20 +39,31 @@ describe("farming", () => {
   addHarvest.test();
   removeHarvest.test();
   takeSnapshot.test();
+  crankSnapshot.test();
   setMinSnapshotWindow.test();
   setFarmOwner.test();
   newHarvestPeriod.test();
+  pauseHarvest.test();
   createFarmer.test();
   startFarming.test();
   stopFarming.test();
+  setLockupSlots.test();
+  setEmissionMultiplier.test();
+  setEmissionFrontLoad.test();
   updateEligibleHarvest.test();
   claimEligibleHarvest.test();
+  claimEligibleHarvestAcrossFarms.test();
+  claimVestedHarvest.test();
   closeFarmer.test();
+  exitFarm.test();
+  transferFarmer.test();
   whitelistFarmForCompounding.test();
   dewhitelistFarmForCompounding.test();
   compoundSameFarm.test();
   compoundAcrossFarms.test();
+  claimAndRestake.test();
   airdropEndpoint.test();
+  donateRewards.test();
 
   before("airdrop SOL to provider wallet", async () => {
     await airdrop(provider.wallet.publicKey);